@@ -0,0 +1,93 @@
+use std::{
+    fs::OpenOptions,
+    io::Write as _,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "linux")]
+use isr_dl_linux::ResolvedPackage;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// A record of the audit log.
+///
+/// Each successful entry creation or retrieval appends one record, as a
+/// line of JSON, to the file set with [`IsrCache::with_audit_log`].
+///
+/// [`IsrCache::with_audit_log`]: crate::IsrCache::with_audit_log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Time of the operation, in seconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// Whether the profile was created, as opposed to retrieved from the
+    /// cache.
+    pub created: bool,
+
+    /// Directory of the entry, relative to the cache directory
+    /// (e.g. `windows/ntkrnlmp.pdb/<guid>`).
+    pub key: PathBuf,
+
+    /// Path to the profile.
+    pub profile_path: PathBuf,
+
+    /// Path to the debug file the profile was created from.
+    pub source_path: Option<PathBuf>,
+
+    /// File extension of the codec used to encode the profile.
+    pub codec: String,
+
+    /// Duration of the operation, in milliseconds.
+    pub duration_ms: u64,
+
+    /// Version of the `isr-cache` crate.
+    pub isr_version: String,
+
+    /// Packages the Linux profile was created from, including their
+    /// SHA256 checksums as reported by the package index.
+    #[cfg(feature = "linux")]
+    #[serde(default)]
+    pub linux_packages: Vec<ResolvedPackage>,
+}
+
+impl AuditRecord {
+    /// Creates a new record with the current time.
+    pub(crate) fn new(
+        key: PathBuf,
+        created: bool,
+        profile_path: &Path,
+        source_path: Option<&Path>,
+        codec: &str,
+        duration: Duration,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self {
+            timestamp,
+            created,
+            key,
+            profile_path: profile_path.to_owned(),
+            source_path: source_path.map(Path::to_owned),
+            codec: codec.to_owned(),
+            duration_ms: duration.as_millis() as u64,
+            isr_version: String::from(env!("CARGO_PKG_VERSION")),
+            #[cfg(feature = "linux")]
+            linux_packages: Vec::new(),
+        }
+    }
+
+    /// Appends the record to the audit log.
+    pub(crate) fn append(&self, path: &Path) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(&line)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use crate::{Codec, Entry, Error};
+
+/// Summary of creating profiles for the files in a directory.
+///
+/// Returned by [`IsrCache::batch_from_directory`].
+///
+/// [`IsrCache::batch_from_directory`]: crate::IsrCache::batch_from_directory
+pub struct BatchSummary<C>
+where
+    C: Codec,
+{
+    /// Entries created (or retrieved) for the files, with their paths.
+    pub entries: Vec<(PathBuf, Entry<C>)>,
+
+    /// Files for which no entry could be created, with the errors.
+    pub failures: Vec<(PathBuf, Error)>,
+}
+
+impl<C> BatchSummary<C>
+where
+    C: Codec,
+{
+    /// Checks whether entries were created for all files.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
@@ -1,6 +1,6 @@
-use std::io::Write;
+use std::{borrow::Cow, collections::HashMap, io::Write};
 
-use isr_core::Profile;
+use isr_core::{types::Types, Profile, Symbols};
 
 /// A codec for encoding and decoding profiles.
 pub trait Codec {
@@ -8,24 +8,192 @@ pub trait Codec {
     const EXTENSION: &'static str;
 
     /// The error type for encoding.
-    type EncodeError: std::error::Error + 'static;
+    type EncodeError: std::error::Error + Send + Sync + 'static;
 
     /// The error type for decoding.
-    type DecodeError: std::error::Error + 'static;
+    type DecodeError: std::error::Error + Send + Sync + 'static;
 
     /// Encodes a profile into the given writer.
+    ///
+    /// The writer is used as is, pass a buffered writer (e.g.
+    /// [`BufWriter`]) when writing to a file. [`IsrCache`] buffers the
+    /// profile files itself.
+    ///
+    /// [`BufWriter`]: std::io::BufWriter
+    /// [`IsrCache`]: crate::IsrCache
     fn encode(writer: impl Write, profile: &Profile) -> Result<(), Self::EncodeError>;
 
     /// Decodes a profile from the given slice.
     fn decode(slice: &[u8]) -> Result<Profile, Self::DecodeError>;
+
+    /// Encodes only the symbols of a profile into the given writer.
+    ///
+    /// The default implementation encodes a profile without types. The
+    /// codecs of this crate encode the bare symbol table instead, which is
+    /// smaller and faster to decode.
+    fn encode_symbols(writer: impl Write, symbols: &Symbols) -> Result<(), Self::EncodeError> {
+        let symbols = symbols
+            .0
+            .iter()
+            .map(|(name, address)| (Cow::Borrowed(name.as_ref()), *address))
+            .collect();
+
+        let profile = Profile::new(Cow::Borrowed(""), Symbols(symbols), Types::default());
+        Self::encode(writer, &profile)
+    }
+
+    /// Decodes symbols encoded by [`encode_symbols`].
+    ///
+    /// [`encode_symbols`]: Self::encode_symbols
+    fn decode_symbols(slice: &[u8]) -> Result<Symbols, Self::DecodeError> {
+        Ok(Self::decode(slice)?.into_symbols())
+    }
+
+    /// Finds the given symbols in a profile encoded by [`encode`].
+    ///
+    /// Symbols that aren't found are omitted from the result.
+    ///
+    /// The default implementation decodes the whole profile. The JSON and
+    /// MessagePack codecs scan the encoded profile instead, only the
+    /// requested symbols are decoded.
+    ///
+    /// [`encode`]: Self::encode
+    fn find_symbols(
+        slice: &[u8],
+        names: &[&str],
+    ) -> Result<HashMap<String, u64>, Self::DecodeError> {
+        let profile = Self::decode(slice)?;
+        Ok(names
+            .iter()
+            .filter_map(|&name| Some((String::from(name), profile.find_symbol(name)?)))
+            .collect())
+    }
+}
+
+/// The codec used by the default cache.
+///
+/// This is [`BincodeCodec`] if the `codec-bincode` feature is enabled,
+/// otherwise [`JsonCodec`].
+#[cfg(feature = "codec-bincode")]
+pub type DefaultCodec = BincodeCodec;
+
+/// The codec used by the default cache.
+///
+/// This is [`BincodeCodec`] if the `codec-bincode` feature is enabled,
+/// otherwise [`JsonCodec`].
+#[cfg(all(not(feature = "codec-bincode"), feature = "codec-json"))]
+pub type DefaultCodec = JsonCodec;
+
+/// A codec known to this crate.
+///
+/// Used when the codec of a profile isn't known upfront, either from the
+/// file extension ([`KnownCodec::from_extension`]) or from the content
+/// ([`detect_codec`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownCodec {
+    /// The bincode format ([`BincodeCodec`]).
+    Bincode,
+
+    /// The JSON format ([`JsonCodec`] or [`CompactJsonCodec`]).
+    Json,
+
+    /// The MessagePack format ([`MsgpackCodec`]).
+    Msgpack,
+}
+
+impl KnownCodec {
+    /// Returns the codec for the given file extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "bin" => Some(Self::Bincode),
+            "json" => Some(Self::Json),
+            "msgpack" => Some(Self::Msgpack),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extension for this codec.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Bincode => "bin",
+            Self::Json => "json",
+            Self::Msgpack => "msgpack",
+        }
+    }
+}
+
+/// Detects the codec of an encoded profile.
+///
+/// The detection is heuristic and only inspects the first few bytes:
+/// - JSON profiles start with `{` (optionally preceded by whitespace).
+/// - MessagePack profiles start with an array or a map marker.
+/// - Bincode profiles start with the architecture name, encoded as its
+///   length (a little-endian `u64`) followed by its bytes.
+///
+/// Returns `None` if the data doesn't look like any known codec.
+pub fn detect_codec(data: &[u8]) -> Option<KnownCodec> {
+    let first = data.iter().find(|byte| !byte.is_ascii_whitespace())?;
+    if *first == b'{' {
+        return Some(KnownCodec::Json);
+    }
+
+    match data[0] {
+        // fixmap, fixarray
+        0x80..=0x9f |
+        // array 16, array 32, map 16, map 32
+        0xdc..=0xdf => return Some(KnownCodec::Msgpack),
+        _ => {}
+    }
+
+    // Architecture names are short identifiers, e.g. `Amd64`.
+    let (length, rest) = data.split_first_chunk::<8>()?;
+    let length = usize::try_from(u64::from_le_bytes(*length)).ok()?;
+    match rest.get(..length) {
+        Some(name)
+            if (1..=32).contains(&length)
+                && name
+                    .iter()
+                    .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'_') =>
+        {
+            Some(KnownCodec::Bincode)
+        }
+        _ => None,
+    }
 }
 
 /// A codec for the bincode format.
 ///
 /// Provides a compact binary representation of profiles.
+///
+/// Decoding is limited to [`BincodeCodec::SIZE_LIMIT`] bytes, so that
+/// a corrupted length prefix fails with [`bincode::ErrorKind::SizeLimit`]
+/// instead of attempting a huge allocation.
 #[cfg(feature = "codec-bincode")]
 pub struct BincodeCodec;
 
+#[cfg(feature = "codec-bincode")]
+impl BincodeCodec {
+    /// Maximum number of bytes read when decoding a profile.
+    pub const SIZE_LIMIT: u64 = 2 << 30;
+
+    /// Returns a deserializer with the same encoding as
+    /// `bincode::deserialize`, but with the size limit.
+    ///
+    /// `bincode::Options::deserialize` ignores the limit for slices.
+    fn deserializer(
+        slice: &[u8],
+    ) -> bincode::Deserializer<bincode::de::read::SliceReader<'_>, impl bincode::Options> {
+        use bincode::Options as _;
+
+        let options = bincode::options()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(Self::SIZE_LIMIT);
+
+        bincode::Deserializer::from_slice(slice, options)
+    }
+}
+
 #[cfg(feature = "codec-bincode")]
 impl Codec for BincodeCodec {
     const EXTENSION: &'static str = "bin";
@@ -38,7 +206,19 @@ impl Codec for BincodeCodec {
     }
 
     fn decode(slice: &[u8]) -> Result<Profile, Self::DecodeError> {
-        bincode::deserialize(slice)
+        use serde::Deserialize as _;
+
+        Profile::deserialize(&mut Self::deserializer(slice))
+    }
+
+    fn encode_symbols(writer: impl Write, symbols: &Symbols) -> Result<(), Self::EncodeError> {
+        bincode::serialize_into(writer, symbols)
+    }
+
+    fn decode_symbols(slice: &[u8]) -> Result<Symbols, Self::DecodeError> {
+        use serde::Deserialize as _;
+
+        Symbols::deserialize(&mut Self::deserializer(slice))
     }
 }
 
@@ -62,14 +242,89 @@ impl Codec for JsonCodec {
     fn decode(slice: &[u8]) -> Result<Profile, Self::DecodeError> {
         serde_json::from_slice(slice)
     }
+
+    fn encode_symbols(writer: impl Write, symbols: &Symbols) -> Result<(), Self::EncodeError> {
+        serde_json::to_writer_pretty(writer, symbols)
+    }
+
+    fn decode_symbols(slice: &[u8]) -> Result<Symbols, Self::DecodeError> {
+        serde_json::from_slice(slice)
+    }
+
+    fn find_symbols(
+        slice: &[u8],
+        names: &[&str],
+    ) -> Result<HashMap<String, u64>, Self::DecodeError> {
+        use serde::de::DeserializeSeed as _;
+
+        let mut deserializer = serde_json::Deserializer::from_slice(slice);
+        let symbols = symbols_only::ProfileSymbols(names).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(symbols)
+    }
+}
+
+/// A codec for the compact JSON format.
+///
+/// Same as [`JsonCodec`], but without indentation and newlines. Produces
+/// smaller files and encodes faster, at the cost of readability.
+#[cfg(feature = "codec-json")]
+pub struct CompactJsonCodec;
+
+#[cfg(feature = "codec-json")]
+impl Codec for CompactJsonCodec {
+    const EXTENSION: &'static str = "json";
+
+    type EncodeError = serde_json::Error;
+    type DecodeError = serde_json::Error;
+
+    fn encode(writer: impl Write, profile: &Profile) -> Result<(), Self::EncodeError> {
+        serde_json::to_writer(writer, profile)
+    }
+
+    fn decode(slice: &[u8]) -> Result<Profile, Self::DecodeError> {
+        serde_json::from_slice(slice)
+    }
+
+    fn encode_symbols(writer: impl Write, symbols: &Symbols) -> Result<(), Self::EncodeError> {
+        serde_json::to_writer(writer, symbols)
+    }
+
+    fn decode_symbols(slice: &[u8]) -> Result<Symbols, Self::DecodeError> {
+        serde_json::from_slice(slice)
+    }
+
+    fn find_symbols(
+        slice: &[u8],
+        names: &[&str],
+    ) -> Result<HashMap<String, u64>, Self::DecodeError> {
+        use serde::de::DeserializeSeed as _;
+
+        let mut deserializer = serde_json::Deserializer::from_slice(slice);
+        let symbols = symbols_only::ProfileSymbols(names).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(symbols)
+    }
 }
 
 /// A codec for the MessagePack format.
 ///
 /// Provides a compact binary representation of profiles.
+///
+/// Profiles are encoded as in human-readable formats (e.g. enums are
+/// internally tagged), like the files written by previous versions.
+///
+/// Decoding is limited to [`MsgpackCodec::MAX_DEPTH`] levels of nesting,
+/// deeper inputs fail with [`rmp_serde::decode::Error::DepthLimitExceeded`].
 #[cfg(feature = "codec-msgpack")]
 pub struct MsgpackCodec;
 
+#[cfg(feature = "codec-msgpack")]
+impl MsgpackCodec {
+    /// Maximum nesting depth when decoding a profile.
+    pub const MAX_DEPTH: usize = 256;
+}
+
 #[cfg(feature = "codec-msgpack")]
 impl Codec for MsgpackCodec {
     const EXTENSION: &'static str = "msgpack";
@@ -78,10 +333,195 @@ impl Codec for MsgpackCodec {
     type DecodeError = rmp_serde::decode::Error;
 
     fn encode(mut writer: impl Write, profile: &Profile) -> Result<(), Self::EncodeError> {
-        rmp_serde::encode::write(&mut writer, profile)
+        use serde::Serialize as _;
+
+        let mut serializer = rmp_serde::Serializer::new(&mut writer).with_human_readable();
+        profile.serialize(&mut serializer)
     }
 
     fn decode(slice: &[u8]) -> Result<Profile, Self::DecodeError> {
-        rmp_serde::from_slice(slice)
+        use serde::Deserialize as _;
+
+        let mut deserializer = rmp_serde::Deserializer::from_read_ref(slice).with_human_readable();
+        deserializer.set_max_depth(Self::MAX_DEPTH);
+        Profile::deserialize(&mut deserializer)
+    }
+
+    fn encode_symbols(mut writer: impl Write, symbols: &Symbols) -> Result<(), Self::EncodeError> {
+        rmp_serde::encode::write(&mut writer, symbols)
+    }
+
+    fn decode_symbols(slice: &[u8]) -> Result<Symbols, Self::DecodeError> {
+        use serde::Deserialize as _;
+
+        let mut deserializer = rmp_serde::Deserializer::from_read_ref(slice);
+        deserializer.set_max_depth(Self::MAX_DEPTH);
+        Symbols::deserialize(&mut deserializer)
+    }
+
+    fn find_symbols(
+        slice: &[u8],
+        names: &[&str],
+    ) -> Result<HashMap<String, u64>, Self::DecodeError> {
+        use serde::de::DeserializeSeed as _;
+
+        let mut deserializer = rmp_serde::Deserializer::from_read_ref(slice);
+        deserializer.set_max_depth(Self::MAX_DEPTH);
+        symbols_only::ProfileSymbols(names).deserialize(&mut deserializer)
+    }
+}
+
+/// Decoding of selected symbols from a profile, without decoding the rest
+/// of it.
+///
+/// Everything else is skipped with [`IgnoredAny`], which doesn't allocate.
+/// Works for self-describing formats only.
+#[cfg(any(feature = "codec-json", feature = "codec-msgpack"))]
+mod symbols_only {
+    use std::{collections::HashMap, fmt};
+
+    use serde::de::{
+        DeserializeSeed, Deserializer, Error as _, IgnoredAny, MapAccess, SeqAccess, Visitor,
+    };
+
+    type Found = HashMap<String, u64>;
+
+    /// Finds the symbols with the given names in a profile.
+    ///
+    /// The profile is either a map (JSON) or a sequence of the fields in
+    /// declaration order (MessagePack).
+    pub struct ProfileSymbols<'n>(pub &'n [&'n str]);
+
+    impl<'de> DeserializeSeed<'de> for ProfileSymbols<'_> {
+        type Value = Found;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    impl<'de> Visitor<'de> for ProfileSymbols<'_> {
+        type Value = Found;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a profile")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut result = None;
+            while let Some(key) = map.next_key_seed(Name(&["symbols"]))? {
+                match key {
+                    Some(_) => result = Some(map.next_value_seed(SymbolsSeed(self.0))?),
+                    None => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+
+            result.ok_or_else(|| A::Error::missing_field("symbols"))
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            // The architecture, followed by the symbols.
+            if seq.next_element::<IgnoredAny>()?.is_none() {
+                return Err(A::Error::invalid_length(0, &self));
+            }
+
+            let result = match seq.next_element_seed(SymbolsSeed(self.0))? {
+                Some(result) => result,
+                None => return Err(A::Error::invalid_length(1, &self)),
+            };
+
+            while seq.next_element::<IgnoredAny>()?.is_some() {}
+            Ok(result)
+        }
+    }
+
+    /// Finds the symbols with the given names in a symbol table.
+    struct SymbolsSeed<'n>(&'n [&'n str]);
+
+    impl<'de> DeserializeSeed<'de> for SymbolsSeed<'_> {
+        type Value = Found;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_newtype_struct("Symbols", self)
+        }
+    }
+
+    impl<'de> Visitor<'de> for SymbolsSeed<'_> {
+        type Value = Found;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a symbol table")
+        }
+
+        fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(self)
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut result = HashMap::new();
+            while let Some(name) = map.next_key_seed(Name(self.0))? {
+                match name {
+                    Some(name) => {
+                        result.insert(String::from(name), map.next_value()?);
+                    }
+                    None => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+
+            Ok(result)
+        }
+    }
+
+    /// Matches a string against the given names, without allocating.
+    ///
+    /// Returns the matching name, if any.
+    struct Name<'n>(&'n [&'n str]);
+
+    impl<'de, 'n> DeserializeSeed<'de> for Name<'n> {
+        type Value = Option<&'n str>;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(self)
+        }
+    }
+
+    impl<'n> Visitor<'_> for Name<'n> {
+        type Value = Option<&'n str>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a string")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(self.0.iter().find(|&&name| name == value).copied())
+        }
     }
 }
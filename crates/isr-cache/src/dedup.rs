@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Directory of the deduplicated profiles, relative to the cache directory.
+pub const OBJECTS: &str = "objects";
+
+/// Deduplicates a profile against the objects in `objects_dir`.
+///
+/// Objects are named after a hash of their content. If an object with the
+/// same content already exists, the profile is replaced with a hard link
+/// to it. Otherwise, the profile becomes a new object. Hash collisions are
+/// resolved by comparing the content.
+///
+/// Returns `true` if the profile was replaced with a link to an existing
+/// object. If the filesystem doesn't support hard links, the profile is
+/// left as is.
+pub fn dedup(objects_dir: &Path, profile_path: &Path) -> Result<bool, Error> {
+    let data = std::fs::read(profile_path)?;
+    std::fs::create_dir_all(objects_dir)?;
+
+    let hash = fnv1a(&data);
+    for index in 0.. {
+        let object_path = object_path(objects_dir, profile_path, hash, index);
+
+        if !object_path.exists() {
+            if let Err(err) = std::fs::hard_link(profile_path, &object_path) {
+                tracing::warn!(?profile_path, %err, "failed to deduplicate profile");
+            }
+
+            return Ok(false);
+        }
+
+        if is_same_file(&object_path, profile_path)? {
+            return Ok(false);
+        }
+
+        if std::fs::read(&object_path)? != data {
+            continue;
+        }
+
+        // Replace the profile atomically, so concurrent readers see either
+        // the old or the new file.
+        let temp_path = profile_path.with_extension("dedup");
+        let _ = std::fs::remove_file(&temp_path);
+
+        if let Err(err) = std::fs::hard_link(&object_path, &temp_path) {
+            tracing::warn!(?profile_path, %err, "failed to deduplicate profile");
+            return Ok(false);
+        }
+
+        std::fs::rename(&temp_path, profile_path)?;
+
+        // Renaming a link over another link to the same file is a no-op.
+        let _ = std::fs::remove_file(&temp_path);
+        return Ok(true);
+    }
+
+    unreachable!()
+}
+
+/// Returns the path of an object.
+///
+/// `<objects>/<hash>.<ext>` for the first object with the given hash,
+/// `<objects>/<hash>-<index>.<ext>` for the colliding ones.
+fn object_path(objects_dir: &Path, profile_path: &Path, hash: u64, index: usize) -> PathBuf {
+    let name = match index {
+        0 => format!("{hash:016x}"),
+        index => format!("{hash:016x}-{index}"),
+    };
+
+    let path = objects_dir.join(name);
+    match profile_path.extension() {
+        Some(extension) => path.with_extension(extension),
+        None => path,
+    }
+}
+
+/// Checks whether both paths are links to the same file.
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> Result<bool, Error> {
+    use std::os::unix::fs::MetadataExt as _;
+
+    let (a, b) = (std::fs::metadata(a)?, std::fs::metadata(b)?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+/// Checks whether both paths are links to the same file.
+///
+/// Not detected on this platform, the profile is relinked instead.
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, _b: &Path) -> Result<bool, Error> {
+    Ok(false)
+}
+
+/// 64-bit FNV-1a hash.
+///
+/// Stable across Rust versions, unlike the hashers in `std`.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
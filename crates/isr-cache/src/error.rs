@@ -5,16 +5,53 @@ pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
+    /// The entry metadata couldn't be read or written.
+    #[error(transparent)]
+    Metadata(#[from] serde_json::Error),
+
+    /// An error occurred while parsing an object file.
+    #[cfg(feature = "dwarf")]
+    #[error(transparent)]
+    Object(#[from] object::Error),
+
+    /// The object file isn't a PE file.
+    #[cfg(feature = "dwarf")]
+    #[error("Not a PE file")]
+    NotPe,
+
+    /// A previous lookup of the entry failed and the negative cache hasn't
+    /// expired yet.
+    #[error("Not found (cached)")]
+    NotFoundCached,
+
+    /// The download gate declined the download.
+    #[cfg(feature = "linux")]
+    #[error("Download declined")]
+    DownloadDeclined,
+
+    /// The profile couldn't be encoded.
+    #[error(transparent)]
+    Encode(Box<dyn std::error::Error + Send + Sync>),
+
+    /// No default cache directory could be determined.
+    #[error("No default cache directory")]
+    NoCacheDirectory,
+
     /// An error occurred while parsing PDB symbols.
     #[cfg(feature = "pdb")]
     #[error(transparent)]
     Pdb(#[from] isr_pdb::Error),
 
     /// An error occurred while parsing DWARF symbols.
-    #[cfg(feature = "linux")]
+    #[cfg(feature = "dwarf")]
     #[error(transparent)]
     Dwarf(#[from] isr_dwarf::Error),
 
+    /// A name used as a path in the cache isn't a single path component.
+    #[cfg(feature = "pdb")]
+    #[error("Invalid path component {0:?}")]
+    InvalidPathComponent(String),
+
     /// An error occurred while downloading a PDB file.
     #[cfg(feature = "pdb")]
     #[error(transparent)]
@@ -25,6 +62,11 @@ pub enum Error {
     #[error(transparent)]
     LinuxDownloader(#[from] isr_dl_linux::Error),
 
+    /// An error occurred while parsing an ISF file.
+    #[cfg(feature = "isf")]
+    #[error(transparent)]
+    Isf(#[from] isr_isf::Error),
+
     /// An error occurred while parsing a Linux kernel banner.
     #[cfg(feature = "linux")]
     #[error("Invalid banner")]
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Policy for ISF files whose cache entry already has a profile.
+///
+/// Used by [`IsrCache::import_isf_directory`].
+///
+/// [`IsrCache::import_isf_directory`]: crate::IsrCache::import_isf_directory
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnConflict {
+    /// The existing profile is kept.
+    #[default]
+    Skip,
+
+    /// The existing profile is replaced.
+    Overwrite,
+}
+
+/// Outcome of an import of ISF files.
+///
+/// Returned by [`IsrCache::import_isf_directory`].
+///
+/// [`IsrCache::import_isf_directory`]: crate::IsrCache::import_isf_directory
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Converted ISF files, with the paths of the created profiles.
+    pub converted: Vec<(PathBuf, PathBuf)>,
+
+    /// ISF files whose entry already has a profile, with the path of the
+    /// existing profile.
+    pub skipped: Vec<(PathBuf, PathBuf)>,
+
+    /// ISF files without metadata mapping them to a cache entry, i.e.
+    /// neither a PDB identification nor an Ubuntu kernel banner.
+    pub unidentified: Vec<PathBuf>,
+
+    /// ISF files that couldn't be converted, with the errors.
+    pub failed: Vec<(PathBuf, Error)>,
+}
+
+/// Outcome of an import of a single ISF file.
+pub(crate) enum ImportOutcome {
+    Converted(PathBuf),
+    Skipped(PathBuf),
+    Unidentified,
+}
+
+/// Collects the ISF files (`.json` and `.json.xz`) in a directory,
+/// recursively.
+pub(crate) fn collect_isf_files(directory: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            collect_isf_files(&path, paths)?;
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+        if name.ends_with(".json") || name.ends_with(".json.xz") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
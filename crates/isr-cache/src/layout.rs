@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+
+use isr_dl_pdb::{CodeView, DebugId};
+
+/// Layout of the PDB files in the Windows part of the cache.
+///
+/// Profiles are always stored under the cache directory, the layout only
+/// determines where the PDB files are read from and downloaded to.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum WindowsLayout {
+    /// PDB files are stored next to the profiles
+    /// (`<cache>/windows/<name>/<guid>/<name>`).
+    #[default]
+    Isr,
+
+    /// PDB files are stored in an existing symbol store
+    /// (`<root>/<name>/<GUID>/<name>`), shared with tools such as `symchk`
+    /// and WinDbg.
+    SymStore {
+        /// Root directory of the symbol store.
+        root: PathBuf,
+    },
+}
+
+impl WindowsLayout {
+    /// Returns the path of the PDB file described by the [`CodeView`]
+    /// information.
+    ///
+    /// `entry_dir` is the directory of the cache entry, used by the
+    /// [`WindowsLayout::Isr`] layout.
+    pub fn pdb_path(&self, entry_dir: &Path, codeview: &CodeView) -> PathBuf {
+        self.file_path(
+            entry_dir,
+            &codeview.path,
+            &codeview.guid.to_ascii_uppercase(),
+        )
+    }
+
+    /// Returns the path of the file identified by the [`DebugId`].
+    ///
+    /// Same as [`pdb_path`] for [`DebugId::CodeView`].
+    ///
+    /// [`pdb_path`]: Self::pdb_path
+    pub fn debug_file_path(&self, entry_dir: &Path, debug_id: &DebugId) -> PathBuf {
+        match debug_id {
+            DebugId::CodeView(codeview) => self.pdb_path(entry_dir, codeview),
+            // The key is already in the symbol store format.
+            DebugId::PeKey(pe_key) => self.file_path(entry_dir, &pe_key.path, &pe_key.key),
+        }
+    }
+
+    fn file_path(&self, entry_dir: &Path, path: &str, identifier: &str) -> PathBuf {
+        match self {
+            Self::Isr => entry_dir.join(path),
+            Self::SymStore { root } => root.join(path).join(identifier).join(path),
+        }
+    }
+}
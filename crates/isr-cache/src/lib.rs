@@ -24,10 +24,7 @@
 //! let cache = IsrCache::<JsonCodec>::new("cache")?;
 //!
 //! // Use the CodeView information of the Windows 10.0.18362.356 kernel.
-//! let codeview = CodeView {
-//!     path: String::from("ntkrnlmp.pdb"),
-//!     guid: String::from("ce7ffb00c20b87500211456b3e905c471"),
-//! };
+//! let codeview = CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471")?;
 //!
 //! // Fetch and create (or get existing) the entry.
 //! let entry = cache.entry_from_codeview(codeview)?;
@@ -73,25 +70,118 @@
 //! [`isr`]: ../isr/index.html
 //! [`vmi`]: ../vmi/index.html
 
+mod audit;
+#[cfg(feature = "pdb")]
+mod batch;
 mod codec;
+mod dedup;
 mod error;
+#[cfg(all(feature = "isf", feature = "unstable"))]
+mod import;
+#[cfg(feature = "pdb")]
+mod layout;
+#[cfg(any(feature = "pdb", feature = "linux"))]
+mod manifest;
+#[cfg(any(feature = "linux", feature = "pdb", feature = "unstable"))]
+mod metadata;
+#[cfg(unix)]
+mod permissions;
+#[cfg(any(feature = "pdb", feature = "linux"))]
+mod plan;
+mod scan;
 
+#[cfg(any(feature = "pdb", feature = "linux"))]
+use std::io::{BufWriter, Write as _};
 use std::{
+    collections::HashMap,
     fs::File,
     path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
 };
 
-pub use isr_core::Profile;
+#[cfg(feature = "pdb")]
+use isr_core::{types::Types, SymbolBase, SymbolScope, Symbols, WindowsBuild};
+pub use isr_core::{Profile, ProfileOptions, ProfileSource};
+#[cfg(feature = "linux")]
+use isr_dl_linux::UbuntuPaths;
+#[cfg(feature = "linux")]
 pub use isr_dl_linux::{
-    LinuxBanner, LinuxVersionSignature, UbuntuDownloader, UbuntuVersionSignature,
+    CompilerKind, LinuxBanner, LinuxVersionSignature, ResolvedPackage, ResolvedPlan, SkipPolicy,
+    UbuntuDownloader, UbuntuVersionSignature, Url,
 };
-pub use isr_dl_pdb::{CodeView, PdbDownloader};
+#[cfg(feature = "pdb")]
+pub use isr_dl_pdb::{CodeView, DebugId, PdbDownloader, PeKey};
 use memmap2::Mmap;
 
+#[cfg(any(feature = "pdb", feature = "linux"))]
+pub use self::manifest::{Manifest, ManifestEntry, PrefetchSummary};
+#[cfg(unix)]
+pub use self::permissions::CachePermissions;
+#[cfg(any(feature = "pdb", feature = "linux"))]
+pub use self::plan::DownloadEstimate;
+#[cfg(feature = "linux")]
+pub use self::plan::LinuxBannerPlan;
 pub use self::{
-    codec::{BincodeCodec, Codec, JsonCodec, MsgpackCodec},
+    audit::AuditRecord,
+    codec::{
+        detect_codec, BincodeCodec, Codec, CompactJsonCodec, DefaultCodec, JsonCodec, KnownCodec,
+        MsgpackCodec,
+    },
     error::Error,
+    scan::RepairSummary,
 };
+#[cfg(feature = "pdb")]
+pub use self::{batch::BatchSummary, layout::WindowsLayout, plan::CodeViewPlan};
+
+isr_core::unstable_api! {
+    pub mod unstable {
+        //! Experimental APIs without semver guarantees.
+
+        #[cfg(feature = "isf")]
+        pub use crate::import::{ImportReport, OnConflict};
+        pub use crate::metadata::EntryMetadata;
+        #[cfg(feature = "pdb")]
+        pub use crate::metadata::WindowsMetadata;
+        pub use crate::scan::CacheReport;
+    }
+}
+
+/// Filename of the entry metadata, stored alongside the profile.
+#[cfg(any(feature = "linux", feature = "pdb", feature = "unstable"))]
+const METADATA: &str = "metadata.json";
+
+/// Size of the buffer between the codec and the profile file.
+///
+/// The codecs issue many small writes.
+#[cfg(any(feature = "pdb", feature = "linux"))]
+const ENCODE_BUFFER_SIZE: usize = 1 << 20;
+
+/// Hook that confirms the downloads of Linux packages.
+#[cfg(feature = "linux")]
+type DownloadGate = Box<dyn Fn(&DownloadEstimate) -> bool + Send + Sync>;
+
+/// Environment variable overriding the default cache directory.
+const CACHE_DIR_ENV: &str = "ISR_CACHE_DIR";
+
+/// Filename of the marker recording a failed lookup.
+const NOT_FOUND: &str = "not-found";
+
+/// Default grace period of [`IsrCache::repair`].
+const DEFAULT_REPAIR_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+// Filenames used in the Linux cache entries.
+#[cfg(feature = "linux")]
+const LINUX_IMAGE_DEB: &str = "linux-image.deb";
+#[cfg(feature = "linux")]
+const LINUX_IMAGE: &str = "vmlinuz";
+#[cfg(feature = "linux")]
+const LINUX_IMAGE_DBGSYM_DEB: &str = "linux-image-dbgsym.deb";
+#[cfg(feature = "linux")]
+const LINUX_IMAGE_DBGSYM: &str = "vmlinux-dbgsym";
+#[cfg(feature = "linux")]
+const LINUX_MODULES_DEB: &str = "linux-modules.deb";
+#[cfg(feature = "linux")]
+const SYSTEMMAP: &str = "System.map";
 
 /// An entry in the [`IsrCache`].
 pub struct Entry<C>
@@ -101,6 +191,9 @@ where
     /// The path to the profile.
     profile_path: PathBuf,
 
+    /// The path to the debug file the profile was created from.
+    source_path: Option<PathBuf>,
+
     /// The raw profile data.
     data: Mmap,
 
@@ -117,25 +210,78 @@ where
         let data = unsafe { Mmap::map(&File::open(&profile_path)?)? };
         Ok(Self {
             profile_path,
+            source_path: None,
             data,
             _codec: std::marker::PhantomData,
         })
     }
 
+    /// Sets the path to the debug file the profile was created from.
+    pub fn with_source_path(self, source_path: impl Into<PathBuf>) -> Self {
+        Self {
+            source_path: Some(source_path.into()),
+            ..self
+        }
+    }
+
     /// Returns the path to the profile.
     pub fn profile_path(&self) -> &Path {
         &self.profile_path
     }
 
+    /// Returns the path to the debug file the profile was created from.
+    ///
+    /// This is the PDB file, the PE file with embedded DWARF debug
+    /// information, or the `vmlinux` with debug symbols (the `System.map`
+    /// for symbols-only profiles). Returns `None` for entries created with
+    /// [`Entry::new`].
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
+    }
+
     /// Returns the raw profile data.
     pub fn data(&self) -> &[u8] {
         &self.data
     }
 
+    /// Returns an owned copy of the raw profile data.
+    ///
+    /// The data is copied from the memory map, the file isn't read again.
+    /// Useful to store the profile elsewhere, e.g. in another cache.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
     /// Decodes the profile from the entry.
+    ///
+    /// The profile borrows from the memory map of the entry. Use
+    /// [`Profile::into_owned`] (or its parts' `into_owned`) to keep it, or
+    /// parts of it, after the entry is dropped.
     pub fn profile(&self) -> Result<Profile, C::DecodeError> {
         C::decode(&self.data)
     }
+
+    /// Finds the given symbols in the profile, without decoding the whole
+    /// profile (see [`Codec::find_symbols`]).
+    ///
+    /// Symbols that aren't found are omitted from the result.
+    pub fn find_symbols(&self, names: &[&str]) -> Result<HashMap<String, u64>, C::DecodeError> {
+        C::find_symbols(&self.data, names)
+    }
+}
+
+isr_core::unstable_api! {
+    impl<C> Entry<C>
+    where
+        C: Codec,
+    {
+        /// Returns the metadata of the entry.
+        ///
+        /// Entries without recorded metadata return empty metadata.
+        pub fn metadata(&self) -> Result<unstable::EntryMetadata, Error> {
+            metadata::EntryMetadata::load(&self.profile_path.with_file_name(METADATA))
+        }
+    }
 }
 
 /// A cache for OS kernel profiles.
@@ -150,6 +296,57 @@ where
     /// The directory where cached profiles are stored.
     directory: PathBuf,
 
+    /// Options used when creating profiles.
+    options: ProfileOptions,
+
+    /// How long failed lookups are remembered.
+    negative_ttl: Option<Duration>,
+
+    /// Path to the audit log.
+    audit_log: Option<PathBuf>,
+
+    /// Layout of the PDB files.
+    #[cfg(feature = "pdb")]
+    windows_layout: WindowsLayout,
+
+    /// Symbol servers the PDB files are downloaded from.
+    #[cfg(feature = "pdb")]
+    symbol_servers: Option<Vec<String>>,
+
+    /// Deduplicate identical profiles.
+    dedup: bool,
+
+    /// Files modified more recently are left alone by [`repair`].
+    ///
+    /// [`repair`]: Self::repair
+    repair_grace_period: Duration,
+
+    /// Number of threads used by [`batch_from_directory`].
+    ///
+    /// [`batch_from_directory`]: Self::batch_from_directory
+    #[cfg(feature = "pdb")]
+    batch_threads: usize,
+
+    /// Repository the Ubuntu packages are downloaded from.
+    #[cfg(feature = "linux")]
+    ubuntu_archive_url: Option<Url>,
+
+    /// Repository the Ubuntu debug symbol packages are downloaded from.
+    #[cfg(feature = "linux")]
+    ubuntu_ddebs_url: Option<Url>,
+
+    /// Policy for the Linux packages that were already downloaded.
+    #[cfg(feature = "linux")]
+    skip_policy: SkipPolicy,
+
+    /// Confirms the downloads of Linux packages.
+    #[cfg(feature = "linux")]
+    download_gate: Option<DownloadGate>,
+
+    /// Permissions of the created files and directories.
+    #[cfg(unix)]
+    permissions: Option<CachePermissions>,
+
     /// The codec used to encode and decode profiles.
     _codec: std::marker::PhantomData<C>,
 }
@@ -166,10 +363,421 @@ where
 
         Ok(Self {
             directory,
+            options: ProfileOptions::default(),
+            negative_ttl: None,
+            audit_log: None,
+            #[cfg(feature = "pdb")]
+            windows_layout: WindowsLayout::default(),
+            #[cfg(feature = "pdb")]
+            symbol_servers: None,
+            dedup: false,
+            repair_grace_period: DEFAULT_REPAIR_GRACE_PERIOD,
+            #[cfg(feature = "pdb")]
+            batch_threads: 1,
+            #[cfg(feature = "linux")]
+            ubuntu_archive_url: None,
+            #[cfg(feature = "linux")]
+            ubuntu_ddebs_url: None,
+            #[cfg(feature = "linux")]
+            skip_policy: SkipPolicy::IfChecksumMatches,
+            #[cfg(feature = "linux")]
+            download_gate: None,
+            #[cfg(unix)]
+            permissions: None,
             _codec: std::marker::PhantomData,
         })
     }
 
+    /// Sets the options used when creating profiles.
+    ///
+    /// Profiles created with non-default options are stored under
+    /// a different filename (see [`ProfileOptions::fingerprint`]), so they
+    /// don't collide with profiles created with the default options.
+    pub fn with_profile_options(self, options: ProfileOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Enables the negative cache.
+    ///
+    /// When a download fails because the debug symbols don't exist,
+    /// a `not-found` marker is stored in the entry directory. Subsequent
+    /// lookups of the same entry fail fast with [`Error::NotFoundCached`]
+    /// until `ttl` expires.
+    pub fn with_negative_cache(self, ttl: Duration) -> Self {
+        Self {
+            negative_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Sets the layout of the PDB files.
+    ///
+    /// With [`WindowsLayout::SymStore`], PDB files are read from and
+    /// downloaded to an existing symbol store, so they aren't duplicated.
+    /// Profiles are still stored under the cache directory.
+    #[cfg(feature = "pdb")]
+    pub fn with_windows_layout(self, windows_layout: WindowsLayout) -> Self {
+        Self {
+            windows_layout,
+            ..self
+        }
+    }
+
+    /// Sets the symbol servers the PDB files are downloaded from.
+    ///
+    /// Defaults to the servers of [`PdbDownloader`].
+    #[cfg(feature = "pdb")]
+    pub fn with_symbol_servers(self, servers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            symbol_servers: Some(servers.into_iter().map(Into::into).collect()),
+            ..self
+        }
+    }
+
+    /// Enables the deduplication of identical profiles.
+    ///
+    /// Newly created profiles are hard-linked to `<cache>/objects/`, named
+    /// after a hash of their content. A profile identical to an existing
+    /// object is replaced with a link to it, so the content is stored only
+    /// once. Entries stay independent: removing one doesn't affect the
+    /// others.
+    ///
+    /// Use [`dedup_existing`] to deduplicate profiles created before.
+    /// Objects no longer used by any entry are kept until
+    /// [`remove_unused_objects`] is called, which is only available on
+    /// Unix: on other platforms, the objects directory grows until it's
+    /// removed by hand.
+    ///
+    /// [`dedup_existing`]: Self::dedup_existing
+    /// [`remove_unused_objects`]: Self::remove_unused_objects
+    pub fn with_dedup(self, dedup: bool) -> Self {
+        Self { dedup, ..self }
+    }
+
+    /// Sets the grace period of [`repair`].
+    ///
+    /// Files and directories modified within the grace period may belong
+    /// to a download or profile creation in progress, so [`repair`] leaves
+    /// them alone. Defaults to one hour.
+    ///
+    /// [`repair`]: Self::repair
+    pub fn with_repair_grace_period(self, repair_grace_period: Duration) -> Self {
+        Self {
+            repair_grace_period,
+            ..self
+        }
+    }
+
+    /// Sets the number of threads used by [`batch_from_directory`].
+    ///
+    /// `0` and `1` create the profiles sequentially, which is the default.
+    ///
+    /// [`batch_from_directory`]: Self::batch_from_directory
+    #[cfg(feature = "pdb")]
+    pub fn with_batch_threads(self, batch_threads: usize) -> Self {
+        Self {
+            batch_threads,
+            ..self
+        }
+    }
+
+    /// Sets the repository the Ubuntu packages are downloaded from, e.g.
+    /// a local mirror.
+    ///
+    /// See [`UbuntuDownloader::with_archive_url`].
+    #[cfg(feature = "linux")]
+    pub fn with_ubuntu_archive_url(self, archive_url: Url) -> Self {
+        Self {
+            ubuntu_archive_url: Some(archive_url),
+            ..self
+        }
+    }
+
+    /// Sets the repository the Ubuntu debug symbol packages are downloaded
+    /// from.
+    ///
+    /// See [`UbuntuDownloader::with_ddebs_url`].
+    #[cfg(feature = "linux")]
+    pub fn with_ubuntu_ddebs_url(self, ddebs_url: Url) -> Self {
+        Self {
+            ubuntu_ddebs_url: Some(ddebs_url),
+            ..self
+        }
+    }
+
+    /// Sets the policy for the Linux packages that were already
+    /// downloaded.
+    ///
+    /// Packages are only requested if a file extracted from them is missing
+    /// or invalid. The policy then decides whether the existing package is
+    /// extracted again or downloaded first.
+    ///
+    /// The policy doesn't apply to intact entries: their packages aren't
+    /// compared with the package index, so a package republished with new
+    /// content (e.g. after a signing refresh) isn't noticed. Remove the
+    /// extracted file (or the entry) to check it against the index again.
+    ///
+    /// Defaults to [`SkipPolicy::IfChecksumMatches`].
+    #[cfg(feature = "linux")]
+    pub fn with_skip_policy(self, skip_policy: SkipPolicy) -> Self {
+        Self {
+            skip_policy,
+            ..self
+        }
+    }
+
+    /// Sets a hook that confirms the downloads of Linux packages.
+    ///
+    /// Before [`entry_from_linux_banner`] downloads any package, the
+    /// `gate` is called with the [`DownloadEstimate`]. If it returns
+    /// `false`, nothing is downloaded and [`Error::DownloadDeclined`] is
+    /// returned. The gate isn't called if nothing needs to be downloaded.
+    ///
+    /// The packages are resolved from the repository indexes once, for both
+    /// the estimate and the download. See [`estimate_linux_download`] for
+    /// how the estimate is made.
+    ///
+    /// [`entry_from_linux_banner`]: Self::entry_from_linux_banner
+    /// [`estimate_linux_download`]: Self::estimate_linux_download
+    #[cfg(feature = "linux")]
+    pub fn with_download_gate(
+        self,
+        gate: impl Fn(&DownloadEstimate) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            download_gate: Some(Box::new(gate)),
+            ..self
+        }
+    }
+
+    /// Sets the permissions of the files and directories created in the
+    /// cache.
+    ///
+    /// Use it to share the cache with a group, or to restrict it to its
+    /// owner. By default, files and directories are created with the
+    /// default permissions, subject to the umask. The cache directory
+    /// itself is created by [`new`] and keeps its permissions.
+    ///
+    /// Only the files and directories created by the cache are affected.
+    /// Existing ones (e.g. created by another user of a shared cache) keep
+    /// their permissions.
+    ///
+    /// [`new`]: Self::new
+    #[cfg(unix)]
+    pub fn with_permissions(self, permissions: CachePermissions) -> Self {
+        Self {
+            permissions: Some(permissions),
+            ..self
+        }
+    }
+
+    /// Enables the audit log.
+    ///
+    /// Every successful entry creation or retrieval appends an
+    /// [`AuditRecord`] to the file at `path`, as a line of JSON. The log
+    /// records which profiles and source files were used, so the analysis
+    /// can be reproduced on another machine.
+    pub fn with_audit_log(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            audit_log: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Removes all `not-found` markers from the cache directory.
+    ///
+    /// Returns the number of removed markers.
+    pub fn clear_negative_cache(&self) -> Result<usize, Error> {
+        fn walk(directory: &Path) -> Result<usize, Error> {
+            let mut removed = 0;
+
+            for entry in std::fs::read_dir(directory)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if entry.file_type()?.is_dir() {
+                    removed += walk(&path)?;
+                }
+                else if entry.file_name() == NOT_FOUND {
+                    std::fs::remove_file(&path)?;
+                    removed += 1;
+                }
+            }
+
+            Ok(removed)
+        }
+
+        walk(&self.directory)
+    }
+
+    /// Deduplicates the profiles already in the cache.
+    ///
+    /// See [`with_dedup`]. Returns the number of profiles that were
+    /// replaced with links to identical ones.
+    ///
+    /// [`with_dedup`]: Self::with_dedup
+    pub fn dedup_existing(&self) -> Result<usize, Error> {
+        fn walk(directory: &Path, objects_dir: &Path) -> Result<usize, Error> {
+            let mut deduplicated = 0;
+
+            for entry in std::fs::read_dir(directory)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if entry.file_type()?.is_dir() {
+                    if path != objects_dir {
+                        deduplicated += walk(&path, objects_dir)?;
+                    }
+                }
+                else if entry.file_name().to_string_lossy().starts_with("profile")
+                    && dedup::dedup(objects_dir, &path)?
+                {
+                    deduplicated += 1;
+                }
+            }
+
+            Ok(deduplicated)
+        }
+
+        let objects_dir = self.directory.join(dedup::OBJECTS);
+        self.create_dir_all(&objects_dir)?;
+        walk(&self.directory, &objects_dir)
+    }
+
+    /// Removes the deduplicated profiles no longer used by any entry.
+    ///
+    /// Returns the number of removed objects. An object is unused when
+    /// it's its only hard link left, which is only known on Unix.
+    #[cfg(unix)]
+    pub fn remove_unused_objects(&self) -> Result<usize, Error> {
+        use std::os::unix::fs::MetadataExt as _;
+
+        let objects_dir = self.directory.join(dedup::OBJECTS);
+        if !objects_dir.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in std::fs::read_dir(objects_dir)? {
+            let entry = entry?;
+
+            // The object itself is the only link left.
+            if entry.metadata()?.nlink() == 1 {
+                std::fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Scans the cache for problems, see [`repair`].
+    ///
+    /// [`repair`]: Self::repair
+    fn scan_cache(&self) -> Result<scan::CacheReport, Error> {
+        let objects_dir = self.directory.join(dedup::OBJECTS);
+        let mut report = scan::CacheReport::default();
+        scan::scan::<C>(&self.directory, &objects_dir, &mut report)?;
+        scan::scan_objects::<C>(&objects_dir, &mut report)?;
+        Ok(report)
+    }
+
+    /// Repairs the problems found in the cache.
+    ///
+    /// Leftovers, empty directories, partially downloaded packages and
+    /// corrupt objects are removed, along with the directories left empty.
+    /// Corrupt profiles are created again if the files they were created
+    /// from are still in the entry (the PDB file with the default
+    /// [`WindowsLayout`], the `System.map` and `vmlinux` of an Ubuntu
+    /// kernel) and the profile options are the same. Otherwise, they're
+    /// removed and created again by the next lookup.
+    ///
+    /// Files and directories modified within the grace period (see
+    /// [`with_repair_grace_period`]) are skipped, as another process may
+    /// still be writing them.
+    ///
+    /// [`with_repair_grace_period`]: Self::with_repair_grace_period
+    pub fn repair(&self) -> Result<RepairSummary, Error> {
+        let report = self.scan_cache()?;
+        let mut summary = RepairSummary::default();
+
+        let removable = report
+            .orphans
+            .iter()
+            .chain(&report.partial_downloads)
+            .chain(report.corrupt_objects.iter().map(|(path, _)| path));
+
+        for path in removable {
+            if scan::is_recent(path, self.repair_grace_period) {
+                summary.skipped.push(path.clone());
+                continue;
+            }
+
+            let result = match path.is_dir() {
+                true => std::fs::remove_dir(path),
+                false => std::fs::remove_file(path),
+            };
+
+            match result {
+                Ok(()) => {
+                    summary.removed.push(path.clone());
+                    scan::remove_empty_parents(path, &self.directory, &mut summary.removed);
+                }
+                Err(err) => tracing::warn!(?path, %err, "failed to remove file"),
+            }
+        }
+
+        for (profile_path, _) in report.corrupt_profiles {
+            if scan::is_recent(&profile_path, self.repair_grace_period) {
+                summary.skipped.push(profile_path);
+                continue;
+            }
+
+            std::fs::remove_file(&profile_path)?;
+
+            #[cfg(any(feature = "pdb", feature = "linux"))]
+            let result = self.regenerate_profile(&profile_path);
+            #[cfg(not(any(feature = "pdb", feature = "linux")))]
+            let result = Ok::<_, Error>(false);
+
+            match result {
+                Ok(true) => {
+                    tracing::info!(?profile_path, "profile regenerated");
+                    summary.regenerated.push(profile_path);
+                    continue;
+                }
+                Ok(false) => (),
+                Err(err) => tracing::warn!(?profile_path, %err, "failed to regenerate profile"),
+            }
+
+            summary.removed.push(profile_path.clone());
+            scan::remove_empty_parents(&profile_path, &self.directory, &mut summary.removed);
+        }
+
+        Ok(summary)
+    }
+
+    /// Creates a new `IsrCache` instance in the default directory.
+    ///
+    /// See [`IsrCache::default_directory`] for how the directory is
+    /// resolved.
+    pub fn from_default_directory() -> Result<Self, Error> {
+        Self::new(Self::default_directory().ok_or(Error::NoCacheDirectory)?)
+    }
+
+    /// Returns the default cache directory.
+    ///
+    /// This is the directory in the `ISR_CACHE_DIR` environment variable if
+    /// set, otherwise the `isr` subdirectory of the platform cache directory
+    /// (e.g. `~/.cache/isr` on Linux).
+    pub fn default_directory() -> Option<PathBuf> {
+        if let Some(directory) = std::env::var_os(CACHE_DIR_ENV) {
+            return Some(PathBuf::from(directory));
+        }
+
+        dirs::cache_dir().map(|directory| directory.join("isr"))
+    }
+
     /// Creates or retrieves a cached profile from a [`CodeView`] debug
     /// information structure.
     ///
@@ -179,32 +787,108 @@ where
     /// path is returned.
     #[cfg(feature = "pdb")]
     pub fn entry_from_codeview(&self, codeview: CodeView) -> Result<Entry<C>, Error> {
-        let path = Path::new(&codeview.path);
+        self.entry_from_debug_id_with_options(codeview.into(), &self.options, None)
+    }
 
-        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2
-        let destination = self
-            .directory
-            .join("windows")
-            .join(path)
-            .join(&codeview.guid);
+    /// Creates or retrieves a cached profile from a [`DebugId`].
+    ///
+    /// Same as [`entry_from_codeview`] for [`DebugId::CodeView`]. Entries
+    /// of PE files without CodeView information are stored under
+    /// `windows/<name>/<key>/`. If the entry has no profile yet (e.g. one
+    /// supplied externally), the file indexed under the [`PeKey`] is
+    /// downloaded from the symbol servers. That is usually the executable
+    /// itself, from whose exports a symbols-only profile is created.
+    /// A PDB file indexed under the key is used as usual.
+    ///
+    /// [`entry_from_codeview`]: Self::entry_from_codeview
+    #[cfg(feature = "pdb")]
+    pub fn entry_from_debug_id(&self, debug_id: DebugId) -> Result<Entry<C>, Error> {
+        self.entry_from_debug_id_with_options(debug_id, &self.options, None)
+    }
+
+    /// Creates or retrieves a cached profile from a [`DebugId`], using the
+    /// given profile options.
+    ///
+    /// The file version of the executable, if known, is recorded in the
+    /// entry metadata.
+    #[cfg(feature = "pdb")]
+    fn entry_from_debug_id_with_options(
+        &self,
+        debug_id: DebugId,
+        options: &ProfileOptions,
+        file_version: Option<WindowsBuild>,
+    ) -> Result<Entry<C>, Error> {
+        debug_id.validate().map_err(isr_dl_pdb::Error::from)?;
+        let is_pe_key = matches!(debug_id, DebugId::PeKey(_));
+
+        let started = Instant::now();
+        let CodeViewPlan {
+            destination_path,
+            pdb_path,
+            profile_path,
+            ..
+        } = self.plan_for_debug_id(&debug_id);
+        let existed = profile_path.exists();
+        self.create_dir_all(&destination_path)?;
 
-        std::fs::create_dir_all(&destination)?;
+        // The profile may have been supplied without the PDB file.
+        if existed && !pdb_path.exists() {
+            tracing::info!(?profile_path, "profile already exists");
+
+            let entry = Entry::new(profile_path)?;
+            self.audit(&entry, false, started);
+            return Ok(entry);
+        }
 
-        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/ntkrnlmp.pdb
-        let pdb_path = destination.join(path);
         if !pdb_path.exists() {
-            PdbDownloader::new(codeview.clone())
+            self.check_not_found(&destination_path)?;
+            self.create_parent_dir(&pdb_path)?;
+
+            let result = self
+                .pdb_downloader(debug_id)
                 .with_output(&pdb_path)
-                .download()?;
+                .download();
+
+            match result {
+                Ok(_) => self.set_file_permissions(&pdb_path)?,
+                Err(isr_dl_pdb::Error::NotFound) => {
+                    self.record_not_found(&destination_path)?;
+                    return Err(isr_dl_pdb::Error::NotFound.into());
+                }
+                Err(err) => return Err(err.into()),
+            }
         }
 
-        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/profile<.ext>
-        let profile_path = destination.join("profile").with_extension(C::EXTENSION);
+        // Files indexed under a PE key are usually the executables
+        // themselves, which only provide their exports.
+        let is_executable = is_pe_key && is_pe_file(&pdb_path)?;
 
         match File::create_new(&profile_path) {
             Ok(profile_file) => {
-                let pdb_file = File::open(&pdb_path)?;
-                isr_pdb::create_profile(pdb_file, |profile| C::encode(profile_file, profile))?;
+                let mut writer = BufWriter::with_capacity(ENCODE_BUFFER_SIZE, profile_file);
+                let result = match is_executable {
+                    true => exports_profile_from_pe(&pdb_path, options).and_then(|profile| {
+                        C::encode(&mut writer, &profile).map_err(|err| Error::Encode(err.into()))
+                    }),
+                    false => {
+                        let pdb_file = File::open(&pdb_path)?;
+                        isr_pdb::create_profile_with_options(pdb_file, options, |profile| {
+                            C::encode(&mut writer, profile)
+                        })
+                        .map_err(Error::from)
+                    }
+                }
+                .and_then(|()| Ok(writer.flush()?));
+
+                // Don't leave a useless profile in the cache.
+                if let Err(err) = result {
+                    let _ = std::fs::remove_file(&profile_path);
+                    return Err(err);
+                }
+
+                self.set_file_permissions(&profile_path)?;
+
+                self.dedup_profile(&profile_path)?;
             }
             Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
                 tracing::info!(?profile_path, "profile already exists");
@@ -212,132 +896,1620 @@ where
             Err(err) => return Err(err.into()),
         }
 
-        Entry::new(profile_path)
+        if !is_executable {
+            let metadata_path = destination_path.join(METADATA);
+            let metadata_existed = metadata_path.exists();
+            store_windows_metadata(&metadata_path, &pdb_path, file_version)?;
+            if !metadata_existed {
+                self.set_file_permissions(&metadata_path)?;
+            }
+        }
+
+        let entry = Entry::new(profile_path)?.with_source_path(pdb_path);
+        self.audit(&entry, !existed, started);
+        Ok(entry)
     }
 
-    /// Creates or retrieves a cached profile from a PE file.
-    ///
-    /// Extracts the [`CodeView`] debug information from the PE file and
-    /// delegates to [`entry_from_codeview`].
+    /// Returns the plan for creating an entry from a [`CodeView`] debug
+    /// information structure.
     ///
-    /// [`entry_from_codeview`]: Self::entry_from_codeview
+    /// Nothing is downloaded and no files or directories are created.
     #[cfg(feature = "pdb")]
-    pub fn entry_from_pe(&self, path: impl AsRef<Path>) -> Result<Entry<C>, Error> {
-        self.entry_from_codeview(CodeView::from_path(path).map_err(isr_dl_pdb::Error::from)?)
+    pub fn plan_for_codeview(&self, codeview: &CodeView) -> CodeViewPlan {
+        self.plan_for_debug_id(&codeview.clone().into())
     }
 
-    /// Creates or retrieves a cached profile based on a Linux kernel banner.
+    /// Returns the plan for creating an entry from a [`DebugId`].
     ///
-    /// Parses the banner to determine the kernel version and downloads the
-    /// necessary debug symbols and system map if not present in the cache.
-    /// Generates and stores the profile, returning its path.
-    #[cfg(feature = "linux")]
-    pub fn entry_from_linux_banner(&self, linux_banner: &str) -> Result<Entry<C>, Error> {
-        let banner = match LinuxBanner::parse(linux_banner) {
-            Some(banner) => banner,
-            None => return Err(Error::InvalidBanner),
-        };
+    /// Nothing is downloaded and no files or directories are created.
+    #[cfg(feature = "pdb")]
+    pub fn plan_for_debug_id(&self, debug_id: &DebugId) -> CodeViewPlan {
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2
+        let destination_path = self.directory.join("windows").join(debug_id.server_path());
 
-        let destination_path = match banner.version_signature {
-            Some(LinuxVersionSignature::Ubuntu(version_signature)) => {
-                self.download_from_ubuntu_version_signature(version_signature)?
-            }
-            _ => return Err(Error::InvalidBanner),
-        };
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/ntkrnlmp.pdb
+        // or <symstore>/ntkrnlmp.pdb/3844DBB920174967BE7AA4A2C20430FA2/ntkrnlmp.pdb
+        let pdb_path = self
+            .windows_layout
+            .debug_file_path(&destination_path, debug_id);
 
-        let profile_path = destination_path
-            .join("profile")
-            .with_extension(C::EXTENSION);
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/profile<.ext>
+        let profile_path = self.profile_path(&destination_path);
 
-        match File::create_new(&profile_path) {
-            Ok(profile_file) => {
-                let kernel_file = File::open(destination_path.join("vmlinux-dbgsym"))?;
-                let systemmap_file = File::open(destination_path.join("System.map"))?;
-                isr_dwarf::create_profile(kernel_file, systemmap_file, |profile| {
-                    C::encode(profile_file, profile)
-                })?;
-            }
-            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
-                tracing::info!(?profile_path, "profile already exists");
-            }
-            Err(err) => return Err(err.into()),
+        CodeViewPlan {
+            urls: self.pdb_downloader(debug_id.clone()).resolve_urls(),
+            destination_path,
+            pdb_path,
+            profile_path,
         }
-
-        Entry::new(profile_path)
     }
 
-    /// Downloads and extracts the required debug symbols from the Ubuntu
-    /// repositories based on the Ubuntu version signature in the Linux banner.
+    /// Estimates the download of creating an entry from a [`CodeView`]
+    /// debug information structure.
     ///
-    /// Returns the path to the directory containing the downloaded and
-    /// extracted files.
-    #[cfg(feature = "linux")]
-    fn download_from_ubuntu_version_signature(
+    /// The size of the PDB file is requested from the symbol servers with
+    /// a `HEAD` request, unless the file is already in the cache. Nothing
+    /// is downloaded and no files or directories are created.
+    #[cfg(feature = "pdb")]
+    pub fn estimate_windows_download(
         &self,
-        version_signature: UbuntuVersionSignature,
-    ) -> Result<PathBuf, isr_dl_linux::Error> {
-        let UbuntuVersionSignature {
-            release,
-            revision,
-            kernel_flavour,
+        codeview: &CodeView,
+    ) -> Result<DownloadEstimate, Error> {
+        let CodeViewPlan {
+            pdb_path,
+            profile_path,
             ..
-        } = version_signature;
+        } = self.plan_for_codeview(codeview);
 
-        // <cache>/ubuntu
-        let downloader = UbuntuDownloader::new(&release, &revision, &kernel_flavour)
-            .with_output_directory(self.directory.join("ubuntu"));
+        let mut estimate = DownloadEstimate::default();
 
-        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic
-        let destination_path = downloader.destination_path();
+        if let Ok(metadata) = std::fs::metadata(&pdb_path) {
+            estimate.already_cached_bytes = metadata.len();
+            return Ok(estimate);
+        }
 
-        // Download only what's necessary.
+        // The profile may have been supplied without the PDB file.
+        if profile_path.exists() {
+            return Ok(estimate);
+        }
+
+        let size = self
+            .pdb_downloader(codeview.clone().into())
+            .download_size()?;
+        let name = match pdb_path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => codeview.path.clone(),
+        };
+
+        estimate.add(name, size.unwrap_or(0));
+        Ok(estimate)
+    }
+
+    /// Returns the directory of the cache entry for the given [`CodeView`]
+    /// debug information structure.
+    ///
+    /// The directory isn't created.
+    #[cfg(feature = "pdb")]
+    pub fn windows_entry_dir(&self, codeview: &CodeView) -> PathBuf {
+        self.directory.join("windows").join(codeview.server_path())
+    }
+
+    /// Returns the directory of the cache entry for the given Ubuntu
+    /// version signature.
+    ///
+    /// The directory isn't created.
+    #[cfg(feature = "linux")]
+    pub fn linux_entry_dir(&self, version_signature: &UbuntuVersionSignature) -> PathBuf {
+        self.ubuntu_downloader_for(version_signature)
+            .destination_path()
+    }
+
+    /// Returns the plan for creating an entry from a Linux kernel banner.
+    ///
+    /// Only the repository indexes are fetched. Nothing else is downloaded
+    /// and no files or directories are created.
+    #[cfg(feature = "linux")]
+    pub fn plan_for_banner(&self, linux_banner: &str) -> Result<LinuxBannerPlan, Error> {
+        let downloader = self.ubuntu_downloader(linux_banner)?;
+
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic
+        let destination_path = downloader.destination_path();
+
+        let packages = downloader
+            .download_linux_image()
+            .download_linux_image_dbgsym()
+            .download_linux_modules()
+            .resolve()
+            .map_err(isr_dl_linux::Error::from)?;
+
+        Ok(LinuxBannerPlan {
+            packages,
+            linux_image_deb_path: destination_path.join(LINUX_IMAGE_DEB),
+            linux_image_path: destination_path.join(LINUX_IMAGE),
+            linux_image_dbgsym_deb_path: destination_path.join(LINUX_IMAGE_DBGSYM_DEB),
+            linux_image_dbgsym_path: destination_path.join(LINUX_IMAGE_DBGSYM),
+            linux_modules_deb_path: destination_path.join(LINUX_MODULES_DEB),
+            systemmap_path: destination_path.join(SYSTEMMAP),
+            profile_path: self.profile_path(&destination_path),
+            destination_path,
+        })
+    }
+
+    /// Estimates the download of creating an entry from a Linux kernel
+    /// banner.
+    ///
+    /// Only the packages whose files are missing from the cache are
+    /// counted, with the sizes reported by the repository indexes.
+    /// Packages already downloaded and kept by the [`SkipPolicy`] count as
+    /// cached. Only the repository indexes are fetched. Nothing else is
+    /// downloaded and no files or directories are created.
+    ///
+    /// Existing packages are compared by size only, even with
+    /// [`SkipPolicy::IfChecksumMatches`]. A package whose checksum doesn't
+    /// match is downloaded again, although it's counted as cached.
+    #[cfg(feature = "linux")]
+    pub fn estimate_linux_download(&self, linux_banner: &str) -> Result<DownloadEstimate, Error> {
+        let downloader = self.ubuntu_downloader(linux_banner)?;
+        let destination_path = downloader.destination_path();
+
+        let packages = match self.resolve_ubuntu_download(downloader) {
+            Ok(Some(download)) => download.packages,
+            Ok(None) => ResolvedPlan::default(),
+            Err(err) => return Err(isr_dl_linux::Error::from(err).into()),
+        };
+
+        self.download_estimate(&destination_path, &packages)
+    }
+
+    /// Creates or retrieves a cached profile from a PE file.
+    ///
+    /// If the PE file embeds DWARF debug information (e.g. binaries built
+    /// by mingw), the profile is created directly from it, without any
+    /// download. Otherwise, extracts the [`CodeView`] debug information from
+    /// the PE file and delegates to [`entry_from_codeview`].
+    ///
+    /// A PE file without CodeView information is stored under its
+    /// [`PeKey`] instead, and a symbols-only profile is created from its
+    /// exports (see [`entry_from_debug_id`]).
+    ///
+    /// The `ImageBase` of the PE file is recorded as the preferred base of
+    /// newly created profiles, unless [`ProfileOptions::preferred_base`] is
+    /// set, and its file version in the entry metadata.
+    ///
+    /// [`entry_from_codeview`]: Self::entry_from_codeview
+    /// [`entry_from_debug_id`]: Self::entry_from_debug_id
+    #[cfg(feature = "pdb")]
+    pub fn entry_from_pe(&self, path: impl AsRef<Path>) -> Result<Entry<C>, Error> {
+        let path = path.as_ref();
+
+        #[cfg(feature = "dwarf")]
+        if let Some(entry) = self.entry_from_pe_dwarf(path)? {
+            return Ok(entry);
+        }
+
+        let data = std::fs::read(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let debug_id = DebugId::from_image_bytes(&data, name).map_err(isr_dl_pdb::Error::from)?;
+
+        // Without CodeView information, the executable itself is the file
+        // indexed under its key.
+        if let DebugId::PeKey(_) = &debug_id {
+            let CodeViewPlan { pdb_path, .. } = self.plan_for_debug_id(&debug_id);
+
+            if !pdb_path.exists() {
+                self.create_parent_dir(&pdb_path)?;
+                std::fs::copy(path, &pdb_path)?;
+                self.set_file_permissions(&pdb_path)?;
+            }
+        }
+
+        let image_base =
+            isr_dl_pdb::image_base_from_image_bytes(&data).map_err(isr_dl_pdb::Error::from)?;
+        let file_version = match isr_dl_pdb::file_version_from_image_bytes(&data) {
+            Ok(file_version) => file_version,
+            Err(err) => {
+                tracing::warn!(?path, %err, "failed to read file version");
+                None
+            }
+        };
+
+        // A preferred base set in the options takes precedence.
+        let preferred_base = self.options.preferred_base.unwrap_or(image_base);
+        let options = self.options.clone().with_preferred_base(preferred_base);
+        self.entry_from_debug_id_with_options(debug_id, &options, file_version)
+    }
+
+    /// Creates or retrieves a cached profile from a local PDB file.
+    ///
+    /// The PDB file is copied into the entry identified by its GUID (or into
+    /// the symbol store, see [`WindowsLayout`]), so the entry is the same as
+    /// if the PDB file had been downloaded.
+    #[cfg(feature = "pdb")]
+    pub fn entry_from_pdb_file(&self, path: impl AsRef<Path>) -> Result<Entry<C>, Error> {
+        let path = path.as_ref();
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let codeview = CodeView::new(name, &isr_pdb::pdb_guid(File::open(path)?)?)
+            .map_err(isr_dl_pdb::Error::from)?;
+
+        let CodeViewPlan { pdb_path, .. } = self.plan_for_codeview(&codeview);
+
+        if !pdb_path.exists() {
+            self.create_parent_dir(&pdb_path)?;
+            std::fs::copy(path, &pdb_path)?;
+            self.set_file_permissions(&pdb_path)?;
+        }
+
+        self.entry_from_codeview(codeview)
+    }
+
+    /// Creates or retrieves cached profiles for the files in a directory.
+    ///
+    /// PDB files (`.pdb`) are handled by [`entry_from_pdb_file`] and PE
+    /// files (`.exe`, `.dll`, `.sys`) by [`entry_from_pe`], which may
+    /// download the PDB files. Other files and subdirectories are ignored.
+    ///
+    /// A failure for one file doesn't stop the others; failures are
+    /// collected in the returned summary. The files are split between
+    /// [`with_batch_threads`] threads. The summary lists them in the order
+    /// of their paths in any case.
+    ///
+    /// [`entry_from_pdb_file`]: Self::entry_from_pdb_file
+    /// [`entry_from_pe`]: Self::entry_from_pe
+    /// [`with_batch_threads`]: Self::with_batch_threads
+    #[cfg(feature = "pdb")]
+    pub fn batch_from_directory(
+        &self,
+        directory: impl AsRef<Path>,
+    ) -> Result<BatchSummary<C>, Error>
+    where
+        C: Send + Sync,
+    {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let threads = self.batch_threads.clamp(1, paths.len().max(1));
+        let results = match threads {
+            1 => self.batch_entries(paths),
+            _ => {
+                let chunk_size = paths.len().div_ceil(threads);
+
+                // Worker threads don't inherit the span of the caller.
+                let span = tracing::Span::current();
+                std::thread::scope(|scope| {
+                    let handles = paths
+                        .chunks(chunk_size)
+                        .map(|chunk| {
+                            let span = span.clone();
+                            scope.spawn(move || {
+                                let _span = span.entered();
+                                self.batch_entries(chunk.to_vec())
+                            })
+                        })
+                        .collect::<Vec<_>>();
+
+                    handles
+                        .into_iter()
+                        .flat_map(|handle| {
+                            handle
+                                .join()
+                                .unwrap_or_else(|err| std::panic::resume_unwind(err))
+                        })
+                        .collect()
+                })
+            }
+        };
+
+        let mut summary = BatchSummary {
+            entries: Vec::new(),
+            failures: Vec::new(),
+        };
+
+        for (path, result) in results {
+            match result {
+                Ok(entry) => summary.entries.push((path, entry)),
+                Err(err) => {
+                    tracing::warn!(?path, %err, "failed to create profile");
+                    summary.failures.push((path, err));
+                }
+            }
+        }
+
+        tracing::info!(
+            entries = summary.entries.len(),
+            failures = summary.failures.len(),
+            threads,
+            "batch finished"
+        );
+
+        Ok(summary)
+    }
+
+    /// Creates or retrieves cached profiles for the PDB and PE files among
+    /// `paths`, in order.
+    #[cfg(feature = "pdb")]
+    fn batch_entries(&self, paths: Vec<PathBuf>) -> Vec<(PathBuf, Result<Entry<C>, Error>)> {
+        let mut results = Vec::new();
+
+        for path in paths {
+            let extension = path
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+
+            let result = match extension.as_deref() {
+                Some("pdb") => self.entry_from_pdb_file(&path),
+                Some("exe" | "dll" | "sys") => self.entry_from_pe(&path),
+                _ => continue,
+            };
+
+            results.push((path, result));
+        }
+
+        results
+    }
+
+    /// Lists the entries of the cache in a [`Manifest`].
+    ///
+    /// Only the entries with a profile created with the options of the
+    /// cache are listed. Windows entries are identified by their directory
+    /// (`windows/<name>/<guid>` or `windows/<name>/<key>`). Linux entries
+    /// are identified by the kernel banner recorded when their packages
+    /// were downloaded. Other entries (e.g. created from exports or
+    /// imported from ISF files) can't be created elsewhere and are left
+    /// out.
+    ///
+    /// Pass the manifest to [`prefetch`] on another machine to create the
+    /// same entries there.
+    ///
+    /// [`prefetch`]: Self::prefetch
+    #[cfg(any(feature = "pdb", feature = "linux"))]
+    pub fn export_manifest(&self) -> Result<Manifest, Error> {
+        let mut entries = Vec::new();
+
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2
+        #[cfg(feature = "pdb")]
+        for name_path in manifest::subdirectories(&self.directory.join("windows"))? {
+            for entry_path in manifest::subdirectories(&name_path)? {
+                if !self.profile_path(&entry_path).exists() {
+                    continue;
+                }
+
+                let path = name_path.file_name().unwrap_or_default().to_string_lossy();
+                let id = entry_path.file_name().unwrap_or_default().to_string_lossy();
+
+                let entry = match CodeView::new(path.clone(), &id) {
+                    Ok(codeview) if codeview.guid == id => ManifestEntry::CodeView {
+                        path: codeview.path,
+                        guid: codeview.guid,
+                    },
+                    _ if manifest::is_pe_key(&id) => ManifestEntry::PeKey {
+                        path: path.into_owned(),
+                        key: id.into_owned(),
+                    },
+                    _ => {
+                        tracing::debug!(?entry_path, "entry not identified; skipping");
+                        continue;
+                    }
+                };
+
+                entries.push(entry);
+            }
+        }
+
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic
+        #[cfg(feature = "linux")]
+        for entry_path in manifest::subdirectories(&self.directory.join("ubuntu"))? {
+            if !self.profile_path(&entry_path).exists()
+                && !self.symbols_only_profile_path(&entry_path).exists()
+            {
+                continue;
+            }
+
+            let metadata = metadata::EntryMetadata::load(&entry_path.join(METADATA))?;
+            match metadata.linux_banner {
+                Some(banner) => entries.push(ManifestEntry::Linux {
+                    banner,
+                    packages: metadata.linux_packages.packages().cloned().collect(),
+                }),
+                None => tracing::debug!(?entry_path, "no kernel banner recorded; skipping"),
+            }
+        }
+
+        Ok(Manifest {
+            isr_version: String::from(env!("CARGO_PKG_VERSION")),
+            entries,
+        })
+    }
+
+    /// Creates or retrieves the entries of a [`Manifest`].
+    ///
+    /// Missing entries are created as by [`entry_from_codeview`],
+    /// [`entry_from_debug_id`] and [`entry_from_linux_banner`], which
+    /// download what's needed. A failure for one entry doesn't stop the
+    /// others; failures are collected in the returned summary.
+    ///
+    /// A Linux package resolved to another version or checksum than the
+    /// one in the manifest is logged as a warning.
+    ///
+    /// [`entry_from_codeview`]: Self::entry_from_codeview
+    /// [`entry_from_debug_id`]: Self::entry_from_debug_id
+    /// [`entry_from_linux_banner`]: Self::entry_from_linux_banner
+    #[cfg(any(feature = "pdb", feature = "linux"))]
+    pub fn prefetch(&self, manifest: &Manifest) -> PrefetchSummary<C> {
+        let mut summary = PrefetchSummary {
+            entries: Vec::new(),
+            failures: Vec::new(),
+        };
+
+        for manifest_entry in &manifest.entries {
+            let result = match manifest_entry {
+                #[cfg(feature = "pdb")]
+                ManifestEntry::CodeView { path, guid } => validate_path_component(path)
+                    .and_then(|()| {
+                        CodeView::new(path.clone(), guid)
+                            .map_err(|err| isr_dl_pdb::Error::from(err).into())
+                    })
+                    .and_then(|codeview| self.entry_from_codeview(codeview)),
+                #[cfg(feature = "pdb")]
+                ManifestEntry::PeKey { path, key } => validate_path_component(path)
+                    .and_then(|()| validate_path_component(key))
+                    .and_then(|()| {
+                        self.entry_from_debug_id(DebugId::PeKey(PeKey {
+                            path: path.clone(),
+                            key: key.clone(),
+                        }))
+                    }),
+                #[cfg(feature = "linux")]
+                ManifestEntry::Linux { banner, packages } => {
+                    let result = self.entry_from_linux_banner(banner);
+                    if let Ok(entry) = &result {
+                        warn_package_mismatch(entry, packages);
+                    }
+                    result
+                }
+            };
+
+            match result {
+                Ok(entry) => summary.entries.push((manifest_entry.clone(), entry)),
+                Err(err) => {
+                    tracing::warn!(?manifest_entry, %err, "failed to prefetch entry");
+                    summary.failures.push((manifest_entry.clone(), err));
+                }
+            }
+        }
+
+        tracing::info!(
+            entries = summary.entries.len(),
+            failures = summary.failures.len(),
+            "prefetch finished"
+        );
+
+        summary
+    }
+
+    /// Creates or retrieves a cached symbols-only profile from the exports
+    /// of a module.
+    ///
+    /// Useful when the debug symbols can't be downloaded, e.g. with the
+    /// exports extracted from a memory dump by
+    /// [`exports_from_image_bytes`]. The profile has no types.
+    ///
+    /// The `version_key` identifies the module build, e.g. the CodeView
+    /// GUID or the `<TimeDateStamp><SizeOfImage>` key. Both `module_name`
+    /// and `version_key` must be single path components, otherwise
+    /// [`Error::InvalidPathComponent`] is returned.
+    ///
+    /// [`exports_from_image_bytes`]: isr_dl_pdb::exports_from_image_bytes
+    #[cfg(feature = "pdb")]
+    pub fn entry_from_exports(
+        &self,
+        module_name: &str,
+        version_key: &str,
+        architecture: &str,
+        symbols: Symbols,
+    ) -> Result<Entry<C>, Error> {
+        validate_path_component(module_name)?;
+        validate_path_component(version_key)?;
+
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/exports
+        let destination_path = self
+            .directory
+            .join("windows")
+            .join(module_name)
+            .join(version_key)
+            .join("exports");
+        self.create_dir_all(&destination_path)?;
+
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/exports/profile.json
+        let started = Instant::now();
+        let profile_path = self.profile_path(&destination_path);
+        let existed = profile_path.exists();
+
+        match File::create_new(&profile_path) {
+            Ok(profile_file) => {
+                let profile = exports_profile(architecture, symbols, &self.options);
+                let mut writer = BufWriter::with_capacity(ENCODE_BUFFER_SIZE, profile_file);
+                let result = C::encode(&mut writer, &profile)
+                    .map_err(|err| Error::Encode(err.into()))
+                    .and_then(|()| Ok(writer.flush()?));
+
+                // Don't leave a useless profile in the cache.
+                if let Err(err) = result {
+                    let _ = std::fs::remove_file(&profile_path);
+                    return Err(err);
+                }
+
+                self.set_file_permissions(&profile_path)?;
+
+                self.dedup_profile(&profile_path)?;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                tracing::info!(?profile_path, "profile already exists");
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let entry = Entry::new(profile_path)?;
+        self.audit(&entry, !existed, started);
+        Ok(entry)
+    }
+
+    /// Creates or retrieves a cached profile from a PE file with embedded
+    /// DWARF debug information.
+    ///
+    /// Returns `None` if the PE file doesn't contain DWARF debug
+    /// information.
+    #[cfg(all(feature = "pdb", feature = "dwarf"))]
+    fn entry_from_pe_dwarf(&self, path: &Path) -> Result<Option<Entry<C>>, Error> {
+        use object::{
+            read::pe::{ImageNtHeaders, ImageOptionalHeader as _, PeFile, PeFile32, PeFile64},
+            FileKind, LittleEndian as LE,
+        };
+
+        // Same as the symbol server key of executables:
+        // <TimeDateStamp><SizeOfImage>
+        fn image_id<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> String {
+            let nt_headers = pe.nt_headers();
+            let timestamp = nt_headers.file_header().time_date_stamp.get(LE);
+            let size_of_image = nt_headers.optional_header().size_of_image();
+            format!("{timestamp:08X}{size_of_image:x}")
+        }
+
+        let started = Instant::now();
+        let data = std::fs::read(path)?;
+        if !isr_dwarf::has_debug_info(&data) {
+            return Ok(None);
+        }
+
+        let image_id = match FileKind::parse(&data[..])? {
+            FileKind::Pe32 => image_id(&PeFile32::parse(&data[..])?),
+            FileKind::Pe64 => image_id(&PeFile64::parse(&data[..])?),
+            _ => return Err(Error::NotPe),
+        };
+
+        // <cache>/windows/example.exe/5F1A2B3C1d000
+        let destination_path = self
+            .directory
+            .join("windows")
+            .join(path.file_name().unwrap_or(path.as_os_str()))
+            .join(image_id);
+        self.create_dir_all(&destination_path)?;
+
+        // <cache>/windows/example.exe/5F1A2B3C1d000/profile.json
+        let profile_path = self.profile_path(&destination_path);
+        let existed = profile_path.exists();
+
+        match File::create_new(&profile_path) {
+            Ok(profile_file) => {
+                let mut writer = BufWriter::with_capacity(ENCODE_BUFFER_SIZE, profile_file);
+                let result = isr_dwarf::create_profile_from_object(
+                    File::open(path)?,
+                    &self.options,
+                    |profile| C::encode(&mut writer, profile),
+                )
+                .map_err(Error::from)
+                .and_then(|()| Ok(writer.flush()?));
+
+                // Don't leave a useless profile in the cache.
+                if let Err(err) = result {
+                    let _ = std::fs::remove_file(&profile_path);
+                    return Err(err);
+                }
+
+                self.set_file_permissions(&profile_path)?;
+
+                self.dedup_profile(&profile_path)?;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                tracing::info!(?profile_path, "profile already exists");
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let entry = Entry::new(profile_path)?.with_source_path(path);
+        self.audit(&entry, !existed, started);
+        Ok(Some(entry))
+    }
+
+    /// Creates or retrieves a cached profile based on a Linux kernel banner.
+    ///
+    /// Parses the banner to determine the kernel version and downloads the
+    /// necessary debug symbols and system map if not present in the cache.
+    /// Generates and stores the profile, returning its path.
+    ///
+    /// If the debug symbols aren't available for the kernel, a symbols-only
+    /// profile is created from the system map. It's stored under its own
+    /// filename (e.g. `profile-symbols.json`), so that the full profile is
+    /// created once the debug symbols become available.
+    #[cfg(feature = "linux")]
+    pub fn entry_from_linux_banner(&self, linux_banner: &str) -> Result<Entry<C>, Error> {
+        let started = Instant::now();
+        let banner = match LinuxBanner::parse(linux_banner) {
+            Some(banner) => banner,
+            None => return Err(Error::InvalidBanner),
+        };
+
+        let downloader = self.ubuntu_downloader(linux_banner)?;
+        let destination_path = downloader.destination_path();
+        let profile_path = self.profile_path(&destination_path);
+
+        // The profile may have been supplied without the kernel files (e.g.
+        // imported from an ISF file).
+        if profile_path.exists() && !destination_path.join(SYSTEMMAP).exists() {
+            tracing::info!(?profile_path, "profile already exists");
+
+            let entry = Entry::new(profile_path)?;
+            self.audit(&entry, false, started);
+            return Ok(entry);
+        }
+
+        self.check_not_found(&destination_path)?;
+
+        let download = match self.resolve_ubuntu_download(downloader) {
+            Ok(download) => download,
+            Err(isr_dl_linux::ubuntu::Error::PackageNotFound) => {
+                self.create_dir_all(&destination_path)?;
+                self.record_not_found(&destination_path)?;
+                return Err(isr_dl_linux::Error::from(
+                    isr_dl_linux::ubuntu::Error::PackageNotFound,
+                )
+                .into());
+            }
+            Err(err) => return Err(isr_dl_linux::Error::from(err).into()),
+        };
+
+        let paths = match download {
+            Some(download) => {
+                if let Some(gate) = &self.download_gate {
+                    let estimate = self.download_estimate(&destination_path, &download.packages)?;
+                    if !estimate.per_package.is_empty() && !gate(&estimate) {
+                        tracing::info!(total_bytes = estimate.total_bytes, "download declined");
+                        return Err(Error::DownloadDeclined);
+                    }
+                }
+
+                // The downloader would create the entry directory with the
+                // default permissions.
+                #[cfg(unix)]
+                if self.permissions.is_some() {
+                    self.create_dir_all(&destination_path)?;
+                }
+
+                self.download_from_ubuntu(download)?
+            }
+            None => {
+                tracing::info!("nothing to download");
+                UbuntuPaths::default()
+            }
+        };
+
+        for path in &paths.created {
+            self.set_file_permissions(path)?;
+        }
+
+        // Record the packages that were just downloaded. Packages that were
+        // already present keep their previously recorded metadata.
+        let packages = paths.packages;
+        if packages.packages().next().is_some() {
+            let metadata_path = destination_path.join(METADATA);
+            let metadata_existed = metadata_path.exists();
+            let mut metadata = metadata::EntryMetadata::load(&metadata_path)?;
+            let recorded = &mut metadata.linux_packages;
+            recorded.linux_image = packages.linux_image.or(recorded.linux_image.take());
+            recorded.linux_image_dbgsym = packages
+                .linux_image_dbgsym
+                .or(recorded.linux_image_dbgsym.take());
+            recorded.linux_modules = packages.linux_modules.or(recorded.linux_modules.take());
+            metadata.linux_banner = Some(linux_banner.to_owned());
+            metadata.store(&metadata_path)?;
+            if !metadata_existed {
+                self.set_file_permissions(&metadata_path)?;
+            }
+        }
+
+        let kernel_path = destination_path.join(LINUX_IMAGE_DBGSYM);
+        let systemmap_path = destination_path.join(SYSTEMMAP);
+        let symbols_only_path = self.symbols_only_profile_path(&destination_path);
+        let (profile_path, source_path) = match kernel_path.exists() {
+            true => (profile_path, kernel_path),
+            false => (symbols_only_path.clone(), systemmap_path.clone()),
+        };
+
+        let existed = profile_path.exists();
+        match File::create_new(&profile_path) {
+            Ok(profile_file) => {
+                let systemmap_file = File::open(&systemmap_path)?;
+                let options = self
+                    .options
+                    .clone()
+                    .with_kernel_version(banner.kernel_version());
+                let mut writer = BufWriter::with_capacity(ENCODE_BUFFER_SIZE, profile_file);
+                let result = match profile_path == symbols_only_path {
+                    false => isr_dwarf::create_profile_with_options(
+                        File::open(&source_path)?,
+                        systemmap_file,
+                        &options,
+                        |profile| C::encode(&mut writer, profile),
+                    ),
+                    true => isr_dwarf::create_profile_symbols_only_with_options(
+                        systemmap_file,
+                        &options,
+                        |profile| C::encode(&mut writer, profile),
+                    ),
+                }
+                .map_err(Error::from)
+                .and_then(|()| Ok(writer.flush()?));
+
+                // Don't leave a useless profile in the cache.
+                if let Err(err) = result {
+                    let _ = std::fs::remove_file(&profile_path);
+                    return Err(err);
+                }
+
+                self.set_file_permissions(&profile_path)?;
+
+                self.dedup_profile(&profile_path)?;
+
+                // The full profile supersedes the symbols-only one.
+                if profile_path != symbols_only_path && symbols_only_path.exists() {
+                    tracing::info!(?symbols_only_path, "removing symbols-only profile");
+                    std::fs::remove_file(&symbols_only_path)?;
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                tracing::info!(?profile_path, "profile already exists");
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let entry = Entry::new(profile_path)?.with_source_path(source_path);
+        self.audit(&entry, !existed, started);
+        Ok(entry)
+    }
+
+    /// Appends a record for the entry to the audit log, if enabled.
+    ///
+    /// Failures to write the log are logged, but don't fail the operation.
+    fn audit(&self, entry: &Entry<C>, created: bool, started: Instant) {
+        let audit_log = match &self.audit_log {
+            Some(audit_log) => audit_log,
+            None => return,
+        };
+
+        let destination_path = entry.profile_path().parent().unwrap_or(&self.directory);
+        let key = destination_path
+            .strip_prefix(&self.directory)
+            .unwrap_or(destination_path);
+
+        #[cfg_attr(not(feature = "linux"), expect(unused_mut))]
+        let mut record = AuditRecord::new(
+            key.to_owned(),
+            created,
+            entry.profile_path(),
+            entry.source_path(),
+            C::EXTENSION,
+            started.elapsed(),
+        );
+
+        #[cfg(feature = "linux")]
+        if let Ok(metadata) = metadata::EntryMetadata::load(&destination_path.join(METADATA)) {
+            record.linux_packages = metadata.linux_packages.packages().cloned().collect();
+        }
+
+        if let Err(err) = record.append(audit_log) {
+            tracing::warn!(?audit_log, %err, "failed to write audit log");
+        }
+    }
+
+    /// Creates a removed profile again from the files in its entry.
+    ///
+    /// Returns `false` if the files are missing or if the profile was
+    /// created with other options.
+    #[cfg(any(feature = "pdb", feature = "linux"))]
+    fn regenerate_profile(&self, profile_path: &Path) -> Result<bool, Error> {
+        let destination_path = match profile_path.parent() {
+            Some(destination_path) => destination_path,
+            None => return Ok(false),
+        };
+
+        let symbols_only = match profile_path {
+            path if path == self.profile_path(destination_path) => false,
+            #[cfg(feature = "linux")]
+            path if path == self.symbols_only_profile_path(destination_path) => true,
+            _ => return Ok(false),
+        };
+
+        // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2/ntkrnlmp.pdb
+        #[cfg(feature = "pdb")]
+        if let Some(pdb_path) = destination_path
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| destination_path.join(name))
+            .filter(|pdb_path| !symbols_only && pdb_path.is_file())
+        {
+            write_profile(profile_path, |writer| {
+                Ok(isr_pdb::create_profile_with_options(
+                    File::open(&pdb_path)?,
+                    &self.options,
+                    |profile| C::encode(&mut *writer, profile),
+                )?)
+            })?;
+
+            self.set_file_permissions(profile_path)?;
+
+            self.dedup_profile(profile_path)?;
+            return Ok(true);
+        }
+
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/System.map
+        #[cfg(feature = "linux")]
+        if is_systemmap(&destination_path.join(SYSTEMMAP)) {
+            // The debug symbols must be present for a full profile only.
+            let kernel_path = destination_path.join(LINUX_IMAGE_DBGSYM);
+            if is_elf(&kernel_path) == symbols_only {
+                return Ok(false);
+            }
+
+            let systemmap_file = File::open(destination_path.join(SYSTEMMAP))?;
+            let kernel_version = destination_path
+                .file_name()
+                .and_then(|name| isr_core::KernelVersion::parse(&name.to_string_lossy()));
+            let options = self.options.clone().with_kernel_version(kernel_version);
+
+            write_profile(profile_path, |writer| {
+                Ok(match !symbols_only {
+                    true => isr_dwarf::create_profile_with_options(
+                        File::open(&kernel_path)?,
+                        systemmap_file,
+                        &options,
+                        |profile| C::encode(&mut *writer, profile),
+                    ),
+                    false => isr_dwarf::create_profile_symbols_only_with_options(
+                        systemmap_file,
+                        &options,
+                        |profile| C::encode(&mut *writer, profile),
+                    ),
+                }?)
+            })?;
+
+            self.set_file_permissions(profile_path)?;
+
+            self.dedup_profile(profile_path)?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Fails if a lookup in the given entry directory failed recently.
+    fn check_not_found(&self, destination_path: &Path) -> Result<(), Error> {
+        let ttl = match self.negative_ttl {
+            Some(ttl) => ttl,
+            None => return Ok(()),
+        };
+
+        let marker_path = destination_path.join(NOT_FOUND);
+        let modified = match std::fs::metadata(&marker_path) {
+            Ok(metadata) => metadata.modified()?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        // A marker from the future (clock skew) is treated as fresh.
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+
+        if age < ttl {
+            tracing::info!(?marker_path, ?age, "lookup failed recently");
+            return Err(Error::NotFoundCached);
+        }
+
+        Ok(())
+    }
+
+    /// Records a failed lookup in the given entry directory.
+    fn record_not_found(&self, destination_path: &Path) -> Result<(), Error> {
+        if self.negative_ttl.is_some() {
+            let marker_path = destination_path.join(NOT_FOUND);
+            let marker_existed = marker_path.exists();
+            File::create(&marker_path)?;
+            if !marker_existed {
+                self.set_file_permissions(&marker_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a directory and its missing parents, with the configured
+    /// permissions.
+    fn create_dir_all(&self, path: &Path) -> Result<(), Error> {
+        #[cfg(unix)]
+        if let Some(permissions) = &self.permissions {
+            return Ok(permissions.create_dir_all(path)?);
+        }
+
+        std::fs::create_dir_all(path)?;
+        Ok(())
+    }
+
+    /// Creates the parent directory of a file, if it doesn't exist.
+    #[cfg(feature = "pdb")]
+    fn create_parent_dir(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the configured permissions to a file created in the cache.
+    #[cfg_attr(not(unix), expect(unused_variables))]
+    fn set_file_permissions(&self, path: &Path) -> Result<(), Error> {
+        #[cfg(unix)]
+        if let Some(permissions) = &self.permissions {
+            permissions.set_file_mode(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Deduplicates a newly created profile, if enabled.
+    fn dedup_profile(&self, profile_path: &Path) -> Result<(), Error> {
+        if self.dedup {
+            let objects_dir = self.directory.join(dedup::OBJECTS);
+            self.create_dir_all(&objects_dir)?;
+            if dedup::dedup(&objects_dir, profile_path)? {
+                tracing::info!(?profile_path, "profile deduplicated");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path to the profile in the given entry directory.
+    ///
+    /// The filename reflects the profile options, e.g. `profile.json` or
+    /// `profile-exported.json`.
+    fn profile_path(&self, destination_path: &Path) -> PathBuf {
+        let filename = match self.options.fingerprint() {
+            Some(fingerprint) => format!("profile-{fingerprint}"),
+            None => String::from("profile"),
+        };
+
+        destination_path.join(filename).with_extension(C::EXTENSION)
+    }
+
+    /// Returns the path to the symbols-only profile of a Linux kernel in
+    /// the given entry directory, e.g. `profile-symbols.json`.
+    #[cfg(feature = "linux")]
+    fn symbols_only_profile_path(&self, destination_path: &Path) -> PathBuf {
+        let filename = match self.options.fingerprint() {
+            Some(fingerprint) => format!("profile-{fingerprint}-symbols"),
+            None => String::from("profile-symbols"),
+        };
+
+        destination_path.join(filename).with_extension(C::EXTENSION)
+    }
+
+    /// Creates an [`UbuntuDownloader`] for the given Linux kernel banner,
+    /// with the output directory set to the cache.
+    #[cfg(feature = "linux")]
+    fn ubuntu_downloader(&self, linux_banner: &str) -> Result<UbuntuDownloader, Error> {
+        let banner = match LinuxBanner::parse(linux_banner) {
+            Some(banner) => banner,
+            None => return Err(Error::InvalidBanner),
+        };
+
+        match &banner.version_signature {
+            Some(LinuxVersionSignature::Ubuntu(version_signature)) => {
+                Ok(self.ubuntu_downloader_for(version_signature))
+            }
+            _ => Err(Error::InvalidBanner),
+        }
+    }
+
+    /// Creates a [`PdbDownloader`] for the given [`DebugId`], with the
+    /// configured symbol servers.
+    #[cfg(feature = "pdb")]
+    fn pdb_downloader(&self, debug_id: DebugId) -> PdbDownloader {
+        let downloader = PdbDownloader::from_debug_id(debug_id);
+        match &self.symbol_servers {
+            Some(servers) => downloader.with_servers(servers),
+            None => downloader,
+        }
+    }
+
+    /// Creates an [`UbuntuDownloader`] for the given Ubuntu version
+    /// signature, with the output directory set to the cache.
+    #[cfg(feature = "linux")]
+    fn ubuntu_downloader_for(
+        &self,
+        version_signature: &UbuntuVersionSignature,
+    ) -> UbuntuDownloader {
+        let UbuntuVersionSignature {
+            release,
+            revision,
+            kernel_flavour,
+            ..
+        } = version_signature;
+
+        // <cache>/ubuntu
+        let downloader = UbuntuDownloader::new(release, revision, kernel_flavour)
+            .with_output_directory(self.directory.join("ubuntu"));
+
+        let downloader = match &self.ubuntu_archive_url {
+            Some(archive_url) => downloader.with_archive_url(archive_url.clone()),
+            None => downloader,
+        };
+
+        match &self.ubuntu_ddebs_url {
+            Some(ddebs_url) => downloader.with_ddebs_url(ddebs_url.clone()),
+            None => downloader,
+        }
+    }
+
+    /// Resolves the packages whose files are missing from the cache or
+    /// invalid.
+    ///
+    /// Returns `None` if there's nothing to download. Nothing is removed.
+    #[cfg(feature = "linux")]
+    fn resolve_ubuntu_download(
+        &self,
+        downloader: UbuntuDownloader,
+    ) -> Result<Option<UbuntuDownload>, isr_dl_linux::ubuntu::Error> {
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic
+        let destination_path = downloader.destination_path();
+        let downloader = downloader.with_skip_policy(self.skip_policy);
+
+        // Download only what's necessary.
+
+        // Packages whose files are missing or invalid are requested again.
+        // Already downloaded packages are kept according to `skip_policy`,
+        // only their files are extracted.
 
         // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/linux-image.deb
         // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/vmlinuz
-        let downloader = match destination_path.join("linux-image.deb").exists() {
+        let downloader = match is_extracted(
+            &destination_path.join(LINUX_IMAGE_DEB),
+            &destination_path.join(LINUX_IMAGE),
+            is_nonempty,
+        ) {
             false => downloader
-                .download_linux_image_as("linux-image.deb")
-                .extract_linux_image_as("vmlinuz"),
+                .download_linux_image_as(LINUX_IMAGE_DEB)
+                .extract_linux_image_as(LINUX_IMAGE),
             true => {
                 tracing::info!("linux-image.deb already exists");
                 downloader
             }
         };
 
-        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/linux-image-dbgsym.deb
-        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/vmlinux-dbgsym
-        let downloader = match destination_path.join("linux-image-dbgsym.deb").exists() {
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/linux-modules.deb
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/System.map
+        let downloader = match is_extracted(
+            &destination_path.join(LINUX_MODULES_DEB),
+            &destination_path.join(SYSTEMMAP),
+            is_systemmap,
+        ) {
             false => downloader
-                .download_linux_image_dbgsym_as("linux-image-dbgsym.deb")
-                .extract_linux_image_dbgsym_as("vmlinux-dbgsym"),
+                .download_linux_modules_as(LINUX_MODULES_DEB)
+                .extract_systemmap_as(SYSTEMMAP),
             true => {
-                tracing::info!("linux-image-dbgsym.deb already exists");
+                tracing::info!("linux-modules.deb already exists");
                 downloader
             }
         };
 
-        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/linux-modules.deb
-        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/System.map
-        let downloader = match destination_path.join("linux-modules.deb").exists() {
-            false => downloader
-                .download_linux_modules_as("linux-modules.deb")
-                .extract_systemmap_as("System.map"),
-            true => {
-                tracing::info!("linux-modules.deb already exists");
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/linux-image-dbgsym.deb
+        // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic/vmlinux-dbgsym
+        let (downloader, fallback) = match is_extracted(
+            &destination_path.join(LINUX_IMAGE_DBGSYM_DEB),
+            &destination_path.join(LINUX_IMAGE_DBGSYM),
+            is_elf,
+        ) {
+            false => (
                 downloader
+                    .clone()
+                    .download_linux_image_dbgsym_as(LINUX_IMAGE_DBGSYM_DEB)
+                    .extract_linux_image_dbgsym_as(LINUX_IMAGE_DBGSYM),
+                Some(downloader),
+            ),
+            true => {
+                tracing::info!("linux-image-dbgsym.deb already exists");
+                (downloader, None)
             }
         };
 
-        match downloader.skip_existing().download() {
-            Ok(_paths) => (),
-            // UbuntuDownloader::download() returns Err(InvalidOptions) if
+        let (downloader, result) = match (downloader.resolve(), fallback) {
+            // Some kernels don't have the dbgsym package. A profile can
+            // still be created from the System.map alone.
+            (Err(isr_dl_linux::ubuntu::Error::PackageNotFound), Some(fallback)) => {
+                tracing::warn!("linux-image-dbgsym not found; creating a symbols-only profile");
+                let result = fallback.resolve();
+                (fallback, result)
+            }
+            (result, _) => (downloader, result),
+        };
+
+        match result {
+            Ok(packages) => Ok(Some(UbuntuDownload {
+                downloader,
+                packages,
+            })),
+            // UbuntuDownloader::resolve() returns Err(InvalidOptions) if
             // there's nothing to download.
-            Err(isr_dl_linux::ubuntu::Error::InvalidOptions) => {
-                tracing::info!("nothing to download");
+            Err(isr_dl_linux::ubuntu::Error::InvalidOptions) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Estimates the download of the resolved packages.
+    #[cfg(feature = "linux")]
+    fn download_estimate(
+        &self,
+        destination_path: &Path,
+        packages: &ResolvedPlan,
+    ) -> Result<DownloadEstimate, Error> {
+        // Hashing the existing packages would read all of them before the
+        // download starts. The download verifies the checksums anyway.
+        let skip_policy = match self.skip_policy {
+            SkipPolicy::IfChecksumMatches => SkipPolicy::IfSizeMatches,
+            skip_policy => skip_policy,
+        };
+
+        let mut estimate = DownloadEstimate::default();
+        for (package, deb_name) in [
+            (&packages.linux_image, LINUX_IMAGE_DEB),
+            (&packages.linux_image_dbgsym, LINUX_IMAGE_DBGSYM_DEB),
+            (&packages.linux_modules, LINUX_MODULES_DEB),
+        ] {
+            let deb_path = destination_path.join(deb_name);
+            let package = match package {
+                Some(package) => package,
+                // Not requested, its file is intact.
+                None => {
+                    if let Ok(metadata) = std::fs::metadata(&deb_path) {
+                        estimate.already_cached_bytes += metadata.len();
+                    }
+                    continue;
+                }
+            };
+
+            let size = package.size.map_or(0, |size| size as u64);
+            match skip_policy.keeps_package(&deb_path, package) {
+                Ok(true) => estimate.already_cached_bytes += size,
+                Ok(false) => estimate.add(&package.package, size),
+                Err(err) => return Err(isr_dl_linux::Error::from(err).into()),
             }
-            Err(err) => return Err(err.into()),
         }
 
-        Ok(destination_path)
+        Ok(estimate)
+    }
+
+    /// Downloads and extracts the resolved packages from the Ubuntu
+    /// repositories.
+    ///
+    /// Returns the packages that were downloaded and the files that were
+    /// written.
+    #[cfg(feature = "linux")]
+    fn download_from_ubuntu(
+        &self,
+        download: UbuntuDownload,
+    ) -> Result<UbuntuPaths, isr_dl_linux::Error> {
+        let UbuntuDownload {
+            downloader,
+            packages,
+        } = download;
+
+        // The files of the requested packages are missing or invalid.
+        // Invalid files (e.g. truncated by an interrupted extraction) are
+        // removed, so that they're extracted again.
+        let destination_path = downloader.destination_path();
+        for (package, name) in [
+            (&packages.linux_image, LINUX_IMAGE),
+            (&packages.linux_image_dbgsym, LINUX_IMAGE_DBGSYM),
+            (&packages.linux_modules, SYSTEMMAP),
+        ] {
+            if package.is_some() {
+                remove_invalid(&destination_path.join(name));
+            }
+        }
+
+        Ok(downloader.download_resolved(packages)?)
+    }
+}
+
+isr_core::unstable_api! {
+    impl<C> IsrCache<C>
+    where
+        C: Codec,
+    {
+        /// Scans the cache for undecodable profiles and objects, partially
+        /// downloaded packages and leftovers of interrupted operations.
+        ///
+        /// Only the profiles encoded with the codec of the cache are decoded.
+        /// Nothing is modified, see [`repair`].
+        ///
+        /// [`repair`]: Self::repair
+        pub fn scan(&self) -> Result<unstable::CacheReport, Error> {
+            self.scan_cache()
+        }
+
+        /// Imports a directory of Volatility ISF files into the cache.
+        ///
+        /// ISF files (`.json` and `.json.xz`) are searched recursively. Each
+        /// file is converted into a profile of the entry identified by its
+        /// metadata: the PDB GUID and age for Windows, the kernel banner for
+        /// Linux. Only Ubuntu kernel banners map to a cache entry. The entries
+        /// are then found by [`entry_from_codeview`] and
+        /// [`entry_from_linux_banner`] without downloading anything.
+        ///
+        /// A failure for one file doesn't stop the others; failures are
+        /// collected in the returned report.
+        ///
+        /// [`entry_from_codeview`]: Self::entry_from_codeview
+        /// [`entry_from_linux_banner`]: Self::entry_from_linux_banner
+        #[cfg(feature = "isf")]
+        pub fn import_isf_directory(
+            &self,
+            directory: impl AsRef<Path>,
+            on_conflict: unstable::OnConflict,
+        ) -> Result<unstable::ImportReport, Error> {
+            let mut paths = Vec::new();
+            import::collect_isf_files(directory.as_ref(), &mut paths)?;
+            paths.sort();
+
+            let mut report = unstable::ImportReport::default();
+            for path in paths {
+                match self.import_isf_file(&path, on_conflict) {
+                    Ok(import::ImportOutcome::Converted(profile_path)) => {
+                        report.converted.push((path, profile_path))
+                    }
+                    Ok(import::ImportOutcome::Skipped(profile_path)) => {
+                        report.skipped.push((path, profile_path))
+                    }
+                    Ok(import::ImportOutcome::Unidentified) => {
+                        tracing::warn!(?path, "no identifying metadata");
+                        report.unidentified.push(path);
+                    }
+                    Err(err) => {
+                        tracing::warn!(?path, %err, "failed to import ISF file");
+                        report.failed.push((path, err));
+                    }
+                }
+            }
+
+            tracing::info!(
+                converted = report.converted.len(),
+                skipped = report.skipped.len(),
+                unidentified = report.unidentified.len(),
+                failed = report.failed.len(),
+                "import finished"
+            );
+
+            Ok(report)
+        }
+
+        /// Imports a single ISF file into the cache.
+        #[cfg(feature = "isf")]
+        fn import_isf_file(
+            &self,
+            path: &Path,
+            on_conflict: unstable::OnConflict,
+        ) -> Result<import::ImportOutcome, Error> {
+            let isf = isr_isf::Isf::open(path)?;
+
+            let destination_path = match isf.identity() {
+                // <cache>/windows/ntkrnlmp.pdb/3844dbb920174967be7aa4a2c20430fa2
+                Some(isr_isf::IsfIdentity::Windows(pdb)) => {
+                    let Some(identifier) = pdb.identifier()
+                    else {
+                        return Ok(import::ImportOutcome::Unidentified);
+                    };
+
+                    let codeview =
+                        CodeView::new(&pdb.database, &identifier).map_err(isr_dl_pdb::Error::from)?;
+                    self.windows_entry_dir(&codeview)
+                }
+
+                // <cache>/ubuntu/6.8.0-40.40~22.04.3-generic
+                Some(isr_isf::IsfIdentity::Linux { banner }) => match self.ubuntu_downloader(&banner) {
+                    Ok(downloader) => downloader.destination_path(),
+                    Err(Error::InvalidBanner) => return Ok(import::ImportOutcome::Unidentified),
+                    Err(err) => return Err(err),
+                },
+
+                None => return Ok(import::ImportOutcome::Unidentified),
+            };
+            self.create_dir_all(&destination_path)?;
+
+            let profile_path = self.profile_path(&destination_path);
+            if profile_path.exists() {
+                match on_conflict {
+                    unstable::OnConflict::Skip => {
+                        tracing::info!(?profile_path, "profile already exists");
+                        return Ok(import::ImportOutcome::Skipped(profile_path));
+                    }
+                    unstable::OnConflict::Overwrite => std::fs::remove_file(&profile_path)?,
+                }
+            }
+
+            write_profile(&profile_path, |writer| {
+                Ok(isr_isf::create_profile_with_options(
+                    &isf,
+                    &self.options,
+                    |profile| C::encode(&mut *writer, profile),
+                )?)
+            })?;
+
+            self.set_file_permissions(&profile_path)?;
+
+            self.dedup_profile(&profile_path)?;
+            Ok(import::ImportOutcome::Converted(profile_path))
+        }
+    }
+}
+
+/// Packages resolved for a Linux cache entry, with the downloader that
+/// resolved them.
+#[cfg(feature = "linux")]
+struct UbuntuDownload {
+    downloader: UbuntuDownloader,
+    packages: ResolvedPlan,
+}
+
+/// Checks whether a package was downloaded and its file extracted intact.
+#[cfg(feature = "linux")]
+fn is_extracted(deb_path: &Path, path: &Path, validate: fn(&Path) -> bool) -> bool {
+    if !deb_path.exists() {
+        return false;
+    }
+
+    if validate(path) {
+        return true;
     }
+
+    tracing::warn!(
+        ?path,
+        "extracted file is missing or invalid; extracting again"
+    );
+    false
+}
+
+/// Removes an extracted file that's about to be extracted again.
+#[cfg(feature = "linux")]
+fn remove_invalid(path: &Path) {
+    if let Err(err) = std::fs::remove_file(path) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!(?path, %err, "failed to remove invalid file");
+        }
+    }
+}
+
+/// Checks that the file exists and is not empty.
+#[cfg(feature = "linux")]
+fn is_nonempty(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > 0)
+}
+
+/// Checks that the file starts with a `System.map` line
+/// (`<address> <type> <name>`).
+#[cfg(feature = "linux")]
+fn is_systemmap(path: &Path) -> bool {
+    use std::io::BufRead as _;
+
+    let mut line = String::new();
+    let read = File::open(path)
+        .and_then(|file| std::io::BufReader::new(file).read_line(&mut line))
+        .is_ok_and(|len| len > 0);
+
+    let mut parts = line.split_whitespace();
+    match (read, parts.next(), parts.next(), parts.next()) {
+        (true, Some(address), Some(type_), Some(_)) => {
+            u64::from_str_radix(address, 16).is_ok() && type_.len() == 1
+        }
+        _ => false,
+    }
+}
+
+/// Checks that the file starts with the ELF magic.
+#[cfg(feature = "linux")]
+fn is_elf(path: &Path) -> bool {
+    use std::io::Read as _;
+
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic == *b"\x7fELF")
+}
+
+/// Creates a new profile file, through a buffer.
+///
+/// The file is removed if the profile can't be created.
+#[cfg(any(feature = "pdb", feature = "linux"))]
+fn write_profile(
+    profile_path: &Path,
+    create: impl FnOnce(&mut BufWriter<File>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let file = File::create_new(profile_path)?;
+    let mut writer = BufWriter::with_capacity(ENCODE_BUFFER_SIZE, file);
+    let result = create(&mut writer).and_then(|()| Ok(writer.flush()?));
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(profile_path);
+    }
+
+    result
+}
+
+/// Checks that a name is a single normal path component.
+///
+/// Names joined to a cache directory must not escape it, e.g. with `..`
+/// or an absolute path.
+#[cfg(feature = "pdb")]
+fn validate_path_component(name: &str) -> Result<(), Error> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(component)), None) if component == name => Ok(()),
+        _ => Err(Error::InvalidPathComponent(name.to_owned())),
+    }
+}
+
+/// Warns about the packages of a prefetched Linux entry that differ from
+/// the packages in the manifest.
+#[cfg(feature = "linux")]
+fn warn_package_mismatch<C>(entry: &Entry<C>, expected: &[ResolvedPackage])
+where
+    C: Codec,
+{
+    let metadata_path = entry.profile_path().with_file_name(METADATA);
+    let metadata = match metadata::EntryMetadata::load(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            tracing::warn!(?metadata_path, %err, "failed to load entry metadata");
+            return;
+        }
+    };
+
+    for package in expected {
+        let found = metadata
+            .linux_packages
+            .packages()
+            .find(|found| found.package == package.package);
+
+        match found {
+            Some(found) if found.version == package.version && found.sha256 == package.sha256 => (),
+            Some(found) => tracing::warn!(
+                package = %package.package,
+                expected = %package.version,
+                found = %found.version,
+                "package differs from the manifest"
+            ),
+            None => tracing::warn!(
+                package = %package.package,
+                "package of the manifest not recorded"
+            ),
+        }
+    }
+}
+
+/// Creates a symbols-only profile from the exports of a module.
+#[cfg(feature = "pdb")]
+fn exports_profile<'a>(
+    architecture: &'a str,
+    mut symbols: Symbols<'a>,
+    options: &ProfileOptions,
+) -> Profile<'a> {
+    if options.symbol_scope == SymbolScope::None {
+        symbols.0.clear();
+    }
+
+    let mut types = Types::default();
+    let truncated = options.apply_max_name_length(&mut symbols, &mut types);
+    if truncated > 0 {
+        tracing::info!(truncated, "truncated long names");
+    }
+
+    Profile::new(architecture.into(), symbols, types)
+        .with_source(ProfileSource::Exports)
+        .with_symbol_base(SymbolBase::Rva)
+        .with_symbol_scope(options.symbol_scope)
+        .with_preferred_base(options.preferred_base)
+}
+
+/// Creates a symbols-only profile from the exports of a PE file.
+///
+/// The `ImageBase` of the PE file is recorded as the preferred base,
+/// unless the options set one.
+#[cfg(feature = "pdb")]
+fn exports_profile_from_pe(
+    path: &Path,
+    options: &ProfileOptions,
+) -> Result<Profile<'static>, Error> {
+    let data = std::fs::read(path)?;
+    let symbols = isr_dl_pdb::exports_from_file_bytes(&data)?;
+    let architecture = isr_dl_pdb::architecture_from_image_bytes(&data)?;
+    let image_base =
+        isr_dl_pdb::image_base_from_image_bytes(&data).map_err(isr_dl_pdb::Error::from)?;
+
+    let options = options
+        .clone()
+        .with_preferred_base(options.preferred_base.unwrap_or(image_base));
+    Ok(exports_profile(architecture, symbols, &options))
+}
+
+/// Checks whether a file starts with the `MZ` signature of PE files.
+#[cfg(feature = "pdb")]
+fn is_pe_file(path: &Path) -> Result<bool, Error> {
+    use std::io::Read as _;
+
+    let mut magic = [0u8; 2];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == *b"MZ"),
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Records the identification of the PDB file (and the file version of
+/// the executable, if known) in the entry metadata.
+///
+/// Existing metadata is only updated if it lacks the information.
+#[cfg(feature = "pdb")]
+fn store_windows_metadata(
+    metadata_path: &Path,
+    pdb_path: &Path,
+    file_version: Option<WindowsBuild>,
+) -> Result<(), Error> {
+    let mut metadata = metadata::EntryMetadata::load(metadata_path)?;
+    if let Some(windows) = &mut metadata.windows {
+        match (windows.file_version, file_version) {
+            (None, Some(file_version)) => windows.file_version = Some(file_version),
+            _ => return Ok(()),
+        }
+    }
+    else {
+        let info = isr_pdb::pdb_info(File::open(pdb_path)?)?;
+        metadata.windows = Some(metadata::WindowsMetadata {
+            guid: info.guid,
+            age: info.age,
+            signature: info.signature,
+            file_version,
+        });
+    }
+
+    metadata.store(metadata_path)
 }
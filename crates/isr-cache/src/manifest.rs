@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "linux")]
+use isr_dl_linux::ResolvedPackage;
+use serde::{Deserialize, Serialize};
+
+use crate::{Codec, Entry, Error};
+
+/// Entries of a cache, as a list of the identifiers they were created
+/// from.
+///
+/// Created by [`IsrCache::export_manifest`] and consumed by
+/// [`IsrCache::prefetch`] on another machine, which creates the same
+/// entries there.
+///
+/// [`IsrCache::export_manifest`]: crate::IsrCache::export_manifest
+/// [`IsrCache::prefetch`]: crate::IsrCache::prefetch
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the `isr-cache` crate that exported the manifest.
+    pub isr_version: String,
+
+    /// The entries, ordered by their directory in the cache.
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// Identifier of an entry of a [`Manifest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ManifestEntry {
+    /// Windows entry of a PDB file, identified by its CodeView
+    /// information.
+    #[cfg(feature = "pdb")]
+    CodeView {
+        /// Name of the PDB file.
+        path: String,
+
+        /// PDB GUID, followed by the age.
+        guid: String,
+    },
+
+    /// Windows entry of a PE file without CodeView information,
+    /// identified by its key on the symbol server.
+    #[cfg(feature = "pdb")]
+    PeKey {
+        /// Name of the file on the symbol server.
+        path: String,
+
+        /// `TimeDateStamp` followed by `SizeOfImage`.
+        key: String,
+    },
+
+    /// Linux entry, identified by the kernel banner.
+    #[cfg(feature = "linux")]
+    Linux {
+        /// The kernel banner.
+        banner: String,
+
+        /// Packages the entry was created from, including their SHA256
+        /// checksums as reported by the package index.
+        #[serde(default)]
+        packages: Vec<ResolvedPackage>,
+    },
+}
+
+impl Manifest {
+    /// Loads a manifest from the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Stores the manifest to the given path.
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Summary of creating the entries of a [`Manifest`].
+///
+/// Returned by [`IsrCache::prefetch`].
+///
+/// [`IsrCache::prefetch`]: crate::IsrCache::prefetch
+pub struct PrefetchSummary<C>
+where
+    C: Codec,
+{
+    /// Entries created (or retrieved), with their identifiers.
+    pub entries: Vec<(ManifestEntry, Entry<C>)>,
+
+    /// Identifiers for which no entry could be created, with the errors.
+    pub failures: Vec<(ManifestEntry, Error)>,
+}
+
+impl<C> PrefetchSummary<C>
+where
+    C: Codec,
+{
+    /// Checks whether all entries were created.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Returns the subdirectories of `path`, sorted.
+///
+/// Returns an empty list if `path` doesn't exist.
+pub(crate) fn subdirectories(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut result = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            result.push(entry.path());
+        }
+    }
+
+    result.sort();
+    Ok(result)
+}
+
+/// Checks whether `key` has the shape of a [`PeKey`] key: the
+/// `TimeDateStamp` as 8 uppercase hex digits, followed by the
+/// `SizeOfImage` as 1 to 8 lowercase hex digits.
+///
+/// [`PeKey`]: crate::PeKey
+#[cfg(feature = "pdb")]
+pub(crate) fn is_pe_key(key: &str) -> bool {
+    let (timestamp, size_of_image) = match key.split_at_checked(8) {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    timestamp
+        .bytes()
+        .all(|byte| byte.is_ascii_digit() || (b'A'..=b'F').contains(&byte))
+        && (1..=8).contains(&size_of_image.len())
+        && size_of_image
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
@@ -0,0 +1,74 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+#[cfg(feature = "pdb")]
+use isr_core::WindowsBuild;
+#[cfg(feature = "linux")]
+use isr_dl_linux::ResolvedPlan;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Metadata stored alongside a cache entry.
+///
+/// Records where the files the profile was created from came from,
+/// so that the entry can be reproduced later.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    /// Packages the Linux profile was created from.
+    #[cfg(feature = "linux")]
+    #[serde(default)]
+    pub linux_packages: ResolvedPlan,
+
+    /// Kernel banner the Linux entry was created from.
+    #[cfg(feature = "linux")]
+    #[serde(default)]
+    pub linux_banner: Option<String>,
+
+    /// PDB file (and executable) the Windows profile was created from.
+    #[cfg(feature = "pdb")]
+    #[serde(default)]
+    pub windows: Option<WindowsMetadata>,
+}
+
+/// Identification of the build a Windows profile was created from.
+#[cfg(feature = "pdb")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowsMetadata {
+    /// GUID of the PDB file, as 32 hex digits.
+    pub guid: String,
+
+    /// Age of the PDB file.
+    pub age: u32,
+
+    /// Signature of the PDB file, usually the timestamp of its creation.
+    pub signature: u32,
+
+    /// File version of the executable (e.g. `10.0.18362.356`).
+    ///
+    /// Only known if the entry was created from the executable.
+    #[serde(default)]
+    pub file_version: Option<WindowsBuild>,
+}
+
+impl EntryMetadata {
+    /// Loads the metadata from the given path.
+    ///
+    /// Returns empty metadata if the file doesn't exist.
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Stores the metadata to the given path.
+    #[cfg(any(feature = "linux", feature = "pdb"))]
+    pub(crate) fn store(&self, path: &Path) -> Result<(), Error> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
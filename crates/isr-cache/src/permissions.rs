@@ -0,0 +1,55 @@
+use std::{os::unix::fs::PermissionsExt as _, path::Path};
+
+/// Permissions of the files and directories created in the cache.
+///
+/// Set with [`IsrCache::with_permissions`]. The modes are applied after
+/// the files and directories are created, so they aren't affected by the
+/// umask.
+///
+/// [`IsrCache::with_permissions`]: crate::IsrCache::with_permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePermissions {
+    /// Mode of the created directories (e.g. `0o2775`).
+    pub directory_mode: u32,
+
+    /// Mode of the created files (e.g. `0o664`).
+    pub file_mode: u32,
+}
+
+impl CachePermissions {
+    /// Creates the permissions from the modes of the directories and files.
+    pub fn new(directory_mode: u32, file_mode: u32) -> Self {
+        Self {
+            directory_mode,
+            file_mode,
+        }
+    }
+
+    /// Creates a directory and its missing parents, with the directory
+    /// mode.
+    ///
+    /// Existing directories keep their permissions.
+    pub(crate) fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        let missing = path
+            .ancestors()
+            .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+            .collect::<Vec<_>>();
+
+        std::fs::create_dir_all(path)?;
+
+        for directory in missing.into_iter().rev() {
+            set_mode(directory, self.directory_mode)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the file mode of a file.
+    pub(crate) fn set_file_mode(&self, path: &Path) -> std::io::Result<()> {
+        set_mode(path, self.file_mode)
+    }
+}
+
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "linux")]
+use isr_dl_linux::ResolvedPlan;
+#[cfg(feature = "pdb")]
+use isr_dl_pdb::Url;
+
+/// Plan for creating a cache entry from [`CodeView`] information.
+///
+/// Returned by [`IsrCache::plan_for_codeview`] and
+/// [`IsrCache::plan_for_debug_id`]. The download size can be queried with
+/// [`PdbDownloader::download_size`].
+///
+/// [`CodeView`]: isr_dl_pdb::CodeView
+/// [`PdbDownloader::download_size`]: isr_dl_pdb::PdbDownloader::download_size
+/// [`IsrCache::plan_for_codeview`]: crate::IsrCache::plan_for_codeview
+/// [`IsrCache::plan_for_debug_id`]: crate::IsrCache::plan_for_debug_id
+#[cfg(feature = "pdb")]
+#[derive(Debug, Clone)]
+pub struct CodeViewPlan {
+    /// Candidate URLs of the PDB file, in the order they are tried.
+    pub urls: Vec<Url>,
+
+    /// Directory of the cache entry.
+    pub destination_path: PathBuf,
+
+    /// Path where the PDB file is stored.
+    pub pdb_path: PathBuf,
+
+    /// Path where the profile is stored.
+    pub profile_path: PathBuf,
+}
+
+/// Plan for creating a cache entry from a Linux kernel banner.
+///
+/// Returned by [`IsrCache::plan_for_banner`].
+///
+/// [`IsrCache::plan_for_banner`]: crate::IsrCache::plan_for_banner
+#[cfg(feature = "linux")]
+#[derive(Debug, Clone)]
+pub struct LinuxBannerPlan {
+    /// Packages resolved from the repository indexes.
+    pub packages: ResolvedPlan,
+
+    /// Directory of the cache entry.
+    pub destination_path: PathBuf,
+
+    /// Path where the `linux-image` package is stored.
+    pub linux_image_deb_path: PathBuf,
+
+    /// Path where the kernel image is extracted.
+    pub linux_image_path: PathBuf,
+
+    /// Path where the `linux-image-dbgsym` package is stored.
+    pub linux_image_dbgsym_deb_path: PathBuf,
+
+    /// Path where the kernel debug symbols are extracted.
+    pub linux_image_dbgsym_path: PathBuf,
+
+    /// Path where the `linux-modules` package is stored.
+    pub linux_modules_deb_path: PathBuf,
+
+    /// Path where the `System.map` file is extracted.
+    pub systemmap_path: PathBuf,
+
+    /// Path where the profile is stored.
+    pub profile_path: PathBuf,
+}
+
+#[cfg(feature = "linux")]
+impl LinuxBannerPlan {
+    /// Returns the estimated download size in bytes.
+    ///
+    /// This is the sum of the package sizes reported by the repository
+    /// indexes. Packages without a reported size aren't counted.
+    pub fn download_size(&self) -> u64 {
+        self.packages
+            .packages()
+            .filter_map(|package| package.size)
+            .map(|size| size as u64)
+            .sum()
+    }
+}
+
+/// Estimated transfer of creating a cache entry.
+///
+/// Returned by [`IsrCache::estimate_linux_download`] and
+/// [`IsrCache::estimate_windows_download`], and passed to the gate set by
+/// [`IsrCache::with_download_gate`].
+///
+/// [`IsrCache::estimate_linux_download`]: crate::IsrCache::estimate_linux_download
+/// [`IsrCache::estimate_windows_download`]: crate::IsrCache::estimate_windows_download
+/// [`IsrCache::with_download_gate`]: crate::IsrCache::with_download_gate
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DownloadEstimate {
+    /// Bytes to download, the sum of [`per_package`].
+    ///
+    /// [`per_package`]: Self::per_package
+    pub total_bytes: u64,
+
+    /// Files to download (package or PDB name) with their sizes.
+    ///
+    /// Files whose size isn't reported count as zero bytes.
+    pub per_package: Vec<(String, u64)>,
+
+    /// Bytes of the files already in the cache, which aren't downloaded
+    /// again.
+    pub already_cached_bytes: u64,
+}
+
+impl DownloadEstimate {
+    /// Adds a file to download.
+    pub(crate) fn add(&mut self, name: impl Into<String>, bytes: u64) {
+        self.total_bytes += bytes;
+        self.per_package.push((name.into(), bytes));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{Codec, Error};
+#[cfg(feature = "linux")]
+use crate::{LINUX_IMAGE_DBGSYM_DEB, LINUX_IMAGE_DEB, LINUX_MODULES_DEB, METADATA};
+
+/// Extensions of the temporary files left behind by interrupted
+/// extractions (`.partial`) and deduplications (`.dedup`).
+const TEMPORARY_EXTENSIONS: &[&str] = &["partial", "dedup"];
+
+/// Problems found in the cache.
+///
+/// Returned by [`IsrCache::scan`].
+///
+/// [`IsrCache::scan`]: crate::IsrCache::scan
+#[derive(Debug, Default)]
+pub struct CacheReport {
+    /// Profiles that decode with the codec of the cache.
+    pub profiles: Vec<PathBuf>,
+
+    /// Profiles that don't decode, with the errors.
+    pub corrupt_profiles: Vec<(PathBuf, Box<dyn std::error::Error + Send + Sync>)>,
+
+    /// Deduplicated objects that don't decode, with the errors.
+    pub corrupt_objects: Vec<(PathBuf, Box<dyn std::error::Error + Send + Sync>)>,
+
+    /// Downloaded packages whose size differs from the size in the package
+    /// index, as recorded in the entry metadata.
+    pub partial_downloads: Vec<PathBuf>,
+
+    /// Temporary files left behind by interrupted operations, and empty
+    /// directories.
+    pub orphans: Vec<PathBuf>,
+}
+
+#[cfg(feature = "unstable")]
+impl CacheReport {
+    /// Checks whether no problem was found.
+    pub fn is_clean(&self) -> bool {
+        self.corrupt_profiles.is_empty()
+            && self.corrupt_objects.is_empty()
+            && self.partial_downloads.is_empty()
+            && self.orphans.is_empty()
+    }
+}
+
+/// Changes made to the cache.
+///
+/// Returned by [`IsrCache::repair`].
+///
+/// [`IsrCache::repair`]: crate::IsrCache::repair
+#[derive(Debug, Default)]
+pub struct RepairSummary {
+    /// Corrupt profiles created again from the files in their entry.
+    pub regenerated: Vec<PathBuf>,
+
+    /// Removed files and directories.
+    pub removed: Vec<PathBuf>,
+
+    /// Files and directories left as they are, because they were modified
+    /// within the grace period.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Scans a directory of the cache, recursively.
+///
+/// The deduplicated objects in `objects_dir` are skipped, see
+/// [`scan_objects`].
+pub(crate) fn scan<C>(
+    directory: &Path,
+    objects_dir: &Path,
+    report: &mut CacheReport,
+) -> Result<(), Error>
+where
+    C: Codec,
+{
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            if path == objects_dir {
+                continue;
+            }
+
+            scan::<C>(&path, objects_dir, report)?;
+
+            if std::fs::read_dir(&path)?.next().is_none() {
+                report.orphans.push(path);
+            }
+
+            continue;
+        }
+
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if extension.is_some_and(|extension| TEMPORARY_EXTENSIONS.contains(&extension)) {
+            report.orphans.push(path);
+        }
+        else if entry.file_name().to_string_lossy().starts_with("profile")
+            && extension == Some(C::EXTENSION)
+        {
+            match C::decode(&std::fs::read(&path)?) {
+                Ok(_) => report.profiles.push(path),
+                Err(err) => report.corrupt_profiles.push((path, err.into())),
+            }
+        }
+    }
+
+    #[cfg(feature = "linux")]
+    check_packages(directory, report);
+
+    Ok(())
+}
+
+/// Decodes the deduplicated objects in `objects_dir`.
+pub(crate) fn scan_objects<C>(objects_dir: &Path, report: &mut CacheReport) -> Result<(), Error>
+where
+    C: Codec,
+{
+    let entries = match std::fs::read_dir(objects_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+
+        if !entry.file_type()?.is_file()
+            || path.extension().and_then(|extension| extension.to_str()) != Some(C::EXTENSION)
+        {
+            continue;
+        }
+
+        if let Err(err) = C::decode(&std::fs::read(&path)?) {
+            report.corrupt_objects.push((path, err.into()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Compares the sizes of the downloaded packages with the sizes recorded
+/// in the entry metadata.
+#[cfg(feature = "linux")]
+fn check_packages(directory: &Path, report: &mut CacheReport) {
+    let metadata_path = directory.join(METADATA);
+    if !metadata_path.exists() {
+        return;
+    }
+
+    let metadata = match crate::metadata::EntryMetadata::load(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            tracing::warn!(?metadata_path, %err, "failed to load entry metadata");
+            return;
+        }
+    };
+
+    let packages = &metadata.linux_packages;
+    for (package, filename) in [
+        (&packages.linux_image, LINUX_IMAGE_DEB),
+        (&packages.linux_image_dbgsym, LINUX_IMAGE_DBGSYM_DEB),
+        (&packages.linux_modules, LINUX_MODULES_DEB),
+    ] {
+        let size = match package.as_ref().and_then(|package| package.size) {
+            Some(size) => size as u64,
+            None => continue,
+        };
+
+        let path = directory.join(filename);
+        if std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() != size) {
+            report.partial_downloads.push(path);
+        }
+    }
+}
+
+/// Checks whether `path` was modified less than `grace_period` ago.
+///
+/// A path whose modification time can't be read is treated as recent.
+pub(crate) fn is_recent(path: &Path, grace_period: Duration) -> bool {
+    let modified = match std::fs::symlink_metadata(path).and_then(|metadata| metadata.modified()) {
+        Ok(modified) => modified,
+        Err(_) => return true,
+    };
+
+    // A file from the future (clock skew) is treated as recent.
+    SystemTime::now()
+        .duration_since(modified)
+        .map_or(true, |age| age < grace_period)
+}
+
+/// Removes the directories left empty by removing `path`, up to `root`.
+pub(crate) fn remove_empty_parents(path: &Path, root: &Path, removed: &mut Vec<PathBuf>) {
+    for directory in path.ancestors().skip(1) {
+        if directory == root || !directory.starts_with(root) {
+            break;
+        }
+
+        // Fails if the directory isn't empty.
+        if std::fs::remove_dir(directory).is_err() {
+            break;
+        }
+
+        removed.push(directory.to_owned());
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "pdb")]
+
+mod common;
+
+use isr_cache::{AuditRecord, DebugId, IsrCache, JsonCodec, PeKey};
+
+use self::common::{
+    pe::{pe_with_exports, TIMESTAMP},
+    Server,
+};
+
+const EXPORTS: &[(&str, u32)] = &[("PsActiveProcessHead", 0x1020)];
+
+fn records(path: &std::path::Path) -> Vec<AuditRecord> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn miss_and_hit() {
+    let pe_key = PeKey {
+        path: String::from("ntoskrnl.exe"),
+        key: format!("{TIMESTAMP:08X}3000"),
+    };
+    let path = format!("/{}/{}/{}", pe_key.path, pe_key.key, pe_key.path);
+    let server = Server::new(move |request| match request == path {
+        true => (200, pe_with_exports(EXPORTS, 0x1000, 0x200)),
+        false => (404, Vec::new()),
+    });
+
+    let directory = tempfile::tempdir().unwrap();
+    let audit_log = directory.path().join("audit.jsonl");
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_symbol_servers([&server.url])
+        .with_audit_log(&audit_log);
+
+    let created = cache
+        .entry_from_debug_id(DebugId::PeKey(pe_key.clone()))
+        .unwrap();
+    let retrieved = cache.entry_from_debug_id(DebugId::PeKey(pe_key)).unwrap();
+
+    let records = records(&audit_log);
+    assert_eq!(records.len(), 2);
+
+    let key = format!("windows/ntoskrnl.exe/{TIMESTAMP:08X}3000");
+    for (record, entry) in records.iter().zip([&created, &retrieved]) {
+        assert_eq!(record.key, std::path::Path::new(&key));
+        assert_eq!(record.profile_path, entry.profile_path());
+        assert_eq!(record.codec, "json");
+        assert_eq!(record.isr_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    assert!(records[0].created);
+    assert!(!records[1].created);
+}
+
+#[test]
+fn failures_not_recorded() {
+    let server = Server::new(|_| (404, Vec::new()));
+
+    let directory = tempfile::tempdir().unwrap();
+    let audit_log = directory.path().join("audit.jsonl");
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_symbol_servers([&server.url])
+        .with_audit_log(&audit_log);
+
+    let pe_key = PeKey {
+        path: String::from("ntoskrnl.exe"),
+        key: format!("{TIMESTAMP:08X}3000"),
+    };
+    assert!(cache.entry_from_debug_id(DebugId::PeKey(pe_key)).is_err());
+    assert!(!audit_log.exists());
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "pdb")]
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use isr_cache::{BatchSummary, IsrCache, JsonCodec};
+
+use self::common::pe::pe_with_exports;
+
+const EXPORTS: &[(&str, u32)] = &[("KeBugCheckEx", 0x1010), ("PsActiveProcessHead", 0x1020)];
+
+/// Writes PE files with exports, files that fail and files that are
+/// ignored.
+fn populate(directory: &Path) {
+    for name in ["a.exe", "b.dll", "c.sys", "e.EXE", "g.exe"] {
+        std::fs::write(
+            directory.join(name),
+            pe_with_exports(EXPORTS, 0x1000, 0x200),
+        )
+        .unwrap();
+    }
+
+    std::fs::write(directory.join("d.pdb"), b"not a PDB").unwrap();
+    std::fs::write(directory.join("f.exe"), b"not a PE").unwrap();
+    std::fs::write(directory.join("notes.txt"), b"ignored").unwrap();
+    std::fs::create_dir(directory.join("h.exe")).unwrap();
+}
+
+fn file_names(paths: impl Iterator<Item = PathBuf>) -> Vec<String> {
+    paths
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect()
+}
+
+fn run(threads: usize) -> BatchSummary<JsonCodec> {
+    let directory = tempfile::tempdir().unwrap();
+    populate(directory.path());
+
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_batch_threads(threads);
+
+    cache.batch_from_directory(directory.path()).unwrap()
+}
+
+#[test]
+fn good_and_bad_files() {
+    let summary = run(1);
+    assert!(!summary.is_success());
+
+    let entries = file_names(summary.entries.iter().map(|(path, _)| path.clone()));
+    assert_eq!(entries, ["a.exe", "b.dll", "c.sys", "e.EXE", "g.exe"]);
+
+    let failures = file_names(summary.failures.iter().map(|(path, _)| path.clone()));
+    assert_eq!(failures, ["d.pdb", "f.exe"]);
+
+    for (_, entry) in &summary.entries {
+        let profile = entry.profile().unwrap();
+        assert_eq!(profile.find_symbol("PsActiveProcessHead"), Some(0x1020));
+    }
+}
+
+#[test]
+fn parallel_matches_sequential() {
+    let sequential = run(0);
+
+    // More threads than files as well.
+    for threads in [2, 3, 16] {
+        let parallel = run(threads);
+
+        assert_eq!(
+            file_names(parallel.entries.iter().map(|(path, _)| path.clone())),
+            file_names(sequential.entries.iter().map(|(path, _)| path.clone())),
+            "threads: {threads}"
+        );
+        assert_eq!(
+            file_names(parallel.failures.iter().map(|(path, _)| path.clone())),
+            file_names(sequential.failures.iter().map(|(path, _)| path.clone())),
+            "threads: {threads}"
+        );
+
+        for ((_, parallel), (_, sequential)) in parallel.entries.iter().zip(&sequential.entries) {
+            assert_eq!(parallel.data(), sequential.data());
+        }
+    }
+}
+
+#[test]
+fn empty_directory() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_batch_threads(4);
+
+    let empty = directory.path().join("empty");
+    std::fs::create_dir(&empty).unwrap();
+
+    let summary = cache.batch_from_directory(&empty).unwrap();
+    assert!(summary.is_success());
+    assert!(summary.entries.is_empty());
+}
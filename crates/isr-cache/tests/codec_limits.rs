@@ -0,0 +1,70 @@
+//! Decoding of the binary codecs is bounded.
+
+use std::borrow::Cow;
+
+use isr_cache::Codec;
+use isr_core::{
+    types::{BaseRef, Field, PointerRef, Struct, StructKind, Type, Types},
+    Profile, Symbols,
+};
+
+/// Creates a profile with a field of `depth` nested pointers.
+fn profile(depth: usize) -> Profile<'static> {
+    let mut type_ = Type::Base(BaseRef::U64);
+    for _ in 0..depth {
+        type_ = Type::Pointer(PointerRef {
+            subtype: Box::new(type_),
+        });
+    }
+
+    let mut types = Types::default();
+    types.structs.insert(
+        Cow::Borrowed("_NESTED"),
+        Struct {
+            kind: StructKind::Struct,
+            size: 8,
+            fields: [(Cow::Borrowed("Pointer"), Field { offset: 0, type_ })]
+                .into_iter()
+                .collect(),
+            incomplete: false,
+        },
+    );
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+}
+
+fn encode<C: Codec>(profile: &Profile) -> Vec<u8> {
+    let mut data = Vec::new();
+    C::encode(&mut data, profile).unwrap();
+    data
+}
+
+#[cfg(feature = "codec-bincode")]
+#[test]
+fn bincode_size_limit() {
+    use isr_cache::BincodeCodec;
+
+    let mut data = encode::<BincodeCodec>(&profile(1));
+    assert!(BincodeCodec::decode(&data).is_ok());
+
+    // The length prefix of the architecture claims more than the limit.
+    data[..8].copy_from_slice(&(BincodeCodec::SIZE_LIMIT + 1).to_le_bytes());
+    let err = BincodeCodec::decode(&data).err().unwrap();
+    assert!(matches!(*err, bincode::ErrorKind::SizeLimit), "{err}");
+}
+
+#[cfg(feature = "codec-msgpack")]
+#[test]
+fn msgpack_depth_limit() {
+    use isr_cache::MsgpackCodec;
+
+    let data = encode::<MsgpackCodec>(&profile(16));
+    assert!(MsgpackCodec::decode(&data).is_ok());
+
+    let data = encode::<MsgpackCodec>(&profile(MsgpackCodec::MAX_DEPTH));
+    let err = MsgpackCodec::decode(&data).err().unwrap();
+    assert!(
+        matches!(err, rmp_serde::decode::Error::DepthLimitExceeded),
+        "{err}"
+    );
+}
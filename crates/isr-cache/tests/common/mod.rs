@@ -0,0 +1,80 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+#[cfg(feature = "isf")]
+#[path = "../../../isr-isf/tests/common/isf.rs"]
+pub mod isf;
+#[path = "../../../isr-dwarf/tests/common/kernel.rs"]
+pub mod kernel;
+#[path = "../../../isr-pdb/tests/common/pdb.rs"]
+pub mod pdb;
+pub mod pe;
+#[path = "../../../isr-dl-linux/tests/common/repository.rs"]
+pub mod repository;
+#[cfg(feature = "linux")]
+pub mod ubuntu;
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A local HTTP server.
+pub struct Server {
+    pub url: String,
+    requests: Arc<AtomicUsize>,
+}
+
+impl Server {
+    /// Serves each request path with the status and body returned by
+    /// `route`.
+    pub fn new(route: impl Fn(&str) -> (u16, Vec<u8>) + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let mut parts = request.split(' ');
+                let method = parts.next().unwrap_or_default().to_owned();
+                let path = parts.next().unwrap_or_default().to_owned();
+
+                // Skip the headers.
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                counter.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = route(&path);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+
+                if method != "HEAD" {
+                    let _ = stream.write_all(&body);
+                }
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Returns the number of requests served so far.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,204 @@
+//! Synthetic PE files.
+
+use object::{
+    pe,
+    write::pe::{NtHeaders, Writer},
+};
+
+/// `TimeDateStamp` of the synthetic PE files.
+pub const TIMESTAMP: u32 = 0x5f4e_3a2b;
+
+/// `ImageBase` of the synthetic PE files.
+pub const IMAGE_BASE: u64 = 0x1_4000_0000;
+
+/// Builds a 64-bit PE file with embedded DWARF sections and a COFF symbol
+/// table, as produced by mingw.
+///
+/// The symbols are functions at the given offsets of `.text`.
+pub fn pe_with_dwarf(sections: &[(&str, &[u8])], symbols: &[(&str, u32)]) -> Vec<u8> {
+    // Section names longer than 8 bytes and all symbol names are stored in
+    // the string table, which starts with its size.
+    let mut strings = vec![0; 4];
+    let mut string = |name: &str| {
+        let offset = strings.len() as u32;
+        strings.extend_from_slice(name.as_bytes());
+        strings.push(0);
+        offset
+    };
+
+    let section_names = sections
+        .iter()
+        .map(|(name, _)| {
+            let mut short_name = [0; 8];
+            let long_name = format!("/{}", string(name));
+            short_name[..long_name.len()].copy_from_slice(long_name.as_bytes());
+            short_name
+        })
+        .collect::<Vec<_>>();
+
+    let mut symbol_table = Vec::new();
+    for (name, offset) in symbols {
+        symbol_table.extend_from_slice(&0u32.to_le_bytes());
+        symbol_table.extend_from_slice(&string(name).to_le_bytes());
+        symbol_table.extend_from_slice(&offset.to_le_bytes());
+        symbol_table.extend_from_slice(&1u16.to_le_bytes());
+        symbol_table.extend_from_slice(&(pe::IMAGE_SYM_DTYPE_FUNCTION << 4).to_le_bytes());
+        symbol_table.extend_from_slice(&[pe::IMAGE_SYM_CLASS_EXTERNAL, 0]);
+    }
+
+    let strings_len = strings.len() as u32;
+    strings[..4].copy_from_slice(&strings_len.to_le_bytes());
+
+    let mut data = Vec::new();
+    let mut writer = Writer::new(true, 0x200, 0x200, &mut data);
+
+    writer.reserve_dos_header_and_stub();
+    writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+    writer.reserve_section_headers(1 + sections.len() as u16);
+    let text = writer.reserve_text_section(0x100);
+    let ranges = sections
+        .iter()
+        .zip(section_names)
+        .map(|((_, data), name)| {
+            let characteristics = pe::IMAGE_SCN_CNT_INITIALIZED_DATA
+                | pe::IMAGE_SCN_MEM_DISCARDABLE
+                | pe::IMAGE_SCN_MEM_READ;
+            let len = data.len() as u32;
+            writer.reserve_section(name, characteristics, len, len)
+        })
+        .collect::<Vec<_>>();
+
+    writer.write_dos_header_and_stub().unwrap();
+    let nt_headers_offset = writer.nt_headers_offset() as usize;
+    writer.write_nt_headers(nt_headers(text.virtual_address));
+    writer.write_section_headers();
+    writer.write_section(text.file_offset, &[0xcc; 0x100]);
+    for ((_, section), range) in sections.iter().zip(ranges) {
+        writer.write_section(range.file_offset, section);
+    }
+
+    // PointerToSymbolTable and NumberOfSymbols of the file header.
+    let symbol_table_offset = data.len() as u32;
+    let file_header = nt_headers_offset + 4;
+    data[file_header + 8..file_header + 12].copy_from_slice(&symbol_table_offset.to_le_bytes());
+    data[file_header + 12..file_header + 16].copy_from_slice(&(symbols.len() as u32).to_le_bytes());
+
+    data.extend_from_slice(&symbol_table);
+    data.extend_from_slice(&strings);
+    data
+}
+
+/// Builds a 64-bit PE file without a debug directory, exporting the given
+/// names at the given RVAs.
+///
+/// With equal section and file alignments, the file layout is the same
+/// as the in-memory layout.
+pub fn pe_with_exports(
+    exports: &[(&str, u32)],
+    section_alignment: u32,
+    file_alignment: u32,
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut writer = Writer::new(true, section_alignment, file_alignment, &mut data);
+
+    writer.reserve_dos_header_and_stub();
+    writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+    writer.reserve_section_headers(2);
+    let text = writer.reserve_text_section(0x100);
+    let edata_len = export_directory_len(exports);
+    let edata = writer.reserve_edata_section(edata_len);
+
+    writer.write_dos_header_and_stub().unwrap();
+    writer.write_nt_headers(nt_headers(text.virtual_address));
+    writer.write_section_headers();
+    writer.write_section(text.file_offset, &[0xcc; 0x100]);
+    writer.write_section(
+        edata.file_offset,
+        &export_directory(edata.virtual_address, exports),
+    );
+
+    data
+}
+
+/// Returns the NT headers of the synthetic PE files.
+fn nt_headers(address_of_entry_point: u32) -> NtHeaders {
+    NtHeaders {
+        machine: pe::IMAGE_FILE_MACHINE_AMD64,
+        time_date_stamp: TIMESTAMP,
+        characteristics: pe::IMAGE_FILE_EXECUTABLE_IMAGE | pe::IMAGE_FILE_LARGE_ADDRESS_AWARE,
+        major_linker_version: 14,
+        minor_linker_version: 0,
+        address_of_entry_point,
+        image_base: IMAGE_BASE,
+        major_operating_system_version: 10,
+        minor_operating_system_version: 0,
+        major_image_version: 10,
+        minor_image_version: 0,
+        major_subsystem_version: 10,
+        minor_subsystem_version: 0,
+        subsystem: pe::IMAGE_SUBSYSTEM_NATIVE,
+        dll_characteristics: 0,
+        size_of_stack_reserve: 0x80000,
+        size_of_stack_commit: 0x1000,
+        size_of_heap_reserve: 0,
+        size_of_heap_commit: 0,
+    }
+}
+
+fn export_directory_len(exports: &[(&str, u32)]) -> u32 {
+    export_directory(0, exports).len() as u32
+}
+
+/// Builds an export directory located at `rva`.
+///
+/// Layout: `IMAGE_EXPORT_DIRECTORY`, address table, name pointer table,
+/// ordinal table, module name, export names.
+fn export_directory(rva: u32, exports: &[(&str, u32)]) -> Vec<u8> {
+    const MODULE_NAME: &[u8] = b"ntoskrnl.exe\0";
+
+    let count = exports.len() as u32;
+    let functions = rva + 40;
+    let names = functions + 4 * count;
+    let ordinals = names + 4 * count;
+    let module_name = ordinals + 2 * count;
+    let mut name = module_name + MODULE_NAME.len() as u32;
+
+    // Characteristics, TimeDateStamp, MajorVersion and MinorVersion are
+    // zero. The ordinal base is 1.
+    let mut directory = Vec::new();
+    for value in [
+        0,
+        0,
+        0,
+        module_name,
+        1,
+        count,
+        count,
+        functions,
+        names,
+        ordinals,
+    ] {
+        directory.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for (_, address) in exports {
+        directory.extend_from_slice(&address.to_le_bytes());
+    }
+
+    for (export, _) in exports {
+        directory.extend_from_slice(&name.to_le_bytes());
+        name += export.len() as u32 + 1;
+    }
+
+    for ordinal in 0..count as u16 {
+        directory.extend_from_slice(&ordinal.to_le_bytes());
+    }
+
+    directory.extend_from_slice(MODULE_NAME);
+    for (export, _) in exports {
+        directory.extend_from_slice(export.as_bytes());
+        directory.push(0);
+    }
+
+    directory
+}
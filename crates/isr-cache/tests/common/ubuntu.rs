@@ -0,0 +1,80 @@
+//! Ubuntu kernel 6.8.0-40-generic, served from a [`Repository`].
+
+use std::path::Path;
+
+use isr_cache::{Codec, IsrCache, Url};
+
+use super::{
+    kernel::Kernel,
+    repository::{deb, Package, Repository},
+    Server,
+};
+
+pub const BANNER: &str = "Linux version 6.8.0-40-generic (buildd@lcy02-amd64-078) \
+    (x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~22.04) 12.3.0, GNU ld (GNU Binutils for Ubuntu) 2.38) \
+    #40~22.04.3-Ubuntu SMP PREEMPT_DYNAMIC Tue Jul 30 17:30:19 UTC 2 \
+    (Ubuntu 6.8.0-40.40~22.04.3-generic 6.8.12)";
+
+pub const DIST: &str = "jammy-updates";
+pub const VERSION: &str = "6.8.0-40.40~22.04.3";
+
+/// Directory of the cache entry, relative to the cache.
+pub const ENTRY: &str = "ubuntu/6.8.0-40.40~22.04.3-generic";
+
+pub const IMAGE: &str = "linux-image-6.8.0-40-generic";
+pub const DBGSYM: &str = "linux-image-unsigned-6.8.0-40-generic-dbgsym";
+pub const MODULES: &str = "linux-modules-6.8.0-40-generic";
+
+/// Returns the `linux-image`, `linux-image-dbgsym` and `linux-modules`
+/// packages of the kernel.
+pub fn packages(kernel: &Kernel) -> [Package; 3] {
+    [
+        Package::new(
+            IMAGE,
+            VERSION,
+            deb(&[("./boot/vmlinuz-6.8.0-40-generic", b"vmlinuz")]),
+        ),
+        Package::new(
+            DBGSYM,
+            VERSION,
+            deb(&[(
+                "./usr/lib/debug/boot/vmlinux-6.8.0-40-generic",
+                &kernel.image,
+            )]),
+        ),
+        Package::new(
+            MODULES,
+            VERSION,
+            deb(&[(
+                "./boot/System.map-6.8.0-40-generic",
+                kernel.systemmap.as_bytes(),
+            )]),
+        ),
+    ]
+}
+
+/// Serves a repository with the packages, as both the archive and the
+/// ddebs repository.
+pub fn serve(packages: &[Package]) -> (Repository, Server) {
+    let repository = Repository::default();
+    for package in packages {
+        repository.publish(DIST, package.clone());
+    }
+
+    let server = Server::new({
+        let repository = repository.clone();
+        move |path| repository.route(path)
+    });
+
+    (repository, server)
+}
+
+/// Creates a cache that downloads from the server.
+pub fn cache<C: Codec>(server: &Server, directory: &Path) -> IsrCache<C> {
+    let url = Url::parse(&server.url).unwrap();
+
+    IsrCache::new(directory)
+        .unwrap()
+        .with_ubuntu_archive_url(url.clone())
+        .with_ubuntu_ddebs_url(url)
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "codec-json")]
+
+use std::borrow::Cow;
+
+use isr_cache::{Codec, CompactJsonCodec, JsonCodec};
+use isr_core::{types::Types, Profile, Symbols};
+
+fn profile() -> Profile<'static> {
+    let symbols = [
+        (Cow::Borrowed("PsActiveProcessHead"), 0x43_7bc0),
+        (Cow::Borrowed("PsInitialSystemProcess"), 0x57_33a0),
+    ]
+    .into_iter()
+    .collect();
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default())
+}
+
+fn encode<C: Codec>() -> Vec<u8> {
+    let mut data = Vec::new();
+    C::encode(&mut data, &profile()).unwrap();
+    data
+}
+
+#[test]
+fn compact() {
+    let pretty = encode::<JsonCodec>();
+    let compact = encode::<CompactJsonCodec>();
+
+    assert!(!compact.contains(&b'\n'));
+    assert!(compact.len() < pretty.len());
+    assert_eq!(CompactJsonCodec::EXTENSION, JsonCodec::EXTENSION);
+
+    // Either codec decodes the output of the other.
+    for data in [&pretty, &compact] {
+        for profile in [
+            JsonCodec::decode(data).unwrap(),
+            CompactJsonCodec::decode(data).unwrap(),
+        ] {
+            assert_eq!(profile.find_symbol("PsActiveProcessHead"), Some(0x43_7bc0));
+            assert_eq!(
+                profile.find_symbol("PsInitialSystemProcess"),
+                Some(0x57_33a0)
+            );
+        }
+    }
+}
+
+#[test]
+fn compact_symbols() {
+    let symbols = profile().take_symbols();
+
+    let mut data = Vec::new();
+    CompactJsonCodec::encode_symbols(&mut data, &symbols).unwrap();
+    assert!(!data.contains(&b'\n'));
+
+    let decoded = JsonCodec::decode_symbols(&data).unwrap();
+    assert_eq!(decoded.0, symbols.0);
+}
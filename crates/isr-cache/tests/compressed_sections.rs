@@ -0,0 +1,55 @@
+#![cfg(feature = "linux")]
+
+mod common;
+
+use isr_cache::{Error, JsonCodec};
+
+use self::common::{
+    kernel::Kernel,
+    ubuntu::{cache, packages, serve, BANNER, ENTRY},
+};
+
+/// Returns the fields of `task_struct` in the cached profile.
+fn task_struct(kernel: &Kernel) -> Result<Vec<(String, u64)>, Error> {
+    let (_repository, server) = serve(&packages(kernel));
+    let directory = tempfile::tempdir().unwrap();
+    let cache = cache::<JsonCodec>(&server, directory.path());
+
+    let entry = cache.entry_from_linux_banner(BANNER)?;
+    assert!(entry
+        .profile_path()
+        .starts_with(directory.path().join(ENTRY)));
+
+    let profile = entry.profile().unwrap();
+    let task_struct = profile.find_struct("task_struct").unwrap();
+    Ok(task_struct
+        .fields
+        .iter()
+        .map(|(name, field)| (name.to_string(), field.offset))
+        .collect())
+}
+
+#[test]
+fn zstd_round_trip() {
+    let expected = task_struct(&Kernel::new(2)).unwrap();
+
+    let kernel = Kernel::with_zstd_sections(2, |data| zstd::encode_all(data, 3).unwrap());
+    assert_eq!(task_struct(&kernel).unwrap(), expected);
+}
+
+#[test]
+fn truncated_zstd_stream() {
+    let kernel = Kernel::with_zstd_sections(2, |data| {
+        let mut compressed = zstd::encode_all(data, 3).unwrap();
+        compressed.truncate(compressed.len() / 2);
+        compressed
+    });
+
+    let (_repository, server) = serve(&packages(&kernel));
+    let directory = tempfile::tempdir().unwrap();
+    let cache = cache::<JsonCodec>(&server, directory.path());
+
+    let result = cache.entry_from_linux_banner(BANNER);
+    assert!(matches!(result, Err(Error::Dwarf(_))));
+    assert!(!directory.path().join(ENTRY).join("profile.json").exists());
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "pdb")]
+
+use std::{borrow::Cow, path::Path};
+
+use isr_cache::{Entry, IsrCache, JsonCodec};
+use isr_core::Symbols;
+
+const SYMBOL: (&str, u64) = ("PsActiveProcessHead", 0x1020);
+
+/// Creates the same exports profile for two builds of the module.
+fn create_entries(cache: &IsrCache<JsonCodec>) {
+    for version_key in ["5F4E3A2B3000", "5F4E3A2B4000"] {
+        let symbols = Symbols([(Cow::Borrowed(SYMBOL.0), SYMBOL.1)].into_iter().collect());
+        cache
+            .entry_from_exports("ntoskrnl.exe", version_key, "Amd64", symbols)
+            .unwrap();
+    }
+}
+
+fn objects(directory: &Path) -> usize {
+    std::fs::read_dir(directory.join("objects"))
+        .unwrap()
+        .count()
+}
+
+fn assert_readable(directory: &Path, version_key: &str) {
+    let profile_path = directory
+        .join("windows/ntoskrnl.exe")
+        .join(version_key)
+        .join("exports/profile.json");
+
+    let entry = Entry::<JsonCodec>::new(profile_path).unwrap();
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.find_symbol(SYMBOL.0), Some(SYMBOL.1));
+}
+
+#[test]
+fn identical_profiles_share_an_object() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_dedup(true);
+
+    create_entries(&cache);
+    assert_eq!(objects(directory.path()), 1);
+    assert_readable(directory.path(), "5F4E3A2B3000");
+    assert_readable(directory.path(), "5F4E3A2B4000");
+
+    // Removing an entry doesn't affect the other.
+    std::fs::remove_dir_all(directory.path().join("windows/ntoskrnl.exe/5F4E3A2B3000")).unwrap();
+    assert_readable(directory.path(), "5F4E3A2B4000");
+
+    #[cfg(unix)]
+    {
+        assert_eq!(cache.remove_unused_objects().unwrap(), 0);
+        assert_readable(directory.path(), "5F4E3A2B4000");
+
+        std::fs::remove_dir_all(directory.path().join("windows/ntoskrnl.exe/5F4E3A2B4000"))
+            .unwrap();
+        assert_eq!(cache.remove_unused_objects().unwrap(), 1);
+        assert_eq!(objects(directory.path()), 0);
+    }
+}
+
+#[test]
+fn dedup_existing() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    create_entries(&cache);
+    assert!(!directory.path().join("objects").exists());
+
+    // The first profile becomes the object, the second is linked to it.
+    assert_eq!(cache.dedup_existing().unwrap(), 1);
+    assert_eq!(objects(directory.path()), 1);
+    assert_readable(directory.path(), "5F4E3A2B3000");
+    assert_readable(directory.path(), "5F4E3A2B4000");
+
+    // Nothing left to deduplicate.
+    assert_eq!(cache.dedup_existing().unwrap(), 0);
+}
@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+
+use isr_cache::{detect_codec, Codec, KnownCodec};
+use isr_core::{types::Types, Profile, Symbols};
+
+fn profile() -> Profile<'static> {
+    let symbols = [(Cow::Borrowed("PsInitialSystemProcess"), 0x57_33a0)]
+        .into_iter()
+        .collect();
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default())
+}
+
+fn encode<C: Codec>() -> Vec<u8> {
+    let mut data = Vec::new();
+    C::encode(&mut data, &profile()).unwrap();
+    data
+}
+
+#[cfg(feature = "codec-json")]
+#[test]
+fn json() {
+    use isr_cache::{CompactJsonCodec, JsonCodec};
+
+    assert_eq!(detect_codec(&encode::<JsonCodec>()), Some(KnownCodec::Json));
+    assert_eq!(
+        detect_codec(&encode::<CompactJsonCodec>()),
+        Some(KnownCodec::Json)
+    );
+    assert_eq!(detect_codec(b" \n\t{}"), Some(KnownCodec::Json));
+}
+
+#[cfg(feature = "codec-msgpack")]
+#[test]
+fn msgpack() {
+    use isr_cache::MsgpackCodec;
+
+    assert_eq!(
+        detect_codec(&encode::<MsgpackCodec>()),
+        Some(KnownCodec::Msgpack)
+    );
+}
+
+#[cfg(feature = "codec-bincode")]
+#[test]
+fn bincode() {
+    use isr_cache::BincodeCodec;
+
+    let data = encode::<BincodeCodec>();
+    assert_eq!(detect_codec(&data), Some(KnownCodec::Bincode));
+
+    // The detected codec decodes the profile.
+    let decoded = BincodeCodec::decode(&data).unwrap();
+    assert_eq!(
+        decoded.find_symbol("PsInitialSystemProcess"),
+        Some(0x57_33a0)
+    );
+}
+
+#[test]
+fn unknown() {
+    assert_eq!(detect_codec(b""), None);
+    assert_eq!(detect_codec(b"   "), None);
+    assert_eq!(detect_codec(b"\x7fELF\x02\x01\x01\x00"), None);
+
+    // Truncated architecture name.
+    assert_eq!(detect_codec(b"\x05\0\0\0\0\0\0\0Amd"), None);
+
+    // Architecture name that isn't an identifier.
+    assert_eq!(detect_codec(b"\x05\0\0\0\0\0\0\0Am d6"), None);
+}
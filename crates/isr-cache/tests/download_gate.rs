@@ -0,0 +1,144 @@
+#![cfg(feature = "linux")]
+
+mod common;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use isr_cache::{DownloadEstimate, Error, IsrCache, JsonCodec};
+
+use self::common::{
+    kernel::Kernel,
+    ubuntu::{cache, packages, serve, BANNER, DBGSYM, ENTRY, IMAGE, MODULES},
+};
+
+/// A gate that records the estimates it's called with.
+#[derive(Clone, Default)]
+struct Gate {
+    accept: Arc<AtomicBool>,
+    estimates: Arc<Mutex<Vec<DownloadEstimate>>>,
+}
+
+impl Gate {
+    fn install(&self, cache: IsrCache<JsonCodec>) -> IsrCache<JsonCodec> {
+        let gate = self.clone();
+        cache.with_download_gate(move |estimate| {
+            gate.estimates.lock().unwrap().push(estimate.clone());
+            gate.accept.load(Ordering::SeqCst)
+        })
+    }
+
+    fn accept(&self, accept: bool) {
+        self.accept.store(accept, Ordering::SeqCst);
+    }
+
+    fn estimates(&self) -> Vec<DownloadEstimate> {
+        self.estimates.lock().unwrap().clone()
+    }
+}
+
+#[test]
+fn declined_download() {
+    let kernel = Kernel::new(2);
+    let mut packages = packages(&kernel);
+
+    // The index reports the size, the package isn't downloaded.
+    packages[0].size = Some(1_400_000_000);
+    let (repository, server) = serve(&packages);
+
+    let directory = tempfile::tempdir().unwrap();
+    let gate = Gate::default();
+    let cache = gate.install(cache(&server, directory.path()));
+
+    let result = cache.entry_from_linux_banner(BANNER);
+    assert!(matches!(result, Err(Error::DownloadDeclined)));
+    assert!(repository.downloads().is_empty());
+    assert!(!directory.path().join(ENTRY).exists());
+
+    let dbgsym_size = packages[1].data.len() as u64;
+    let modules_size = packages[2].data.len() as u64;
+    let expected = DownloadEstimate {
+        total_bytes: 1_400_000_000 + dbgsym_size + modules_size,
+        per_package: vec![
+            (IMAGE.into(), 1_400_000_000),
+            (DBGSYM.into(), dbgsym_size),
+            (MODULES.into(), modules_size),
+        ],
+        already_cached_bytes: 0,
+    };
+    assert_eq!(gate.estimates(), std::slice::from_ref(&expected));
+    assert_eq!(cache.estimate_linux_download(BANNER).unwrap(), expected);
+}
+
+#[test]
+fn accepted_download() {
+    let kernel = Kernel::new(2);
+    let packages = packages(&kernel);
+    let sizes = packages
+        .iter()
+        .map(|package| package.data.len() as u64)
+        .collect::<Vec<_>>();
+    let (repository, server) = serve(&packages);
+
+    // The indexes fetched to resolve the packages.
+    let directory = tempfile::tempdir().unwrap();
+    cache::<JsonCodec>(&server, &directory.path().join("plan"))
+        .plan_for_banner(BANNER)
+        .unwrap();
+    let index_requests = server.requests();
+
+    let gate = Gate::default();
+    gate.accept(true);
+    let cache = gate.install(cache(&server, &directory.path().join("cache")));
+    let entry_path = directory.path().join("cache").join(ENTRY);
+
+    // The download uses the packages resolved for the estimate.
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    assert_eq!(entry.profile_path(), entry_path.join("profile.json"));
+    assert_eq!(repository.downloads().len(), 3);
+    assert_eq!(server.requests(), 2 * index_requests + 3);
+    assert_eq!(gate.estimates().len(), 1);
+    assert_eq!(gate.estimates()[0].total_bytes, sizes.iter().sum::<u64>());
+
+    // Nothing to download, not even the indexes.
+    let requests = server.requests();
+    let expected = DownloadEstimate {
+        already_cached_bytes: sizes.iter().sum(),
+        ..Default::default()
+    };
+    assert_eq!(cache.estimate_linux_download(BANNER).unwrap(), expected);
+    cache.entry_from_linux_banner(BANNER).unwrap();
+    assert_eq!(server.requests(), requests);
+    assert_eq!(gate.estimates().len(), 1);
+
+    // The kernel is extracted again from the package that was kept,
+    // without asking.
+    std::fs::remove_file(entry_path.join("vmlinux-dbgsym")).unwrap();
+    assert_eq!(cache.estimate_linux_download(BANNER).unwrap(), expected);
+    cache.entry_from_linux_banner(BANNER).unwrap();
+    assert!(entry_path.join("vmlinux-dbgsym").exists());
+    assert_eq!(repository.downloads().len(), 3);
+    assert_eq!(gate.estimates().len(), 1);
+
+    // A truncated package is downloaded again.
+    std::fs::write(entry_path.join("linux-image.deb"), b"!<arch>\n").unwrap();
+    std::fs::remove_file(entry_path.join("vmlinuz")).unwrap();
+    let expected = DownloadEstimate {
+        total_bytes: sizes[0],
+        per_package: vec![(IMAGE.into(), sizes[0])],
+        already_cached_bytes: sizes[1] + sizes[2],
+    };
+    assert_eq!(cache.estimate_linux_download(BANNER).unwrap(), expected);
+
+    gate.accept(false);
+    let result = cache.entry_from_linux_banner(BANNER);
+    assert!(matches!(result, Err(Error::DownloadDeclined)));
+    assert_eq!(repository.downloads().len(), 3);
+
+    gate.accept(true);
+    cache.entry_from_linux_banner(BANNER).unwrap();
+    assert_eq!(repository.downloads().len(), 4);
+    assert_eq!(gate.estimates()[1..], [expected.clone(), expected]);
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "linux")]
+
+mod common;
+
+use isr_cache::{BincodeCodec, Codec, JsonCodec, MsgpackCodec};
+
+use self::common::{
+    kernel::Kernel,
+    ubuntu::{cache, packages, serve, BANNER},
+};
+
+/// Checks that the buffered profile file holds the whole encoded profile.
+fn complete<C: Codec>() {
+    let kernel = Kernel::new(2);
+    let (_repository, server) = serve(&packages(&kernel));
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = cache::<C>(&server, directory.path());
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+
+    let mut encoded = Vec::new();
+    C::encode(&mut encoded, &entry.profile().unwrap()).unwrap();
+
+    let written = std::fs::read(entry.profile_path()).unwrap();
+    assert!(!written.is_empty());
+    assert!(written == encoded);
+}
+
+#[test]
+fn json() {
+    complete::<JsonCodec>();
+}
+
+#[test]
+fn msgpack() {
+    complete::<MsgpackCodec>();
+}
+
+#[test]
+fn bincode() {
+    complete::<BincodeCodec>();
+}
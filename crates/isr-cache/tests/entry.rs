@@ -0,0 +1,123 @@
+use std::{borrow::Cow, path::Path};
+
+use isr_cache::{Codec, Entry};
+use isr_core::{
+    types::{BaseRef, Enum, Type, Types, Variant},
+    Profile, Symbols,
+};
+
+fn profile() -> Profile<'static> {
+    let symbols = [
+        (Cow::Borrowed("PsActiveProcessHead"), 0x43_7bc0),
+        (Cow::Borrowed("PsInitialSystemProcess"), 0x57_33a0),
+    ]
+    .into_iter()
+    .collect();
+
+    let mut types = Types::default();
+    types.enums.insert(
+        Cow::Borrowed("_POOL_TYPE"),
+        Enum::new(
+            Type::Base(BaseRef::I32),
+            [
+                (Cow::Borrowed("NonPagedPool"), Variant::I64(0)),
+                (Cow::Borrowed("PagedPool"), Variant::I64(1)),
+            ]
+            .into_iter()
+            .collect(),
+        ),
+    );
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), types)
+}
+
+/// Encodes the profile, to compare profiles.
+fn encode<C: Codec>(profile: &Profile) -> Vec<u8> {
+    let mut data = Vec::new();
+    C::encode(&mut data, profile).unwrap();
+    data
+}
+
+/// Writes the profile to a file and checks that the copied bytes of the
+/// entry decode to the same profile.
+fn round_trip<C: Codec>(directory: &Path) {
+    let profile = profile();
+
+    let profile_path = directory.join(format!("profile.{}", C::EXTENSION));
+    let data = encode::<C>(&profile);
+    std::fs::write(&profile_path, &data).unwrap();
+
+    let entry = Entry::<C>::new(profile_path.clone()).unwrap();
+    let bytes = entry.to_vec();
+    assert_eq!(bytes, data);
+    assert_eq!(bytes, entry.data());
+
+    // The copy outlives the entry and its file.
+    let expected = encode::<C>(&entry.profile().unwrap());
+    drop(entry);
+    std::fs::remove_file(&profile_path).unwrap();
+
+    let decoded = C::decode(&bytes).unwrap();
+    assert_eq!(encode::<C>(&decoded), expected);
+    assert_eq!(encode::<C>(&decoded), data);
+    assert_eq!(decoded.find_symbol("PsActiveProcessHead"), Some(0x43_7bc0));
+}
+
+#[cfg(feature = "codec-json")]
+#[test]
+fn to_vec_json() {
+    let directory = tempfile::tempdir().unwrap();
+    round_trip::<isr_cache::JsonCodec>(directory.path());
+}
+
+#[cfg(feature = "codec-msgpack")]
+#[test]
+fn to_vec_msgpack() {
+    let directory = tempfile::tempdir().unwrap();
+    round_trip::<isr_cache::MsgpackCodec>(directory.path());
+}
+
+#[cfg(feature = "codec-bincode")]
+#[test]
+fn to_vec_bincode() {
+    let directory = tempfile::tempdir().unwrap();
+    round_trip::<isr_cache::BincodeCodec>(directory.path());
+}
+
+/// Looks up symbols in the entry, without decoding the whole profile.
+fn find_symbols<C: Codec>(directory: &Path) {
+    let profile_path = directory.join(format!("profile.{}", C::EXTENSION));
+    std::fs::write(&profile_path, encode::<C>(&profile())).unwrap();
+
+    let entry = Entry::<C>::new(profile_path).unwrap();
+    let found = entry
+        .find_symbols(&["PsInitialSystemProcess", "KeBugCheckEx"])
+        .unwrap();
+
+    // Symbols that aren't found are omitted.
+    assert_eq!(found.len(), 1);
+    assert_eq!(found.get("PsInitialSystemProcess"), Some(&0x57_33a0));
+
+    assert!(entry.find_symbols(&[]).unwrap().is_empty());
+}
+
+#[cfg(feature = "codec-json")]
+#[test]
+fn find_symbols_json() {
+    let directory = tempfile::tempdir().unwrap();
+    find_symbols::<isr_cache::JsonCodec>(directory.path());
+}
+
+#[cfg(feature = "codec-msgpack")]
+#[test]
+fn find_symbols_msgpack() {
+    let directory = tempfile::tempdir().unwrap();
+    find_symbols::<isr_cache::MsgpackCodec>(directory.path());
+}
+
+#[cfg(feature = "codec-bincode")]
+#[test]
+fn find_symbols_bincode() {
+    let directory = tempfile::tempdir().unwrap();
+    find_symbols::<isr_cache::BincodeCodec>(directory.path());
+}
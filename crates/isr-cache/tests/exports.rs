@@ -0,0 +1,64 @@
+#![cfg(feature = "pdb")]
+
+use std::borrow::Cow;
+
+use isr_cache::{Error, IsrCache, JsonCodec, ProfileSource};
+use isr_core::Symbols;
+
+fn symbols() -> Symbols<'static> {
+    Symbols(
+        [(Cow::Borrowed("PsActiveProcessHead"), 0x1020)]
+            .into_iter()
+            .collect(),
+    )
+}
+
+#[test]
+fn entry_from_exports() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    let entry = cache
+        .entry_from_exports("ntoskrnl.exe", "5F4E3A2B3000", "Amd64", symbols())
+        .unwrap();
+
+    assert_eq!(
+        entry.profile_path(),
+        directory
+            .path()
+            .join("windows/ntoskrnl.exe/5F4E3A2B3000/exports/profile.json")
+    );
+
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.source(), Some(ProfileSource::Exports));
+    assert_eq!(profile.find_symbol("PsActiveProcessHead"), Some(0x1020));
+}
+
+#[test]
+fn invalid_path_components() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache_path = directory.path().join("cache");
+    let cache = IsrCache::<JsonCodec>::new(&cache_path).unwrap();
+    let outside = directory.path().join("outside");
+
+    for (module_name, version_key) in [
+        ("..", "5F4E3A2B3000"),
+        ("../../outside", "5F4E3A2B3000"),
+        ("ntoskrnl.exe", "../../../outside"),
+        ("ntoskrnl.exe", outside.to_str().unwrap()),
+        ("ntoskrnl.exe/..", "5F4E3A2B3000"),
+        ("./ntoskrnl.exe", "5F4E3A2B3000"),
+        ("ntoskrnl.exe/", "5F4E3A2B3000"),
+        ("", "5F4E3A2B3000"),
+        ("ntoskrnl.exe", "."),
+    ] {
+        let result = cache.entry_from_exports(module_name, version_key, "Amd64", symbols());
+        assert!(
+            matches!(result, Err(Error::InvalidPathComponent(_))),
+            "{module_name:?} {version_key:?} accepted"
+        );
+    }
+
+    assert!(!outside.exists());
+    assert!(!cache_path.join("windows").exists());
+}
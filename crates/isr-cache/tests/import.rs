@@ -0,0 +1,115 @@
+#![cfg(all(feature = "isf", feature = "unstable"))]
+
+mod common;
+
+use std::path::Path;
+
+use isr_cache::{
+    unstable::{ImportReport, OnConflict},
+    CodeView, IsrCache, JsonCodec,
+};
+use isr_core::ProfileSource;
+
+use self::common::{
+    isf::{self, DATABASE},
+    ubuntu::{BANNER, ENTRY},
+    Server,
+};
+
+/// Writes the ISF files to import into `directory`.
+fn write_isf_files(directory: &Path) {
+    std::fs::create_dir_all(directory.join("windows")).unwrap();
+    std::fs::create_dir_all(directory.join("linux")).unwrap();
+
+    let files: [(&str, Vec<u8>); 5] = [
+        ("windows/ntkrnlmp.json", isf::windows()),
+        ("linux/ubuntu.json.xz", isf::xz(&isf::linux(BANNER))),
+        (
+            "linux/unknown.json",
+            br#"{"metadata":{"format":"6.2.0"}}"#.to_vec(),
+        ),
+        ("linux/broken.json", b"{".to_vec()),
+        ("linux/README.md", b"ignored".to_vec()),
+    ];
+
+    for (path, data) in files {
+        std::fs::write(directory.join(path), data).unwrap();
+    }
+}
+
+/// Returns the file names of the converted, skipped, unidentified and
+/// failed files.
+fn file_names(report: &ImportReport) -> [Vec<String>; 4] {
+    let name = |path: &Path| path.file_name().unwrap().to_string_lossy().into_owned();
+
+    [
+        report
+            .converted
+            .iter()
+            .map(|(path, _)| name(path))
+            .collect(),
+        report.skipped.iter().map(|(path, _)| name(path)).collect(),
+        report.unidentified.iter().map(|path| name(path)).collect(),
+        report.failed.iter().map(|(path, _)| name(path)).collect(),
+    ]
+}
+
+#[test]
+fn import_isf_directory() {
+    let directory = tempfile::tempdir().unwrap();
+    let isf_directory = directory.path().join("isf");
+    write_isf_files(&isf_directory);
+
+    // Nothing is downloaded.
+    let server = Server::new(|_| (404, Vec::new()));
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_symbol_servers([server.url.as_str()]);
+
+    let report = cache
+        .import_isf_directory(&isf_directory, OnConflict::Skip)
+        .unwrap();
+    assert_eq!(
+        file_names(&report),
+        [
+            vec!["ubuntu.json.xz", "ntkrnlmp.json"],
+            vec![],
+            vec!["unknown.json"],
+            vec!["broken.json"],
+        ]
+    );
+    assert_eq!(
+        report.converted[0].1,
+        directory
+            .path()
+            .join("cache")
+            .join(ENTRY)
+            .join("profile.json")
+    );
+
+    // The imported entries are found without downloading anything.
+    let codeview = CodeView::new(DATABASE, "3844dbb920174967be7aa4a2c20430fa2").unwrap();
+    let entry = cache.entry_from_codeview(codeview).unwrap();
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.source(), Some(ProfileSource::Isf));
+    assert!(profile.find_struct("_EPROCESS").is_some());
+
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.source(), Some(ProfileSource::Isf));
+    assert!(profile.find_struct("task_struct").is_some());
+    assert_eq!(server.requests(), 0);
+
+    // Existing profiles are kept or replaced.
+    let report = cache
+        .import_isf_directory(&isf_directory, OnConflict::Skip)
+        .unwrap();
+    assert_eq!(report.converted.len(), 0);
+    assert_eq!(report.skipped.len(), 2);
+
+    let report = cache
+        .import_isf_directory(&isf_directory, OnConflict::Overwrite)
+        .unwrap();
+    assert_eq!(report.converted.len(), 2);
+    assert_eq!(report.skipped.len(), 0);
+}
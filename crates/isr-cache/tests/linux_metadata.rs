@@ -0,0 +1,41 @@
+#![cfg(all(feature = "linux", feature = "unstable"))]
+
+mod common;
+
+use isr_cache::JsonCodec;
+
+use self::common::{
+    kernel::Kernel,
+    ubuntu::{cache, packages, serve, BANNER, DBGSYM, DIST, ENTRY, IMAGE, MODULES, VERSION},
+};
+
+#[test]
+fn package_provenance() {
+    let kernel = Kernel::new(2);
+    let packages = packages(&kernel);
+    let (_repository, server) = serve(&packages);
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = cache::<JsonCodec>(&server, directory.path());
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    assert!(directory.path().join(ENTRY).join("metadata.json").exists());
+
+    let metadata = entry.metadata().unwrap();
+    assert_eq!(metadata.linux_banner.as_deref(), Some(BANNER));
+
+    let recorded = metadata.linux_packages.packages().collect::<Vec<_>>();
+    assert_eq!(recorded.len(), 3);
+    for ((found, expected), name) in recorded.iter().zip(&packages).zip([IMAGE, DBGSYM, MODULES]) {
+        assert_eq!(found.package, name);
+        assert_eq!(found.version, VERSION);
+        assert_eq!(found.dist, DIST);
+        assert_eq!(found.url.path(), format!("/{}", expected.filename(DIST)));
+        assert_eq!(found.size, Some(expected.data.len()));
+        assert_eq!(found.sha256.as_deref(), Some(&*expected.sha256()));
+    }
+
+    // The metadata is kept when the entry is opened again.
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    let metadata = entry.metadata().unwrap();
+    assert_eq!(metadata.linux_packages.packages().count(), 3);
+}
@@ -0,0 +1,194 @@
+#![cfg(feature = "pdb")]
+
+mod common;
+
+use std::{borrow::Cow, path::Path};
+
+use isr_cache::{Codec, DebugId, IsrCache, JsonCodec, Manifest, ManifestEntry, PeKey};
+use isr_core::{types::Types, Profile, Symbols};
+
+use self::common::{
+    pe::{pe_with_exports, TIMESTAMP},
+    Server,
+};
+
+const EXPORTS: &[(&str, u32)] = &[("KeBugCheckEx", 0x1010), ("PsActiveProcessHead", 0x1020)];
+
+const GUID: &str = "3844dbb920174967be7aa4a2c20430fa2";
+
+#[cfg(feature = "linux")]
+const BANNER: &str = "Linux version 6.8.0-40-generic (buildd@lcy02-amd64-078) \
+    (x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~22.04) 12.3.0, GNU ld (GNU Binutils for Ubuntu) 2.38) \
+    #40~22.04.3-Ubuntu SMP PREEMPT_DYNAMIC Tue Jul 30 17:30:19 UTC 2 \
+    (Ubuntu 6.8.0-40.40~22.04.3-generic 6.8.12)";
+
+/// Writes a profile, as if the entry had been created before.
+fn write_profile(entry_path: &Path) {
+    let symbols = [(Cow::Borrowed("PsInitialSystemProcess"), 0x57_33a0)]
+        .into_iter()
+        .collect();
+    let profile = Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default());
+
+    std::fs::create_dir_all(entry_path).unwrap();
+    let mut data = Vec::new();
+    JsonCodec::encode(&mut data, &profile).unwrap();
+    std::fs::write(entry_path.join("profile.json"), data).unwrap();
+}
+
+fn pe_key() -> PeKey {
+    PeKey {
+        path: String::from("ntoskrnl.exe"),
+        key: format!("{TIMESTAMP:08X}3000"),
+    }
+}
+
+/// Serves the executable indexed under [`pe_key`].
+fn server() -> Server {
+    let pe_key = pe_key();
+    let path = format!("/{}/{}/{}", pe_key.path, pe_key.key, pe_key.path);
+    Server::new(move |request| match request == path {
+        true => (200, pe_with_exports(EXPORTS, 0x1000, 0x200)),
+        false => (404, Vec::new()),
+    })
+}
+
+#[test]
+fn export_and_prefetch() {
+    let server = server();
+    let directory = tempfile::tempdir().unwrap();
+
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("a"))
+        .unwrap()
+        .with_symbol_servers([&server.url]);
+
+    let entry = cache.entry_from_debug_id(DebugId::PeKey(pe_key())).unwrap();
+    let data = entry.data().to_vec();
+
+    // A PDB entry created before, and an entry created from exports,
+    // which can't be created elsewhere.
+    write_profile(&directory.path().join("a/windows/ntkrnlmp.pdb").join(GUID));
+    write_profile(&directory.path().join("a/windows/hal.dll/10.0.19041.1"));
+
+    let manifest = cache.export_manifest().unwrap();
+    assert_eq!(manifest.isr_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest.entries.len(), 2, "{:?}", manifest.entries);
+    assert!(matches!(
+        &manifest.entries[0],
+        ManifestEntry::CodeView { path, guid } if path == "ntkrnlmp.pdb" && guid == GUID
+    ));
+    assert!(matches!(
+        &manifest.entries[1],
+        ManifestEntry::PeKey { path, key } if *path == pe_key().path && *key == pe_key().key
+    ));
+
+    // The manifest goes to another machine.
+    let manifest_path = directory.path().join("manifest.json");
+    manifest.store(&manifest_path).unwrap();
+    let manifest = Manifest::load(&manifest_path).unwrap();
+
+    let other = IsrCache::<JsonCodec>::new(directory.path().join("b"))
+        .unwrap()
+        .with_symbol_servers([&server.url]);
+
+    let requests = server.requests();
+    let summary = other.prefetch(&manifest);
+    assert!(server.requests() > requests);
+
+    // The server doesn't have the PDB file.
+    assert_eq!(summary.failures.len(), 1);
+    assert!(matches!(
+        summary.failures[0].0,
+        ManifestEntry::CodeView { .. }
+    ));
+
+    assert_eq!(summary.entries.len(), 1);
+    let (manifest_entry, entry) = &summary.entries[0];
+    assert!(matches!(manifest_entry, ManifestEntry::PeKey { .. }));
+    assert_eq!(entry.data(), data);
+    assert!(entry.profile_path().starts_with(directory.path().join("b")));
+
+    // Both entries exist already in the original cache.
+    let requests = server.requests();
+    let summary = cache.prefetch(&manifest);
+    assert!(summary.is_success());
+    assert_eq!(summary.entries.len(), 2);
+    assert_eq!(server.requests(), requests);
+}
+
+#[test]
+fn export_empty_cache() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    let manifest = cache.export_manifest().unwrap();
+    assert!(manifest.entries.is_empty());
+    assert!(cache.prefetch(&manifest).is_success());
+}
+
+#[test]
+fn export_skips_entries_without_profile() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    // Created with other profile options.
+    let entry_path = directory.path().join("windows/ntkrnlmp.pdb").join(GUID);
+    std::fs::create_dir_all(&entry_path).unwrap();
+    std::fs::write(entry_path.join("profile-exported.json"), b"{}").unwrap();
+
+    assert!(cache.export_manifest().unwrap().entries.is_empty());
+}
+
+#[test]
+fn prefetch_rejects_paths() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache")).unwrap();
+
+    let manifest = Manifest {
+        isr_version: String::new(),
+        entries: vec![
+            ManifestEntry::PeKey {
+                path: String::from("../escape.exe"),
+                key: pe_key().key,
+            },
+            ManifestEntry::PeKey {
+                path: pe_key().path,
+                key: String::from("/tmp"),
+            },
+        ],
+    };
+
+    let summary = cache.prefetch(&manifest);
+    assert_eq!(summary.failures.len(), 2);
+    for (_, err) in &summary.failures {
+        assert!(matches!(err, isr_cache::Error::InvalidPathComponent(_)));
+    }
+}
+
+#[cfg(feature = "linux")]
+#[test]
+fn export_linux_entry() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    // An entry with a recorded banner, and one imported without it.
+    let entry_path = directory.path().join("ubuntu/6.8.0-40.40~22.04.3-generic");
+    write_profile(&entry_path);
+    let metadata = serde_json::json!({ "linux_banner": BANNER });
+    std::fs::write(entry_path.join("metadata.json"), metadata.to_string()).unwrap();
+    write_profile(&directory.path().join("ubuntu/5.15.0-25.25-generic"));
+
+    let manifest = cache.export_manifest().unwrap();
+    assert_eq!(manifest.entries.len(), 1);
+    assert!(matches!(
+        &manifest.entries[0],
+        ManifestEntry::Linux { banner, packages } if banner == BANNER && packages.is_empty()
+    ));
+
+    // The profile exists, so nothing is downloaded.
+    let summary = cache.prefetch(&manifest);
+    assert!(summary.is_success());
+    assert_eq!(
+        summary.entries[0].1.profile_path(),
+        entry_path.join("profile.json")
+    );
+}
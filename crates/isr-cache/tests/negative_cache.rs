@@ -0,0 +1,63 @@
+#![cfg(feature = "pdb")]
+
+mod common;
+
+use std::time::Duration;
+
+use isr_cache::{CodeView, Error, IsrCache, JsonCodec};
+
+use self::common::Server;
+
+fn codeview() -> CodeView {
+    CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471").unwrap()
+}
+
+#[test]
+fn not_found_short_circuits() {
+    let server = Server::new(|_| (404, b"not found".to_vec()));
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_symbol_servers([&server.url])
+        .with_negative_cache(Duration::from_secs(3600));
+
+    let result = cache.entry_from_codeview(codeview());
+    assert!(matches!(
+        result,
+        Err(Error::PdbDownloader(isr_dl_pdb::Error::NotFound))
+    ));
+    let requests = server.requests();
+    assert!(requests > 0);
+
+    let result = cache.entry_from_codeview(codeview());
+    assert!(matches!(result, Err(Error::NotFoundCached)));
+    assert_eq!(server.requests(), requests);
+
+    assert_eq!(cache.clear_negative_cache().unwrap(), 1);
+    let result = cache.entry_from_codeview(codeview());
+    assert!(matches!(
+        result,
+        Err(Error::PdbDownloader(isr_dl_pdb::Error::NotFound))
+    ));
+    assert!(server.requests() > requests);
+}
+
+#[test]
+fn server_error_is_not_cached() {
+    let server = Server::new(|_| (500, b"internal error".to_vec()));
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_symbol_servers([&server.url])
+        .with_negative_cache(Duration::from_secs(3600));
+
+    for _ in 0..2 {
+        let result = cache.entry_from_codeview(codeview());
+        assert!(matches!(
+            result,
+            Err(Error::PdbDownloader(isr_dl_pdb::Error::Failed))
+        ));
+    }
+
+    assert_eq!(cache.clear_negative_cache().unwrap(), 0);
+}
@@ -0,0 +1,81 @@
+#![cfg(all(feature = "pdb", feature = "dwarf"))]
+
+mod common;
+
+use isr_cache::{IsrCache, JsonCodec, ProfileSource};
+use isr_core::SymbolBase;
+use object::{
+    read::pe::{ImageNtHeaders as _, ImageOptionalHeader as _, PeFile64},
+    Object as _, ObjectSection as _,
+};
+
+use self::common::{
+    kernel::Kernel,
+    pe::{pe_with_dwarf, pe_with_exports, IMAGE_BASE, TIMESTAMP},
+    Server,
+};
+
+/// Builds a PE file with the DWARF sections of the kernel fixture.
+fn pe() -> Vec<u8> {
+    let kernel = Kernel::new(1);
+    let object = object::File::parse(&*kernel.image).unwrap();
+    let sections = object
+        .sections()
+        .filter(|section| section.name().unwrap().starts_with(".debug_"))
+        .map(|section| (section.name().unwrap(), section.data().unwrap()))
+        .collect::<Vec<_>>();
+
+    pe_with_dwarf(
+        &sections,
+        &[("KiSystemStartup", 0x10), ("KeBugCheckEx", 0x80)],
+    )
+}
+
+#[test]
+fn has_debug_info() {
+    assert!(isr_dwarf::has_debug_info(&pe()));
+    assert!(!isr_dwarf::has_debug_info(&pe_with_exports(
+        &[],
+        0x200,
+        0x200
+    )));
+}
+
+#[test]
+fn entry_from_pe() {
+    let directory = tempfile::tempdir().unwrap();
+    let pe_path = directory.path().join("example.exe");
+    let pe = pe();
+    std::fs::write(&pe_path, &pe).unwrap();
+
+    // Nothing is downloaded.
+    let server = Server::new(|_| (404, Vec::new()));
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_symbol_servers([&server.url]);
+
+    let entry = cache.entry_from_pe(&pe_path).unwrap();
+    assert_eq!(server.requests(), 0);
+
+    // <TimeDateStamp><SizeOfImage>
+    let pe = PeFile64::parse(&*pe).unwrap();
+    let size_of_image = pe.nt_headers().optional_header().size_of_image();
+    let image_id = format!("{TIMESTAMP:08X}{size_of_image:x}");
+    let entry_path = directory
+        .path()
+        .join("cache/windows/example.exe")
+        .join(image_id);
+    assert_eq!(entry.profile_path(), entry_path.join("profile.json"));
+    assert_eq!(entry.source_path(), Some(&*pe_path));
+
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.architecture(), "Amd64");
+    assert_eq!(profile.source(), Some(ProfileSource::Dwarf));
+    assert_eq!(profile.symbol_base(), SymbolBase::Rva);
+
+    // The symbols are RVAs.
+    let text = pe.section_by_name(".text").unwrap().address() - IMAGE_BASE;
+    assert_eq!(profile.find_symbol("KiSystemStartup"), Some(text + 0x10));
+    assert_eq!(profile.find_symbol("KeBugCheckEx"), Some(text + 0x80));
+    assert!(profile.find_struct("task_struct").is_some());
+}
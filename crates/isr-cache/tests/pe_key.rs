@@ -0,0 +1,72 @@
+#![cfg(feature = "pdb")]
+
+mod common;
+
+use isr_cache::{DebugId, IsrCache, JsonCodec, PeKey, ProfileSource};
+
+use self::common::{
+    pe::{pe_with_exports, IMAGE_BASE, TIMESTAMP},
+    Server,
+};
+
+const EXPORTS: &[(&str, u32)] = &[("KeBugCheckEx", 0x1010), ("PsActiveProcessHead", 0x1020)];
+
+#[test]
+fn entry_from_pe_without_codeview() {
+    let directory = tempfile::tempdir().unwrap();
+    let pe_path = directory.path().join("ntoskrnl.exe");
+    std::fs::write(&pe_path, pe_with_exports(EXPORTS, 0x1000, 0x200)).unwrap();
+
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache")).unwrap();
+    let entry = cache.entry_from_pe(&pe_path).unwrap();
+
+    let key = format!("{TIMESTAMP:08X}3000");
+    let entry_path = directory
+        .path()
+        .join("cache/windows/ntoskrnl.exe")
+        .join(&key);
+    assert_eq!(entry.profile_path(), entry_path.join("profile.json"));
+    assert_eq!(entry.source_path(), Some(&*entry_path.join("ntoskrnl.exe")));
+
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.source(), Some(ProfileSource::Exports));
+    assert_eq!(profile.architecture(), "Amd64");
+    assert_eq!(profile.preferred_base(), Some(IMAGE_BASE));
+    assert_eq!(profile.find_symbol("PsActiveProcessHead"), Some(0x1020));
+    assert_eq!(profile.find_symbol("KeBugCheckEx"), Some(0x1010));
+
+    // Retrieved again without re-reading the executable.
+    std::fs::remove_file(&pe_path).unwrap();
+    let pe_key = PeKey {
+        path: String::from("ntoskrnl.exe"),
+        key,
+    };
+    let entry = cache.entry_from_debug_id(DebugId::PeKey(pe_key)).unwrap();
+    assert_eq!(entry.profile_path(), entry_path.join("profile.json"));
+}
+
+#[test]
+fn executable_downloaded_by_pe_key() {
+    let key = format!("{TIMESTAMP:08X}3000");
+    let path = format!("/ntoskrnl.exe/{key}/ntoskrnl.exe");
+    let server = Server::new(move |request| match request == path {
+        true => (200, pe_with_exports(EXPORTS, 0x1000, 0x200)),
+        false => (404, Vec::new()),
+    });
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_symbol_servers([&server.url]);
+
+    let pe_key = PeKey {
+        path: String::from("ntoskrnl.exe"),
+        key,
+    };
+    let entry = cache.entry_from_debug_id(DebugId::PeKey(pe_key)).unwrap();
+
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.source(), Some(ProfileSource::Exports));
+    assert_eq!(profile.preferred_base(), Some(IMAGE_BASE));
+    assert_eq!(profile.find_symbol("PsActiveProcessHead"), Some(0x1020));
+}
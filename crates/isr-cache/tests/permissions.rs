@@ -0,0 +1,79 @@
+#![cfg(all(unix, feature = "pdb"))]
+
+use std::{fs::Permissions, os::unix::fs::PermissionsExt as _, path::Path, time::Duration};
+
+use isr_cache::{CachePermissions, IsrCache, JsonCodec};
+use isr_core::Symbols;
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o7777
+}
+
+fn symbols() -> Symbols<'static> {
+    let mut symbols = Symbols::default();
+    symbols.0.insert("PsActiveProcessHead".into(), 0x1000);
+    symbols
+}
+
+#[test]
+fn created_files_and_directories() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_permissions(CachePermissions::new(0o2770, 0o640));
+
+    let entry = cache
+        .entry_from_exports("ntoskrnl.exe", "5f4e3a2b1000", "Amd64", symbols())
+        .unwrap();
+
+    let profile_path = entry.profile_path();
+    assert_eq!(mode(profile_path), 0o640);
+
+    // Every directory created below the cache directory.
+    let mut path = profile_path.parent().unwrap();
+    while path != directory.path() {
+        assert_eq!(mode(path), 0o2770, "{}", path.display());
+        path = path.parent().unwrap();
+    }
+}
+
+#[test]
+fn existing_files_keep_their_mode() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_permissions(CachePermissions::new(0o2770, 0o640))
+        .with_negative_cache(Duration::from_secs(3600));
+
+    let entry = cache
+        .entry_from_exports("ntoskrnl.exe", "5f4e3a2b1000", "Amd64", symbols())
+        .unwrap();
+
+    let profile_path = entry.profile_path().to_owned();
+    let entry_path = profile_path.parent().unwrap();
+    std::fs::set_permissions(&profile_path, Permissions::from_mode(0o600)).unwrap();
+    std::fs::set_permissions(entry_path, Permissions::from_mode(0o750)).unwrap();
+
+    // A cache hit doesn't touch the permissions.
+    let entry = cache
+        .entry_from_exports("ntoskrnl.exe", "5f4e3a2b1000", "Amd64", symbols())
+        .unwrap();
+
+    assert_eq!(entry.profile_path(), profile_path);
+    assert_eq!(mode(&profile_path), 0o600);
+    assert_eq!(mode(entry_path), 0o750);
+}
+
+#[test]
+fn default_permissions() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    let entry = cache
+        .entry_from_exports("ntoskrnl.exe", "5f4e3a2b1000", "Amd64", symbols())
+        .unwrap();
+
+    // Subject to the umask.
+    assert_eq!(mode(entry.profile_path()) & 0o7000, 0);
+    assert_eq!(mode(entry.profile_path().parent().unwrap()) & 0o7000, 0);
+}
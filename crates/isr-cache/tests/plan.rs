@@ -0,0 +1,140 @@
+mod common;
+
+#[cfg(feature = "pdb")]
+#[test]
+fn codeview_plan() {
+    use isr_cache::{CodeView, IsrCache, JsonCodec};
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_symbol_servers(["http://first.test", "http://second.test"]);
+
+    let codeview = CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471").unwrap();
+    let plan = cache.plan_for_codeview(&codeview);
+
+    // Each server is tried with the plain and the compressed file name.
+    let urls = plan.urls.iter().map(|url| url.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            "http://first.test/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471/ntkrnlmp.pdb",
+            "http://first.test/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471/ntkrnlmp.pd_",
+            "http://second.test/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471/ntkrnlmp.pdb",
+            "http://second.test/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471/ntkrnlmp.pd_",
+        ]
+    );
+
+    let entry_path = directory
+        .path()
+        .join("windows/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471");
+    assert_eq!(plan.destination_path, entry_path);
+    assert_eq!(plan.pdb_path, entry_path.join("ntkrnlmp.pdb"));
+    assert_eq!(plan.profile_path, entry_path.join("profile.json"));
+    assert_eq!(cache.windows_entry_dir(&codeview), entry_path);
+
+    // Nothing was created.
+    assert!(!directory.path().join("windows").exists());
+}
+
+#[cfg(feature = "pdb")]
+#[test]
+fn symstore_plan() {
+    use isr_cache::{CodeView, IsrCache, JsonCodec, WindowsLayout};
+
+    let directory = tempfile::tempdir().unwrap();
+    let symstore = directory.path().join("symbols");
+    let cache = IsrCache::<JsonCodec>::new(directory.path().join("cache"))
+        .unwrap()
+        .with_windows_layout(WindowsLayout::SymStore {
+            root: symstore.clone(),
+        });
+
+    let codeview = CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471").unwrap();
+    let plan = cache.plan_for_codeview(&codeview);
+
+    // The PDB file is in the symbol store, with an uppercase GUID.
+    assert_eq!(
+        plan.pdb_path,
+        symstore.join("ntkrnlmp.pdb/CE7FFB00C20B87500211456B3E905C471/ntkrnlmp.pdb")
+    );
+
+    // The profile stays in the cache.
+    let entry_path = directory
+        .path()
+        .join("cache/windows/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471");
+    assert_eq!(plan.destination_path, entry_path);
+    assert_eq!(plan.profile_path, entry_path.join("profile.json"));
+    assert!(!symstore.exists());
+
+    // A PDB file placed by another tool is found.
+    std::fs::create_dir_all(plan.pdb_path.parent().unwrap()).unwrap();
+    std::fs::write(&plan.pdb_path, b"PDB").unwrap();
+    let estimate = cache.estimate_windows_download(&codeview).unwrap();
+    assert_eq!(estimate.already_cached_bytes, 3);
+}
+
+#[cfg(feature = "linux")]
+#[test]
+fn banner_plan() {
+    use isr_cache::{JsonCodec, LinuxBanner, LinuxVersionSignature};
+
+    use self::common::{
+        kernel::Kernel,
+        ubuntu::{cache, packages, serve, BANNER, DBGSYM, DIST, ENTRY, IMAGE, MODULES, VERSION},
+    };
+
+    let kernel = Kernel::new(2);
+    let packages = packages(&kernel);
+    let (repository, server) = serve(&packages);
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = cache::<JsonCodec>(&server, directory.path());
+    let plan = cache.plan_for_banner(BANNER).unwrap();
+
+    let resolved = plan
+        .packages
+        .packages()
+        .map(|package| {
+            assert_eq!(package.dist, DIST);
+            assert_eq!(package.version, VERSION);
+            assert_eq!(package.substituted_for, None);
+            (package.package.as_str(), package.url.path())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        resolved,
+        [
+            (IMAGE, &*format!("/{}", packages[0].filename(DIST))),
+            (DBGSYM, &*format!("/{}", packages[1].filename(DIST))),
+            (MODULES, &*format!("/{}", packages[2].filename(DIST))),
+        ]
+    );
+
+    let sizes = packages.iter().map(|package| package.data.len() as u64);
+    assert_eq!(plan.download_size(), sizes.sum::<u64>());
+
+    let entry_path = directory.path().join(ENTRY);
+    assert_eq!(plan.destination_path, entry_path);
+
+    let version_signature = match LinuxBanner::parse(BANNER).unwrap().version_signature {
+        Some(LinuxVersionSignature::Ubuntu(version_signature)) => version_signature,
+        _ => panic!("not an Ubuntu banner"),
+    };
+    assert_eq!(cache.linux_entry_dir(&version_signature), entry_path);
+    assert_eq!(
+        plan.linux_image_deb_path,
+        entry_path.join("linux-image.deb")
+    );
+    assert_eq!(plan.linux_image_path, entry_path.join("vmlinuz"));
+    assert_eq!(
+        plan.linux_image_dbgsym_path,
+        entry_path.join("vmlinux-dbgsym")
+    );
+    assert_eq!(plan.systemmap_path, entry_path.join("System.map"));
+    assert_eq!(plan.profile_path, entry_path.join("profile.json"));
+
+    // Only the indexes were fetched.
+    assert!(repository.downloads().is_empty());
+    assert!(!entry_path.exists());
+}
@@ -0,0 +1,266 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use isr_cache::{Codec, IsrCache, JsonCodec};
+use isr_core::{types::Types, Profile, Symbols};
+
+const GUID: &str = "3844dbb920174967be7aa4a2c20430fa2";
+
+const CORRUPT_GUID: &str = "1b72224d37b8179228200ed8994498b21";
+
+#[cfg(feature = "linux")]
+const VERSION_SIGNATURE: &str = "6.8.0-40.40~22.04.3-generic";
+
+/// Encodes a profile with a single symbol.
+fn encoded_profile() -> Vec<u8> {
+    let symbols = [(Cow::Borrowed("PsInitialSystemProcess"), 0x57_33a0)]
+        .into_iter()
+        .collect();
+    let profile = Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default());
+
+    let mut data = Vec::new();
+    JsonCodec::encode(&mut data, &profile).unwrap();
+    data
+}
+
+fn write(path: &Path, data: impl AsRef<[u8]>) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, data).unwrap();
+}
+
+/// Sets the modification time of `path` to two hours ago.
+fn age(path: &Path) {
+    let modified = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+    File::open(path).unwrap().set_modified(modified).unwrap();
+}
+
+/// Paths of a messed-up cache.
+struct Cache {
+    profile: PathBuf,
+    corrupt_profile: PathBuf,
+    object: PathBuf,
+    corrupt_object: PathBuf,
+    partial_file: PathBuf,
+    dedup_file: PathBuf,
+    empty_directory: PathBuf,
+    #[cfg(feature = "linux")]
+    linux: PathBuf,
+}
+
+impl Cache {
+    /// Creates the cache: a good profile (deduplicated), a corrupt profile
+    /// without the PDB file, a corrupt object, leftovers of interrupted
+    /// operations and an empty directory. With Linux, an entry with
+    /// a corrupt symbols-only profile and a truncated package as well.
+    fn new(root: &Path) -> Self {
+        let cache = Self {
+            profile: root
+                .join("windows/ntkrnlmp.pdb")
+                .join(GUID)
+                .join("profile.json"),
+            corrupt_profile: root
+                .join("windows/ntkrnlmp.pdb")
+                .join(CORRUPT_GUID)
+                .join("profile.json"),
+            object: root.join("objects/00000000000000aa.json"),
+            corrupt_object: root.join("objects/00000000000000bb.json"),
+            partial_file: root
+                .join("windows/ntkrnlmp.pdb")
+                .join(GUID)
+                .join("ntkrnlmp.pdb.partial"),
+            dedup_file: root.join("windows/hal.dll/10.0.19041.1/profile.dedup"),
+            empty_directory: root.join("windows/ntoskrnl.exe/5C8FB1E7a5f000"),
+            #[cfg(feature = "linux")]
+            linux: root.join("ubuntu").join(VERSION_SIGNATURE),
+        };
+
+        write(&cache.profile, encoded_profile());
+        write(&cache.corrupt_profile, br#"{"architecture": "Amd64", "sym"#);
+        std::fs::create_dir_all(cache.object.parent().unwrap()).unwrap();
+        std::fs::hard_link(&cache.profile, &cache.object).unwrap();
+        write(&cache.corrupt_object, b"\0\0\0\0");
+        write(&cache.partial_file, b"MSF");
+        write(&cache.dedup_file, b"{");
+        std::fs::create_dir_all(&cache.empty_directory).unwrap();
+
+        #[cfg(feature = "linux")]
+        {
+            let mut systemmap = String::from("ffffffff81000000 T _text\n");
+            for index in 0..1_200 {
+                let address = 0xffff_ffff_8110_0000_u64 + index * 0x40;
+                systemmap += &format!("{address:016x} t filler_{index}\n");
+            }
+            write(&cache.linux.join("System.map"), systemmap);
+            write(&cache.linux.join("profile-symbols.json"), b"[]");
+
+            let package = |package: &str, size: usize| {
+                serde_json::json!({
+                    "dist": "jammy-updates",
+                    "package": package,
+                    "version": "6.8.0-40.40~22.04.3",
+                    "url": format!("http://archive.ubuntu.com/ubuntu/pool/{package}.deb"),
+                    "size": size,
+                    "sha256": null,
+                    "substituted_for": null,
+                })
+            };
+            let metadata = serde_json::json!({
+                "linux_packages": {
+                    "linux_image": package("linux-image-6.8.0-40-generic", 1_000),
+                    "linux_image_dbgsym": null,
+                    "linux_modules": package("linux-modules-6.8.0-40-generic", 10),
+                },
+            });
+            write(&cache.linux.join("metadata.json"), metadata.to_string());
+            write(&cache.linux.join("linux-image.deb"), [0; 100]);
+            write(&cache.linux.join("linux-modules.deb"), [0; 10]);
+        }
+
+        cache
+    }
+}
+
+#[test]
+#[cfg(feature = "unstable")]
+fn scan_messed_up_cache() {
+    let directory = tempfile::tempdir().unwrap();
+    let paths = Cache::new(directory.path());
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    let report = cache.scan().unwrap();
+    assert!(!report.is_clean());
+    assert_eq!(report.profiles.as_slice(), [paths.profile.as_path()]);
+
+    let corrupt_profiles = report
+        .corrupt_profiles
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    #[cfg(feature = "linux")]
+    assert_eq!(corrupt_profiles.len(), 2);
+    assert!(corrupt_profiles.contains(&paths.corrupt_profile));
+
+    let corrupt_objects = report
+        .corrupt_objects
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(corrupt_objects.as_slice(), [paths.corrupt_object.as_path()]);
+
+    let mut orphans = report.orphans.clone();
+    orphans.sort();
+    let mut expected = vec![paths.dedup_file, paths.empty_directory, paths.partial_file];
+    expected.sort();
+    assert_eq!(orphans, expected);
+
+    #[cfg(feature = "linux")]
+    assert_eq!(
+        report.partial_downloads,
+        [paths.linux.join("linux-image.deb")]
+    );
+
+    // Nothing was modified.
+    assert!(paths.corrupt_profile.exists());
+    assert!(paths.corrupt_object.exists());
+}
+
+#[test]
+fn repair_messed_up_cache() {
+    let directory = tempfile::tempdir().unwrap();
+    let paths = Cache::new(directory.path());
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_repair_grace_period(Duration::ZERO);
+
+    let summary = cache.repair().unwrap();
+    assert!(summary.skipped.is_empty(), "{:?}", summary.skipped);
+    #[cfg(feature = "unstable")]
+    assert!(cache.scan().unwrap().is_clean());
+
+    // The corrupt profile can't be created again without the PDB file,
+    // its entry is removed.
+    let entry = paths.corrupt_profile.parent().unwrap();
+    for path in [
+        &paths.corrupt_profile,
+        &paths.corrupt_object,
+        &paths.partial_file,
+        &paths.dedup_file,
+        &paths.empty_directory,
+    ] {
+        assert!(summary.removed.contains(path), "{path:?}");
+        assert!(!path.exists(), "{path:?}");
+    }
+    assert!(summary.removed.contains(&entry.to_owned()));
+    assert!(!entry.exists());
+
+    // Emptied by the removal of its only file.
+    let hal = paths.dedup_file.parent().unwrap();
+    assert!(summary.removed.contains(&hal.to_owned()));
+    assert!(!directory.path().join("windows/hal.dll").exists());
+
+    // The good profile and its object are left alone.
+    assert_eq!(std::fs::read(&paths.profile).unwrap(), encoded_profile());
+    assert!(paths.object.exists());
+
+    #[cfg(feature = "linux")]
+    {
+        let profile_path = paths.linux.join("profile-symbols.json");
+        assert_eq!(summary.regenerated.as_slice(), [profile_path.as_path()]);
+
+        let data = std::fs::read(&profile_path).unwrap();
+        let profile = JsonCodec::decode(&data).unwrap();
+        assert_eq!(profile.find_symbol("_text"), Some(0xffff_ffff_8100_0000));
+
+        assert!(summary
+            .removed
+            .contains(&paths.linux.join("linux-image.deb")));
+        assert!(paths.linux.join("linux-modules.deb").exists());
+    }
+
+    // Nothing left to repair.
+    let summary = cache.repair().unwrap();
+    assert!(summary.removed.is_empty());
+    assert!(summary.regenerated.is_empty());
+}
+
+#[test]
+fn repair_skips_recent_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let paths = Cache::new(directory.path());
+    let cache = IsrCache::<JsonCodec>::new(directory.path()).unwrap();
+
+    // Everything was just written, possibly by another process.
+    let summary = cache.repair().unwrap();
+    assert!(summary.removed.is_empty());
+    assert!(summary.regenerated.is_empty());
+    #[cfg(feature = "unstable")]
+    {
+        let report = cache.scan().unwrap();
+        assert_eq!(
+            summary.skipped.len(),
+            report.corrupt_profiles.len()
+                + report.corrupt_objects.len()
+                + report.partial_downloads.len()
+                + report.orphans.len()
+        );
+    }
+
+    assert!(paths.corrupt_profile.exists());
+    assert!(paths.partial_file.exists());
+
+    // Left by an interrupted operation a while ago.
+    age(&paths.partial_file);
+    age(&paths.corrupt_profile);
+
+    let summary = cache.repair().unwrap();
+    assert!(summary.removed.contains(&paths.partial_file));
+    assert!(summary.removed.contains(&paths.corrupt_profile));
+    assert!(!summary.skipped.contains(&paths.partial_file));
+    assert!(summary.skipped.contains(&paths.corrupt_object));
+    assert!(paths.corrupt_object.exists());
+    assert!(paths.dedup_file.exists());
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "linux")]
+
+mod common;
+
+use isr_cache::{JsonCodec, ProfileSource};
+
+use self::common::{
+    kernel::{Kernel, SYMBOLS},
+    ubuntu::{cache, packages, serve, BANNER, DIST, ENTRY},
+};
+
+#[test]
+fn dbgsym_published_later() {
+    let kernel = Kernel::new(2);
+    let [image, dbgsym, modules] = packages(&kernel);
+
+    // Only the System.map is available at first.
+    let (repository, server) = serve(&[image, modules]);
+    let directory = tempfile::tempdir().unwrap();
+    let cache = cache::<JsonCodec>(&server, directory.path());
+    let entry_path = directory.path().join(ENTRY);
+
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    assert_eq!(
+        entry.profile_path(),
+        entry_path.join("profile-symbols.json")
+    );
+    assert_eq!(entry.source_path(), Some(&*entry_path.join("System.map")));
+    assert_eq!(repository.downloads().len(), 2);
+
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.source(), Some(ProfileSource::Dwarf));
+    let (name, _, address) = SYMBOLS[2];
+    assert_eq!(profile.find_symbol(name), Some(address));
+    assert!(profile.find_struct("task_struct").is_none());
+
+    // The symbols-only profile is kept while the dbgsym package is
+    // missing.
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    assert_eq!(
+        entry.profile_path(),
+        entry_path.join("profile-symbols.json")
+    );
+    assert_eq!(repository.downloads().len(), 2);
+
+    // Only the dbgsym package is downloaded, and the full profile
+    // replaces the symbols-only one.
+    repository.publish(DIST, dbgsym);
+    let entry = cache.entry_from_linux_banner(BANNER).unwrap();
+    assert_eq!(entry.profile_path(), entry_path.join("profile.json"));
+    assert_eq!(
+        entry.source_path(),
+        Some(&*entry_path.join("vmlinux-dbgsym"))
+    );
+    assert_eq!(repository.downloads().len(), 3);
+    assert!(!entry_path.join("profile-symbols.json").exists());
+
+    let profile = entry.profile().unwrap();
+    assert_eq!(profile.find_symbol(name), Some(address));
+    assert!(profile.find_struct("task_struct").is_some());
+}
@@ -0,0 +1,36 @@
+#![cfg(all(feature = "pdb", feature = "unstable"))]
+
+mod common;
+
+use isr_cache::{CodeView, IsrCache, JsonCodec};
+
+use self::common::{pdb::Pdb, Server};
+
+#[test]
+fn pdb_identity() {
+    let pdb = Pdb {
+        age: 3,
+        ..Default::default()
+    };
+    let data = pdb.to_bytes();
+    let server = Server::new(move |path| match path.ends_with("/ntkrnlmp.pdb") {
+        true => (200, data.clone()),
+        false => (404, Vec::new()),
+    });
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = IsrCache::<JsonCodec>::new(directory.path())
+        .unwrap()
+        .with_symbol_servers([server.url.as_str()]);
+
+    let codeview = CodeView::new("ntkrnlmp.pdb", "b9db443817206749be7aa4a2c20430fa3").unwrap();
+    let entry = cache.entry_from_codeview(codeview).unwrap();
+
+    let windows = entry.metadata().unwrap().windows.unwrap();
+    assert_eq!(windows.guid, "b9db443817206749be7aa4a2c20430fa");
+    assert_eq!(windows.age, 3);
+    assert_eq!(windows.signature, 0x5f4e_3a2b);
+
+    // Only known when the entry is created from the executable.
+    assert_eq!(windows.file_version, None);
+}
@@ -0,0 +1,91 @@
+//! Compares [`Profile::find_symbols`] with a loop over
+//! [`Profile::find_symbol`].
+
+use std::{borrow::Cow, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use indexmap::{IndexMap, IndexSet};
+use isr_core::{types::Types, Profile, Symbols};
+
+/// Number of symbols in the profile.
+const SYMBOLS: usize = 50_000;
+
+/// Number of symbols looked up, every fourth one is missing.
+const NAMES: usize = 500;
+
+fn profile(names: &[String]) -> Profile<'_> {
+    let symbols = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (Cow::Borrowed(name.as_str()), index as u64 * 0x10))
+        .collect();
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default())
+}
+
+fn find_symbols(c: &mut Criterion) {
+    let symbols = (0..SYMBOLS)
+        .map(|index| format!("Symbol{index}"))
+        .collect::<Vec<_>>();
+    let profile = profile(&symbols);
+
+    let names = (0..NAMES)
+        .map(|index| match index % 4 {
+            0 => format!("Missing{index}"),
+            _ => format!("Symbol{}", index * 97 % SYMBOLS),
+        })
+        .collect::<Vec<_>>();
+    let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("find_symbols");
+
+    group.bench_function("loop", |b| {
+        b.iter(|| {
+            let mut found = Vec::with_capacity(names.len());
+            let mut missing = Vec::new();
+
+            for &name in black_box(&names) {
+                match profile.find_symbol(name) {
+                    Some(address) => found.push((name, address)),
+                    None => missing.push(name),
+                }
+            }
+
+            (found, missing)
+        })
+    });
+
+    // Same result as `find_symbols`.
+    group.bench_function("loop_index_map", |b| {
+        b.iter(|| {
+            let mut found = IndexMap::with_capacity(names.len());
+            let mut missing = IndexSet::new();
+
+            for &name in black_box(&names) {
+                match profile.find_symbol(name) {
+                    Some(address) => {
+                        found.insert(name, address);
+                    }
+                    None => {
+                        missing.insert(name);
+                    }
+                }
+            }
+
+            (found, missing)
+        })
+    });
+
+    group.bench_function("find_symbols", |b| {
+        b.iter(|| profile.find_symbols(black_box(&names).iter().copied()))
+    });
+
+    group.bench_function("find_symbols_bulk", |b| {
+        b.iter(|| profile.find_symbols_bulk(black_box(&names)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, find_symbols);
+criterion_main!(benches);
@@ -1,7 +1,48 @@
 //! ISR core library.
 
+#[macro_use]
+mod macros;
+
+mod lookup;
+mod name;
+mod options;
+mod pdb;
 mod profile;
+mod reachable;
+mod relaxed;
+mod render;
+mod report;
+mod repr;
 mod symbols;
 pub mod types;
+mod value;
+mod version;
+
+pub use self::{
+    lookup::{Lookup, MissingNames, StructLookup, SymbolLookup},
+    name::{truncate_name, unique_name},
+    options::{ProfileOptions, ResolutionPolicy, Strictness, DEFAULT_MAX_TYPE_DEPTH},
+    pdb::{parse_pdb_guid, pdb_identifier},
+    profile::{ArchitectureMismatch, FieldPathError, NestedField, Profile, ProfileSource},
+    reachable::ReachableSet,
+    relaxed::{AmbiguousName, Relaxation, RelaxedMatch},
+    render::RenderOptions,
+    symbols::{SymbolBase, SymbolScope, Symbols},
+    value::{StructReadCheck, StructValue, Value},
+    version::{KernelVersion, WindowsBuild},
+};
+
+unstable_api! {
+    pub mod unstable {
+        //! Experimental APIs without semver guarantees.
+
+        pub use crate::report::{ProfileReport, SkippedType, Warning};
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    //! Used by the profile parsers, not part of the public API.
 
-pub use self::{profile::Profile, symbols::Symbols};
+    pub use crate::report::{ProfileReport, SkippedType, Warning};
+}
@@ -0,0 +1,180 @@
+use indexmap::{IndexMap, IndexSet};
+
+use crate::types::Struct;
+
+/// Result of looking up multiple names at once.
+///
+/// Returned by [`Profile::find_symbols`] and [`Profile::find_structs`].
+///
+/// [`Profile::find_symbols`]: crate::Profile::find_symbols
+/// [`Profile::find_structs`]: crate::Profile::find_structs
+#[derive(Debug, Clone)]
+pub struct Lookup<'n, T> {
+    /// Names that were found, in the order they were requested.
+    pub found: IndexMap<&'n str, T>,
+
+    /// Names that weren't found, in the order they were requested.
+    pub missing: IndexSet<&'n str>,
+}
+
+/// Result of [`Profile::find_symbols`].
+///
+/// [`Profile::find_symbols`]: crate::Profile::find_symbols
+pub type SymbolLookup<'n> = Lookup<'n, u64>;
+
+/// Result of [`Profile::find_structs`].
+///
+/// [`Profile::find_structs`]: crate::Profile::find_structs
+pub type StructLookup<'n, 'p> = Lookup<'n, &'p Struct<'p>>;
+
+impl<'n, T> Lookup<'n, T> {
+    /// Looks up each name with `find`.
+    ///
+    /// Duplicate names are looked up and reported once.
+    pub(crate) fn collect(
+        names: impl IntoIterator<Item = &'n str>,
+        mut find: impl FnMut(&str) -> Option<T>,
+    ) -> Self {
+        let names = names.into_iter();
+        let mut found = IndexMap::with_capacity(names.size_hint().0);
+        let mut missing = IndexSet::new();
+
+        for name in names {
+            // Missing names are looked up again if repeated, `missing`
+            // reports them once.
+            if let indexmap::map::Entry::Vacant(entry) = found.entry(name) {
+                match find(name) {
+                    Some(value) => {
+                        entry.insert(value);
+                    }
+                    None => {
+                        missing.insert(name);
+                    }
+                }
+            }
+        }
+
+        Self { found, missing }
+    }
+
+    /// Returns `true` if all names were found.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Returns the found names, or an error listing the missing ones.
+    pub fn require_all(self) -> Result<IndexMap<&'n str, T>, MissingNames> {
+        match self.missing.is_empty() {
+            true => Ok(self.found),
+            false => Err(MissingNames {
+                names: self.missing.into_iter().map(String::from).collect(),
+            }),
+        }
+    }
+}
+
+/// Error returned by [`Lookup::require_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingNames {
+    /// Names that weren't found.
+    pub names: Vec<String>,
+}
+
+impl std::fmt::Display for MissingNames {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Not found: {}", self.names.join(", "))
+    }
+}
+
+impl std::error::Error for MissingNames {}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{types::Types, Profile, Symbols};
+
+    fn profile() -> Profile<'static> {
+        let symbols = [
+            ("KeBugCheckEx", 0x1c_14e0),
+            ("PsActiveProcessHead", 0x43_7bc0),
+            ("PsInitialSystemProcess", 0x57_33a0),
+        ]
+        .into_iter()
+        .map(|(name, rva)| (Cow::Borrowed(name), rva))
+        .collect();
+
+        Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default())
+    }
+
+    #[test]
+    fn partial_miss() {
+        let profile = profile();
+        let lookup = profile.find_symbols([
+            "PsInitialSystemProcess",
+            "MmPfnDatabase",
+            "KeBugCheckEx",
+            "PspCidTable",
+        ]);
+
+        assert!(!lookup.is_complete());
+        assert_eq!(
+            lookup.found.iter().collect::<Vec<_>>(),
+            [
+                (&"PsInitialSystemProcess", &0x57_33a0),
+                (&"KeBugCheckEx", &0x1c_14e0)
+            ]
+        );
+        assert_eq!(
+            lookup.missing.iter().collect::<Vec<_>>(),
+            [&"MmPfnDatabase", &"PspCidTable"]
+        );
+
+        let err = lookup.require_all().unwrap_err();
+        assert_eq!(err.names, ["MmPfnDatabase", "PspCidTable"]);
+        assert_eq!(err.to_string(), "Not found: MmPfnDatabase, PspCidTable");
+    }
+
+    #[test]
+    fn duplicates() {
+        let profile = profile();
+        let lookup = profile.find_symbols([
+            "MmPfnDatabase",
+            "KeBugCheckEx",
+            "MmPfnDatabase",
+            "KeBugCheckEx",
+        ]);
+
+        assert_eq!(lookup.found.len(), 1);
+        assert_eq!(
+            lookup.missing.iter().collect::<Vec<_>>(),
+            [&"MmPfnDatabase"]
+        );
+    }
+
+    #[test]
+    fn all_found() {
+        let profile = profile();
+        let lookup = profile.find_symbols(["PsActiveProcessHead", "KeBugCheckEx"]);
+
+        assert!(lookup.is_complete());
+        let found = lookup.require_all().unwrap();
+        assert_eq!(found["PsActiveProcessHead"], 0x43_7bc0);
+        assert_eq!(found.get_index(1), Some((&"KeBugCheckEx", &0x1c_14e0)));
+    }
+
+    #[test]
+    fn bulk() {
+        let profile = profile();
+        assert_eq!(
+            profile.find_symbols_bulk(&[
+                "KeBugCheckEx",
+                "MmPfnDatabase",
+                "KeBugCheckEx",
+                "PsActiveProcessHead"
+            ]),
+            [Some(0x1c_14e0), None, Some(0x1c_14e0), Some(0x43_7bc0)]
+        );
+        assert!(profile.find_symbols_bulk(&[]).is_empty());
+    }
+}
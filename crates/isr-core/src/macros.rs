@@ -0,0 +1,19 @@
+/// Marks items as unstable.
+///
+/// The items are only compiled when the `unstable` feature of the crate
+/// invoking the macro is enabled, and their documentation carries a note
+/// that they are exempt from semver guarantees.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! unstable_api {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "unstable")]
+            #[doc = ""]
+            #[doc = "<div class=\"warning\">This API is unstable and only \
+                     available with the <code>unstable</code> feature. It may \
+                     change or be removed in any release.</div>"]
+            $item
+        )*
+    };
+}
@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+
+use crate::{
+    types::{Type, Types},
+    Symbols,
+};
+
+/// Length of the suffix appended to truncated names (`~` and 16 hex digits).
+const TRUNCATED_SUFFIX_LEN: usize = 17;
+
+/// Truncates a name to at most `max_len` bytes.
+///
+/// Names longer than `max_len` are cut at a character boundary and
+/// suffixed with `~` followed by a stable hash of the full name, so that
+/// distinct names remain distinct and the result is the same across runs.
+///
+/// If `max_len` is shorter than the suffix (17 bytes), the result is the
+/// suffix cut to `max_len` bytes. Such names are much more likely to
+/// collide.
+pub fn truncate_name(name: &str, max_len: usize) -> Cow<'_, str> {
+    if name.len() <= max_len {
+        return Cow::Borrowed(name);
+    }
+
+    let suffix = format!("~{:016x}", fnv1a(name));
+    if max_len < TRUNCATED_SUFFIX_LEN {
+        return Cow::Owned(suffix[..max_len].to_owned());
+    }
+
+    let mut prefix_len = max_len - TRUNCATED_SUFFIX_LEN;
+    while !name.is_char_boundary(prefix_len) {
+        prefix_len -= 1;
+    }
+
+    Cow::Owned(format!("{}{suffix}", &name[..prefix_len]))
+}
+
+/// Makes a name unique among the keys of `map`.
+///
+/// Appends `~1`, `~2`, ... until the name doesn't collide with an existing
+/// key. Used for names that were converted lossily (e.g. from invalid
+/// UTF-8) and might collide with other names.
+pub fn unique_name<'a, V>(map: &IndexMap<Cow<'a, str>, V>, name: Cow<'a, str>) -> Cow<'a, str> {
+    if !map.contains_key(&name) {
+        return name;
+    }
+
+    let mut index = 1;
+    loop {
+        let candidate = format!("{name}~{index}");
+        if !map.contains_key(candidate.as_str()) {
+            return Cow::Owned(candidate);
+        }
+
+        index += 1;
+    }
+}
+
+impl Symbols<'_> {
+    /// Truncates the symbol names to at most `max_len` bytes.
+    ///
+    /// Returns the number of truncated names. See [`truncate_name`].
+    pub fn truncate_names(&mut self, max_len: usize) -> usize {
+        let mut count = 0;
+        self.0 = truncate_keys(std::mem::take(&mut self.0), max_len, &mut count);
+        count
+    }
+}
+
+impl Types<'_> {
+    /// Truncates the type, field and variant names to at most `max_len`
+    /// bytes.
+    ///
+    /// References to the renamed types are updated, so the types remain
+    /// consistent. Returns the number of truncated names. See
+    /// [`truncate_name`].
+    pub fn truncate_names(&mut self, max_len: usize) -> usize {
+        let mut count = 0;
+
+        let mut enums = truncate_keys(std::mem::take(&mut self.enums), max_len, &mut count);
+        for enum_ in enums.values_mut() {
+            truncate_type_refs(&mut enum_.subtype, max_len, &mut count);
+            enum_.fields = truncate_keys(std::mem::take(&mut enum_.fields), max_len, &mut count);
+        }
+
+        let mut structs = truncate_keys(std::mem::take(&mut self.structs), max_len, &mut count);
+        for udt in structs.values_mut() {
+            udt.fields = truncate_keys(std::mem::take(&mut udt.fields), max_len, &mut count);
+            for field in udt.fields.values_mut() {
+                truncate_type_refs(&mut field.type_, max_len, &mut count);
+            }
+        }
+
+        // The provenance follows the renamed types, without counting them
+        // twice.
+        let provenance = &mut self.provenance;
+        provenance.enums = truncate_keys(std::mem::take(&mut provenance.enums), max_len, &mut 0);
+        provenance.structs =
+            truncate_keys(std::mem::take(&mut provenance.structs), max_len, &mut 0);
+
+        self.enums = enums;
+        self.structs = structs;
+        count
+    }
+}
+
+fn truncate_keys<'a, V>(
+    map: IndexMap<Cow<'a, str>, V>,
+    max_len: usize,
+    count: &mut usize,
+) -> IndexMap<Cow<'a, str>, V> {
+    map.into_iter()
+        .map(|(name, value)| (truncate_cow(name, max_len, count), value))
+        .collect()
+}
+
+fn truncate_type_refs(type_: &mut Type, max_len: usize, count: &mut usize) {
+    match type_ {
+        Type::Enum(enum_ref) => {
+            enum_ref.name = truncate_cow(std::mem::take(&mut enum_ref.name), max_len, count);
+        }
+        Type::Struct(struct_ref) => {
+            struct_ref.name = truncate_cow(std::mem::take(&mut struct_ref.name), max_len, count);
+        }
+        Type::Array(array) => truncate_type_refs(&mut array.subtype, max_len, count),
+        Type::Pointer(pointer) => truncate_type_refs(&mut pointer.subtype, max_len, count),
+        Type::Bitfield(bitfield) => truncate_type_refs(&mut bitfield.subtype, max_len, count),
+        Type::Base(_) | Type::Function => (),
+    }
+}
+
+fn truncate_cow<'a>(name: Cow<'a, str>, max_len: usize, count: &mut usize) -> Cow<'a, str> {
+    if name.len() <= max_len {
+        return name;
+    }
+
+    *count += 1;
+    Cow::Owned(truncate_name(&name, max_len).into_owned())
+}
+
+/// 64-bit FNV-1a hash.
+///
+/// Unlike the standard library hashers, the result is stable across Rust
+/// versions and platforms.
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate() {
+        let name = "KiSystemCall64ShadowCommon";
+
+        assert_eq!(truncate_name(name, 64), name);
+        assert_eq!(truncate_name(name, name.len()), name);
+
+        for max_len in 0..name.len() {
+            let truncated = truncate_name(name, max_len);
+            assert_eq!(truncated.len(), max_len, "{truncated:?}");
+        }
+
+        let truncated = truncate_name(name, 20);
+        assert!(truncated.starts_with("KiS~"), "{truncated:?}");
+        assert_ne!(truncated, truncate_name("KiSystemCall64Shadow", 20));
+    }
+
+    #[test]
+    fn truncate_at_char_boundary() {
+        let name = "\u{e9}".repeat(12);
+
+        // The prefix would end in the middle of the second character.
+        let truncated = truncate_name(&name, 20);
+        assert!(truncated.len() <= 20);
+        assert!(truncated.starts_with("\u{e9}~"), "{truncated:?}");
+    }
+}
@@ -0,0 +1,448 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    types::{Enum, Struct, Types},
+    KernelVersion, SymbolScope, Symbols,
+};
+
+/// Default maximum depth of nested types, see
+/// [`ProfileOptions::max_type_depth`].
+pub const DEFAULT_MAX_TYPE_DEPTH: usize = 128;
+
+/// Options controlling profile generation.
+///
+/// Shared by the profile producers (`isr-pdb`, `isr-dwarf`).
+#[derive(Debug, Clone, Default)]
+pub struct ProfileOptions {
+    /// Kernel version to record in the profile.
+    pub kernel_version: Option<KernelVersion>,
+
+    /// Preferred (link-time) base address of the image to record in the
+    /// profile, e.g. the `ImageBase` of the PE the PDB belongs to.
+    pub preferred_base: Option<u64>,
+
+    /// Accept a System.map that fails the sanity checks (too few symbols,
+    /// zeroed addresses). Useful for unusual embedded kernels.
+    pub allow_suspect_systemmap: bool,
+
+    /// Scope of the symbols to include in the profile.
+    pub symbol_scope: SymbolScope,
+
+    /// Additional kinds of System.map symbols to include, besides text
+    /// and data (e.g. `b`/`B` for BSS, `r`/`R` for read-only data).
+    pub systemmap_extra_kinds: Vec<char>,
+
+    /// Maximum length of symbol, type and field names, in bytes.
+    ///
+    /// Longer names are truncated (see [`truncate_name`]).
+    ///
+    /// [`truncate_name`]: crate::truncate_name
+    pub max_name_length: Option<usize>,
+
+    /// Add unversioned aliases for versioned symbols (`foo` for
+    /// `foo@@VERSION`).
+    ///
+    /// See [`Symbols::add_unversioned_aliases`].
+    pub unversioned_symbol_aliases: bool,
+
+    /// Record types that are only forward declared as incomplete structs
+    /// (see [`Struct::incomplete`]).
+    ///
+    /// [`Struct::incomplete`]: crate::types::Struct::incomplete
+    pub forward_declaration_stubs: bool,
+
+    /// Skip type records that fail to parse instead of failing the whole
+    /// profile creation.
+    ///
+    /// The skipped records are recorded in the profile report (see
+    /// `unstable::ProfileReport`).
+    pub partial_on_error: bool,
+
+    /// Record where each enum and struct was defined (see
+    /// [`Profile::type_provenance`]).
+    ///
+    /// Off by default to keep the profiles small. Only supported by the
+    /// DWARF producer, PDB types are not tied to a module.
+    ///
+    /// [`Profile::type_provenance`]: crate::Profile::type_provenance
+    pub record_provenance: bool,
+
+    /// How types defined more than once with the same name are resolved.
+    ///
+    /// Defaults to [`ResolutionPolicy::LastSeen`] for PDB profiles and to
+    /// [`ResolutionPolicy::MostFields`] for DWARF profiles.
+    pub resolution_policy: Option<ResolutionPolicy>,
+
+    /// How lossy conversions (e.g. an unknown base type becoming `void`)
+    /// are handled.
+    ///
+    /// Doesn't affect the content of the profile, only whether it's
+    /// created at all.
+    pub strictness: Strictness,
+
+    /// Maximum depth of nested types (e.g. a pointer to a pointer).
+    ///
+    /// Deeper types, which only occur in malformed or cyclic debug
+    /// information, are replaced with `void` and reported as lossy
+    /// conversions. Defaults to [`DEFAULT_MAX_TYPE_DEPTH`].
+    pub max_type_depth: Option<usize>,
+
+    /// Number of threads used to collect the types.
+    ///
+    /// `0` and `1` collect the types sequentially. Only supported by the
+    /// DWARF producer. Doesn't affect the content of the profile.
+    pub threads: usize,
+
+    /// Identifier of the profile creation, recorded in the tracing span of
+    /// the producers (see [`run_span`]).
+    ///
+    /// Tells apart the logs of profiles created concurrently. A unique
+    /// identifier is generated if not set. Doesn't affect the content of
+    /// the profile.
+    ///
+    /// [`run_span`]: Self::run_span
+    pub run_id: Option<String>,
+}
+
+/// Handling of lossy conversions during the profile creation.
+///
+/// Lossy conversions are always recorded in the profile report (see
+/// `unstable::ProfileReport::warnings`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strictness {
+    /// Log lossy conversions at the debug level only.
+    Lenient,
+
+    /// Log lossy conversions as warnings.
+    #[default]
+    Warn,
+
+    /// Fail the profile creation on the first lossy conversion.
+    Strict,
+}
+
+/// Policy for resolving types defined more than once with the same name.
+///
+/// Only one definition is kept in the profile. On ties, the definition
+/// seen first is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionPolicy {
+    /// Keep the first definition.
+    FirstSeen,
+
+    /// Keep the last definition.
+    LastSeen,
+
+    /// Keep the largest definition.
+    ///
+    /// Enums have no size of their own, they're resolved by the number of
+    /// variants.
+    LargestSize,
+
+    /// Keep the definition with the most fields (or variants).
+    MostFields,
+}
+
+impl ResolutionPolicy {
+    /// Checks whether a new definition of a struct replaces the previous
+    /// one.
+    pub fn replaces_struct(self, new: &Struct, previous: &Struct) -> bool {
+        match self {
+            Self::LargestSize => new.size > previous.size,
+            _ => self.replaces(new.fields.len(), previous.fields.len()),
+        }
+    }
+
+    /// Checks whether a new definition of an enum replaces the previous
+    /// one.
+    pub fn replaces_enum(self, new: &Enum, previous: &Enum) -> bool {
+        self.replaces(new.fields.len(), previous.fields.len())
+    }
+
+    fn replaces(self, new_fields: usize, previous_fields: usize) -> bool {
+        match self {
+            Self::FirstSeen => false,
+            Self::LastSeen => true,
+            Self::LargestSize | Self::MostFields => new_fields > previous_fields,
+        }
+    }
+}
+
+impl ProfileOptions {
+    /// Sets the kernel version to record in the profile.
+    pub fn with_kernel_version(self, kernel_version: impl Into<Option<KernelVersion>>) -> Self {
+        Self {
+            kernel_version: kernel_version.into(),
+            ..self
+        }
+    }
+
+    /// Sets the preferred base address of the image to record in the
+    /// profile.
+    pub fn with_preferred_base(self, preferred_base: impl Into<Option<u64>>) -> Self {
+        Self {
+            preferred_base: preferred_base.into(),
+            ..self
+        }
+    }
+
+    /// Sets the scope of the symbols to include in the profile.
+    pub fn with_symbol_scope(self, symbol_scope: SymbolScope) -> Self {
+        Self {
+            symbol_scope,
+            ..self
+        }
+    }
+
+    /// Sets the additional kinds of System.map symbols to include.
+    pub fn with_systemmap_extra_kinds(
+        self,
+        systemmap_extra_kinds: impl IntoIterator<Item = char>,
+    ) -> Self {
+        Self {
+            systemmap_extra_kinds: systemmap_extra_kinds.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum length of symbol, type and field names.
+    pub fn with_max_name_length(self, max_name_length: impl Into<Option<usize>>) -> Self {
+        Self {
+            max_name_length: max_name_length.into(),
+            ..self
+        }
+    }
+
+    /// Sets whether unversioned aliases are added for versioned symbols.
+    pub fn with_unversioned_symbol_aliases(self, unversioned_symbol_aliases: bool) -> Self {
+        Self {
+            unversioned_symbol_aliases,
+            ..self
+        }
+    }
+
+    /// Sets whether forward declared types are recorded as incomplete
+    /// structs.
+    pub fn with_forward_declaration_stubs(self, forward_declaration_stubs: bool) -> Self {
+        Self {
+            forward_declaration_stubs,
+            ..self
+        }
+    }
+
+    /// Sets whether type records that fail to parse are skipped.
+    pub fn with_partial_on_error(self, partial_on_error: bool) -> Self {
+        Self {
+            partial_on_error,
+            ..self
+        }
+    }
+
+    /// Sets whether the definitions of the types are recorded.
+    pub fn with_record_provenance(self, record_provenance: bool) -> Self {
+        Self {
+            record_provenance,
+            ..self
+        }
+    }
+
+    /// Sets how types defined more than once are resolved.
+    pub fn with_resolution_policy(
+        self,
+        resolution_policy: impl Into<Option<ResolutionPolicy>>,
+    ) -> Self {
+        Self {
+            resolution_policy: resolution_policy.into(),
+            ..self
+        }
+    }
+
+    /// Sets how lossy conversions are handled.
+    pub fn with_strictness(self, strictness: Strictness) -> Self {
+        Self { strictness, ..self }
+    }
+
+    /// Sets the maximum depth of nested types.
+    pub fn with_max_type_depth(self, max_type_depth: impl Into<Option<usize>>) -> Self {
+        Self {
+            max_type_depth: max_type_depth.into(),
+            ..self
+        }
+    }
+
+    /// Returns the maximum depth of nested types.
+    pub fn max_type_depth(&self) -> usize {
+        self.max_type_depth.unwrap_or(DEFAULT_MAX_TYPE_DEPTH)
+    }
+
+    /// Sets the number of threads used to collect the types.
+    pub fn with_threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
+    /// Returns a short string identifying the options that affect the
+    /// content of the profile, or `None` if they're all at their defaults.
+    ///
+    /// Used to keep profiles created with different options apart.
+    pub fn fingerprint(&self) -> Option<String> {
+        let mut parts = Vec::new();
+
+        match self.symbol_scope {
+            SymbolScope::All => (),
+            SymbolScope::Exported => parts.push(String::from("exported")),
+            SymbolScope::None => parts.push(String::from("nosymbols")),
+        }
+
+        if !self.systemmap_extra_kinds.is_empty() {
+            // Only letters are valid kinds, anything else can't be part of a
+            // file name.
+            let kinds = self
+                .systemmap_extra_kinds
+                .iter()
+                .filter(|kind| kind.is_ascii_alphanumeric())
+                .collect::<String>();
+            parts.push(format!("kinds{kinds}"));
+        }
+
+        if let Some(max_name_length) = self.max_name_length {
+            parts.push(format!("maxname{max_name_length}"));
+        }
+
+        if let Some(preferred_base) = self.preferred_base {
+            parts.push(format!("base{preferred_base:x}"));
+        }
+
+        if self.unversioned_symbol_aliases {
+            parts.push(String::from("unversioned"));
+        }
+
+        if self.forward_declaration_stubs {
+            parts.push(String::from("stubs"));
+        }
+
+        if self.partial_on_error {
+            parts.push(String::from("partial"));
+        }
+
+        if self.record_provenance {
+            parts.push(String::from("provenance"));
+        }
+
+        if let Some(max_type_depth) = self.max_type_depth {
+            parts.push(format!("maxdepth{max_type_depth}"));
+        }
+
+        match self.resolution_policy {
+            None => (),
+            Some(ResolutionPolicy::FirstSeen) => parts.push(String::from("firstseen")),
+            Some(ResolutionPolicy::LastSeen) => parts.push(String::from("lastseen")),
+            Some(ResolutionPolicy::LargestSize) => parts.push(String::from("largest")),
+            Some(ResolutionPolicy::MostFields) => parts.push(String::from("mostfields")),
+        }
+
+        match parts.is_empty() {
+            true => None,
+            false => Some(parts.join("-")),
+        }
+    }
+
+    /// Truncates the names in the symbols and types according to
+    /// [`max_name_length`].
+    ///
+    /// Returns the number of truncated names.
+    ///
+    /// [`max_name_length`]: Self::max_name_length
+    pub fn apply_max_name_length(&self, symbols: &mut Symbols, types: &mut Types) -> usize {
+        match self.max_name_length {
+            Some(max_name_length) => {
+                symbols.truncate_names(max_name_length) + types.truncate_names(max_name_length)
+            }
+            None => 0,
+        }
+    }
+
+    /// Adds unversioned aliases to the symbols if
+    /// [`unversioned_symbol_aliases`] is set.
+    ///
+    /// Returns the number of added aliases.
+    ///
+    /// [`unversioned_symbol_aliases`]: Self::unversioned_symbol_aliases
+    pub fn apply_unversioned_symbol_aliases(&self, symbols: &mut Symbols) -> usize {
+        match self.unversioned_symbol_aliases {
+            true => symbols.add_unversioned_aliases(),
+            false => 0,
+        }
+    }
+
+    /// Sets whether a System.map failing the sanity checks is accepted.
+    pub fn with_allow_suspect_systemmap(self, allow_suspect_systemmap: bool) -> Self {
+        Self {
+            allow_suspect_systemmap,
+            ..self
+        }
+    }
+
+    /// Sets the identifier of the profile creation.
+    pub fn with_run_id(self, run_id: impl Into<String>) -> Self {
+        Self {
+            run_id: Some(run_id.into()),
+            ..self
+        }
+    }
+
+    /// Creates the tracing span of a profile creation by `producer` (e.g.
+    /// `"pdb"`).
+    ///
+    /// The span records the [`run_id`], or a newly generated identifier
+    /// (unique within the process) if it isn't set. The producers enter it
+    /// for the whole profile creation, including their worker threads.
+    ///
+    /// [`run_id`]: Self::run_id
+    pub fn run_span(&self, producer: &'static str) -> tracing::Span {
+        static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+        match &self.run_id {
+            Some(run_id) => tracing::info_span!("create_profile", producer, run_id = %run_id),
+            None => {
+                let run_id = NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed);
+                tracing::info_span!("create_profile", producer, run_id)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint() {
+        assert_eq!(ProfileOptions::default().fingerprint(), None);
+
+        let options = ProfileOptions::default().with_preferred_base(0x1_4000_0000);
+        assert_eq!(options.fingerprint().as_deref(), Some("base140000000"));
+
+        let options = options.with_symbol_scope(SymbolScope::Exported);
+        assert_eq!(
+            options.fingerprint().as_deref(),
+            Some("exported-base140000000")
+        );
+
+        let options = ProfileOptions::default().with_systemmap_extra_kinds(['b', 'B', '/']);
+        assert_eq!(options.fingerprint().as_deref(), Some("kindsbB"));
+
+        // Profiles with different preferred bases are kept apart.
+        assert_ne!(
+            ProfileOptions::default()
+                .with_preferred_base(0xffffffff_81000000)
+                .fingerprint(),
+            ProfileOptions::default()
+                .with_preferred_base(0xffffffff_81200000)
+                .fingerprint()
+        );
+    }
+}
@@ -0,0 +1,99 @@
+/// Formats the identifier of a PDB file on the symbol server.
+///
+/// The identifier consists of the GUID as 32 lowercase hex digits,
+/// followed by the age in hex without padding (e.g. `1` or `1c`). The
+/// GUID is given in its binary form, with the first three components in
+/// little-endian order (see [`parse_pdb_guid`]).
+pub fn pdb_identifier(guid: &[u8; 16], age: u32) -> String {
+    let guid0 = u32::from_le_bytes(guid[0..4].try_into().unwrap());
+    let guid1 = u16::from_le_bytes(guid[4..6].try_into().unwrap());
+    let guid2 = u16::from_le_bytes(guid[6..8].try_into().unwrap());
+    let guid3 = &guid[8..16];
+
+    format!(
+        "{:08x}{:04x}{:04x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:x}",
+        guid0,
+        guid1,
+        guid2,
+        guid3[0],
+        guid3[1],
+        guid3[2],
+        guid3[3],
+        guid3[4],
+        guid3[5],
+        guid3[6],
+        guid3[7],
+        age,
+    )
+}
+
+/// Parses a PDB GUID written as 32 hex digits.
+///
+/// Dashes and braces are ignored, so the registry format
+/// (`{3844DBB9-2017-4967-BE7A-A4A2C20430FA}`) is accepted as well.
+///
+/// Returns the binary form expected by [`pdb_identifier`], or `None` if
+/// the GUID is malformed.
+pub fn parse_pdb_guid(guid: &str) -> Option<[u8; 16]> {
+    let digits = guid
+        .bytes()
+        .filter(|byte| !matches!(byte, b'{' | b'}' | b'-'))
+        .collect::<Vec<_>>();
+
+    if digits.len() != 32 || !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+
+    let mut bytes = [0u8; 16];
+    for (byte, pair) in bytes.iter_mut().zip(digits.chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+
+    // The first three components are stored in little-endian order.
+    bytes[0..4].reverse();
+    bytes[4..6].reverse();
+    bytes[6..8].reverse();
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_round_trip() {
+        let guid = parse_pdb_guid("3844DBB9-2017-4967-BE7A-A4A2C20430FA").unwrap();
+        assert_eq!(&guid[..4], &[0xb9, 0xdb, 0x44, 0x38]);
+        assert_eq!(
+            pdb_identifier(&guid, 2),
+            "3844dbb920174967be7aa4a2c20430fa2"
+        );
+        assert_eq!(
+            parse_pdb_guid("{3844dbb920174967be7aa4a2c20430fa}"),
+            Some(guid)
+        );
+    }
+
+    #[test]
+    fn identifier_age() {
+        let guid = [0; 16];
+        let zeros = "0".repeat(32);
+        assert_eq!(pdb_identifier(&guid, 0), format!("{zeros}0"));
+        assert_eq!(pdb_identifier(&guid, 0xf), format!("{zeros}f"));
+        assert_eq!(pdb_identifier(&guid, 0x10), format!("{zeros}10"));
+        assert_eq!(pdb_identifier(&guid, 0x12), format!("{zeros}12"));
+        assert_eq!(pdb_identifier(&guid, u32::MAX), format!("{zeros}ffffffff"));
+    }
+
+    #[test]
+    fn malformed_guid() {
+        assert_eq!(parse_pdb_guid(""), None);
+        assert_eq!(parse_pdb_guid("3844dbb920174967be7aa4a2c20430f"), None);
+        assert_eq!(parse_pdb_guid("3844dbb920174967be7aa4a2c20430fa2"), None);
+        assert_eq!(parse_pdb_guid("3844dbb920174967be7aa4a2c20430fg"), None);
+        assert_eq!(
+            parse_pdb_guid("3844dbb920174967be7aa4a2c20430f\u{e9}"),
+            None
+        );
+    }
+}
@@ -1,12 +1,116 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet, io::Write};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    symbols::Symbols,
-    types::{BaseRef, Enum, Struct, Type, Types},
+    relaxed::RelaxedIndex,
+    render::{self, RenderOptions},
+    symbols::{SymbolBase, SymbolScope, Symbols},
+    types::{BaseRef, Enum, Field, Provenance, Struct, Type, Types, Variant},
+    AmbiguousName, KernelVersion, Lookup, ReachableSet, Relaxation, RelaxedMatch, StructLookup,
+    SymbolLookup,
 };
 
+/// Backend a profile was created from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileSource {
+    /// PDB file (`isr-pdb`).
+    Pdb,
+
+    /// DWARF debug information and/or a `System.map` (`isr-dwarf`).
+    Dwarf,
+
+    /// Export table of a PE image, without types.
+    Exports,
+
+    /// Volatility ISF file (`isr-isf`).
+    Isf,
+}
+
+/// Error returned by [`Profile::expect_architecture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchitectureMismatch {
+    /// Accepted architectures.
+    pub expected: Vec<String>,
+
+    /// Architecture of the profile.
+    pub found: String,
+}
+
+impl std::fmt::Display for ArchitectureMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected architecture {}, found {}",
+            self.expected.join(" or "),
+            self.found
+        )
+    }
+}
+
+impl std::error::Error for ArchitectureMismatch {}
+
+/// Field found in a struct or in one of its nested structs.
+#[derive(Debug, Clone, Copy)]
+pub struct NestedField<'a> {
+    /// Name of the struct that directly contains the field.
+    pub parent: &'a str,
+
+    /// Name of the field.
+    pub name: &'a str,
+
+    /// Offset of the field within the struct it was looked up in.
+    pub offset: u64,
+
+    /// The field, with its offset within `parent`.
+    pub field: &'a Field<'a>,
+}
+
+/// Error returned by [`Profile::container_of`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldPathError {
+    /// The struct doesn't exist.
+    TypeNotFound(String),
+
+    /// The struct doesn't have the field.
+    FieldNotFound {
+        /// Name of the struct.
+        type_name: String,
+
+        /// Name of the field.
+        field_name: String,
+    },
+
+    /// The field is followed by another one in the path, but isn't a
+    /// struct.
+    NotAStruct {
+        /// Name of the struct.
+        type_name: String,
+
+        /// Name of the field.
+        field_name: String,
+    },
+}
+
+impl std::fmt::Display for FieldPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TypeNotFound(type_name) => write!(f, "Type not found: {type_name}"),
+            Self::FieldNotFound {
+                type_name,
+                field_name,
+            } => write!(f, "Field not found: {type_name}.{field_name}"),
+            Self::NotAStruct {
+                type_name,
+                field_name,
+            } => write!(f, "Field is not a struct: {type_name}.{field_name}"),
+        }
+    }
+}
+
+impl std::error::Error for FieldPathError {}
+
 /// Profile.
 ///
 /// Contains information about the target architecture, symbols, and types.
@@ -23,6 +127,38 @@ pub struct Profile<'a> {
     /// Types.
     #[serde(borrow)]
     types: Types<'a>,
+
+    /// Kernel version (Linux profiles only).
+    #[serde(default)]
+    kernel_version: Option<KernelVersion>,
+
+    /// Convention used for the symbol addresses.
+    ///
+    /// Profiles created before this field was introduced don't have it,
+    /// in which case it is inferred (see [`Profile::symbol_base`]).
+    #[serde(default)]
+    symbol_base: Option<SymbolBase>,
+
+    /// Preferred (link-time) base address of the image.
+    ///
+    /// Profiles created before this field was introduced don't have it,
+    /// in which case it is inferred (see [`Profile::preferred_base`]).
+    #[serde(default)]
+    preferred_base: Option<u64>,
+
+    /// Backend the profile was created from.
+    ///
+    /// Profiles created before this field was introduced don't have it.
+    #[serde(default)]
+    source: Option<ProfileSource>,
+
+    /// Scope of the symbols included in the profile.
+    #[serde(default)]
+    symbol_scope: SymbolScope,
+
+    /// Index for [`Profile::find_struct_relaxed`], built on first use.
+    #[serde(skip)]
+    relaxed_index: RelaxedIndex,
 }
 
 impl<'a> Profile<'a> {
@@ -32,9 +168,109 @@ impl<'a> Profile<'a> {
             architecture,
             symbols,
             types,
+            kernel_version: None,
+            symbol_base: None,
+            preferred_base: None,
+            source: None,
+            symbol_scope: SymbolScope::All,
+            relaxed_index: RelaxedIndex::default(),
+        }
+    }
+
+    /// Sets the scope of the symbols included in the profile.
+    pub fn with_symbol_scope(self, symbol_scope: SymbolScope) -> Self {
+        Self {
+            symbol_scope,
+            ..self
+        }
+    }
+
+    /// Sets the convention used for the symbol addresses.
+    pub fn with_symbol_base(self, symbol_base: SymbolBase) -> Self {
+        Self {
+            symbol_base: Some(symbol_base),
+            ..self
+        }
+    }
+
+    /// Sets the preferred (link-time) base address of the image.
+    pub fn with_preferred_base(self, preferred_base: impl Into<Option<u64>>) -> Self {
+        Self {
+            preferred_base: preferred_base.into(),
+            ..self
+        }
+    }
+
+    /// Sets the backend the profile was created from.
+    pub fn with_source(self, source: ProfileSource) -> Self {
+        Self {
+            source: Some(source),
+            ..self
+        }
+    }
+
+    /// Sets the kernel version of the profile.
+    pub fn with_kernel_version(self, kernel_version: impl Into<Option<KernelVersion>>) -> Self {
+        Self {
+            kernel_version: kernel_version.into(),
+            ..self
+        }
+    }
+
+    /// Splits the profile into the architecture, symbols and types.
+    ///
+    /// The parts may borrow from the input the profile was decoded from
+    /// (e.g. the memory map of an `isr-cache` entry). Convert them with
+    /// [`Symbols::into_owned`] and [`Types::into_owned`] before dropping
+    /// the input.
+    pub fn into_parts(self) -> (Cow<'a, str>, Symbols<'a>, Types<'a>) {
+        (self.architecture, self.symbols, self.types)
+    }
+
+    /// Takes the symbols out of the profile, leaving an empty symbol
+    /// table.
+    ///
+    /// See [`Profile::into_parts`] for keeping the symbols after the input
+    /// is dropped.
+    pub fn take_symbols(&mut self) -> Symbols<'a> {
+        std::mem::take(&mut self.symbols)
+    }
+
+    /// Takes the types out of the profile, leaving no types.
+    ///
+    /// See [`Profile::into_parts`] for keeping the types after the input
+    /// is dropped.
+    pub fn take_types(&mut self) -> Types<'a> {
+        self.relaxed_index = RelaxedIndex::default();
+        std::mem::take(&mut self.types)
+    }
+
+    /// Converts the profile into a profile that doesn't borrow from the
+    /// input it was decoded from.
+    pub fn into_owned(self) -> Profile<'static> {
+        Profile {
+            architecture: Cow::Owned(self.architecture.into_owned()),
+            symbols: self.symbols.into_owned(),
+            types: self.types.into_owned(),
+            kernel_version: self.kernel_version,
+            symbol_base: self.symbol_base,
+            preferred_base: self.preferred_base,
+            source: self.source,
+            symbol_scope: self.symbol_scope,
+            relaxed_index: RelaxedIndex::default(),
         }
     }
 
+    /// Returns the backend the profile was created from, if known.
+    pub fn source(&self) -> Option<ProfileSource> {
+        self.source
+    }
+
+    /// Returns the kernel version, if known.
+    pub fn kernel_version(&self) -> Option<KernelVersion> {
+        self.kernel_version
+    }
+
     /// Returns an iterator over the symbols.
     pub fn symbols(&self) -> impl Iterator<Item = (&str, &u64)> {
         self.symbols
@@ -43,8 +279,42 @@ impl<'a> Profile<'a> {
             .map(|(name, value)| (name.as_ref(), value))
     }
 
+    /// Writes the symbols as JSON lines, one `{"name":..,"address":..}`
+    /// object per line.
+    ///
+    /// The addresses are relative to the base of the image, the same as
+    /// the values returned by [`symbols`]. The writer isn't buffered, wrap
+    /// it in a [`BufWriter`] if needed.
+    ///
+    /// [`symbols`]: Self::symbols
+    /// [`BufWriter`]: std::io::BufWriter
+    pub fn write_symbols_jsonl(&self, mut writer: impl Write) -> std::io::Result<()> {
+        #[derive(Serialize)]
+        struct Line<'a> {
+            name: &'a str,
+            address: u64,
+        }
+
+        for (name, &address) in self.symbols() {
+            serde_json::to_writer(&mut writer, &Line { name, address })?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the symbol table.
+    pub fn symbol_table(&self) -> &Symbols<'a> {
+        &self.symbols
+    }
+
+    /// Consumes the profile and returns the symbol table.
+    pub fn into_symbols(self) -> Symbols<'a> {
+        self.symbols
+    }
+
     /// Returns the types.
-    pub fn types(&self) -> &Types {
+    pub fn types(&self) -> &Types<'_> {
         &self.types
     }
 
@@ -79,10 +349,53 @@ impl<'a> Profile<'a> {
     }
 
     /// Returns the size of a struct type in bytes.
+    ///
+    /// Returns `None` if the struct is unknown, and `Some(0)` for structs
+    /// known to be empty (e.g. forward declarations without a definition).
     pub fn struct_size(&self, name: &str) -> Option<u64> {
         self.types.structs.get(name).map(|udt| udt.size)
     }
 
+    /// Returns the largest offset plus size of the fields of a structure.
+    ///
+    /// Normally this doesn't exceed the size of the structure. A larger
+    /// value indicates a field extending past the end of the structure,
+    /// e.g. a variable-length trailing array or inconsistent debug
+    /// information. Fields whose size can't be determined are ignored.
+    pub fn max_field_extent(&self, type_name: &str) -> Option<u64> {
+        let udt = self.find_struct(type_name)?;
+
+        let extent = udt
+            .fields
+            .values()
+            .filter_map(|field| field.offset.checked_add(self.type_size(&field.type_)?))
+            .max()
+            .unwrap_or(0);
+
+        Some(extent)
+    }
+
+    /// Returns the target architecture (e.g. `Amd64`).
+    pub fn architecture(&self) -> &str {
+        &self.architecture
+    }
+
+    /// Checks that the profile targets one of the expected architectures.
+    ///
+    /// Guards against e.g. resolving offsets meant for 64-bit Windows
+    /// against an x86 profile, where the pointer-sized fields resolve
+    /// without an error but have the wrong size.
+    pub fn expect_architecture(&self, expected: &[&str]) -> Result<(), ArchitectureMismatch> {
+        if expected.contains(&self.architecture()) {
+            return Ok(());
+        }
+
+        Err(ArchitectureMismatch {
+            expected: expected.iter().map(|&arch| String::from(arch)).collect(),
+            found: String::from(self.architecture()),
+        })
+    }
+
     /// Returns the size of a pointer in bytes.
     pub fn pointer_size(&self) -> u64 {
         match self.architecture.as_ref() {
@@ -97,13 +410,912 @@ impl<'a> Profile<'a> {
         self.symbols.0.get(symbol_name).copied()
     }
 
+    /// Finds a symbol that may go by several names (e.g. a symbol renamed
+    /// across builds).
+    ///
+    /// The names are tried in order, like the aliases of the `symbols!`
+    /// macro. Returns the first name found and its address.
+    pub fn find_symbol_any<'n>(&self, symbol_names: &[&'n str]) -> Option<(&'n str, u64)> {
+        symbol_names.iter().find_map(|&symbol_name| {
+            self.symbols
+                .0
+                .get(symbol_name)
+                .map(|&address| (symbol_name, address))
+        })
+    }
+
+    /// Finds multiple symbols by name.
+    ///
+    /// Returns the addresses in the same order as the names, with `None`
+    /// for the symbols that weren't found. See [`Profile::find_symbols`].
+    pub fn find_symbols_bulk(&self, symbol_names: &[&str]) -> Vec<Option<u64>> {
+        let lookup = self.find_symbols(symbol_names.iter().copied());
+
+        symbol_names
+            .iter()
+            .map(|symbol_name| lookup.found.get(symbol_name).copied())
+            .collect()
+    }
+
+    /// Finds multiple symbols by name, collecting the missing ones.
+    ///
+    /// Use [`SymbolLookup::require_all`] to fail if any symbol is missing.
+    pub fn find_symbols<'n>(
+        &self,
+        symbol_names: impl IntoIterator<Item = &'n str>,
+    ) -> SymbolLookup<'n> {
+        Lookup::collect(symbol_names, |symbol_name| self.find_symbol(symbol_name))
+    }
+
+    /// Returns the scope of the symbols included in the profile.
+    pub fn symbol_scope(&self) -> SymbolScope {
+        self.symbol_scope
+    }
+
+    /// Returns the convention used for the symbol addresses.
+    ///
+    /// If the profile doesn't record it, profiles containing the `_text`
+    /// symbol (i.e. created from a `System.map`) are assumed to use
+    /// [`SymbolBase::VirtualAddress`], all others [`SymbolBase::Rva`].
+    pub fn symbol_base(&self) -> SymbolBase {
+        if let Some(symbol_base) = self.symbol_base {
+            return symbol_base;
+        }
+
+        match self.find_symbol("_text") {
+            Some(default_base) => SymbolBase::VirtualAddress { default_base },
+            None => SymbolBase::Rva,
+        }
+    }
+
+    /// Returns the preferred (link-time) base address of the image, if known.
+    ///
+    /// This is the `ImageBase` from the PE optional header for Windows
+    /// profiles and the address of `_text` for Linux profiles. If the
+    /// profile doesn't record it, the link-time base of the
+    /// [`SymbolBase::VirtualAddress`] convention is returned.
+    ///
+    /// Under KASLR, the image is loaded at a different base; use
+    /// [`Profile::rebase_symbol`] with the runtime base to rebase a symbol.
+    pub fn preferred_base(&self) -> Option<u64> {
+        match (self.preferred_base, self.symbol_base()) {
+            (Some(preferred_base), _) => Some(preferred_base),
+            (None, SymbolBase::VirtualAddress { default_base }) => Some(default_base),
+            (None, SymbolBase::Rva) => None,
+        }
+    }
+
+    /// Finds a symbol by name and returns its address relative to the
+    /// image base.
+    pub fn symbol_rva(&self, symbol_name: &str) -> Option<u64> {
+        let value = self.find_symbol(symbol_name)?;
+
+        match self.symbol_base() {
+            SymbolBase::Rva => Some(value),
+            SymbolBase::VirtualAddress { default_base } => value.checked_sub(default_base),
+        }
+    }
+
+    /// Finds a symbol by name and returns its virtual address for an image
+    /// loaded at `actual_base`.
+    pub fn symbol_va(&self, symbol_name: &str, actual_base: u64) -> Option<u64> {
+        actual_base.checked_add(self.symbol_rva(symbol_name)?)
+    }
+
+    /// Finds a symbol by name and returns its virtual address for an image
+    /// loaded at `runtime_base` (e.g. under KASLR).
+    ///
+    /// Applies the arithmetic of the profile's [`SymbolBase`]: RVAs are
+    /// added to the runtime base, link-time virtual addresses are moved by
+    /// the difference between the runtime and link-time bases. Returns
+    /// `None` if the symbol isn't found or the address overflows.
+    pub fn rebase_symbol(&self, symbol_name: &str, runtime_base: u64) -> Option<u64> {
+        self.symbol_va(symbol_name, runtime_base)
+    }
+
     /// Finds an enum by name.
-    pub fn find_enum(&self, type_name: &str) -> Option<&Enum> {
+    pub fn find_enum(&self, type_name: &str) -> Option<&Enum<'_>> {
         self.types.enums.get(type_name)
     }
 
+    /// Returns an iterator over the enums, with their underlying types and
+    /// variants (name and value).
+    pub fn iter_enums_with_variants(
+        &self,
+    ) -> impl Iterator<Item = (&str, &Type<'_>, impl Iterator<Item = (&str, &Variant)>)> {
+        self.types.enums.iter().map(|(name, enum_)| {
+            let variants = enum_
+                .fields
+                .iter()
+                .map(|(name, variant)| (name.as_ref(), variant));
+
+            (name.as_ref(), &enum_.subtype, variants)
+        })
+    }
+
     /// Finds a struct by name.
-    pub fn find_struct(&self, type_name: &str) -> Option<&Struct> {
+    pub fn find_struct(&self, type_name: &str) -> Option<&Struct<'_>> {
         self.types.structs.get(type_name)
     }
+
+    /// Finds multiple structs by name, collecting the missing ones.
+    ///
+    /// Use [`StructLookup::require_all`] to fail if any struct is missing.
+    pub fn find_structs<'n>(
+        &self,
+        type_names: impl IntoIterator<Item = &'n str>,
+    ) -> StructLookup<'n, '_> {
+        Lookup::collect(type_names, |type_name| self.find_struct(type_name))
+    }
+
+    /// Finds a struct by name, ignoring the differences selected by
+    /// `relaxation` (e.g. `EPROCESS` finds `_EPROCESS`).
+    ///
+    /// An exact match is preferred. Fails if no struct matches exactly and
+    /// more than one matches after normalization. The normalized names
+    /// are indexed on the first call with each relaxation.
+    pub fn find_struct_relaxed(
+        &self,
+        type_name: &str,
+        relaxation: Relaxation,
+    ) -> Result<Option<RelaxedMatch<'_, 'a>>, AmbiguousName> {
+        self.relaxed_index
+            .find(&self.types.structs, type_name, relaxation)
+    }
+
+    /// Returns the names of the structs with a field of the given struct or
+    /// enum type (e.g. the structs that embed a `_LIST_ENTRY`).
+    ///
+    /// Only fields of the type itself match, pointers to it and arrays of it
+    /// don't.
+    pub fn structs_with_field_type(&self, type_name: &str) -> Vec<&str> {
+        self.types
+            .structs
+            .iter()
+            .filter(|(_, udt)| {
+                udt.fields.values().any(|field| match &field.type_ {
+                    Type::Struct(udt) => udt.name == type_name,
+                    Type::Enum(enum_) => enum_.name == type_name,
+                    _ => false,
+                })
+            })
+            .map(|(name, _)| name.as_ref())
+            .collect()
+    }
+
+    /// Renders a struct as a C declaration, with the fields in offset
+    /// order.
+    ///
+    /// Base types are rendered as fixed-width integer types (e.g.
+    /// `uint32_t`). With [`RenderOptions::annotations`], the output can be
+    /// compared with the source of the struct.
+    pub fn render_c(&self, type_name: &str, options: RenderOptions) -> Option<String> {
+        render::render_struct(self, type_name, options)
+    }
+
+    /// Returns where a struct or enum was defined.
+    ///
+    /// Structs are looked up first. Returns `None` if the profile was
+    /// created without [`ProfileOptions::record_provenance`].
+    ///
+    /// [`ProfileOptions::record_provenance`]: crate::ProfileOptions::record_provenance
+    pub fn type_provenance(&self, type_name: &str) -> Option<&Provenance> {
+        let provenance = &self.types.provenance;
+        provenance
+            .structs
+            .get(type_name)
+            .or_else(|| provenance.enums.get(type_name))
+    }
+
+    /// Finds a field declared directly in a struct.
+    ///
+    /// Fields of nested structs are not searched.
+    pub fn core_field(&self, type_name: &str, field_name: &str) -> Option<&Field<'_>> {
+        self.find_struct(type_name)?.fields.get(field_name)
+    }
+
+    /// Returns the backing type of a bitfield (e.g. `unsigned int` for
+    /// `unsigned int Flag : 1`).
+    ///
+    /// Fields of nested anonymous structs are found as well. Returns `None`
+    /// if the field doesn't exist or isn't a bitfield.
+    pub fn bitfield_subtype(&self, type_name: &str, field_name: &str) -> Option<&Type<'_>> {
+        match &self.find_nested_field(type_name, field_name)?.field.type_ {
+            Type::Bitfield(bitfield) => Some(&bitfield.subtype),
+            _ => None,
+        }
+    }
+
+    /// Finds a field in a struct or in its nested structs.
+    ///
+    /// Returns the name of the struct that directly contains the field and
+    /// the offset of the field within that struct (not within
+    /// `type_name`).
+    pub fn find_field_in(&self, type_name: &str, field_name: &str) -> Option<(&str, u64)> {
+        let nested = self.find_nested_field(type_name, field_name)?;
+        Some((nested.parent, nested.field.offset))
+    }
+
+    /// Finds a field in a struct or in its nested structs.
+    ///
+    /// The fields of the struct are searched first, then the fields of its
+    /// nested structs, depth first in declaration order.
+    pub fn find_nested_field(&self, type_name: &str, field_name: &str) -> Option<NestedField<'_>> {
+        self.find_nested_field_by(type_name, &|udt| udt.fields.get_key_value(field_name))
+    }
+
+    /// Same as [`find_nested_field`], but finds the first field whose name
+    /// satisfies `matches`.
+    ///
+    /// [`find_nested_field`]: Self::find_nested_field
+    pub fn find_nested_field_matching(
+        &self,
+        type_name: &str,
+        matches: impl Fn(&str) -> bool,
+    ) -> Option<NestedField<'_>> {
+        self.find_nested_field_by(type_name, &|udt| {
+            udt.fields.iter().find(|(name, _)| matches(name))
+        })
+    }
+
+    /// Returns the offset of a (possibly nested) member within a struct.
+    ///
+    /// The `field_path` is a dot-separated path of field names, e.g.
+    /// `Pcb.ProcessListEntry`. Fields of nested anonymous structs are found
+    /// as well. Subtracting the offset from the address of the member gives
+    /// the address of the containing struct.
+    pub fn container_of(&self, type_name: &str, field_path: &str) -> Result<u64, FieldPathError> {
+        let mut type_name = type_name;
+        let mut offset = 0;
+        let mut fields = field_path.split('.').peekable();
+
+        while let Some(field_name) = fields.next() {
+            if self.find_struct(type_name).is_none() {
+                return Err(FieldPathError::TypeNotFound(type_name.to_owned()));
+            }
+
+            let nested = match self.find_nested_field(type_name, field_name) {
+                Some(nested) => nested,
+                None => {
+                    return Err(FieldPathError::FieldNotFound {
+                        type_name: type_name.to_owned(),
+                        field_name: field_name.to_owned(),
+                    })
+                }
+            };
+
+            offset += nested.offset;
+
+            if fields.peek().is_some() {
+                type_name = match &nested.field.type_ {
+                    Type::Struct(udt) => &udt.name,
+                    _ => {
+                        return Err(FieldPathError::NotAStruct {
+                            type_name: type_name.to_owned(),
+                            field_name: field_name.to_owned(),
+                        })
+                    }
+                };
+            }
+        }
+
+        Ok(offset)
+    }
+
+    /// Finds an anonymous struct or union member of a struct by its index
+    /// among the anonymous members, in declaration order.
+    ///
+    /// The backends synthesize the names of anonymous members (e.g.
+    /// `__unnamed_field_3`), which may change between builds. The index
+    /// doesn't depend on the naming. Returns the synthesized name and the
+    /// member.
+    pub fn anon_member(&self, parent: &str, index: usize) -> Option<(&str, &Field<'_>)> {
+        self.find_struct(parent)?
+            .fields_in_declaration_order()
+            .filter(|(name, field)| is_anonymous_member(name, field))
+            .nth(index)
+    }
+
+    /// Finds a field with `find` in a struct or in its nested structs.
+    fn find_nested_field_by<'s>(
+        &'s self,
+        type_name: &str,
+        find: &dyn Fn(&'s Struct<'a>) -> Option<(&'s Cow<'a, str>, &'s Field<'a>)>,
+    ) -> Option<NestedField<'s>> {
+        let (parent, udt) = self.types.structs.get_key_value(type_name)?;
+
+        if let Some((name, field)) = find(udt) {
+            return Some(NestedField {
+                parent,
+                name,
+                offset: field.offset,
+                field,
+            });
+        }
+
+        for field in udt.fields.values() {
+            let udt = match &field.type_ {
+                Type::Struct(udt) => udt,
+                _ => continue,
+            };
+
+            if let Some(nested) = self.find_nested_field_by(&udt.name, find) {
+                return Some(NestedField {
+                    offset: field.offset + nested.offset,
+                    ..nested
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Returns the names of the given structs and enums, and of all the
+    /// structs and enums they transitively reference.
+    ///
+    /// References through fields, pointers, arrays and bitfields are
+    /// followed. Names that aren't in the profile are ignored.
+    pub fn closure(&self, structs: &[&str]) -> HashSet<String> {
+        self.reachable_types(structs).into_types()
+    }
+
+    /// Returns the structs and enums reachable from the given roots.
+    ///
+    /// Follows field types, array, pointer and bitfield subtypes, and enum
+    /// subtypes. Referenced names that aren't in the profile are reported
+    /// by [`ReachableSet::missing`].
+    pub fn reachable_types(&self, roots: &[&str]) -> ReachableSet {
+        ReachableSet::new(&self.types, roots, None)
+    }
+
+    /// Same as [`reachable_types`], but visits only the types at most
+    /// `max_depth` references away from the roots.
+    ///
+    /// [`reachable_types`]: Self::reachable_types
+    pub fn reachable_types_with_depth(&self, roots: &[&str], max_depth: usize) -> ReachableSet {
+        ReachableSet::new(&self.types, roots, Some(max_depth))
+    }
+}
+
+/// Checks whether a field is an anonymous struct or union member, i.e. a
+/// struct field with a synthesized name.
+fn is_anonymous_member(name: &str, field: &Field) -> bool {
+    matches!(field.type_, Type::Struct(_))
+        && (name.starts_with("__unnamed") || name.starts_with("__anonymous_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ArrayRef, BitfieldRef, Enum, EnumRef, PointerRef, StructKind, StructRef};
+
+    fn profile(symbols: &[(&'static str, u64)]) -> Profile<'static> {
+        let symbols = symbols
+            .iter()
+            .map(|&(name, value)| (Cow::Borrowed(name), value))
+            .collect();
+
+        Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), Types::default())
+    }
+
+    #[test]
+    fn rebase_rva() {
+        // Windows profiles store RVAs.
+        let profile = profile(&[("PsActiveProcessHead", 0x43_7bc0)])
+            .with_symbol_base(SymbolBase::Rva)
+            .with_preferred_base(0x1_4000_0000);
+
+        assert_eq!(profile.preferred_base(), Some(0x1_4000_0000));
+        assert_eq!(
+            profile.rebase_symbol("PsActiveProcessHead", 0xfffff800_1b000000),
+            Some(0xfffff800_1b437bc0)
+        );
+
+        // Loaded at the preferred base.
+        assert_eq!(
+            profile.rebase_symbol("PsActiveProcessHead", 0x1_4000_0000),
+            Some(0x1_4043_7bc0)
+        );
+
+        assert_eq!(profile.rebase_symbol("MmPfnDatabase", 0x1_4000_0000), None);
+        assert_eq!(profile.rebase_symbol("PsActiveProcessHead", u64::MAX), None);
+    }
+
+    #[test]
+    fn rebase_virtual_address() {
+        // Linux profiles store link-time virtual addresses.
+        let profile = profile(&[
+            ("_text", 0xffffffff_81000000),
+            ("init_task", 0xffffffff_82a0c940),
+        ]);
+
+        assert_eq!(
+            profile.symbol_base(),
+            SymbolBase::VirtualAddress {
+                default_base: 0xffffffff_81000000
+            }
+        );
+        assert_eq!(profile.preferred_base(), Some(0xffffffff_81000000));
+
+        // KASLR slide of 0x2e000000.
+        assert_eq!(
+            profile.rebase_symbol("init_task", 0xffffffff_af000000),
+            Some(0xffffffff_b0a0c940)
+        );
+        assert_eq!(
+            profile.rebase_symbol("_text", 0xffffffff_af000000),
+            Some(0xffffffff_af000000)
+        );
+
+        // Loaded at the link-time base.
+        assert_eq!(
+            profile.rebase_symbol("init_task", 0xffffffff_81000000),
+            Some(0xffffffff_82a0c940)
+        );
+    }
+
+    #[test]
+    fn rebase_below_base() {
+        // Symbols below the link-time base can't be rebased.
+        let profile = profile(&[("_text", 0xffffffff_81000000), ("early", 0x1000)]);
+        assert_eq!(profile.rebase_symbol("early", 0xffffffff_af000000), None);
+    }
+
+    #[test]
+    fn preferred_base_without_metadata() {
+        let profile = profile(&[("PsActiveProcessHead", 0x43_7bc0)]);
+        assert_eq!(profile.preferred_base(), None);
+
+        // Profiles created before the preferred base was recorded.
+        let json = r#"{"architecture":"Amd64","symbols":{"_text":18446744071578845184},"types":{"enums":{},"structs":{}}}"#;
+        let profile = serde_json::from_str::<Profile>(json).unwrap();
+        assert_eq!(profile.preferred_base(), Some(0xffffffff_81000000));
+    }
+
+    #[test]
+    fn find_symbol_any() {
+        let profile = profile(&[("KiSystemCall64", 0x1000), ("KiSystemCall64Shadow", 0x2000)]);
+
+        // The first name found wins, regardless of the symbol order.
+        assert_eq!(
+            profile.find_symbol_any(&["KiSystemCall64Shadow", "KiSystemCall64"]),
+            Some(("KiSystemCall64Shadow", 0x2000))
+        );
+        assert_eq!(
+            profile.find_symbol_any(&["KiSystemCall", "KiSystemCall64"]),
+            Some(("KiSystemCall64", 0x1000))
+        );
+        assert_eq!(profile.find_symbol_any(&["KiSystemCall"]), None);
+        assert_eq!(profile.find_symbol_any(&[]), None);
+    }
+
+    #[test]
+    fn symbols_jsonl() {
+        let profile = profile(&[("PsActiveProcessHead", 0x43_7bc0), ("\"quoted\"", 0x10)]);
+
+        let mut output = Vec::new();
+        profile.write_symbols_jsonl(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                r#"{"name":"PsActiveProcessHead","address":4422592}"#,
+                "\n",
+                r#"{"name":"\"quoted\"","address":16}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn source() {
+        let profile = profile(&[("PsActiveProcessHead", 0x43_7bc0)]);
+        assert_eq!(profile.source(), None);
+
+        let profile = profile.with_source(ProfileSource::Pdb);
+        assert_eq!(profile.source(), Some(ProfileSource::Pdb));
+
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(json.contains(r#""source":"pdb""#), "{json}");
+        let profile = serde_json::from_str::<Profile>(&json).unwrap();
+        assert_eq!(profile.source(), Some(ProfileSource::Pdb));
+
+        // Profiles created before the source was recorded.
+        let json = r#"{"architecture":"Amd64","symbols":{},"types":{"enums":{},"structs":{}}}"#;
+        let profile = serde_json::from_str::<Profile>(json).unwrap();
+        assert_eq!(profile.source(), None);
+    }
+
+    #[test]
+    fn iter_enums_with_variants() {
+        let mut types = Types::default();
+        types.enums.insert(
+            Cow::Borrowed("_POOL_TYPE"),
+            Enum::new(
+                Type::Base(BaseRef::I32),
+                [("NonPagedPool", 0), ("PagedPool", 1), ("Invalid", -1)]
+                    .into_iter()
+                    .map(|(name, value)| (Cow::Borrowed(name), Variant::I32(value)))
+                    .collect(),
+            ),
+        );
+        let profile = Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types);
+
+        let enums = profile
+            .iter_enums_with_variants()
+            .map(|(name, subtype, variants)| {
+                let variants = variants
+                    .map(|(name, value)| (name, value.as_i128()))
+                    .collect::<Vec<_>>();
+                (name, matches!(subtype, Type::Base(BaseRef::I32)), variants)
+            })
+            .collect::<Vec<_>>();
+
+        // The variants are in declaration order.
+        assert_eq!(
+            enums,
+            [(
+                "_POOL_TYPE",
+                true,
+                vec![("NonPagedPool", 0), ("PagedPool", 1), ("Invalid", -1)]
+            )]
+        );
+    }
+
+    fn udt(size: u64, fields: Vec<(&'static str, u64, Type<'static>)>) -> Struct<'static> {
+        Struct {
+            kind: StructKind::Struct,
+            size,
+            fields: fields
+                .into_iter()
+                .map(|(name, offset, type_)| (Cow::Borrowed(name), Field { offset, type_ }))
+                .collect(),
+            incomplete: false,
+        }
+    }
+
+    fn struct_ref(name: &'static str) -> Type<'static> {
+        Type::Struct(StructRef {
+            name: Cow::Borrowed(name),
+        })
+    }
+
+    fn process_profile() -> Profile<'static> {
+        let pointer = || {
+            Type::Pointer(PointerRef {
+                subtype: Box::new(struct_ref("_LIST_ENTRY")),
+            })
+        };
+
+        let mut types = Types::default();
+        types.structs.insert(
+            Cow::Borrowed("_LIST_ENTRY"),
+            udt(16, vec![("Flink", 0, pointer()), ("Blink", 8, pointer())]),
+        );
+        types.structs.insert(
+            Cow::Borrowed("_KPROCESS"),
+            udt(
+                0x438,
+                vec![("ProcessListEntry", 0xe8, struct_ref("_LIST_ENTRY"))],
+            ),
+        );
+        types.structs.insert(
+            Cow::Borrowed("__unnamed_1"),
+            udt(8, vec![("ExitStatus", 4, Type::Base(BaseRef::I32))]),
+        );
+        types.structs.insert(
+            Cow::Borrowed("_EPROCESS"),
+            udt(
+                0x800,
+                vec![
+                    ("Pcb", 0, struct_ref("_KPROCESS")),
+                    ("ActiveProcessLinks", 0x448, struct_ref("_LIST_ENTRY")),
+                    ("__unnamed_field_0", 0x460, struct_ref("__unnamed_1")),
+                ],
+            ),
+        );
+
+        Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+    }
+
+    #[test]
+    fn nested_field() {
+        let profile = process_profile();
+
+        let nested = profile
+            .find_nested_field("_EPROCESS", "ExitStatus")
+            .unwrap();
+        assert_eq!(nested.parent, "__unnamed_1");
+        assert_eq!(nested.name, "ExitStatus");
+        assert_eq!(nested.offset, 0x464);
+        assert_eq!(nested.field.offset, 4);
+
+        assert_eq!(
+            profile.find_field_in("_EPROCESS", "ExitStatus"),
+            Some(("__unnamed_1", 4))
+        );
+
+        // Fields of the struct itself come first.
+        let nested = profile.find_nested_field("_EPROCESS", "Pcb").unwrap();
+        assert_eq!((nested.parent, nested.offset), ("_EPROCESS", 0));
+
+        let nested = profile
+            .find_nested_field_matching("_EPROCESS", |name| name.eq_ignore_ascii_case("exitstatus"))
+            .unwrap();
+        assert_eq!((nested.name, nested.offset), ("ExitStatus", 0x464));
+
+        assert!(profile.find_nested_field("_EPROCESS", "Missing").is_none());
+        assert!(profile.find_nested_field("_MISSING", "Pcb").is_none());
+    }
+
+    #[test]
+    fn find_field_in() {
+        let profile = process_profile();
+
+        assert_eq!(
+            profile.find_field_in("_EPROCESS", "ActiveProcessLinks"),
+            Some(("_EPROCESS", 0x448))
+        );
+
+        // The offset is within the struct containing the field.
+        assert_eq!(
+            profile.find_field_in("_EPROCESS", "ProcessListEntry"),
+            Some(("_KPROCESS", 0xe8))
+        );
+        assert_eq!(
+            profile.find_field_in("_EPROCESS", "Blink"),
+            Some(("_LIST_ENTRY", 8))
+        );
+
+        assert_eq!(profile.find_field_in("_EPROCESS", "Missing"), None);
+        assert_eq!(profile.find_field_in("_MISSING", "Pcb"), None);
+    }
+
+    #[test]
+    fn container_of() {
+        let profile = process_profile();
+
+        assert_eq!(
+            profile.container_of("_EPROCESS", "ActiveProcessLinks"),
+            Ok(0x448)
+        );
+        assert_eq!(
+            profile.container_of("_EPROCESS", "Pcb.ProcessListEntry"),
+            Ok(0xe8)
+        );
+        assert_eq!(
+            profile.container_of("_EPROCESS", "Pcb.ProcessListEntry.Blink"),
+            Ok(0xf0)
+        );
+        assert_eq!(profile.container_of("_EPROCESS", "ExitStatus"), Ok(0x464));
+    }
+
+    #[test]
+    fn container_of_errors() {
+        let profile = process_profile();
+
+        assert_eq!(
+            profile.container_of("_MISSING", "Pcb"),
+            Err(FieldPathError::TypeNotFound("_MISSING".into()))
+        );
+        assert_eq!(
+            profile.container_of("_EPROCESS", "Pcb.Missing"),
+            Err(FieldPathError::FieldNotFound {
+                type_name: "_KPROCESS".into(),
+                field_name: "Missing".into(),
+            })
+        );
+        assert_eq!(
+            profile.container_of("_EPROCESS", "ExitStatus.Value"),
+            Err(FieldPathError::NotAStruct {
+                type_name: "_EPROCESS".into(),
+                field_name: "ExitStatus".into(),
+            })
+        );
+        assert_eq!(
+            profile
+                .container_of("_EPROCESS", "Pcb.Missing")
+                .unwrap_err()
+                .to_string(),
+            "Field not found: _KPROCESS.Missing"
+        );
+    }
+
+    #[test]
+    fn closure() {
+        let mut profile = process_profile();
+
+        // struct _POOL_TRACKER { _POOL_TYPE *Types[4]; };
+        profile.types.enums.insert(
+            Cow::Borrowed("_POOL_TYPE"),
+            Enum::new(Type::Base(BaseRef::I32), Default::default()),
+        );
+        let types = Type::Array(ArrayRef {
+            subtype: Box::new(Type::Pointer(PointerRef {
+                subtype: Box::new(Type::Enum(EnumRef {
+                    name: Cow::Borrowed("_POOL_TYPE"),
+                })),
+            })),
+            dims: [4].into_iter().collect(),
+            size: 4,
+        });
+        profile.types.structs.insert(
+            Cow::Borrowed("_POOL_TRACKER"),
+            udt(32, vec![("Types", 0, types)]),
+        );
+
+        let closure = |roots: &[&str]| {
+            let mut names = profile.closure(roots).into_iter().collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        // The self-referencing `_LIST_ENTRY` ends the walk.
+        assert_eq!(closure(&["_KPROCESS"]), ["_KPROCESS", "_LIST_ENTRY"]);
+        assert_eq!(
+            closure(&["_EPROCESS", "_MISSING"]),
+            ["_EPROCESS", "_KPROCESS", "_LIST_ENTRY", "__unnamed_1"]
+        );
+        assert_eq!(closure(&["_POOL_TRACKER"]), ["_POOL_TRACKER", "_POOL_TYPE"]);
+        assert!(closure(&["_MISSING"]).is_empty());
+    }
+
+    #[test]
+    fn reachable_types() {
+        let mut profile = process_profile();
+
+        // `_EX_PUSH_LOCK` isn't in the profile.
+        profile.types.structs.insert(
+            Cow::Borrowed("_HANDLE_TABLE"),
+            udt(
+                16,
+                vec![
+                    (
+                        "QuotaProcess",
+                        0,
+                        Type::Pointer(PointerRef {
+                            subtype: Box::new(struct_ref("_EPROCESS")),
+                        }),
+                    ),
+                    ("HandleTableLock", 8, struct_ref("_EX_PUSH_LOCK")),
+                ],
+            ),
+        );
+
+        fn sorted<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+            let mut names = names.collect::<Vec<_>>();
+            names.sort();
+            names
+        }
+
+        let reachable = profile.reachable_types(&["_HANDLE_TABLE", "_MISSING"]);
+        assert_eq!(reachable.len(), 5);
+        assert!(reachable.contains("__unnamed_1"));
+        assert_eq!(sorted(reachable.missing()), ["_EX_PUSH_LOCK", "_MISSING"]);
+
+        // The roots are at depth 0.
+        let reachable = profile.reachable_types_with_depth(&["_HANDLE_TABLE"], 0);
+        assert_eq!(sorted(reachable.iter()), ["_HANDLE_TABLE"]);
+        assert_eq!(reachable.missing().count(), 0);
+
+        let reachable = profile.reachable_types_with_depth(&["_HANDLE_TABLE"], 1);
+        assert_eq!(sorted(reachable.iter()), ["_EPROCESS", "_HANDLE_TABLE"]);
+        assert_eq!(reachable.missing().collect::<Vec<_>>(), ["_EX_PUSH_LOCK"]);
+
+        assert!(profile.reachable_types(&["_MISSING"]).is_empty());
+    }
+
+    #[test]
+    fn bitfield_subtype() {
+        let mut profile = process_profile();
+
+        let bitfield = Type::Bitfield(BitfieldRef {
+            subtype: Box::new(Type::Base(BaseRef::U32)),
+            bit_length: 1,
+            bit_position: 13,
+        });
+        profile
+            .types
+            .structs
+            .get_mut("__unnamed_1")
+            .unwrap()
+            .fields
+            .insert(
+                Cow::Borrowed("BreakOnTermination"),
+                Field {
+                    offset: 0,
+                    type_: bitfield,
+                },
+            );
+
+        // Also in a nested anonymous struct.
+        assert!(matches!(
+            profile.bitfield_subtype("_EPROCESS", "BreakOnTermination"),
+            Some(Type::Base(BaseRef::U32))
+        ));
+
+        // Not a bitfield.
+        assert!(profile
+            .bitfield_subtype("_EPROCESS", "ExitStatus")
+            .is_none());
+        assert!(profile.bitfield_subtype("_EPROCESS", "Missing").is_none());
+    }
+
+    #[test]
+    fn structs_with_field_type() {
+        let profile = process_profile();
+
+        // The pointers in `_LIST_ENTRY` itself don't count.
+        let mut names = profile.structs_with_field_type("_LIST_ENTRY");
+        names.sort();
+        assert_eq!(names, ["_EPROCESS", "_KPROCESS"]);
+
+        assert_eq!(
+            profile.structs_with_field_type("__unnamed_1"),
+            ["_EPROCESS"]
+        );
+        assert!(profile.structs_with_field_type("_EPROCESS").is_empty());
+    }
+
+    #[test]
+    fn anon_member() {
+        let mut profile = process_profile();
+        profile.types.structs.insert(
+            Cow::Borrowed("_KTHREAD"),
+            udt(
+                0x30,
+                vec![
+                    ("__unnamed_field_7", 0x20, struct_ref("__unnamed_1")),
+                    ("Header", 0, struct_ref("_LIST_ENTRY")),
+                    ("__anonymous_1", 0x10, struct_ref("__unnamed_1")),
+                    ("__unnamed_count", 0x18, Type::Base(BaseRef::U32)),
+                ],
+            ),
+        );
+
+        // Declaration order, named members and non-struct fields are
+        // skipped.
+        let (name, field) = profile.anon_member("_KTHREAD", 0).unwrap();
+        assert_eq!((name, field.offset), ("__unnamed_field_7", 0x20));
+        let (name, field) = profile.anon_member("_KTHREAD", 1).unwrap();
+        assert_eq!((name, field.offset), ("__anonymous_1", 0x10));
+        assert!(profile.anon_member("_KTHREAD", 2).is_none());
+
+        let (name, _) = profile.anon_member("_EPROCESS", 0).unwrap();
+        assert_eq!(name, "__unnamed_field_0");
+        assert!(profile.anon_member("_MISSING", 0).is_none());
+    }
+
+    #[test]
+    fn max_field_extent() {
+        let mut profile = process_profile();
+        assert_eq!(profile.max_field_extent("_KPROCESS"), Some(0xf8));
+        assert_eq!(profile.max_field_extent("_EPROCESS"), Some(0x468));
+        assert_eq!(profile.max_field_extent("_MISSING"), None);
+
+        // A flexible array member extends past the end of the structure,
+        // fields of unknown size are ignored.
+        let array = Type::Array(ArrayRef {
+            subtype: Box::new(Type::Base(BaseRef::U8)),
+            dims: [0].into_iter().collect(),
+            size: 0,
+        });
+        profile.types.structs.insert(
+            Cow::Borrowed("_MESSAGE"),
+            udt(
+                4,
+                vec![
+                    ("Length", 0, Type::Base(BaseRef::U32)),
+                    ("Data", 4, array),
+                    ("Unknown", 8, struct_ref("_MISSING")),
+                ],
+            ),
+        );
+        assert_eq!(profile.max_field_extent("_MESSAGE"), Some(5));
+
+        profile
+            .types
+            .structs
+            .insert(Cow::Borrowed("_EMPTY"), udt(0, vec![]));
+        assert_eq!(profile.max_field_extent("_EMPTY"), Some(0));
+    }
 }
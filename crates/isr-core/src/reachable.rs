@@ -0,0 +1,102 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::types::{Type, Types};
+
+/// Set of struct and enum names reachable from a set of root types.
+///
+/// Returned by [`Profile::reachable_types`].
+///
+/// [`Profile::reachable_types`]: crate::Profile::reachable_types
+#[derive(Debug, Default, Clone)]
+pub struct ReachableSet {
+    /// Reachable types present in the profile.
+    types: HashSet<String>,
+
+    /// Referenced types absent from the profile.
+    missing: HashSet<String>,
+}
+
+impl ReachableSet {
+    /// Walks the types reachable from `roots`.
+    ///
+    /// Roots are at depth 0. With `max_depth`, types further than
+    /// `max_depth` references from the roots aren't visited.
+    pub(crate) fn new(types: &Types, roots: &[&str], max_depth: Option<usize>) -> Self {
+        let mut result = Self::default();
+        let mut pending = roots.iter().map(|&name| (name, 0)).collect::<VecDeque<_>>();
+        let mut references = Vec::new();
+
+        while let Some((name, depth)) = pending.pop_front() {
+            if result.types.contains(name) || result.missing.contains(name) {
+                continue;
+            }
+
+            if let Some(enum_) = types.enums.get(name) {
+                collect_references(&enum_.subtype, &mut references);
+            }
+            else if let Some(udt) = types.structs.get(name) {
+                for field in udt.fields.values() {
+                    collect_references(&field.type_, &mut references);
+                }
+            }
+            else {
+                result.missing.insert(String::from(name));
+                continue;
+            }
+
+            result.types.insert(String::from(name));
+
+            if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                references.clear();
+                continue;
+            }
+
+            pending.extend(references.drain(..).map(|name| (name, depth + 1)));
+        }
+
+        result
+    }
+
+    /// Checks whether a type is reachable.
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.contains(name)
+    }
+
+    /// Returns an iterator over the names of the reachable types.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.types.iter().map(String::as_str)
+    }
+
+    /// Returns the number of reachable types.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Checks whether no type is reachable.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Returns an iterator over the names of the types that are referenced
+    /// (or requested as roots), but absent from the profile.
+    pub fn missing(&self) -> impl Iterator<Item = &str> {
+        self.missing.iter().map(String::as_str)
+    }
+
+    /// Returns the names of the reachable types.
+    pub fn into_types(self) -> HashSet<String> {
+        self.types
+    }
+}
+
+/// Collects the names of the structs and enums referenced by a type.
+fn collect_references<'a>(type_: &'a Type, references: &mut Vec<&'a str>) {
+    match type_ {
+        Type::Enum(enum_) => references.push(&enum_.name),
+        Type::Struct(udt) => references.push(&udt.name),
+        Type::Array(array) => collect_references(&array.subtype, references),
+        Type::Pointer(pointer) => collect_references(&pointer.subtype, references),
+        Type::Bitfield(bitfield) => collect_references(&bitfield.subtype, references),
+        Type::Base(_) | Type::Function => (),
+    }
+}
@@ -0,0 +1,203 @@
+use std::{borrow::Cow, collections::HashMap, sync::OnceLock};
+
+use indexmap::IndexMap;
+
+use crate::types::Struct;
+
+/// Differences in type names ignored by [`Profile::find_struct_relaxed`].
+///
+/// [`Profile::find_struct_relaxed`]: crate::Profile::find_struct_relaxed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Relaxation {
+    /// Leading underscores are ignored (`_EPROCESS` matches `EPROCESS`).
+    pub leading_underscores: bool,
+
+    /// ASCII case is ignored (`_EPROCESS` matches `_eprocess`).
+    pub ascii_case: bool,
+}
+
+impl Relaxation {
+    /// Ignores both leading underscores and ASCII case.
+    pub const ALL: Self = Self {
+        leading_underscores: true,
+        ascii_case: true,
+    };
+
+    /// Sets whether leading underscores are ignored.
+    pub fn with_leading_underscores(self, leading_underscores: bool) -> Self {
+        Self {
+            leading_underscores,
+            ..self
+        }
+    }
+
+    /// Sets whether ASCII case is ignored.
+    pub fn with_ascii_case(self, ascii_case: bool) -> Self {
+        Self { ascii_case, ..self }
+    }
+
+    /// Returns `true` if no difference is ignored.
+    pub fn is_exact(&self) -> bool {
+        !self.leading_underscores && !self.ascii_case
+    }
+
+    /// Returns the name with the ignored differences removed.
+    fn normalize(&self, name: &str) -> String {
+        let name = match self.leading_underscores {
+            true => name.trim_start_matches('_'),
+            false => name,
+        };
+
+        match self.ascii_case {
+            true => name.to_ascii_lowercase(),
+            false => name.to_owned(),
+        }
+    }
+
+    /// Returns the differences between two names that match under this
+    /// relaxation.
+    fn difference(&self, found: &str, requested: &str) -> Self {
+        let (found_trimmed, requested_trimmed) = (
+            found.trim_start_matches('_'),
+            requested.trim_start_matches('_'),
+        );
+
+        let leading_underscores = self.leading_underscores
+            && found.len() - found_trimmed.len() != requested.len() - requested_trimmed.len();
+
+        let ascii_case = self.ascii_case
+            && match self.leading_underscores {
+                true => found_trimmed != requested_trimmed,
+                false => found != requested,
+            };
+
+        Self {
+            leading_underscores,
+            ascii_case,
+        }
+    }
+
+    /// Returns the slot of the relaxation in the [`RelaxedIndex`].
+    fn slot(&self) -> Option<usize> {
+        match (self.leading_underscores, self.ascii_case) {
+            (false, false) => None,
+            (true, false) => Some(0),
+            (false, true) => Some(1),
+            (true, true) => Some(2),
+        }
+    }
+}
+
+/// Struct found by [`Profile::find_struct_relaxed`].
+///
+/// [`Profile::find_struct_relaxed`]: crate::Profile::find_struct_relaxed
+#[derive(Debug, Clone, Copy)]
+pub struct RelaxedMatch<'p, 'a> {
+    /// Name of the struct in the profile.
+    pub name: &'p str,
+
+    /// The struct.
+    pub udt: &'p Struct<'a>,
+
+    /// Differences that had to be ignored to match the requested name.
+    ///
+    /// [`Relaxation::is_exact`] for exact matches.
+    pub matched: Relaxation,
+}
+
+/// Error returned by [`Profile::find_struct_relaxed`] when more than one
+/// struct matches the requested name.
+///
+/// [`Profile::find_struct_relaxed`]: crate::Profile::find_struct_relaxed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmbiguousName {
+    /// Requested name.
+    pub name: String,
+
+    /// Names of the matching structs.
+    pub candidates: Vec<String>,
+}
+
+impl std::fmt::Display for AmbiguousName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Name {} is ambiguous, it matches {}",
+            self.name,
+            self.candidates.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for AmbiguousName {}
+
+/// Lazily built index of the normalized struct names, one per relaxation.
+///
+/// Maps a normalized name to the indices of the matching structs.
+#[derive(Default)]
+pub(crate) struct RelaxedIndex {
+    slots: [OnceLock<HashMap<String, Vec<usize>>>; 3],
+}
+
+impl RelaxedIndex {
+    /// Finds a struct by its normalized name.
+    ///
+    /// An exact match is preferred. Otherwise, fails if more than one
+    /// struct matches.
+    pub(crate) fn find<'p, 'a>(
+        &self,
+        structs: &'p IndexMap<Cow<'a, str>, Struct<'a>>,
+        name: &str,
+        relaxation: Relaxation,
+    ) -> Result<Option<RelaxedMatch<'p, 'a>>, AmbiguousName> {
+        if let Some((found, udt)) = structs.get_key_value(name) {
+            return Ok(Some(RelaxedMatch {
+                name: found,
+                udt,
+                matched: Relaxation::default(),
+            }));
+        }
+
+        let slot = match relaxation.slot() {
+            Some(slot) => slot,
+            None => return Ok(None),
+        };
+
+        let index = self.slots[slot].get_or_init(|| {
+            let mut index = HashMap::<_, Vec<_>>::new();
+            for (position, name) in structs.keys().enumerate() {
+                index
+                    .entry(relaxation.normalize(name))
+                    .or_default()
+                    .push(position);
+            }
+            index
+        });
+
+        match index.get(&relaxation.normalize(name)).map(Vec::as_slice) {
+            None | Some([]) => Ok(None),
+            Some(&[position]) => {
+                let (found, udt) = structs.get_index(position).unwrap();
+                Ok(Some(RelaxedMatch {
+                    name: found,
+                    udt,
+                    matched: relaxation.difference(found, name),
+                }))
+            }
+            Some(positions) => Err(AmbiguousName {
+                name: name.to_owned(),
+                candidates: positions
+                    .iter()
+                    .filter_map(|&position| structs.get_index(position))
+                    .map(|(found, _)| found.to_string())
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Debug for RelaxedIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelaxedIndex").finish_non_exhaustive()
+    }
+}
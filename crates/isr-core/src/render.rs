@@ -0,0 +1,357 @@
+use std::fmt::Write as _;
+
+use crate::{
+    types::{ArrayRef, BaseRef, StructKind, Type},
+    Profile, ProfileSource,
+};
+
+/// Options of [`Profile::render_c`].
+///
+/// [`Profile::render_c`]: crate::Profile::render_c
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Fields are annotated with their offset and size
+    /// (`// offset 0x10, size 8`), and the struct with its source file and
+    /// line if the profile records them.
+    pub annotations: bool,
+}
+
+impl RenderOptions {
+    /// Sets whether the fields and the struct are annotated.
+    pub fn with_annotations(self, annotations: bool) -> Self {
+        Self { annotations }
+    }
+}
+
+/// Renders a struct as a C declaration.
+pub(crate) fn render_struct(
+    profile: &Profile,
+    type_name: &str,
+    options: RenderOptions,
+) -> Option<String> {
+    let udt = profile.find_struct(type_name)?;
+    let mut result = String::new();
+
+    if options.annotations {
+        if let Some(provenance) = profile.type_provenance(type_name) {
+            match (&provenance.file, provenance.line) {
+                (Some(file), Some(line)) => writeln!(result, "// {file}:{line}").unwrap(),
+                (Some(file), None) => writeln!(result, "// {file}").unwrap(),
+                _ => {}
+            }
+        }
+    }
+
+    writeln!(result, "{} {type_name} {{", keyword(&udt.kind)).unwrap();
+
+    for (name, field) in udt.fields_in_offset_order() {
+        write!(result, "    {};", declaration(profile, &field.type_, name)).unwrap();
+
+        if options.annotations {
+            write!(result, " // offset {:#x}", field.offset).unwrap();
+
+            if let Some(size) = type_size(profile, &field.type_) {
+                write!(result, ", size {size}").unwrap();
+            }
+
+            if let Type::Bitfield(bitfield) = &field.type_ {
+                write!(result, ", bit {}", bitfield.bit_position).unwrap();
+            }
+        }
+
+        result.push('\n');
+    }
+
+    match options.annotations {
+        true => writeln!(result, "}}; // size {:#x}", udt.size).unwrap(),
+        false => writeln!(result, "}};").unwrap(),
+    }
+
+    Some(result)
+}
+
+/// Returns the declaration of `name` with the given type, e.g.
+/// `uint8_t (*name)[16]`.
+fn declaration(profile: &Profile, type_: &Type, name: &str) -> String {
+    match type_ {
+        Type::Base(base) => format!("{} {name}", base_name(base)),
+        Type::Enum(enum_) => format!("enum {} {name}", enum_.name),
+        Type::Struct(udt) => {
+            let keyword = profile
+                .find_struct(&udt.name)
+                .map(|udt| keyword(&udt.kind))
+                .unwrap_or("struct");
+
+            format!("{keyword} {} {name}", udt.name)
+        }
+        Type::Array(array) => {
+            let dims = array_dims(profile, array)
+                .iter()
+                .map(|dim| match dim {
+                    0 => String::from("[]"),
+                    dim => format!("[{dim}]"),
+                })
+                .collect::<String>();
+
+            declaration(profile, &array.subtype, &format!("{name}{dims}"))
+        }
+        Type::Pointer(pointer) => match &*pointer.subtype {
+            Type::Array(_) | Type::Function => {
+                declaration(profile, &pointer.subtype, &format!("(*{name})"))
+            }
+            subtype => declaration(profile, subtype, &format!("*{name}")),
+        },
+        Type::Bitfield(bitfield) => format!(
+            "{} : {}",
+            declaration(profile, &bitfield.subtype, name),
+            bitfield.bit_length
+        ),
+        Type::Function => format!("void {name}()"),
+    }
+}
+
+/// Returns the dimensions of an array.
+///
+/// PDB profiles record the size of the array in bytes instead.
+fn array_dims(profile: &Profile, array: &ArrayRef) -> Vec<u64> {
+    if profile.source() != Some(ProfileSource::Pdb) {
+        return array.dims.to_vec();
+    }
+
+    match profile.type_size(&array.subtype) {
+        Some(element_size) if element_size > 0 => vec![array.size / element_size],
+        _ => vec![0],
+    }
+}
+
+/// Returns the size of a field type in bytes, including all the elements
+/// of arrays.
+fn type_size(profile: &Profile, type_: &Type) -> Option<u64> {
+    match type_ {
+        Type::Array(array) => {
+            let element_size = type_size(profile, &array.subtype)?;
+            array_dims(profile, array)
+                .into_iter()
+                .try_fold(element_size, u64::checked_mul)
+        }
+        type_ => profile.type_size(type_),
+    }
+}
+
+/// Returns the C keyword of a struct kind.
+fn keyword(kind: &StructKind) -> &'static str {
+    match kind {
+        StructKind::Struct | StructKind::Interface => "struct",
+        StructKind::Class => "class",
+        StructKind::Union => "union",
+    }
+}
+
+/// Returns the C name of a base type.
+fn base_name(base: &BaseRef) -> &'static str {
+    match base {
+        BaseRef::Void => "void",
+        BaseRef::Bool => "bool",
+        BaseRef::Char => "char",
+        BaseRef::Wchar => "wchar_t",
+        BaseRef::I8 => "int8_t",
+        BaseRef::I16 => "int16_t",
+        BaseRef::I32 => "int32_t",
+        BaseRef::I64 => "int64_t",
+        BaseRef::I128 => "__int128",
+        BaseRef::U8 => "uint8_t",
+        BaseRef::U16 => "uint16_t",
+        BaseRef::U32 => "uint32_t",
+        BaseRef::U64 => "uint64_t",
+        BaseRef::U128 => "unsigned __int128",
+        BaseRef::F8 => "__fp8",
+        BaseRef::F16 => "_Float16",
+        BaseRef::F32 => "float",
+        BaseRef::F64 => "double",
+        BaseRef::F128 => "long double",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::{
+        types::{
+            BitfieldRef, Enum, EnumRef, Field, PointerRef, Provenance, Struct, StructRef, Types,
+            Variant,
+        },
+        Symbols,
+    };
+
+    fn udt(
+        kind: StructKind,
+        size: u64,
+        fields: Vec<(&'static str, u64, Type<'static>)>,
+    ) -> Struct<'static> {
+        Struct {
+            kind,
+            size,
+            fields: fields
+                .into_iter()
+                .map(|(name, offset, type_)| (Cow::Borrowed(name), Field { offset, type_ }))
+                .collect(),
+            incomplete: false,
+        }
+    }
+
+    fn pointer(subtype: Type<'static>) -> Type<'static> {
+        Type::Pointer(PointerRef {
+            subtype: Box::new(subtype),
+        })
+    }
+
+    fn array(subtype: BaseRef, dim: u64, size: u64) -> Type<'static> {
+        Type::Array(ArrayRef {
+            subtype: Box::new(Type::Base(subtype)),
+            dims: [dim].into_iter().collect(),
+            size,
+        })
+    }
+
+    fn struct_ref(name: &'static str) -> Type<'static> {
+        Type::Struct(StructRef {
+            name: Cow::Borrowed(name),
+        })
+    }
+
+    fn types() -> Types<'static> {
+        let mut types = Types::default();
+        types.enums.insert(
+            Cow::Borrowed("_POOL_TYPE"),
+            Enum::new(
+                Type::Base(BaseRef::I32),
+                [(Cow::Borrowed("NonPagedPool"), Variant::I32(0))]
+                    .into_iter()
+                    .collect(),
+            ),
+        );
+        types.structs.insert(
+            Cow::Borrowed("_DATA"),
+            udt(
+                StructKind::Union,
+                8,
+                vec![("Value", 0, Type::Base(BaseRef::U64))],
+            ),
+        );
+
+        // Declared out of offset order.
+        let flags = Type::Bitfield(BitfieldRef {
+            subtype: Box::new(Type::Base(BaseRef::U32)),
+            bit_length: 2,
+            bit_position: 3,
+        });
+        let pool_type = Type::Enum(EnumRef {
+            name: Cow::Borrowed("_POOL_TYPE"),
+        });
+        types.structs.insert(
+            Cow::Borrowed("_OBJECT"),
+            udt(
+                StructKind::Struct,
+                0x38,
+                vec![
+                    ("Data", 0x30, struct_ref("_DATA")),
+                    ("Next", 0, pointer(struct_ref("_OBJECT"))),
+                    ("Name", 8, array(BaseRef::U8, 16, 16)),
+                    ("Buffer", 0x18, pointer(array(BaseRef::U16, 4, 8))),
+                    ("Flags", 0x20, flags),
+                    ("Type", 0x24, pool_type),
+                    ("Callback", 0x28, pointer(Type::Function)),
+                ],
+            ),
+        );
+
+        types
+    }
+
+    fn profile(types: Types<'static>) -> Profile<'static> {
+        Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+    }
+
+    #[test]
+    fn declarations() {
+        let profile = profile(types());
+
+        let rendered = profile.render_c("_OBJECT", RenderOptions::default());
+        assert_eq!(
+            rendered.as_deref(),
+            Some(concat!(
+                "struct _OBJECT {\n",
+                "    struct _OBJECT *Next;\n",
+                "    uint8_t Name[16];\n",
+                "    uint16_t (*Buffer)[4];\n",
+                "    uint32_t Flags : 2;\n",
+                "    enum _POOL_TYPE Type;\n",
+                "    void (*Callback)();\n",
+                "    union _DATA Data;\n",
+                "};\n",
+            ))
+        );
+
+        assert!(profile
+            .render_c("_MISSING", RenderOptions::default())
+            .is_none());
+    }
+
+    #[test]
+    fn annotations() {
+        let mut types = types();
+        types.provenance.structs.insert(
+            Cow::Borrowed("_OBJECT"),
+            Provenance {
+                unit: Some(String::from("ob.c")),
+                file: Some(String::from("ob.h")),
+                line: Some(12),
+            },
+        );
+        let profile = profile(types);
+
+        let options = RenderOptions::default().with_annotations(true);
+        assert_eq!(
+            profile.render_c("_OBJECT", options).as_deref(),
+            Some(concat!(
+                "// ob.h:12\n",
+                "struct _OBJECT {\n",
+                "    struct _OBJECT *Next; // offset 0x0, size 8\n",
+                "    uint8_t Name[16]; // offset 0x8, size 16\n",
+                "    uint16_t (*Buffer)[4]; // offset 0x18, size 8\n",
+                "    uint32_t Flags : 2; // offset 0x20, size 4, bit 3\n",
+                "    enum _POOL_TYPE Type; // offset 0x24, size 4\n",
+                "    void (*Callback)(); // offset 0x28, size 8\n",
+                "    union _DATA Data; // offset 0x30, size 8\n",
+                "}; // size 0x38\n",
+            ))
+        );
+    }
+
+    #[test]
+    fn pdb_arrays() {
+        // The size of the array is in bytes.
+        let mut types = Types::default();
+        types.structs.insert(
+            Cow::Borrowed("_UNICODE_NAME"),
+            udt(
+                StructKind::Struct,
+                32,
+                vec![("Buffer", 0, array(BaseRef::U16, 32, 32))],
+            ),
+        );
+        let profile = profile(types).with_source(ProfileSource::Pdb);
+
+        let options = RenderOptions::default().with_annotations(true);
+        assert_eq!(
+            profile.render_c("_UNICODE_NAME", options).as_deref(),
+            Some(concat!(
+                "struct _UNICODE_NAME {\n",
+                "    uint16_t Buffer[16]; // offset 0x0, size 32\n",
+                "}; // size 0x20\n",
+            ))
+        );
+    }
+}
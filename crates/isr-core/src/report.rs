@@ -0,0 +1,111 @@
+use crate::Strictness;
+
+/// Summary of the profile creation.
+///
+/// Types defined more than once under the same name indicate that the
+/// profile may be less reliable, as only one of the definitions is kept.
+#[derive(Debug, Default, Clone)]
+pub struct ProfileReport {
+    /// Names of the enums that were defined more than once.
+    pub duplicate_enums: Vec<String>,
+
+    /// Names of the structs (and unions) that were defined more than once.
+    pub duplicate_structs: Vec<String>,
+
+    /// Type records that couldn't be parsed and were skipped.
+    ///
+    /// Only populated with [`ProfileOptions::partial_on_error`].
+    ///
+    /// [`ProfileOptions::partial_on_error`]: crate::ProfileOptions::partial_on_error
+    pub skipped_types: Vec<SkippedType>,
+
+    /// Offsets of the imported DWARF units that live in a supplementary
+    /// object file (e.g. created by `dwz -m`) and whose types weren't
+    /// collected.
+    pub supplementary_imports: Vec<u64>,
+
+    /// Lossy conversions, e.g. names that are not valid UTF-8 or types
+    /// that couldn't be represented.
+    ///
+    /// See [`ProfileOptions::strictness`].
+    ///
+    /// [`ProfileOptions::strictness`]: crate::ProfileOptions::strictness
+    pub warnings: Vec<Warning>,
+}
+
+/// A type record skipped during the profile creation.
+#[derive(Debug, Clone)]
+pub struct SkippedType {
+    /// Index of the type record, or `None` if the rest of the type stream
+    /// was skipped.
+    pub index: Option<u32>,
+
+    /// Why the record was skipped.
+    pub reason: String,
+}
+
+/// A lossy conversion during the profile creation.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    /// Name of the affected symbol, type or field.
+    pub name: String,
+
+    /// What was lost.
+    pub message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+impl ProfileReport {
+    /// Records a lossy conversion and logs it according to the
+    /// strictness.
+    pub fn warn(
+        &mut self,
+        strictness: Strictness,
+        name: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        let warning = Warning {
+            name: name.into(),
+            message: message.into(),
+        };
+
+        match strictness {
+            Strictness::Lenient => tracing::debug!(name = %warning.name, "{}", warning.message),
+            Strictness::Warn | Strictness::Strict => {
+                tracing::warn!(name = %warning.name, "{}", warning.message)
+            }
+        }
+
+        self.warnings.push(warning);
+    }
+
+    /// Returns the first lossy conversion if the strictness doesn't allow
+    /// any.
+    pub fn check_strictness(&self, strictness: Strictness) -> Result<(), Warning> {
+        match (strictness, self.warnings.first()) {
+            (Strictness::Strict, Some(warning)) => Err(warning.clone()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of duplicate type definitions.
+    pub fn duplicates(&self) -> usize {
+        self.duplicate_enums.len() + self.duplicate_structs.len()
+    }
+
+    /// Checks whether any type was defined more than once.
+    pub fn has_duplicates(&self) -> bool {
+        self.duplicates() > 0
+    }
+
+    /// Checks whether the profile is missing types that couldn't be parsed
+    /// or resolved.
+    pub fn is_partial(&self) -> bool {
+        !self.skipped_types.is_empty() || !self.supplementary_imports.is_empty()
+    }
+}
@@ -0,0 +1,130 @@
+//! Serialized representation of the enums of a profile.
+//!
+//! Human-readable formats (JSON) use internally tagged and untagged enums.
+//! Deserializing those requires a self-describing format, so the other
+//! formats (e.g. bincode) use externally tagged enums instead.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    symbols::SymbolBase,
+    types::{BaseRef, Type, Variant},
+};
+
+/// Implements `Serialize` and `Deserialize` with the `tagged`
+/// representation in human-readable formats and the `compact` one
+/// otherwise.
+macro_rules! impl_serde {
+    ($type:ident $(<$lifetime:lifetime>)?) => {
+        impl$(<$lifetime>)? Serialize for $type $(<$lifetime>)? {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                if serializer.is_human_readable() {
+                    tagged::$type::serialize(self, serializer)
+                }
+                else {
+                    compact::$type::serialize(self, serializer)
+                }
+            }
+        }
+
+        impl<'de $(: $lifetime, $lifetime)?> Deserialize<'de> for $type $(<$lifetime>)? {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                if deserializer.is_human_readable() {
+                    tagged::$type::deserialize(deserializer)
+                }
+                else {
+                    compact::$type::deserialize(deserializer)
+                }
+            }
+        }
+    };
+}
+
+impl_serde!(SymbolBase);
+impl_serde!(Type<'a>);
+impl_serde!(BaseRef);
+impl_serde!(Variant);
+
+/// Defines the representation of the enums, with the given attributes.
+macro_rules! representation {
+    ($name:ident, [$($symbol_base:tt)*], [$($type:tt)*], [$($base:tt)*], [$($variant:tt)*]) => {
+        mod $name {
+            use serde::{Deserialize, Serialize};
+
+            use crate::types::{ArrayRef, BitfieldRef, EnumRef, PointerRef, StructRef};
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(remote = "crate::symbols::SymbolBase", rename_all = "snake_case" $($symbol_base)*)]
+            pub enum SymbolBase {
+                Rva,
+                VirtualAddress { default_base: u64 },
+            }
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(remote = "crate::types::Type", rename_all = "snake_case" $($type)*)]
+            pub enum Type<'a> {
+                Base(crate::types::BaseRef),
+                Enum(#[serde(borrow)] EnumRef<'a>),
+                Struct(#[serde(borrow)] StructRef<'a>),
+                Array(#[serde(borrow)] ArrayRef<'a>),
+                Pointer(#[serde(borrow)] PointerRef<'a>),
+                Bitfield(#[serde(borrow)] BitfieldRef<'a>),
+                Function,
+            }
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(remote = "crate::types::BaseRef", rename_all = "snake_case" $($base)*)]
+            pub enum BaseRef {
+                Void,
+                Bool,
+                Char,
+                Wchar,
+                I8,
+                I16,
+                I32,
+                I64,
+                I128,
+                U8,
+                U16,
+                U32,
+                U64,
+                U128,
+                F8,
+                F16,
+                F32,
+                F64,
+                F128,
+            }
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(remote = "crate::types::Variant" $($variant)*)]
+            pub enum Variant {
+                U8(u8),
+                U16(u16),
+                U32(u32),
+                U64(u64),
+                U128(u128),
+                I8(i8),
+                I16(i16),
+                I32(i32),
+                I64(i64),
+                I128(i128),
+            }
+        }
+    };
+}
+
+representation!(
+    tagged,
+    [, tag = "kind"],
+    [, tag = "kind"],
+    [, tag = "subkind"],
+    [, untagged]
+);
+representation!(compact, [], [], [], []);
@@ -4,5 +4,132 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 /// Symbols.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Symbols<'p>(#[serde(borrow)] pub IndexMap<Cow<'p, str>, u64>);
+
+impl Symbols<'_> {
+    /// Converts the symbols into symbols that don't borrow from the input
+    /// they were decoded from.
+    pub fn into_owned(self) -> Symbols<'static> {
+        Symbols(
+            self.0
+                .into_iter()
+                .map(|(name, address)| (Cow::Owned(name.into_owned()), address))
+                .collect(),
+        )
+    }
+
+    /// Adds unversioned aliases for symbols with a default version.
+    ///
+    /// A symbol `foo@@VERSION` is additionally inserted as `foo`, unless
+    /// a symbol with that name already exists. The versioned names are
+    /// kept. Symbols with a non-default version (`foo@VERSION`) are left
+    /// alone.
+    ///
+    /// Returns the number of added aliases.
+    pub fn add_unversioned_aliases(&mut self) -> usize {
+        let aliases = self
+            .0
+            .iter()
+            .filter_map(|(name, &address)| {
+                let (name, _) = name.split_once("@@")?;
+                (!name.is_empty() && !self.0.contains_key(name))
+                    .then(|| (String::from(name), address))
+            })
+            .collect::<Vec<_>>();
+
+        let mut added = 0;
+        for (name, address) in aliases {
+            // The first versioned symbol wins.
+            if !self.0.contains_key(name.as_str()) {
+                self.0.insert(Cow::Owned(name), address);
+                added += 1;
+            }
+        }
+
+        added
+    }
+}
+
+/// Convention used for the symbol addresses of a profile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolBase {
+    /// Symbols are relative virtual addresses (RVAs) from the image base.
+    ///
+    /// Used by PDB profiles.
+    #[default]
+    Rva,
+
+    /// Symbols are absolute virtual addresses at the link-time base.
+    ///
+    /// Used by `System.map` based profiles, where `default_base` is the
+    /// address of the `_text` symbol.
+    VirtualAddress {
+        /// Link-time base address of the image.
+        default_base: u64,
+    },
+}
+
+/// Scope of the symbols included in a profile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolScope {
+    /// All symbols.
+    ///
+    /// For PDB profiles, these are the public symbols. For `System.map`
+    /// profiles, these are the global and local text and data symbols.
+    #[default]
+    All,
+
+    /// Only exported (global) symbols.
+    ///
+    /// For PDB profiles, these are the public symbols. For `System.map`
+    /// profiles, these are the global text and data symbols.
+    Exported,
+
+    /// No symbols.
+    None,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_aliases() {
+        let mut symbols = Symbols(
+            [
+                ("memcpy@@GLIBC_2.14", 0x1000),
+                ("memcpy@GLIBC_2.2.5", 0x2000),
+                ("open@@VERS_1", 0x3000),
+                ("open@@VERS_2", 0x4000),
+                ("read@@VERS_1", 0x5000),
+                ("read", 0x6000),
+                ("@@VERS_1", 0x7000),
+                ("write@VERS_1", 0x8000),
+            ]
+            .into_iter()
+            .map(|(name, address)| (Cow::Borrowed(name), address))
+            .collect(),
+        );
+
+        assert_eq!(symbols.add_unversioned_aliases(), 2);
+        assert_eq!(symbols.0.len(), 10);
+
+        // The versioned names are kept.
+        assert_eq!(symbols.0["memcpy@@GLIBC_2.14"], 0x1000);
+        assert_eq!(symbols.0["memcpy"], 0x1000);
+
+        // The first versioned symbol wins.
+        assert_eq!(symbols.0["open"], 0x3000);
+
+        // Existing symbols aren't replaced.
+        assert_eq!(symbols.0["read"], 0x6000);
+
+        // Non-default versions and empty names aren't aliased.
+        assert!(!symbols.0.contains_key("write"));
+        assert!(!symbols.0.contains_key(""));
+
+        assert_eq!(symbols.add_unversioned_aliases(), 0);
+    }
+}
@@ -3,7 +3,7 @@
 //! This module contains the types used to represent the data structures of the
 //! profile and symbols files.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, cmp::Ordering, collections::HashMap, sync::OnceLock};
 
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,113 @@ pub struct Types<'a> {
     pub enums: IndexMap<Cow<'a, str>, Enum<'a>>,
     #[serde(borrow)]
     pub structs: IndexMap<Cow<'a, str>, Struct<'a>>,
+
+    /// Where the types were defined.
+    ///
+    /// Only recorded with [`ProfileOptions::record_provenance`].
+    ///
+    /// [`ProfileOptions::record_provenance`]: crate::ProfileOptions::record_provenance
+    #[serde(borrow, default)]
+    pub provenance: TypeProvenance<'a>,
+}
+
+impl<'a> Types<'a> {
+    /// Adds an incomplete struct (size 0, no fields) for a type that is
+    /// only forward declared.
+    ///
+    /// Returns `false` if a struct with the same name already exists.
+    pub fn add_stub(&mut self, name: Cow<'a, str>, kind: StructKind) -> bool {
+        if self.structs.contains_key(&name) {
+            return false;
+        }
+
+        self.structs.insert(
+            name,
+            Struct {
+                kind,
+                size: 0,
+                fields: IndexMap::new(),
+                incomplete: true,
+            },
+        );
+
+        true
+    }
+
+    /// Converts the types into types that don't borrow from the input
+    /// they were decoded from.
+    pub fn into_owned(self) -> Types<'static> {
+        Types {
+            enums: owned_map(self.enums, Enum::into_owned),
+            structs: owned_map(self.structs, Struct::into_owned),
+            provenance: self.provenance.into_owned(),
+        }
+    }
+}
+
+/// Converts the names of a map into owned strings.
+fn owned_map<'a, V, W>(
+    map: IndexMap<Cow<'a, str>, V>,
+    into_owned: impl Fn(V) -> W,
+) -> IndexMap<Cow<'static, str>, W> {
+    map.into_iter()
+        .map(|(name, value)| (owned(name), into_owned(value)))
+        .collect()
+}
+
+/// Converts a string into an owned one.
+fn owned(name: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(name.into_owned())
+}
+
+//
+// Provenance
+//
+
+/// Definitions of the enums and structs, keyed by type name.
+///
+/// Empty in profiles created without
+/// [`ProfileOptions::record_provenance`]. Otherwise, each entry takes
+/// roughly the length of the unit and file names plus a few bytes.
+///
+/// [`ProfileOptions::record_provenance`]: crate::ProfileOptions::record_provenance
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TypeProvenance<'a> {
+    #[serde(borrow)]
+    pub enums: IndexMap<Cow<'a, str>, Provenance>,
+    #[serde(borrow)]
+    pub structs: IndexMap<Cow<'a, str>, Provenance>,
+}
+
+impl TypeProvenance<'_> {
+    /// Checks whether no provenance is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.enums.is_empty() && self.structs.is_empty()
+    }
+
+    /// Converts the provenance into one that doesn't borrow from the
+    /// input.
+    pub fn into_owned(self) -> TypeProvenance<'static> {
+        TypeProvenance {
+            enums: owned_map(self.enums, |provenance| provenance),
+            structs: owned_map(self.structs, |provenance| provenance),
+        }
+    }
+}
+
+/// Definition of a type, i.e. the one kept when the type is defined more
+/// than once.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Name of the compilation unit (DWARF) or module (PDB) defining the
+    /// type.
+    pub unit: Option<String>,
+
+    /// Source file declaring the type.
+    pub file: Option<String>,
+
+    /// Line of the declaration in the source file.
+    pub line: Option<u64>,
 }
 
 //
@@ -22,17 +129,180 @@ pub struct Types<'a> {
 //
 
 /// Enum type.
+///
+/// Enums whose variant values are `0..n` in declaration order are
+/// serialized as a list of names, see [`Enum::is_dense`].
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Enum<'a> {
     #[serde(borrow)]
     pub subtype: Type<'a>,
-    #[serde(borrow)]
+
+    /// Variants of the enum.
+    ///
+    /// The index used by [`Enum::name_of`] is built on its first call,
+    /// the variants shouldn't be modified afterwards.
+    #[serde(borrow, with = "enum_fields")]
     pub fields: IndexMap<Cow<'a, str>, Variant>,
+
+    /// Index of the first variant with each value.
+    #[serde(skip)]
+    names: OnceLock<HashMap<(bool, u128), usize>>,
+}
+
+impl<'a> Enum<'a> {
+    /// Creates an enum with the given underlying type and variants.
+    pub fn new(subtype: Type<'a>, fields: IndexMap<Cow<'a, str>, Variant>) -> Self {
+        Self {
+            subtype,
+            fields,
+            names: OnceLock::new(),
+        }
+    }
+
+    /// Converts the enum into one that doesn't borrow from the input.
+    pub fn into_owned(self) -> Enum<'static> {
+        Enum::new(
+            self.subtype.into_owned(),
+            owned_map(self.fields, |variant| variant),
+        )
+    }
+
+    /// Checks whether the variant values are `0..n` in declaration order.
+    pub fn is_dense(&self) -> bool {
+        enum_fields::is_dense(&self.fields)
+    }
+
+    /// Returns the value of a variant.
+    pub fn value_of(&self, name: &str) -> Option<&Variant> {
+        self.fields.get(name)
+    }
+
+    /// Returns the name of a variant with the given value.
+    ///
+    /// The first call builds an index of the values, the lookups are
+    /// constant-time. If several variants share the value, the first
+    /// declared one is returned.
+    pub fn name_of(&self, value: &Variant) -> Option<&str> {
+        let names = self.names.get_or_init(|| {
+            let mut names = HashMap::with_capacity(self.fields.len());
+            for (index, variant) in self.fields.values().enumerate() {
+                names.entry(variant.ordering_key()).or_insert(index);
+            }
+            names
+        });
+
+        let index = *names.get(&value.ordering_key())?;
+        self.fields.get_index(index).map(|(name, _)| name.as_ref())
+    }
+}
+
+/// Serialization of the enum variants.
+///
+/// In human-readable formats, dense enums are serialized as a list of
+/// names with implicit values, other enums as a map of names to values.
+/// Both forms are accepted when deserializing. Other formats always use
+/// the map, since telling the forms apart requires a self-describing
+/// format.
+mod enum_fields {
+    use std::{borrow::Cow, fmt};
+
+    use indexmap::IndexMap;
+    use serde::{
+        de::{MapAccess, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::Variant;
+
+    type Fields<'a> = IndexMap<Cow<'a, str>, Variant>;
+
+    /// Name of a variant, borrowed from the input if possible.
+    #[derive(Deserialize)]
+    struct Name<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    pub fn is_dense(fields: &Fields) -> bool {
+        !fields.is_empty()
+            && fields
+                .values()
+                .enumerate()
+                .all(|(index, variant)| *variant == Variant::U64(index as u64))
+    }
+
+    pub fn serialize<S>(fields: &Fields, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() && is_dense(fields) {
+            serializer.collect_seq(fields.keys())
+        }
+        else {
+            fields.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Fields<'de>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(FieldsVisitor)
+        }
+        else {
+            deserializer.deserialize_map(FieldsVisitor)
+        }
+    }
+
+    struct FieldsVisitor;
+
+    impl<'de> Visitor<'de> for FieldsVisitor {
+        type Value = Fields<'de>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map of variants or a list of variant names")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut fields = IndexMap::with_capacity(seq.size_hint().unwrap_or(0));
+
+            while let Some(Name(name)) = seq.next_element()? {
+                // Same as the smallest type the untagged map form decodes to.
+                let index = fields.len() as u64;
+                let variant = match index {
+                    0..=0xff => Variant::U8(index as u8),
+                    0x100..=0xffff => Variant::U16(index as u16),
+                    0x1_0000..=0xffff_ffff => Variant::U32(index as u32),
+                    _ => Variant::U64(index),
+                };
+
+                fields.insert(name, variant);
+            }
+
+            Ok(fields)
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut fields = IndexMap::with_capacity(map.size_hint().unwrap_or(0));
+
+            while let Some((Name(name), variant)) = map.next_entry()? {
+                fields.insert(name, variant);
+            }
+
+            Ok(fields)
+        }
+    }
 }
 
 /// Enum variant.
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
+///
+/// Variants are compared by their numeric value, regardless of the
+/// underlying integer type (`U8(5) == I32(5)`).
+#[derive(Debug, Clone, Copy)]
 pub enum Variant {
     U8(u8),
     U16(u16),
@@ -46,6 +316,93 @@ pub enum Variant {
     I128(i128),
 }
 
+impl Variant {
+    /// Returns the value of the variant as `u64`.
+    ///
+    /// Negative values are sign-extended and larger values are truncated.
+    pub fn as_u64(&self) -> u64 {
+        match *self {
+            Self::U8(value) => value as u64,
+            Self::U16(value) => value as u64,
+            Self::U32(value) => value as u64,
+            Self::U64(value) => value,
+            Self::U128(value) => value as u64,
+            Self::I8(value) => value as u64,
+            Self::I16(value) => value as u64,
+            Self::I32(value) => value as u64,
+            Self::I64(value) => value as u64,
+            Self::I128(value) => value as u64,
+        }
+    }
+
+    /// Returns the value of the variant as `i128`.
+    ///
+    /// `u128` values larger than `i128::MAX` wrap around.
+    pub fn as_i128(&self) -> i128 {
+        match *self {
+            Self::U8(value) => value as i128,
+            Self::U16(value) => value as i128,
+            Self::U32(value) => value as i128,
+            Self::U64(value) => value as i128,
+            Self::U128(value) => value as i128,
+            Self::I8(value) => value as i128,
+            Self::I16(value) => value as i128,
+            Self::I32(value) => value as i128,
+            Self::I64(value) => value as i128,
+            Self::I128(value) => value,
+        }
+    }
+
+    /// Returns the value of the variant as `u128`.
+    ///
+    /// Negative values are sign-extended.
+    pub fn as_u128(&self) -> u128 {
+        match *self {
+            Self::U8(value) => value as u128,
+            Self::U16(value) => value as u128,
+            Self::U32(value) => value as u128,
+            Self::U64(value) => value as u128,
+            Self::U128(value) => value,
+            Self::I8(value) => value as u128,
+            Self::I16(value) => value as u128,
+            Self::I32(value) => value as u128,
+            Self::I64(value) => value as u128,
+            Self::I128(value) => value as u128,
+        }
+    }
+
+    /// Returns a key ordering the variants by their numeric value.
+    ///
+    /// Negative values sort before non-negative ones. Reinterpreting
+    /// negative values as `u128` preserves their relative order.
+    fn ordering_key(&self) -> (bool, u128) {
+        (
+            self.as_i128() >= 0 || matches!(self, Self::U128(_)),
+            self.as_u128(),
+        )
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        self.ordering_key() == other.ordering_key()
+    }
+}
+
+impl Eq for Variant {}
+
+impl PartialOrd for Variant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Variant {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ordering_key().cmp(&other.ordering_key())
+    }
+}
+
 //
 // Struct
 //
@@ -57,6 +414,62 @@ pub struct Struct<'a> {
     pub size: u64,
     #[serde(borrow)]
     pub fields: IndexMap<Cow<'a, str>, Field<'a>>,
+
+    /// The struct is only forward declared, its size and fields are
+    /// unknown.
+    ///
+    /// See [`ProfileOptions::forward_declaration_stubs`].
+    ///
+    /// [`ProfileOptions::forward_declaration_stubs`]: crate::ProfileOptions::forward_declaration_stubs
+    #[serde(default)]
+    pub incomplete: bool,
+}
+
+impl<'a> Struct<'a> {
+    /// Converts the struct into one that doesn't borrow from the input.
+    pub fn into_owned(self) -> Struct<'static> {
+        Struct {
+            kind: self.kind,
+            size: self.size,
+            fields: owned_map(self.fields, Field::into_owned),
+            incomplete: self.incomplete,
+        }
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Checks whether the struct has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns an iterator over the field names in declaration order.
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.keys().map(|name| name.as_ref())
+    }
+
+    /// Returns an iterator over the fields in declaration order.
+    ///
+    /// This is the order in which the fields were emitted by the producer.
+    pub fn fields_in_declaration_order(&self) -> impl Iterator<Item = (&str, &Field<'a>)> {
+        self.fields
+            .iter()
+            .map(|(name, field)| (name.as_ref(), field))
+    }
+
+    /// Returns the fields sorted by offset and bit position.
+    ///
+    /// Fields at the same position (e.g. members of a union) keep their
+    /// declaration order. The fields are collected and sorted on every call,
+    /// callers iterating the same struct repeatedly should keep the result.
+    pub fn fields_in_offset_order(&self) -> Vec<(&str, &Field<'a>)> {
+        let mut fields = self.fields_in_declaration_order().collect::<Vec<_>>();
+        fields.sort_by_key(|(_, field)| (field.offset, field.bit_position()));
+        fields
+    }
 }
 
 /// Struct kind.
@@ -87,39 +500,86 @@ pub struct Field<'a> {
     pub type_: Type<'a>,
 }
 
+impl Field<'_> {
+    /// Converts the field into one that doesn't borrow from the input.
+    pub fn into_owned(self) -> Field<'static> {
+        Field {
+            offset: self.offset,
+            type_: self.type_.into_owned(),
+        }
+    }
+
+    /// Returns the bit position of the field within its offset.
+    ///
+    /// This is zero for fields that aren't bitfields.
+    pub fn bit_position(&self) -> u64 {
+        match &self.type_ {
+            Type::Bitfield(bitfield) => bitfield.bit_position,
+            _ => 0,
+        }
+    }
+}
+
 //
 // Type
 //
 
 /// Type.
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case", tag = "kind")]
+#[derive(Debug)]
 pub enum Type<'a> {
     /// Base type.
     Base(BaseRef),
 
     /// Enum type.
-    Enum(#[serde(borrow)] EnumRef<'a>),
+    Enum(EnumRef<'a>),
 
     /// Struct type.
-    Struct(#[serde(borrow)] StructRef<'a>),
+    Struct(StructRef<'a>),
 
     /// Array type.
-    Array(#[serde(borrow)] ArrayRef<'a>),
+    Array(ArrayRef<'a>),
 
     /// Pointer type.
-    Pointer(#[serde(borrow)] PointerRef<'a>),
+    Pointer(PointerRef<'a>),
 
     /// Bitfield type.
-    Bitfield(#[serde(borrow)] BitfieldRef<'a>),
+    Bitfield(BitfieldRef<'a>),
 
     /// Function type.
     Function,
 }
 
+impl Type<'_> {
+    /// Converts the type into one that doesn't borrow from the input.
+    pub fn into_owned(self) -> Type<'static> {
+        match self {
+            Self::Base(base) => Type::Base(base),
+            Self::Enum(enum_) => Type::Enum(EnumRef {
+                name: owned(enum_.name),
+            }),
+            Self::Struct(udt) => Type::Struct(StructRef {
+                name: owned(udt.name),
+            }),
+            Self::Array(array) => Type::Array(ArrayRef {
+                subtype: Box::new(array.subtype.into_owned()),
+                dims: array.dims,
+                size: array.size,
+            }),
+            Self::Pointer(pointer) => Type::Pointer(PointerRef {
+                subtype: Box::new(pointer.subtype.into_owned()),
+            }),
+            Self::Bitfield(bitfield) => Type::Bitfield(BitfieldRef {
+                subtype: Box::new(bitfield.subtype.into_owned()),
+                bit_length: bitfield.bit_length,
+                bit_position: bitfield.bit_position,
+            }),
+            Self::Function => Type::Function,
+        }
+    }
+}
+
 /// Base type reference.
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case", tag = "subkind")]
+#[derive(Debug)]
 pub enum BaseRef {
     /// Void type.
     Void,
@@ -206,3 +666,175 @@ pub struct PointerRef<'a> {
     #[serde(borrow)]
     pub subtype: Box<Type<'a>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enum_(variants: &[(&'static str, Variant)]) -> Enum<'static> {
+        Enum::new(
+            Type::Base(BaseRef::I32),
+            variants
+                .iter()
+                .map(|&(name, value)| (Cow::Borrowed(name), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn name_of_dense() {
+        let names = (0..5000)
+            .map(|index| format!("NR_{index}"))
+            .collect::<Vec<_>>();
+        let enum_ = Enum::new(
+            Type::Base(BaseRef::U32),
+            names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (Cow::Borrowed(name.as_str()), Variant::U64(index as u64)))
+                .collect(),
+        );
+
+        assert!(enum_.is_dense());
+        assert_eq!(enum_.name_of(&Variant::U64(0)), Some("NR_0"));
+        assert_eq!(enum_.name_of(&Variant::U32(4999)), Some("NR_4999"));
+        assert_eq!(enum_.name_of(&Variant::U64(5000)), None);
+        assert_eq!(enum_.value_of("NR_1234"), Some(&Variant::U64(1234)));
+    }
+
+    #[test]
+    fn name_of_sparse() {
+        let enum_ = enum_(&[
+            ("Negative", Variant::I32(-1)),
+            ("Zero", Variant::I32(0)),
+            ("Large", Variant::U64(0x8000_0000)),
+        ]);
+
+        assert!(!enum_.is_dense());
+        assert_eq!(enum_.name_of(&Variant::I64(-1)), Some("Negative"));
+        assert_eq!(enum_.name_of(&Variant::U8(0)), Some("Zero"));
+        assert_eq!(enum_.name_of(&Variant::U32(0x8000_0000)), Some("Large"));
+        assert_eq!(enum_.name_of(&Variant::I32(1)), None);
+
+        // -1 isn't u64::MAX.
+        assert_eq!(enum_.name_of(&Variant::U64(u64::MAX)), None);
+    }
+
+    #[test]
+    fn name_of_duplicates() {
+        // The first declared variant wins, also when the value matches the
+        // index of a later variant.
+        let enum_ = enum_(&[
+            ("NonPagedPool", Variant::I64(0)),
+            ("NonPagedPoolExecute", Variant::I64(0)),
+            ("PagedPool", Variant::I64(1)),
+            ("NonPagedPoolMustSucceed", Variant::I64(2)),
+            ("DontUseThisType", Variant::I64(1)),
+        ]);
+
+        assert_eq!(enum_.name_of(&Variant::I64(0)), Some("NonPagedPool"));
+        assert_eq!(enum_.name_of(&Variant::I64(1)), Some("PagedPool"));
+        assert_eq!(
+            enum_.name_of(&Variant::I64(2)),
+            Some("NonPagedPoolMustSucceed")
+        );
+    }
+
+    #[test]
+    fn name_of_deserialized() {
+        let enum_ = enum_(&[("A", Variant::U64(0)), ("B", Variant::U64(7))]);
+        let json = serde_json::to_string(&enum_).unwrap();
+
+        let enum_ = serde_json::from_str::<Enum>(&json).unwrap();
+        assert_eq!(enum_.name_of(&Variant::U64(7)), Some("B"));
+        assert_eq!(enum_.into_owned().name_of(&Variant::U64(0)), Some("A"));
+    }
+
+    #[test]
+    fn variant_as_integers() {
+        assert_eq!(Variant::U8(0xff).as_i128(), 0xff);
+        assert_eq!(Variant::I8(-1).as_i128(), -1);
+        assert_eq!(Variant::U64(u64::MAX).as_i128(), u64::MAX as i128);
+        assert_eq!(Variant::I128(i128::MIN).as_i128(), i128::MIN);
+
+        // `u128` values above `i128::MAX` wrap around.
+        assert_eq!(Variant::U128(u128::MAX).as_i128(), -1);
+
+        assert_eq!(Variant::U32(7).as_u128(), 7);
+        assert_eq!(Variant::U128(u128::MAX).as_u128(), u128::MAX);
+
+        // Negative values are sign-extended.
+        assert_eq!(Variant::I8(-1).as_u128(), u128::MAX);
+        assert_eq!(Variant::I64(-2).as_u128(), u128::MAX - 1);
+    }
+
+    #[test]
+    fn variant_ordering() {
+        assert_eq!(Variant::U8(5), Variant::I32(5));
+        assert_eq!(Variant::I8(-1), Variant::I64(-1));
+        assert_ne!(Variant::I64(-1), Variant::U64(u64::MAX));
+        assert_ne!(Variant::I128(-1), Variant::U128(u128::MAX));
+
+        let mut variants = vec![
+            Variant::U128(u128::MAX),
+            Variant::U8(1),
+            Variant::I64(i64::MIN),
+            Variant::U64(0),
+            Variant::I8(-1),
+        ];
+        variants.sort();
+        assert_eq!(
+            variants,
+            [
+                Variant::I64(i64::MIN),
+                Variant::I8(-1),
+                Variant::U64(0),
+                Variant::U8(1),
+                Variant::U128(u128::MAX),
+            ]
+        );
+    }
+
+    #[test]
+    fn fields_in_offset_order() {
+        let bitfield = |bit_position| {
+            Type::Bitfield(BitfieldRef {
+                subtype: Box::new(Type::Base(BaseRef::U32)),
+                bit_length: 1,
+                bit_position,
+            })
+        };
+
+        // Declared out of order, with a union of `Flags` and `Value` and
+        // two bitfields sharing an offset.
+        let fields = [
+            ("Next", 16, Type::Base(BaseRef::U64)),
+            ("Flags", 0, Type::Base(BaseRef::U32)),
+            ("Value", 0, Type::Base(BaseRef::U64)),
+            ("High", 8, bitfield(31)),
+            ("Low", 8, bitfield(0)),
+        ];
+        let udt = Struct {
+            kind: StructKind::Struct,
+            size: 24,
+            fields: fields
+                .into_iter()
+                .map(|(name, offset, type_)| (Cow::Borrowed(name), Field { offset, type_ }))
+                .collect(),
+            incomplete: false,
+        };
+
+        assert_eq!(udt.len(), 5);
+        assert!(!udt.is_empty());
+
+        let declared = udt.field_names().collect::<Vec<_>>();
+        assert_eq!(declared, ["Next", "Flags", "Value", "High", "Low"]);
+
+        let sorted = udt
+            .fields_in_offset_order()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(sorted, ["Flags", "Value", "Low", "High", "Next"]);
+    }
+}
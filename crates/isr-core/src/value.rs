@@ -0,0 +1,472 @@
+use indexmap::IndexMap;
+
+use crate::{
+    types::{BaseRef, Type, Variant},
+    Profile,
+};
+
+/// Value decoded from memory according to a profile type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'p> {
+    /// Boolean value.
+    Bool(bool),
+
+    /// Character value.
+    Char(u8),
+
+    /// Signed integer value.
+    Signed(i128),
+
+    /// Unsigned integer value (including wide characters).
+    Unsigned(u128),
+
+    /// Floating-point value.
+    Float(f64),
+
+    /// Pointer value.
+    Pointer(u64),
+
+    /// Enum value, with the name of the matching variant (if any).
+    Enum {
+        /// Numeric value.
+        value: i128,
+
+        /// Name of the variant with this value.
+        variant: Option<&'p str>,
+    },
+
+    /// Nested struct value.
+    Struct(StructValue<'p>),
+}
+
+/// Fields of a struct decoded from memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructValue<'p> {
+    /// Name of the struct.
+    pub name: &'p str,
+
+    /// Decoded fields, in declaration order.
+    pub fields: IndexMap<&'p str, Value<'p>>,
+}
+
+/// Result of checking a buffer against the size of a struct (see
+/// [`Profile::check_struct_read`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructReadCheck {
+    /// Size of the struct in the profile.
+    pub expected: u64,
+
+    /// Length of the buffer.
+    pub actual: u64,
+}
+
+impl StructReadCheck {
+    /// Checks whether the buffer holds the whole struct.
+    pub fn is_complete(&self) -> bool {
+        self.actual >= self.expected
+    }
+
+    /// Returns how many bytes the buffer is short of the struct size.
+    pub fn missing(&self) -> u64 {
+        self.expected.saturating_sub(self.actual)
+    }
+}
+
+impl<'p> StructValue<'p> {
+    /// Returns the value of a field.
+    pub fn get(&self, field_name: &str) -> Option<&Value<'p>> {
+        self.fields.get(field_name)
+    }
+}
+
+impl<'a> Profile<'a> {
+    /// Decodes a value of the given type from the beginning of a buffer.
+    ///
+    /// Supports base types, enums, pointers and bitfields. Returns `None`
+    /// for other types, or if the buffer is too short. Nested structs are
+    /// decoded by [`read_struct_with_depth`].
+    ///
+    /// All supported architectures are little-endian.
+    ///
+    /// [`read_struct_with_depth`]: Self::read_struct_with_depth
+    pub fn read_value(&self, type_: &Type, bytes: &[u8]) -> Option<Value<'_>> {
+        match type_ {
+            Type::Base(base) => read_base(base, bytes),
+            Type::Enum(r) => {
+                let enum_ = self.find_enum(&r.name)?;
+                self.enum_value(&r.name, self.read_value(&enum_.subtype, bytes)?)
+            }
+            Type::Pointer(_) => match self.pointer_size() {
+                4 => Some(Value::Pointer(read_u128(bytes, 4)? as u64)),
+                _ => Some(Value::Pointer(read_u128(bytes, 8)? as u64)),
+            },
+            Type::Bitfield(r) => {
+                let size = self.type_size(&r.subtype)?;
+                let storage = read_u128(bytes, size)?;
+                let bits = storage.checked_shr(r.bit_position as u32).unwrap_or(0)
+                    & low_mask(r.bit_length);
+
+                let raw = match is_signed(&r.subtype) {
+                    true => Value::Signed(sign_extend(bits, r.bit_length)),
+                    false => Value::Unsigned(bits),
+                };
+
+                match (&*r.subtype, raw) {
+                    (Type::Enum(e), raw) => self.enum_value(&e.name, raw),
+                    (Type::Base(BaseRef::Bool), Value::Unsigned(bits)) => {
+                        Some(Value::Bool(bits != 0))
+                    }
+                    (_, raw) => Some(raw),
+                }
+            }
+            Type::Struct(_) | Type::Array(_) | Type::Function => None,
+        }
+    }
+
+    /// Checks whether a buffer is large enough to hold a struct, e.g. to
+    /// detect truncated reads before decoding the fields.
+    ///
+    /// Returns `None` if the struct is not found.
+    pub fn check_struct_read(&self, struct_name: &str, bytes: &[u8]) -> Option<StructReadCheck> {
+        Some(StructReadCheck {
+            expected: self.struct_size(struct_name)?,
+            actual: bytes.len() as u64,
+        })
+    }
+
+    /// Decodes the fields of a struct from a buffer.
+    ///
+    /// Scalar, pointer, enum and bitfield fields are decoded, nested
+    /// structs and arrays are skipped. Fields that don't fit into the
+    /// buffer are omitted. Returns `None` if the struct is not found.
+    pub fn read_struct(&self, struct_name: &str, bytes: &[u8]) -> Option<StructValue<'_>> {
+        self.read_struct_with_depth(struct_name, bytes, 0)
+    }
+
+    /// Decodes the fields of a struct from a buffer, recursing into nested
+    /// structs up to `max_depth` levels.
+    ///
+    /// See [`read_struct`].
+    ///
+    /// [`read_struct`]: Self::read_struct
+    pub fn read_struct_with_depth(
+        &self,
+        struct_name: &str,
+        bytes: &[u8],
+        max_depth: usize,
+    ) -> Option<StructValue<'_>> {
+        let (name, udt) = self.types().structs.get_key_value(struct_name)?;
+
+        let mut fields = IndexMap::with_capacity(udt.fields.len());
+        for (field_name, field) in &udt.fields {
+            let bytes = match usize::try_from(field.offset)
+                .ok()
+                .and_then(|offset| bytes.get(offset..))
+            {
+                Some(bytes) => bytes,
+                None => continue,
+            };
+
+            let value = match &field.type_ {
+                Type::Struct(r) if max_depth > 0 => self
+                    .read_struct_with_depth(&r.name, bytes, max_depth - 1)
+                    .map(Value::Struct),
+                type_ => self.read_value(type_, bytes),
+            };
+
+            if let Some(value) = value {
+                fields.insert(field_name.as_ref(), value);
+            }
+        }
+
+        Some(StructValue {
+            name: name.as_ref(),
+            fields,
+        })
+    }
+
+    /// Converts a decoded integer into a value of the given enum.
+    fn enum_value(&self, enum_name: &str, raw: Value) -> Option<Value<'_>> {
+        let value = match raw {
+            Value::Signed(value) => value,
+            Value::Unsigned(value) => value as i128,
+            _ => return None,
+        };
+
+        Some(Value::Enum {
+            value,
+            variant: self.find_enum(enum_name)?.name_of(&Variant::I128(value)),
+        })
+    }
+}
+
+fn read_base(base: &BaseRef, bytes: &[u8]) -> Option<Value<'static>> {
+    let value = match base {
+        BaseRef::Void => return None,
+        BaseRef::Bool => Value::Bool(*bytes.first()? != 0),
+        BaseRef::Char => Value::Char(*bytes.first()?),
+        BaseRef::Wchar | BaseRef::U16 => Value::Unsigned(read_u128(bytes, 2)?),
+        BaseRef::U8 => Value::Unsigned(read_u128(bytes, 1)?),
+        BaseRef::U32 => Value::Unsigned(read_u128(bytes, 4)?),
+        BaseRef::U64 => Value::Unsigned(read_u128(bytes, 8)?),
+        BaseRef::U128 => Value::Unsigned(read_u128(bytes, 16)?),
+        BaseRef::I8 => Value::Signed(sign_extend(read_u128(bytes, 1)?, 8)),
+        BaseRef::I16 => Value::Signed(sign_extend(read_u128(bytes, 2)?, 16)),
+        BaseRef::I32 => Value::Signed(sign_extend(read_u128(bytes, 4)?, 32)),
+        BaseRef::I64 => Value::Signed(sign_extend(read_u128(bytes, 8)?, 64)),
+        BaseRef::I128 => Value::Signed(read_u128(bytes, 16)? as i128),
+        BaseRef::F32 => Value::Float(f32::from_bits(read_u128(bytes, 4)? as u32) as f64),
+        BaseRef::F64 => Value::Float(f64::from_bits(read_u128(bytes, 8)? as u64)),
+
+        // No native representation.
+        BaseRef::F8 | BaseRef::F16 | BaseRef::F128 => return None,
+    };
+
+    Some(value)
+}
+
+/// Checks whether the type is a signed integer.
+fn is_signed(type_: &Type) -> bool {
+    matches!(
+        type_,
+        Type::Base(BaseRef::I8 | BaseRef::I16 | BaseRef::I32 | BaseRef::I64 | BaseRef::I128)
+    )
+}
+
+/// Reads a little-endian unsigned integer of `size` bytes.
+fn read_u128(bytes: &[u8], size: u64) -> Option<u128> {
+    let size = usize::try_from(size).ok().filter(|size| *size <= 16)?;
+    let bytes = bytes.get(..size)?;
+
+    let mut buffer = [0u8; 16];
+    buffer[..size].copy_from_slice(bytes);
+    Some(u128::from_le_bytes(buffer))
+}
+
+fn low_mask(bits: u64) -> u128 {
+    match bits {
+        0 => 0,
+        bits if bits >= 128 => u128::MAX,
+        bits => (1u128 << bits) - 1,
+    }
+}
+
+fn sign_extend(value: u128, bits: u64) -> i128 {
+    match bits {
+        0 => 0,
+        bits if bits >= 128 => value as i128,
+        bits => {
+            let shift = 128 - bits as u32;
+            ((value << shift) as i128) >> shift
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::{
+        types::{
+            ArrayRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Struct, StructKind, StructRef,
+            Types,
+        },
+        Symbols,
+    };
+
+    fn udt(size: u64, fields: Vec<(&'static str, u64, Type<'static>)>) -> Struct<'static> {
+        Struct {
+            kind: StructKind::Struct,
+            size,
+            fields: fields
+                .into_iter()
+                .map(|(name, offset, type_)| (Cow::Borrowed(name), Field { offset, type_ }))
+                .collect(),
+            incomplete: false,
+        }
+    }
+
+    fn bitfield(subtype: Type<'static>, bit_position: u64, bit_length: u64) -> Type<'static> {
+        Type::Bitfield(BitfieldRef {
+            subtype: Box::new(subtype),
+            bit_length,
+            bit_position,
+        })
+    }
+
+    fn profile() -> Profile<'static> {
+        let pool_type = || {
+            Type::Enum(EnumRef {
+                name: Cow::Borrowed("_POOL_TYPE"),
+            })
+        };
+
+        let mut types = Types::default();
+        types.enums.insert(
+            Cow::Borrowed("_POOL_TYPE"),
+            Enum::new(
+                Type::Base(BaseRef::I32),
+                [("NonPagedPool", 0), ("PagedPool", 1)]
+                    .into_iter()
+                    .map(|(name, value)| (Cow::Borrowed(name), Variant::I32(value)))
+                    .collect(),
+            ),
+        );
+        types.structs.insert(
+            Cow::Borrowed("_HEADER"),
+            udt(4, vec![("Size", 0, Type::Base(BaseRef::U32))]),
+        );
+        types.structs.insert(
+            Cow::Borrowed("_OBJECT"),
+            udt(
+                32,
+                vec![
+                    ("PoolType", 0, pool_type()),
+                    ("Flags", 4, bitfield(Type::Base(BaseRef::U32), 0, 3)),
+                    ("Delta", 4, bitfield(Type::Base(BaseRef::I32), 3, 4)),
+                    ("Enabled", 4, bitfield(Type::Base(BaseRef::Bool), 7, 1)),
+                    ("Kind", 4, bitfield(pool_type(), 8, 2)),
+                    (
+                        "Next",
+                        8,
+                        Type::Pointer(PointerRef {
+                            subtype: Box::new(Type::Base(BaseRef::Void)),
+                        }),
+                    ),
+                    ("Count", 16, Type::Base(BaseRef::I16)),
+                    (
+                        "Header",
+                        24,
+                        Type::Struct(StructRef {
+                            name: Cow::Borrowed("_HEADER"),
+                        }),
+                    ),
+                    (
+                        "Name",
+                        28,
+                        Type::Array(ArrayRef {
+                            subtype: Box::new(Type::Base(BaseRef::Char)),
+                            dims: [4].into_iter().collect(),
+                            size: 4,
+                        }),
+                    ),
+                ],
+            ),
+        );
+
+        Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+    }
+
+    fn object() -> Vec<u8> {
+        let mut bytes = vec![0u8; 32];
+        bytes[0..4].copy_from_slice(&1i32.to_le_bytes());
+        // Flags = 5, Delta = -2, Enabled, Kind = 1.
+        bytes[4..8].copy_from_slice(&(5u32 | (0b1110 << 3) | (1 << 7) | (1 << 8)).to_le_bytes());
+        bytes[8..16].copy_from_slice(&0xffff_a000_1234_5678u64.to_le_bytes());
+        bytes[16..18].copy_from_slice(&(-2i16).to_le_bytes());
+        bytes[24..28].copy_from_slice(&0x40u32.to_le_bytes());
+        bytes[28..32].copy_from_slice(b"Proc");
+        bytes
+    }
+
+    #[test]
+    fn read_struct() {
+        let profile = profile();
+        let value = profile.read_struct("_OBJECT", &object()).unwrap();
+        assert_eq!(value.name, "_OBJECT");
+
+        let pool_type = |value, variant| Value::Enum { value, variant };
+        assert_eq!(
+            value.fields.into_iter().collect::<Vec<_>>(),
+            [
+                ("PoolType", pool_type(1, Some("PagedPool"))),
+                ("Flags", Value::Unsigned(5)),
+                ("Delta", Value::Signed(-2)),
+                ("Enabled", Value::Bool(true)),
+                ("Kind", pool_type(1, Some("PagedPool"))),
+                ("Next", Value::Pointer(0xffff_a000_1234_5678)),
+                ("Count", Value::Signed(-2)),
+            ]
+        );
+
+        assert!(profile.read_struct("_MISSING", &object()).is_none());
+    }
+
+    #[test]
+    fn read_struct_with_depth() {
+        let profile = profile();
+        let value = profile
+            .read_struct_with_depth("_OBJECT", &object(), 1)
+            .unwrap();
+
+        let Some(Value::Struct(header)) = value.get("Header")
+        else {
+            panic!("missing header: {value:?}");
+        };
+        assert_eq!(header.name, "_HEADER");
+        assert_eq!(header.get("Size"), Some(&Value::Unsigned(0x40)));
+
+        // Arrays are still skipped.
+        assert!(value.get("Name").is_none());
+    }
+
+    #[test]
+    fn read_truncated_struct() {
+        let profile = profile();
+
+        // Only the fields within the buffer.
+        let value = profile.read_struct("_OBJECT", &object()[..12]).unwrap();
+        assert_eq!(
+            value.fields.keys().copied().collect::<Vec<_>>(),
+            ["PoolType", "Flags", "Delta", "Enabled", "Kind"]
+        );
+    }
+
+    #[test]
+    fn check_struct_read() {
+        let profile = profile();
+
+        let check = profile
+            .check_struct_read("_OBJECT", &object()[..12])
+            .unwrap();
+        assert_eq!((check.expected, check.actual), (32, 12));
+        assert!(!check.is_complete());
+        assert_eq!(check.missing(), 20);
+
+        // A larger buffer is complete too.
+        let check = profile.check_struct_read("_OBJECT", &[0; 48]).unwrap();
+        assert!(check.is_complete());
+        assert_eq!(check.missing(), 0);
+
+        assert!(profile.check_struct_read("_MISSING", &object()).is_none());
+    }
+
+    #[test]
+    fn read_value() {
+        let profile = profile();
+
+        let type_ = Type::Base(BaseRef::I8);
+        assert_eq!(profile.read_value(&type_, &[0xff]), Some(Value::Signed(-1)));
+
+        let type_ = Type::Base(BaseRef::F32);
+        let bytes = 1.5f32.to_le_bytes();
+        assert_eq!(profile.read_value(&type_, &bytes), Some(Value::Float(1.5)));
+
+        // An unknown variant.
+        let type_ = Type::Enum(EnumRef {
+            name: Cow::Borrowed("_POOL_TYPE"),
+        });
+        assert_eq!(
+            profile.read_value(&type_, &7i32.to_le_bytes()),
+            Some(Value::Enum {
+                value: 7,
+                variant: None
+            })
+        );
+
+        // Too short.
+        let type_ = Type::Base(BaseRef::U32);
+        assert_eq!(profile.read_value(&type_, &[1, 2]), None);
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Linux kernel version.
+///
+/// Represents the numeric part of a kernel release, e.g. `6.8.0-40` in
+/// `6.8.0-40-generic`. Versions are ordered by their components, so they
+/// can be compared directly (`version >= KernelVersion::new(5, 15, 0)`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct KernelVersion {
+    /// Major version.
+    pub major: u32,
+
+    /// Minor version.
+    pub minor: u32,
+
+    /// Patch level.
+    pub patch: u32,
+
+    /// ABI number (distribution specific), e.g. `40` in `6.8.0-40`.
+    pub abi: Option<u32>,
+}
+
+impl KernelVersion {
+    /// Creates a new kernel version without an ABI number.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            abi: None,
+        }
+    }
+
+    /// Sets the ABI number.
+    pub const fn with_abi(self, abi: u32) -> Self {
+        Self {
+            abi: Some(abi),
+            ..self
+        }
+    }
+
+    /// Parses a kernel release string.
+    ///
+    /// Accepts strings such as `6.8`, `6.8.12`, `6.8.0-40` and
+    /// `6.8.0-40-generic`. Anything following the ABI number is ignored.
+    pub fn parse(release: &str) -> Option<Self> {
+        let (version, rest) = match release.split_once('-') {
+            Some((version, rest)) => (version, Some(rest)),
+            None => (release, None),
+        };
+
+        let mut parts = version.split('.');
+        let major = parse_leading_number(parts.next()?)?;
+        let minor = parse_leading_number(parts.next()?)?;
+        let patch = match parts.next() {
+            Some(patch) => parse_leading_number(patch)?,
+            None => 0,
+        };
+
+        let abi = rest.and_then(|rest| {
+            let abi = rest.split(['-', '.', '+', '~']).next()?;
+            abi.parse().ok()
+        });
+
+        Some(Self {
+            major,
+            minor,
+            patch,
+            abi,
+        })
+    }
+}
+
+impl fmt::Display for KernelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+
+        if let Some(abi) = self.abi {
+            write!(f, "-{abi}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Windows build version.
+///
+/// Represents a Windows file version, e.g. `10.0.18362.356`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct WindowsBuild {
+    /// Major version.
+    pub major: u32,
+
+    /// Minor version.
+    pub minor: u32,
+
+    /// Build number.
+    pub build: u32,
+
+    /// Revision (update build revision).
+    pub revision: u32,
+}
+
+impl WindowsBuild {
+    /// Creates a new Windows build version.
+    pub const fn new(major: u32, minor: u32, build: u32, revision: u32) -> Self {
+        Self {
+            major,
+            minor,
+            build,
+            revision,
+        }
+    }
+
+    /// Parses a Windows version string.
+    ///
+    /// Accepts strings such as `10.0.18362` and `10.0.18362.356`.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let build = parts.next()?.parse().ok()?;
+        let revision = match parts.next() {
+            Some(revision) => revision.parse().ok()?,
+            None => 0,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            major,
+            minor,
+            build,
+            revision,
+        })
+    }
+}
+
+impl fmt::Display for WindowsBuild {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.revision
+        )
+    }
+}
+
+/// Parses the leading decimal digits of a string (e.g. `0rc1` -> `0`).
+fn parse_leading_number(value: &str) -> Option<u32> {
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+
+    value[..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_kernel_releases() {
+        assert_eq!(
+            KernelVersion::parse("6.8"),
+            Some(KernelVersion::new(6, 8, 0))
+        );
+        assert_eq!(
+            KernelVersion::parse("6.8.12"),
+            Some(KernelVersion::new(6, 8, 12))
+        );
+        assert_eq!(
+            KernelVersion::parse("6.8.0-40"),
+            Some(KernelVersion::new(6, 8, 0).with_abi(40))
+        );
+        assert_eq!(
+            KernelVersion::parse("6.8.0-40-generic"),
+            Some(KernelVersion::new(6, 8, 0).with_abi(40))
+        );
+        assert_eq!(
+            KernelVersion::parse("5.15.0-1034.38~20.04.1-azure"),
+            Some(KernelVersion::new(5, 15, 0).with_abi(1034))
+        );
+        assert_eq!(
+            KernelVersion::parse("6.9.0rc1"),
+            Some(KernelVersion::new(6, 9, 0))
+        );
+        assert_eq!(KernelVersion::parse("6"), None);
+        assert_eq!(KernelVersion::parse("generic"), None);
+    }
+
+    #[test]
+    fn kernel_release_ordering() {
+        let mut releases = [
+            "6.8.0-40-generic",
+            "5.4.0-150-generic",
+            "6.8.0-9-generic",
+            "6.8.0",
+            "5.15.0-91-generic",
+            "6.10.0-1-generic",
+            "5.4.0-26-generic",
+        ]
+        .map(|release| KernelVersion::parse(release).unwrap());
+
+        releases.sort();
+
+        assert_eq!(
+            releases.map(|version| version.to_string()),
+            [
+                "5.4.0-26",
+                "5.4.0-150",
+                "5.15.0-91",
+                "6.8.0",
+                "6.8.0-9",
+                "6.8.0-40",
+                "6.10.0-1",
+            ]
+        );
+
+        let version = KernelVersion::parse("6.8.0-40-generic").unwrap();
+        assert!(version >= KernelVersion::new(5, 15, 0));
+        assert!(version >= KernelVersion::new(6, 8, 0));
+        assert!(version < KernelVersion::new(6, 8, 1));
+        assert!(version < KernelVersion::new(6, 8, 0).with_abi(41));
+    }
+
+    #[test]
+    fn windows_build_ordering() {
+        assert_eq!(
+            WindowsBuild::parse("10.0.18362"),
+            Some(WindowsBuild::new(10, 0, 18362, 0))
+        );
+        assert_eq!(WindowsBuild::parse("10.0.18362.356.1"), None);
+        assert_eq!(WindowsBuild::parse("10.0"), None);
+
+        let mut builds = [
+            "10.0.22621.2428",
+            "6.1.7601.17514",
+            "10.0.18362.356",
+            "10.0.19041.1",
+        ]
+        .map(|build| WindowsBuild::parse(build).unwrap());
+
+        builds.sort();
+
+        assert_eq!(
+            builds.map(|build| build.to_string()),
+            [
+                "6.1.7601.17514",
+                "10.0.18362.356",
+                "10.0.19041.1",
+                "10.0.22621.2428",
+            ]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::LazyLock;
 
+use isr_core::KernelVersion;
 use regex::Regex;
 
 #[derive(Debug)]
@@ -15,6 +16,33 @@ pub struct UbuntuVersionSignature {
     pub mainline_kernel_version: String,
 }
 
+impl UbuntuVersionSignature {
+    /// Returns the kernel version (e.g. `6.8.0-40`) of the signature.
+    pub fn kernel_version(&self) -> Option<KernelVersion> {
+        let revision_short = match self.revision.split_once('.') {
+            Some((revision_short, _)) => revision_short,
+            None => &self.revision,
+        };
+
+        KernelVersion::parse(&format!("{}-{revision_short}", self.release))
+    }
+
+    /// Returns the mainline kernel version (e.g. `6.8.12`) of the signature.
+    pub fn mainline_kernel_version(&self) -> Option<KernelVersion> {
+        KernelVersion::parse(&self.mainline_kernel_version)
+    }
+}
+
+/// Compiler family used to build the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompilerKind {
+    /// GNU Compiler Collection.
+    Gcc,
+
+    /// LLVM Clang.
+    Clang,
+}
+
 /// Linux banner.
 #[derive(Debug)]
 pub struct LinuxBanner {
@@ -36,13 +64,16 @@ impl LinuxBanner {
         // (x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~22.04) 12.3.0, GNU ld (GNU Binutils for Ubuntu) 2.38)
         // #40~22.04.3-Ubuntu SMP PREEMPT_DYNAMIC Tue Jul 30 17:30:19 UTC 2 (Ubuntu 6.8.0-40.40~22.04.3-generic 6.8.12)
         //
+        // Clang-built kernels nest parentheses in the compiler string:
+        //
+        // (Android (8490178, based on r450784d) clang version 14.0.6 (https://android.googlesource.com/toolchain/llvm-project 4c603efb0cca074e9238af8b4106c30add4418f6), LLD 14.0.6)
+        //
 
         static LINUX_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
             Regex::new(concat!(
                 r"Linux version (?<UTS_RELEASE>[0-9]+\.[0-9]+\.[0-9]+[^ ]*) ",
                 r"\((?<LINUX_COMPILE_BY>[^@]*)@(?<LINUX_COMPILE_HOST>[^)]*)\) ",
-                r"\((?<LINUX_COMPILER>.*)\) ",
-                r"#(?<UTS_VERSION>.*)"
+                r"\(",
             ))
             .unwrap()
         });
@@ -52,17 +83,65 @@ impl LinuxBanner {
             None => return None,
         };
 
-        let version_signature = try_parse_ubuntu_signature(&captures["UTS_VERSION"]);
+        // The regex crate cannot match balanced parentheses, so the compiler
+        // string is extracted manually.
+        let rest = &banner[captures.get(0)?.end()..];
+        let (linux_compiler, rest) = split_balanced(rest)?;
+
+        let uts_version = rest.strip_prefix(" #")?;
+        let uts_version = match uts_version.find(['\r', '\n']) {
+            Some(end) => &uts_version[..end],
+            None => uts_version,
+        };
+
+        let version_signature = try_parse_ubuntu_signature(uts_version);
 
         Some(Self {
             uts_release: captures["UTS_RELEASE"].to_string(),
             linux_compile_by: captures["LINUX_COMPILE_BY"].to_string(),
             linux_compile_host: captures["LINUX_COMPILE_HOST"].to_string(),
-            linux_compiler: captures["LINUX_COMPILER"].to_string(),
-            uts_version: captures["UTS_VERSION"].to_string(),
+            linux_compiler: linux_compiler.to_string(),
+            uts_version: uts_version.to_string(),
             version_signature,
         })
     }
+
+    /// Returns the compiler family parsed from the `LINUX_COMPILER` string.
+    pub fn compiler_kind(&self) -> Option<CompilerKind> {
+        if self.linux_compiler.contains("clang") {
+            Some(CompilerKind::Clang)
+        }
+        else if self.linux_compiler.contains("gcc") {
+            Some(CompilerKind::Gcc)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the kernel version parsed from the `UTS_RELEASE` string.
+    pub fn kernel_version(&self) -> Option<KernelVersion> {
+        KernelVersion::parse(&self.uts_release)
+    }
+}
+
+/// Splits a string at the parenthesis closing an already opened one.
+///
+/// Returns the enclosed string and the remainder after the closing
+/// parenthesis.
+fn split_balanced(s: &str) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+
+    for (index, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some((&s[..index], &s[index + 1..])),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+
+    None
 }
 
 fn try_parse_ubuntu_signature(uts_version: &str) -> Option<LinuxVersionSignature> {
@@ -73,8 +152,8 @@ fn try_parse_ubuntu_signature(uts_version: &str) -> Option<LinuxVersionSignature
     static UBUNTU_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         Regex::new(concat!(
             r"\(Ubuntu ",
-            r"(?<UBUNTU_RELEASE>.*)-(?<UBUNTU_REVISION>.*)-(?<UBUNTU_KERNEL_FLAVOUR>.*) ",
-            r"(?<UBUNTU_MAINLINE_KERNEL_VERSION>.*)\)"
+            r"(?<UBUNTU_RELEASE>[^ -]+)-(?<UBUNTU_REVISION>[^ -]+)-(?<UBUNTU_KERNEL_FLAVOUR>[^ ]+) ",
+            r"(?<UBUNTU_MAINLINE_KERNEL_VERSION>[^ )]+)\)"
         ))
         .unwrap()
     });
@@ -91,3 +170,64 @@ fn try_parse_ubuntu_signature(uts_version: &str) -> Option<LinuxVersionSignature
         mainline_kernel_version: captures["UBUNTU_MAINLINE_KERNEL_VERSION"].into(),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcc() {
+        let banner = LinuxBanner::parse(concat!(
+            "Linux version 6.8.0-40-generic (buildd@lcy02-amd64-078) ",
+            "(x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~22.04) 12.3.0, GNU ld (GNU Binutils for Ubuntu) 2.38) ",
+            "#40~22.04.3-Ubuntu SMP PREEMPT_DYNAMIC Tue Jul 30 17:30:19 UTC 2 (Ubuntu 6.8.0-40.40~22.04.3-generic 6.8.12)\n",
+        ))
+        .unwrap();
+
+        assert_eq!(banner.uts_release, "6.8.0-40-generic");
+        assert_eq!(
+            banner.linux_compiler,
+            "x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~22.04) 12.3.0, GNU ld (GNU Binutils for Ubuntu) 2.38"
+        );
+        assert_eq!(banner.compiler_kind(), Some(CompilerKind::Gcc));
+        assert!(banner.uts_version.starts_with("40~22.04.3-Ubuntu SMP"));
+        assert!(banner.uts_version.ends_with("6.8.12)"));
+
+        let Some(LinuxVersionSignature::Ubuntu(signature)) = banner.version_signature
+        else {
+            panic!("missing Ubuntu signature");
+        };
+        assert_eq!(signature.release, "6.8.0");
+        assert_eq!(signature.revision, "40.40~22.04.3");
+        assert_eq!(signature.kernel_flavour, "generic");
+        assert_eq!(signature.mainline_kernel_version, "6.8.12");
+    }
+
+    #[test]
+    fn clang() {
+        let banner = LinuxBanner::parse(concat!(
+            "Linux version 5.15.94-android14-11 (build-user@build-host) ",
+            "(Android (8508608, based on r450784e) clang version 14.0.7 (https://android.googlesource.com/toolchain/llvm-project 4c603efb0cca074e9238af8b4106c30add4418f6), LLD 14.0.7) ",
+            "#1 SMP PREEMPT Mon Apr 3 12:00:00 UTC 2023",
+        ))
+        .unwrap();
+
+        assert_eq!(banner.uts_release, "5.15.94-android14-11");
+        assert!(banner.linux_compiler.starts_with("Android (8508608"));
+        assert!(banner.linux_compiler.ends_with("LLD 14.0.7"));
+        assert_eq!(banner.compiler_kind(), Some(CompilerKind::Clang));
+        assert_eq!(
+            banner.uts_version,
+            "1 SMP PREEMPT Mon Apr 3 12:00:00 UTC 2023"
+        );
+        assert!(banner.version_signature.is_none());
+    }
+
+    #[test]
+    fn unbalanced() {
+        let banner = LinuxBanner::parse(
+            "Linux version 6.8.0-40-generic (buildd@lcy02) (gcc (Ubuntu 12.3.0 #40 SMP",
+        );
+        assert!(banner.is_none());
+    }
+}
@@ -4,8 +4,11 @@ mod banner;
 mod error;
 pub mod ubuntu;
 
+pub use isr_core::KernelVersion;
+pub use url::Url;
+
 pub use self::{
-    banner::{LinuxBanner, LinuxVersionSignature, UbuntuVersionSignature},
+    banner::{CompilerKind, LinuxBanner, LinuxVersionSignature, UbuntuVersionSignature},
     error::Error,
-    ubuntu::{UbuntuDownloader, UbuntuPaths},
+    ubuntu::{ResolvedPackage, ResolvedPlan, SkipPolicy, UbuntuDownloader, UbuntuPaths},
 };
@@ -21,6 +21,9 @@ pub enum Error {
     #[error("URL does not contain filename")]
     UrlDoesNotContainFilename,
 
+    #[error("Malformed stanza at line {line}")]
+    MalformedStanza { line: usize },
+
     #[error("Invalid options")]
     InvalidOptions,
 
@@ -1,6 +1,9 @@
 mod error;
+mod plan;
 pub mod repository;
 mod repository_cache;
+mod skip;
+mod version;
 
 use std::{
     fs::File,
@@ -8,16 +11,23 @@ use std::{
 };
 
 use debpkg::DebPkg;
+use reqwest::blocking::Client;
 use url::Url;
 
 pub use self::{
-    error::Error, repository::UbuntuRepositoryEntry, repository_cache::UbuntuPackageCache,
+    error::Error,
+    plan::{ResolvedPackage, ResolvedPlan},
+    repository::UbuntuRepositoryEntry,
+    repository_cache::UbuntuPackageCache,
+    skip::SkipPolicy,
+    version::{DebianVersion, VersionMatch},
 };
 use crate::{LinuxBanner, LinuxVersionSignature, UbuntuVersionSignature};
 
 pub const DEFAULT_DDEBS_URL: &str = "http://ddebs.ubuntu.com";
 pub const DEFAULT_ARCHIVE_URL: &str = "http://cz.archive.ubuntu.com/ubuntu";
 pub const DEFAULT_ARCH: &str = "amd64";
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
 pub const DEFAULT_DISTS: &[&str] = &[
     "trusty",        // 14.04
     "xenial",        // 16.04
@@ -30,24 +40,34 @@ pub const DEFAULT_DISTS: &[&str] = &[
     "noble-updates", // 24.04
 ];
 
+#[derive(Clone)]
 enum Filename {
     Original,
     Custom(PathBuf),
 }
 
+#[derive(Clone)]
 pub struct UbuntuDownloader {
     arch: String,
     dists: Vec<String>,
 
     release: String,
     version: String,
+    kernel_flavour: String,
 
     archive_url: Url,
     ddebs_url: Url,
 
     output_directory: Option<PathBuf>,
     subdirectory: String,
-    skip_existing: bool,
+    skip_policy: SkipPolicy,
+    version_match: VersionMatch,
+
+    user_agent: Option<String>,
+    max_redirects: usize,
+
+    package_cache: Option<UbuntuPackageCache>,
+    ddebs_package_cache: Option<UbuntuPackageCache>,
 
     linux_image_deb: Option<Filename>,
     linux_image_dbgsym_deb: Option<Filename>,
@@ -66,6 +86,14 @@ pub struct UbuntuPaths {
     pub linux_image: Option<PathBuf>,
     pub linux_image_dbgsym: Option<PathBuf>,
     pub systemmap: Option<PathBuf>,
+
+    /// Packages the files were downloaded from.
+    pub packages: ResolvedPlan,
+
+    /// Files written by the download, i.e. the downloaded packages and
+    /// the extracted files. Files kept by the [`SkipPolicy`] aren't
+    /// included.
+    pub created: Vec<PathBuf>,
 }
 
 impl UbuntuDownloader {
@@ -100,11 +128,17 @@ impl UbuntuDownloader {
             dists: DEFAULT_DISTS.iter().map(ToString::to_string).collect(),
             release: kernel_release,
             version: kernel_version,
+            kernel_flavour: variant.into(),
             archive_url: DEFAULT_ARCHIVE_URL.try_into().unwrap(),
             ddebs_url: DEFAULT_DDEBS_URL.try_into().unwrap(),
             output_directory: None,
             subdirectory,
-            skip_existing: false,
+            skip_policy: SkipPolicy::Never,
+            version_match: VersionMatch::default(),
+            user_agent: None,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            package_cache: None,
+            ddebs_package_cache: None,
             linux_image_deb: None,
             linux_image_dbgsym_deb: None,
             linux_modules_deb: None,
@@ -133,6 +167,24 @@ impl UbuntuDownloader {
         }
     }
 
+    /// Changes the kernel flavour (e.g. `generic`, `lowlatency`), keeping
+    /// the release and revision.
+    pub fn with_flavour(self, flavour: &str) -> Self {
+        let suffix = format!("-{}", self.kernel_flavour);
+        let release = self.release.strip_suffix(&suffix).unwrap_or(&self.release);
+        let subdirectory = self
+            .subdirectory
+            .strip_suffix(&suffix)
+            .unwrap_or(&self.subdirectory);
+
+        Self {
+            release: format!("{release}-{flavour}"),
+            subdirectory: format!("{subdirectory}-{flavour}"),
+            kernel_flavour: flavour.into(),
+            ..self
+        }
+    }
+
     pub fn with_arch(self, arch: impl Into<String>) -> Self {
         Self {
             arch: arch.into(),
@@ -147,6 +199,28 @@ impl UbuntuDownloader {
         }
     }
 
+    /// Appends a dist (e.g. a release newer than [`DEFAULT_DISTS`]) to the
+    /// dists to search.
+    ///
+    /// Dists that are already in the list are not added again.
+    pub fn add_dist(self, dist: impl Into<String>) -> Self {
+        self.add_dists([dist])
+    }
+
+    /// Appends dists to the dists to search.
+    ///
+    /// Dists that are already in the list are not added again.
+    pub fn add_dists(mut self, dists: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        for dist in dists {
+            let dist = dist.into();
+            if !self.dists.contains(&dist) {
+                self.dists.push(dist);
+            }
+        }
+
+        self
+    }
+
     pub fn with_archive_url(self, archive_url: Url) -> Self {
         Self {
             archive_url,
@@ -165,9 +239,73 @@ impl UbuntuDownloader {
         }
     }
 
+    /// Keeps the files that were already downloaded.
+    ///
+    /// Same as [`with_skip_policy`] with [`SkipPolicy::IfExists`].
+    ///
+    /// [`with_skip_policy`]: Self::with_skip_policy
     pub fn skip_existing(self) -> Self {
+        self.with_skip_policy(SkipPolicy::IfExists)
+    }
+
+    /// Sets the policy for files that were already downloaded.
+    ///
+    /// Defaults to [`SkipPolicy::Never`].
+    pub fn with_skip_policy(self, skip_policy: SkipPolicy) -> Self {
+        Self {
+            skip_policy,
+            ..self
+        }
+    }
+
+    /// Sets the strictness of package version matching.
+    ///
+    /// Defaults to [`VersionMatch::Exact`]. The less strict modes must be
+    /// opted into, they substitute another version of the package when
+    /// the requested one is missing.
+    pub fn with_version_match(self, version_match: VersionMatch) -> Self {
+        Self {
+            version_match,
+            ..self
+        }
+    }
+
+    /// Sets the `User-Agent` header sent with the requests.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
         Self {
-            skip_existing: true,
+            user_agent: Some(user_agent.into()),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of redirects followed by a request.
+    ///
+    /// Defaults to [`DEFAULT_MAX_REDIRECTS`]. Zero disables redirects.
+    pub fn with_max_redirects(self, max_redirects: usize) -> Self {
+        Self {
+            max_redirects,
+            ..self
+        }
+    }
+
+    /// Uses the given indexes of the archive repository instead of fetching
+    /// them.
+    ///
+    /// Useful to share the indexes between downloads of multiple kernels.
+    pub fn with_package_cache(self, package_cache: UbuntuPackageCache) -> Self {
+        Self {
+            package_cache: Some(package_cache),
+            ..self
+        }
+    }
+
+    /// Uses the given indexes of the ddebs repository instead of fetching
+    /// them.
+    ///
+    /// Useful to share the indexes between downloads of multiple kernels.
+    pub fn with_ddebs_package_cache(self, ddebs_package_cache: UbuntuPackageCache) -> Self {
+        Self {
+            ddebs_package_cache: Some(ddebs_package_cache),
             ..self
         }
     }
@@ -256,11 +394,277 @@ impl UbuntuDownloader {
         }
     }
 
+    /// Resolves the requested packages without downloading them.
+    ///
+    /// Only the repository indexes are fetched. The returned plan contains
+    /// the URLs, sizes and checksums of the packages that [`download`]
+    /// would fetch.
+    ///
+    /// [`download`]: Self::download
+    pub fn resolve(&self) -> Result<ResolvedPlan, Error> {
+        self.validate()?;
+
+        let indexes = self.fetch_indexes(&self.client()?)?;
+        self.resolve_with(&indexes)
+    }
+
+    /// Resolves the requested packages for multiple kernel flavours.
+    ///
+    /// The repository indexes are flavour-independent, therefore they are
+    /// fetched only once.
+    pub fn resolve_flavours(
+        &self,
+        flavours: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Vec<ResolvedPlan>, Error> {
+        self.validate()?;
+
+        let indexes = self.fetch_indexes(&self.client()?)?;
+        flavours
+            .into_iter()
+            .map(|flavour| {
+                self.clone()
+                    .with_flavour(flavour.as_ref())
+                    .resolve_with(&indexes)
+            })
+            .collect()
+    }
+
     pub fn download(self) -> Result<UbuntuPaths, Error> {
-        //
-        // Validate options.
-        //
+        self.validate()?;
+
+        let client = self.client()?;
+        let indexes = self.fetch_indexes(&client)?;
+        self.download_with(&client, &indexes)
+    }
+
+    /// Downloads the packages of a plan returned by [`resolve`], without
+    /// fetching the repository indexes again.
+    ///
+    /// Only the packages that were requested are downloaded. The plan must
+    /// have been resolved by a downloader with the same options.
+    ///
+    /// [`resolve`]: Self::resolve
+    pub fn download_resolved(self, plan: ResolvedPlan) -> Result<UbuntuPaths, Error> {
+        self.validate()?;
+
+        let client = self.client()?;
+        self.download_plan_with(&client, plan)
+    }
+
+    /// Downloads the requested packages for multiple kernel flavours.
+    ///
+    /// The repository indexes are flavour-independent, therefore they are
+    /// fetched only once. Each flavour is stored in its own subdirectory.
+    pub fn download_flavours(
+        self,
+        flavours: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Vec<UbuntuPaths>, Error> {
+        self.validate()?;
+
+        let client = self.client()?;
+        let indexes = self.fetch_indexes(&client)?;
+        flavours
+            .into_iter()
+            .map(|flavour| {
+                self.clone()
+                    .with_flavour(flavour.as_ref())
+                    .download_with(&client, &indexes)
+            })
+            .collect()
+    }
+
+    /// Creates the HTTP client shared by all requests of a download.
+    fn client(&self) -> Result<Client, Error> {
+        let max_redirects = self.max_redirects;
+        let redirect_policy = reqwest::redirect::Policy::custom(move |attempt| {
+            tracing::debug!(
+                url = %attempt.url(),
+                from = ?attempt.previous().last().map(Url::as_str),
+                "redirect"
+            );
+
+            match attempt.previous().len() > max_redirects {
+                true => attempt.error("too many redirects"),
+                false => attempt.follow(),
+            }
+        });
+
+        let mut builder = Client::builder().redirect(redirect_policy);
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Fetches the repository indexes required by the requested packages.
+    fn fetch_indexes(&self, client: &Client) -> Result<Indexes, Error> {
+        let archive = match self.linux_image_deb.is_some() || self.linux_modules_deb.is_some() {
+            true => Some(match &self.package_cache {
+                Some(package_cache) => package_cache.clone(),
+                None => UbuntuPackageCache::fetch_with_client(
+                    client,
+                    self.archive_url.clone(),
+                    &self.arch,
+                    &self.dists,
+                )?,
+            }),
+            false => None,
+        };
+
+        let archive = archive.map(|archive| archive.with_version_match(self.version_match));
+
+        let ddebs = match self.linux_image_dbgsym_deb.is_some() {
+            true => Some(match &self.ddebs_package_cache {
+                Some(package_cache) => package_cache.clone(),
+                None => self.fetch_ddebs_indexes(client, archive.as_ref())?,
+            }),
+            false => None,
+        };
+
+        let ddebs = ddebs.map(|ddebs| ddebs.with_version_match(self.version_match));
+
+        Ok(Indexes { archive, ddebs })
+    }
+
+    /// Fetches the ddebs repository indexes.
+    ///
+    /// The dbgsym package is almost always in the same dist as the kernel
+    /// image. If the image was found in the archive, only its dist (and
+    /// the base dist, e.g. `jammy` for `jammy-updates`) is fetched first.
+    /// All dists are fetched if the dbgsym package isn't found there.
+    fn fetch_ddebs_indexes(
+        &self,
+        client: &Client,
+        archive: Option<&UbuntuPackageCache>,
+    ) -> Result<UbuntuPackageCache, Error> {
+        let dist = archive.and_then(|archive| {
+            find_linux_image(archive, &self.release, &self.version)
+                .or_else(|_| find_linux_modules(archive, &self.release, &self.version))
+                .ok()?
+                .dist
+                .clone()
+        });
+
+        if let Some(dist) = dist {
+            let mut dists = vec![dist.as_str()];
+            if let Some((base, _)) = dist.split_once('-') {
+                dists.push(base);
+            }
+
+            let ddebs = UbuntuPackageCache::fetch_with_client(
+                client,
+                self.ddebs_url.clone(),
+                &self.arch,
+                &dists,
+            )?;
+
+            let found = find_linux_image_dbgsym(
+                &ddebs.clone().with_version_match(self.version_match),
+                &self.release,
+                &self.version,
+            )
+            .is_ok();
+
+            if found {
+                return Ok(ddebs);
+            }
 
+            tracing::info!(?dists, "dbgsym package not found; fetching all dists");
+        }
+
+        UbuntuPackageCache::fetch_with_client(
+            client,
+            self.ddebs_url.clone(),
+            &self.arch,
+            &self.dists,
+        )
+    }
+
+    fn resolve_with(&self, indexes: &Indexes) -> Result<ResolvedPlan, Error> {
+        let mut result = ResolvedPlan::default();
+
+        if let Some(packages) = &indexes.archive {
+            if self.linux_image_deb.is_some() {
+                let entry = find_linux_image(packages, &self.release, &self.version)?;
+                result.linux_image = Some(ResolvedPackage::new(packages, entry, &self.version)?);
+            }
+
+            if self.linux_modules_deb.is_some() {
+                let entry = find_linux_modules(packages, &self.release, &self.version)?;
+                result.linux_modules = Some(ResolvedPackage::new(packages, entry, &self.version)?);
+            }
+        }
+
+        if let Some(packages) = &indexes.ddebs {
+            if self.linux_image_dbgsym_deb.is_some() {
+                let entry = find_linux_image_dbgsym(packages, &self.release, &self.version)?;
+                result.linux_image_dbgsym =
+                    Some(ResolvedPackage::new(packages, entry, &self.version)?);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn download_with(self, client: &Client, indexes: &Indexes) -> Result<UbuntuPaths, Error> {
+        let plan = self.resolve_with(indexes)?;
+        self.download_plan_with(client, plan)
+    }
+
+    fn download_plan_with(self, client: &Client, plan: ResolvedPlan) -> Result<UbuntuPaths, Error> {
+        let destination_path = self.destination_path();
+        std::fs::create_dir_all(&destination_path)?;
+
+        let mut result = UbuntuPaths {
+            output_directory: destination_path.clone(),
+            ..Default::default()
+        };
+
+        let downloaded = download_and_extract(
+            client,
+            plan.linux_image.as_ref(),
+            &destination_path,
+            self.skip_policy,
+            &format!("./boot/vmlinuz-{}", self.release),
+            self.linux_image_deb,
+            self.extract_linux_image,
+        )?;
+        result.linux_image_deb = downloaded.deb_path;
+        result.linux_image = downloaded.path;
+        result.created.extend(downloaded.created);
+
+        let downloaded = download_and_extract(
+            client,
+            plan.linux_modules.as_ref(),
+            &destination_path,
+            self.skip_policy,
+            &format!("./boot/System.map-{}", self.release),
+            self.linux_modules_deb,
+            self.extract_systemmap,
+        )?;
+        result.linux_modules_deb = downloaded.deb_path;
+        result.systemmap = downloaded.path;
+        result.created.extend(downloaded.created);
+
+        let downloaded = download_and_extract(
+            client,
+            plan.linux_image_dbgsym.as_ref(),
+            &destination_path,
+            self.skip_policy,
+            &format!("./usr/lib/debug/boot/vmlinux-{}", self.release),
+            self.linux_image_dbgsym_deb,
+            self.extract_linux_image_dbgsym,
+        )?;
+        result.linux_image_dbgsym_deb = downloaded.deb_path;
+        result.linux_image_dbgsym = downloaded.path;
+        result.created.extend(downloaded.created);
+
+        result.packages = plan;
+        Ok(result)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
         if self.extract_linux_image.is_some() && self.linux_image_deb.is_none() {
             tracing::error!("extract_linux_image requires download_linux_image");
             return Err(Error::InvalidOptions);
@@ -284,151 +688,130 @@ impl UbuntuDownloader {
             return Err(Error::InvalidOptions);
         }
 
-        let destination_path = self.destination_path();
-        std::fs::create_dir_all(&destination_path)?;
-
-        let mut result = UbuntuPaths {
-            output_directory: destination_path.clone(),
-            ..Default::default()
-        };
-
-        if self.linux_image_deb.is_some() || self.linux_modules_deb.is_some() {
-            let packages = UbuntuPackageCache::fetch(self.archive_url, &self.arch, &self.dists)?;
-
-            (result.linux_image_deb, result.linux_image) = find_and_download_and_extract(
-                &packages,
-                &self.release,
-                &self.version,
-                &destination_path,
-                self.skip_existing,
-                find_linux_image_url,
-                &format!("./boot/vmlinuz-{}", self.release),
-                self.linux_image_deb,
-                self.extract_linux_image,
-            )?;
-
-            (result.linux_modules_deb, result.systemmap) = find_and_download_and_extract(
-                &packages,
-                &self.release,
-                &self.version,
-                &destination_path,
-                self.skip_existing,
-                find_linux_modules_url,
-                &format!("./boot/System.map-{}", self.release),
-                self.linux_modules_deb,
-                self.extract_systemmap,
-            )?;
-        }
+        Ok(())
+    }
+}
 
-        if self.linux_image_dbgsym_deb.is_some() {
-            let packages = UbuntuPackageCache::fetch(self.ddebs_url, &self.arch, &self.dists)?;
+/// Repository indexes fetched for a single download.
+struct Indexes {
+    archive: Option<UbuntuPackageCache>,
+    ddebs: Option<UbuntuPackageCache>,
+}
 
-            (result.linux_image_dbgsym_deb, result.linux_image_dbgsym) =
-                find_and_download_and_extract(
-                    &packages,
-                    &self.release,
-                    &self.version,
-                    &destination_path,
-                    self.skip_existing,
-                    find_linux_image_dbgsym_url,
-                    &format!("./usr/lib/debug/boot/vmlinux-{}", self.release),
-                    self.linux_image_dbgsym_deb,
-                    self.extract_linux_image_dbgsym,
-                )?;
-        }
+/// Package downloaded and file extracted by [`download_and_extract`].
+#[derive(Default)]
+struct Downloaded {
+    deb_path: Option<PathBuf>,
+    path: Option<PathBuf>,
 
-        Ok(result)
-    }
+    /// Files that were written.
+    created: Vec<PathBuf>,
 }
 
-#[expect(clippy::too_many_arguments)]
-fn find_and_download_and_extract(
-    packages: &UbuntuPackageCache,
-    release: &str,
-    version: &str,
+/// Downloads a package and extracts a file from it.
+fn download_and_extract(
+    client: &Client,
+    package: Option<&ResolvedPackage>,
     output_directory: &Path,
-    skip_existing: bool,
-    find_package_fn: impl Fn(&UbuntuPackageCache, &str, &str) -> Result<Url, Error>,
+    skip_policy: SkipPolicy,
     deb_entry: &str,
     deb_filename: Option<Filename>,
     extract_filename: Option<Filename>,
-) -> Result<(Option<PathBuf>, Option<PathBuf>), Error> {
-    let deb_filename = match deb_filename {
-        Some(deb_filename) => deb_filename,
-        None => return Ok((None, None)),
+) -> Result<Downloaded, Error> {
+    let (package, deb_filename) = match (package, deb_filename) {
+        (Some(package), Some(deb_filename)) => (package, deb_filename),
+        _ => return Ok(Downloaded::default()),
     };
 
-    let url = find_package_fn(packages, release, version)?;
-    let deb_path = path_from_url(&url, output_directory, deb_filename)?;
+    let mut created = Vec::new();
 
-    if !deb_path.exists() || !skip_existing {
-        download(url, &deb_path)?;
-    }
-    else {
-        tracing::info!(path = %deb_path.display(), "skipping download");
-    }
+    let deb_path = path_from_url(&package.url, output_directory, deb_filename)?;
+
+    let downloaded = match skip_policy.keeps_package(&deb_path, package)? {
+        true => {
+            tracing::info!(path = %deb_path.display(), "skipping download");
+            false
+        }
+        false => {
+            download(client, package.url.clone(), &deb_path)?;
+            created.push(deb_path.clone());
+            true
+        }
+    };
 
     let extract_filename = match extract_filename {
         Some(extract_filename) => extract_filename,
-        None => return Ok((Some(deb_path), None)),
+        None => {
+            return Ok(Downloaded {
+                deb_path: Some(deb_path),
+                path: None,
+                created,
+            })
+        }
     };
 
     let path = path_from_deb_entry(deb_entry, output_directory, extract_filename)?;
 
-    if !path.exists() || !skip_existing {
+    // A package downloaded again may have different contents.
+    if downloaded || !skip_policy.keeps_extracted(&path) {
         unpack_deb_entry(&deb_path, deb_entry, &path)?;
+        created.push(path.clone());
     }
     else {
         tracing::info!(path = %path.display(), "skipping extraction");
     }
 
-    Ok((Some(deb_path), Some(path)))
+    Ok(Downloaded {
+        deb_path: Some(deb_path),
+        path: Some(path),
+        created,
+    })
 }
 
-fn find_linux_image_url(
-    packages: &UbuntuPackageCache,
+fn find_linux_image<'a>(
+    packages: &'a UbuntuPackageCache,
     release: &str,
     version: &str,
-) -> Result<Url, Error> {
+) -> Result<&'a UbuntuRepositoryEntry, Error> {
     let package = format!("linux-image-{release}");
     if let Some(candidate) = packages.find_package(&package, version)? {
-        return packages.package_url(candidate);
+        return Ok(candidate);
     }
 
     let package = format!("linux-image-unsigned-{release}");
     if let Some(candidate) = packages.find_package(&package, version)? {
-        return packages.package_url(candidate);
+        return Ok(candidate);
     }
 
     Err(Error::PackageNotFound)
 }
 
-fn find_linux_image_dbgsym_url(
-    packages: &UbuntuPackageCache,
+fn find_linux_image_dbgsym<'a>(
+    packages: &'a UbuntuPackageCache,
     release: &str,
     version: &str,
-) -> Result<Url, Error> {
+) -> Result<&'a UbuntuRepositoryEntry, Error> {
     let package = format!("linux-image-{release}-dbgsym");
     if let Some(candidate) = packages.find_dbgsym_package(&package, version)? {
-        return packages.package_url(candidate);
+        return Ok(candidate);
     }
 
     let package = format!("linux-image-unsigned-{release}-dbgsym");
     if let Some(candidate) = packages.find_dbgsym_package(&package, version)? {
-        return packages.package_url(candidate);
+        return Ok(candidate);
     }
 
     Err(Error::PackageNotFound)
 }
 
-fn find_linux_modules_url(
-    packages: &UbuntuPackageCache,
+fn find_linux_modules<'a>(
+    packages: &'a UbuntuPackageCache,
     release: &str,
     version: &str,
-) -> Result<Url, Error> {
+) -> Result<&'a UbuntuRepositoryEntry, Error> {
     let package = format!("linux-modules-{release}");
     if let Some(candidate) = packages.find_package(&package, version)? {
-        return packages.package_url(candidate);
+        return Ok(candidate);
     }
 
     Err(Error::PackageNotFound)
@@ -455,11 +838,11 @@ fn path_from_url(
     }
 }
 
-fn download(url: Url, destination_path: impl AsRef<Path>) -> Result<(), Error> {
+fn download(client: &Client, url: Url, destination_path: impl AsRef<Path>) -> Result<(), Error> {
     let destination_path = destination_path.as_ref();
 
     tracing::info!(%url, "downloading");
-    let mut response = reqwest::blocking::get(url)?.error_for_status()?;
+    let mut response = client.get(url).send()?.error_for_status()?;
     let mut file = File::create(destination_path)?;
     response.copy_to(&mut file)?;
 
@@ -501,7 +884,14 @@ fn unpack_deb_entry(
 
         if entry.header().path()? == deb_entry_path {
             tracing::info!(path = %deb_entry_path.display(), "unpacking");
-            entry.unpack(destination_path)?;
+
+            // Unpack to a temporary file first, so an interrupted
+            // extraction doesn't leave a truncated file behind.
+            let mut partial_path = destination_path.as_os_str().to_owned();
+            partial_path.push(".partial");
+
+            entry.unpack(&partial_path)?;
+            std::fs::rename(&partial_path, destination_path)?;
             return Ok(());
         }
     }
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{Error, UbuntuPackageCache, UbuntuRepositoryEntry};
+
+/// A package resolved from the repository indexes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedPackage {
+    /// Distribution (e.g. `jammy-updates`) the package was found in.
+    pub dist: String,
+
+    /// Package name.
+    pub package: String,
+
+    /// Exact package version.
+    pub version: String,
+
+    /// URL of the `.deb` file.
+    pub url: Url,
+
+    /// Size of the `.deb` file in bytes, as reported by the index.
+    pub size: Option<usize>,
+
+    /// SHA256 of the `.deb` file, as reported by the index.
+    pub sha256: Option<String>,
+
+    /// The requested version, if a package with a different version
+    /// was substituted for it (see [`VersionMatch`]).
+    ///
+    /// [`VersionMatch`]: super::VersionMatch
+    pub substituted_for: Option<String>,
+}
+
+impl ResolvedPackage {
+    pub(crate) fn new(
+        packages: &UbuntuPackageCache,
+        entry: &UbuntuRepositoryEntry,
+        requested_version: &str,
+    ) -> Result<Self, Error> {
+        let version = entry.version.clone().unwrap_or_default();
+        let substituted_for = match version == requested_version {
+            true => None,
+            false => Some(requested_version.to_owned()),
+        };
+
+        Ok(Self {
+            dist: entry.dist.clone().unwrap_or_default(),
+            package: entry.package.clone().unwrap_or_default(),
+            version,
+            url: packages.package_url(entry)?,
+            size: entry.size,
+            sha256: entry.sha256.clone(),
+            substituted_for,
+        })
+    }
+}
+
+/// Packages that [`UbuntuDownloader::download`] would fetch.
+///
+/// [`UbuntuDownloader::download`]: super::UbuntuDownloader::download
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ResolvedPlan {
+    /// The `linux-image` package.
+    pub linux_image: Option<ResolvedPackage>,
+
+    /// The `linux-image-dbgsym` package.
+    pub linux_image_dbgsym: Option<ResolvedPackage>,
+
+    /// The `linux-modules` package.
+    pub linux_modules: Option<ResolvedPackage>,
+}
+
+impl ResolvedPlan {
+    /// Returns an iterator over the resolved packages.
+    pub fn packages(&self) -> impl Iterator<Item = &ResolvedPackage> {
+        [
+            self.linux_image.as_ref(),
+            self.linux_image_dbgsym.as_ref(),
+            self.linux_modules.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
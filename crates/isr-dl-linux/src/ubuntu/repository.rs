@@ -1,12 +1,15 @@
-use std::io::Read as _;
+use std::io::{BufRead, BufReader};
 
 use flate2::read::GzDecoder;
+use reqwest::blocking::Client;
 use url::Url;
 
 pub use super::error::Error;
 
 #[derive(Debug, Default)]
 pub struct UbuntuRepositoryEntry {
+    pub dist: Option<String>,
+
     pub package: Option<String>,
     pub version: Option<String>,
     pub filename: Option<String>,
@@ -25,34 +28,81 @@ pub struct UbuntuRepositoryEntry {
 }
 
 pub fn fetch(host: Url, arch: &str, dist: &str) -> Result<Vec<UbuntuRepositoryEntry>, Error> {
-    let mut result = Vec::new();
+    fetch_with_client(&Client::new(), host, arch, dist)
+}
+
+/// Fetches and parses the `Packages` index using the given HTTP client.
+pub fn fetch_with_client(
+    client: &Client,
+    host: Url,
+    arch: &str,
+    dist: &str,
+) -> Result<Vec<UbuntuRepositoryEntry>, Error> {
     let full_url = host.join(&format!("dists/{dist}/main/binary-{arch}/Packages.gz"))?;
 
     tracing::info!(url = %full_url, "requesting");
-    let response = reqwest::blocking::get(full_url)?.error_for_status()?;
-
-    let data = response.bytes()?;
-    let mut decoder = GzDecoder::new(&data[..]);
-    let mut text = String::new();
-    decoder.read_to_string(&mut text)?;
-
-    let mut entry = UbuntuRepositoryEntry::default();
-    for line in text.lines() {
-        if line.is_empty() {
-            result.push(entry);
-            entry = UbuntuRepositoryEntry::default();
+    let response = client.get(full_url).send()?.error_for_status()?;
+    tracing::debug!(bytes = ?response.content_length(), "fetched index");
+
+    // Indexes from the archive are large, parse them as they are decompressed.
+    let decoder = BufReader::new(GzDecoder::new(response));
+    parse(decoder, dist, true)
+}
+
+/// Parses a `Packages` index.
+///
+/// In lenient mode, malformed stanzas are logged and skipped. Otherwise,
+/// the first malformed stanza fails the parsing with
+/// [`Error::MalformedStanza`].
+pub fn parse(
+    reader: impl BufRead,
+    dist: &str,
+    lenient: bool,
+) -> Result<Vec<UbuntuRepositoryEntry>, Error> {
+    let mut result = Vec::new();
+    let mut stanza = Stanza::default();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        // Some mirrors serve the index with `\r\n` line endings.
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        let line_number = index + 1;
+
+        if line.trim().is_empty() {
+            stanza.finish(&mut result, dist, lenient)?;
             continue;
         }
 
-        if line.starts_with(' ') {
+        if stanza.start == 0 {
+            stanza.start = line_number;
+        }
+
+        // Continuation of a multi-line field (e.g. `Description`).
+        if line.starts_with([' ', '\t']) {
+            if !stanza.has_field {
+                stanza.malformed.get_or_insert(line_number);
+            }
+
             continue;
         }
 
-        let (key, value) = match line.split_once(": ") {
-            Some((key, value)) => (key, value),
-            None => continue,
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key, value.trim()),
+            None => {
+                stanza.malformed.get_or_insert(line_number);
+                continue;
+            }
         };
 
+        stanza.has_field = true;
+
+        // Fields with an empty value (`Depends:`) are treated as absent.
+        if value.is_empty() {
+            continue;
+        }
+
+        let entry = &mut stanza.entry;
         match key {
             "Package" => entry.package = Some(value.into()),
             "Version" => entry.version = Some(value.into()),
@@ -70,5 +120,189 @@ pub fn fetch(host: Url, arch: &str, dist: &str) -> Result<Vec<UbuntuRepositoryEn
         }
     }
 
+    // The last stanza doesn't have to be followed by an empty line.
+    stanza.finish(&mut result, dist, lenient)?;
+
     Ok(result)
 }
+
+/// Stanza being parsed.
+#[derive(Default)]
+struct Stanza {
+    entry: UbuntuRepositoryEntry,
+
+    /// Line number of the first line of the stanza (0 if empty).
+    start: usize,
+
+    /// Whether the stanza contains at least one field.
+    has_field: bool,
+
+    /// Line number of the first malformed line.
+    malformed: Option<usize>,
+}
+
+impl Stanza {
+    /// Pushes the parsed entry and resets the stanza.
+    fn finish(
+        &mut self,
+        result: &mut Vec<UbuntuRepositoryEntry>,
+        dist: &str,
+        lenient: bool,
+    ) -> Result<(), Error> {
+        let Self {
+            mut entry,
+            start,
+            malformed,
+            ..
+        } = std::mem::take(self);
+
+        // Multiple empty lines in a row.
+        if start == 0 {
+            return Ok(());
+        }
+
+        let malformed = match (malformed, &entry.package) {
+            (Some(line), _) => Some(line),
+            (None, None) => Some(start),
+            (None, Some(_)) => None,
+        };
+
+        if let Some(line) = malformed {
+            if !lenient {
+                return Err(Error::MalformedStanza { line });
+            }
+
+            tracing::warn!(dist, start, line, "skipping malformed stanza");
+            return Ok(());
+        }
+
+        entry.dist = Some(dist.into());
+        result.push(entry);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    /// Index with the quirks found in real indexes: multi-line fields,
+    /// fields with empty values, `\r\n` line endings, repeated empty lines,
+    /// and no empty line after the last stanza.
+    const PACKAGES: &str = "\
+Package: linux-image-6.8.0-40-generic-dbgsym\r
+Version: 6.8.0-40.40\r
+Filename: pool/main/l/linux/a.ddeb\r
+Size: 1024\r
+Description: Linux kernel debug image\r
+ This package provides the debug symbols.\r
+ .\r
+ Multiple paragraphs.\r
+\r
+\r
+Package: linux-image-6.8.0-41-generic-dbgsym
+Version: 6.8.0-41.41
+Depends:
+Filename: pool/main/l/linux/b.ddeb
+SHA256: 5f70bf18a086007016e948b04aed3b82103a36bea41755b6cddfaf10ace3c6ef
+
+
+Package: linux-image-6.8.0-42-generic-dbgsym
+Version: 6.8.0-42.42
+Filename: pool/main/l/linux/c.ddeb";
+
+    /// Index with a malformed stanza between two valid ones.
+    const MALFORMED: &str = "\
+Package: first
+Version: 1
+
+Package: second
+Version 2
+
+ continuation without a field
+Version: 3
+
+Package: last
+Version: 4
+";
+
+    fn packages(entries: &[UbuntuRepositoryEntry]) -> Vec<&str> {
+        entries
+            .iter()
+            .map(|entry| entry.package.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn quirks() {
+        let entries = parse(PACKAGES.as_bytes(), "noble", false).unwrap();
+        assert_eq!(
+            packages(&entries),
+            [
+                "linux-image-6.8.0-40-generic-dbgsym",
+                "linux-image-6.8.0-41-generic-dbgsym",
+                "linux-image-6.8.0-42-generic-dbgsym",
+            ]
+        );
+
+        let first = &entries[0];
+        assert_eq!(first.dist.as_deref(), Some("noble"));
+        assert_eq!(first.version.as_deref(), Some("6.8.0-40.40"));
+        assert_eq!(first.filename.as_deref(), Some("pool/main/l/linux/a.ddeb"));
+        assert_eq!(first.size, Some(1024));
+
+        let second = &entries[1];
+        assert_eq!(second.depends, None);
+        assert_eq!(second.filename.as_deref(), Some("pool/main/l/linux/b.ddeb"));
+        assert_eq!(
+            second.sha256.as_deref(),
+            Some("5f70bf18a086007016e948b04aed3b82103a36bea41755b6cddfaf10ace3c6ef")
+        );
+    }
+
+    #[test]
+    fn last_stanza_without_empty_line() {
+        let entries = parse(PACKAGES.as_bytes(), "noble", false).unwrap();
+        let last = entries.last().unwrap();
+        assert_eq!(
+            last.package.as_deref(),
+            Some("linux-image-6.8.0-42-generic-dbgsym")
+        );
+        assert_eq!(last.version.as_deref(), Some("6.8.0-42.42"));
+        assert_eq!(last.filename.as_deref(), Some("pool/main/l/linux/c.ddeb"));
+    }
+
+    #[test]
+    fn malformed_stanza() {
+        let result = parse(MALFORMED.as_bytes(), "noble", false);
+        assert!(matches!(result, Err(Error::MalformedStanza { line: 5 })));
+    }
+
+    #[test]
+    fn lenient_skips_malformed_stanzas() {
+        let entries = parse(MALFORMED.as_bytes(), "noble", true).unwrap();
+        assert_eq!(packages(&entries), ["first", "last"]);
+        assert_eq!(entries[1].version.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn stanza_without_package() {
+        let result = parse("Package: first\n\nVersion: 1\n".as_bytes(), "noble", false);
+        assert!(matches!(result, Err(Error::MalformedStanza { line: 3 })));
+    }
+
+    #[test]
+    fn compressed() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(PACKAGES.as_bytes()).unwrap();
+        let data = encoder.finish().unwrap();
+
+        let decoder = BufReader::new(GzDecoder::new(data.as_slice()));
+        let entries = parse(decoder, "noble", true).unwrap();
+        assert_eq!(entries.len(), 3);
+    }
+}
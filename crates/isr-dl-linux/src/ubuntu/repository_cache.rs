@@ -1,14 +1,23 @@
+use std::sync::Arc;
+
 use indexmap::IndexMap;
+use reqwest::blocking::Client;
 use url::Url;
 
 use super::{
     error::Error,
     repository::{self, UbuntuRepositoryEntry},
+    version::{DebianVersion, VersionMatch},
 };
 
+/// Package indexes of an Ubuntu repository, grouped by dist.
+///
+/// Cloning is cheap, the indexes are shared between the clones.
+#[derive(Clone)]
 pub struct UbuntuPackageCache {
     host: Url,
-    packages: IndexMap<String, IndexMap<String, UbuntuRepositoryEntry>>,
+    packages: Arc<IndexMap<String, IndexMap<String, UbuntuRepositoryEntry>>>,
+    version_match: VersionMatch,
 }
 
 impl UbuntuPackageCache {
@@ -16,13 +25,23 @@ impl UbuntuPackageCache {
         host: Url,
         arch: &str,
         dists: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, Error> {
+        Self::fetch_with_client(&Client::new(), host, arch, dists)
+    }
+
+    /// Fetches the package indexes using the given HTTP client.
+    pub fn fetch_with_client(
+        client: &Client,
+        host: Url,
+        arch: &str,
+        dists: impl IntoIterator<Item = impl AsRef<str>>,
     ) -> Result<Self, Error> {
         let mut packages = IndexMap::<String, IndexMap<String, UbuntuRepositoryEntry>>::new();
 
         for dist in dists {
             let dist = dist.as_ref();
 
-            let repository = repository::fetch(host.clone(), arch, dist)?;
+            let repository = repository::fetch_with_client(client, host.clone(), arch, dist)?;
             let packages = packages.entry(dist.to_owned()).or_default();
 
             for entry in repository {
@@ -36,7 +55,29 @@ impl UbuntuPackageCache {
             }
         }
 
-        Ok(Self { host, packages })
+        Ok(Self {
+            host,
+            packages: Arc::new(packages),
+            version_match: VersionMatch::default(),
+        })
+    }
+
+    /// Returns the host of the repository.
+    pub fn host(&self) -> &Url {
+        &self.host
+    }
+
+    /// Returns the dists whose indexes are cached.
+    pub fn dists(&self) -> impl Iterator<Item = &str> {
+        self.packages.keys().map(String::as_str)
+    }
+
+    /// Sets the strictness of version matching.
+    pub fn with_version_match(self, version_match: VersionMatch) -> Self {
+        Self {
+            version_match,
+            ..self
+        }
     }
 
     pub fn find_package(
@@ -70,9 +111,12 @@ impl UbuntuPackageCache {
         version: &str,
         dbgsym: bool,
     ) -> Result<Option<&UbuntuRepositoryEntry>, Error> {
+        let requested_version = DebianVersion::parse(version);
+
         let mut candidates = Vec::new();
+        let mut fallback_candidates = Vec::new();
 
-        for (dist, packages) in &self.packages {
+        for (dist, packages) in self.packages.iter() {
             let entry = match packages.get(package) {
                 Some(entry) => entry,
                 None => continue,
@@ -83,10 +127,6 @@ impl UbuntuPackageCache {
                 None => continue,
             };
 
-            if entry_version != version {
-                continue;
-            }
-
             if dbgsym {
                 //
                 // Some dbgsym packages have dependencies on the main package.
@@ -103,12 +143,51 @@ impl UbuntuPackageCache {
                 }
             };
 
-            // let entry_filename = match &entry.filename {
-            //     Some(entry_filename) => entry_filename,
-            //     None => continue,
-            // };
+            if entry_version == version {
+                candidates.push((dist.as_str(), entry));
+                continue;
+            }
 
-            candidates.push((dist.as_str(), entry));
+            let entry_version = DebianVersion::parse(entry_version);
+            if entry_version.matches(&requested_version, self.version_match) {
+                fallback_candidates.push((dist.as_str(), entry_version, entry));
+            }
+        }
+
+        if candidates.is_empty() {
+            let candidate = match self.version_match {
+                // Prefer the closest newer version, then the closest older one.
+                VersionMatch::Nearest => {
+                    let (newer, older): (Vec<_>, Vec<_>) = fallback_candidates
+                        .into_iter()
+                        .partition(|(_, entry_version, _)| *entry_version > requested_version);
+
+                    match newer.into_iter().min_by(|(_, a, _), (_, b, _)| a.cmp(b)) {
+                        Some(candidate) => Some(candidate),
+                        None => older.into_iter().max_by(|(_, a, _), (_, b, _)| a.cmp(b)),
+                    }
+                }
+
+                // Prefer the highest version among the fallback candidates.
+                _ => fallback_candidates
+                    .into_iter()
+                    .max_by(|(_, a, _), (_, b, _)| a.cmp(b)),
+            };
+
+            if let Some((dist, entry_version, entry)) = candidate {
+                tracing::warn!(
+                    package,
+                    dist,
+                    requested_version = version,
+                    substituted_version = ?entry.version,
+                    ?entry_version,
+                    "exact version not found; substituting"
+                );
+
+                return Ok(Some(entry));
+            }
+
+            return Ok(None);
         }
 
         let candidate = match candidates.pop() {
@@ -128,3 +207,141 @@ impl UbuntuPackageCache {
         Ok(Some(candidate.1))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGES: &str = "\
+Package: linux-image-unsigned-6.8.0-40-generic-dbgsym
+Version: 1:6.8.0-40.40
+Filename: pool/main/l/linux/epoch.ddeb
+
+Package: linux-image-unsigned-6.5.0-21-generic-dbgsym
+Version: 6.5.0-21.21~22.04.1+1
+Filename: pool/main/l/linux/rebuild.ddeb
+
+Package: linux-image-6.5.0-21-generic-dbgsym
+Version: 6.5.0-21.21~22.04.1
+Depends: linux-image-unsigned-6.5.0-21-generic-dbgsym
+Filename: pool/main/l/linux/meta.ddeb
+";
+
+    fn cache(version_match: VersionMatch) -> UbuntuPackageCache {
+        let packages = repository::parse(PACKAGES.as_bytes(), "noble", false)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.package.clone().unwrap(), entry))
+            .collect();
+
+        UbuntuPackageCache {
+            host: Url::parse("http://ddebs.ubuntu.com/").unwrap(),
+            packages: Arc::new(IndexMap::from([(String::from("noble"), packages)])),
+            version_match,
+        }
+    }
+
+    fn filename(entry: Option<&UbuntuRepositoryEntry>) -> Option<&str> {
+        entry.and_then(|entry| entry.filename.as_deref())
+    }
+
+    #[test]
+    fn exact_by_default() {
+        let cache = cache(VersionMatch::default());
+
+        let package = "linux-image-unsigned-6.8.0-40-generic-dbgsym";
+        let entry = cache.find_dbgsym_package(package, "6.8.0-40.40").unwrap();
+        assert!(entry.is_none());
+
+        let entry = cache.find_dbgsym_package(package, "1:6.8.0-40.40").unwrap();
+        assert_eq!(filename(entry), Some("pool/main/l/linux/epoch.ddeb"));
+    }
+
+    #[test]
+    fn epoch() {
+        let package = "linux-image-unsigned-6.8.0-40-generic-dbgsym";
+
+        let cache = cache(VersionMatch::IgnoreEpoch);
+        let entry = cache.find_dbgsym_package(package, "6.8.0-40.40").unwrap();
+        assert_eq!(filename(entry), Some("pool/main/l/linux/epoch.ddeb"));
+
+        // Another revision.
+        let entry = cache.find_dbgsym_package(package, "6.8.0-40.41").unwrap();
+        assert!(entry.is_none());
+    }
+
+    #[test]
+    fn rebuild_suffix() {
+        let package = "linux-image-unsigned-6.5.0-21-generic-dbgsym";
+
+        let entry = cache(VersionMatch::IgnoreEpoch)
+            .find_dbgsym_package(package, "6.5.0-21.21~22.04.1")
+            .unwrap()
+            .is_some();
+        assert!(!entry);
+
+        let cache = cache(VersionMatch::SameAbi);
+        let entry = cache
+            .find_dbgsym_package(package, "6.5.0-21.21~22.04.1")
+            .unwrap();
+        assert_eq!(filename(entry), Some("pool/main/l/linux/rebuild.ddeb"));
+
+        // Another ABI.
+        let entry = cache
+            .find_dbgsym_package(package, "6.5.0-22.22~22.04.1")
+            .unwrap();
+        assert!(entry.is_none());
+    }
+
+    #[test]
+    fn nearest() {
+        let package = "linux-image-unsigned-6.8.0-generic-dbgsym";
+
+        // A revision per dist.
+        let packages = ["38.38", "42.42", "45.45"]
+            .into_iter()
+            .map(|revision| {
+                let index = format!(
+                    "Package: {package}\nVersion: 6.8.0-{revision}\nFilename: {revision}.ddeb\n"
+                );
+                let packages = repository::parse(index.as_bytes(), "noble", false)
+                    .unwrap()
+                    .into_iter()
+                    .map(|entry| (entry.package.clone().unwrap(), entry))
+                    .collect();
+
+                (format!("noble-{revision}"), packages)
+            })
+            .collect();
+
+        let cache = UbuntuPackageCache {
+            host: Url::parse("http://ddebs.ubuntu.com/").unwrap(),
+            packages: Arc::new(packages),
+            version_match: VersionMatch::Nearest,
+        };
+
+        // The closest newer revision.
+        let entry = cache.find_dbgsym_package(package, "6.8.0-40.40").unwrap();
+        assert_eq!(filename(entry), Some("42.42.ddeb"));
+
+        let entry = cache.find_dbgsym_package(package, "6.8.0-30.30").unwrap();
+        assert_eq!(filename(entry), Some("38.38.ddeb"));
+
+        // Then the closest older one.
+        let entry = cache.find_dbgsym_package(package, "6.8.0-50.50").unwrap();
+        assert_eq!(filename(entry), Some("45.45.ddeb"));
+
+        // Another upstream version.
+        let entry = cache.find_dbgsym_package(package, "6.9.0-42.42").unwrap();
+        assert!(entry.is_none());
+    }
+
+    #[test]
+    fn dbgsym_with_dependencies_is_skipped() {
+        let entry = cache(VersionMatch::Exact)
+            .find_dbgsym_package("linux-image-6.5.0-21-generic-dbgsym", "6.5.0-21.21~22.04.1")
+            .unwrap()
+            .is_some();
+        assert!(!entry);
+    }
+}
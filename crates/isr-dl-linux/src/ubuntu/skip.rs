@@ -0,0 +1,86 @@
+use std::{fs::File, path::Path};
+
+use sha2::{Digest as _, Sha256};
+
+use super::{Error, ResolvedPackage};
+
+/// Policy for files that were already downloaded.
+///
+/// Packages whose size or checksum isn't in the index are kept if they
+/// exist. Extracted files are kept if they exist and their package wasn't
+/// downloaded again.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SkipPolicy {
+    /// Files are always downloaded and extracted again.
+    #[default]
+    Never,
+
+    /// Existing files are kept.
+    IfExists,
+
+    /// Existing packages are kept if their size matches the index.
+    ///
+    /// Cheaper than [`IfChecksumMatches`], but doesn't detect packages
+    /// republished with the same size.
+    ///
+    /// [`IfChecksumMatches`]: Self::IfChecksumMatches
+    IfSizeMatches,
+
+    /// Existing packages are kept if their SHA256 matches the index.
+    ///
+    /// Packages republished under the same filename (e.g. after a signing
+    /// refresh) are downloaded again.
+    IfChecksumMatches,
+}
+
+impl SkipPolicy {
+    /// Checks whether an existing package can be kept.
+    ///
+    /// Returns `false` if the package doesn't exist.
+    pub fn keeps_package(&self, path: &Path, package: &ResolvedPackage) -> Result<bool, Error> {
+        if *self == Self::Never || !path.exists() {
+            return Ok(false);
+        }
+
+        if *self == Self::IfChecksumMatches {
+            if let Some(expected) = &package.sha256 {
+                let actual = sha256(path)?;
+                if !actual.eq_ignore_ascii_case(expected) {
+                    tracing::info!(?path, expected, actual, "checksum mismatch");
+                    return Ok(false);
+                }
+
+                return Ok(true);
+            }
+        }
+
+        if matches!(self, Self::IfSizeMatches | Self::IfChecksumMatches) {
+            if let Some(expected) = package.size {
+                let actual = std::fs::metadata(path)?.len();
+                if actual != expected as u64 {
+                    tracing::info!(?path, expected, actual, "size mismatch");
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Checks whether an existing extracted file can be kept.
+    pub(crate) fn keeps_extracted(&self, path: &Path) -> bool {
+        *self != Self::Never && path.exists()
+    }
+}
+
+/// Returns the SHA256 of a file, as lowercase hex digits.
+fn sha256(path: &Path) -> Result<String, Error> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
@@ -0,0 +1,157 @@
+use std::cmp::Ordering;
+
+/// Strictness of package version matching.
+///
+/// An exact match is always preferred. The less strict modes are only
+/// consulted when no package with the exact version exists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionMatch {
+    /// The version string must match exactly.
+    #[default]
+    Exact,
+
+    /// The versions must match, ignoring the epoch (`1:6.8.0-40.40`).
+    IgnoreEpoch,
+
+    /// The upstream version and the ABI number must match, the packaging
+    /// suffix may differ (e.g. `6.8.0-40.40~22.04.3` and `6.8.0-40.40~22.04.3+1`).
+    SameAbi,
+
+    /// Only the upstream version must match, the closest available revision
+    /// is selected.
+    ///
+    /// Newer revisions are preferred over older ones. This trades exactness
+    /// for availability when the requested revision has been removed from
+    /// the mirror.
+    Nearest,
+}
+
+/// Debian package version (`[epoch:]upstream[-revision]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebianVersion<'a> {
+    /// Epoch.
+    pub epoch: u32,
+
+    /// Upstream version.
+    pub upstream: &'a str,
+
+    /// Debian revision.
+    pub revision: &'a str,
+}
+
+impl<'a> DebianVersion<'a> {
+    /// Parses a Debian package version.
+    pub fn parse(version: &'a str) -> Self {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => match epoch.parse() {
+                Ok(epoch) => (epoch, rest),
+                Err(_) => (0, version),
+            },
+            None => (0, version),
+        };
+
+        let (upstream, revision) = match rest.rsplit_once('-') {
+            Some((upstream, revision)) => (upstream, revision),
+            None => (rest, ""),
+        };
+
+        Self {
+            epoch,
+            upstream,
+            revision,
+        }
+    }
+
+    /// Returns the ABI number of an Ubuntu kernel package version.
+    ///
+    /// For `6.8.0-40.40~22.04.3`, the ABI number is `40`.
+    pub fn abi(&self) -> &'a str {
+        match self.revision.split_once('.') {
+            Some((abi, _)) => abi,
+            None => self.revision,
+        }
+    }
+
+    /// Checks whether the versions match under the given strictness.
+    pub fn matches(&self, other: &DebianVersion, strictness: VersionMatch) -> bool {
+        match strictness {
+            VersionMatch::Exact => self == other,
+            VersionMatch::IgnoreEpoch => {
+                self.upstream == other.upstream && self.revision == other.revision
+            }
+            VersionMatch::SameAbi => self.upstream == other.upstream && self.abi() == other.abi(),
+            VersionMatch::Nearest => self.upstream == other.upstream,
+        }
+    }
+}
+
+impl PartialOrd for DebianVersion<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DebianVersion<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| compare_part(self.upstream, other.upstream))
+            .then_with(|| compare_part(self.revision, other.revision))
+    }
+}
+
+/// Compares version parts using the `dpkg` algorithm.
+fn compare_part(mut a: &str, mut b: &str) -> Ordering {
+    // Order of non-digit characters: `~` sorts before everything (even the
+    // end of the string), letters sort before non-letters.
+    fn order(c: Option<char>) -> i32 {
+        match c {
+            Some('~') => -1,
+            None => 0,
+            Some(c) if c.is_ascii_alphabetic() => c as i32,
+            Some(c) => c as i32 + 256,
+        }
+    }
+
+    fn split_at_digit(s: &str, digits: bool) -> (&str, &str) {
+        let end = s
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(s.len());
+        s.split_at(end)
+    }
+
+    while !a.is_empty() || !b.is_empty() {
+        let (a_text, a_rest) = split_at_digit(a, false);
+        let (b_text, b_rest) = split_at_digit(b, false);
+
+        let mut a_chars = a_text.chars();
+        let mut b_chars = b_text.chars();
+        loop {
+            let (ac, bc) = (a_chars.next(), b_chars.next());
+            if ac.is_none() && bc.is_none() {
+                break;
+            }
+
+            match order(ac).cmp(&order(bc)) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+
+        let (a_num, a_rest) = split_at_digit(a_rest, true);
+        let (b_num, b_rest) = split_at_digit(b_rest, true);
+
+        let a_num = a_num.trim_start_matches('0');
+        let b_num = b_num.trim_start_matches('0');
+
+        match a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num)) {
+            Ordering::Equal => (),
+            ordering => return ordering,
+        }
+
+        a = a_rest;
+        b = b_rest;
+    }
+
+    Ordering::Equal
+}
@@ -0,0 +1,184 @@
+//! Miniature Ubuntu repository: `Packages.gz` indexes and `.deb` files
+//! built in memory.
+
+use std::{
+    collections::BTreeMap,
+    io::Write as _,
+    sync::{Arc, Mutex},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use sha2::{Digest as _, Sha256};
+
+/// Architecture of the indexes.
+pub const ARCH: &str = "amd64";
+
+/// A package of the repository.
+#[derive(Clone)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub data: Vec<u8>,
+
+    /// Overrides the `Size` field of the index.
+    pub size: Option<usize>,
+}
+
+impl Package {
+    pub fn new(name: &str, version: &str, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            data,
+            size: None,
+        }
+    }
+
+    /// Path of the package, relative to the repository.
+    pub fn filename(&self, dist: &str) -> String {
+        format!("pool/{dist}/{}_{}_{ARCH}.deb", self.name, self.version)
+    }
+
+    /// SHA256 of the package, as lowercase hex digits.
+    pub fn sha256(&self) -> String {
+        Sha256::digest(&self.data)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Packages by dist.
+    dists: BTreeMap<String, Vec<Package>>,
+
+    /// Paths of the packages served so far.
+    downloads: Vec<String>,
+
+    /// Dists whose index was served so far.
+    indexes: Vec<String>,
+}
+
+/// A repository, shared with the server that serves it.
+#[derive(Clone, Default)]
+pub struct Repository {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Repository {
+    /// Adds a package to `dist`, replacing the package with the same name
+    /// (i.e. republishing it).
+    pub fn publish(&self, dist: &str, package: Package) {
+        let mut inner = self.inner.lock().unwrap();
+        let packages = inner.dists.entry(dist.into()).or_default();
+        packages.retain(|existing| existing.name != package.name);
+        packages.push(package);
+    }
+
+    /// Returns the paths of the packages served so far.
+    pub fn downloads(&self) -> Vec<String> {
+        self.inner.lock().unwrap().downloads.clone()
+    }
+
+    /// Returns the dists whose index was served so far.
+    pub fn indexes(&self) -> Vec<String> {
+        self.inner.lock().unwrap().indexes.clone()
+    }
+
+    /// Serves the indexes and the packages.
+    ///
+    /// Dists without packages have an empty index.
+    pub fn route(&self, path: &str) -> (u16, Vec<u8>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let index_suffix = format!("/main/binary-{ARCH}/Packages.gz");
+        if let Some(dist) = path
+            .strip_prefix("/dists/")
+            .and_then(|path| path.strip_suffix(&index_suffix))
+        {
+            inner.indexes.push(dist.into());
+            let packages = inner.dists.get(dist).map(Vec::as_slice).unwrap_or_default();
+            return (200, index(dist, packages));
+        }
+
+        let data = inner.dists.iter().find_map(|(dist, packages)| {
+            packages
+                .iter()
+                .find(|package| path == format!("/{}", package.filename(dist)))
+                .map(|package| package.data.clone())
+        });
+
+        match data {
+            Some(data) => {
+                inner.downloads.push(path.into());
+                (200, data)
+            }
+            None => (404, Vec::new()),
+        }
+    }
+}
+
+/// Builds the gzipped `Packages` index of a dist.
+fn index(dist: &str, packages: &[Package]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    for package in packages {
+        writeln!(encoder, "Package: {}", package.name).unwrap();
+        writeln!(encoder, "Version: {}", package.version).unwrap();
+        writeln!(encoder, "Filename: {}", package.filename(dist)).unwrap();
+        writeln!(
+            encoder,
+            "Size: {}",
+            package.size.unwrap_or(package.data.len())
+        )
+        .unwrap();
+        writeln!(encoder, "SHA256: {}", package.sha256()).unwrap();
+        writeln!(encoder).unwrap();
+    }
+
+    encoder.finish().unwrap()
+}
+
+/// Builds a `.deb` with the given files in its data archive.
+///
+/// Paths are stored as they are, e.g. `./boot/System.map-6.8.0-40-generic`.
+pub fn deb(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut result = b"!<arch>\n".to_vec();
+    ar_member(&mut result, "debian-binary", b"2.0\n");
+    ar_member(&mut result, "control.tar", &tar(&[("./control", b"")]));
+    ar_member(&mut result, "data.tar", &tar(files));
+    result
+}
+
+fn ar_member(archive: &mut Vec<u8>, name: &str, data: &[u8]) {
+    writeln!(
+        archive,
+        "{name:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`",
+        0,
+        0,
+        0,
+        100644,
+        data.len()
+    )
+    .unwrap();
+    archive.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        archive.push(b'\n');
+    }
+}
+
+fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+
+        // `set_path` strips the leading `./`.
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, *data).unwrap();
+    }
+
+    builder.into_inner().unwrap()
+}
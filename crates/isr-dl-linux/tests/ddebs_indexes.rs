@@ -0,0 +1,117 @@
+mod common;
+
+use isr_dl_linux::{ubuntu::UbuntuPackageCache, UbuntuDownloader};
+
+use self::common::{
+    repository::{deb, Package, Repository, ARCH},
+    Server,
+};
+
+const VERSION: &str = "6.8.0-40.40~22.04.3";
+const IMAGE: &str = "linux-image-6.8.0-40-generic";
+const DBGSYM: &str = "linux-image-unsigned-6.8.0-40-generic-dbgsym";
+
+/// Serves the archive with the `linux-image` package in `jammy-updates`,
+/// and the ddebs repository with the dbgsym package in `dbgsym_dist`.
+fn serve(dbgsym_dist: &str) -> [(Repository, Server); 2] {
+    let archive = Repository::default();
+    archive.publish(
+        "jammy-updates",
+        Package::new(IMAGE, VERSION, deb(&[("./boot/vmlinuz", b"vmlinuz")])),
+    );
+
+    let ddebs = Repository::default();
+    ddebs.publish(
+        dbgsym_dist,
+        Package::new(DBGSYM, VERSION, deb(&[("./vmlinux", b"vmlinux")])),
+    );
+
+    [archive, ddebs].map(|repository| {
+        let server = Server::new({
+            let repository = repository.clone();
+            move |path| repository.route(path)
+        });
+
+        (repository, server)
+    })
+}
+
+fn url(server: &Server) -> url::Url {
+    url::Url::parse(&server.url).unwrap()
+}
+
+/// Returns a downloader of the image and dbgsym packages, with the
+/// default dists.
+fn downloader(archive: &Server, ddebs: &Server) -> UbuntuDownloader {
+    UbuntuDownloader::new("6.8.0", "40.40~22.04.3", "generic")
+        .with_archive_url(url(archive))
+        .with_ddebs_url(url(ddebs))
+        .download_linux_image()
+        .download_linux_image_dbgsym()
+}
+
+/// Returns the package name of the resolved dbgsym package.
+fn resolve_dbgsym(downloader: &UbuntuDownloader) -> String {
+    let plan = downloader.resolve().unwrap();
+    plan.linux_image_dbgsym.unwrap().package
+}
+
+#[test]
+fn dist_of_the_image() {
+    let [(archive, archive_server), (ddebs, ddebs_server)] = serve("jammy-updates");
+
+    let downloader = downloader(&archive_server, &ddebs_server);
+    assert_eq!(resolve_dbgsym(&downloader), DBGSYM);
+
+    // Only the dist of the image and its base dist.
+    assert_eq!(archive.indexes().len(), 9);
+    assert_eq!(ddebs.indexes(), ["jammy-updates", "jammy"]);
+}
+
+#[test]
+fn all_dists_if_not_found() {
+    let [(_, archive_server), (ddebs, ddebs_server)] = serve("noble");
+
+    let downloader = downloader(&archive_server, &ddebs_server);
+    assert_eq!(resolve_dbgsym(&downloader), DBGSYM);
+
+    let indexes = ddebs.indexes();
+    assert_eq!(indexes[..2], ["jammy-updates", "jammy"]);
+    assert_eq!(indexes.len(), 2 + 9);
+}
+
+#[test]
+fn shared_package_caches() {
+    let [(archive, archive_server), (ddebs, ddebs_server)] = serve("jammy-updates");
+
+    let dists = ["jammy", "jammy-updates"];
+    let archive_cache = UbuntuPackageCache::fetch(url(&archive_server), ARCH, dists).unwrap();
+    let ddebs_cache = UbuntuPackageCache::fetch(url(&ddebs_server), ARCH, dists).unwrap();
+    assert_eq!(archive_cache.dists().collect::<Vec<_>>(), dists);
+
+    for _ in 0..2 {
+        let downloader = downloader(&archive_server, &ddebs_server)
+            .with_package_cache(archive_cache.clone())
+            .with_ddebs_package_cache(ddebs_cache.clone());
+        assert_eq!(resolve_dbgsym(&downloader), DBGSYM);
+    }
+
+    // Only the indexes fetched for the caches.
+    assert_eq!(archive.indexes().len(), 2);
+    assert_eq!(ddebs.indexes().len(), 2);
+}
+
+#[test]
+fn added_dists() {
+    let [(_, archive_server), (ddebs, ddebs_server)] = serve("oracular");
+
+    // The default dists are kept, the listed ones aren't added again.
+    let downloader = downloader(&archive_server, &ddebs_server)
+        .add_dist("oracular")
+        .add_dists(["jammy", "oracular"]);
+    assert_eq!(resolve_dbgsym(&downloader), DBGSYM);
+
+    let indexes = ddebs.indexes();
+    assert_eq!(indexes.len(), 2 + 9 + 1);
+    assert_eq!(indexes.last().unwrap(), "oracular");
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use isr_dl_linux::UbuntuDownloader;
+
+use self::common::{
+    repository::{deb, Package, Repository},
+    Server,
+};
+
+const DIST: &str = "jammy-updates";
+const VERSION: &str = "6.8.0-40.40~22.04.3";
+const FLAVOURS: [&str; 2] = ["generic", "lowlatency"];
+
+/// Returns the `System.map` of the flavour.
+fn systemmap(flavour: &str) -> Vec<u8> {
+    format!("ffffffff81000000 T _text\nffffffff81000100 T {flavour}\n").into_bytes()
+}
+
+/// Serves a repository with the `linux-modules` package of each flavour.
+fn serve() -> (Repository, Server) {
+    let repository = Repository::default();
+    for flavour in FLAVOURS {
+        let data = deb(&[(
+            &format!("./boot/System.map-6.8.0-40-{flavour}"),
+            &systemmap(flavour),
+        )]);
+        let name = format!("linux-modules-6.8.0-40-{flavour}");
+        repository.publish(DIST, Package::new(&name, VERSION, data));
+    }
+
+    let server = Server::new({
+        let repository = repository.clone();
+        move |path| repository.route(path)
+    });
+
+    (repository, server)
+}
+
+fn downloader(server: &Server) -> UbuntuDownloader {
+    let url = url::Url::parse(&server.url).unwrap();
+
+    UbuntuDownloader::new("6.8.0", "40.40~22.04.3", "generic")
+        .with_archive_url(url.clone())
+        .with_ddebs_url(url)
+        .with_dists([DIST])
+        .download_linux_modules_as("linux-modules.deb")
+        .extract_systemmap_as("System.map")
+}
+
+#[test]
+fn resolve_flavours() {
+    let (repository, server) = serve();
+
+    let plans = downloader(&server).resolve_flavours(FLAVOURS).unwrap();
+    let packages = plans
+        .iter()
+        .map(|plan| plan.linux_modules.as_ref().unwrap().package.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        packages,
+        [
+            "linux-modules-6.8.0-40-generic",
+            "linux-modules-6.8.0-40-lowlatency"
+        ]
+    );
+
+    // A single index, fetched once.
+    assert_eq!(server.requests(), 1);
+    assert!(repository.downloads().is_empty());
+}
+
+#[test]
+fn download_flavours() {
+    let (repository, server) = serve();
+    let directory = tempfile::tempdir().unwrap();
+
+    let paths = downloader(&server)
+        .with_output_directory(directory.path())
+        .download_flavours(FLAVOURS)
+        .unwrap();
+    assert_eq!(paths.len(), FLAVOURS.len());
+
+    for (paths, flavour) in paths.iter().zip(FLAVOURS) {
+        let output_directory = directory.path().join(format!("{VERSION}-{flavour}"));
+        assert_eq!(paths.output_directory, output_directory);
+
+        let systemmap_path = paths.systemmap.as_ref().unwrap();
+        assert_eq!(*systemmap_path, output_directory.join("System.map"));
+        assert_eq!(std::fs::read(systemmap_path).unwrap(), systemmap(flavour));
+    }
+
+    // The index once, then each package.
+    assert_eq!(repository.downloads().len(), 2);
+    assert_eq!(server.requests(), 3);
+}
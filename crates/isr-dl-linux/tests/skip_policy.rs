@@ -0,0 +1,150 @@
+mod common;
+
+use std::path::Path;
+
+use isr_dl_linux::{SkipPolicy, UbuntuDownloader};
+
+use self::common::{
+    repository::{deb, Package, Repository},
+    Server,
+};
+
+const DIST: &str = "jammy-updates";
+const VERSION: &str = "6.8.0-40.40~22.04.3";
+const MODULES: &str = "linux-modules-6.8.0-40-generic";
+const SYSTEMMAP_ENTRY: &str = "./boot/System.map-6.8.0-40-generic";
+
+/// Two `System.map` files of the same size.
+const SYSTEMMAP: &[u8] = b"ffffffff81000000 T _text\n";
+const SYSTEMMAP_REPUBLISHED: &[u8] = b"ffffffff82000000 T _text\n";
+
+fn modules(systemmap: &[u8]) -> Package {
+    Package::new(MODULES, VERSION, deb(&[(SYSTEMMAP_ENTRY, systemmap)]))
+}
+
+/// Serves a repository with the `linux-modules` package.
+fn serve() -> (Repository, Server) {
+    let repository = Repository::default();
+    repository.publish(DIST, modules(SYSTEMMAP));
+
+    let server = Server::new({
+        let repository = repository.clone();
+        move |path| repository.route(path)
+    });
+
+    (repository, server)
+}
+
+fn downloader(server: &Server, output_directory: &Path, policy: SkipPolicy) -> UbuntuDownloader {
+    let url = url::Url::parse(&server.url).unwrap();
+
+    UbuntuDownloader::new("6.8.0", "40.40~22.04.3", "generic")
+        .with_archive_url(url.clone())
+        .with_ddebs_url(url)
+        .with_dists([DIST])
+        .with_output_directory(output_directory)
+        .with_skip_policy(policy)
+        .download_linux_modules_as("linux-modules.deb")
+        .extract_systemmap_as("System.map")
+}
+
+/// Downloads the package twice, then once more after it's republished
+/// with the same size and different content.
+///
+/// Returns the number of downloads after each step.
+fn republish(policy: SkipPolicy) -> [usize; 3] {
+    let (repository, server) = serve();
+    let directory = tempfile::tempdir().unwrap();
+    let systemmap_path = directory
+        .path()
+        .join(format!("{VERSION}-generic/System.map"));
+
+    let paths = downloader(&server, directory.path(), policy)
+        .download()
+        .unwrap();
+    assert_eq!(paths.systemmap.as_deref(), Some(systemmap_path.as_path()));
+    assert_eq!(std::fs::read(&systemmap_path).unwrap(), SYSTEMMAP);
+    let first = repository.downloads().len();
+
+    downloader(&server, directory.path(), policy)
+        .download()
+        .unwrap();
+    let second = repository.downloads().len();
+
+    repository.publish(DIST, modules(SYSTEMMAP_REPUBLISHED));
+    let paths = downloader(&server, directory.path(), policy)
+        .download()
+        .unwrap();
+    let third = repository.downloads().len();
+
+    // The extracted file follows the package.
+    let downloaded = third > second;
+    let expected = match downloaded {
+        true => SYSTEMMAP_REPUBLISHED,
+        false => SYSTEMMAP,
+    };
+    assert_eq!(
+        std::fs::read(&systemmap_path).unwrap(),
+        expected,
+        "{policy:?}"
+    );
+    assert_eq!(
+        paths.created.contains(&systemmap_path),
+        downloaded || policy == SkipPolicy::Never
+    );
+
+    [first, second, third]
+}
+
+#[test]
+fn republished_package() {
+    assert_eq!(republish(SkipPolicy::Never), [1, 2, 3]);
+    assert_eq!(republish(SkipPolicy::IfExists), [1, 1, 1]);
+    assert_eq!(republish(SkipPolicy::IfSizeMatches), [1, 1, 1]);
+    assert_eq!(republish(SkipPolicy::IfChecksumMatches), [1, 1, 2]);
+}
+
+#[test]
+fn truncated_package() {
+    for (policy, downloads) in [
+        (SkipPolicy::IfExists, 1),
+        (SkipPolicy::IfSizeMatches, 2),
+        (SkipPolicy::IfChecksumMatches, 2),
+    ] {
+        let (repository, server) = serve();
+        let directory = tempfile::tempdir().unwrap();
+
+        let paths = downloader(&server, directory.path(), policy)
+            .download()
+            .unwrap();
+
+        let deb_path = paths.linux_modules_deb.unwrap();
+        let data = std::fs::read(&deb_path).unwrap();
+        std::fs::write(&deb_path, &data[..data.len() / 2]).unwrap();
+
+        downloader(&server, directory.path(), policy)
+            .download()
+            .unwrap();
+        assert_eq!(repository.downloads().len(), downloads, "{policy:?}");
+    }
+}
+
+#[test]
+fn missing_extracted_file() {
+    let (repository, server) = serve();
+    let directory = tempfile::tempdir().unwrap();
+
+    let paths = downloader(&server, directory.path(), SkipPolicy::IfChecksumMatches)
+        .download()
+        .unwrap();
+    let systemmap_path = paths.systemmap.unwrap();
+    std::fs::remove_file(&systemmap_path).unwrap();
+
+    // Extracted again from the package that was kept.
+    let paths = downloader(&server, directory.path(), SkipPolicy::IfChecksumMatches)
+        .download()
+        .unwrap();
+    assert_eq!(repository.downloads().len(), 1);
+    assert_eq!(paths.created.as_slice(), [systemmap_path.as_path()]);
+    assert_eq!(std::fs::read(&systemmap_path).unwrap(), SYSTEMMAP);
+}
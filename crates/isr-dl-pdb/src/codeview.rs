@@ -1,17 +1,21 @@
 use std::path::Path;
 
+use isr_core::{parse_pdb_guid, pdb_identifier};
 use object::{
     read::pe::{ImageNtHeaders, PeFile, PeFile32, PeFile64},
     FileKind, Object,
 };
 
 /// CodeView information extracted from a PDB file.
+///
+/// Prefer [`CodeView::new`] over constructing the struct directly, so the
+/// GUID is normalized.
 #[derive(Debug, Clone)]
 pub struct CodeView {
     /// Path to the PDB file.
     pub path: String,
 
-    /// PDB GUID.
+    /// PDB GUID, followed by the age (see [`pdb_identifier`]).
     pub guid: String,
 }
 
@@ -28,9 +32,43 @@ pub enum Error {
 
     #[error("CodeView not found")]
     NotFound,
+
+    #[error("Empty PDB path")]
+    EmptyPath,
+
+    #[error("Invalid PDB GUID {0:?}")]
+    InvalidGuid(String),
+
+    #[error("Invalid PE key {0:?}")]
+    InvalidKey(String),
 }
 
 impl CodeView {
+    /// Creates the CodeView information from a PDB path and GUID.
+    ///
+    /// The GUID is normalized to the form expected by the symbol servers
+    /// (see [`pdb_identifier`]). The GUID itself is 32 hex digits, followed
+    /// by 1 to 8 hex digits of age:
+    ///
+    /// - `3844dbb920174967be7aa4a2c20430fa2` (canonical form)
+    /// - `3844DBB920174967BE7AA4A2C20430FA2` (uppercase, as shown by WinDbg)
+    /// - `3844DBB9-2017-4967-BE7A-A4A2C20430FA2` (with dashes)
+    /// - `{3844DBB9-2017-4967-BE7A-A4A2C20430FA}2` (registry format)
+    /// - `3844dbb920174967be7aa4a2c20430fa1c` (age 28)
+    /// - `3844dbb920174967be7aa4a2c20430fa0n28` (age 28, in decimal with
+    ///   the `0n` prefix used by WinDbg)
+    ///
+    /// Leading zeros of the age are removed.
+    pub fn new(path: impl Into<String>, guid: &str) -> Result<CodeView, Error> {
+        let codeview = CodeView {
+            path: path.into(),
+            guid: normalize_guid(guid).ok_or_else(|| Error::InvalidGuid(guid.to_owned()))?,
+        };
+
+        codeview.validate()?;
+        Ok(codeview)
+    }
+
     pub fn from_pe<Pe>(pe: &PeFile<Pe>) -> Result<CodeView, Error>
     where
         Pe: ImageNtHeaders,
@@ -44,38 +82,89 @@ impl CodeView {
         let age = cv.age();
         let path = cv.path();
 
-        let guid0 = u32::from_le_bytes(guid[0..4].try_into().unwrap());
-        let guid1 = u16::from_le_bytes(guid[4..6].try_into().unwrap());
-        let guid2 = u16::from_le_bytes(guid[6..8].try_into().unwrap());
-        let guid3 = &guid[8..16];
-
         Ok(CodeView {
             path: String::from_utf8_lossy(path).to_string(),
-            guid: format!(
-                "{:08x}{:04x}{:04x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:01x}",
-                guid0,
-                guid1,
-                guid2,
-                guid3[0],
-                guid3[1],
-                guid3[2],
-                guid3[3],
-                guid3[4],
-                guid3[5],
-                guid3[6],
-                guid3[7],
-                age & 0xf,
-            ),
+            guid: pdb_identifier(&guid, age),
         })
     }
 
+    /// Returns the path of the PDB file relative to the symbol server
+    /// (`<name>/<identifier>`).
+    ///
+    /// Both the download URLs and the cache entries are derived from it,
+    /// so they always refer to the same PDB file.
+    pub fn server_path(&self) -> String {
+        format!("{}/{}", self.path, self.guid)
+    }
+
+    /// Checks that the CodeView information is well-formed.
+    ///
+    /// The path must be non-empty and the GUID must be in the canonical
+    /// form produced by [`CodeView::new`]: 32 lowercase hex digits for
+    /// the GUID itself, followed by the age in hex without leading zeros.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.path.is_empty() {
+            return Err(Error::EmptyPath);
+        }
+
+        if normalize_guid(&self.guid).as_deref() != Some(self.guid.as_str()) {
+            return Err(Error::InvalidGuid(self.guid.clone()));
+        }
+
+        Ok(())
+    }
+
     pub fn from_path(path: impl AsRef<Path>) -> Result<CodeView, Error> {
-        let data = std::fs::read(path)?;
+        Self::from_image_bytes(&std::fs::read(path)?)
+    }
 
-        match FileKind::parse(&data[..])? {
-            FileKind::Pe32 => Self::from_pe(&PeFile32::parse(&data[..])?),
-            FileKind::Pe64 => Self::from_pe(&PeFile64::parse(&data[..])?),
+    /// Extracts the CodeView information from the bytes of a PE image.
+    pub fn from_image_bytes(data: &[u8]) -> Result<CodeView, Error> {
+        match FileKind::parse(data)? {
+            FileKind::Pe32 => Self::from_pe(&PeFile32::parse(data)?),
+            FileKind::Pe64 => Self::from_pe(&PeFile64::parse(data)?),
             kind => Err(Error::UnsupportedArchitecture(kind)),
         }
     }
 }
+
+/// Normalizes a GUID followed by the age to the form of [`pdb_identifier`].
+fn normalize_guid(guid: &str) -> Option<String> {
+    let guid = guid
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '{' | '}' | '-'))
+        .collect::<String>();
+
+    if !guid.is_ascii() || guid.len() < 33 {
+        return None;
+    }
+
+    let (guid, age) = guid.split_at(32);
+    let age = match age.strip_prefix("0n") {
+        Some(age) => parse_digits(age, 10)?,
+        None if age.len() <= 8 => parse_digits(age, 16)?,
+        None => return None,
+    };
+
+    Some(pdb_identifier(&parse_pdb_guid(guid)?, age))
+}
+
+/// Parses a number made of digits only (no sign).
+fn parse_digits(digits: &str, radix: u32) -> Option<u32> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    u32::from_str_radix(digits, radix).ok()
+}
+
+/// Returns the preferred base address (`ImageBase` from the optional
+/// header) of a PE image.
+pub fn image_base_from_image_bytes(data: &[u8]) -> Result<u64, Error> {
+    match FileKind::parse(data)? {
+        FileKind::Pe32 => Ok(PeFile32::parse(data)?.relative_address_base()),
+        FileKind::Pe64 => Ok(PeFile64::parse(data)?.relative_address_base()),
+        kind => Err(Error::UnsupportedArchitecture(kind)),
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use object::{
+    read::pe::{ImageNtHeaders, ImageOptionalHeader as _, PeFile, PeFile32, PeFile64},
+    FileKind, LittleEndian as LE,
+};
+
+use crate::codeview::{CodeView, Error};
+
+/// Symbol server key of a PE file without CodeView information.
+///
+/// Executables are indexed on symbol servers by their `TimeDateStamp`
+/// and `SizeOfImage` (`<name>/<TimeDateStamp><SizeOfImage>/<name>`).
+/// The key identifies the module build, e.g. for a symbols-only profile
+/// created from the exports.
+#[derive(Debug, Clone)]
+pub struct PeKey {
+    /// Name of the file on the symbol server.
+    pub path: String,
+
+    /// `TimeDateStamp` as 8 uppercase hex digits, followed by
+    /// `SizeOfImage` as lowercase hex digits.
+    pub key: String,
+}
+
+impl PeKey {
+    /// Computes the key of a PE file, stored on the symbol server under
+    /// the given name.
+    pub fn from_pe<Pe>(pe: &PeFile<Pe>, path: impl Into<String>) -> PeKey
+    where
+        Pe: ImageNtHeaders,
+    {
+        let nt_headers = pe.nt_headers();
+        let timestamp = nt_headers.file_header().time_date_stamp.get(LE);
+        let size_of_image = nt_headers.optional_header().size_of_image();
+
+        PeKey {
+            path: path.into(),
+            key: format!("{timestamp:08X}{size_of_image:x}"),
+        }
+    }
+
+    /// Computes the key of a PE file, named after the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<PeKey, Error> {
+        let path = path.as_ref();
+        Self::from_image_bytes(&std::fs::read(path)?, file_name(path))
+    }
+
+    /// Computes the key from the bytes of a PE image.
+    pub fn from_image_bytes(data: &[u8], path: impl Into<String>) -> Result<PeKey, Error> {
+        match FileKind::parse(data)? {
+            FileKind::Pe32 => Ok(Self::from_pe(&PeFile32::parse(data)?, path)),
+            FileKind::Pe64 => Ok(Self::from_pe(&PeFile64::parse(data)?, path)),
+            kind => Err(Error::UnsupportedArchitecture(kind)),
+        }
+    }
+
+    /// Returns the path of the file relative to the symbol server
+    /// (`<name>/<key>`).
+    pub fn server_path(&self) -> String {
+        format!("{}/{}", self.path, self.key)
+    }
+
+    /// Checks that the key is well-formed.
+    ///
+    /// The path must be non-empty and the key must consist of 9 to 16 hex
+    /// characters (8 for the timestamp and the rest for the size).
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.path.is_empty() {
+            return Err(Error::EmptyPath);
+        }
+
+        if !(9..=16).contains(&self.key.len()) || !self.key.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::InvalidKey(self.key.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Identifier of a file on a symbol server.
+#[derive(Debug, Clone)]
+pub enum DebugId {
+    /// PDB file identified by the CodeView information.
+    CodeView(CodeView),
+
+    /// File identified by the key of a PE file without CodeView
+    /// information.
+    PeKey(PeKey),
+}
+
+impl DebugId {
+    /// Extracts the CodeView information from a PE file, falling back to
+    /// its key if it has none.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<DebugId, Error> {
+        let path = path.as_ref();
+        Self::from_image_bytes(&std::fs::read(path)?, file_name(path))
+    }
+
+    /// Extracts the CodeView information from the bytes of a PE image,
+    /// falling back to its key (named `path`) if it has none.
+    pub fn from_image_bytes(data: &[u8], path: impl Into<String>) -> Result<DebugId, Error> {
+        match CodeView::from_image_bytes(data) {
+            Ok(codeview) => Ok(DebugId::CodeView(codeview)),
+            Err(Error::NotFound) => Ok(DebugId::PeKey(PeKey::from_image_bytes(data, path)?)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the name of the file on the symbol server.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::CodeView(codeview) => &codeview.path,
+            Self::PeKey(pe_key) => &pe_key.path,
+        }
+    }
+
+    /// Returns the identifier of the file on the symbol server.
+    pub fn identifier(&self) -> &str {
+        match self {
+            Self::CodeView(codeview) => &codeview.guid,
+            Self::PeKey(pe_key) => &pe_key.key,
+        }
+    }
+
+    /// Returns the path of the file relative to the symbol server
+    /// (`<name>/<identifier>`).
+    pub fn server_path(&self) -> String {
+        match self {
+            Self::CodeView(codeview) => codeview.server_path(),
+            Self::PeKey(pe_key) => pe_key.server_path(),
+        }
+    }
+
+    /// Checks that the identifier is well-formed.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Self::CodeView(codeview) => codeview.validate(),
+            Self::PeKey(pe_key) => pe_key.validate(),
+        }
+    }
+}
+
+impl From<CodeView> for DebugId {
+    fn from(codeview: CodeView) -> Self {
+        Self::CodeView(codeview)
+    }
+}
+
+impl From<PeKey> for DebugId {
+    fn from(pe_key: PeKey) -> Self {
+        Self::PeKey(pe_key)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
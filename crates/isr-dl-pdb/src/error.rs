@@ -9,6 +9,18 @@ pub enum Error {
     #[error(transparent)]
     CodeView(#[from] crate::codeview::Error),
 
+    #[error(transparent)]
+    Object(#[from] object::Error),
+
+    #[error("Unsupported architecture {0:?}")]
+    UnsupportedArchitecture(object::Architecture),
+
+    #[error("Export table not found")]
+    ExportsNotFound,
+
+    #[error("PDB not found on any server")]
+    NotFound,
+
     #[error("Failed to download PDB")]
     Failed,
 }
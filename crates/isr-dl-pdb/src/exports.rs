@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+use isr_core::Symbols;
+use object::{
+    pe,
+    read::pe::{Export, ExportTable, ExportTarget, ImageNtHeaders, PeFile},
+    Architecture, FileKind, Object as _,
+};
+
+use crate::Error;
+
+/// Extracts the exported symbols from a PE image in its in-memory
+/// (virtual) layout, e.g. a kernel image read from a memory dump.
+///
+/// Returns the names of the exports mapped to their RVAs. Forwarded
+/// exports and exports without a name are skipped.
+pub fn exports_from_image_bytes(data: &[u8]) -> Result<Symbols<'static>, Error> {
+    match FileKind::parse(data)? {
+        FileKind::Pe32 => exports::<pe::ImageNtHeaders32>(data),
+        FileKind::Pe64 => exports::<pe::ImageNtHeaders64>(data),
+        _ => Err(Error::ExportsNotFound),
+    }
+}
+
+/// Extracts the exported symbols from a PE file in its on-disk layout,
+/// e.g. an executable downloaded from a symbol server.
+///
+/// Same as [`exports_from_image_bytes`] otherwise.
+pub fn exports_from_file_bytes(data: &[u8]) -> Result<Symbols<'static>, Error> {
+    match FileKind::parse(data)? {
+        FileKind::Pe32 => file_exports::<pe::ImageNtHeaders32>(data),
+        FileKind::Pe64 => file_exports::<pe::ImageNtHeaders64>(data),
+        _ => Err(Error::ExportsNotFound),
+    }
+}
+
+/// Returns the architecture of a PE file, named as in the profiles
+/// (e.g. `Amd64`).
+pub fn architecture_from_image_bytes(data: &[u8]) -> Result<&'static str, Error> {
+    let architecture = match FileKind::parse(data)? {
+        FileKind::Pe32 => PeFile::<pe::ImageNtHeaders32>::parse(data)?.architecture(),
+        FileKind::Pe64 => PeFile::<pe::ImageNtHeaders64>::parse(data)?.architecture(),
+        _ => Architecture::Unknown,
+    };
+
+    match architecture {
+        Architecture::X86_64 => Ok("Amd64"),
+        Architecture::I386 => Ok("X86"),
+        Architecture::Aarch64 => Ok("Arm64"),
+        architecture => Err(Error::UnsupportedArchitecture(architecture)),
+    }
+}
+
+fn file_exports<Pe: ImageNtHeaders>(data: &[u8]) -> Result<Symbols<'static>, Error> {
+    match PeFile::<Pe>::parse(data)?.export_table()? {
+        Some(export_table) => collect(export_table.exports()?),
+        None => Err(Error::ExportsNotFound),
+    }
+}
+
+fn exports<Pe: ImageNtHeaders>(data: &[u8]) -> Result<Symbols<'static>, Error> {
+    let dos_header = pe::ImageDosHeader::parse(data)?;
+    let mut offset = dos_header.nt_headers_offset().into();
+    let (_, data_directories) = Pe::parse(data, &mut offset)?;
+
+    let (virtual_address, size) = match data_directories.get(pe::IMAGE_DIRECTORY_ENTRY_EXPORT) {
+        Some(directory) => directory.address_range(),
+        None => return Err(Error::ExportsNotFound),
+    };
+
+    // In the virtual layout, RVAs are offsets into the image.
+    let start = virtual_address as usize;
+    let directory = match data.get(start..start.saturating_add(size as usize)) {
+        Some(directory) if virtual_address != 0 => directory,
+        _ => return Err(Error::ExportsNotFound),
+    };
+
+    collect(ExportTable::parse(directory, virtual_address)?.exports()?)
+}
+
+fn collect(exports: Vec<Export>) -> Result<Symbols<'static>, Error> {
+    let mut symbols = IndexMap::new();
+    for export in exports {
+        let (name, address) = match (export.name, export.target) {
+            (Some(name), ExportTarget::Address(address)) => (name, address),
+            _ => continue,
+        };
+
+        let name = String::from_utf8_lossy(name).into_owned();
+        symbols.entry(Cow::Owned(name)).or_insert(address as u64);
+    }
+
+    tracing::info!(count = symbols.len(), "collected exports");
+    Ok(Symbols(symbols))
+}
@@ -0,0 +1,44 @@
+use std::{collections::HashSet, sync::Mutex};
+
+/// Servers that failed to respond during the lifetime of the process.
+///
+/// Shared by all downloaders, so a dead server costs a timeout only once.
+static FAILED_SERVERS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Records that the server failed to respond.
+pub(crate) fn mark_failed(server: &str) {
+    if let Ok(mut failed) = FAILED_SERVERS.lock() {
+        if failed
+            .get_or_insert_with(HashSet::new)
+            .insert(server.to_owned())
+        {
+            tracing::warn!(server, "server failed; deprioritizing");
+        }
+    }
+}
+
+/// Records that the server responded.
+pub(crate) fn mark_healthy(server: &str) {
+    if let Ok(mut failed) = FAILED_SERVERS.lock() {
+        if let Some(failed) = failed.as_mut() {
+            failed.remove(server);
+        }
+    }
+}
+
+/// Checks whether the server failed to respond before.
+pub(crate) fn has_failed(server: &str) -> bool {
+    match FAILED_SERVERS.lock() {
+        Ok(failed) => failed
+            .as_ref()
+            .is_some_and(|failed| failed.contains(server)),
+        Err(_) => false,
+    }
+}
+
+/// Forgets all failed servers.
+pub(crate) fn reset() {
+    if let Ok(mut failed) = FAILED_SERVERS.lock() {
+        *failed = None;
+    }
+}
@@ -1,29 +1,56 @@
 //! Download PDB files from Microsoft symbol servers.
 
 mod codeview;
+mod debug_id;
 mod error;
+mod exports;
+mod health;
+mod version;
 
 use std::{
     fs::File,
     path::{Path, PathBuf},
 };
 
-pub use self::{codeview::CodeView, error::Error};
+pub use isr_core::pdb_identifier;
+use reqwest::blocking::Client;
+pub use reqwest::Url;
+
+pub use self::{
+    codeview::{image_base_from_image_bytes, CodeView},
+    debug_id::{DebugId, PeKey},
+    error::Error,
+    exports::{architecture_from_image_bytes, exports_from_file_bytes, exports_from_image_bytes},
+    version::file_version_from_image_bytes,
+};
 
 pub const DEFAULT_SERVER_URL: &str = "http://msdl.microsoft.com/download/symbols";
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
 
 pub struct PdbDownloader {
-    codeview: CodeView,
+    debug_id: DebugId,
     servers: Vec<String>,
     output: Option<PathBuf>,
+    user_agent: Option<String>,
+    max_redirects: usize,
 }
 
 impl PdbDownloader {
     pub fn new(codeview: CodeView) -> Self {
+        Self::from_debug_id(codeview.into())
+    }
+
+    /// Creates a downloader for any file indexed on the symbol servers.
+    ///
+    /// Besides PDB files, files are sometimes indexed by the key of a PE
+    /// file without CodeView information (see [`PeKey`]).
+    pub fn from_debug_id(debug_id: DebugId) -> Self {
         Self {
-            codeview,
+            debug_id,
             servers: vec![DEFAULT_SERVER_URL.into()],
             output: None,
+            user_agent: None,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
         }
     }
 
@@ -45,40 +72,203 @@ impl PdbDownloader {
         }
     }
 
-    pub fn download(self) -> Result<PathBuf, Error> {
-        let CodeView { path, guid } = self.codeview;
+    /// Sets the `User-Agent` header sent with the requests.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: Some(user_agent.into()),
+            ..self
+        }
+    }
 
-        for server in &self.servers {
-            let path_with_underscore = path.chars().rev().skip(1).collect::<String>() + "_";
+    /// Sets the maximum number of redirects followed by a request.
+    ///
+    /// Defaults to [`DEFAULT_MAX_REDIRECTS`]. Zero disables redirects.
+    pub fn with_max_redirects(self, max_redirects: usize) -> Self {
+        Self {
+            max_redirects,
+            ..self
+        }
+    }
 
-            for suffix in &[&path, &path_with_underscore] {
-                let url = format!("{server}/{path}/{guid}/{suffix}");
+    /// Forgets the servers that failed to respond.
+    ///
+    /// Servers that fail to respond (e.g. time out) are tried last by all
+    /// downloaders for the rest of the process lifetime, until they respond
+    /// again or this is called.
+    pub fn reset_server_health() {
+        health::reset();
+    }
 
-                tracing::info!(url, "requesting");
-                let response = reqwest::blocking::get(&url);
-                if response.is_err() {
+    /// Returns the candidate URLs of the PDB file, in the order in which
+    /// [`download`] tries them.
+    ///
+    /// Servers that failed to respond before are tried last. No network
+    /// requests are made.
+    ///
+    /// [`download`]: Self::download
+    pub fn resolve_urls(&self) -> Vec<Url> {
+        self.resolve_server_urls()
+            .into_iter()
+            .map(|(_, url)| url)
+            .collect()
+    }
+
+    /// Returns the candidate URLs of the PDB file with their servers.
+    fn resolve_server_urls(&self) -> Vec<(&str, Url)> {
+        let path = self.debug_id.path();
+        let server_path = self.debug_id.server_path();
+
+        // Compressed files on the symbol server replace the last character
+        // of the filename with an underscore (e.g. `ntkrnlmp.pd_`).
+        let mut path_with_underscore = String::from(path);
+        path_with_underscore.pop();
+        path_with_underscore.push('_');
+
+        // The sort is stable, so the configured order is kept otherwise.
+        let mut servers = self.servers.iter().map(String::as_str).collect::<Vec<_>>();
+        servers.sort_by_key(|server| health::has_failed(server));
+
+        let mut result = Vec::new();
+        for server in servers {
+            for suffix in [path, &*path_with_underscore] {
+                let url = format!("{server}/{server_path}/{suffix}");
+
+                match Url::parse(&url) {
+                    Ok(url) => result.push((server, url)),
+                    Err(err) => tracing::warn!(url, %err, "invalid URL"),
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns the size of the PDB file on the first server that has it,
+    /// without downloading it.
+    ///
+    /// Only `HEAD` requests are made. Returns `None` if the server doesn't
+    /// report the size.
+    pub fn download_size(&self) -> Result<Option<u64>, Error> {
+        self.debug_id.validate()?;
+
+        let client = self.client()?;
+        for (server, url) in self.resolve_server_urls() {
+            tracing::info!(%url, "requesting size");
+            let response = match client.head(url).send() {
+                Ok(response) => {
+                    health::mark_healthy(server);
+                    response
+                }
+                Err(_) => {
+                    health::mark_failed(server);
                     continue;
                 }
+            };
 
-                let output = match &self.output {
-                    Some(output) => {
-                        if output.is_dir() {
-                            output.join(format!("{guid}_{path}"))
-                        }
-                        else {
-                            output.clone()
-                        }
-                    }
-                    None => PathBuf::from(format!("{guid}_{path}")),
-                };
-
-                tracing::info!(?output, "downloading");
-                let mut file = File::create(&output)?;
-                response?.copy_to(&mut file)?;
-                return Ok(output);
+            if !response.status().is_success() {
+                continue;
             }
+
+            let size = response
+                .headers()
+                .get(reqwest::header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok());
+
+            return Ok(size);
         }
 
         Err(Error::Failed)
     }
+
+    /// Downloads the PDB file from the first server that has it.
+    ///
+    /// Servers are tried in the order of [`resolve_urls`]. Responses with
+    /// a non-success status are skipped. Fails with [`Error::NotFound`] if
+    /// every server answered `404 Not Found`, and with [`Error::Failed`]
+    /// otherwise.
+    ///
+    /// [`resolve_urls`]: Self::resolve_urls
+    pub fn download(self) -> Result<PathBuf, Error> {
+        self.debug_id.validate()?;
+
+        let client = self.client()?;
+        let urls = self.resolve_server_urls();
+        let (path, guid) = (self.debug_id.path(), self.debug_id.identifier());
+
+        let output = match &self.output {
+            Some(output) => {
+                if output.is_dir() {
+                    output.join(format!("{guid}_{path}"))
+                }
+                else {
+                    output.clone()
+                }
+            }
+            None => PathBuf::from(format!("{guid}_{path}")),
+        };
+
+        let mut all_not_found = !urls.is_empty();
+        for (server, url) in urls {
+            tracing::info!(%url, "requesting");
+            let mut response = match client.get(url).send() {
+                Ok(response) => {
+                    health::mark_healthy(server);
+                    response
+                }
+                Err(_) => {
+                    health::mark_failed(server);
+                    all_not_found = false;
+                    continue;
+                }
+            };
+
+            let status = response.status();
+            if !status.is_success() {
+                tracing::info!(%status, "not available");
+                all_not_found &= status == reqwest::StatusCode::NOT_FOUND;
+                continue;
+            }
+
+            tracing::info!(?output, "downloading");
+            let mut file = File::create(&output)?;
+            if let Err(err) = response.copy_to(&mut file) {
+                // Don't leave a truncated file behind.
+                drop(file);
+                let _ = std::fs::remove_file(&output);
+                return Err(err.into());
+            }
+
+            return Ok(output);
+        }
+
+        match all_not_found {
+            true => Err(Error::NotFound),
+            false => Err(Error::Failed),
+        }
+    }
+
+    /// Creates the HTTP client shared by all requests of a download.
+    fn client(&self) -> Result<Client, Error> {
+        let max_redirects = self.max_redirects;
+        let redirect_policy = reqwest::redirect::Policy::custom(move |attempt| {
+            tracing::debug!(
+                url = %attempt.url(),
+                from = ?attempt.previous().last().map(Url::as_str),
+                "redirect"
+            );
+
+            match attempt.previous().len() > max_redirects {
+                true => attempt.error("too many redirects"),
+                false => attempt.follow(),
+            }
+        });
+
+        let mut builder = Client::builder().redirect(redirect_policy);
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(builder.build()?)
+    }
 }
@@ -0,0 +1,109 @@
+use isr_core::WindowsBuild;
+use object::{
+    pe,
+    read::pe::{
+        ImageNtHeaders, PeFile, PeFile32, PeFile64, ResourceDirectoryEntryData,
+        ResourceDirectoryTable, ResourceNameOrId,
+    },
+    FileKind, LittleEndian as LE,
+};
+
+use crate::Error;
+
+/// Signature of the `VS_FIXEDFILEINFO` structure.
+const VS_FFI_SIGNATURE: u32 = 0xfeef_04bd;
+
+/// Extracts the file version (e.g. `10.0.18362.356`) from the version
+/// resource of a PE file.
+///
+/// Returns `None` if the file has no version resource.
+pub fn file_version_from_image_bytes(data: &[u8]) -> Result<Option<WindowsBuild>, Error> {
+    match FileKind::parse(data)? {
+        FileKind::Pe32 => file_version(&PeFile32::parse(data)?),
+        FileKind::Pe64 => file_version(&PeFile64::parse(data)?),
+        kind => Err(crate::codeview::Error::UnsupportedArchitecture(kind).into()),
+    }
+}
+
+fn file_version<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> Result<Option<WindowsBuild>, Error> {
+    let sections = pe.section_table();
+    let resources = match pe
+        .data_directories()
+        .resource_directory(pe.data(), &sections)?
+    {
+        Some(resources) => resources,
+        None => return Ok(None),
+    };
+
+    // Resources are organized in three levels: type, name and language.
+    // Only one version resource is expected, so the first name and
+    // language are taken.
+    let types = resources.root()?;
+    let version = types
+        .entries
+        .iter()
+        .find(|entry| matches!(entry.name_or_id(), ResourceNameOrId::Id(pe::RT_VERSION)));
+
+    let names = match version {
+        Some(version) => version.data(resources)?,
+        None => return Ok(None),
+    };
+
+    let languages = match first_entry(names) {
+        Some(entry) => entry.data(resources)?,
+        None => return Ok(None),
+    };
+
+    let data_entry = match first_entry(languages) {
+        Some(entry) => entry.data(resources)?,
+        None => return Ok(None),
+    };
+
+    let data_entry = match data_entry {
+        ResourceDirectoryEntryData::Data(data_entry) => data_entry,
+        ResourceDirectoryEntryData::Table(_) => return Ok(None),
+    };
+
+    let size = data_entry.size.get(LE) as usize;
+    let data = match sections.pe_data_at(pe.data(), data_entry.offset_to_data.get(LE)) {
+        Some(data) => &data[..size.min(data.len())],
+        None => return Ok(None),
+    };
+
+    Ok(parse_version_info(data))
+}
+
+/// Returns the first entry of a table.
+fn first_entry<'data>(
+    data: ResourceDirectoryEntryData<'data>,
+) -> Option<&'data pe::ImageResourceDirectoryEntry> {
+    data.table()
+        .and_then(|ResourceDirectoryTable { entries, .. }| entries.first())
+}
+
+/// Parses the file version from the `VS_FIXEDFILEINFO` structure in a
+/// `VS_VERSIONINFO` resource.
+///
+/// The structure follows a variable-length key, aligned to 4 bytes, so
+/// it's located by its signature.
+fn parse_version_info(data: &[u8]) -> Option<WindowsBuild> {
+    let read_u32 = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+
+    let offset = (0..data.len())
+        .step_by(4)
+        .find(|offset| read_u32(*offset) == Some(VS_FFI_SIGNATURE))?;
+
+    // Skip the signature and the structure version.
+    let version_ms = read_u32(offset + 8)?;
+    let version_ls = read_u32(offset + 12)?;
+
+    Some(WindowsBuild::new(
+        version_ms >> 16,
+        version_ms & 0xffff,
+        version_ls >> 16,
+        version_ls & 0xffff,
+    ))
+}
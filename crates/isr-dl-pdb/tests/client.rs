@@ -0,0 +1,123 @@
+//! The requests of a download share the configured User-Agent and
+//! redirect limit.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+use isr_dl_pdb::{CodeView, Error, PdbDownloader};
+
+/// A local server redirecting `/hop<N>/...` to `/hop<N-1>/...`, and
+/// serving the PDB at `/hop0/...`.
+struct Server {
+    url: String,
+
+    /// The User-Agent of each request.
+    user_agents: Arc<Mutex<Vec<String>>>,
+}
+
+impl Server {
+    fn new() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let user_agents = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = user_agents.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let path = request.split(' ').nth(1).unwrap_or_default().to_owned();
+
+                let mut user_agent = String::new();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("user-agent") {
+                            user_agent = value.trim().to_owned();
+                        }
+                    }
+                    line.clear();
+                }
+                recorded.lock().unwrap().push(user_agent);
+
+                let (hop, rest) = path[4..].split_once('/').unwrap();
+                let response = match hop.parse::<usize>().unwrap() {
+                    0 => String::from("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n"),
+                    hop => format!(
+                        "HTTP/1.1 302 Found\r\nLocation: /hop{}/{rest}\r\nContent-Length: 0\r\n",
+                        hop - 1
+                    ),
+                };
+                let _ = write!(stream, "{response}Connection: close\r\n\r\nPDB");
+            }
+        });
+
+        Self { url, user_agents }
+    }
+
+    /// Returns the URL of the server path redirected `hops` times.
+    fn url(&self, hops: usize) -> String {
+        format!("{}/hop{hops}", self.url)
+    }
+
+    fn user_agents(&self) -> Vec<String> {
+        self.user_agents.lock().unwrap().clone()
+    }
+}
+
+fn downloader(server: String, output: &std::path::Path) -> PdbDownloader {
+    let codeview = CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471").unwrap();
+
+    PdbDownloader::new(codeview)
+        .with_servers([server])
+        .with_output(output)
+}
+
+#[test]
+fn user_agent() {
+    let server = Server::new();
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory.path().join("ntkrnlmp.pdb");
+
+    downloader(server.url(2), &output)
+        .with_user_agent("isr-test/1.0")
+        .download()
+        .unwrap();
+
+    // Also sent after the redirects.
+    assert_eq!(server.user_agents(), ["isr-test/1.0"; 3]);
+    assert_eq!(std::fs::read(&output).unwrap(), b"PDB");
+}
+
+#[test]
+fn max_redirects() {
+    let server = Server::new();
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory.path().join("ntkrnlmp.pdb");
+
+    let path = downloader(server.url(2), &output)
+        .with_max_redirects(2)
+        .download()
+        .unwrap();
+    assert_eq!(path, output);
+
+    // One redirect too many fails the request.
+    std::fs::remove_file(&output).unwrap();
+    let result = downloader(server.url(3), &output)
+        .with_max_redirects(2)
+        .download();
+    assert!(matches!(result, Err(Error::Failed)));
+    assert!(!output.exists());
+
+    // Zero disables redirects.
+    let result = downloader(server.url(1), &output)
+        .with_max_redirects(0)
+        .download();
+    assert!(matches!(result, Err(Error::Failed)));
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use isr_dl_pdb::{pdb_identifier, CodeView};
+
+use self::common::pe_with_codeview;
+
+const GUID: &str = "3844dbb920174967be7aa4a2c20430fa";
+
+/// `GUID` in its binary form.
+const GUID_BYTES: [u8; 16] = [
+    0xb9, 0xdb, 0x44, 0x38, 0x17, 0x20, 0x67, 0x49, 0xbe, 0x7a, 0xa4, 0xa2, 0xc2, 0x04, 0x30, 0xfa,
+];
+
+#[test]
+fn accepted_shapes() {
+    for (input, expected) in [
+        // Canonical form.
+        ("3844dbb920174967be7aa4a2c20430fa2", "2"),
+        // Uppercase, as shown by WinDbg.
+        ("3844DBB920174967BE7AA4A2C20430FA2", "2"),
+        // With dashes.
+        ("3844DBB9-2017-4967-BE7A-A4A2C20430FA2", "2"),
+        // Registry format.
+        ("{3844DBB9-2017-4967-BE7A-A4A2C20430FA}2", "2"),
+        // Surrounding whitespace.
+        (" 3844dbb920174967be7aa4a2c20430fa2\n", "2"),
+        // Ages above 0xf are in hex.
+        ("3844dbb920174967be7aa4a2c20430fa10", "10"),
+        ("3844dbb920174967be7aa4a2c20430fa12", "12"),
+        ("3844dbb920174967be7aa4a2c20430fa1C", "1c"),
+        ("3844dbb920174967be7aa4a2c20430faffffffff", "ffffffff"),
+        // Leading zeros are removed.
+        ("3844dbb920174967be7aa4a2c20430fa0002", "2"),
+        ("3844dbb920174967be7aa4a2c20430fa00", "0"),
+        // Decimal ages need the `0n` prefix.
+        ("3844dbb920174967be7aa4a2c20430fa0n12", "c"),
+        ("3844dbb920174967be7aa4a2c20430fa0n28", "1c"),
+        ("3844dbb920174967be7aa4a2c20430fa0n4294967295", "ffffffff"),
+    ] {
+        let codeview = CodeView::new("ntkrnlmp.pdb", input)
+            .unwrap_or_else(|err| panic!("{input:?} rejected: {err}"));
+
+        assert_eq!(codeview.guid, format!("{GUID}{expected}"), "{input:?}");
+        codeview.validate().unwrap();
+    }
+}
+
+#[test]
+fn rejected_shapes() {
+    for input in [
+        "",
+        // No age.
+        "3844dbb920174967be7aa4a2c20430fa",
+        // GUID too short.
+        "3844dbb920174967be7aa4a2c20430f2",
+        // Not hex.
+        "3844dbb920174967be7aa4a2c20430fg2",
+        "3844dbb920174967be7aa4a2c20430fa2g",
+        "3844dbb920174967be7aa4a2c20430fa+2",
+        // Age longer than 8 hex digits.
+        "3844dbb920174967be7aa4a2c20430fa100000000",
+        // Decimal age out of range or empty.
+        "3844dbb920174967be7aa4a2c20430fa0n4294967296",
+        "3844dbb920174967be7aa4a2c20430fa0n",
+        "3844dbb920174967be7aa4a2c20430fa0n1c",
+        // Not ASCII.
+        "3844dbb920174967be7aa4a2c20430f\u{e9}2",
+    ] {
+        let err = CodeView::new("ntkrnlmp.pdb", input).expect_err(input);
+        assert_eq!(err.to_string(), format!("Invalid PDB GUID {input:?}"));
+    }
+
+    let err = CodeView::new("", "3844dbb920174967be7aa4a2c20430fa2").unwrap_err();
+    assert_eq!(err.to_string(), "Empty PDB path");
+}
+
+#[test]
+fn validate_requires_canonical_form() {
+    for guid in [
+        "3844DBB920174967BE7AA4A2C20430FA2",
+        "3844dbb920174967be7aa4a2c20430fa02",
+        "3844dbb920174967be7aa4a2c20430fa0n12",
+        "{3844dbb9-2017-4967-be7a-a4a2c20430fa}2",
+    ] {
+        let codeview = CodeView {
+            path: String::from("ntkrnlmp.pdb"),
+            guid: String::from(guid),
+        };
+
+        let err = codeview.validate().expect_err(guid);
+        assert_eq!(err.to_string(), format!("Invalid PDB GUID {guid:?}"));
+    }
+}
+
+#[test]
+fn from_image_bytes() {
+    for (age, expected) in [(2, "2"), (0x10, "10"), (0x1c, "1c")] {
+        let data = pe_with_codeview("ntkrnlmp.pdb", GUID_BYTES, age);
+        let codeview = CodeView::from_image_bytes(&data).unwrap();
+
+        assert_eq!(codeview.path, "ntkrnlmp.pdb");
+        assert_eq!(codeview.guid, format!("{GUID}{expected}"));
+        assert_eq!(codeview.guid, pdb_identifier(&GUID_BYTES, age));
+        codeview.validate().unwrap();
+    }
+}
+
+#[test]
+fn server_path() {
+    let data = pe_with_codeview("ntkrnlmp.pdb", GUID_BYTES, 0x1c);
+    let from_image = CodeView::from_image_bytes(&data).unwrap();
+    let from_windbg = CodeView::new("ntkrnlmp.pdb", "3844DBB920174967BE7AA4A2C20430FA1C").unwrap();
+
+    assert_eq!(from_image.server_path(), from_windbg.server_path());
+    assert_eq!(from_image.server_path(), format!("ntkrnlmp.pdb/{GUID}1c"));
+}
@@ -0,0 +1,260 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use object::{
+    pe,
+    write::pe::{NtHeaders, Writer},
+};
+
+/// `TimeDateStamp` of the synthetic PE files.
+pub const TIMESTAMP: u32 = 0x5f4e_3a2b;
+
+/// `ImageBase` of the synthetic PE files.
+pub const IMAGE_BASE: u64 = 0x1_4000_0000;
+
+/// Builds a 64-bit PE file without a debug directory, exporting the given
+/// names at the given RVAs.
+///
+/// With equal section and file alignments, the file layout is the same
+/// as the in-memory layout.
+pub fn pe_with_exports(
+    exports: &[(&str, u32)],
+    section_alignment: u32,
+    file_alignment: u32,
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut writer = Writer::new(true, section_alignment, file_alignment, &mut data);
+
+    writer.reserve_dos_header_and_stub();
+    writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+    writer.reserve_section_headers(2);
+    let text = writer.reserve_text_section(0x100);
+    let edata_len = export_directory_len(exports);
+    let edata = writer.reserve_edata_section(edata_len);
+
+    writer.write_dos_header_and_stub().unwrap();
+    writer.write_nt_headers(nt_headers(text.virtual_address));
+    writer.write_section_headers();
+    writer.write_section(text.file_offset, &[0xcc; 0x100]);
+    writer.write_section(
+        edata.file_offset,
+        &export_directory(edata.virtual_address, exports),
+    );
+
+    data
+}
+
+/// Builds a 64-bit PE file with a CodeView (`RSDS`) debug directory
+/// referencing the given PDB.
+///
+/// The first three components of `guid` are in little-endian order, as
+/// stored in the file.
+pub fn pe_with_codeview(pdb_path: &str, guid: [u8; 16], age: u32) -> Vec<u8> {
+    const DEBUG_DIRECTORY_LEN: u32 = 28;
+
+    let mut codeview = b"RSDS".to_vec();
+    codeview.extend_from_slice(&guid);
+    codeview.extend_from_slice(&age.to_le_bytes());
+    codeview.extend_from_slice(pdb_path.as_bytes());
+    codeview.push(0);
+
+    let mut data = Vec::new();
+    let mut writer = Writer::new(true, 0x1000, 0x200, &mut data);
+
+    writer.reserve_dos_header_and_stub();
+    writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+    writer.reserve_section_headers(1);
+    let rdata = writer.reserve_rdata_section(DEBUG_DIRECTORY_LEN + codeview.len() as u32);
+    writer.set_data_directory(
+        pe::IMAGE_DIRECTORY_ENTRY_DEBUG,
+        rdata.virtual_address,
+        DEBUG_DIRECTORY_LEN,
+    );
+
+    // Characteristics, TimeDateStamp, MajorVersion and MinorVersion,
+    // followed by Type, SizeOfData, AddressOfRawData and PointerToRawData.
+    let mut section = Vec::new();
+    for value in [0, TIMESTAMP, 0] {
+        section.extend_from_slice(&value.to_le_bytes());
+    }
+    for value in [
+        pe::IMAGE_DEBUG_TYPE_CODEVIEW,
+        codeview.len() as u32,
+        rdata.virtual_address + DEBUG_DIRECTORY_LEN,
+        rdata.file_offset + DEBUG_DIRECTORY_LEN,
+    ] {
+        section.extend_from_slice(&value.to_le_bytes());
+    }
+    section.extend_from_slice(&codeview);
+
+    writer.write_dos_header_and_stub().unwrap();
+    writer.write_nt_headers(nt_headers(0));
+    writer.write_section_headers();
+    writer.write_section(rdata.file_offset, &section);
+
+    data
+}
+
+/// Builds a 64-bit PE file with a version resource holding the given
+/// file version.
+pub fn pe_with_version(version: [u16; 4]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut writer = Writer::new(true, 0x1000, 0x200, &mut data);
+
+    writer.reserve_dos_header_and_stub();
+    writer.reserve_nt_headers(pe::IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+    writer.reserve_section_headers(1);
+    let rsrc_len = version_resource(0, version).len() as u32;
+    let rsrc = writer.reserve_rsrc_section(rsrc_len);
+
+    writer.write_dos_header_and_stub().unwrap();
+    writer.write_nt_headers(nt_headers(0));
+    writer.write_section_headers();
+    writer.write_section(
+        rsrc.file_offset,
+        &version_resource(rsrc.virtual_address, version),
+    );
+
+    data
+}
+
+/// Builds a resource directory located at `rva`, with a single
+/// `RT_VERSION` resource.
+///
+/// Layout: the type, name and language tables with one entry each, the
+/// data entry, and the `VS_VERSIONINFO` structure.
+fn version_resource(rva: u32, version: [u16; 4]) -> Vec<u8> {
+    const SUBDIRECTORY: u32 = 0x8000_0000;
+
+    let mut data = Vec::new();
+    for (id, offset) in [
+        (u32::from(pe::RT_VERSION), SUBDIRECTORY | 24),
+        (1, SUBDIRECTORY | 48),
+        (0x409, 72),
+    ] {
+        // Characteristics, TimeDateStamp, MajorVersion and MinorVersion,
+        // NumberOfNamedEntries and NumberOfIdEntries.
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&id.to_le_bytes());
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    // wLength, wValueLength and wType, followed by the key, padded to
+    // 4 bytes.
+    let mut version_info = Vec::new();
+    for value in [92u16, 52, 0] {
+        version_info.extend_from_slice(&value.to_le_bytes());
+    }
+    for c in "VS_VERSION_INFO\0".encode_utf16() {
+        version_info.extend_from_slice(&c.to_le_bytes());
+    }
+    version_info.extend_from_slice(&[0; 2]);
+
+    // VS_FIXEDFILEINFO: signature, structure version, file version,
+    // product version and the remaining fields.
+    let [major, minor, build, revision] = version.map(u32::from);
+    for value in [
+        0xfeef_04bd,
+        0x0001_0000,
+        major << 16 | minor,
+        build << 16 | revision,
+        major << 16 | minor,
+        build << 16 | revision,
+    ] {
+        version_info.extend_from_slice(&value.to_le_bytes());
+    }
+    version_info.extend_from_slice(&[0; 28]);
+
+    // OffsetToData, Size, CodePage and Reserved.
+    for value in [rva + 88, version_info.len() as u32, 0, 0] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(&version_info);
+
+    data
+}
+
+fn nt_headers(address_of_entry_point: u32) -> NtHeaders {
+    NtHeaders {
+        machine: pe::IMAGE_FILE_MACHINE_AMD64,
+        time_date_stamp: TIMESTAMP,
+        characteristics: pe::IMAGE_FILE_EXECUTABLE_IMAGE | pe::IMAGE_FILE_LARGE_ADDRESS_AWARE,
+        major_linker_version: 14,
+        minor_linker_version: 0,
+        address_of_entry_point,
+        image_base: IMAGE_BASE,
+        major_operating_system_version: 10,
+        minor_operating_system_version: 0,
+        major_image_version: 10,
+        minor_image_version: 0,
+        major_subsystem_version: 10,
+        minor_subsystem_version: 0,
+        subsystem: pe::IMAGE_SUBSYSTEM_NATIVE,
+        dll_characteristics: 0,
+        size_of_stack_reserve: 0x80000,
+        size_of_stack_commit: 0x1000,
+        size_of_heap_reserve: 0,
+        size_of_heap_commit: 0,
+    }
+}
+
+fn export_directory_len(exports: &[(&str, u32)]) -> u32 {
+    export_directory(0, exports).len() as u32
+}
+
+/// Builds an export directory located at `rva`.
+///
+/// Layout: `IMAGE_EXPORT_DIRECTORY`, address table, name pointer table,
+/// ordinal table, module name, export names.
+fn export_directory(rva: u32, exports: &[(&str, u32)]) -> Vec<u8> {
+    const MODULE_NAME: &[u8] = b"ntoskrnl.exe\0";
+
+    let count = exports.len() as u32;
+    let functions = rva + 40;
+    let names = functions + 4 * count;
+    let ordinals = names + 4 * count;
+    let module_name = ordinals + 2 * count;
+    let mut name = module_name + MODULE_NAME.len() as u32;
+
+    // Characteristics, TimeDateStamp, MajorVersion and MinorVersion are
+    // zero. The ordinal base is 1.
+    let mut directory = Vec::new();
+    for value in [
+        0,
+        0,
+        0,
+        module_name,
+        1,
+        count,
+        count,
+        functions,
+        names,
+        ordinals,
+    ] {
+        directory.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for (_, address) in exports {
+        directory.extend_from_slice(&address.to_le_bytes());
+    }
+
+    for (export, _) in exports {
+        directory.extend_from_slice(&name.to_le_bytes());
+        name += export.len() as u32 + 1;
+    }
+
+    for ordinal in 0..count as u16 {
+        directory.extend_from_slice(&ordinal.to_le_bytes());
+    }
+
+    directory.extend_from_slice(MODULE_NAME);
+    for (export, _) in exports {
+        directory.extend_from_slice(export.as_bytes());
+        directory.push(0);
+    }
+
+    directory
+}
@@ -0,0 +1,121 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+};
+
+use isr_dl_pdb::{CodeView, Error, PdbDownloader};
+
+/// Serves each request path with the status and body returned by `route`.
+///
+/// Returns the base URL of the server.
+fn serve(route: fn(&str) -> (u16, &'static [u8])) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let path = request.split(' ').nth(1).unwrap_or_default().to_owned();
+
+            // Skip the headers.
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let (status, body) = route(&path);
+            write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+
+    url
+}
+
+fn codeview() -> CodeView {
+    CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471").unwrap()
+}
+
+#[test]
+fn all_not_found() {
+    let server = serve(|_| (404, b"<html>not found</html>"));
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory.path().join("ntkrnlmp.pdb");
+
+    let result = PdbDownloader::new(codeview())
+        .with_servers([server])
+        .with_output(&output)
+        .download();
+
+    assert!(matches!(result, Err(Error::NotFound)));
+    assert!(!output.exists());
+}
+
+#[test]
+fn server_error_is_not_not_found() {
+    let failing = serve(|_| (500, b"internal error"));
+    let missing = serve(|_| (404, b"not found"));
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory.path().join("ntkrnlmp.pdb");
+
+    let result = PdbDownloader::new(codeview())
+        .with_servers([failing, missing])
+        .with_output(&output)
+        .download();
+
+    assert!(matches!(result, Err(Error::Failed)));
+    assert!(!output.exists());
+}
+
+#[test]
+fn next_server_on_error_status() {
+    let failing = serve(|_| (503, b"unavailable"));
+    let working = serve(|path| match path {
+        "/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471/ntkrnlmp.pdb" => (200, b"PDB"),
+        _ => (404, b""),
+    });
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory.path().join("ntkrnlmp.pdb");
+
+    let path = PdbDownloader::new(codeview())
+        .with_servers([failing, working])
+        .with_output(&output)
+        .download()
+        .unwrap();
+
+    assert_eq!(path, output);
+    assert_eq!(std::fs::read(&output).unwrap(), b"PDB");
+}
+
+#[test]
+fn download_size() {
+    let missing = serve(|_| (404, b"not found"));
+    let working = serve(|path| match path {
+        "/ntkrnlmp.pdb/ce7ffb00c20b87500211456b3e905c471/ntkrnlmp.pdb" => (200, b"PDB"),
+        _ => (404, b""),
+    });
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory.path().join("ntkrnlmp.pdb");
+
+    let downloader = PdbDownloader::new(codeview())
+        .with_servers([missing.clone(), working])
+        .with_output(&output);
+    assert_eq!(downloader.download_size().unwrap(), Some(3));
+
+    // Nothing is downloaded.
+    assert!(!output.exists());
+
+    let result = PdbDownloader::new(codeview())
+        .with_servers([missing])
+        .download_size();
+    assert!(matches!(result, Err(Error::Failed)));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use isr_dl_pdb::{
+    architecture_from_image_bytes, exports_from_file_bytes, exports_from_image_bytes,
+    image_base_from_image_bytes, CodeView, DebugId, PeKey,
+};
+
+use self::common::{pe_with_exports, IMAGE_BASE, TIMESTAMP};
+
+const EXPORTS: &[(&str, u32)] = &[
+    ("KeBugCheckEx", 0x1010),
+    ("PsActiveProcessHead", 0x1020),
+    ("PsInitialSystemProcess", 0x1030),
+];
+
+#[test]
+fn exports_in_memory_layout() {
+    let data = pe_with_exports(EXPORTS, 0x1000, 0x1000);
+    let symbols = exports_from_image_bytes(&data).unwrap();
+
+    assert_eq!(symbols.0.len(), EXPORTS.len());
+    for (name, address) in EXPORTS {
+        assert_eq!(symbols.0[*name], *address as u64);
+    }
+}
+
+#[test]
+fn exports_in_file_layout() {
+    let data = pe_with_exports(EXPORTS, 0x1000, 0x200);
+    let symbols = exports_from_file_bytes(&data).unwrap();
+
+    assert_eq!(symbols.0.len(), EXPORTS.len());
+    for (name, address) in EXPORTS {
+        assert_eq!(symbols.0[*name], *address as u64);
+    }
+
+    assert_eq!(architecture_from_image_bytes(&data).unwrap(), "Amd64");
+    assert_eq!(image_base_from_image_bytes(&data).unwrap(), IMAGE_BASE);
+}
+
+#[test]
+fn pe_key_without_codeview() {
+    let data = pe_with_exports(EXPORTS, 0x1000, 0x200);
+
+    let err = CodeView::from_image_bytes(&data).unwrap_err();
+    assert_eq!(err.to_string(), "CodeView not found");
+
+    let pe_key = match DebugId::from_image_bytes(&data, "ntoskrnl.exe").unwrap() {
+        DebugId::PeKey(pe_key) => pe_key,
+        DebugId::CodeView(_) => panic!("unexpected CodeView"),
+    };
+
+    // Two sections and the headers, each aligned to 0x1000.
+    assert_eq!(pe_key.key, format!("{TIMESTAMP:08X}3000"));
+    assert_eq!(
+        pe_key.server_path(),
+        format!("ntoskrnl.exe/{TIMESTAMP:08X}3000")
+    );
+    pe_key.validate().unwrap();
+
+    let from_image = PeKey::from_image_bytes(&data, "ntoskrnl.exe").unwrap();
+    assert_eq!(from_image.key, pe_key.key);
+}
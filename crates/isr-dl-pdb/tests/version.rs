@@ -0,0 +1,23 @@
+mod common;
+
+use isr_core::WindowsBuild;
+
+use self::common::{pe_with_exports, pe_with_version};
+
+#[test]
+fn file_version() {
+    let pe = pe_with_version([10, 0, 18362, 356]);
+    assert_eq!(
+        isr_dl_pdb::file_version_from_image_bytes(&pe).unwrap(),
+        Some(WindowsBuild::new(10, 0, 18362, 356))
+    );
+}
+
+#[test]
+fn no_version_resource() {
+    let pe = pe_with_exports(&[("KeBugCheckEx", 0x1000)], 0x1000, 0x200);
+    assert_eq!(
+        isr_dl_pdb::file_version_from_image_bytes(&pe).unwrap(),
+        None
+    );
+}
@@ -0,0 +1,46 @@
+//! Measures [`isr_dwarf::create_profile_with_options`] on a synthetic
+//! kernel.
+
+#[path = "../tests/common/kernel.rs"]
+#[allow(dead_code)]
+mod kernel;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use isr_core::ProfileOptions;
+
+use self::kernel::Kernel;
+
+/// Number of compilation units of the kernel.
+const UNITS: usize = 2_000;
+
+fn create_profile(c: &mut Criterion) {
+    let kernel = Kernel::new(UNITS);
+
+    let mut group = c.benchmark_group("create_profile");
+    group.sample_size(20);
+
+    for threads in [1, 4] {
+        let options = ProfileOptions::default().with_threads(threads);
+
+        group.bench_function(format!("threads_{threads}"), |b| {
+            b.iter_batched(
+                || kernel.files(),
+                |(kernel, systemmap)| {
+                    isr_dwarf::create_profile_with_options(kernel, systemmap, &options, |profile| {
+                        black_box(profile);
+                        Ok::<_, std::io::Error>(())
+                    })
+                    .unwrap()
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, create_profile);
+criterion_main!(benches);
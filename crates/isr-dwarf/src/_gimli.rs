@@ -190,6 +190,9 @@ pub fn load_dwarf_sections<'data>(
     use object::{Object as _, ObjectSection};
 
     // Load a `Section` that may own its data.
+    //
+    // Compressed sections (`SHF_COMPRESSED` with zlib or zstd, and GNU
+    // `.zdebug_*` sections) are decompressed by `object`.
     fn load_section<'data>(
         object: &object::File<'data>,
         name: &str,
@@ -9,9 +9,18 @@ pub enum Error {
     #[error(transparent)]
     Gimli(#[from] gimli::Error),
 
+    #[error("Unsupported architecture {0:?}")]
+    UnsupportedArchitecture(object::Architecture),
+
     #[error("invalid system map")]
     InvalidSystemMap,
 
+    #[error("suspect system map: {reason}")]
+    SuspectSystemMap { reason: String },
+
+    #[error("Lossy conversion in strict mode: {0}")]
+    Strict(isr_core::__private::Warning),
+
     #[error("Serialization error: {0}")]
-    Serialize(Box<dyn std::error::Error>),
+    Serialize(Box<dyn std::error::Error + Send + Sync>),
 }
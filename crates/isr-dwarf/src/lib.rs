@@ -1,4 +1,6 @@
 //! DWARF debugging information parsing and processing.
+//!
+//! Profiles can be created concurrently on multiple threads.
 
 mod _gimli;
 mod error;
@@ -6,4 +8,12 @@ mod profile;
 pub mod symbols;
 pub mod types;
 
-pub use self::{error::Error, profile::create_profile};
+#[cfg(feature = "unstable")]
+pub use self::profile::create_profile_with_report;
+pub use self::{
+    error::Error,
+    profile::{
+        create_profile, create_profile_from_object, create_profile_symbols_only,
+        create_profile_symbols_only_with_options, create_profile_with_options, has_debug_info,
+    },
+};
@@ -1,10 +1,16 @@
-use std::{borrow::Cow, fs::File, io::Read};
+use std::{borrow::Cow, collections::HashSet, fs::File, io::Read};
 
-use gimli::RunTimeEndian;
-use isr_core::{types::Types, Profile, Symbols};
-use object::{Endianness, Object as _};
+use gimli::{Dwarf, RunTimeEndian};
+use indexmap::IndexMap;
+use isr_core::{
+    __private::ProfileReport,
+    types::{StructKind, Types},
+    Profile, ProfileOptions, ProfileSource, SymbolBase, SymbolScope, Symbols,
+};
+use object::{Architecture, Endianness, Object as _, ObjectSymbol as _, SymbolKind};
 
 use super::{
+    _gimli::Reader,
     symbols::SystemMapSymbols as _,
     types::{DwarfCache, DwarfTypes as _},
     Error,
@@ -12,23 +18,357 @@ use super::{
 
 pub fn create_profile<F, E>(
     kernel_file: File,
-    mut systemmap_file: File,
+    systemmap_file: File,
+    serialize: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    create_profile_with_options(
+        kernel_file,
+        systemmap_file,
+        &ProfileOptions::default(),
+        serialize,
+    )
+}
+
+pub fn create_profile_with_options<F, E>(
+    kernel_file: File,
+    systemmap_file: File,
+    options: &ProfileOptions,
     serialize: F,
 ) -> Result<(), Error>
 where
     F: FnOnce(&Profile) -> Result<(), E>,
-    E: std::error::Error + 'static,
+    E: std::error::Error + Send + Sync + 'static,
 {
+    create_profile_inner(kernel_file, systemmap_file, options, serialize)?;
+    Ok(())
+}
+
+isr_core::unstable_api! {
+    /// Creates a profile and returns a summary of the profile creation.
+    pub fn create_profile_with_report<F, E>(
+        kernel_file: File,
+        systemmap_file: File,
+        options: &ProfileOptions,
+        serialize: F,
+    ) -> Result<ProfileReport, Error>
+    where
+        F: FnOnce(&Profile) -> Result<(), E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        create_profile_inner(kernel_file, systemmap_file, options, serialize)
+    }
+}
+
+fn create_profile_inner<F, E>(
+    kernel_file: File,
+    mut systemmap_file: File,
+    options: &ProfileOptions,
+    serialize: F,
+) -> Result<ProfileReport, Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = options.run_span("dwarf").entered();
+
+    // Parse the symbols first, so that a suspect System.map is reported
+    // before the (expensive) type collection.
+    let mut systemmap = Vec::new();
+    systemmap_file.read_to_end(&mut systemmap)?;
+    let systemmap = String::from_utf8_lossy(&systemmap);
+    let mut symbols = parse_systemmap(&systemmap, options)?;
+
     let kernel_mmap = unsafe { memmap2::Mmap::map(&kernel_file)? };
     let object = object::File::parse(&*kernel_mmap)?;
-    let endian = match object.endianness() {
-        Endianness::Little => RunTimeEndian::Little,
-        Endianness::Big => RunTimeEndian::Big,
+
+    let dwarf_sections = super::_gimli::load_dwarf_sections(&object)?;
+    let dwarf = super::_gimli::load_dwarf(&dwarf_sections, endian(&object));
+    let (mut types, report) = collect_types(&dwarf, options)?;
+
+    let truncated = options.apply_max_name_length(&mut symbols, &mut types);
+    if truncated > 0 {
+        tracing::info!(truncated, "truncated long names");
+    }
+
+    let symbol_base = systemmap_symbol_base(&symbols);
+    let preferred_base = options.preferred_base.or(systemmap_text(&symbols));
+
+    tracing::debug!("writing profile");
+    let profile = Profile::new(Cow::Borrowed("Amd64"), symbols, types)
+        .with_source(ProfileSource::Dwarf)
+        .with_kernel_version(options.kernel_version)
+        .with_symbol_base(symbol_base)
+        .with_preferred_base(preferred_base)
+        .with_symbol_scope(options.symbol_scope);
+
+    serialize(&profile).map_err(|err| Error::Serialize(err.into()))?;
+
+    Ok(report)
+}
+
+pub fn create_profile_symbols_only<F, E>(systemmap_file: File, serialize: F) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    create_profile_symbols_only_with_options(systemmap_file, &ProfileOptions::default(), serialize)
+}
+
+/// Creates a profile from a System.map alone.
+///
+/// The profile contains symbols, but no types. Useful when the debug
+/// symbols of the kernel aren't available.
+pub fn create_profile_symbols_only_with_options<F, E>(
+    mut systemmap_file: File,
+    options: &ProfileOptions,
+    serialize: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = options.run_span("dwarf").entered();
+
+    let mut systemmap = Vec::new();
+    systemmap_file.read_to_end(&mut systemmap)?;
+    let systemmap = String::from_utf8_lossy(&systemmap);
+    let mut symbols = parse_systemmap(&systemmap, options)?;
+
+    let mut types = Types::default();
+    let truncated = options.apply_max_name_length(&mut symbols, &mut types);
+    if truncated > 0 {
+        tracing::info!(truncated, "truncated long names");
+    }
+
+    let symbol_base = systemmap_symbol_base(&symbols);
+    let preferred_base = options.preferred_base.or(systemmap_text(&symbols));
+
+    tracing::debug!("writing profile");
+    let profile = Profile::new(Cow::Borrowed("Amd64"), symbols, types)
+        .with_source(ProfileSource::Dwarf)
+        .with_kernel_version(options.kernel_version)
+        .with_symbol_base(symbol_base)
+        .with_preferred_base(preferred_base)
+        .with_symbol_scope(options.symbol_scope);
+
+    serialize(&profile).map_err(|err| Error::Serialize(err.into()))?;
+
+    Ok(())
+}
+
+/// Checks whether the object file (e.g. a PE built by mingw) contains
+/// embedded DWARF debug information.
+pub fn has_debug_info(data: &[u8]) -> bool {
+    match object::File::parse(data) {
+        Ok(object) => object.section_by_name(".debug_info").is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Creates a profile from an object file with embedded DWARF debug
+/// information (e.g. a PE built by mingw).
+///
+/// Symbols are taken from the symbol table of the object file and are
+/// stored as RVAs.
+pub fn create_profile_from_object<F, E>(
+    object_file: File,
+    options: &ProfileOptions,
+    serialize: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = options.run_span("dwarf").entered();
+    let mmap = unsafe { memmap2::Mmap::map(&object_file)? };
+    let object = object::File::parse(&*mmap)?;
+
+    let architecture = match object.architecture() {
+        Architecture::X86_64 => "Amd64",
+        Architecture::I386 => "X86",
+        Architecture::Aarch64 => "Arm64",
+        architecture => return Err(Error::UnsupportedArchitecture(architecture)),
     };
 
+    tracing::debug!("collecting symbols");
+    let base = object.relative_address_base();
+    let mut symbols = IndexMap::new();
+    for symbol in object.symbols() {
+        if symbol.is_undefined() || !matches!(symbol.kind(), SymbolKind::Text | SymbolKind::Data) {
+            continue;
+        }
+
+        match options.symbol_scope {
+            SymbolScope::All => (),
+            SymbolScope::Exported if symbol.is_global() => (),
+            SymbolScope::Exported | SymbolScope::None => continue,
+        }
+
+        let name = match symbol.name() {
+            Ok(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+
+        let rva = match symbol.address().checked_sub(base) {
+            Some(rva) => rva,
+            None => continue,
+        };
+
+        symbols.entry(Cow::Borrowed(name)).or_insert(rva);
+    }
+
+    tracing::info!(
+        scope = ?options.symbol_scope,
+        count = symbols.len(),
+        "collected symbols"
+    );
+
+    let mut symbols = Symbols(symbols);
+    add_unversioned_aliases(&mut symbols, options);
+
     let dwarf_sections = super::_gimli::load_dwarf_sections(&object)?;
-    let dwarf = super::_gimli::load_dwarf(&dwarf_sections, endian);
+    let dwarf = super::_gimli::load_dwarf(&dwarf_sections, endian(&object));
+    let (mut types, _) = collect_types(&dwarf, options)?;
+
+    let truncated = options.apply_max_name_length(&mut symbols, &mut types);
+    if truncated > 0 {
+        tracing::info!(truncated, "truncated long names");
+    }
+
+    tracing::debug!("writing profile");
+    let profile = Profile::new(Cow::Borrowed(architecture), symbols, types)
+        .with_source(ProfileSource::Dwarf)
+        .with_kernel_version(options.kernel_version)
+        .with_symbol_base(SymbolBase::Rva)
+        .with_preferred_base(options.preferred_base.or((base != 0).then_some(base)))
+        .with_symbol_scope(options.symbol_scope);
+
+    serialize(&profile).map_err(|err| Error::Serialize(err.into()))?;
+
+    Ok(())
+}
+
+fn parse_systemmap<'a>(systemmap: &'a str, options: &ProfileOptions) -> Result<Symbols<'a>, Error> {
+    tracing::debug!("collecting symbols");
+    let mut symbols = Symbols::parse_with_kinds(
+        systemmap,
+        options.symbol_scope,
+        &options.systemmap_extra_kinds,
+    )?;
+    tracing::info!(
+        scope = ?options.symbol_scope,
+        count = symbols.0.len(),
+        "collected symbols"
+    );
+
+    // A System.map without symbols is expected when no symbols are
+    // requested.
+    if !options.allow_suspect_systemmap && options.symbol_scope != SymbolScope::None {
+        symbols.check()?;
+    }
+
+    add_unversioned_aliases(&mut symbols, options);
+
+    Ok(symbols)
+}
+
+fn add_unversioned_aliases(symbols: &mut Symbols, options: &ProfileOptions) {
+    let added = options.apply_unversioned_symbol_aliases(symbols);
+    if added > 0 {
+        tracing::info!(added, "added unversioned symbol aliases");
+    }
+}
+
+/// Returns the symbol base of a System.map, which contains absolute
+/// addresses at the link-time base.
+fn systemmap_symbol_base(symbols: &Symbols) -> SymbolBase {
+    SymbolBase::VirtualAddress {
+        default_base: systemmap_text(symbols).unwrap_or(0),
+    }
+}
+
+/// Returns the link-time address of the kernel image (`_text`).
+fn systemmap_text(symbols: &Symbols) -> Option<u64> {
+    symbols.0.get("_text").copied()
+}
 
+fn endian(object: &object::File) -> RunTimeEndian {
+    match object.endianness() {
+        Endianness::Little => RunTimeEndian::Little,
+        Endianness::Big => RunTimeEndian::Big,
+    }
+}
+
+/// Collects the types of the units on multiple threads.
+///
+/// Each thread collects a contiguous range of units, skipping the types
+/// it has already seen. The ranges are then added in order, so the result
+/// is the same as when collecting sequentially.
+fn collect_types_parallel<'data>(
+    dwarf: &Dwarf<Reader<'data>>,
+    types: &mut Types<'data>,
+    cache: &mut DwarfCache,
+    threads: usize,
+) -> Result<(), Error> {
+    let mut headers = Vec::new();
+    let mut iter = dwarf.units();
+    while let Some(header) = iter.next()? {
+        headers.push(header);
+    }
+
+    tracing::debug!(threads, "collecting types: {} units", headers.len());
+
+    let chunk_size = headers.len().div_ceil(threads);
+    let shared_cache = &*cache;
+
+    // Worker threads don't inherit the span of the profile creation.
+    let span = tracing::Span::current();
+    let chunks = std::thread::scope(|scope| {
+        let handles = headers
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let span = span.clone();
+                scope.spawn(move || {
+                    let _span = span.entered();
+                    let mut seen = HashSet::new();
+                    let mut items = Vec::new();
+
+                    for header in chunk {
+                        let unit = dwarf.unit(header.clone())?;
+                        let unit_ref = unit.unit_ref(dwarf);
+                        items.extend(shared_cache.collect(&unit_ref, &mut seen)?);
+                    }
+
+                    Ok::<_, gimli::Error>(items)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for items in chunks {
+        cache.insert(types, items?, true);
+    }
+
+    Ok(())
+}
+
+fn collect_types<'data>(
+    dwarf: &Dwarf<Reader<'data>>,
+    options: &ProfileOptions,
+) -> Result<(Types<'data>, ProfileReport), Error> {
     let mut types = Types::default();
 
     tracing::debug!("collecting types");
@@ -38,28 +378,68 @@ where
         unit_len += 1;
     }
 
-    let mut cache = DwarfCache::new();
-    let mut iter = dwarf.units();
-    let mut unit_idx = 0;
-    while let Some(header) = iter.next()? {
-        unit_idx += 1;
+    let mut cache = DwarfCache::new()
+        .with_record_provenance(options.record_provenance)
+        .with_strictness(options.strictness)
+        .with_max_type_depth(options.max_type_depth());
+    if let Some(resolution_policy) = options.resolution_policy {
+        cache = cache.with_resolution_policy(resolution_policy);
+    }
+    let threads = options.threads.min(unit_len);
+    if threads > 1 {
+        collect_types_parallel(dwarf, &mut types, &mut cache, threads)?;
+    }
+    else {
+        let mut iter = dwarf.units();
+        let mut unit_idx = 0;
+        while let Some(header) = iter.next()? {
+            unit_idx += 1;
 
-        tracing::debug!("collecting types: {unit_idx}/{unit_len}");
+            tracing::debug!("collecting types: {unit_idx}/{unit_len}");
 
-        let unit = dwarf.unit(header)?;
-        let unit_ref = unit.unit_ref(&dwarf);
-        types.add(&unit_ref, &mut cache)?;
+            let unit = dwarf.unit(header)?;
+            let unit_ref = unit.unit_ref(dwarf);
+            types.add(&unit_ref, &mut cache)?;
+        }
     }
 
-    tracing::debug!("collecting symbols");
-    let mut systemmap = String::new();
-    systemmap_file.read_to_string(&mut systemmap)?;
-    let symbols = Symbols::parse(&systemmap)?;
+    if options.forward_declaration_stubs {
+        let mut stubs = 0;
+        for (tag, name) in cache.forward_declarations() {
+            let kind = match tag {
+                gimli::DW_TAG_structure_type => StructKind::Struct,
+                gimli::DW_TAG_union_type => StructKind::Union,
+                _ => continue,
+            };
 
-    tracing::debug!("writing profile");
-    let profile = Profile::new(Cow::Borrowed("Amd64"), symbols, types);
+            if types.add_stub(Cow::Owned(String::from(name)), kind) {
+                stubs += 1;
+            }
+        }
 
-    serialize(&profile).map_err(|err| Error::Serialize(err.into()))?;
+        if stubs > 0 {
+            tracing::info!(stubs, "added forward declaration stubs");
+        }
+    }
 
-    Ok(())
+    let report = cache.into_report();
+    if report.has_duplicates() {
+        tracing::warn!(
+            duplicates = report.duplicates(),
+            "types defined more than once; the profile may be less reliable"
+        );
+    }
+
+    if report.is_partial() {
+        tracing::warn!(
+            imports = report.supplementary_imports.len(),
+            "units imported from a supplementary object file; the profile is incomplete"
+        );
+    }
+
+    report
+        .check_strictness(options.strictness)
+        .map_err(Error::Strict)?;
+
+    Ok((types, report))
 }
@@ -1,24 +1,81 @@
 use indexmap::IndexMap;
-use isr_core::Symbols;
+use isr_core::{SymbolScope, Symbols};
 
 use super::Error;
 
+/// Minimum number of symbols in a usable System.map.
+const MIN_SYMBOLS: usize = 1_000;
+
+/// Number of symbols below which a distribution kernel is unusual.
+const TYPICAL_MIN_SYMBOLS: usize = 50_000;
+
+/// Maximum percentage of zero addresses in a usable System.map.
+const MAX_ZERO_PERCENT: usize = 50;
+
 pub trait SystemMapSymbols<'a> {
     fn parse(systemmap: &'a str) -> Result<Symbols<'a>, Error>;
+
+    /// Parses only the symbols within the given scope.
+    ///
+    /// Exported symbols are the global (uppercase) text and data symbols.
+    fn parse_with_scope(systemmap: &'a str, scope: SymbolScope) -> Result<Symbols<'a>, Error>;
+
+    /// Parses only the symbols within the given scope, including the
+    /// additional kinds of symbols (e.g. `b`/`B` for BSS, `r`/`R` for
+    /// read-only data).
+    ///
+    /// Only the global (uppercase) additional kinds are exported.
+    fn parse_with_kinds(
+        systemmap: &'a str,
+        scope: SymbolScope,
+        extra_kinds: &[char],
+    ) -> Result<Symbols<'a>, Error>;
+
+    /// Checks that the symbols look like a complete System.map.
+    ///
+    /// Fails on System.maps with addresses zeroed out (e.g. copied from
+    /// a live system with `kernel.kptr_restrict` set) or with too few
+    /// symbols to be useful.
+    fn check(&self) -> Result<(), Error>;
 }
 
 impl<'a> SystemMapSymbols<'a> for Symbols<'a> {
     fn parse(systemmap: &'a str) -> Result<Symbols<'a>, Error> {
+        Self::parse_with_scope(systemmap, SymbolScope::All)
+    }
+
+    fn parse_with_scope(systemmap: &'a str, scope: SymbolScope) -> Result<Symbols<'a>, Error> {
+        Self::parse_with_kinds(systemmap, scope, &[])
+    }
+
+    fn parse_with_kinds(
+        systemmap: &'a str,
+        scope: SymbolScope,
+        extra_kinds: &[char],
+    ) -> Result<Symbols<'a>, Error> {
         let mut result = IndexMap::new();
 
+        let mut kinds = match scope {
+            SymbolScope::All => vec!['d', 'D', 't', 'T'],
+            SymbolScope::Exported => vec!['D', 'T'],
+            SymbolScope::None => return Ok(Self(result)),
+        };
+
+        kinds.extend(extra_kinds.iter().filter(|kind| match scope {
+            SymbolScope::Exported => kind.is_ascii_uppercase(),
+            _ => true,
+        }));
+
         for line in systemmap.lines() {
             let mut parts = line.split_whitespace();
             let rva = parts.next().ok_or(Error::InvalidSystemMap)?;
             let kind = parts.next().ok_or(Error::InvalidSystemMap)?;
             let name = parts.next().ok_or(Error::InvalidSystemMap)?;
 
-            if !matches!(kind, "d" | "D" | "t" | "T") {
-                continue;
+            let mut chars = kind.chars();
+            match (chars.next(), chars.next()) {
+                (Some(kind), None) if kinds.contains(&kind) => (),
+                _ => continue,
             }
 
             let rva = u64::from_str_radix(rva, 16).map_err(|_| Error::InvalidSystemMap)?;
@@ -27,4 +84,30 @@ impl<'a> SystemMapSymbols<'a> for Symbols<'a> {
 
         Ok(Self(result))
     }
+
+    fn check(&self) -> Result<(), Error> {
+        let count = self.0.len();
+        if count < MIN_SYMBOLS {
+            return Err(Error::SuspectSystemMap {
+                reason: format!("only {count} symbols (expected at least {MIN_SYMBOLS})"),
+            });
+        }
+
+        let zero_count = self.0.values().filter(|&&address| address == 0).count();
+        if zero_count * 100 > count * MAX_ZERO_PERCENT {
+            return Err(Error::SuspectSystemMap {
+                reason: format!("{zero_count} of {count} symbols have zero address"),
+            });
+        }
+
+        if count < TYPICAL_MIN_SYMBOLS {
+            tracing::warn!(
+                count,
+                expected = TYPICAL_MIN_SYMBOLS,
+                "System.map has unusually few symbols"
+            );
+        }
+
+        Ok(())
+    }
 }
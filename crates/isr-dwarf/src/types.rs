@@ -1,13 +1,17 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet};
 
 use gimli::{
-    Attribute, DebuggingInformationEntry, EntriesTree, EntriesTreeNode, Error, Reader as _,
-    UnitRef, UnitSectionOffset,
+    Attribute, AttributeValue, DebugInfoOffset, DebuggingInformationEntry, Dwarf, EntriesTree,
+    EntriesTreeNode, Error, Reader as _, Unit, UnitRef, UnitSectionOffset,
 };
-use indexmap::map::Entry;
-use isr_core::types::{
-    ArrayRef, BaseRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Struct, StructKind,
-    StructRef, Type, Types, Variant,
+use indexmap::{map::Entry, IndexSet};
+use isr_core::{
+    __private::{ProfileReport, Warning},
+    types::{
+        ArrayRef, BaseRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Provenance, Struct,
+        StructKind, StructRef, Type, Types, Variant,
+    },
+    ResolutionPolicy, Strictness, DEFAULT_MAX_TYPE_DEPTH,
 };
 use smallvec::SmallVec;
 
@@ -30,7 +34,580 @@ fn type_name<'data>(
     }
 }
 
-pub type DwarfCache = std::collections::HashSet<(String, u64, u64)>;
+/// Key used to deduplicate the type definitions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum DeclarationKey {
+    /// Declaration (file, line, column).
+    Declaration(String, u64, u64),
+
+    /// Tag and name, for types without declaration information.
+    Name(gimli::DwTag, String),
+
+    /// Partial unit, at the offset of its entry in `.debug_info`.
+    PartialUnit(usize),
+}
+
+/// Item collected from a unit by [`collect_unit`].
+pub(crate) enum UnitItem<'data> {
+    /// Named type seen as a forward declaration.
+    ForwardDeclaration(gimli::DwTag, String),
+
+    /// Unit imported from a supplementary object file.
+    SupplementaryImport(u64),
+
+    /// Type definition.
+    Definition(Box<Definition<'data>>),
+}
+
+/// Type definition collected from a unit, not yet added to the types.
+pub(crate) struct Definition<'data> {
+    key: DeclarationKey,
+    tag: gimli::DwTag,
+    name: Cow<'data, str>,
+
+    /// `None` if the definition was skipped.
+    type_: Option<DefinedType<'data>>,
+
+    /// Only collected with [`DwarfCache::with_record_provenance`].
+    provenance: Option<Provenance>,
+
+    /// Lossy conversions of the definition.
+    warnings: Vec<Warning>,
+}
+
+enum DefinedType<'data> {
+    Enum(Enum<'data>),
+    Struct(Struct<'data>),
+}
+
+/// Cache of the types already added, used for deduplication.
+#[derive(Debug)]
+pub struct DwarfCache {
+    /// Types already added.
+    declarations: HashSet<DeclarationKey>,
+
+    /// Named types seen only as forward declarations (so far).
+    forward_declarations: IndexSet<(gimli::DwTag, String)>,
+
+    /// Types defined more than once.
+    report: ProfileReport,
+
+    /// Record where the types were defined.
+    record_provenance: bool,
+
+    /// Resolution of types defined more than once.
+    resolution_policy: ResolutionPolicy,
+
+    /// Handling of lossy conversions.
+    strictness: Strictness,
+
+    /// Maximum depth of nested types.
+    max_type_depth: usize,
+}
+
+impl Default for DwarfCache {
+    fn default() -> Self {
+        Self {
+            declarations: HashSet::new(),
+            forward_declarations: IndexSet::new(),
+            report: ProfileReport::default(),
+            record_provenance: false,
+            resolution_policy: ResolutionPolicy::MostFields,
+            strictness: Strictness::default(),
+            max_type_depth: DEFAULT_MAX_TYPE_DEPTH,
+        }
+    }
+}
+
+impl DwarfCache {
+    /// Creates a new empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how types defined more than once are resolved.
+    ///
+    /// Defaults to [`ResolutionPolicy::MostFields`].
+    pub fn with_resolution_policy(self, resolution_policy: ResolutionPolicy) -> Self {
+        Self {
+            resolution_policy,
+            ..self
+        }
+    }
+
+    /// Sets how lossy conversions are handled.
+    ///
+    /// The conversions are recorded in the report in any case.
+    pub fn with_strictness(self, strictness: Strictness) -> Self {
+        Self { strictness, ..self }
+    }
+
+    /// Sets the maximum depth of nested types (e.g. a pointer to a
+    /// pointer).
+    ///
+    /// Deeper types are replaced with `void`. Defaults to
+    /// [`DEFAULT_MAX_TYPE_DEPTH`].
+    pub fn with_max_type_depth(self, max_type_depth: usize) -> Self {
+        Self {
+            max_type_depth,
+            ..self
+        }
+    }
+
+    /// Sets whether the definitions of the added types are recorded in
+    /// [`Types::provenance`].
+    pub fn with_record_provenance(self, record_provenance: bool) -> Self {
+        Self {
+            record_provenance,
+            ..self
+        }
+    }
+
+    /// Returns the tags and names of the forward declared types.
+    ///
+    /// Types that are also defined are included as well.
+    pub fn forward_declarations(&self) -> impl Iterator<Item = (gimli::DwTag, &str)> {
+        self.forward_declarations
+            .iter()
+            .map(|(tag, name)| (*tag, name.as_str()))
+    }
+
+    /// Returns the summary of the types added.
+    pub(crate) fn into_report(self) -> ProfileReport {
+        self.report
+    }
+
+    /// Collects the definitions of a unit that aren't in `seen`.
+    ///
+    /// Doesn't modify the cache, so units can be collected in parallel,
+    /// each thread with its own `seen` set. Partial units are skipped,
+    /// their definitions are collected with the units importing them.
+    pub(crate) fn collect<'data>(
+        &self,
+        unit: &UnitRef<Reader<'data>>,
+        seen: &mut HashSet<DeclarationKey>,
+    ) -> Result<Vec<UnitItem<'data>>, Error> {
+        if is_partial_unit(unit)? {
+            return Ok(Vec::new());
+        }
+
+        collect_unit(
+            unit,
+            seen,
+            self.strictness,
+            self.resolution_policy,
+            self.record_provenance,
+            self.max_type_depth,
+        )
+    }
+
+    /// Adds the items collected from a unit to the types, in order.
+    ///
+    /// With `deduplicate`, definitions already added (collected by another
+    /// thread) are skipped. Otherwise, the items must have been collected
+    /// with the declarations of the cache as the `seen` set.
+    pub(crate) fn insert<'data>(
+        &mut self,
+        types: &mut Types<'data>,
+        items: Vec<UnitItem<'data>>,
+        deduplicate: bool,
+    ) {
+        // At most one new type per definition, so the maps grow once per
+        // batch of items rather than while inserting them.
+        let (mut enums, mut structs) = (0, 0);
+        for item in &items {
+            if let UnitItem::Definition(definition) = item {
+                match definition.tag {
+                    gimli::DW_TAG_enumeration_type => enums += 1,
+                    _ => structs += 1,
+                }
+            }
+        }
+        types.enums.reserve(enums);
+        types.structs.reserve(structs);
+
+        for item in items {
+            let definition = match item {
+                UnitItem::ForwardDeclaration(tag, name) => {
+                    self.forward_declarations.insert((tag, name));
+                    continue;
+                }
+                UnitItem::SupplementaryImport(offset) => {
+                    self.report.supplementary_imports.push(offset);
+                    continue;
+                }
+                UnitItem::Definition(definition) => *definition,
+            };
+
+            if deduplicate && !self.declarations.insert(definition.key.clone()) {
+                continue;
+            }
+
+            self.insert_definition(types, definition);
+        }
+    }
+
+    fn insert_definition<'data>(
+        &mut self,
+        types: &mut Types<'data>,
+        definition: Definition<'data>,
+    ) {
+        let Definition {
+            tag,
+            name,
+            type_,
+            provenance,
+            warnings,
+            ..
+        } = definition;
+
+        self.report.warnings.extend(warnings);
+
+        // Another declaration with the same name was already added,
+        // only one of them is kept.
+        if tag == gimli::DW_TAG_enumeration_type {
+            if types.enums.contains_key(&name) {
+                self.report.duplicate_enums.push(name.to_string());
+            }
+        }
+        else if types.structs.contains_key(&name) {
+            self.report.duplicate_structs.push(name.to_string());
+        }
+
+        let policy = self.resolution_policy;
+        let kept = match type_ {
+            Some(DefinedType::Enum(enum_)) => insert_enum(types, name.clone(), enum_, policy),
+            Some(DefinedType::Struct(udt)) => insert_struct(types, name.clone(), udt, policy),
+            None => false,
+        };
+
+        if let (true, Some(provenance)) = (kept, provenance) {
+            match tag {
+                gimli::DW_TAG_enumeration_type => types.provenance.enums.insert(name, provenance),
+                _ => types.provenance.structs.insert(name, provenance),
+            };
+        }
+    }
+}
+
+isr_core::unstable_api! {
+    impl DwarfCache {
+        /// Returns the summary of the types added so far.
+        pub fn report(&self) -> &ProfileReport {
+            &self.report
+        }
+    }
+}
+
+/// State shared by the parsing of a type definition.
+pub struct ParseContext<'r> {
+    /// Report the lossy conversions are recorded in.
+    report: &'r mut ProfileReport,
+
+    /// How lossy conversions are handled.
+    strictness: Strictness,
+
+    /// Which definition is kept when names collide.
+    policy: ResolutionPolicy,
+
+    /// Remaining depth of nested types.
+    depth: usize,
+}
+
+impl ParseContext<'_> {
+    /// Records a lossy conversion of the type `name`.
+    fn warn(&mut self, name: impl Into<String>, message: impl Into<String>) {
+        self.report.warn(self.strictness, name, message);
+    }
+
+    /// Calls `f` one level of nesting deeper.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.depth -= 1;
+        let result = f(self);
+        self.depth += 1;
+        result
+    }
+}
+
+/// Returns whether the unit is a partial unit.
+///
+/// The definitions of a partial unit are collected where it's imported,
+/// rather than as a unit of its own.
+fn is_partial_unit(unit: &UnitRef<Reader<'_>>) -> Result<bool, Error> {
+    let mut entries = unit.entries();
+    Ok(entries
+        .next_dfs()?
+        .is_some_and(|(_, entry)| entry.tag() == gimli::DW_TAG_partial_unit))
+}
+
+/// Returns the unit containing the entry at `offset`.
+fn unit_at<'data>(
+    dwarf: &Dwarf<Reader<'data>>,
+    offset: DebugInfoOffset,
+) -> Result<Option<Unit<Reader<'data>>>, Error> {
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let Some(start) = header.offset().as_debug_info_offset()
+        else {
+            continue;
+        };
+
+        if (start.0..start.0 + header.length_including_self()).contains(&offset.0) {
+            return dwarf.unit(header).map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Collects the definitions of a unit that aren't in `seen`, including
+/// those of the partial units it imports.
+fn collect_unit<'data>(
+    unit: &UnitRef<Reader<'data>>,
+    seen: &mut HashSet<DeclarationKey>,
+    strictness: Strictness,
+    policy: ResolutionPolicy,
+    record_provenance: bool,
+    max_depth: usize,
+) -> Result<Vec<UnitItem<'data>>, Error> {
+    let mut items = Vec::new();
+    let mut tree = unit.entries_tree(None)?;
+    let mut children = tree.root()?.children();
+
+    while let Some(child) = children.next()? {
+        if child.entry().tag() == gimli::DW_TAG_imported_unit {
+            match child
+                .entry()
+                .attr(gimli::DW_AT_import)?
+                .as_ref()
+                .map(Attribute::value)
+            {
+                // Each partial unit is collected once, where it's first
+                // imported.
+                Some(AttributeValue::DebugInfoRef(offset))
+                    if seen.insert(DeclarationKey::PartialUnit(offset.0)) =>
+                {
+                    match unit_at(unit.dwarf, offset)? {
+                        Some(imported) => items.extend(collect_unit(
+                            &imported.unit_ref(unit.dwarf),
+                            seen,
+                            strictness,
+                            policy,
+                            record_provenance,
+                            max_depth,
+                        )?),
+                        None => tracing::warn!(offset = offset.0, "imported unit not found"),
+                    }
+                }
+                Some(AttributeValue::DebugInfoRefSup(offset)) => {
+                    tracing::warn!(
+                        offset = offset.0,
+                        "imported unit in a supplementary object file"
+                    );
+
+                    items.push(UnitItem::SupplementaryImport(offset.0 as u64));
+                }
+                _ => (),
+            }
+
+            continue;
+        }
+
+        if !matches!(
+            child.entry().tag(),
+            gimli::DW_TAG_enumeration_type
+                | gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_union_type
+        ) {
+            continue;
+        }
+
+        if child.entry().declaration()?.unwrap_or(false) {
+            if let Some(name) = child.entry().name(unit)? {
+                items.push(UnitItem::ForwardDeclaration(child.entry().tag(), name));
+            }
+
+            continue;
+        }
+
+        let decl_file = child.entry().decl_file(unit)?;
+        let decl_line = child.entry().decl_line()?;
+        let decl_column = child.entry().decl_column()?;
+
+        let key = match (&decl_file, decl_line, decl_column) {
+            (Some(decl_file), Some(decl_line), Some(decl_column)) => {
+                DeclarationKey::Declaration(decl_file.clone(), decl_line, decl_column)
+            }
+            _ => {
+                // Fall back to deduplication by name.
+                let name = type_name(unit, child.entry())?;
+                tracing::warn!(%name, "missing declaration information");
+
+                DeclarationKey::Name(child.entry().tag(), name.into_owned())
+            }
+        };
+
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+
+        let name = type_name(unit, child.entry())?;
+        let mut report = ProfileReport::default();
+        let mut context = ParseContext {
+            report: &mut report,
+            strictness,
+            policy,
+            depth: max_depth,
+        };
+        let tag = child.entry().tag();
+        let type_ = match tag {
+            gimli::DW_TAG_enumeration_type => {
+                parse_enum(unit, child, &mut context)?.map(DefinedType::Enum)
+            }
+            gimli::DW_TAG_structure_type => Some(DefinedType::Struct(parse_struct(
+                unit,
+                child,
+                StructKind::Struct,
+                &mut context,
+            )?)),
+            gimli::DW_TAG_union_type => Some(DefinedType::Struct(parse_struct(
+                unit,
+                child,
+                StructKind::Union,
+                &mut context,
+            )?)),
+
+            // Skip other tags.
+            _ => None,
+        };
+
+        let provenance = record_provenance.then(|| Provenance {
+            unit: unit
+                .name
+                .as_ref()
+                .and_then(|name| name.to_string_lossy().ok())
+                .map(String::from),
+            file: decl_file,
+            line: decl_line,
+        });
+
+        items.push(UnitItem::Definition(Box::new(Definition {
+            key,
+            tag,
+            name,
+            type_,
+            provenance,
+            warnings: report.warnings,
+        })));
+    }
+
+    Ok(items)
+}
+
+#[tracing::instrument(skip_all, fields(name))]
+fn parse_enum<'data>(
+    unit: &UnitRef<Reader<'data>>,
+    node: EntriesTreeNode<Reader<'data>>,
+    context: &mut ParseContext<'_>,
+) -> Result<Option<Enum<'data>>, Error> {
+    let name = type_name(unit, node.entry())?;
+    tracing::Span::current().record("name", &*name);
+
+    let type_ = match node.entry().type_(unit)? {
+        Some(type_) => type_,
+        None => {
+            context.warn(&*name, "enum doesn't have a type; skipping");
+            return Ok(None);
+        }
+    };
+
+    let mut enum_ = Enum::new(Type::from_type(unit, type_, context)?, Default::default());
+
+    enum_.add_fields(unit, node, context)?;
+    Ok(Some(enum_))
+}
+
+#[tracing::instrument(skip_all, fields(name))]
+fn parse_struct<'data>(
+    unit: &UnitRef<Reader<'data>>,
+    node: EntriesTreeNode<Reader<'data>>,
+    kind: StructKind,
+    context: &mut ParseContext<'_>,
+) -> Result<Struct<'data>, Error> {
+    let name = type_name(unit, node.entry())?;
+    tracing::Span::current().record("name", &*name);
+
+    let mut udt = Struct {
+        kind,
+        size: node.entry().byte_size()?.unwrap_or(0),
+        fields: Default::default(),
+        incomplete: false,
+    };
+
+    udt.add_fields(unit, node, context)?;
+    Ok(udt)
+}
+
+/// Adds an enum, returns `true` if it was kept (see [`ResolutionPolicy`]).
+fn insert_enum<'data>(
+    types: &mut Types<'data>,
+    name: Cow<'data, str>,
+    new_enum: Enum<'data>,
+    policy: ResolutionPolicy,
+) -> bool {
+    match types.enums.entry(name) {
+        Entry::Vacant(entry) => {
+            entry.insert(new_enum);
+            true
+        }
+        Entry::Occupied(mut entry) => {
+            if !policy.replaces_enum(&new_enum, entry.get()) {
+                return false;
+            }
+
+            tracing::warn!(
+                name = %entry.key(),
+                new_enum_fields = new_enum.fields.len(),
+                previous_enum_fields = entry.get().fields.len(),
+                "duplicate enum name; overwriting"
+            );
+
+            *entry.get_mut() = new_enum;
+            true
+        }
+    }
+}
+
+/// Adds a struct or union, returns `true` if it was kept (see
+/// [`ResolutionPolicy`]).
+fn insert_struct<'data>(
+    types: &mut Types<'data>,
+    name: Cow<'data, str>,
+    new_udt: Struct<'data>,
+    policy: ResolutionPolicy,
+) -> bool {
+    match types.structs.entry(name) {
+        Entry::Vacant(entry) => {
+            entry.insert(new_udt);
+            true
+        }
+        Entry::Occupied(mut entry) => {
+            if !policy.replaces_struct(&new_udt, entry.get()) {
+                return false;
+            }
+
+            tracing::warn!(
+                name = %entry.key(),
+                new_udt_fields = new_udt.fields.len(),
+                previous_udt_fields = entry.get().fields.len(),
+                "duplicate UDT name; overwriting"
+            );
+
+            *entry.get_mut() = new_udt;
+            true
+        }
+    }
+}
 
 pub trait DwarfTypes<'data>
 where
@@ -38,18 +615,24 @@ where
 {
     fn add(&mut self, unit: &UnitRef<Reader<'data>>, cache: &mut DwarfCache) -> Result<(), Error>;
 
+    /// Adds an enum, returns `true` if it was kept (see
+    /// [`ResolutionPolicy`]).
     fn add_enum(
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
-    ) -> Result<(), Error>;
+        context: &mut ParseContext<'_>,
+    ) -> Result<bool, Error>;
 
+    /// Adds a struct or union, returns `true` if it was kept (see
+    /// [`ResolutionPolicy`]).
     fn add_struct(
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
         kind: StructKind,
-    ) -> Result<(), Error>;
+        context: &mut ParseContext<'_>,
+    ) -> Result<bool, Error>;
 }
 
 trait DwarfStruct<'data> {
@@ -57,12 +640,14 @@ trait DwarfStruct<'data> {
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 
     fn add_field(
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 }
 
@@ -71,12 +656,14 @@ trait DwarfEnum<'data> {
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 
     fn add_field(
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 }
 
@@ -87,154 +674,59 @@ where
     fn new(
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<Self, Error>;
 
     fn from_type(
         unit: &UnitRef<Reader<'data>>,
         type_: EntriesTree<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<Self, Error>;
 }
 
 impl<'data> DwarfTypes<'data> for Types<'data> {
     fn add(&mut self, unit: &UnitRef<Reader<'data>>, cache: &mut DwarfCache) -> Result<(), Error> {
-        let mut tree = unit.entries_tree(None)?;
-        let mut children = tree.root()?.children();
-
-        while let Some(child) = children.next()? {
-            if !matches!(
-                child.entry().tag(),
-                gimli::DW_TAG_enumeration_type
-                    | gimli::DW_TAG_structure_type
-                    | gimli::DW_TAG_union_type
-            ) {
-                continue;
-            }
-
-            if child.entry().declaration()?.unwrap_or(false) {
-                continue;
-            }
-
-            let decl_file = child.entry().decl_file(unit)?;
-            let decl_line = child.entry().decl_line()?;
-            let decl_column = child.entry().decl_column()?;
-
-            match (decl_file, decl_line, decl_column) {
-                (Some(decl_file), Some(decl_line), Some(decl_column)) => {
-                    if !cache.insert((decl_file, decl_line, decl_column)) {
-                        continue;
-                    }
-                }
-                _ => {
-                    let name = type_name(unit, child.entry())?;
-                    tracing::warn!(%name, "missing declaration information");
-                }
-            }
-
-            match child.entry().tag() {
-                gimli::DW_TAG_enumeration_type => self.add_enum(unit, child)?,
-                gimli::DW_TAG_structure_type => self.add_struct(unit, child, StructKind::Struct)?,
-                gimli::DW_TAG_union_type => self.add_struct(unit, child, StructKind::Union)?,
-
-                // Skip other tags.
-                _ => (),
-            }
+        if is_partial_unit(unit)? {
+            return Ok(());
         }
 
+        let items = collect_unit(
+            unit,
+            &mut cache.declarations,
+            cache.strictness,
+            cache.resolution_policy,
+            cache.record_provenance,
+            cache.max_type_depth,
+        )?;
+
+        cache.insert(self, items, false);
         Ok(())
     }
 
-    #[tracing::instrument(skip_all, fields(name))]
     fn add_enum(
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
-    ) -> Result<(), Error> {
+        context: &mut ParseContext<'_>,
+    ) -> Result<bool, Error> {
         let name = type_name(unit, node.entry())?;
-        tracing::Span::current().record("name", &*name);
-
-        let type_ = match node.entry().type_(unit)? {
-            Some(type_) => type_,
-            None => {
-                tracing::warn!("enum doesn't have a type");
-                return Ok(());
-            }
-        };
 
-        let mut new_enum = Enum {
-            subtype: Type::from_type(unit, type_)?,
-            fields: Default::default(),
-        };
-
-        new_enum.add_fields(unit, node)?;
-
-        let new_enum_fields = new_enum.fields.len();
-
-        match self.enums.entry(name.clone()) {
-            Entry::Vacant(entry) => {
-                entry.insert(new_enum);
-            }
-            Entry::Occupied(mut entry) => {
-                let previous_udt = entry.get_mut();
-                let previous_enum_fields = previous_udt.fields.len();
-
-                if new_enum_fields > previous_enum_fields {
-                    tracing::warn!(
-                        %name,
-                        new_enum_fields,
-                        previous_enum_fields,
-                        "duplicate enum name; overwriting"
-                    );
-
-                    *previous_udt = new_enum;
-                }
-            }
+        match parse_enum(unit, node, context)? {
+            Some(enum_) => Ok(insert_enum(self, name, enum_, context.policy)),
+            None => Ok(false),
         }
-
-        Ok(())
     }
 
-    #[tracing::instrument(skip_all, fields(name))]
     fn add_struct(
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
         kind: StructKind,
-    ) -> Result<(), Error> {
+        context: &mut ParseContext<'_>,
+    ) -> Result<bool, Error> {
         let name = type_name(unit, node.entry())?;
-        tracing::Span::current().record("name", &*name);
-
-        let mut new_udt = Struct {
-            kind,
-            size: node.entry().byte_size()?.unwrap_or(0),
-            fields: Default::default(),
-        };
-
-        new_udt.add_fields(unit, node)?;
-
-        let new_udt_fields = new_udt.fields.len();
-
-        match self.structs.entry(name.clone()) {
-            Entry::Vacant(entry) => {
-                entry.insert(new_udt);
-            }
-            Entry::Occupied(mut entry) => {
-                let previous_udt = entry.get_mut();
-                let previous_udt_fields = previous_udt.fields.len();
-
-                if new_udt_fields > previous_udt_fields {
-                    tracing::warn!(
-                        %name,
-                        new_udt_fields,
-                        previous_udt_fields,
-                        "duplicate UDT name; overwriting"
-                    );
-
-                    *previous_udt = new_udt;
-                }
-            }
-        }
-
-        Ok(())
+        let udt = parse_struct(unit, node, kind, context)?;
+        Ok(insert_struct(self, name, udt, context.policy))
     }
 }
 
@@ -243,20 +735,24 @@ impl<'data> DwarfStruct<'data> for Struct<'data> {
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         let mut children = node.children();
 
         while let Some(child) = children.next()? {
             if child.entry().tag() != gimli::DW_TAG_member {
-                tracing::warn!(
-                    tag = ?child.entry().tag(),
-                    "unexpected tag (expected DW_TAG_member)"
+                context.warn(
+                    type_name(unit, child.entry())?,
+                    format!(
+                        "unexpected tag {} (expected DW_TAG_member)",
+                        child.entry().tag()
+                    ),
                 );
 
                 continue;
             }
 
-            self.add_field(unit, child)?;
+            self.add_field(unit, child, context)?;
         }
 
         Ok(())
@@ -267,6 +763,7 @@ impl<'data> DwarfStruct<'data> for Struct<'data> {
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         debug_assert_eq!(node.entry().tag(), gimli::DW_TAG_member);
 
@@ -289,7 +786,7 @@ impl<'data> DwarfStruct<'data> for Struct<'data> {
             name.into(),
             Field {
                 offset,
-                type_: Type::new(unit, node)?,
+                type_: Type::new(unit, node, context)?,
             },
         );
 
@@ -302,20 +799,24 @@ impl<'data> DwarfEnum<'data> for Enum<'data> {
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         let mut children = node.children();
 
         while let Some(child) = children.next()? {
             if child.entry().tag() != gimli::DW_TAG_enumerator {
-                tracing::warn!(
-                    tag = ?child.entry().tag(),
-                    "unexpected tag (expected DW_TAG_enumerator)"
+                context.warn(
+                    type_name(unit, child.entry())?,
+                    format!(
+                        "unexpected tag {} (expected DW_TAG_enumerator)",
+                        child.entry().tag()
+                    ),
                 );
 
                 continue;
             }
 
-            self.add_field(unit, child)?;
+            self.add_field(unit, child, context)?;
         }
 
         Ok(())
@@ -326,6 +827,7 @@ impl<'data> DwarfEnum<'data> for Enum<'data> {
         &mut self,
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         debug_assert_eq!(node.entry().tag(), gimli::DW_TAG_enumerator);
 
@@ -350,12 +852,15 @@ impl<'data> DwarfEnum<'data> for Enum<'data> {
                     Variant::I64(value)
                 }
                 else {
-                    tracing::warn!(?value, "enumerator has invalid value");
+                    context.warn(
+                        name,
+                        format!("enumerator has invalid value {value:?}; skipping"),
+                    );
                     return Ok(());
                 }
             }
             None => {
-                tracing::warn!("enumerator doesn't have a value");
+                context.warn(name, "enumerator doesn't have a value; skipping");
                 return Ok(());
             }
         };
@@ -370,6 +875,7 @@ impl<'data> DwarfType<'data> for Type<'data> {
     fn new(
         unit: &UnitRef<Reader<'data>>,
         node: EntriesTreeNode<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<Self, Error> {
         let type_ = match node.entry().type_(unit)? {
             Some(type_) => type_,
@@ -385,21 +891,31 @@ impl<'data> DwarfType<'data> for Type<'data> {
             return Ok(Self::Bitfield(BitfieldRef {
                 bit_length,
                 bit_position,
-                subtype: Box::new(Self::from_type(unit, type_)?),
+                subtype: Box::new(Self::from_type(unit, type_, context)?),
             }));
         }
 
-        Self::from_type(unit, type_)
+        Self::from_type(unit, type_, context)
     }
 
     fn from_type(
         unit: &UnitRef<Reader<'data>>,
         mut type_: EntriesTree<Reader<'data>>,
+        context: &mut ParseContext<'_>,
     ) -> Result<Self, Error> {
         let node = type_.root()?;
 
+        // Guards against cyclic or too deeply nested type chains.
+        if context.depth == 0 {
+            context.warn(
+                type_name(unit, node.entry())?,
+                "type nesting exceeds the maximum depth, treated as void",
+            );
+            return Ok(Self::Base(BaseRef::Void));
+        }
+
         let result = match node.entry().tag() {
-            gimli::DW_TAG_base_type => Self::Base(__type_from_base_type(unit, node)?),
+            gimli::DW_TAG_base_type => Self::Base(__type_from_base_type(unit, node, context)?),
 
             gimli::DW_TAG_enumeration_type => Self::Enum(EnumRef {
                 name: type_name(unit, node.entry())?,
@@ -409,22 +925,27 @@ impl<'data> DwarfType<'data> for Type<'data> {
                 name: type_name(unit, node.entry())?,
             }),
 
-            gimli::DW_TAG_array_type => Self::Array(__type_from_array_type(unit, type_)?),
+            gimli::DW_TAG_array_type => {
+                Self::Array(context.nested(|context| __type_from_array_type(unit, type_, context))?)
+            }
 
             gimli::DW_TAG_pointer_type => Self::Pointer(PointerRef {
-                subtype: Box::new(Self::new(unit, node)?),
+                subtype: Box::new(context.nested(|context| Self::new(unit, node, context))?),
             }),
 
             gimli::DW_TAG_subroutine_type => Self::Function,
 
             gimli::DW_TAG_typedef | gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => {
-                Self::new(unit, node)?
+                context.nested(|context| Self::new(unit, node, context))?
             }
 
             tag => {
                 // dump_attrs(unit, node.entry())?;
 
-                tracing::error!(?tag, "unexpected tag");
+                context.warn(
+                    type_name(unit, node.entry())?,
+                    format!("unexpected tag {tag}, treated as void"),
+                );
                 Self::Base(BaseRef::Void)
             }
         };
@@ -437,6 +958,7 @@ impl<'data> DwarfType<'data> for Type<'data> {
 fn __type_from_base_type<'data>(
     unit: &UnitRef<Reader<'data>>,
     node: EntriesTreeNode<Reader<'data>>,
+    context: &mut ParseContext<'_>,
 ) -> Result<BaseRef, Error> {
     debug_assert_eq!(node.entry().tag(), gimli::DW_TAG_base_type);
 
@@ -446,7 +968,7 @@ fn __type_from_base_type<'data>(
     let byte_size = match node.entry().byte_size()? {
         Some(byte_size) => byte_size,
         None => {
-            tracing::warn!("base type doesn't have a byte size");
+            context.warn(name, "base type doesn't have a byte size, treated as void");
             return Ok(BaseRef::Void);
         }
     };
@@ -458,7 +980,10 @@ fn __type_from_base_type<'data>(
     let encoding = match node.entry().encoding()? {
         Some(encoding) => encoding,
         None => {
-            tracing::warn!("base type doesn't have an encoding");
+            context.warn(
+                &*name,
+                "base type doesn't have an encoding, treated as unsigned",
+            );
             return Ok(match byte_size {
                 1 => BaseRef::U8,
                 2 => BaseRef::U16,
@@ -466,7 +991,10 @@ fn __type_from_base_type<'data>(
                 8 => BaseRef::U64,
                 16 => BaseRef::U128,
                 _ => {
-                    tracing::error!(byte_size, "unsupported base type");
+                    context.warn(
+                        &*name,
+                        format!("unsupported base type of {byte_size} bytes, treated as void"),
+                    );
                     BaseRef::Void
                 }
             });
@@ -477,7 +1005,10 @@ fn __type_from_base_type<'data>(
         gimli::DW_ATE_boolean => match byte_size {
             1 => BaseRef::Bool,
             _ => {
-                tracing::error!(byte_size, "unsupported boolean base type");
+                context.warn(
+                    &*name,
+                    format!("unsupported boolean base type of {byte_size} bytes, treated as void"),
+                );
                 BaseRef::Void
             }
         },
@@ -488,7 +1019,10 @@ fn __type_from_base_type<'data>(
             8 => BaseRef::I64,
             16 => BaseRef::I128,
             _ => {
-                tracing::error!(byte_size, "unsupported signed base type");
+                context.warn(
+                    &*name,
+                    format!("unsupported signed base type of {byte_size} bytes, treated as void"),
+                );
                 BaseRef::Void
             }
         },
@@ -499,7 +1033,10 @@ fn __type_from_base_type<'data>(
             8 => BaseRef::U64,
             16 => BaseRef::U128,
             _ => {
-                tracing::error!(byte_size, "unsupported unsigned base type");
+                context.warn(
+                    &*name,
+                    format!("unsupported unsigned base type of {byte_size} bytes, treated as void"),
+                );
                 BaseRef::Void
             }
         },
@@ -507,21 +1044,26 @@ fn __type_from_base_type<'data>(
             4 => BaseRef::F32,
             8 => BaseRef::F64,
             _ => {
-                tracing::error!(byte_size, "unsupported float base type");
+                context.warn(
+                    &*name,
+                    format!("unsupported float base type of {byte_size} bytes, treated as void"),
+                );
                 BaseRef::Void
             }
         },
-        _ => match byte_size {
-            1 => BaseRef::U8,
-            2 => BaseRef::U16,
-            4 => BaseRef::U32,
-            8 => BaseRef::U64,
-            16 => BaseRef::U128,
-            _ => {
-                tracing::error!(?encoding, byte_size, "unsupported base type");
-                BaseRef::Void
+        _ => {
+            match byte_size {
+                1 => BaseRef::U8,
+                2 => BaseRef::U16,
+                4 => BaseRef::U32,
+                8 => BaseRef::U64,
+                16 => BaseRef::U128,
+                _ => {
+                    context.warn(&*name, format!("unsupported base type {encoding} of {byte_size} bytes, treated as void"));
+                    BaseRef::Void
+                }
             }
-        },
+        }
     };
 
     Ok(result)
@@ -530,6 +1072,7 @@ fn __type_from_base_type<'data>(
 fn __type_from_array_type<'data>(
     unit: &UnitRef<Reader<'data>>,
     mut type_: EntriesTree<Reader<'data>>,
+    context: &mut ParseContext<'_>,
 ) -> Result<ArrayRef<'data>, Error> {
     let node = type_.root()?;
     debug_assert_eq!(node.entry().tag(), gimli::DW_TAG_array_type);
@@ -562,7 +1105,7 @@ fn __type_from_array_type<'data>(
     let node = type_.root()?;
 
     Ok(ArrayRef {
-        subtype: Box::new(Type::new(unit, node)?),
+        subtype: Box::new(Type::new(unit, node, context)?),
         dims: dimensions.into_iter().map(|dim| dim.unwrap_or(0)).collect(),
         size: count,
     })
@@ -0,0 +1,526 @@
+//! Miniature Linux kernel: an ELF file with DWARF types and a matching
+//! System.map.
+//!
+//! Every compilation unit defines the same set of kernel types (as if
+//! they were included from the same headers), plus a struct of its own
+//! (`unit<N>`), so that the deduplication across units is exercised.
+
+use std::{
+    fs::File,
+    io::{Seek as _, Write as _},
+};
+
+use gimli::{
+    write::{
+        AttributeValue, Dwarf, EndianVec, FileId, LineProgram, LineString, Reference, Sections,
+        Unit, UnitEntryId, Writer as _,
+    },
+    DwAt, DwTag, Encoding, Format, LineEncoding, LittleEndian,
+};
+use object::{
+    elf,
+    write::{Object, SectionId},
+    Architecture, BinaryFormat, Endianness, SectionFlags, SectionKind,
+};
+
+/// Link-time address of `_text`.
+pub const TEXT: u64 = 0xffff_ffff_8100_0000;
+
+/// Symbols of the System.map, besides the filler symbols.
+pub const SYMBOLS: &[(&str, char, u64)] = &[
+    ("_text", 'T', TEXT),
+    ("startup_64", 'T', TEXT),
+    ("schedule", 'T', 0xffff_ffff_810c_1230),
+    ("__schedule", 't', 0xffff_ffff_810c_2000),
+    ("init_task", 'D', 0xffff_ffff_82a0_c940),
+    ("jiffies", 'D', 0xffff_ffff_82a0_5000),
+    ("linux_banner", 'R', 0xffff_ffff_8220_0100),
+];
+
+/// Number of filler symbols, so that the System.map passes the sanity
+/// checks.
+const FILLER_SYMBOLS: usize = 1_200;
+
+pub struct Kernel {
+    /// The ELF image with the debug sections.
+    pub image: Vec<u8>,
+
+    /// The System.map.
+    pub systemmap: String,
+}
+
+impl Kernel {
+    /// Builds a kernel whose types are spread over `units` compilation
+    /// units.
+    pub fn new(units: usize) -> Self {
+        Self {
+            image: image(units, false, None),
+            systemmap: systemmap(),
+        }
+    }
+
+    /// Builds a kernel whose last unit imports a partial unit defining
+    /// `struct cred`, as `dwz` does for types shared by several units.
+    pub fn with_partial_unit(units: usize) -> Self {
+        Self {
+            image: image(units, true, None),
+            systemmap: systemmap(),
+        }
+    }
+
+    /// Builds a kernel whose debug sections are `SHF_COMPRESSED` with
+    /// zstd, as done by `objcopy --compress-debug-sections=zstd`.
+    ///
+    /// `compress` returns the zstd stream of the section data.
+    pub fn with_zstd_sections(units: usize, compress: impl Fn(&[u8]) -> Vec<u8>) -> Self {
+        Self {
+            image: image(units, false, Some(&compress)),
+            systemmap: systemmap(),
+        }
+    }
+
+    /// Returns the kernel image and the System.map as files.
+    pub fn files(&self) -> (File, File) {
+        (temp_file(&self.image), temp_file(self.systemmap.as_bytes()))
+    }
+}
+
+/// Compresses the data of a debug section.
+type Compress<'a> = dyn Fn(&[u8]) -> Vec<u8> + 'a;
+
+fn temp_file(data: &[u8]) -> File {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(data).unwrap();
+    file.rewind().unwrap();
+    file
+}
+
+fn systemmap() -> String {
+    let mut result = String::new();
+    for (name, kind, address) in SYMBOLS {
+        result += &format!("{address:016x} {kind} {name}\n");
+    }
+
+    for index in 0..FILLER_SYMBOLS {
+        let address = TEXT + 0x0010_0000 + index as u64 * 0x40;
+        result += &format!("{address:016x} t filler_{index}\n");
+    }
+
+    result
+}
+
+fn image(units: usize, partial_unit: bool, compress: Option<&Compress<'_>>) -> Vec<u8> {
+    let encoding = Encoding {
+        format: Format::Dwarf32,
+        version: 4,
+        address_size: 8,
+    };
+
+    let mut dwarf = Dwarf::new();
+    for index in 0..units {
+        let unit = dwarf.units.add(Unit::new(
+            encoding,
+            line_program(encoding, &format!("kernel/unit{index}.c")),
+        ));
+        UnitBuilder::new(dwarf.units.get_mut(unit)).build(index);
+    }
+
+    // The partial unit is written after the unit importing it.
+    let partial = partial_unit.then(|| {
+        let partial = dwarf
+            .units
+            .add(Unit::new(encoding, line_program(encoding, "<dwz>")));
+        UnitBuilder::new(dwarf.units.get_mut(partial)).build_partial();
+
+        let root = dwarf.units.get(partial).root();
+        let (importing, _) = dwarf.units.iter().nth(units - 1).unwrap();
+        let unit = dwarf.units.get_mut(importing);
+        let import = unit.add(unit.root(), gimli::DW_TAG_imported_unit);
+        unit.get_mut(import).set(
+            gimli::DW_AT_import,
+            AttributeValue::DebugInfoRef(Reference::Entry(partial, root)),
+        );
+
+        partial
+    });
+
+    let mut sections = Sections::new(EndianVec::new(LittleEndian));
+    dwarf.write(&mut sections).unwrap();
+
+    // `gimli::write` only writes compilation units, so the tag of the
+    // root entry is patched. It's the first abbreviation of the unit.
+    if let Some(partial) = partial {
+        let index = dwarf
+            .units
+            .iter()
+            .position(|(id, _)| id == partial)
+            .unwrap();
+        let debug_info = gimli::read::DebugInfo::new(sections.debug_info.slice(), LittleEndian);
+        let mut headers = debug_info.units();
+        for _ in 0..index {
+            headers.next().unwrap();
+        }
+        let header = headers.next().unwrap().unwrap();
+        let abbreviation = header.debug_abbrev_offset().0;
+
+        assert_eq!(
+            sections.debug_abbrev.slice()[abbreviation..][..2],
+            [1, 0x11]
+        );
+        sections
+            .debug_abbrev
+            .write_at(abbreviation + 1, &[gimli::DW_TAG_partial_unit.0 as u8])
+            .unwrap();
+    }
+
+    let mut object = Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+    sections
+        .for_each(|id, data| {
+            if !data.slice().is_empty() {
+                let section = object.add_section(Vec::new(), id.name().into(), SectionKind::Debug);
+                match compress {
+                    Some(compress) => add_compressed(&mut object, section, data.slice(), compress),
+                    None => {
+                        object.append_section_data(section, data.slice(), 1);
+                    }
+                }
+            }
+
+            Ok::<_, gimli::write::Error>(())
+        })
+        .unwrap();
+
+    object.write().unwrap()
+}
+
+/// Sets the data of a `SHF_COMPRESSED` section: an `Elf64_Chdr` followed
+/// by the zstd stream.
+fn add_compressed(object: &mut Object, section: SectionId, data: &[u8], compress: &Compress<'_>) {
+    let mut compressed = Vec::new();
+    compressed.extend_from_slice(&elf::ELFCOMPRESS_ZSTD.to_le_bytes());
+    compressed.extend_from_slice(&0u32.to_le_bytes());
+    compressed.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    compressed.extend_from_slice(&1u64.to_le_bytes());
+    compressed.extend_from_slice(&compress(data));
+
+    object.append_section_data(section, &compressed, 8);
+    object.section_mut(section).flags = SectionFlags::Elf {
+        sh_flags: elf::SHF_COMPRESSED.into(),
+    };
+}
+
+fn line_program(encoding: Encoding, file: &str) -> LineProgram {
+    LineProgram::new(
+        encoding,
+        LineEncoding::default(),
+        LineString::String(b"/build/linux".to_vec()),
+        LineString::String(file.into()),
+        None,
+    )
+}
+
+struct UnitBuilder<'a> {
+    unit: &'a mut Unit,
+}
+
+impl<'a> UnitBuilder<'a> {
+    fn new(unit: &'a mut Unit) -> Self {
+        Self { unit }
+    }
+
+    fn file(&mut self, path: &str) -> FileId {
+        let directory = self.unit.line_program.default_directory();
+        self.unit
+            .line_program
+            .add_file(LineString::String(path.into()), directory, None)
+    }
+
+    fn add(
+        &mut self,
+        parent: Option<UnitEntryId>,
+        tag: DwTag,
+        attributes: Vec<(DwAt, AttributeValue)>,
+    ) -> UnitEntryId {
+        let parent = parent.unwrap_or(self.unit.root());
+        let id = self.unit.add(parent, tag);
+        let entry = self.unit.get_mut(id);
+        for (name, value) in attributes {
+            entry.set(name, value);
+        }
+
+        id
+    }
+
+    fn base(&mut self, name: &str, encoding: gimli::DwAte, byte_size: u64) -> UnitEntryId {
+        self.add(
+            None,
+            gimli::DW_TAG_base_type,
+            vec![
+                (gimli::DW_AT_name, string(name)),
+                (gimli::DW_AT_encoding, AttributeValue::Encoding(encoding)),
+                (gimli::DW_AT_byte_size, AttributeValue::Udata(byte_size)),
+            ],
+        )
+    }
+
+    fn pointer(&mut self, type_: Option<UnitEntryId>) -> UnitEntryId {
+        let mut attributes = vec![(gimli::DW_AT_byte_size, AttributeValue::Udata(8))];
+        if let Some(type_) = type_ {
+            attributes.push((gimli::DW_AT_type, AttributeValue::UnitRef(type_)));
+        }
+
+        self.add(None, gimli::DW_TAG_pointer_type, attributes)
+    }
+
+    /// Adds a struct or union defined at the given declaration.
+    fn udt(
+        &mut self,
+        tag: DwTag,
+        name: Option<&str>,
+        byte_size: u64,
+        declaration: (FileId, u64, u64),
+    ) -> UnitEntryId {
+        let (file, line, column) = declaration;
+        let mut attributes = vec![
+            (gimli::DW_AT_byte_size, AttributeValue::Udata(byte_size)),
+            (
+                gimli::DW_AT_decl_file,
+                AttributeValue::FileIndex(Some(file)),
+            ),
+            (gimli::DW_AT_decl_line, AttributeValue::Udata(line)),
+            (gimli::DW_AT_decl_column, AttributeValue::Udata(column)),
+        ];
+        if let Some(name) = name {
+            attributes.push((gimli::DW_AT_name, string(name)));
+        }
+
+        self.add(None, tag, attributes)
+    }
+
+    fn member(&mut self, parent: UnitEntryId, name: Option<&str>, type_: UnitEntryId, offset: u64) {
+        let mut attributes = vec![
+            (gimli::DW_AT_type, AttributeValue::UnitRef(type_)),
+            (
+                gimli::DW_AT_data_member_location,
+                AttributeValue::Udata(offset),
+            ),
+        ];
+        if let Some(name) = name {
+            attributes.push((gimli::DW_AT_name, string(name)));
+        }
+
+        self.add(Some(parent), gimli::DW_TAG_member, attributes);
+    }
+
+    fn bitfield_member(
+        &mut self,
+        parent: UnitEntryId,
+        name: &str,
+        type_: UnitEntryId,
+        bit_offset: u64,
+        bit_size: u64,
+    ) {
+        self.add(
+            Some(parent),
+            gimli::DW_TAG_member,
+            vec![
+                (gimli::DW_AT_name, string(name)),
+                (gimli::DW_AT_type, AttributeValue::UnitRef(type_)),
+                (gimli::DW_AT_bit_size, AttributeValue::Udata(bit_size)),
+                (
+                    gimli::DW_AT_data_bit_offset,
+                    AttributeValue::Udata(bit_offset),
+                ),
+            ],
+        );
+    }
+
+    fn build(mut self, index: usize) {
+        let types_h = self.file("include/linux/types.h");
+        let pid_h = self.file("include/linux/pid.h");
+        let sched_h = self.file("include/linux/sched.h");
+        let unit_c = self.file(&format!("kernel/unit{index}.c"));
+
+        let root = self.unit.root();
+        self.unit
+            .get_mut(root)
+            .set(gimli::DW_AT_name, string(&format!("kernel/unit{index}.c")));
+
+        let int = self.base("int", gimli::DW_ATE_signed, 4);
+        let unsigned_int = self.base("unsigned int", gimli::DW_ATE_unsigned, 4);
+        let unsigned_long = self.base("long unsigned int", gimli::DW_ATE_unsigned, 8);
+        let char = self.base("char", gimli::DW_ATE_signed_char, 1);
+        let bool = self.base("_Bool", gimli::DW_ATE_boolean, 1);
+
+        let pid_t = self.add(
+            None,
+            gimli::DW_TAG_typedef,
+            vec![
+                (gimli::DW_AT_name, string("pid_t")),
+                (gimli::DW_AT_type, AttributeValue::UnitRef(int)),
+            ],
+        );
+
+        // struct list_head { struct list_head *next, *prev; };
+        let list_head = self.udt(
+            gimli::DW_TAG_structure_type,
+            Some("list_head"),
+            16,
+            (types_h, 178, 8),
+        );
+        let list_head_ptr = self.pointer(Some(list_head));
+        self.member(list_head, Some("next"), list_head_ptr, 0);
+        self.member(list_head, Some("prev"), list_head_ptr, 8);
+
+        // enum pid_type { ... };
+        let pid_type = self.add(
+            None,
+            gimli::DW_TAG_enumeration_type,
+            vec![
+                (gimli::DW_AT_name, string("pid_type")),
+                (gimli::DW_AT_type, AttributeValue::UnitRef(unsigned_int)),
+                (gimli::DW_AT_byte_size, AttributeValue::Udata(4)),
+                (
+                    gimli::DW_AT_decl_file,
+                    AttributeValue::FileIndex(Some(pid_h)),
+                ),
+                (gimli::DW_AT_decl_line, AttributeValue::Udata(9)),
+                (gimli::DW_AT_decl_column, AttributeValue::Udata(6)),
+            ],
+        );
+        let variants = [
+            "PIDTYPE_PID",
+            "PIDTYPE_TGID",
+            "PIDTYPE_PGID",
+            "PIDTYPE_SID",
+            "PIDTYPE_MAX",
+        ];
+        for (value, name) in variants.into_iter().enumerate() {
+            self.add(
+                Some(pid_type),
+                gimli::DW_TAG_enumerator,
+                vec![
+                    (gimli::DW_AT_name, string(name)),
+                    (
+                        gimli::DW_AT_const_value,
+                        AttributeValue::Udata(value as u64),
+                    ),
+                ],
+            );
+        }
+
+        // struct mm_struct; (forward declaration only)
+        let mm_struct = self.add(
+            None,
+            gimli::DW_TAG_structure_type,
+            vec![
+                (gimli::DW_AT_name, string("mm_struct")),
+                (gimli::DW_AT_declaration, AttributeValue::Flag(true)),
+            ],
+        );
+        let mm_struct_ptr = self.pointer(Some(mm_struct));
+
+        // char comm[16];
+        let comm = self.add(
+            None,
+            gimli::DW_TAG_array_type,
+            vec![(gimli::DW_AT_type, AttributeValue::UnitRef(char))],
+        );
+        self.add(
+            Some(comm),
+            gimli::DW_TAG_subrange_type,
+            vec![
+                (gimli::DW_AT_type, AttributeValue::UnitRef(unsigned_long)),
+                (gimli::DW_AT_upper_bound, AttributeValue::Udata(15)),
+            ],
+        );
+
+        // union { void *stack; struct vm_struct *stack_vm_area; };
+        let void_ptr = self.pointer(None);
+        let stack = self.udt(gimli::DW_TAG_union_type, None, 8, (sched_h, 760, 2));
+        self.member(stack, Some("stack"), void_ptr, 0);
+        self.member(stack, Some("stack_vm_area"), void_ptr, 0);
+
+        // void (*thread_fn)(void);
+        let function = self.add(None, gimli::DW_TAG_subroutine_type, vec![]);
+        let function_ptr = self.pointer(Some(function));
+
+        let volatile_long = self.add(
+            None,
+            gimli::DW_TAG_volatile_type,
+            vec![(gimli::DW_AT_type, AttributeValue::UnitRef(unsigned_long))],
+        );
+
+        let task_struct = self.udt(
+            gimli::DW_TAG_structure_type,
+            Some("task_struct"),
+            96,
+            (sched_h, 737, 8),
+        );
+        self.member(task_struct, Some("__state"), unsigned_int, 0);
+        self.member(task_struct, Some("flags"), unsigned_int, 4);
+        self.bitfield_member(task_struct, "sched_reset_on_fork", unsigned_int, 64, 1);
+        self.bitfield_member(
+            task_struct,
+            "sched_contributes_to_load",
+            unsigned_int,
+            65,
+            1,
+        );
+        self.bitfield_member(task_struct, "in_execve", unsigned_int, 68, 3);
+        self.member(task_struct, Some("on_cpu"), bool, 12);
+        self.member(task_struct, Some("tasks"), list_head, 16);
+        self.member(task_struct, Some("pid"), pid_t, 32);
+        self.member(task_struct, Some("tgid"), pid_t, 36);
+        self.member(task_struct, Some("comm"), comm, 40);
+        self.member(task_struct, Some("mm"), mm_struct_ptr, 56);
+        self.member(task_struct, None, stack, 64);
+        self.member(task_struct, Some("thread_fn"), function_ptr, 72);
+        self.member(task_struct, Some("nvcsw"), volatile_long, 80);
+        self.member(task_struct, Some("pid_type"), pid_type, 88);
+
+        // struct __va_list_tag { ... }; (generated by the compiler, without
+        // declaration information)
+        let va_list_tag = self.add(
+            None,
+            gimli::DW_TAG_structure_type,
+            vec![
+                (gimli::DW_AT_name, string("__va_list_tag")),
+                (gimli::DW_AT_byte_size, AttributeValue::Udata(24)),
+            ],
+        );
+        self.member(va_list_tag, Some("gp_offset"), unsigned_int, 0);
+        self.member(va_list_tag, Some("fp_offset"), unsigned_int, 4);
+        self.member(va_list_tag, Some("overflow_arg_area"), void_ptr, 8);
+        self.member(va_list_tag, Some("reg_save_area"), void_ptr, 16);
+
+        // A struct only defined in this unit.
+        let own = self.udt(
+            gimli::DW_TAG_structure_type,
+            Some(&format!("unit{index}")),
+            8,
+            (unit_c, 1, 8),
+        );
+        self.member(own, Some("id"), unsigned_long, 0);
+    }
+
+    /// Builds the partial unit of [`Kernel::with_partial_unit`].
+    fn build_partial(mut self) {
+        let cred_h = self.file("include/linux/cred.h");
+        let unsigned_int = self.base("unsigned int", gimli::DW_ATE_unsigned, 4);
+
+        // struct cred { unsigned int usage; unsigned int uid; };
+        let cred = self.udt(
+            gimli::DW_TAG_structure_type,
+            Some("cred"),
+            8,
+            (cred_h, 111, 8),
+        );
+        self.member(cred, Some("usage"), unsigned_int, 0);
+        self.member(cred, Some("uid"), unsigned_int, 4);
+    }
+}
+
+fn string(value: &str) -> AttributeValue {
+    AttributeValue::String(value.into())
+}
@@ -0,0 +1,38 @@
+//! Types without declaration information are deduplicated by name.
+
+#![cfg(feature = "unstable")]
+
+mod common;
+
+use isr_core::ProfileOptions;
+
+use self::common::kernel::Kernel;
+
+#[test]
+fn missing_declaration() {
+    // Every unit defines `__va_list_tag`, without declaration information.
+    let kernel = Kernel::new(3);
+
+    for threads in [1, 2] {
+        let (image, systemmap) = kernel.files();
+        let options = ProfileOptions::default().with_threads(threads);
+
+        let mut fields = None;
+        let report = isr_dwarf::create_profile_with_report(image, systemmap, &options, |profile| {
+            fields = profile
+                .find_struct("__va_list_tag")
+                .map(|udt| (udt.size, udt.fields.len()));
+            Ok::<_, std::io::Error>(())
+        })
+        .unwrap();
+
+        assert_eq!(fields, Some((24, 4)));
+
+        // Only the first definition was added.
+        assert!(
+            report.duplicate_structs.is_empty(),
+            "{:?}",
+            report.duplicate_structs
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! The definitions of the types are recorded on request.
+
+mod common;
+
+use isr_core::{types::Provenance, ProfileOptions};
+
+use self::common::kernel::Kernel;
+
+/// Returns the provenance of `task_struct`, `pid_type` and `__va_list_tag`.
+fn provenance(record_provenance: bool) -> [Option<Provenance>; 3] {
+    let (image, systemmap) = Kernel::new(2).files();
+    let options = ProfileOptions::default().with_record_provenance(record_provenance);
+
+    let mut result = Default::default();
+    isr_dwarf::create_profile_with_options(image, systemmap, &options, |profile| {
+        result = ["task_struct", "pid_type", "__va_list_tag"]
+            .map(|name| profile.type_provenance(name).cloned());
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    result
+}
+
+#[test]
+fn recorded() {
+    let [task_struct, pid_type, va_list_tag] = provenance(true);
+
+    // The first definition is kept.
+    assert_eq!(
+        task_struct,
+        Some(Provenance {
+            unit: Some(String::from("kernel/unit0.c")),
+            file: Some(String::from("include/linux/sched.h")),
+            line: Some(737),
+        })
+    );
+    assert_eq!(
+        pid_type,
+        Some(Provenance {
+            unit: Some(String::from("kernel/unit0.c")),
+            file: Some(String::from("include/linux/pid.h")),
+            line: Some(9),
+        })
+    );
+
+    // Generated by the compiler, without declaration information.
+    assert_eq!(
+        va_list_tag,
+        Some(Provenance {
+            unit: Some(String::from("kernel/unit0.c")),
+            file: None,
+            line: None,
+        })
+    );
+}
+
+#[test]
+fn not_recorded() {
+    assert_eq!(provenance(false), [None, None, None]);
+}
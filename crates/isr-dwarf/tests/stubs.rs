@@ -0,0 +1,39 @@
+mod common;
+
+use isr_core::ProfileOptions;
+
+use self::common::kernel::Kernel;
+
+/// Returns the size, number of fields and completeness of `task_struct`
+/// and of the forward declared `mm_struct`.
+fn structs(forward_declaration_stubs: bool) -> [Option<(u64, usize, bool)>; 2] {
+    let (image, systemmap) = Kernel::new(2).files();
+    let options =
+        ProfileOptions::default().with_forward_declaration_stubs(forward_declaration_stubs);
+
+    let mut result = [None; 2];
+    isr_dwarf::create_profile_with_options(image, systemmap, &options, |profile| {
+        result = ["task_struct", "mm_struct"].map(|name| {
+            let udt = profile.find_struct(name)?;
+            Some((udt.size, udt.fields.len(), udt.incomplete))
+        });
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    result
+}
+
+#[test]
+fn without_stubs() {
+    let [task_struct, mm_struct] = structs(false);
+    assert!(matches!(task_struct, Some((96, _, false))));
+    assert_eq!(mm_struct, None);
+}
+
+#[test]
+fn with_stubs() {
+    let [task_struct, mm_struct] = structs(true);
+    assert!(matches!(task_struct, Some((96, _, false))));
+    assert_eq!(mm_struct, Some((0, 0, true)));
+}
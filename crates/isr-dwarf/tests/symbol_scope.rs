@@ -0,0 +1,60 @@
+//! The symbols of a profile are limited to the requested scope.
+
+mod common;
+
+use isr_core::{ProfileOptions, SymbolScope};
+
+use self::common::kernel::Kernel;
+
+/// Returns the scope recorded in the profile and its symbol names.
+fn symbols(scope: SymbolScope) -> (SymbolScope, Vec<String>) {
+    let (image, systemmap) = Kernel::new(1).files();
+
+    // The filler symbols are local, so the few exported ones would fail
+    // the sanity checks.
+    let options = ProfileOptions::default()
+        .with_symbol_scope(scope)
+        .with_allow_suspect_systemmap(scope == SymbolScope::Exported);
+
+    let mut result = None;
+    isr_dwarf::create_profile_with_options(image, systemmap, &options, |profile| {
+        let names = profile.symbols().map(|(name, _)| name.to_owned());
+        result = Some((profile.symbol_scope(), names.collect()));
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    result.unwrap()
+}
+
+#[test]
+fn all() {
+    let (scope, names) = symbols(SymbolScope::All);
+    assert_eq!(scope, SymbolScope::All);
+
+    for name in ["_text", "schedule", "__schedule", "init_task", "filler_0"] {
+        assert!(names.iter().any(|symbol| symbol == name), "{name}");
+    }
+}
+
+#[test]
+fn exported() {
+    let (scope, names) = symbols(SymbolScope::Exported);
+    assert_eq!(scope, SymbolScope::Exported);
+
+    // Only the global (uppercase) text and data symbols.
+    let mut names = names;
+    names.sort();
+    assert_eq!(
+        names,
+        ["_text", "init_task", "jiffies", "schedule", "startup_64"]
+    );
+}
+
+#[test]
+fn none() {
+    // The empty System.map isn't rejected by the sanity checks.
+    let (scope, names) = symbols(SymbolScope::None);
+    assert_eq!(scope, SymbolScope::None);
+    assert!(names.is_empty());
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use isr_core::{ProfileOptions, SymbolScope};
+use isr_dwarf::Error;
+
+use self::common::kernel::{Kernel, SYMBOLS};
+
+/// Creates the profile of the kernel with the System.map.
+fn create_profile(systemmap: String, options: &ProfileOptions) -> Result<(), Error> {
+    let kernel = Kernel {
+        systemmap,
+        ..Kernel::new(1)
+    };
+
+    let (image, systemmap) = kernel.files();
+    isr_dwarf::create_profile_with_options(image, systemmap, options, |_| {
+        Ok::<_, std::io::Error>(())
+    })
+}
+
+#[test]
+fn complete() {
+    let systemmap = Kernel::new(1).systemmap;
+    assert!(create_profile(systemmap, &ProfileOptions::default()).is_ok());
+}
+
+#[test]
+fn too_few_symbols() {
+    let systemmap = SYMBOLS
+        .iter()
+        .map(|(name, kind, address)| format!("{address:016x} {kind} {name}\n"))
+        .collect::<String>();
+
+    let result = create_profile(systemmap.clone(), &ProfileOptions::default());
+    assert!(matches!(result, Err(Error::SuspectSystemMap { .. })));
+
+    let options = ProfileOptions::default().with_allow_suspect_systemmap(true);
+    assert!(create_profile(systemmap, &options).is_ok());
+}
+
+#[test]
+fn zeroed_addresses() {
+    // As read from /proc/kallsyms with `kernel.kptr_restrict` set.
+    let systemmap = Kernel::new(1)
+        .systemmap
+        .lines()
+        .map(|line| format!("{:016x}{}\n", 0, &line[16..]))
+        .collect::<String>();
+
+    let result = create_profile(systemmap.clone(), &ProfileOptions::default());
+    let Err(Error::SuspectSystemMap { reason }) = result
+    else {
+        panic!("expected a suspect System.map");
+    };
+    assert!(reason.contains("zero address"), "{reason}");
+
+    let options = ProfileOptions::default().with_allow_suspect_systemmap(true);
+    assert!(create_profile(systemmap, &options).is_ok());
+}
+
+/// Returns the names of the BSS and read-only data symbols in the profile.
+fn extra_symbols(options: &ProfileOptions) -> Vec<String> {
+    let mut systemmap = Kernel::new(1).systemmap;
+    systemmap += "ffffffff82a00000 B __per_cpu_start\n";
+    systemmap += "ffffffff82a00040 b __bss_local\n";
+    systemmap += "ffffffff82b00000 R __start_rodata\n";
+
+    let kernel = Kernel {
+        systemmap,
+        ..Kernel::new(1)
+    };
+
+    let mut names = Vec::new();
+    let (image, systemmap) = kernel.files();
+    isr_dwarf::create_profile_with_options(image, systemmap, options, |profile| {
+        names = profile
+            .symbols()
+            .map(|(name, _)| name.to_owned())
+            .filter(|name| name.starts_with("__"))
+            .collect();
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    names.retain(|name| name != "__schedule");
+    names
+}
+
+#[test]
+fn extra_kinds() {
+    // Only text and data by default.
+    assert!(extra_symbols(&ProfileOptions::default()).is_empty());
+
+    let options = ProfileOptions::default().with_systemmap_extra_kinds(['b', 'B']);
+    assert_eq!(extra_symbols(&options), ["__per_cpu_start", "__bss_local"]);
+
+    // Only the global extra kinds are exported.
+    let options = options
+        .with_symbol_scope(SymbolScope::Exported)
+        .with_allow_suspect_systemmap(true);
+    assert_eq!(extra_symbols(&options), ["__per_cpu_start"]);
+}
@@ -0,0 +1,17 @@
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("xz decompression failed: {0}")]
+    Xz(#[from] lzma_rs::error::Error),
+
+    #[error("Unsupported ISF format {0:?}")]
+    UnsupportedFormat(String),
+
+    #[error("Serialization error: {0}")]
+    Serialize(Box<dyn std::error::Error + Send + Sync>),
+}
@@ -0,0 +1,283 @@
+use std::{borrow::Cow, path::Path};
+
+use base64::Engine as _;
+use indexmap::IndexMap;
+use isr_core::{parse_pdb_guid, pdb_identifier, types::Variant};
+use serde::Deserialize;
+
+use super::Error;
+
+/// Magic of the xz container format.
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\x00";
+
+/// Major version of the supported ISF format.
+const FORMAT_MAJOR: &str = "6";
+
+/// Parsed ISF file.
+#[derive(Debug, Deserialize)]
+pub struct Isf {
+    pub(crate) metadata: Metadata,
+    #[serde(default)]
+    pub(crate) base_types: IndexMap<String, BaseType>,
+    #[serde(default)]
+    pub(crate) user_types: IndexMap<String, UserType>,
+    #[serde(default)]
+    pub(crate) enums: IndexMap<String, IsfEnum>,
+    #[serde(default)]
+    pub(crate) symbols: IndexMap<String, IsfSymbol>,
+}
+
+impl Isf {
+    /// Parses an ISF file, decompressing it first if it's xz-compressed.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        let data = decompress(data)?;
+        let isf = serde_json::from_slice::<Self>(&data)?;
+
+        if isf.metadata.format.split('.').next() != Some(FORMAT_MAJOR) {
+            return Err(Error::UnsupportedFormat(isf.metadata.format));
+        }
+
+        Ok(isf)
+    }
+
+    /// Reads and parses an ISF file (`.json` or `.json.xz`).
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_slice(&std::fs::read(path)?)
+    }
+
+    /// Returns the version of the ISF format, e.g. `6.2.0`.
+    pub fn format(&self) -> &str {
+        &self.metadata.format
+    }
+
+    /// Returns the metadata identifying the image the ISF file describes.
+    ///
+    /// Windows ISF files are identified by the PDB they were created from,
+    /// Linux ISF files by the constant data of the `linux_banner` symbol.
+    pub fn identity(&self) -> Option<IsfIdentity> {
+        if let Some(pdb) = self.pdb() {
+            return Some(IsfIdentity::Windows(PdbIdentity {
+                database: pdb.database.clone(),
+                guid: pdb.guid.clone(),
+                age: pdb.age,
+            }));
+        }
+
+        self.linux_banner()
+            .map(|banner| IsfIdentity::Linux { banner })
+    }
+
+    /// Returns the PDB metadata of a Windows ISF file.
+    pub(crate) fn pdb(&self) -> Option<&PdbMetadata> {
+        self.metadata.windows.as_ref()?.pdb.as_ref()
+    }
+
+    /// Returns the Linux banner stored in the constant data of the
+    /// `linux_banner` symbol.
+    pub(crate) fn linux_banner(&self) -> Option<String> {
+        let constant_data = self.symbols.get("linux_banner")?.constant_data.as_ref()?;
+        let data = match base64::engine::general_purpose::STANDARD.decode(constant_data) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::warn!(%err, "invalid constant data of linux_banner");
+                return None;
+            }
+        };
+
+        let data = match data.iter().position(|&byte| byte == 0) {
+            Some(end) => &data[..end],
+            None => &data[..],
+        };
+
+        let banner = String::from_utf8_lossy(data).trim_end().to_owned();
+        (!banner.is_empty()).then_some(banner)
+    }
+}
+
+/// Identification of the image an ISF file describes.
+///
+/// Returned by [`Isf::identity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsfIdentity {
+    /// Windows image, identified by its PDB.
+    Windows(PdbIdentity),
+
+    /// Linux kernel, identified by its banner.
+    Linux {
+        /// Linux kernel banner.
+        banner: String,
+    },
+}
+
+/// Identification of a PDB file, as stored in Windows ISF files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdbIdentity {
+    /// Name of the PDB file, e.g. `ntkrnlmp.pdb`.
+    pub database: String,
+
+    /// GUID of the PDB file, e.g. `3844DBB9-2017-4967-BE7A-A4A2C20430FA`.
+    pub guid: String,
+
+    /// Age of the PDB file.
+    pub age: u32,
+}
+
+impl PdbIdentity {
+    /// Returns the GUID followed by the age, as used by symbol servers
+    /// (see [`pdb_identifier`]).
+    ///
+    /// Returns `None` if the GUID isn't made of 32 hex digits.
+    pub fn identifier(&self) -> Option<String> {
+        Some(pdb_identifier(&parse_pdb_guid(&self.guid)?, self.age))
+    }
+}
+
+/// Decompresses xz-compressed data.
+///
+/// Other data is returned as is.
+fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    if !data.starts_with(XZ_MAGIC) {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    let mut result = Vec::new();
+    lzma_rs::xz_decompress(&mut &data[..], &mut result)?;
+    Ok(Cow::Owned(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Metadata {
+    pub format: String,
+    pub windows: Option<WindowsMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WindowsMetadata {
+    pub pdb: Option<PdbMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PdbMetadata {
+    #[serde(rename = "GUID")]
+    pub guid: String,
+    pub age: u32,
+    pub database: String,
+    pub machine_type: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BaseType {
+    pub kind: BaseKind,
+    pub size: u64,
+    #[serde(default)]
+    pub signed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BaseKind {
+    Int,
+    Char,
+    Bool,
+    Float,
+    Void,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UserType {
+    pub kind: UserKind,
+    pub size: u64,
+    #[serde(default)]
+    pub fields: IndexMap<String, IsfField>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum UserKind {
+    Struct,
+    Union,
+    Class,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct IsfField {
+    pub offset: u64,
+    #[serde(rename = "type")]
+    pub type_: TypeDescriptor,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub(crate) enum TypeDescriptor {
+    Base {
+        name: String,
+    },
+    Pointer {
+        subtype: Box<TypeDescriptor>,
+    },
+    Array {
+        count: u64,
+        subtype: Box<TypeDescriptor>,
+    },
+    Struct {
+        name: String,
+    },
+    Union {
+        name: String,
+    },
+    Class {
+        name: String,
+    },
+    Enum {
+        name: String,
+    },
+    Bitfield {
+        bit_position: u64,
+        bit_length: u64,
+        #[serde(rename = "type")]
+        type_: Box<TypeDescriptor>,
+    },
+    Function {},
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct IsfEnum {
+    pub base: String,
+    pub constants: IndexMap<String, Variant>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct IsfSymbol {
+    pub address: u64,
+    pub constant_data: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier() {
+        let mut pdb = PdbIdentity {
+            database: String::from("ntkrnlmp.pdb"),
+            guid: String::from("3844DBB9-2017-4967-BE7A-A4A2C20430FA"),
+            age: 2,
+        };
+
+        assert_eq!(
+            pdb.identifier().as_deref(),
+            Some("3844dbb920174967be7aa4a2c20430fa2")
+        );
+
+        pdb.age = 0x1c;
+        assert_eq!(
+            pdb.identifier().as_deref(),
+            Some("3844dbb920174967be7aa4a2c20430fa1c")
+        );
+
+        pdb.guid = String::from("3844DBB9-2017-4967-BE7A");
+        assert_eq!(pdb.identifier(), None);
+    }
+}
@@ -0,0 +1,11 @@
+//! Volatility Intermediate Symbol Format (ISF) importer.
+
+mod error;
+mod format;
+mod profile;
+
+pub use self::{
+    error::Error,
+    format::{Isf, IsfIdentity, PdbIdentity},
+    profile::{create_profile, create_profile_with_options},
+};
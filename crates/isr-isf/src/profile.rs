@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+use isr_core::{
+    types::{
+        ArrayRef, BaseRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Struct, StructKind,
+        StructRef, Type, Types,
+    },
+    KernelVersion, Profile, ProfileOptions, ProfileSource, SymbolBase, SymbolScope, Symbols,
+};
+use smallvec::smallvec;
+
+use super::{
+    format::{BaseKind, TypeDescriptor, UserKind},
+    Error, Isf,
+};
+
+pub fn create_profile<F, E>(isf: &Isf, serialize: F) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    create_profile_with_options(isf, &ProfileOptions::default(), serialize)
+}
+
+/// Creates a profile from an ISF file.
+///
+/// Symbols of Windows ISF files are RVAs, symbols of Linux ISF files are
+/// virtual addresses based at `_text`, the same as the profiles created
+/// from a PDB file or a `System.map`.
+pub fn create_profile_with_options<F, E>(
+    isf: &Isf,
+    options: &ProfileOptions,
+    serialize: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = options.run_span("isf").entered();
+    let architecture = architecture(isf);
+    tracing::debug!("architecture: {architecture}");
+
+    tracing::debug!("collecting symbols");
+    let mut symbols = match options.symbol_scope {
+        // ISF doesn't record the visibility of the symbols.
+        SymbolScope::All | SymbolScope::Exported => Symbols(
+            isf.symbols
+                .iter()
+                .map(|(name, symbol)| (Cow::Borrowed(name.as_str()), symbol.address))
+                .collect(),
+        ),
+        SymbolScope::None => Symbols(IndexMap::new()),
+    };
+
+    tracing::debug!("collecting types");
+    let mut types = Types {
+        enums: isf
+            .enums
+            .iter()
+            .map(|(name, enum_)| {
+                let udt = Enum::new(
+                    Type::Base(base_ref(isf, &enum_.base)),
+                    enum_
+                        .constants
+                        .iter()
+                        .map(|(name, value)| (Cow::Borrowed(name.as_str()), *value))
+                        .collect(),
+                );
+
+                (Cow::Borrowed(name.as_str()), udt)
+            })
+            .collect(),
+        structs: isf
+            .user_types
+            .iter()
+            .map(|(name, user_type)| {
+                let udt = Struct {
+                    kind: match user_type.kind {
+                        UserKind::Struct => StructKind::Struct,
+                        UserKind::Union => StructKind::Union,
+                        UserKind::Class => StructKind::Class,
+                    },
+                    size: user_type.size,
+                    fields: user_type
+                        .fields
+                        .iter()
+                        .map(|(name, field)| {
+                            let field = Field {
+                                offset: field.offset,
+                                type_: convert_type(isf, &field.type_),
+                            };
+
+                            (Cow::Borrowed(name.as_str()), field)
+                        })
+                        .collect(),
+                    incomplete: false,
+                };
+
+                (Cow::Borrowed(name.as_str()), udt)
+            })
+            .collect(),
+        ..Types::default()
+    };
+
+    let truncated = options.apply_max_name_length(&mut symbols, &mut types);
+    if truncated > 0 {
+        tracing::info!(truncated, "truncated long names");
+    }
+
+    let (symbol_base, preferred_base, kernel_version) = match isf.pdb() {
+        Some(_) => (SymbolBase::Rva, options.preferred_base, None),
+        None => {
+            let text = isf.symbols.get("_text").map(|symbol| symbol.address);
+            let kernel_version = isf.linux_banner().and_then(|banner| {
+                // Linux version 6.8.0-40-generic (...)
+                KernelVersion::parse(banner.split_whitespace().nth(2)?)
+            });
+
+            (
+                SymbolBase::VirtualAddress {
+                    default_base: text.unwrap_or(0),
+                },
+                options.preferred_base.or(text),
+                options.kernel_version.or(kernel_version),
+            )
+        }
+    };
+
+    tracing::debug!("writing profile");
+    let profile = Profile::new(Cow::Borrowed(architecture), symbols, types)
+        .with_source(ProfileSource::Isf)
+        .with_kernel_version(kernel_version)
+        .with_symbol_base(symbol_base)
+        .with_preferred_base(preferred_base)
+        .with_symbol_scope(options.symbol_scope);
+
+    serialize(&profile).map_err(|err| Error::Serialize(err.into()))?;
+
+    Ok(())
+}
+
+/// Returns the architecture of the ISF file.
+///
+/// Windows ISF files record the machine type of the PDB file. Otherwise,
+/// the architecture is guessed from the size of a pointer.
+fn architecture(isf: &Isf) -> &'static str {
+    match isf.pdb().and_then(|pdb| pdb.machine_type) {
+        Some(0x8664) => return "Amd64",
+        Some(0x14c) => return "X86",
+        Some(0xaa64) => return "Arm64",
+        _ => {}
+    }
+
+    match isf.base_types.get("pointer").map(|pointer| pointer.size) {
+        Some(4) => "X86",
+        _ => "Amd64",
+    }
+}
+
+/// Converts an ISF type descriptor.
+fn convert_type<'a>(isf: &Isf, descriptor: &'a TypeDescriptor) -> Type<'a> {
+    match descriptor {
+        TypeDescriptor::Base { name } => Type::Base(base_ref(isf, name)),
+        TypeDescriptor::Pointer { subtype } => Type::Pointer(PointerRef {
+            subtype: Box::new(convert_type(isf, subtype)),
+        }),
+        TypeDescriptor::Array { count, subtype } => Type::Array(ArrayRef {
+            subtype: Box::new(convert_type(isf, subtype)),
+            dims: smallvec![*count],
+            size: *count,
+        }),
+        TypeDescriptor::Struct { name }
+        | TypeDescriptor::Union { name }
+        | TypeDescriptor::Class { name } => Type::Struct(StructRef {
+            name: Cow::Borrowed(name),
+        }),
+        TypeDescriptor::Enum { name } => Type::Enum(EnumRef {
+            name: Cow::Borrowed(name),
+        }),
+        TypeDescriptor::Bitfield {
+            bit_position,
+            bit_length,
+            type_,
+        } => Type::Bitfield(BitfieldRef {
+            subtype: Box::new(convert_type(isf, type_)),
+            bit_length: *bit_length,
+            bit_position: *bit_position,
+        }),
+        TypeDescriptor::Function {} => Type::Function,
+    }
+}
+
+/// Converts an ISF base type, referenced by name.
+fn base_ref(isf: &Isf, name: &str) -> BaseRef {
+    let base_type = match isf.base_types.get(name) {
+        Some(base_type) => base_type,
+        None => {
+            tracing::warn!(name, "unknown base type");
+            return BaseRef::Void;
+        }
+    };
+
+    match (&base_type.kind, base_type.size, base_type.signed) {
+        (BaseKind::Void, _, _) => BaseRef::Void,
+        (BaseKind::Bool, _, _) => BaseRef::Bool,
+        (BaseKind::Char, 1, _) => BaseRef::Char,
+        (BaseKind::Char, 2, _) => BaseRef::Wchar,
+        (BaseKind::Float, 2, _) => BaseRef::F16,
+        (BaseKind::Float, 4, _) => BaseRef::F32,
+        (BaseKind::Float, 8, _) => BaseRef::F64,
+        (BaseKind::Float, 16, _) => BaseRef::F128,
+        (BaseKind::Int, 1, true) => BaseRef::I8,
+        (BaseKind::Int, 2, true) => BaseRef::I16,
+        (BaseKind::Int | BaseKind::Char, 4, true) => BaseRef::I32,
+        (BaseKind::Int | BaseKind::Char, 8, true) => BaseRef::I64,
+        (BaseKind::Int | BaseKind::Char, 16, true) => BaseRef::I128,
+        (BaseKind::Int, 1, false) => BaseRef::U8,
+        (BaseKind::Int, 2, false) => BaseRef::U16,
+        (BaseKind::Int | BaseKind::Char, 4, false) => BaseRef::U32,
+        (BaseKind::Int | BaseKind::Char, 8, false) => BaseRef::U64,
+        (BaseKind::Int | BaseKind::Char, 16, false) => BaseRef::U128,
+        (kind, size, _) => {
+            tracing::warn!(name, ?kind, size, "unsupported base type");
+            BaseRef::Void
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Builds Volatility ISF files.
+
+use serde_json::{json, Value};
+
+/// Format of the ISF files.
+pub const FORMAT: &str = "6.2.0";
+
+pub const DATABASE: &str = "ntkrnlmp.pdb";
+pub const GUID: &str = "3844DBB9-2017-4967-BE7A-A4A2C20430FA";
+pub const AGE: u32 = 2;
+
+/// Address of the `_text` symbol of the Linux kernel.
+pub const TEXT: u64 = 0xffff_ffff_8100_0000;
+
+/// Returns an ISF file of `ntkrnlmp.pdb`, with `_EPROCESS` and
+/// `_POOL_TYPE`.
+pub fn windows() -> Vec<u8> {
+    let isf = json!({
+        "metadata": {
+            "format": FORMAT,
+            "windows": {
+                "pdb": {
+                    "GUID": GUID,
+                    "age": AGE,
+                    "database": DATABASE,
+                    "machine_type": 0x8664,
+                },
+            },
+        },
+        "base_types": base_types(),
+        "user_types": {
+            "_LIST_ENTRY": list_entry("_LIST_ENTRY", "Flink", "Blink"),
+            "_EPROCESS": {
+                "kind": "struct",
+                "size": 0xa40,
+                "fields": {
+                    "UniqueProcessId": {
+                        "offset": 0x440,
+                        "type": { "kind": "pointer", "subtype": base("void") },
+                    },
+                    "ActiveProcessLinks": {
+                        "offset": 0x448,
+                        "type": { "kind": "struct", "name": "_LIST_ENTRY" },
+                    },
+                    "BreakOnTermination": {
+                        "offset": 0x464,
+                        "type": {
+                            "kind": "bitfield",
+                            "bit_position": 13,
+                            "bit_length": 1,
+                            "type": base("unsigned long"),
+                        },
+                    },
+                    "ImageFileName": {
+                        "offset": 0x5a8,
+                        "type": { "kind": "array", "count": 15, "subtype": base("unsigned char") },
+                    },
+                    "PoolType": {
+                        "offset": 0x5b8,
+                        "type": { "kind": "enum", "name": "_POOL_TYPE" },
+                    },
+                },
+            },
+        },
+        "enums": {
+            "_POOL_TYPE": {
+                "base": "int",
+                "size": 4,
+                "constants": { "NonPagedPool": 0, "PagedPool": 1 },
+            },
+        },
+        "symbols": {
+            "PsActiveProcessHead": { "address": 0x43_7bc0 },
+            "PsInitialSystemProcess": { "address": 0x4f_d0a0 },
+        },
+    });
+
+    serde_json::to_vec(&isf).unwrap()
+}
+
+/// Returns an ISF file of a Linux kernel with the banner, with
+/// `task_struct`.
+pub fn linux(banner: &str) -> Vec<u8> {
+    let mut constant_data = format!("{banner}\n").into_bytes();
+    constant_data.push(0);
+
+    let isf = json!({
+        "metadata": {
+            "format": FORMAT,
+            "linux": { "symbols": [] },
+        },
+        "base_types": base_types(),
+        "user_types": {
+            "list_head": list_entry("list_head", "next", "prev"),
+            "task_struct": {
+                "kind": "struct",
+                "size": 0x2600,
+                "fields": {
+                    "tasks": {
+                        "offset": 0x900,
+                        "type": { "kind": "struct", "name": "list_head" },
+                    },
+                    "pid": { "offset": 0x9a8, "type": base("int") },
+                },
+            },
+        },
+        "symbols": {
+            "_text": { "address": TEXT },
+            "init_task": { "address": 0xffff_ffff_82a0_c940_u64 },
+            "linux_banner": {
+                "address": 0xffff_ffff_8260_0120_u64,
+                "constant_data": base64(&constant_data),
+            },
+        },
+    });
+
+    serde_json::to_vec(&isf).unwrap()
+}
+
+/// Compresses an ISF file with xz.
+pub fn xz(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    lzma_rs::xz_compress(&mut &data[..], &mut result).unwrap();
+    result
+}
+
+fn base_types() -> Value {
+    let base_type = |kind, size, signed| json!({ "kind": kind, "size": size, "signed": signed });
+
+    json!({
+        "void": base_type("void", 0, false),
+        "int": base_type("int", 4, true),
+        "unsigned char": base_type("char", 1, false),
+        "unsigned long": base_type("int", 4, false),
+        "pointer": base_type("int", 8, false),
+    })
+}
+
+fn base(name: &str) -> Value {
+    json!({ "kind": "base", "name": name })
+}
+
+/// Returns a doubly linked list entry, e.g. `_LIST_ENTRY`.
+fn list_entry(name: &str, next: &str, prev: &str) -> Value {
+    let pointer = json!({
+        "kind": "pointer",
+        "subtype": { "kind": "struct", "name": name },
+    });
+
+    json!({
+        "kind": "struct",
+        "size": 16,
+        "fields": {
+            next: { "offset": 0, "type": pointer },
+            prev: { "offset": 8, "type": pointer },
+        },
+    })
+}
+
+/// Encodes data with the standard base64 alphabet, with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => result.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3f) as usize] as char),
+                false => result.push('='),
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use isr_core::{
+    types::{BaseRef, Type, Variant},
+    KernelVersion, Profile, ProfileSource, SymbolBase,
+};
+use isr_isf::{Error, Isf, IsfIdentity, PdbIdentity};
+
+use self::common::isf::{self, AGE, DATABASE, GUID, TEXT};
+
+const BANNER: &str = "Linux version 6.8.0-40-generic (buildd@lcy02-amd64-078) \
+    (x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~22.04) 12.3.0, GNU ld (GNU Binutils for Ubuntu) 2.38) \
+    #40~22.04.3-Ubuntu SMP PREEMPT_DYNAMIC Tue Jul 30 17:30:19 UTC 2 \
+    (Ubuntu 6.8.0-40.40~22.04.3-generic 6.8.12)";
+
+/// Converts the ISF file and passes the profile to `f`.
+fn with_profile(isf: &Isf, f: impl FnOnce(&Profile)) {
+    isr_isf::create_profile(isf, |profile| {
+        f(profile);
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn windows() {
+    let isf = Isf::from_slice(&isf::windows()).unwrap();
+    assert_eq!(isf.format(), "6.2.0");
+    assert_eq!(
+        isf.identity(),
+        Some(IsfIdentity::Windows(PdbIdentity {
+            database: String::from(DATABASE),
+            guid: String::from(GUID),
+            age: AGE,
+        }))
+    );
+
+    with_profile(&isf, |profile| {
+        assert_eq!(profile.architecture(), "Amd64");
+        assert_eq!(profile.source(), Some(ProfileSource::Isf));
+        assert_eq!(profile.symbol_base(), SymbolBase::Rva);
+        assert_eq!(profile.find_symbol("PsActiveProcessHead"), Some(0x43_7bc0));
+
+        let udt = profile.find_struct("_EPROCESS").unwrap();
+        assert_eq!(udt.size, 0xa40);
+        assert_eq!(udt.fields["ActiveProcessLinks"].offset, 0x448);
+
+        let Type::Bitfield(bitfield) = &udt.fields["BreakOnTermination"].type_
+        else {
+            panic!("not a bitfield");
+        };
+        assert_eq!((bitfield.bit_position, bitfield.bit_length), (13, 1));
+        assert!(matches!(*bitfield.subtype, Type::Base(BaseRef::U32)));
+
+        let Type::Array(array) = &udt.fields["ImageFileName"].type_
+        else {
+            panic!("not an array");
+        };
+        assert_eq!(array.dims.as_slice(), [15]);
+        assert!(matches!(*array.subtype, Type::Base(BaseRef::Char)));
+
+        let enum_ = profile.find_enum("_POOL_TYPE").unwrap();
+        assert!(matches!(enum_.subtype, Type::Base(BaseRef::I32)));
+        assert_eq!(enum_.value_of("PagedPool"), Some(&Variant::U8(1)));
+    });
+}
+
+#[test]
+fn linux_xz() {
+    let isf = Isf::from_slice(&isf::xz(&isf::linux(BANNER))).unwrap();
+    assert_eq!(
+        isf.identity(),
+        Some(IsfIdentity::Linux {
+            banner: String::from(BANNER)
+        })
+    );
+
+    with_profile(&isf, |profile| {
+        assert_eq!(profile.architecture(), "Amd64");
+        assert_eq!(profile.kernel_version(), KernelVersion::parse("6.8.0-40"));
+        assert_eq!(
+            profile.symbol_base(),
+            SymbolBase::VirtualAddress { default_base: TEXT }
+        );
+        assert_eq!(profile.preferred_base(), Some(TEXT));
+
+        let udt = profile.find_struct("task_struct").unwrap();
+        assert_eq!(udt.fields["pid"].offset, 0x9a8);
+    });
+}
+
+#[test]
+fn unsupported_format() {
+    let mut data = serde_json::from_slice::<serde_json::Value>(&isf::windows()).unwrap();
+    data["metadata"]["format"] = "4.1.0".into();
+
+    let result = Isf::from_slice(&serde_json::to_vec(&data).unwrap());
+    assert!(matches!(result, Err(Error::UnsupportedFormat(format)) if format == "4.1.0"));
+}
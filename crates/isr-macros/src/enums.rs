@@ -0,0 +1,367 @@
+/// Defines conversion tables for enums.
+///
+/// Many constants (e.g. `_POOL_TYPE`) are only captured in the debug
+/// symbols as enum types, and their values might change across OS builds.
+/// This macro generates, for each defined enum, a struct with the value of
+/// each variant read from the profile.
+///
+/// Rust enum discriminants must be known at compile time, therefore they
+/// can't be filled from a profile. Instead, the generated struct acts as
+/// a conversion table between names and values, and optionally between
+/// a Rust enum and values (see `derive_tryfrom` below).
+///
+/// # Usage
+///
+/// ```rust
+/// # use std::borrow::Cow;
+/// #
+/// # use isr_core::{
+/// #     types::{BaseRef, Enum, Type, Types, Variant},
+/// #     Profile, Symbols,
+/// # };
+/// # use isr_macros::enums;
+/// #
+/// enums! {
+///     #[derive(Debug)]
+///     pub struct Enums {
+///         // Also generate the `PoolType` Rust enum.
+///         #[isr(derive_tryfrom = PoolType)]
+///         enum _POOL_TYPE {
+///             NonPagedPool,
+///             PagedPool,
+///
+///             // Provide aliases when variants might have different names
+///             // across builds.
+///             #[isr(alias = "NonPagedPoolExecute")]
+///             NonPagedPoolMustSucceed,
+///         }
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Use a profile with the `_POOL_TYPE` enum of a Windows 10.0.18362.356
+/// // kernel.
+/// # let variants = [
+/// #     ("NonPagedPool", 0),
+/// #     ("NonPagedPoolExecute", 0),
+/// #     ("PagedPool", 1),
+/// # ];
+/// # let mut types = Types::default();
+/// # types.enums.insert(
+/// #     Cow::Borrowed("_POOL_TYPE"),
+/// #     Enum::new(
+/// #         Type::Base(BaseRef::I32),
+/// #         variants
+/// #             .into_iter()
+/// #             .map(|(name, value)| (Cow::Borrowed(name), Variant::I64(value)))
+/// #             .collect(),
+/// #     ),
+/// # );
+/// # let profile = Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types);
+/// let enums = Enums::new(&profile)?;
+///
+/// assert_eq!(enums._POOL_TYPE.NonPagedPool, 0);
+/// assert_eq!(enums._POOL_TYPE.PagedPool, 1);
+///
+/// assert_eq!(enums._POOL_TYPE.name_of(1), Some("PagedPool"));
+/// assert_eq!(enums._POOL_TYPE.value_of("PagedPool"), Some(1));
+///
+/// let pool_type = enums._POOL_TYPE.try_from_value(1)?;
+/// assert_eq!(pool_type, PoolType::PagedPool);
+/// assert_eq!(enums._POOL_TYPE.to_value(pool_type), 1);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Attributes
+///
+/// - `#[isr(alias = <alias>)]`: Specifies an alternative name for a variant.
+///
+///   `<alias>` can be a single literal or an array of literals, e.g.:
+///   - `#[isr(alias = "alternative_name")]`
+///   - `#[isr(alias = ["name1", "name2", ...])]`
+///
+/// - `#[isr(derive_tryfrom = <name>)]`: On an enum, also generates a Rust
+///   enum called `<name>` with the declared variants. Its discriminants
+///   don't follow the profile, the inner struct converts between the Rust
+///   enum and the values instead. The name must be given, as it can't be
+///   derived from the enum name.
+///
+/// The generated struct provides a `new` method that takes a reference to
+/// a [`Profile`] and returns a [`Result`] containing the populated struct or
+/// an error if any enums or variants are not found. It also implements
+/// [`FromProfile`], so it can be stored in a `static` [`IsrLazy`].
+///
+/// Each inner struct also implements the following conversion methods:
+/// - `name_of(value)`: Returns the name of the variant with the given value.
+/// - `value_of(name)`: Returns the value of the variant with the given name.
+///
+/// With `derive_tryfrom`, the inner struct also provides:
+/// - `try_from_value(value)`: Returns the Rust enum variant with the given
+///   value, or [`Error::Conversion`] if no variant has it.
+/// - `to_value(variant)`: Returns the value of the Rust enum variant.
+///
+/// `TryFrom<u64>` and `Into<u64>` can't be implemented for the Rust enum,
+/// as the conversion depends on the profile. Keep the generated struct
+/// around (e.g. in a `static` [`IsrLazy`]) and convert through it.
+///
+/// [`Profile`]: isr_core::Profile
+/// [`FromProfile`]: crate::FromProfile
+/// [`IsrLazy`]: crate::IsrLazy
+/// [`Error::Conversion`]: crate::Error::Conversion
+#[macro_export]
+macro_rules! enums {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($rest:tt)*
+        }
+    ) => {
+        $crate::enums!(@outer
+            $vis,
+            [ $(#[$meta])* ],
+            struct $name {
+                $($rest)*
+            }
+        );
+
+        $crate::enums!(@inner
+            $vis,
+            [ $(#[$meta])* ],
+            $($rest)*
+        );
+    };
+
+    (@outer
+        $vis:vis,
+        [$($meta:tt)*],
+        struct $name:ident {
+            $(
+                $(#[isr($($eattr:tt)*)])?
+                enum $iname:ident {
+                    $(
+                        $(#[isr($($vattr:tt)*)])?
+                        $vname:ident
+                    ),* $(,)?
+                }
+            )+
+        }
+    ) => {
+        #[allow(non_camel_case_types, non_snake_case, missing_docs)]
+        $($meta)*
+        $vis struct $name {
+            $(
+                $vis $iname: $iname,
+            )*
+        }
+
+        impl $name {
+            /// Creates a new enums instance.
+            $vis fn new(profile: &$crate::__private::Profile) -> Result<Self, $crate::Error> {
+                Ok(Self {
+                    $(
+                        $iname: $iname::new(profile)?,
+                    )+
+                })
+            }
+        }
+
+        impl $crate::FromProfile for $name {
+            fn from_profile(
+                profile: &$crate::__private::Profile,
+            ) -> Result<Self, $crate::Error> {
+                Self::new(profile)
+            }
+        }
+    };
+
+    (@inner
+        $vis:vis,
+        [$($meta:tt)*],
+        $(#[isr(derive_tryfrom = $ename:ident)])?
+        enum $iname:ident {
+            $(
+                $(#[isr($($vattr:tt)*)])?
+                $vname:ident
+            ),* $(,)?
+        }
+
+        $($rest:tt)*
+    ) => {
+        #[allow(non_camel_case_types, non_snake_case, missing_docs)]
+        $($meta)*
+        $vis struct $iname {
+            $(
+                pub $vname: u64,
+            )*
+        }
+
+        impl $iname {
+            #[doc = concat!("Creates a new `", stringify!($iname), "` instance.")]
+            $vis fn new(profile: &$crate::__private::Profile) -> Result<Self, $crate::Error> {
+                Ok(Self {
+                    $(
+                        $vname: $crate::enums!(@assign
+                            profile,
+                            $iname,
+                            $vname,
+                            [$($($vattr)*)?]
+                        )?,
+                    )*
+                })
+            }
+
+            /// Returns the name of the variant with the given value.
+            $vis fn name_of(&self, value: u64) -> Option<&'static str> {
+                $(
+                    if value == self.$vname {
+                        return Some(stringify!($vname));
+                    }
+                )*
+
+                None
+            }
+
+            /// Returns the value of the variant with the given name.
+            $vis fn value_of(&self, name: &str) -> Option<u64> {
+                $(
+                    if name == stringify!($vname) {
+                        return Some(self.$vname);
+                    }
+                )*
+
+                None
+            }
+        }
+
+        $crate::enums!(@tryfrom
+            $vis,
+            [$($ename)?],
+            $iname,
+            [$($vname),*]
+        );
+
+        $crate::enums!(@inner
+            $vis,
+            [$($meta)*],
+            $($rest)*
+        );
+    };
+
+    (@inner
+        $vis:vis,
+        [$($meta:tt)*],
+    ) => {};
+
+    //
+    // @tryfrom
+    //
+
+    (@tryfrom
+        $vis:vis,
+        [],
+        $iname:ident,
+        [$($vname:ident),*]
+    ) => {};
+
+    (@tryfrom
+        $vis:vis,
+        [$ename:ident],
+        $iname:ident,
+        [$($vname:ident),*]
+    ) => {
+        #[doc = concat!("Variants of the `", stringify!($iname), "` enum.")]
+        ///
+        #[doc = concat!("Converted from and to values by `", stringify!($iname), "`.")]
+        #[allow(non_camel_case_types, missing_docs)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $ename {
+            $($vname),*
+        }
+
+        impl $ename {
+            /// All variants, in declaration order.
+            $vis const VARIANTS: &'static [Self] = &[$(Self::$vname),*];
+
+            /// Returns the name of the variant.
+            $vis fn name(self) -> &'static str {
+                match self {
+                    $(Self::$vname => stringify!($vname)),*
+                }
+            }
+        }
+
+        impl $iname {
+            /// Returns the variant with the given value.
+            ///
+            /// If several variants share the value, the first declared one
+            /// is returned.
+            $vis fn try_from_value(&self, value: u64) -> Result<$ename, $crate::Error> {
+                $ename::VARIANTS
+                    .iter()
+                    .copied()
+                    .find(|&variant| self.to_value(variant) == value)
+                    .ok_or($crate::Error::Conversion(concat!(
+                        "no ",
+                        stringify!($iname),
+                        " variant has the value"
+                    )))
+            }
+
+            /// Returns the value of the variant.
+            $vis fn to_value(&self, variant: $ename) -> u64 {
+                match variant {
+                    $($ename::$vname => self.$vname),*
+                }
+            }
+        }
+    };
+
+    //
+    // @assign
+    //
+
+    (@assign
+        $profile:ident,
+        $iname:ident,
+        $vname:ident,
+        []
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile
+            .find_variant_value(stringify!($iname), stringify!($vname))
+    }};
+
+    (@assign
+        $profile:ident,
+        $iname:ident,
+        $vname:ident,
+        [alias = $alias:literal]
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile
+            .find_variant_value(stringify!($iname), stringify!($vname))
+            .or_else(|_| $profile
+                .find_variant_value(stringify!($iname), $alias)
+            )
+    }};
+
+    (@assign
+        $profile:ident,
+        $iname:ident,
+        $vname:ident,
+        [alias = [$($alias:literal),+ $(,)?]]
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile
+            .find_variant_value(stringify!($iname), stringify!($vname))
+            $(
+                .or_else(|_| $profile
+                    .find_variant_value(stringify!($iname), $alias)
+                )
+            )+
+    }};
+}
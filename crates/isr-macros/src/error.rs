@@ -6,14 +6,62 @@ pub enum Error {
     #[error("Failed to find symbol {0}")]
     SymbolNotFound(String),
 
+    #[error("Failed to find symbols {}", .0.join(", "))]
+    SymbolsNotFound(Vec<String>),
+
     #[error("Failed to find type {0}")]
     TypeNotFound(String),
 
+    #[error("Type name {type_name} is ambiguous, it matches {}", candidates.join(", "))]
+    AmbiguousType {
+        type_name: String,
+        candidates: Vec<String>,
+    },
+
     #[error("Failed to find field {field_name} in type {type_name}")]
     FieldNotFound {
         type_name: String,
         field_name: String,
     },
+
+    #[error("Field {field_name} in type {type_name} has zero size")]
+    ZeroSizedField {
+        type_name: String,
+        field_name: String,
+    },
+
+    #[error("Failed to find variant {variant_name} in enum {type_name}")]
+    VariantNotFound {
+        type_name: String,
+        variant_name: String,
+    },
+
+    #[error("Member address {member_address:#x} is lower than the member offset {offset:#x}")]
+    ContainerUnderflow { member_address: u64, offset: u64 },
+
+    #[error("Expected architecture {}, found {found}", expected.join(" or "))]
+    ArchitectureMismatch {
+        expected: Vec<String>,
+        found: String,
+    },
+}
+
+impl From<isr_core::ArchitectureMismatch> for Error {
+    fn from(value: isr_core::ArchitectureMismatch) -> Self {
+        Self::ArchitectureMismatch {
+            expected: value.expected,
+            found: value.found,
+        }
+    }
+}
+
+impl From<isr_core::AmbiguousName> for Error {
+    fn from(value: isr_core::AmbiguousName) -> Self {
+        Self::AmbiguousType {
+            type_name: value.name,
+            candidates: value.candidates,
+        }
+    }
 }
 
 impl Error {
@@ -21,6 +69,15 @@ impl Error {
         Self::SymbolNotFound(symbol_name.into())
     }
 
+    /// Returns [`Error::SymbolNotFound`] for a single symbol, otherwise
+    /// [`Error::SymbolsNotFound`].
+    pub fn symbols_not_found(mut symbol_names: Vec<String>) -> Self {
+        match symbol_names.len() {
+            1 => Self::SymbolNotFound(symbol_names.remove(0)),
+            _ => Self::SymbolsNotFound(symbol_names),
+        }
+    }
+
     pub fn type_not_found(type_name: impl Into<String>) -> Self {
         Self::TypeNotFound(type_name.into())
     }
@@ -31,4 +88,21 @@ impl Error {
             field_name: field_name.into(),
         }
     }
+
+    pub fn zero_sized_field(type_name: impl Into<String>, field_name: impl Into<String>) -> Self {
+        Self::ZeroSizedField {
+            type_name: type_name.into(),
+            field_name: field_name.into(),
+        }
+    }
+
+    pub fn variant_not_found(
+        type_name: impl Into<String>,
+        variant_name: impl Into<String>,
+    ) -> Self {
+        Self::VariantNotFound {
+            type_name: type_name.into(),
+            variant_name: variant_name.into(),
+        }
+    }
 }
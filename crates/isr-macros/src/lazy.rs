@@ -0,0 +1,144 @@
+use std::sync::OnceLock;
+
+use isr_core::Profile;
+
+use crate::Error;
+
+/// Types that can be resolved from a [`Profile`].
+///
+/// Implemented by the structs generated by the [`offsets!`], [`symbols!`]
+/// and [`enums!`] macros.
+///
+/// [`offsets!`]: crate::offsets
+/// [`symbols!`]: crate::symbols
+/// [`enums!`]: crate::enums
+pub trait FromProfile: Sized {
+    /// Resolves the value from the profile.
+    fn from_profile(profile: &Profile) -> Result<Self, Error>;
+}
+
+/// Lazily resolved, process-wide value.
+///
+/// Allows the structs generated by the [`offsets!`], [`symbols!`] and
+/// [`enums!`] macros to be stored in a `static` and resolved once, when the
+/// profile becomes available.
+///
+/// Failed resolutions don't poison the value, so the initialization can
+/// be retried (e.g. with a different profile) until it succeeds. Once
+/// initialized, the value never changes.
+///
+/// # Usage
+///
+/// ```rust
+/// # use std::borrow::Cow;
+/// #
+/// # use isr_core::{
+/// #     types::{BaseRef, Field as TypeField, Struct, StructKind, Type, Types},
+/// #     Profile, Symbols,
+/// # };
+/// # use isr_macros::{offsets, Field, IsrLazy};
+/// #
+/// offsets! {
+///     pub struct Offsets {
+///         struct _EPROCESS {
+///             UniqueProcessId: Field,
+///         }
+///     }
+/// }
+///
+/// static OFFSETS: IsrLazy<Offsets> = IsrLazy::new();
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let field = TypeField {
+/// #     offset: 744,
+/// #     type_: Type::Base(BaseRef::U64),
+/// # };
+/// # let mut types = Types::default();
+/// # types.structs.insert(
+/// #     Cow::Borrowed("_EPROCESS"),
+/// #     Struct {
+/// #         kind: StructKind::Struct,
+/// #         size: 2176,
+/// #         fields: [(Cow::Borrowed("UniqueProcessId"), field)].into_iter().collect(),
+/// #         incomplete: false,
+/// #     },
+/// # );
+/// # let profile = Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types);
+/// // Once, at startup.
+/// OFFSETS.get_or_init(&profile)?;
+///
+/// // Everywhere after.
+/// let offsets = OFFSETS.get().expect("offsets not initialized");
+/// assert_eq!(offsets._EPROCESS.UniqueProcessId.offset, 744);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`offsets!`]: crate::offsets
+/// [`symbols!`]: crate::symbols
+/// [`enums!`]: crate::enums
+#[derive(Debug)]
+pub struct IsrLazy<T> {
+    cell: OnceLock<T>,
+}
+
+impl<T> IsrLazy<T> {
+    /// Creates a new, uninitialized value.
+    pub const fn new() -> Self {
+        Self {
+            cell: OnceLock::new(),
+        }
+    }
+
+    /// Returns the value, or `None` if it isn't initialized yet.
+    pub fn get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    /// Returns the value without checking that it is initialized.
+    ///
+    /// Intended for hot paths that are only reached after a successful
+    /// [`get_or_init`].
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized.
+    ///
+    /// [`get_or_init`]: Self::get_or_init
+    pub unsafe fn get_unchecked(&self) -> &T {
+        debug_assert!(self.cell.get().is_some(), "IsrLazy is not initialized");
+
+        unsafe { self.cell.get().unwrap_unchecked() }
+    }
+
+    /// Returns `true` if the value is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+}
+
+impl<T> IsrLazy<T>
+where
+    T: FromProfile,
+{
+    /// Returns the value, resolving it from the profile if it isn't
+    /// initialized yet.
+    ///
+    /// If the resolution fails, the error is returned and the value stays
+    /// uninitialized. If several threads race to initialize the value, the
+    /// first successful resolution wins and the others are discarded.
+    pub fn get_or_init(&self, profile: &Profile) -> Result<&T, Error> {
+        if let Some(value) = self.cell.get() {
+            return Ok(value);
+        }
+
+        let value = T::from_profile(profile)?;
+        Ok(self.cell.get_or_init(|| value))
+    }
+}
+
+impl<T> Default for IsrLazy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,17 +1,33 @@
-//! [`offsets!`] and [`symbols!`] macros.
+//! [`offsets!`], [`symbols!`] and [`enums!`] macros.
 
+mod enums;
 mod error;
+mod lazy;
+mod matcher;
 mod offsets;
 mod profile;
 mod symbols;
+#[cfg(any(feature = "windows-helpers", feature = "linux-helpers"))]
+mod walk;
+
+#[cfg(feature = "linux-helpers")]
+pub mod linux;
+#[cfg(feature = "windows-helpers")]
+pub mod windows;
 
 pub mod __private {
-    pub use isr_core::Profile;
+    pub use isr_core::{Profile, Relaxation};
 
-    pub use super::{offsets::IntoField, profile::ProfileExt, symbols::IntoSymbol};
+    pub use super::{
+        offsets::{field_end, struct_len, IntoField},
+        profile::ProfileExt,
+        symbols::{IntoSymbol, SymbolDescriptor},
+    };
 }
 
 pub use self::{
     error::Error,
-    offsets::{Bitfield, Field},
+    lazy::{FromProfile, IsrLazy},
+    matcher::{CaseInsensitive, FieldNameMatcher, StripPrefix},
+    offsets::{Bitfield, Field, FieldDescriptor},
 };
@@ -0,0 +1,246 @@
+//! Helpers for walking Linux kernel objects.
+//!
+//! The helpers take fields resolved by the [`offsets!`] macro, not a
+//! profile, and read the memory through the [`Memory`] trait, so they're
+//! independent of any VMI backend and of the kernel version.
+//!
+//! [`offsets!`]: crate::offsets
+
+use std::collections::HashSet;
+
+pub use crate::walk::{Error, ListIter, Memory, DEFAULT_MAX_ENTRIES};
+use crate::{
+    walk::{read_pointer, read_uint},
+    Field,
+};
+
+/// Maximum length of a path component (`NAME_MAX`).
+const NAME_MAX: u64 = 255;
+
+/// Iterator over the entries of a `list_head` list.
+///
+/// In [`ListIter::new`], `next` is the `next` field of `list_head` and
+/// `member` is the `list_head` field of the containing structure (e.g.
+/// `tasks` of `task_struct`).
+pub type ListHeadIter<'m, M> = ListIter<'m, M>;
+
+/// In-order iterator over the nodes of a red-black tree (`rb_root`).
+///
+/// Returns the addresses of the containing structures. Iteration stops
+/// after the first error. Trees with cycles and trees with more than the
+/// maximum number of nodes are reported as errors.
+pub struct RbTreeIter<'m, M>
+where
+    M: Memory,
+{
+    memory: &'m M,
+    rb_left: Field,
+    rb_right: Field,
+    member: Field,
+    max_entries: usize,
+    stack: Vec<u64>,
+    pending: Option<u64>,
+    visited: HashSet<u64>,
+    failed: bool,
+}
+
+impl<'m, M> RbTreeIter<'m, M>
+where
+    M: Memory,
+{
+    /// Creates an iterator over the tree with the given root node.
+    ///
+    /// `root` is the value of the `rb_node` field of `rb_root`, `0` for
+    /// an empty tree. `rb_left` and `rb_right` are the fields of
+    /// `rb_node`, their size is the pointer size. `member` is the
+    /// `rb_node` field of the containing structure (e.g. `vm_rb` of
+    /// `vm_area_struct`).
+    pub fn new(memory: &'m M, root: u64, rb_left: Field, rb_right: Field, member: Field) -> Self {
+        Self {
+            memory,
+            rb_left,
+            rb_right,
+            member,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            stack: Vec::new(),
+            pending: (root != 0).then_some(root),
+            visited: HashSet::new(),
+            failed: false,
+        }
+    }
+
+    /// Sets the maximum number of nodes.
+    ///
+    /// Defaults to [`DEFAULT_MAX_ENTRIES`].
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            ..self
+        }
+    }
+
+    fn next_node(&mut self) -> Result<Option<u64>, Error<M::Error>> {
+        // Descend to the leftmost node of the pending subtree.
+        while let Some(node) = self.pending.take() {
+            if self.visited.len() >= self.max_entries {
+                return Err(Error::TooManyEntries(self.max_entries));
+            }
+
+            if !self.visited.insert(node) {
+                return Err(Error::Loop(node));
+            }
+
+            self.stack.push(node);
+            self.pending = self.read_child(node, self.rb_left)?;
+        }
+
+        let node = match self.stack.pop() {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        self.pending = self.read_child(node, self.rb_right)?;
+        Ok(Some(node))
+    }
+
+    fn read_child(&self, node: u64, child: Field) -> Result<Option<u64>, Error<M::Error>> {
+        let child = read_pointer(self.memory, node.wrapping_add(child.offset), child)?;
+        Ok((child != 0).then_some(child))
+    }
+}
+
+impl<M> Iterator for RbTreeIter<'_, M>
+where
+    M: Memory,
+{
+    type Item = Result<u64, Error<M::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.next_node() {
+            Ok(Some(node)) => Some(self.member.container_of(node).map_err(Error::from)),
+            Ok(None) => None,
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Reconstructs paths of `dentry` structures.
+#[derive(Debug, Clone, Copy)]
+pub struct DentryPathReader {
+    /// The `d_parent` field of `dentry`, its size is the pointer size.
+    pub d_parent: Field,
+
+    /// The `d_name` field of `dentry`.
+    pub d_name: Field,
+
+    /// The `len` field of `qstr`.
+    pub len: Field,
+
+    /// The `name` field of `qstr`, its size is the pointer size.
+    pub name: Field,
+
+    /// Maximum number of path components.
+    pub max_depth: usize,
+}
+
+impl DentryPathReader {
+    /// Creates a reader from the fields of `dentry` and `qstr`.
+    pub fn new(d_parent: Field, d_name: Field, len: Field, name: Field) -> Self {
+        Self {
+            d_parent,
+            d_name,
+            len,
+            name,
+            max_depth: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Sets the maximum number of path components.
+    ///
+    /// Defaults to [`DEFAULT_MAX_ENTRIES`].
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Reads the path of the dentry at the given address.
+    ///
+    /// Follows `d_parent` up to the root of the filesystem, whose parent
+    /// is itself. The path is relative to that root, not to the root of
+    /// the mount namespace. Invalid UTF-8 is replaced with `U+FFFD`.
+    pub fn read<M>(&self, memory: &M, dentry: u64) -> Result<String, Error<M::Error>>
+    where
+        M: Memory,
+    {
+        let mut components = Vec::new();
+        let mut dentry = dentry;
+
+        loop {
+            let parent = read_pointer(
+                memory,
+                dentry.wrapping_add(self.d_parent.offset),
+                self.d_parent,
+            )?;
+
+            if parent == 0 {
+                return Err(Error::NullLink(dentry));
+            }
+
+            if parent == dentry {
+                break;
+            }
+
+            if components.len() >= self.max_depth {
+                return Err(Error::TooManyEntries(self.max_depth));
+            }
+
+            components.push(self.read_name(memory, dentry)?);
+            dentry = parent;
+        }
+
+        if components.is_empty() {
+            return Ok(String::from("/"));
+        }
+
+        let mut path = String::new();
+        for component in components.iter().rev() {
+            path.push('/');
+            path.push_str(&String::from_utf8_lossy(component));
+        }
+
+        Ok(path)
+    }
+
+    /// Reads the name of a dentry.
+    fn read_name<M>(&self, memory: &M, dentry: u64) -> Result<Vec<u8>, Error<M::Error>>
+    where
+        M: Memory,
+    {
+        let qstr = dentry.wrapping_add(self.d_name.offset);
+        let length = read_uint(memory, qstr.wrapping_add(self.len.offset), self.len)?;
+
+        if length > NAME_MAX {
+            return Err(Error::InvalidStringLength {
+                length,
+                maximum_length: NAME_MAX,
+            });
+        }
+
+        let name = read_pointer(memory, qstr.wrapping_add(self.name.offset), self.name)?;
+        let mut bytes = vec![0; length as usize];
+        memory
+            .read(name, &mut bytes)
+            .map_err(|source| Error::Read {
+                address: name,
+                source,
+            })?;
+
+        Ok(bytes)
+    }
+}
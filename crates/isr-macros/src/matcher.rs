@@ -0,0 +1,46 @@
+/// Rule for matching field names that differ from the requested name.
+///
+/// Used by [`find_field_descriptor_with`] and the `#[isr(matcher = ...)]`
+/// attribute of the [`offsets!`] macro. An exact match is always tried
+/// first, the matcher is only consulted when there is none.
+///
+/// Implemented for closures taking the candidate field name and the
+/// requested name.
+///
+/// [`find_field_descriptor_with`]: crate::__private::ProfileExt::find_field_descriptor_with
+/// [`offsets!`]: crate::offsets
+pub trait FieldNameMatcher {
+    /// Checks whether a field name of the profile matches the requested
+    /// name.
+    fn matches(&self, field_name: &str, requested: &str) -> bool;
+}
+
+impl<F> FieldNameMatcher for F
+where
+    F: Fn(&str, &str) -> bool,
+{
+    fn matches(&self, field_name: &str, requested: &str) -> bool {
+        self(field_name, requested)
+    }
+}
+
+/// Matches field names case-insensitively (ASCII only).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseInsensitive;
+
+impl FieldNameMatcher for CaseInsensitive {
+    fn matches(&self, field_name: &str, requested: &str) -> bool {
+        field_name.eq_ignore_ascii_case(requested)
+    }
+}
+
+/// Matches field names after stripping a prefix from them
+/// (e.g. `m_` in `m_Flags`).
+#[derive(Debug, Clone, Copy)]
+pub struct StripPrefix<'a>(pub &'a str);
+
+impl FieldNameMatcher for StripPrefix<'_> {
+    fn matches(&self, field_name: &str, requested: &str) -> bool {
+        field_name.strip_prefix(self.0) == Some(requested)
+    }
+}
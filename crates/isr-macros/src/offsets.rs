@@ -1,3 +1,5 @@
+use isr_core::{types::Type, Profile};
+
 use crate::Error;
 
 /// A field within a structure.
@@ -16,6 +18,47 @@ pub struct Field {
     pub size: u64,
 }
 
+impl Field {
+    /// Returns the address of the containing structure, given the address
+    /// of this field (the `container_of` pattern).
+    ///
+    /// Fails with [`Error::ContainerUnderflow`] if the address is lower
+    /// than the offset of the field.
+    pub fn container_of(&self, member_address: u64) -> Result<u64, Error> {
+        member_address
+            .checked_sub(self.offset)
+            .ok_or(Error::ContainerUnderflow {
+                member_address,
+                offset: self.offset,
+            })
+    }
+
+    /// Resolves a field of a profile struct.
+    ///
+    /// Returns `None` if the size of the field type can't be determined.
+    pub fn from_core(profile: &Profile, field: &isr_core::types::Field) -> Option<Self> {
+        Some(Self {
+            offset: field.offset,
+            size: profile.type_size(&field.type_)?,
+        })
+    }
+
+    /// Returns the end of the field (its offset plus its size), in bytes.
+    ///
+    /// Saturates at `u64::MAX` instead of overflowing.
+    pub const fn end(&self) -> u64 {
+        self.offset.saturating_add(self.size)
+    }
+
+    /// Checks whether the field lies within a structure of the given size.
+    pub fn fits_within(&self, struct_len: usize) -> bool {
+        match self.offset.checked_add(self.size) {
+            Some(end) => end <= struct_len as u64,
+            None => false,
+        }
+    }
+}
+
 /// A bitfield within a structure.
 ///
 /// `Bitfield` provides information about the offset, size, bit position, and
@@ -37,6 +80,72 @@ pub struct Bitfield {
 }
 
 impl Bitfield {
+    /// Resolves a bitfield of a profile struct.
+    ///
+    /// Returns `None` if the field is not a bitfield or if the size of its
+    /// storage unit can't be determined.
+    pub fn from_core(profile: &Profile, field: &isr_core::types::Field) -> Option<Self> {
+        match &field.type_ {
+            Type::Bitfield(bitfield) => Some(Self {
+                offset: field.offset,
+                size: profile.type_size(&field.type_)?,
+                bit_position: bitfield.bit_position,
+                bit_length: bitfield.bit_length,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Creates a bitfield from its absolute bit offset from the beginning
+    /// of the structure.
+    ///
+    /// The bitfield is placed into the naturally aligned storage unit of
+    /// `storage_size` bytes that contains its first bit. Returns `None` if
+    /// the storage size or the bit length is zero, or if the bitfield
+    /// doesn't fit into the storage unit (e.g. bit position 31 with length 2
+    /// in a 4-byte unit).
+    pub fn from_absolute(offset_bits: u64, bit_length: u64, storage_size: u64) -> Option<Self> {
+        if storage_size == 0 || bit_length == 0 {
+            return None;
+        }
+
+        let storage_bits = storage_size.checked_mul(8)?;
+        let offset = offset_bits / storage_bits * storage_size;
+        let bit_position = offset_bits % storage_bits;
+
+        if bit_position + bit_length > storage_bits {
+            return None;
+        }
+
+        Some(Self {
+            offset,
+            size: storage_size,
+            bit_position,
+            bit_length,
+        })
+    }
+
+    /// Returns the end of the underlying field containing the bitfield
+    /// (its offset plus its size), in bytes.
+    ///
+    /// Saturates at `u64::MAX` instead of overflowing.
+    pub const fn end(&self) -> u64 {
+        self.offset.saturating_add(self.size)
+    }
+
+    /// Returns the offset of the first bit of the bitfield from the
+    /// beginning of the structure, in bits.
+    pub fn absolute_bit_offset(&self) -> u64 {
+        self.offset * 8 + self.bit_position
+    }
+
+    /// Checks whether the bitfield contains the bit at the given absolute
+    /// bit offset from the beginning of the structure.
+    pub fn contains_bit(&self, abs_bit: u64) -> bool {
+        let start = self.absolute_bit_offset();
+        abs_bit >= start && abs_bit - start < self.bit_length
+    }
+
     /// Extracts the bitfield value from a given integer.
     ///
     /// This method performs bitwise operations to isolate and return the
@@ -63,6 +172,17 @@ pub enum FieldDescriptor {
 }
 
 impl FieldDescriptor {
+    /// Resolves a field of a profile struct into a [`Field`] or a
+    /// [`Bitfield`].
+    ///
+    /// Returns `None` if the size of the field type can't be determined.
+    pub fn from_core(profile: &Profile, field: &isr_core::types::Field) -> Option<Self> {
+        match &field.type_ {
+            Type::Bitfield(_) => Bitfield::from_core(profile, field).map(Self::Bitfield),
+            _ => Field::from_core(profile, field).map(Self::Field),
+        }
+    }
+
     /// Returns the offset of the field or bitfield, in bytes.
     pub fn offset(&self) -> u64 {
         match self {
@@ -78,6 +198,15 @@ impl FieldDescriptor {
             FieldDescriptor::Bitfield(bitfield) => bitfield.size,
         }
     }
+
+    /// Returns the end of the field or bitfield (its offset plus its
+    /// size), in bytes.
+    pub fn end(&self) -> u64 {
+        match self {
+            FieldDescriptor::Field(field) => field.end(),
+            FieldDescriptor::Bitfield(bitfield) => bitfield.end(),
+        }
+    }
 }
 
 impl TryFrom<FieldDescriptor> for u64 {
@@ -115,6 +244,28 @@ impl TryFrom<FieldDescriptor> for Bitfield {
     }
 }
 
+/// Returns the end of a resolved field (its offset plus its size), or zero
+/// if the field wasn't resolved.
+///
+/// Fails with [`Error::Conversion`] if the end overflows.
+pub fn field_end(descriptor: &Result<FieldDescriptor, Error>) -> Result<u64, Error> {
+    match descriptor {
+        Ok(descriptor) => descriptor
+            .offset()
+            .checked_add(descriptor.size())
+            .ok_or(Error::Conversion("field end overflows u64")),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Converts a structure length to `usize`.
+///
+/// Fails with [`Error::Conversion`] if the length doesn't fit (e.g. on
+/// 32-bit targets).
+pub fn struct_len(len: u64) -> Result<usize, Error> {
+    usize::try_from(len).map_err(|_| Error::Conversion("structure length exceeds usize"))
+}
+
 //
 //
 //
@@ -273,27 +424,131 @@ impl IntoField<Option<Bitfield>> for Result<FieldDescriptor, Error> {
 ///   - `#[isr(alias = "alternative_name")]`
 ///   - `#[isr(alias = ["name1", "name2", ...])]`
 ///
+/// - `#[isr(matcher = <matcher>)]`: Matches a field whose name differs from
+///   the requested one by a rule rather than a fixed list, e.g.
+///   `#[isr(matcher = CaseInsensitive)]`. An exact match is preferred. See
+///   [`FieldNameMatcher`].
+///
+/// - `#[isr(arch = <arch>)]`: On the outer structure, makes `new` fail with
+///   [`Error::ArchitectureMismatch`] if the profile targets another
+///   architecture (see [`Profile::expect_architecture`]).
+///
+///   `<arch>` can be a single literal or an array of literals, e.g.:
+///   - `#[isr(arch = "Amd64")]`
+///   - `#[isr(arch = ["Amd64", "Arm64"])]`
+///
+/// - `#[isr(relaxed)]`: On the outer structure, structures (and their
+///   aliases) that aren't found by their exact name are looked up with
+///   leading underscores and ASCII case ignored, e.g. `EPROCESS` finds
+///   `_EPROCESS` (see [`Profile::find_struct_relaxed`]). `new` fails with
+///   [`Error::AmbiguousType`] if more than one structure matches.
+///
+/// The `arch` and `relaxed` attributes must precede the other attributes
+/// of the outer structure.
+///
 /// The generated struct provides a `new` method that takes a reference to
 /// a [`Profile`] and returns a [`Result`] containing the populated struct or
-/// an error if any fields or structures are not found.
+/// an error if any fields or structures are not found. It also implements
+/// [`FromProfile`], so it can be stored in a `static` [`IsrLazy`].
+///
+/// `new` fails with [`Error::Conversion`] if the end of a field overflows
+/// `u64`, or if the length or the effective length of a structure doesn't
+/// fit into `usize` (e.g. a corrupted profile on a 32-bit target).
+///
+/// Fields whose type has zero size (e.g. a forward-declared struct) fail
+/// with [`Error::ZeroSizedField`], or resolve to `None` if declared as
+/// optional. Empty structures (zero size, no fields) can still be declared
+/// without fields.
 ///
 /// Each inner struct also implements the following convenience methods:
 /// - `is_empty()`: Returns `true` if the structure has zero size.
 /// - `len()`: Returns the size of the structure in bytes.
 /// - `effective_len()`: Returns the offset of the last defined field plus its size.
+/// - `exceeds_len()`: Returns `true` if a defined field extends past the size
+///   of the structure.
 ///
 /// [`Profile`]: isr_core::Profile
+/// [`FromProfile`]: crate::FromProfile
+/// [`IsrLazy`]: crate::IsrLazy
+/// [`FieldNameMatcher`]: crate::FieldNameMatcher
+/// [`Error::ZeroSizedField`]: crate::Error::ZeroSizedField
+/// [`Error::Conversion`]: crate::Error::Conversion
+/// [`Error::ArchitectureMismatch`]: crate::Error::ArchitectureMismatch
+/// [`Profile::expect_architecture`]: isr_core::Profile::expect_architecture
+/// [`Profile::find_struct_relaxed`]: isr_core::Profile::find_struct_relaxed
+/// [`Error::AmbiguousType`]: crate::Error::AmbiguousType
 #[macro_export]
 macro_rules! offsets {
+    (
+        #[isr($($oattr:tt)*)]
+        $($rest:tt)*
+    ) => {
+        $crate::offsets!(@options
+            [],
+            [],
+            #[isr($($oattr)*)]
+            $($rest)*
+        );
+    };
+
     (
         $(#[$meta:meta])*
         $vis:vis struct $name:ident {
             $($rest:tt)*
         }
+    ) => {
+        $crate::offsets!(@options
+            [],
+            [],
+            $(#[$meta])*
+            $vis struct $name {
+                $($rest)*
+            }
+        );
+    };
+
+    //
+    // @options
+    //
+
+    (@options
+        [$($arch:tt)?],
+        [$($relaxed:ident)?],
+        #[isr(arch = $new_arch:tt)]
+        $($rest:tt)*
+    ) => {
+        $crate::offsets!(@options
+            [$new_arch],
+            [$($relaxed)?],
+            $($rest)*
+        );
+    };
+
+    (@options
+        [$($arch:tt)?],
+        [$($relaxed:ident)?],
+        #[isr(relaxed)]
+        $($rest:tt)*
+    ) => {
+        $crate::offsets!(@options
+            [$($arch)?],
+            [relaxed],
+            $($rest)*
+        );
+    };
+
+    (@options
+        [$($arch:tt)?],
+        [$($relaxed:ident)?],
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($rest:tt)*
+        }
     ) => {
         $crate::offsets!(@outer
             $vis,
             [ $(#[$meta])* ],
+            [$($arch)?],
             struct $name {
                 $($rest)*
             }
@@ -302,6 +557,7 @@ macro_rules! offsets {
         $crate::offsets!(@inner
             $vis,
             [ $(#[$meta])* ],
+            [$($relaxed)?],
             $($rest)*
         );
     };
@@ -309,6 +565,7 @@ macro_rules! offsets {
     (@outer
         $vis:vis,
         [$($meta:tt)*],
+        [$($arch:tt)?],
         struct $name:ident {
             $(
                 $(#[isr($($iattr:tt)*)])?
@@ -332,6 +589,8 @@ macro_rules! offsets {
         impl $name {
             /// Creates a new offsets instance.
             $vis fn new(profile: &$crate::__private::Profile) -> Result<Self, $crate::Error> {
+                $crate::offsets!(@arch profile, [$($arch)?]);
+
                 Ok(Self {
                     $(
                         $iname: $iname::new(profile)?,
@@ -339,11 +598,20 @@ macro_rules! offsets {
                 })
             }
         }
+
+        impl $crate::FromProfile for $name {
+            fn from_profile(
+                profile: &$crate::__private::Profile,
+            ) -> Result<Self, $crate::Error> {
+                Self::new(profile)
+            }
+        }
     };
 
     (@inner
         $vis:vis,
         [$($meta:tt)*],
+        [$($relaxed:ident)?],
         $(#[isr($($iattr:tt)*)])?
         struct $iname:ident {
             $(
@@ -369,11 +637,19 @@ macro_rules! offsets {
             $vis fn new(profile: &$crate::__private::Profile) -> Result<Self, $crate::Error> {
                 use $crate::__private::IntoField as _;
 
-                let name = $crate::offsets!(@find
+                let name = match $crate::offsets!(@find
                     profile,
                     $iname,
                     [$($($iattr)*)?]
-                ).ok_or($crate::Error::type_not_found(stringify!($iname)))?;
+                ) {
+                    Some(name) => name,
+                    None => $crate::offsets!(@relaxed
+                        profile,
+                        $iname,
+                        [$($relaxed)?],
+                        [$($($iattr)*)?]
+                    )?,
+                };
 
                 let len = profile
                     .struct_size(name)
@@ -383,15 +659,12 @@ macro_rules! offsets {
                 $(
                     effective_len = u64::max(
                         effective_len,
-                        match $crate::offsets!(@assign
+                        $crate::__private::field_end(&$crate::offsets!(@assign
                             profile,
                             name,
                             $fname,
                             [$($($fattr)*)?]
-                        ) {
-                            Ok(descriptor) => descriptor.size() + descriptor.offset(),
-                            Err(_) => 0,
-                        }
+                        ))?,
                     );
                 )*
 
@@ -404,8 +677,8 @@ macro_rules! offsets {
                             [$($($fattr)*)?]
                         ).into_field()?,
                     )*
-                    __len: len as usize,
-                    __effective_len: effective_len as usize,
+                    __len: $crate::__private::struct_len(len)?,
+                    __effective_len: $crate::__private::struct_len(effective_len)?,
                 })
             }
 
@@ -425,11 +698,21 @@ macro_rules! offsets {
             $vis fn effective_len(&self) -> usize {
                 self.__effective_len
             }
+
+            /// Returns `true` if a defined field extends past the size of
+            /// the structure.
+            ///
+            /// This happens with variable-length trailing arrays or with
+            /// inconsistent debug information.
+            $vis fn exceeds_len(&self) -> bool {
+                self.__effective_len > self.__len
+            }
         }
 
         $crate::offsets!(@inner
             $vis,
             [$($meta)*],
+            [$($relaxed)?],
             $($rest)*
         );
     };
@@ -437,8 +720,32 @@ macro_rules! offsets {
     (@inner
         $vis:vis,
         [$($meta:tt)*],
+        [$($relaxed:ident)?],
+    ) => {};
+
+    //
+    // @arch
+    //
+
+    (@arch
+        $profile:ident,
+        []
     ) => {};
 
+    (@arch
+        $profile:ident,
+        [$arch:literal]
+    ) => {
+        $profile.expect_architecture(&[$arch])?;
+    };
+
+    (@arch
+        $profile:ident,
+        [[$($arch:literal),+ $(,)?]]
+    ) => {
+        $profile.expect_architecture(&[$($arch),+])?;
+    };
+
     //
     // @find
     //
@@ -483,6 +790,61 @@ macro_rules! offsets {
             )+
     }};
 
+    //
+    // @relaxed
+    //
+
+    (@relaxed
+        $profile:ident,
+        $iname:ident,
+        [],
+        [$($iattr:tt)*]
+    ) => {{
+        Err::<&str, _>($crate::Error::type_not_found(stringify!($iname)))
+    }};
+
+    (@relaxed
+        $profile:ident,
+        $iname:ident,
+        [relaxed],
+        []
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile.find_struct_name_relaxed(
+            &[stringify!($iname)],
+            $crate::__private::Relaxation::ALL,
+        )
+    }};
+
+    (@relaxed
+        $profile:ident,
+        $iname:ident,
+        [relaxed],
+        [alias = $alias:literal]
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile.find_struct_name_relaxed(
+            &[stringify!($iname), $alias],
+            $crate::__private::Relaxation::ALL,
+        )
+    }};
+
+    (@relaxed
+        $profile:ident,
+        $iname:ident,
+        [relaxed],
+        [alias = [$($alias:literal),+ $(,)?]]
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile.find_struct_name_relaxed(
+            &[stringify!($iname), $($alias),+],
+            $crate::__private::Relaxation::ALL,
+        )
+    }};
+
     //
     // @assign
     //
@@ -530,4 +892,16 @@ macro_rules! offsets {
                 )
             )+
     }};
+
+    (@assign
+        $profile:ident,
+        $iname:ident,
+        $fname:ident,
+        [matcher = $matcher:expr]
+    ) => {{
+        use $crate::__private::ProfileExt as _;
+
+        $profile
+            .find_field_descriptor_with($iname, stringify!($fname), &$matcher)
+    }};
 }
@@ -1,77 +1,67 @@
-use isr_core::{types::Type, Profile};
+use isr_core::{types::Type, Profile, Relaxation};
 
-use crate::{offsets::FieldDescriptor, symbols::SymbolDescriptor, Bitfield, Error, Field};
+use crate::{
+    offsets::FieldDescriptor, symbols::SymbolDescriptor, Bitfield, Error, Field, FieldNameMatcher,
+};
 
 pub trait ProfileExt {
     fn find_field(&self, type_name: &str, field_name: &str) -> Option<Field>;
     fn find_bitfield(&self, type_name: &str, field_name: &str) -> Option<Bitfield>;
+    fn find_field_type(&self, type_name: &str, field_name: &str) -> Option<&Type<'_>>;
+    fn field_extends_struct(&self, type_name: &str, field_name: &str) -> Option<bool>;
     fn find_symbol_descriptor(&self, symbol_name: &str) -> Result<SymbolDescriptor, Error>;
     fn find_field_descriptor(
         &self,
         type_name: &str,
         field_name: &str,
     ) -> Result<FieldDescriptor, Error>;
+    fn find_field_descriptor_with(
+        &self,
+        type_name: &str,
+        field_name: &str,
+        matcher: &dyn FieldNameMatcher,
+    ) -> Result<FieldDescriptor, Error>;
+    fn find_matching_field_name(
+        &self,
+        type_name: &str,
+        field_name: &str,
+        matcher: &dyn FieldNameMatcher,
+    ) -> Option<&str>;
+    fn find_variant_value(&self, type_name: &str, variant_name: &str) -> Result<u64, Error>;
+    fn find_struct_name_relaxed(
+        &self,
+        type_names: &[&str],
+        relaxation: Relaxation,
+    ) -> Result<&str, Error>;
 }
 
 impl ProfileExt for Profile<'_> {
     fn find_field(&self, type_name: &str, field_name: &str) -> Option<Field> {
-        let udt = self.find_struct(type_name)?;
-
-        if let Some(field) = udt.fields.get(field_name) {
-            return Some(Field {
-                offset: field.offset,
-                size: self.type_size(&field.type_)?,
-            });
-        }
-
-        for field in udt.fields.values() {
-            let udt = match &field.type_ {
-                Type::Struct(udt) => udt,
-                _ => continue,
-            };
-
-            if let Some(child) = self.find_field(&udt.name, field_name) {
-                return Some(Field {
-                    offset: field.offset + child.offset,
-                    size: child.size,
-                });
-            }
-        }
+        let nested = self.find_nested_field(type_name, field_name)?;
 
-        None
+        Some(Field {
+            offset: nested.offset,
+            ..Field::from_core(self, nested.field)?
+        })
     }
 
     fn find_bitfield(&self, type_name: &str, field_name: &str) -> Option<Bitfield> {
-        let udt = self.find_struct(type_name)?;
-
-        if let Some(field) = udt.fields.get(field_name) {
-            if let Type::Bitfield(bitfield) = &field.type_ {
-                return Some(Bitfield {
-                    offset: field.offset,
-                    size: self.type_size(&field.type_)?,
-                    bit_position: bitfield.bit_position,
-                    bit_length: bitfield.bit_length,
-                });
-            }
-        }
+        let nested = self.find_nested_field(type_name, field_name)?;
 
-        for field in udt.fields.values() {
-            let udt = match &field.type_ {
-                Type::Struct(udt) => udt,
-                _ => continue,
-            };
-
-            if let Some(child) = self.find_bitfield(&udt.name, field_name) {
-                return Some(Bitfield {
-                    offset: field.offset + child.offset,
-                    size: child.size,
-                    bit_position: child.bit_position,
-                    bit_length: child.bit_length,
-                });
-            };
-        }
+        Some(Bitfield {
+            offset: nested.offset,
+            ..Bitfield::from_core(self, nested.field)?
+        })
+    }
 
-        None
+    fn find_field_type(&self, type_name: &str, field_name: &str) -> Option<&Type<'_>> {
+        Some(&self.find_nested_field(type_name, field_name)?.field.type_)
+    }
+
+    fn field_extends_struct(&self, type_name: &str, field_name: &str) -> Option<bool> {
+        let len = self.struct_size(type_name)?;
+        let field = self.find_field(type_name, field_name)?;
+        Some(!field.fits_within(usize::try_from(len).ok()?))
     }
 
     fn find_symbol_descriptor(&self, symbol_name: &str) -> Result<SymbolDescriptor, Error> {
@@ -86,58 +76,83 @@ impl ProfileExt for Profile<'_> {
         type_name: &str,
         field_name: &str,
     ) -> Result<FieldDescriptor, Error> {
-        let udt = match self.find_struct(type_name) {
-            Some(udt) => udt,
+        if self.find_struct(type_name).is_none() {
+            return Err(Error::type_not_found(type_name));
+        }
+
+        let nested = match self.find_nested_field(type_name, field_name) {
+            Some(nested) => nested,
+            None => return Err(Error::field_not_found(type_name, field_name)),
+        };
+
+        match FieldDescriptor::from_core(self, nested.field) {
+            Some(descriptor) if descriptor.size() == 0 => {
+                Err(Error::zero_sized_field(type_name, field_name))
+            }
+            Some(FieldDescriptor::Field(field)) => Ok(FieldDescriptor::Field(Field {
+                offset: nested.offset,
+                ..field
+            })),
+            Some(FieldDescriptor::Bitfield(bitfield)) => Ok(FieldDescriptor::Bitfield(Bitfield {
+                offset: nested.offset,
+                ..bitfield
+            })),
+            None => Err(Error::field_not_found(type_name, field_name)),
+        }
+    }
+
+    fn find_field_descriptor_with(
+        &self,
+        type_name: &str,
+        field_name: &str,
+        matcher: &dyn FieldNameMatcher,
+    ) -> Result<FieldDescriptor, Error> {
+        match self.find_field_descriptor(type_name, field_name) {
+            Err(Error::FieldNotFound { .. }) => (),
+            result => return result,
+        }
+
+        match self.find_matching_field_name(type_name, field_name, matcher) {
+            Some(name) => self.find_field_descriptor(type_name, name),
+            None => Err(Error::field_not_found(type_name, field_name)),
+        }
+    }
+
+    fn find_matching_field_name(
+        &self,
+        type_name: &str,
+        field_name: &str,
+        matcher: &dyn FieldNameMatcher,
+    ) -> Option<&str> {
+        self.find_nested_field_matching(type_name, |name| matcher.matches(name, field_name))
+            .map(|nested| nested.name)
+    }
+
+    fn find_variant_value(&self, type_name: &str, variant_name: &str) -> Result<u64, Error> {
+        let enum_ = match self.find_enum(type_name) {
+            Some(enum_) => enum_,
             None => return Err(Error::type_not_found(type_name)),
         };
 
-        if let Some(field) = udt.fields.get(field_name) {
-            return Ok(match &field.type_ {
-                Type::Bitfield(bitfield) => FieldDescriptor::Bitfield(Bitfield {
-                    offset: field.offset,
-                    size: match self.type_size(&field.type_) {
-                        Some(size) => size,
-                        None => {
-                            return Err(Error::field_not_found(type_name, field_name));
-                        }
-                    },
-                    bit_position: bitfield.bit_position,
-                    bit_length: bitfield.bit_length,
-                }),
-                _ => FieldDescriptor::Field(Field {
-                    offset: field.offset,
-                    size: match self.type_size(&field.type_) {
-                        Some(size) => size,
-                        None => {
-                            return Err(Error::field_not_found(type_name, field_name));
-                        }
-                    },
-                }),
-            });
+        match enum_.fields.get(variant_name) {
+            Some(variant) => Ok(variant.as_u64()),
+            None => Err(Error::variant_not_found(type_name, variant_name)),
         }
+    }
 
-        for field in udt.fields.values() {
-            let udt = match &field.type_ {
-                Type::Struct(udt) => udt,
-                _ => continue,
-            };
-
-            if let Ok(child) = self.find_field_descriptor(&udt.name, field_name) {
-                return Ok(match child {
-                    FieldDescriptor::Field(child) => FieldDescriptor::Field(Field {
-                        offset: field.offset + child.offset,
-                        size: child.size,
-                    }),
-                    FieldDescriptor::Bitfield(child) => FieldDescriptor::Bitfield(Bitfield {
-                        offset: field.offset + child.offset,
-                        size: child.size,
-                        bit_position: child.bit_position,
-                        bit_length: child.bit_length,
-                    }),
-                });
+    fn find_struct_name_relaxed(
+        &self,
+        type_names: &[&str],
+        relaxation: Relaxation,
+    ) -> Result<&str, Error> {
+        for type_name in type_names {
+            if let Some(found) = self.find_struct_relaxed(type_name, relaxation)? {
+                return Ok(found.name);
             }
         }
 
-        Err(Error::field_not_found(type_name, field_name))
+        Err(Error::type_not_found(
+            type_names.first().copied().unwrap_or_default(),
+        ))
     }
 }
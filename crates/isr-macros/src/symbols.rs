@@ -110,14 +110,37 @@ impl IntoSymbol<Option<u64>> for Result<SymbolDescriptor, Error> {
 ///   - `#[isr(alias = "alternative_name")]`
 ///   - `#[isr(alias = ["name1", "name2", ...])]`
 ///
+/// - `#[isr(arch = <arch>)]`: On the structure, makes `new` fail with
+///   [`Error::ArchitectureMismatch`] if the profile targets another
+///   architecture (see [`Profile::expect_architecture`]). Must be the first
+///   attribute. `<arch>` can be a single literal or an array of literals.
+///
 /// The generated struct provides a `new` method that takes a reference to
 /// a [`Profile`] and returns a `Result` containing the populated struct or
-/// an error if any symbols are not found.
+/// an error if any symbols are not found. All symbols are looked up before
+/// failing, the error lists every missing symbol (see
+/// [`Error::symbols_not_found`]). It also implements
+/// [`FromProfile`], so it can be stored in a `static` [`IsrLazy`].
+///
+/// The symbol values are taken from the profile as-is, so they follow the
+/// profile's [`SymbolBase`] convention: relative virtual addresses for PDB
+/// profiles and absolute link-time addresses for `System.map` profiles.
+/// Use [`Profile::symbol_va`] or [`Profile::symbol_rva`] to get addresses
+/// independent of the convention.
 ///
 /// [`Profile`]: isr_core::Profile
+/// [`SymbolBase`]: isr_core::SymbolBase
+/// [`Profile::symbol_va`]: isr_core::Profile::symbol_va
+/// [`Profile::symbol_rva`]: isr_core::Profile::symbol_rva
+/// [`FromProfile`]: crate::FromProfile
+/// [`IsrLazy`]: crate::IsrLazy
+/// [`Error::ArchitectureMismatch`]: crate::Error::ArchitectureMismatch
+/// [`Error::symbols_not_found`]: crate::Error::symbols_not_found
+/// [`Profile::expect_architecture`]: isr_core::Profile::expect_architecture
 #[macro_export]
 macro_rules! symbols {
-    (
+    (@struct
+        [$($arch:tt)?]
         $(#[$symbols_attrs:meta])*
         $vis:vis struct $name:ident {
             $(
@@ -134,20 +157,94 @@ macro_rules! symbols {
 
         impl $name {
             /// Creates a new symbol instance.
+            #[allow(non_snake_case)]
             $vis fn new(profile: &$crate::__private::Profile) -> Result<Self, $crate::Error> {
                 use $crate::__private::IntoSymbol as _;
 
-                Ok(Self {
-                    $(
-                        $fname: $crate::symbols!(@assign
-                            profile,
-                            $fname,
-                            [$($($isr_attr)*)?]
-                        ).into_symbol()?,
-                    )*
-                })
+                $crate::symbols!(@arch profile, [$($arch)?]);
+
+                // Look up all symbols before failing, so that the error
+                // lists every missing symbol.
+                let mut missing = Vec::new();
+                $(
+                    let $fname = match $crate::symbols!(@assign
+                        profile,
+                        $fname,
+                        [$($($isr_attr)*)?]
+                    ).into_symbol() {
+                        Ok(value) => Some(value),
+                        Err($crate::Error::SymbolNotFound(symbol_name)) => {
+                            missing.push(symbol_name);
+                            None
+                        }
+                        Err(err) => return Err(err),
+                    };
+                )*
+
+                match ($($fname,)*) {
+                    ($(Some($fname),)*) => Ok(Self { $($fname),* }),
+                    _ => Err($crate::Error::symbols_not_found(missing)),
+                }
             }
         }
+
+        impl $crate::FromProfile for $name {
+            fn from_profile(
+                profile: &$crate::__private::Profile,
+            ) -> Result<Self, $crate::Error> {
+                Self::new(profile)
+            }
+        }
+    };
+
+    (
+        #[isr(arch = $arch:tt)]
+        $(#[$symbols_attrs:meta])*
+        $vis:vis struct $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::symbols!(@struct
+            [$arch]
+            $(#[$symbols_attrs])*
+            $vis struct $name {
+                $($body)*
+            }
+        );
+    };
+
+    (
+        $(#[$symbols_attrs:meta])*
+        $vis:vis struct $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::symbols!(@struct
+            []
+            $(#[$symbols_attrs])*
+            $vis struct $name {
+                $($body)*
+            }
+        );
+    };
+
+    (@arch
+        $profile:ident,
+        []
+    ) => {};
+
+    (@arch
+        $profile:ident,
+        [$arch:literal]
+    ) => {
+        $profile.expect_architecture(&[$arch])?;
+    };
+
+    (@arch
+        $profile:ident,
+        [[$($arch:literal),+ $(,)?]]
+    ) => {
+        $profile.expect_architecture(&[$($arch),+])?;
     };
 
     (@assign
@@ -0,0 +1,178 @@
+//! Building blocks shared by the Windows and Linux helpers.
+
+use std::collections::HashSet;
+
+use crate::Field;
+
+/// Default maximum number of entries visited by the iterators.
+pub const DEFAULT_MAX_ENTRIES: usize = 0x10000;
+
+/// Virtual memory of the inspected system.
+pub trait Memory {
+    /// Error returned by failed reads.
+    type Error: std::error::Error + 'static;
+
+    /// Reads `buffer.len()` bytes from the given virtual address.
+    fn read(&self, address: u64, buffer: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error<E>
+where
+    E: std::error::Error + 'static,
+{
+    #[error("Failed to read memory at {address:#x}")]
+    Read {
+        address: u64,
+        #[source]
+        source: E,
+    },
+
+    #[error(transparent)]
+    Field(#[from] crate::Error),
+
+    #[error("Unsupported field size {0}")]
+    UnsupportedSize(u64),
+
+    #[error("Null link at {0:#x}")]
+    NullLink(u64),
+
+    #[error("Loop at {0:#x}")]
+    Loop(u64),
+
+    #[error("More than {0} entries")]
+    TooManyEntries(usize),
+
+    #[error("Invalid string length {length} (maximum length {maximum_length})")]
+    InvalidStringLength { length: u64, maximum_length: u64 },
+}
+
+/// Iterator over a circular doubly-linked list.
+///
+/// Follows the forward links from the list head and returns the addresses
+/// of the containing structures. Iteration stops at the list head, or
+/// after the first error. Lists that loop without returning to the head
+/// and lists longer than the maximum number of entries are reported as
+/// errors.
+pub struct ListIter<'m, M>
+where
+    M: Memory,
+{
+    memory: &'m M,
+    head: u64,
+    next: Field,
+    member: Field,
+    max_entries: usize,
+    current: Option<u64>,
+    visited: HashSet<u64>,
+}
+
+impl<'m, M> ListIter<'m, M>
+where
+    M: Memory,
+{
+    /// Creates an iterator over the list with the given head.
+    ///
+    /// `next` is the forward link of the list node, its size is the
+    /// pointer size. `member` is the list node field of the containing
+    /// structure.
+    pub fn new(memory: &'m M, head: u64, next: Field, member: Field) -> Self {
+        Self {
+            memory,
+            head,
+            next,
+            member,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            current: Some(head),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Sets the maximum number of entries.
+    ///
+    /// Defaults to [`DEFAULT_MAX_ENTRIES`].
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            ..self
+        }
+    }
+
+    fn next_entry(&mut self, entry: u64) -> Result<Option<u64>, Error<M::Error>> {
+        let next = read_pointer(self.memory, entry.wrapping_add(self.next.offset), self.next)?;
+
+        if next == 0 {
+            return Err(Error::NullLink(entry));
+        }
+
+        if next == self.head {
+            return Ok(None);
+        }
+
+        if self.visited.len() >= self.max_entries {
+            return Err(Error::TooManyEntries(self.max_entries));
+        }
+
+        if !self.visited.insert(next) {
+            return Err(Error::Loop(next));
+        }
+
+        Ok(Some(next))
+    }
+}
+
+impl<M> Iterator for ListIter<'_, M>
+where
+    M: Memory,
+{
+    type Item = Result<u64, Error<M::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.current.take()?;
+
+        match self.next_entry(entry) {
+            Ok(Some(next)) => match self.member.container_of(next) {
+                Ok(container) => {
+                    self.current = Some(next);
+                    Some(Ok(container))
+                }
+                Err(err) => Some(Err(err.into())),
+            },
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Reads a pointer, whose size is given by the field.
+pub(crate) fn read_pointer<M>(
+    memory: &M,
+    address: u64,
+    field: Field,
+) -> Result<u64, Error<M::Error>>
+where
+    M: Memory,
+{
+    match field.size {
+        4 | 8 => read_uint(memory, address, field),
+        size => Err(Error::UnsupportedSize(size)),
+    }
+}
+
+/// Reads a little-endian unsigned integer of the size of the field.
+pub(crate) fn read_uint<M>(memory: &M, address: u64, field: Field) -> Result<u64, Error<M::Error>>
+where
+    M: Memory,
+{
+    let mut buffer = [0u8; 8];
+    let size = match usize::try_from(field.size) {
+        Ok(size @ 1..=8) => size,
+        _ => return Err(Error::UnsupportedSize(field.size)),
+    };
+
+    memory
+        .read(address, &mut buffer[..size])
+        .map_err(|source| Error::Read { address, source })?;
+
+    Ok(u64::from_le_bytes(buffer))
+}
@@ -0,0 +1,125 @@
+//! Helpers for walking Windows kernel objects.
+//!
+//! The helpers take fields resolved by the [`offsets!`] macro, not a
+//! profile, and read the memory through the [`Memory`] trait, so they're
+//! independent of any VMI backend.
+//!
+//! [`offsets!`]: crate::offsets
+
+pub use crate::walk::{Error, ListIter, Memory, DEFAULT_MAX_ENTRIES};
+use crate::{
+    walk::{read_pointer, read_uint},
+    Bitfield, Field,
+};
+
+/// Iterator over the entries of a `_LIST_ENTRY` chain.
+///
+/// In [`ListIter::new`], `next` is the `Flink` field of `_LIST_ENTRY` and
+/// `member` is the `_LIST_ENTRY` field of the containing structure (e.g.
+/// `ActiveProcessLinks` of `_EPROCESS`).
+pub type ListEntryIter<'m, M> = ListIter<'m, M>;
+
+/// Reader of `_UNICODE_STRING` structures.
+#[derive(Debug, Clone, Copy)]
+pub struct UnicodeStringReader {
+    /// The `Length` field, in bytes.
+    pub length: Field,
+
+    /// The `MaximumLength` field, in bytes.
+    pub maximum_length: Field,
+
+    /// The `Buffer` field, its size is the pointer size.
+    pub buffer: Field,
+}
+
+impl UnicodeStringReader {
+    /// Creates a reader from the fields of `_UNICODE_STRING`.
+    pub fn new(length: Field, maximum_length: Field, buffer: Field) -> Self {
+        Self {
+            length,
+            maximum_length,
+            buffer,
+        }
+    }
+
+    /// Reads the string at the given address.
+    ///
+    /// Fails if `Length` is odd or exceeds `MaximumLength`. Invalid UTF-16
+    /// is replaced with `U+FFFD`.
+    pub fn read<M>(&self, memory: &M, address: u64) -> Result<String, Error<M::Error>>
+    where
+        M: Memory,
+    {
+        let length = read_uint(
+            memory,
+            address.wrapping_add(self.length.offset),
+            self.length,
+        )?;
+        let maximum_length = read_uint(
+            memory,
+            address.wrapping_add(self.maximum_length.offset),
+            self.maximum_length,
+        )?;
+
+        if length % 2 != 0 || length > maximum_length {
+            return Err(Error::InvalidStringLength {
+                length,
+                maximum_length,
+            });
+        }
+
+        if length == 0 {
+            return Ok(String::new());
+        }
+
+        let buffer = read_pointer(
+            memory,
+            address.wrapping_add(self.buffer.offset),
+            self.buffer,
+        )?;
+        let mut bytes = vec![0; length as usize];
+        memory
+            .read(buffer, &mut bytes)
+            .map_err(|source| Error::Read {
+                address: buffer,
+                source,
+            })?;
+
+        let units = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]));
+
+        Ok(char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect())
+    }
+}
+
+/// Decoder of `_EX_FAST_REF` values, which store a reference count in the
+/// low bits of an object pointer.
+#[derive(Debug, Clone, Copy)]
+pub struct ExFastRef {
+    /// The `RefCnt` bitfield.
+    pub ref_cnt: Bitfield,
+}
+
+impl ExFastRef {
+    /// Creates a decoder from the `RefCnt` bitfield of `_EX_FAST_REF`.
+    pub fn new(ref_cnt: Bitfield) -> Self {
+        Self { ref_cnt }
+    }
+
+    /// Returns the object pointer, with the reference count bits cleared.
+    pub fn object_pointer(&self, value: u64) -> u64 {
+        let mask = match 1u64.checked_shl(self.ref_cnt.bit_length as u32) {
+            Some(bit) => bit - 1,
+            None => u64::MAX,
+        };
+        value & !(mask << self.ref_cnt.bit_position)
+    }
+
+    /// Returns the reference count.
+    pub fn ref_count(&self, value: u64) -> u64 {
+        self.ref_cnt.value_from(value)
+    }
+}
@@ -0,0 +1,73 @@
+//! Measures [`isr_pdb::create_profile`] on a synthetic PDB with wide
+//! structs and enums.
+
+#[path = "../tests/common/pdb.rs"]
+#[allow(dead_code)]
+mod pdb;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use self::pdb::{Pdb, Types, T_ULONG, T_UQUAD};
+
+/// Number of structs and of enums in the PDB.
+const TYPES: usize = 2_000;
+
+/// Number of fields of each struct and of variants of each enum.
+const FIELDS: usize = 64;
+
+fn pdb() -> Pdb {
+    let names = (0..FIELDS)
+        .map(|index| format!("Field{index}"))
+        .collect::<Vec<_>>();
+
+    let fields = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.as_str(), T_UQUAD, index as u64 * 8))
+        .collect::<Vec<_>>();
+
+    let variants = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.as_str(), index as i64))
+        .collect::<Vec<_>>();
+
+    let mut types = Types::default();
+    for index in 0..TYPES {
+        types.structure(&format!("_STRUCT{index}"), FIELDS as u64 * 8, &fields);
+        types.enumeration(&format!("_ENUM{index}"), T_ULONG, &variants);
+    }
+
+    Pdb {
+        types,
+        ..Default::default()
+    }
+}
+
+fn create_profile(c: &mut Criterion) {
+    let pdb = pdb();
+
+    let mut group = c.benchmark_group("create_profile");
+    group.sample_size(20);
+
+    group.bench_function("wide_types", |b| {
+        b.iter_batched(
+            || pdb.file(),
+            |file| {
+                isr_pdb::create_profile(file, |profile| {
+                    black_box(profile);
+                    Ok::<_, std::io::Error>(())
+                })
+                .unwrap()
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, create_profile);
+criterion_main!(benches);
@@ -6,6 +6,9 @@ pub enum Error {
     #[error(transparent)]
     Pdb(#[from] pdb::Error),
 
+    #[error("Lossy conversion in strict mode: {0}")]
+    Strict(isr_core::__private::Warning),
+
     #[error("Serialization error: {0}")]
-    Serialize(Box<dyn std::error::Error>),
+    Serialize(Box<dyn std::error::Error + Send + Sync>),
 }
@@ -0,0 +1,97 @@
+use std::fs::File;
+
+use isr_core::{parse_pdb_guid, pdb_identifier};
+use pdb::{PDBInformation, Source, PDB};
+
+use super::Error;
+
+/// Identification of a PDB file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdbInfo {
+    /// GUID of the PDB file, as 32 hex digits.
+    pub guid: String,
+
+    /// Age of the PDB file, incremented on every incremental link.
+    pub age: u32,
+
+    /// Signature of the PDB file, usually the timestamp of its creation.
+    pub signature: u32,
+}
+
+impl PdbInfo {
+    /// Returns the GUID followed by the age, as used by symbol servers
+    /// (see [`pdb_identifier`]).
+    ///
+    /// Returns `None` if the GUID isn't made of 32 hex digits.
+    pub fn identifier(&self) -> Option<String> {
+        Some(pdb_identifier(&parse_pdb_guid(&self.guid)?, self.age))
+    }
+}
+
+/// Returns the GUID of a PDB file as used by symbol servers.
+///
+/// The GUID is formatted as 32 hex digits followed by the age, the same
+/// way as in the CodeView information of the matching executable (see
+/// [`pdb_identifier`]).
+pub fn pdb_guid(pdb_file: File) -> Result<String, Error> {
+    let mut pdb = PDB::open(pdb_file)?;
+    let (info, age) = information(&mut pdb)?;
+    Ok(pdb_identifier(&info.guid.to_bytes_le(), age))
+}
+
+/// Returns the identification of a PDB file.
+pub fn pdb_info(pdb_file: File) -> Result<PdbInfo, Error> {
+    let mut pdb = PDB::open(pdb_file)?;
+    let (info, age) = information(&mut pdb)?;
+
+    Ok(PdbInfo {
+        guid: info.guid.simple().to_string(),
+        age,
+        signature: info.signature,
+    })
+}
+
+/// Returns the PDB information stream and the age of a PDB file.
+///
+/// The age of the debug information stream takes precedence, it is the
+/// one stored in the CodeView information of the matching executable.
+fn information<'s, S>(pdb: &mut PDB<'s, S>) -> Result<(PDBInformation<'s>, u32), Error>
+where
+    S: Source<'s> + 's,
+{
+    let info = pdb.pdb_information()?;
+    let age = match pdb.debug_information()?.age() {
+        Some(age) => age,
+        None => info.age,
+    };
+
+    Ok((info, age))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier() {
+        let mut info = PdbInfo {
+            guid: String::from("3844dbb920174967be7aa4a2c20430fa"),
+            age: 2,
+            signature: 0,
+        };
+
+        assert_eq!(
+            info.identifier().as_deref(),
+            Some("3844dbb920174967be7aa4a2c20430fa2")
+        );
+
+        info.age = 0x12;
+        assert_eq!(
+            info.identifier().as_deref(),
+            Some("3844dbb920174967be7aa4a2c20430fa12")
+        );
+
+        info.guid.pop();
+        assert_eq!(info.identifier(), None);
+    }
+}
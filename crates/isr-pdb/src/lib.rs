@@ -1,8 +1,17 @@
 //! PDB file format parser.
+//!
+//! Profiles can be created concurrently on multiple threads.
 
 mod error;
+mod guid;
 mod profile;
 mod symbols;
 mod types;
 
-pub use self::{error::Error, profile::create_profile};
+#[cfg(feature = "unstable")]
+pub use self::profile::create_profile_with_report;
+pub use self::{
+    error::Error,
+    guid::{pdb_guid, pdb_info, PdbInfo},
+    profile::{create_profile, create_profile_with_options},
+};
@@ -1,15 +1,61 @@
-use std::fs::File;
+use std::{borrow::Cow, fs::File};
 
-use isr_core::{types::Types, Profile, Symbols};
-use pdb::PDB;
+use indexmap::IndexMap;
+use isr_core::{
+    __private::{ProfileReport, SkippedType},
+    types::Types,
+    Profile, ProfileOptions, ProfileSource, SymbolBase, SymbolScope, Symbols,
+};
+use pdb::{AddressMap, DebugInformation, FallibleIterator as _, PDB};
 
 use super::{symbols::PdbSymbols as _, types::PdbTypes as _, Error};
 
 pub fn create_profile<F, E>(pdb_file: File, serialize: F) -> Result<(), Error>
 where
     F: FnOnce(&Profile) -> Result<(), E>,
-    E: std::error::Error + 'static,
+    E: std::error::Error + Send + Sync + 'static,
 {
+    create_profile_with_options(pdb_file, &ProfileOptions::default(), serialize)
+}
+
+pub fn create_profile_with_options<F, E>(
+    pdb_file: File,
+    options: &ProfileOptions,
+    serialize: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    create_profile_inner(pdb_file, options, serialize)?;
+    Ok(())
+}
+
+isr_core::unstable_api! {
+    /// Creates a profile and returns a summary of the profile creation.
+    pub fn create_profile_with_report<F, E>(
+        pdb_file: File,
+        options: &ProfileOptions,
+        serialize: F,
+    ) -> Result<ProfileReport, Error>
+    where
+        F: FnOnce(&Profile) -> Result<(), E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        create_profile_inner(pdb_file, options, serialize)
+    }
+}
+
+fn create_profile_inner<F, E>(
+    pdb_file: File,
+    options: &ProfileOptions,
+    serialize: F,
+) -> Result<ProfileReport, Error>
+where
+    F: FnOnce(&Profile) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = options.run_span("pdb").entered();
     let mut pdb = PDB::open(pdb_file)?;
 
     tracing::debug!("collecting debug information");
@@ -18,18 +64,120 @@ where
     tracing::debug!("architecture: {architecture}");
 
     tracing::debug!("collecting symbols");
+    let mut report = ProfileReport::default();
     let address_map = pdb.address_map()?;
     let symbol_table = pdb.global_symbols()?;
-    let symbols = Symbols::parse(address_map, symbol_table.iter())?;
+    let mut symbols = match options.symbol_scope {
+        // Only public symbols are collected, so `All` and `Exported`
+        // are currently the same.
+        SymbolScope::All | SymbolScope::Exported => {
+            let mut symbols = Symbols::parse(
+                &address_map,
+                symbol_table.iter(),
+                &mut report,
+                options.strictness,
+            )?;
+
+            // Some (e.g. trimmed) PDBs have no public symbols in the global
+            // symbol stream, but still have them in the module streams.
+            if symbols.0.is_empty() {
+                tracing::debug!("no global symbols, collecting symbols from modules");
+                symbols = module_symbols(&mut pdb, &dbi, &address_map, &mut report, options)?;
+            }
+
+            symbols
+        }
+        SymbolScope::None => Symbols(IndexMap::new()),
+    };
+    tracing::info!(
+        scope = ?options.symbol_scope,
+        count = symbols.0.len(),
+        "collected symbols"
+    );
 
     tracing::debug!("collecting types");
-    let tpi = pdb.type_information()?;
-    let types = Types::parse(tpi.finder(), tpi.iter())?;
+    let tpi = match pdb.type_information() {
+        Ok(tpi) => Some(tpi),
+        Err(err) if options.partial_on_error => {
+            tracing::warn!(%err, "failed to read type information; skipping all types");
+            report.skipped_types.push(SkippedType {
+                index: None,
+                reason: err.to_string(),
+            });
+            None
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut types = match &tpi {
+        Some(tpi) => Types::parse(tpi.finder(), tpi.iter(), options, &mut report)?,
+        None => Types::default(),
+    };
+
+    if report.has_duplicates() {
+        tracing::warn!(
+            duplicates = report.duplicates(),
+            "types defined more than once; the profile may be less reliable"
+        );
+    }
+
+    if report.is_partial() {
+        tracing::warn!(
+            skipped = report.skipped_types.len(),
+            "type records skipped; the profile is incomplete"
+        );
+    }
+
+    report
+        .check_strictness(options.strictness)
+        .map_err(Error::Strict)?;
+
+    let truncated = options.apply_max_name_length(&mut symbols, &mut types);
+    if truncated > 0 {
+        tracing::info!(truncated, "truncated long names");
+    }
 
     tracing::debug!("writing profile");
-    let profile = Profile::new(architecture, symbols, types);
+    let profile = Profile::new(architecture, symbols, types)
+        .with_source(ProfileSource::Pdb)
+        .with_symbol_base(SymbolBase::Rva)
+        .with_preferred_base(options.preferred_base)
+        .with_symbol_scope(options.symbol_scope);
 
     serialize(&profile).map_err(|err| Error::Serialize(err.into()))?;
 
-    Ok(())
+    Ok(report)
+}
+
+/// Collects the public symbols from the symbol streams of all modules.
+///
+/// If a symbol is found in more than one module, the first one is kept.
+fn module_symbols(
+    pdb: &mut PDB<File>,
+    dbi: &DebugInformation,
+    address_map: &AddressMap,
+    report: &mut ProfileReport,
+    options: &ProfileOptions,
+) -> Result<Symbols<'static>, Error> {
+    let mut result = IndexMap::new();
+
+    let mut modules = dbi.modules()?;
+    while let Some(module) = modules.next()? {
+        let module_info = match pdb.module_info(&module)? {
+            Some(module_info) => module_info,
+            None => continue,
+        };
+
+        let symbols = Symbols::parse(
+            address_map,
+            module_info.symbols()?,
+            report,
+            options.strictness,
+        )?;
+        for (name, rva) in symbols.0 {
+            result.entry(Cow::Owned(name.into_owned())).or_insert(rva);
+        }
+    }
+
+    Ok(Symbols(result))
 }
@@ -1,48 +1,65 @@
+use std::borrow::Cow;
+
 use indexmap::IndexMap;
-use isr_core::Symbols;
+use isr_core::{__private::ProfileReport, unique_name, Strictness, Symbols};
 use pdb::{AddressMap, Error, FallibleIterator, SymbolData, SymbolIter};
 
 pub trait PdbSymbols<'p> {
     fn parse<'s>(
-        address_map: AddressMap<'s>,
+        address_map: &AddressMap<'s>,
         symbol_iter: SymbolIter<'p>,
+        report: &mut ProfileReport,
+        strictness: Strictness,
     ) -> Result<Symbols<'p>, Error>;
 }
 
 impl<'p> PdbSymbols<'p> for Symbols<'p> {
     fn parse<'s>(
-        address_map: AddressMap<'s>,
+        address_map: &AddressMap<'s>,
         symbol_iter: SymbolIter<'p>,
+        report: &mut ProfileReport,
+        strictness: Strictness,
     ) -> Result<Symbols<'p>, Error> {
         let mut result = IndexMap::new();
 
         let mut symbol_iter = symbol_iter;
         while let Some(symbol) = symbol_iter.next()? {
             if let SymbolData::Public(data) = symbol.parse()? {
-                let name = match std::str::from_utf8(data.name.as_bytes()) {
-                    Ok(name) => name,
-                    Err(_) => {
-                        tracing::warn!(
-                            name = %data.name,
-                            "failed to convert symbol name to UTF-8"
-                        );
-                        continue;
+                let name = match String::from_utf8_lossy(data.name.as_bytes()) {
+                    Cow::Borrowed(name) => Cow::Borrowed(name),
+                    Cow::Owned(name) => {
+                        // The lossy form might collide with another name.
+                        let name = unique_name(&result, Cow::Owned(name));
+                        report.warn(strictness, &*name, "symbol name is not valid UTF-8");
+                        name
                     }
                 };
 
-                let rva = match data.offset.to_rva(&address_map) {
+                // If the PDB has an OMAP, the offset refers to the original
+                // layout and is translated to the optimized one.
+                let rva = match data.offset.to_rva(address_map) {
                     Some(rva) => rva,
-                    None => {
-                        tracing::warn!(
+                    None if data.offset.to_internal_rva(address_map).is_some() => {
+                        // The OMAP has no mapping for the symbol, its code was
+                        // removed from the optimized image.
+                        tracing::debug!(
                             name = %name,
                             rva = ?data.offset,
-                            "failed to convert offset to RVA"
+                            "symbol eliminated by OMAP"
+                        );
+                        continue;
+                    }
+                    None => {
+                        report.warn(
+                            strictness,
+                            &*name,
+                            format!("failed to convert offset {:?} to RVA", data.offset),
                         );
                         continue;
                     }
                 };
 
-                result.insert(name.into(), u32::from(rva).into());
+                result.insert(name, u32::from(rva).into());
             }
         }
 
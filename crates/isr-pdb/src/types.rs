@@ -1,14 +1,49 @@
 use std::borrow::Cow;
 
-use isr_core::types::{
-    ArrayRef, BaseRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Struct, StructKind,
-    StructRef, Type, Types, Variant,
+use indexmap::{map::Entry, IndexMap};
+use isr_core::{
+    __private::{ProfileReport, SkippedType},
+    types::{
+        ArrayRef, BaseRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Struct, StructKind,
+        StructRef, Type, Types, Variant,
+    },
+    unique_name, ProfileOptions, ResolutionPolicy, Strictness,
 };
 use pdb::{
     ClassKind, ClassType, EnumerationType, Error, ItemFinder, ItemIter, PrimitiveKind, RawString,
     TypeData, TypeFinder, TypeIndex, UnionType,
 };
 
+/// State shared by the parsing of the type records.
+pub struct ParseContext<'r> {
+    /// Report the lossy conversions are recorded in.
+    report: &'r mut ProfileReport,
+
+    /// How lossy conversions are handled.
+    strictness: Strictness,
+
+    /// Which definition is kept when names collide.
+    policy: ResolutionPolicy,
+
+    /// Remaining depth of nested types.
+    depth: usize,
+}
+
+impl ParseContext<'_> {
+    /// Records a lossy conversion of the type `name`.
+    fn warn(&mut self, name: impl Into<String>, message: impl Into<String>) {
+        self.report.warn(self.strictness, name, message);
+    }
+
+    /// Calls `f` one level of nesting deeper.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.depth -= 1;
+        let result = f(self);
+        self.depth += 1;
+        result
+    }
+}
+
 fn type_name(name: RawString, index: TypeIndex) -> Cow<'_, str> {
     let name = String::from_utf8_lossy(name.as_bytes());
 
@@ -29,13 +64,24 @@ where
     fn parse(
         type_finder: ItemFinder<'p, TypeIndex>,
         type_iter: ItemIter<'p, TypeIndex>,
+        options: &ProfileOptions,
+        report: &mut ProfileReport,
     ) -> Result<Self, Error>;
 
+    fn add_type(
+        &mut self,
+        type_finder: &TypeFinder<'p>,
+        typ: &pdb::Type<'p>,
+        forward_declarations: Option<&mut Vec<(Cow<'p, str>, StructKind)>>,
+        context: &mut ParseContext<'_>,
+    ) -> Result<(), Error>;
+
     fn add_enum(
         &mut self,
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         enumeration: EnumerationType<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 
     fn add_class(
@@ -43,6 +89,7 @@ where
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         class: ClassType<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 
     fn add_union(
@@ -50,6 +97,7 @@ where
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         union: UnionType<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 }
 
@@ -58,9 +106,16 @@ trait PdbEnum<'p> {
         &mut self,
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 
-    fn add_field(&mut self, type_finder: &TypeFinder<'p>, field: &TypeData<'p>);
+    fn add_field(
+        &mut self,
+        type_finder: &TypeFinder<'p>,
+        type_index: TypeIndex,
+        field: &TypeData<'p>,
+        context: &mut ParseContext<'_>,
+    );
 }
 
 trait PdbStruct<'p> {
@@ -68,6 +123,7 @@ trait PdbStruct<'p> {
         &mut self,
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 
     fn add_field(
@@ -75,6 +131,7 @@ trait PdbStruct<'p> {
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         field: &TypeData<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error>;
 }
 
@@ -82,7 +139,11 @@ trait PdbType<'p>
 where
     Self: Sized,
 {
-    fn new(type_finder: &TypeFinder<'p>, type_index: TypeIndex) -> Result<Self, Error>;
+    fn new(
+        type_finder: &TypeFinder<'p>,
+        type_index: TypeIndex,
+        context: &mut ParseContext<'_>,
+    ) -> Result<Self, Error>;
 }
 
 /*
@@ -119,6 +180,8 @@ impl<'p> PdbTypes<'p> for Types<'p> {
     fn parse(
         type_finder: ItemFinder<'p, TypeIndex>,
         type_iter: ItemIter<'p, TypeIndex>,
+        options: &ProfileOptions,
+        report: &mut ProfileReport,
     ) -> Result<Self, Error> {
         use pdb::FallibleIterator as _;
 
@@ -126,57 +189,150 @@ impl<'p> PdbTypes<'p> for Types<'p> {
         let mut type_iter = type_iter;
 
         let mut result = Self::default();
+        let mut forward_declarations = Vec::new();
 
-        while let Some(typ) = type_iter.next()? {
-            // keep building the index
-            type_finder.update(&type_iter);
+        let mut context = ParseContext {
+            report,
+            strictness: options.strictness,
 
-            match typ.parse()? {
-                TypeData::Enumeration(enumeration)
-                    if !enumeration.properties.forward_reference() =>
-                {
-                    result.add_enum(&type_finder, typ.index(), enumeration)?;
-                }
+            // Later definitions replace the earlier ones by default.
+            policy: options
+                .resolution_policy
+                .unwrap_or(ResolutionPolicy::LastSeen),
+
+            depth: options.max_type_depth(),
+        };
 
-                TypeData::Class(class) if !class.properties.forward_reference() => {
-                    result.add_class(&type_finder, typ.index(), class)?;
+        loop {
+            let typ = match type_iter.next() {
+                Ok(Some(typ)) => typ,
+                Ok(None) => break,
+                Err(err) if options.partial_on_error => {
+                    // The position of the next record is unknown.
+                    tracing::warn!(%err, "failed to read type record; skipping remaining types");
+                    context.report.skipped_types.push(SkippedType {
+                        index: None,
+                        reason: err.to_string(),
+                    });
+                    break;
                 }
+                Err(err) => return Err(err),
+            };
 
-                TypeData::Union(union) if !union.properties.forward_reference() => {
-                    result.add_union(&type_finder, typ.index(), union)?;
+            // keep building the index
+            type_finder.update(&type_iter);
+
+            let forward_declarations = match options.forward_declaration_stubs {
+                true => Some(&mut forward_declarations),
+                false => None,
+            };
+
+            match result.add_type(&type_finder, &typ, forward_declarations, &mut context) {
+                Ok(()) => (),
+                Err(err) if options.partial_on_error => {
+                    let index = u32::from(typ.index());
+                    tracing::warn!(index, %err, "failed to parse type record; skipping");
+                    context.report.skipped_types.push(SkippedType {
+                        index: Some(index),
+                        reason: err.to_string(),
+                    });
                 }
+                Err(err) => return Err(err),
+            }
+        }
 
-                _ => (), // ignore everything else
+        // Types defined later in the stream are already added, so only
+        // the types without any definition become stubs.
+        let mut stubs = 0;
+        for (name, kind) in forward_declarations {
+            if !name.starts_with("__anonymous_") && result.add_stub(name, kind) {
+                stubs += 1;
             }
         }
 
+        if stubs > 0 {
+            tracing::info!(stubs, "added forward declaration stubs");
+        }
+
         Ok(result)
     }
 
+    fn add_type(
+        &mut self,
+        type_finder: &TypeFinder<'p>,
+        typ: &pdb::Type<'p>,
+        forward_declarations: Option<&mut Vec<(Cow<'p, str>, StructKind)>>,
+        context: &mut ParseContext<'_>,
+    ) -> Result<(), Error> {
+        match (typ.parse()?, forward_declarations) {
+            (TypeData::Enumeration(enumeration), _)
+                if !enumeration.properties.forward_reference() =>
+            {
+                self.add_enum(type_finder, typ.index(), enumeration, context)?;
+            }
+
+            (TypeData::Class(class), _) if !class.properties.forward_reference() => {
+                self.add_class(type_finder, typ.index(), class, context)?;
+            }
+
+            (TypeData::Union(union), _) if !union.properties.forward_reference() => {
+                self.add_union(type_finder, typ.index(), union, context)?;
+            }
+
+            (TypeData::Class(class), Some(forward_declarations)) => {
+                let kind = match class.kind {
+                    ClassKind::Struct => StructKind::Struct,
+                    ClassKind::Class => StructKind::Class,
+                    ClassKind::Interface => StructKind::Interface,
+                };
+
+                forward_declarations.push((type_name(class.name, typ.index()), kind));
+            }
+
+            (TypeData::Union(union), Some(forward_declarations)) => {
+                forward_declarations.push((type_name(union.name, typ.index()), StructKind::Union));
+            }
+
+            _ => (), // ignore everything else
+        }
+
+        Ok(())
+    }
+
     fn add_enum(
         &mut self,
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         enumeration: EnumerationType<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         let name = type_name(enumeration.name, type_index);
 
-        let mut new_enum = Enum {
-            subtype: Type::new(type_finder, enumeration.underlying_type)?,
-            fields: Default::default(),
-        };
-
-        new_enum.add_fields(type_finder, enumeration.fields)?;
+        let mut new_enum = Enum::new(
+            Type::new(type_finder, enumeration.underlying_type, context)?,
+            IndexMap::with_capacity(enumeration.count.into()),
+        );
 
-        let new_enum_fields = new_enum.fields.len();
+        new_enum.add_fields(type_finder, enumeration.fields, context)?;
 
-        if let Some(previous_udt) = self.enums.insert(name.clone(), new_enum) {
-            tracing::warn!(
-                %name,
-                new_enum_fields,
-                previous_enum_fields = previous_udt.fields.len(),
-                "duplicate enum name; overwriting"
-            );
+        match self.enums.entry(name.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(new_enum);
+            }
+            Entry::Occupied(mut entry) => {
+                context.report.duplicate_enums.push(name.to_string());
+
+                if context.policy.replaces_enum(&new_enum, entry.get()) {
+                    tracing::warn!(
+                        %name,
+                        new_enum_fields = new_enum.fields.len(),
+                        previous_enum_fields = entry.get().fields.len(),
+                        "duplicate enum name; overwriting"
+                    );
+
+                    entry.insert(new_enum);
+                }
+            }
         }
 
         Ok(())
@@ -187,6 +343,7 @@ impl<'p> PdbTypes<'p> for Types<'p> {
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         class: ClassType<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         let name = type_name(class.name, type_index);
 
@@ -197,24 +354,15 @@ impl<'p> PdbTypes<'p> for Types<'p> {
                 ClassKind::Interface => StructKind::Interface,
             },
             size: class.size,
-            fields: Default::default(),
+            fields: IndexMap::with_capacity(class.count.into()),
+            incomplete: false,
         };
 
         if let Some(fields) = class.fields {
-            new_udt.add_fields(type_finder, fields)?;
-        }
-
-        let new_udt_fields = new_udt.fields.len();
-
-        if let Some(previous_udt) = self.structs.insert(name.clone(), new_udt) {
-            tracing::warn!(
-                %name,
-                new_udt_fields,
-                previous_udt_fields = previous_udt.fields.len(),
-                "duplicate UDT name; overwriting"
-            );
+            new_udt.add_fields(type_finder, fields, context)?;
         }
 
+        insert_struct(self, name, new_udt, context);
         Ok(())
     }
 
@@ -223,29 +371,53 @@ impl<'p> PdbTypes<'p> for Types<'p> {
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         union: UnionType<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         let name = type_name(union.name, type_index);
 
         let mut new_udt = Struct {
             kind: StructKind::Union,
             size: union.size,
-            fields: Default::default(),
+            fields: IndexMap::with_capacity(union.count.into()),
+            incomplete: false,
         };
 
-        new_udt.add_fields(type_finder, union.fields)?;
+        new_udt.add_fields(type_finder, union.fields, context)?;
 
-        let new_udt_fields = new_udt.fields.len();
+        insert_struct(self, name, new_udt, context);
+        Ok(())
+    }
+}
 
-        if let Some(previous_udt) = self.structs.insert(name.clone(), new_udt) {
-            tracing::warn!(
-                %name,
-                new_udt_fields,
-                previous_udt_fields = previous_udt.fields.len(),
-                "duplicate UDT name; overwriting"
-            );
+/// Adds a struct, resolving a conflict with a previous definition of the
+/// same name according to the policy.
+fn insert_struct<'p>(
+    types: &mut Types<'p>,
+    name: Cow<'p, str>,
+    new_udt: Struct<'p>,
+    context: &mut ParseContext<'_>,
+) {
+    match types.structs.entry(name) {
+        Entry::Vacant(entry) => {
+            entry.insert(new_udt);
         }
+        Entry::Occupied(mut entry) => {
+            context
+                .report
+                .duplicate_structs
+                .push(entry.key().to_string());
 
-        Ok(())
+            if context.policy.replaces_struct(&new_udt, entry.get()) {
+                tracing::warn!(
+                    name = %entry.key(),
+                    new_udt_fields = new_udt.fields.len(),
+                    previous_udt_fields = entry.get().fields.len(),
+                    "duplicate UDT name; overwriting"
+                );
+
+                entry.insert(new_udt);
+            }
+        }
     }
 }
 
@@ -254,23 +426,23 @@ impl<'p> PdbEnum<'p> for Enum<'p> {
         &mut self,
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         match type_finder.find(type_index)?.parse()? {
             TypeData::FieldList(data) => {
                 for field in &data.fields {
-                    self.add_field(type_finder, field);
+                    self.add_field(type_finder, type_index, field, context);
                 }
 
                 if let Some(continuation) = data.continuation {
-                    self.add_fields(type_finder, continuation)?;
+                    self.add_fields(type_finder, continuation, context)?;
                 }
             }
 
             type_data => {
-                tracing::warn!(
-                    ?type_index,
-                    ?type_data,
-                    "unexpected type (expected FieldList)"
+                context.warn(
+                    format!("type {type_index}"),
+                    format!("unexpected type (expected FieldList): {type_data:?}"),
                 );
             }
         }
@@ -278,22 +450,33 @@ impl<'p> PdbEnum<'p> for Enum<'p> {
         Ok(())
     }
 
-    fn add_field(&mut self, _type_finder: &TypeFinder<'p>, field: &TypeData<'p>) {
+    fn add_field(
+        &mut self,
+        _type_finder: &TypeFinder<'p>,
+        type_index: TypeIndex,
+        field: &TypeData<'p>,
+        context: &mut ParseContext<'_>,
+    ) {
         match field {
             TypeData::Enumerate(data) => {
-                let name = match std::str::from_utf8(data.name.as_bytes()) {
-                    Ok(name) => name,
-                    Err(_) => {
-                        tracing::warn!(name = %data.name, "failed to convert field name to UTF-8");
-                        return;
+                let name = match String::from_utf8_lossy(data.name.as_bytes()) {
+                    Cow::Borrowed(name) => Cow::Borrowed(name),
+                    Cow::Owned(name) => {
+                        // The lossy form might collide with another name.
+                        let name = unique_name(&self.fields, Cow::Owned(name));
+                        context.warn(&*name, "field name is not valid UTF-8");
+                        name
                     }
                 };
 
-                self.fields.insert(name.into(), convert_variant(data.value));
+                self.fields.insert(name, convert_variant(data.value));
             }
 
             type_data => {
-                tracing::warn!(?type_data, "unexpected type (expected Enumerate)");
+                context.warn(
+                    format!("type {type_index}"),
+                    format!("unexpected type (expected Enumerate): {type_data:?}"),
+                );
             }
         }
     }
@@ -304,23 +487,23 @@ impl<'p> PdbStruct<'p> for Struct<'p> {
         &mut self,
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         match type_finder.find(type_index)?.parse()? {
             TypeData::FieldList(data) => {
                 for field in &data.fields {
-                    self.add_field(type_finder, type_index, field)?;
+                    self.add_field(type_finder, type_index, field, context)?;
                 }
 
                 if let Some(continuation) = data.continuation {
-                    self.add_fields(type_finder, continuation)?;
+                    self.add_fields(type_finder, continuation, context)?;
                 }
             }
 
             type_data => {
-                tracing::warn!(
-                    ?type_index,
-                    ?type_data,
-                    "unexpected type (expected FieldList)"
+                context.warn(
+                    format!("type {type_index}"),
+                    format!("unexpected type (expected FieldList): {type_data:?}"),
                 );
             }
         }
@@ -333,6 +516,7 @@ impl<'p> PdbStruct<'p> for Struct<'p> {
         type_finder: &TypeFinder<'p>,
         type_index: TypeIndex,
         field: &TypeData<'p>,
+        context: &mut ParseContext<'_>,
     ) -> Result<(), Error> {
         match field {
             TypeData::Member(data) => {
@@ -340,13 +524,16 @@ impl<'p> PdbStruct<'p> for Struct<'p> {
                     type_name(data.name, type_index),
                     Field {
                         offset: data.offset,
-                        type_: Type::new(type_finder, data.field_type)?,
+                        type_: Type::new(type_finder, data.field_type, context)?,
                     },
                 );
             }
 
             type_data => {
-                tracing::warn!(?type_data, "unexpected type (expected Member)");
+                context.warn(
+                    format!("type {type_index}"),
+                    format!("unexpected type (expected Member): {type_data:?}"),
+                );
             }
         }
 
@@ -355,13 +542,26 @@ impl<'p> PdbStruct<'p> for Struct<'p> {
 }
 
 impl<'p> PdbType<'p> for Type<'p> {
-    fn new(type_finder: &TypeFinder<'p>, type_index: TypeIndex) -> Result<Self, Error> {
+    fn new(
+        type_finder: &TypeFinder<'p>,
+        type_index: TypeIndex,
+        context: &mut ParseContext<'_>,
+    ) -> Result<Self, Error> {
+        // Guards against cyclic or too deeply nested type chains.
+        if context.depth == 0 {
+            context.warn(
+                format!("type {type_index}"),
+                "type nesting exceeds the maximum depth, treated as void",
+            );
+            return Ok(Self::Base(BaseRef::Void));
+        }
+
         let result = match type_finder.find(type_index)?.parse()? {
             TypeData::Primitive(data) => match data.indirection {
                 Some(_indirection) => Self::Pointer(PointerRef {
-                    subtype: Box::new(from_primitive_kind(data.kind)),
+                    subtype: Box::new(from_primitive_kind(data.kind, context)),
                 }),
-                None => from_primitive_kind(data.kind),
+                None => from_primitive_kind(data.kind, context),
             },
 
             TypeData::Enumeration(data) => Self::Enum(EnumRef {
@@ -377,27 +577,40 @@ impl<'p> PdbType<'p> for Type<'p> {
             }),
 
             TypeData::Array(data) => Self::Array(ArrayRef {
-                subtype: Box::new(Self::new(type_finder, data.element_type)?),
+                subtype: Box::new(
+                    context.nested(|context| Self::new(type_finder, data.element_type, context))?,
+                ),
                 dims: data.dimensions.iter().map(|dim| *dim as u64).collect(),
                 size: data.dimensions.into_iter().product::<u32>() as u64,
             }),
 
             TypeData::Pointer(data) => Self::Pointer(PointerRef {
-                subtype: Box::new(Self::new(type_finder, data.underlying_type)?),
+                subtype: Box::new(
+                    context
+                        .nested(|context| Self::new(type_finder, data.underlying_type, context))?,
+                ),
             }),
 
             TypeData::Bitfield(data) => Self::Bitfield(BitfieldRef {
                 bit_length: data.length as u64,
                 bit_position: data.position as u64,
-                subtype: Box::new(Self::new(type_finder, data.underlying_type)?),
+                subtype: Box::new(
+                    context
+                        .nested(|context| Self::new(type_finder, data.underlying_type, context))?,
+                ),
             }),
 
             TypeData::Procedure(_) => Self::Function,
 
-            TypeData::Modifier(data) => Self::new(type_finder, data.underlying_type)?,
+            TypeData::Modifier(data) => {
+                context.nested(|context| Self::new(type_finder, data.underlying_type, context))?
+            }
 
             type_data => {
-                tracing::error!(?type_data, "Unknown type");
+                context.warn(
+                    format!("type {type_index}"),
+                    format!("unknown type, treated as void: {type_data:?}"),
+                );
                 Self::Base(BaseRef::Void)
             }
         };
@@ -406,7 +619,7 @@ impl<'p> PdbType<'p> for Type<'p> {
     }
 }
 
-fn from_primitive_kind<'p>(kind: PrimitiveKind) -> Type<'p> {
+fn from_primitive_kind<'p>(kind: PrimitiveKind, context: &mut ParseContext<'_>) -> Type<'p> {
     Type::Base(match kind {
         PrimitiveKind::Void => BaseRef::Void,
 
@@ -430,7 +643,7 @@ fn from_primitive_kind<'p>(kind: PrimitiveKind) -> Type<'p> {
         PrimitiveKind::F64 => BaseRef::F64,
 
         _ => {
-            tracing::error!(?kind, "Unhandled primitive");
+            context.warn(format!("{kind:?}"), "unhandled primitive, treated as void");
             BaseRef::Void
         }
     })
@@ -0,0 +1,557 @@
+//! Miniature PDB files: an MSF container with the PDB, TPI and DBI
+//! streams, the public symbols and, optionally, an OMAP.
+//!
+//! Only the parts read by the profile creation are written. There are no
+//! modules, no hash tables and no IPI stream.
+
+use std::{
+    fs::File,
+    io::{Seek as _, Write as _},
+};
+
+/// Size of an MSF page.
+const PAGE_SIZE: usize = 0x200;
+
+/// First index of the TPI stream.
+pub const FIRST_TYPE_INDEX: u32 = 0x1000;
+
+/// Primitive type indexes.
+pub const T_VOID: u32 = 0x0003;
+pub const T_UCHAR: u32 = 0x0020;
+pub const T_USHORT: u32 = 0x0021;
+pub const T_ULONG: u32 = 0x0022;
+pub const T_UQUAD: u32 = 0x0023;
+pub const T_INT4: u32 = 0x0074;
+pub const T_64PVOID: u32 = 0x0603;
+
+pub const LF_POINTER: u16 = 0x1002;
+pub const LF_FIELDLIST: u16 = 0x1203;
+pub const LF_BITFIELD: u16 = 0x1205;
+pub const LF_ENUMERATE: u16 = 0x1502;
+pub const LF_ARRAY: u16 = 0x1503;
+pub const LF_STRUCTURE: u16 = 0x1505;
+pub const LF_UNION: u16 = 0x1506;
+pub const LF_ENUM: u16 = 0x1507;
+pub const LF_MEMBER: u16 = 0x150d;
+
+const LF_NUMERIC: u64 = 0x8000;
+const LF_QUADWORD: u16 = 0x8009;
+const LF_UQUADWORD: u16 = 0x800a;
+
+const S_PUB32: u16 = 0x110e;
+
+/// `fwdref` bit of the type properties.
+const FORWARD_REFERENCE: u16 = 0x0080;
+
+/// `public` member access.
+const PUBLIC: u16 = 3;
+
+/// Records of the TPI stream.
+#[derive(Default)]
+pub struct Types {
+    records: Vec<Vec<u8>>,
+}
+
+impl Types {
+    /// Appends a record and returns its type index.
+    pub fn record(&mut self, kind: u16, data: &[u8]) -> u32 {
+        let mut record = kind.to_le_bytes().to_vec();
+        record.extend_from_slice(data);
+        pad(&mut record, 2);
+
+        self.records.push(record);
+        FIRST_TYPE_INDEX + self.records.len() as u32 - 1
+    }
+
+    /// Appends a 64-bit pointer to the type.
+    pub fn pointer(&mut self, underlying: u32) -> u32 {
+        // CV_PTR_64, 8 bytes.
+        let attributes: u32 = 0x0c | (8 << 13);
+
+        let mut data = underlying.to_le_bytes().to_vec();
+        data.extend_from_slice(&attributes.to_le_bytes());
+        self.record(LF_POINTER, &data)
+    }
+
+    /// Appends a one-dimensional array of `size` bytes.
+    pub fn array(&mut self, element: u32, size: u64) -> u32 {
+        let mut data = element.to_le_bytes().to_vec();
+        data.extend_from_slice(&T_UQUAD.to_le_bytes());
+        numeric(&mut data, size);
+        data.push(0);
+        self.record(LF_ARRAY, &data)
+    }
+
+    /// Appends a bitfield of the underlying type.
+    pub fn bitfield(&mut self, underlying: u32, position: u8, length: u8) -> u32 {
+        let mut data = underlying.to_le_bytes().to_vec();
+        data.extend_from_slice(&[length, position]);
+        self.record(LF_BITFIELD, &data)
+    }
+
+    /// Appends a struct with the fields `(name, type, offset)`, preceded
+    /// by its field list.
+    pub fn structure(&mut self, name: &str, size: u64, fields: &[(&str, u32, u64)]) -> u32 {
+        let list = self.field_list(fields);
+        self.class(LF_STRUCTURE, name, size, fields.len(), Some(list))
+    }
+
+    /// Appends a union with the fields `(name, type, offset)`, preceded by
+    /// its field list.
+    pub fn union(&mut self, name: &str, size: u64, fields: &[(&str, u32, u64)]) -> u32 {
+        let list = self.field_list(fields);
+        self.class(LF_UNION, name, size, fields.len(), Some(list))
+    }
+
+    /// Appends a forward declaration of a struct.
+    pub fn forward_structure(&mut self, name: &str) -> u32 {
+        self.class(LF_STRUCTURE, name, 0, 0, None)
+    }
+
+    /// Appends an enum with the variants `(name, value)`, preceded by its
+    /// field list.
+    pub fn enumeration(&mut self, name: &str, underlying: u32, variants: &[(&str, i64)]) -> u32 {
+        let mut list = Vec::new();
+        for &(name, value) in variants {
+            list.extend_from_slice(&LF_ENUMERATE.to_le_bytes());
+            list.extend_from_slice(&PUBLIC.to_le_bytes());
+            match u64::try_from(value) {
+                Ok(value) => numeric(&mut list, value),
+                Err(_) => {
+                    list.extend_from_slice(&LF_QUADWORD.to_le_bytes());
+                    list.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            cstring(&mut list, name);
+            pad(&mut list, 0);
+        }
+        let fields = self.record(LF_FIELDLIST, &list);
+
+        let mut data = (variants.len() as u16).to_le_bytes().to_vec();
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&underlying.to_le_bytes());
+        data.extend_from_slice(&fields.to_le_bytes());
+        cstring(&mut data, name);
+        self.record(LF_ENUM, &data)
+    }
+
+    fn field_list(&mut self, fields: &[(&str, u32, u64)]) -> u32 {
+        let mut list = Vec::new();
+        for &(name, type_index, offset) in fields {
+            list.extend_from_slice(&LF_MEMBER.to_le_bytes());
+            list.extend_from_slice(&PUBLIC.to_le_bytes());
+            list.extend_from_slice(&type_index.to_le_bytes());
+            numeric(&mut list, offset);
+            cstring(&mut list, name);
+            pad(&mut list, 0);
+        }
+
+        self.record(LF_FIELDLIST, &list)
+    }
+
+    fn class(
+        &mut self,
+        kind: u16,
+        name: &str,
+        size: u64,
+        count: usize,
+        fields: Option<u32>,
+    ) -> u32 {
+        let properties = match fields {
+            Some(_) => 0,
+            None => FORWARD_REFERENCE,
+        };
+
+        let mut data = (count as u16).to_le_bytes().to_vec();
+        data.extend_from_slice(&properties.to_le_bytes());
+        data.extend_from_slice(&fields.unwrap_or(0).to_le_bytes());
+        if kind == LF_STRUCTURE {
+            // No base class and no vtable.
+            data.extend_from_slice(&[0; 8]);
+        }
+        numeric(&mut data, size);
+        cstring(&mut data, name);
+        self.record(kind, &data)
+    }
+
+    /// Returns the TPI stream.
+    fn stream(&self) -> Vec<u8> {
+        let records = self
+            .records
+            .iter()
+            .flat_map(|record| {
+                let length = record.len() as u16;
+                length
+                    .to_le_bytes()
+                    .into_iter()
+                    .chain(record.iter().copied())
+            })
+            .collect::<Vec<_>>();
+
+        let mut stream = Vec::new();
+        for value in [
+            20040203,
+            56,
+            FIRST_TYPE_INDEX,
+            FIRST_TYPE_INDEX + self.records.len() as u32,
+            records.len() as u32,
+        ] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // No hash stream.
+        stream.extend_from_slice(&0xffffu16.to_le_bytes());
+        stream.extend_from_slice(&0xffffu16.to_le_bytes());
+        stream.extend_from_slice(&4u32.to_le_bytes());
+        stream.extend_from_slice(&0x3ffffu32.to_le_bytes());
+        stream.extend_from_slice(&[0; 24]);
+
+        stream.extend_from_slice(&records);
+        stream
+    }
+}
+
+/// A section header.
+#[derive(Clone, Copy)]
+pub struct Section {
+    pub name: &'static str,
+    pub virtual_address: u32,
+    pub size: u32,
+}
+
+impl Section {
+    fn write(&self, stream: &mut Vec<u8>) {
+        let mut name = [0; 8];
+        name[..self.name.len()].copy_from_slice(self.name.as_bytes());
+        stream.extend_from_slice(&name);
+
+        for value in [self.size, self.virtual_address, self.size, 0, 0, 0] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // No relocations and no line numbers, readable code.
+        stream.extend_from_slice(&[0; 4]);
+        stream.extend_from_slice(&0x6000_0020u32.to_le_bytes());
+    }
+}
+
+/// Mapping between the original and the optimized layout of the image.
+pub struct Omap {
+    /// Sections of the original layout.
+    pub original_sections: Vec<Section>,
+
+    /// Records mapping original RVAs to optimized ones, `(source,
+    /// target)`. A zero target marks eliminated code.
+    pub from_src: Vec<(u32, u32)>,
+
+    /// Records mapping optimized RVAs to original ones.
+    pub to_src: Vec<(u32, u32)>,
+}
+
+/// A public symbol.
+pub struct Public {
+    pub name: &'static [u8],
+
+    /// Section number, starting at 1.
+    pub section: u16,
+
+    /// Offset in the section.
+    pub offset: u32,
+}
+
+/// A module (object file) with its own symbol stream.
+pub struct Module {
+    pub name: &'static str,
+
+    /// Public symbols in the symbol stream of the module.
+    pub publics: Vec<Public>,
+}
+
+/// A PDB file.
+pub struct Pdb {
+    pub machine_type: u16,
+    pub age: u32,
+    pub guid: [u8; 16],
+    pub types: Types,
+    pub sections: Vec<Section>,
+    pub publics: Vec<Public>,
+    pub omap: Option<Omap>,
+    pub modules: Vec<Module>,
+}
+
+impl Default for Pdb {
+    fn default() -> Self {
+        Self {
+            // IMAGE_FILE_MACHINE_AMD64
+            machine_type: 0x8664,
+            age: 1,
+            guid: *b"\x38\x44\xdb\xb9\x20\x17\x49\x67\xbe\x7a\xa4\xa2\xc2\x04\x30\xfa",
+            types: Types::default(),
+            sections: vec![Section {
+                name: ".text",
+                virtual_address: 0x1000,
+                size: 0x1000,
+            }],
+            publics: Vec::new(),
+            omap: None,
+            modules: Vec::new(),
+        }
+    }
+}
+
+/// Stream numbers, after the fixed PDB, TPI, DBI and IPI streams. The
+/// module streams follow.
+const SYMBOL_RECORDS_STREAM: u16 = 5;
+const SECTION_HEADERS_STREAM: u16 = 6;
+const OMAP_TO_SRC_STREAM: u16 = 7;
+const OMAP_FROM_SRC_STREAM: u16 = 8;
+const ORIGINAL_SECTION_HEADERS_STREAM: u16 = 9;
+
+impl Pdb {
+    /// Returns the content of the PDB file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut streams = vec![
+            Vec::new(),
+            self.info_stream(),
+            self.types.stream(),
+            Vec::new(),
+            Vec::new(),
+            symbol_records(&self.publics),
+            sections_stream(&self.sections),
+        ];
+
+        if let Some(omap) = &self.omap {
+            streams.push(omap_stream(&omap.to_src));
+            streams.push(omap_stream(&omap.from_src));
+            streams.push(sections_stream(&omap.original_sections));
+        }
+
+        let first_module_stream = streams.len() as u16;
+        for module in &self.modules {
+            // CV_SIGNATURE_C13, followed by the symbol records.
+            let mut stream = 4u32.to_le_bytes().to_vec();
+            stream.extend_from_slice(&symbol_records(&module.publics));
+            streams.push(stream);
+        }
+
+        // The DBI stream references the module streams.
+        streams[3] = self.dbi_stream(first_module_stream);
+        msf(&streams)
+    }
+
+    /// Returns the PDB file.
+    pub fn file(&self) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&self.to_bytes()).unwrap();
+        file.rewind().unwrap();
+        file
+    }
+
+    fn info_stream(&self) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for value in [20000404, 0x5f4e_3a2b, self.age] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+        stream.extend_from_slice(&self.guid);
+
+        // No named streams: an empty string buffer and an empty hash
+        // table.
+        for value in [0u32, 0, 1, 0, 0] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+
+        stream
+    }
+
+    fn dbi_stream(&self, first_module_stream: u16) -> Vec<u8> {
+        let none = 0xffffu16;
+
+        let mut stream = Vec::new();
+        for value in [u32::MAX, 19990903, self.age] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [none, 0, none, 0, SYMBOL_RECORDS_STREAM, 0] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let modules = self.module_list(first_module_stream);
+
+        // No section contributions, section map, files, type servers or
+        // EC info, only the modules and the optional debug header.
+        let mut debug_header = [none; 11];
+        debug_header[5] = SECTION_HEADERS_STREAM;
+        if self.omap.is_some() {
+            debug_header[3] = OMAP_TO_SRC_STREAM;
+            debug_header[4] = OMAP_FROM_SRC_STREAM;
+            debug_header[10] = ORIGINAL_SECTION_HEADERS_STREAM;
+        }
+
+        let module_list_size = modules.len() as u32;
+        let debug_header_size = 2 * debug_header.len() as u32;
+        for value in [module_list_size, 0, 0, 0, 0, 0, debug_header_size, 0] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+        stream.extend_from_slice(&0u16.to_le_bytes());
+        stream.extend_from_slice(&self.machine_type.to_le_bytes());
+        stream.extend_from_slice(&0u32.to_le_bytes());
+        stream.extend_from_slice(&modules);
+
+        for index in debug_header {
+            stream.extend_from_slice(&index.to_le_bytes());
+        }
+
+        stream
+    }
+
+    /// Returns the module info substream of the DBI stream.
+    fn module_list(&self, first_module_stream: u16) -> Vec<u8> {
+        let mut list = Vec::new();
+        for (index, module) in self.modules.iter().enumerate() {
+            let symbols_size = 4 + symbol_records(&module.publics).len() as u32;
+
+            // Opened, followed by the section contribution.
+            list.extend_from_slice(&[0; 4 + 28]);
+
+            // Flags and stream, sizes of the symbols and lines, file count
+            // and padding, file name offsets, source and PDB file names.
+            list.extend_from_slice(&0u16.to_le_bytes());
+            list.extend_from_slice(&(first_module_stream + index as u16).to_le_bytes());
+            for value in [symbols_size, 0, 0, 0, 0, 0, 0] {
+                list.extend_from_slice(&value.to_le_bytes());
+            }
+
+            // Module and object file names.
+            cstring(&mut list, module.name);
+            cstring(&mut list, module.name);
+            while list.len() % 4 != 0 {
+                list.push(0);
+            }
+        }
+
+        list
+    }
+}
+
+/// Returns the `S_PUB32` records of the public symbols.
+fn symbol_records(publics: &[Public]) -> Vec<u8> {
+    let mut stream = Vec::new();
+    for public in publics {
+        let mut record = S_PUB32.to_le_bytes().to_vec();
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&public.offset.to_le_bytes());
+        record.extend_from_slice(&public.section.to_le_bytes());
+        record.extend_from_slice(public.name);
+        record.push(0);
+        while (record.len() + 2) % 4 != 0 {
+            record.push(0);
+        }
+
+        stream.extend_from_slice(&(record.len() as u16).to_le_bytes());
+        stream.extend_from_slice(&record);
+    }
+
+    stream
+}
+
+fn sections_stream(sections: &[Section]) -> Vec<u8> {
+    let mut stream = Vec::new();
+    for section in sections {
+        section.write(&mut stream);
+    }
+    stream
+}
+
+fn omap_stream(records: &[(u32, u32)]) -> Vec<u8> {
+    records
+        .iter()
+        .flat_map(|&(source, target)| source.to_le_bytes().into_iter().chain(target.to_le_bytes()))
+        .collect()
+}
+
+/// Writes the streams into an MSF 7.00 container.
+///
+/// Page 0 is the header, page 1 the free page map. The streams follow,
+/// then the stream directory and the page listing the directory pages.
+fn msf(streams: &[Vec<u8>]) -> Vec<u8> {
+    let pages = |length: usize| length.div_ceil(PAGE_SIZE);
+
+    let mut next_page = 2;
+    let mut stream_pages = Vec::new();
+    for stream in streams {
+        let count = pages(stream.len()) as u32;
+        stream_pages.push(next_page..next_page + count);
+        next_page += count;
+    }
+
+    let mut directory = Vec::new();
+    directory.extend_from_slice(&(streams.len() as u32).to_le_bytes());
+    for stream in streams {
+        directory.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+    }
+    for page in stream_pages.iter().cloned().flatten() {
+        directory.extend_from_slice(&page.to_le_bytes());
+    }
+
+    let directory_pages = next_page..next_page + pages(directory.len()) as u32;
+    let directory_list_page = directory_pages.end;
+    assert!(directory_pages.len() * 4 <= PAGE_SIZE);
+
+    let page_count = directory_list_page + 1;
+    let mut file = vec![0; page_count as usize * PAGE_SIZE];
+
+    let magic = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+    let mut header = magic.to_vec();
+    for value in [
+        PAGE_SIZE as u32,
+        1,
+        page_count,
+        directory.len() as u32,
+        0,
+        directory_list_page,
+    ] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+    file[..header.len()].copy_from_slice(&header);
+
+    let write = |file: &mut Vec<u8>, first_page: u32, data: &[u8]| {
+        let start = first_page as usize * PAGE_SIZE;
+        file[start..start + data.len()].copy_from_slice(data);
+    };
+
+    for (stream, pages) in streams.iter().zip(&stream_pages) {
+        write(&mut file, pages.start, stream);
+    }
+    write(&mut file, directory_pages.start, &directory);
+
+    let directory_list = directory_pages
+        .flat_map(u32::to_le_bytes)
+        .collect::<Vec<_>>();
+    write(&mut file, directory_list_page, &directory_list);
+
+    file
+}
+
+/// Appends a numeric leaf.
+fn numeric(data: &mut Vec<u8>, value: u64) {
+    if value < LF_NUMERIC {
+        data.extend_from_slice(&(value as u16).to_le_bytes());
+    }
+    else {
+        data.extend_from_slice(&LF_UQUADWORD.to_le_bytes());
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn cstring(data: &mut Vec<u8>, value: &str) {
+    data.extend_from_slice(value.as_bytes());
+    data.push(0);
+}
+
+/// Pads with `LF_PAD` bytes, so that `data.len() + offset` is a multiple
+/// of 4.
+fn pad(data: &mut Vec<u8>, offset: usize) {
+    while (data.len() + offset) % 4 != 0 {
+        let remaining = 4 - (data.len() + offset) % 4;
+        data.push(0xf0 | remaining as u8);
+    }
+}
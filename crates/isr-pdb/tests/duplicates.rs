@@ -0,0 +1,58 @@
+//! Types defined more than once under the same name are reported.
+
+#![cfg(feature = "unstable")]
+
+mod common;
+
+use isr_core::ProfileOptions;
+
+use self::common::pdb::{Pdb, T_ULONG, T_UQUAD};
+
+#[test]
+fn duplicates_reported() {
+    let mut pdb = Pdb::default();
+    let types = &mut pdb.types;
+
+    types.structure(
+        "_LIST_ENTRY",
+        16,
+        &[("Flink", T_UQUAD, 0), ("Blink", T_UQUAD, 8)],
+    );
+    types.structure("_LIST_ENTRY", 8, &[("Flink", T_UQUAD, 0)]);
+    types.structure("_KPROCESS", 8, &[("Header", T_UQUAD, 0)]);
+    types.enumeration("_POOL_TYPE", T_ULONG, &[("NonPagedPool", 0)]);
+    types.enumeration(
+        "_POOL_TYPE",
+        T_ULONG,
+        &[("NonPagedPool", 0), ("PagedPool", 1)],
+    );
+
+    let report =
+        isr_pdb::create_profile_with_report(pdb.file(), &ProfileOptions::default(), |_| {
+            Ok::<_, std::io::Error>(())
+        })
+        .unwrap();
+
+    assert_eq!(report.duplicate_structs, ["_LIST_ENTRY"]);
+    assert_eq!(report.duplicate_enums, ["_POOL_TYPE"]);
+    assert_eq!(report.duplicates(), 2);
+    assert!(report.has_duplicates());
+}
+
+#[test]
+fn no_duplicates() {
+    let mut pdb = Pdb::default();
+    pdb.types.structure(
+        "_LIST_ENTRY",
+        16,
+        &[("Flink", T_UQUAD, 0), ("Blink", T_UQUAD, 8)],
+    );
+
+    let report =
+        isr_pdb::create_profile_with_report(pdb.file(), &ProfileOptions::default(), |_| {
+            Ok::<_, std::io::Error>(())
+        })
+        .unwrap();
+
+    assert!(!report.has_duplicates());
+}
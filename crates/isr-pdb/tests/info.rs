@@ -0,0 +1,19 @@
+mod common;
+
+use self::common::pdb::Pdb;
+
+#[test]
+fn pdb_info() {
+    let pdb = Pdb {
+        age: 3,
+        ..Default::default()
+    };
+
+    let info = isr_pdb::pdb_info(pdb.file()).unwrap();
+    assert_eq!(info.guid, "b9db443817206749be7aa4a2c20430fa");
+    assert_eq!(info.age, 3);
+    assert_eq!(info.signature, 0x5f4e_3a2b);
+
+    let guid = isr_pdb::pdb_guid(pdb.file()).unwrap();
+    assert_eq!(guid, "b9db443817206749be7aa4a2c20430fa3");
+}
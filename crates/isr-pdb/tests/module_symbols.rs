@@ -0,0 +1,82 @@
+//! Symbols are collected from the module streams if the global symbol
+//! stream has no public symbols.
+
+mod common;
+
+use self::common::pdb::{Module, Pdb, Public};
+
+/// Returns the RVAs of the symbols of the profile.
+fn symbols(pdb: &Pdb) -> Vec<(String, u64)> {
+    let mut result = Vec::new();
+    isr_pdb::create_profile(pdb.file(), |profile| {
+        result = profile
+            .symbols()
+            .map(|(name, &rva)| (name.to_owned(), rva))
+            .collect();
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    result.sort();
+    result
+}
+
+fn public(name: &'static [u8], offset: u32) -> Public {
+    Public {
+        name,
+        section: 1,
+        offset,
+    }
+}
+
+fn modules() -> Vec<Module> {
+    vec![
+        Module {
+            name: "ntoskrnl.obj",
+            publics: vec![
+                public(b"KeBugCheckEx", 0x10),
+                public(b"KiSystemStartup", 0x20),
+            ],
+        },
+        Module {
+            name: "pool.obj",
+            publics: vec![
+                public(b"ExAllocatePool", 0x30),
+                public(b"KeBugCheckEx", 0x40),
+            ],
+        },
+    ]
+}
+
+#[test]
+fn without_global_symbols() {
+    let pdb = Pdb {
+        modules: modules(),
+        ..Default::default()
+    };
+
+    // The first module defining a symbol wins.
+    assert_eq!(
+        symbols(&pdb),
+        [
+            (String::from("ExAllocatePool"), 0x1030),
+            (String::from("KeBugCheckEx"), 0x1010),
+            (String::from("KiSystemStartup"), 0x1020),
+        ]
+    );
+}
+
+#[test]
+fn with_global_symbols() {
+    let pdb = Pdb {
+        publics: vec![public(b"PsActiveProcessHead", 0x80)],
+        modules: modules(),
+        ..Default::default()
+    };
+
+    // The module streams aren't read.
+    assert_eq!(
+        symbols(&pdb),
+        [(String::from("PsActiveProcessHead"), 0x1080)]
+    );
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use isr_core::{ProfileOptions, Strictness};
+use isr_pdb::{create_profile_with_options, Error};
+
+use self::common::pdb::{Omap, Pdb, Public, Section};
+
+/// Creates a PDB whose `.text` section was rearranged by the linker.
+///
+/// In the original layout, `.text` starts at 0x1000. The code at
+/// 0x1000..0x1100 moved to 0x1800, the code at 0x1100..0x1200 was
+/// eliminated and the rest stayed in place.
+fn pdb(publics: Vec<Public>) -> Pdb {
+    Pdb {
+        sections: vec![Section {
+            name: ".text",
+            virtual_address: 0x1000,
+            size: 0x2000,
+        }],
+        publics,
+        omap: Some(Omap {
+            original_sections: vec![Section {
+                name: ".text",
+                virtual_address: 0x1000,
+                size: 0x1000,
+            }],
+            from_src: vec![(0x1000, 0x1800), (0x1100, 0), (0x1200, 0x1200)],
+            to_src: vec![(0x1200, 0x1200), (0x1800, 0x1000), (0x1900, 0)],
+        }),
+        ..Default::default()
+    }
+}
+
+/// Returns the symbols of the profile created from the PDB.
+fn symbols(pdb: &Pdb, options: &ProfileOptions) -> Result<Vec<(String, u64)>, Error> {
+    let mut symbols = Vec::new();
+    create_profile_with_options(pdb.file(), options, |profile| {
+        symbols = profile
+            .symbols()
+            .map(|(name, &rva)| (name.to_owned(), rva))
+            .collect();
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    Ok(symbols)
+}
+
+fn public(name: &'static [u8], section: u16, offset: u32) -> Public {
+    Public {
+        name,
+        section,
+        offset,
+    }
+}
+
+#[test]
+fn remapped_and_eliminated() {
+    let pdb = pdb(vec![
+        public(b"KiSystemCall64", 1, 0x10),
+        public(b"KiEliminated", 1, 0x150),
+        public(b"KeBugCheckEx", 1, 0x250),
+    ]);
+
+    // Eliminated symbols are dropped without a warning, even in strict
+    // mode.
+    let options = ProfileOptions::default().with_strictness(Strictness::Strict);
+    let symbols = symbols(&pdb, &options).unwrap();
+    assert_eq!(
+        symbols,
+        [
+            (String::from("KiSystemCall64"), 0x1810),
+            (String::from("KeBugCheckEx"), 0x1250),
+        ]
+    );
+}
+
+#[test]
+fn without_omap() {
+    let pdb = Pdb {
+        publics: vec![public(b"KiSystemCall64", 1, 0x10)],
+        ..Default::default()
+    };
+
+    let symbols = symbols(&pdb, &ProfileOptions::default()).unwrap();
+    assert_eq!(symbols, [(String::from("KiSystemCall64"), 0x1010)]);
+}
+
+#[test]
+fn unknown_section() {
+    // The original layout has a single section.
+    let pdb = pdb(vec![
+        public(b"KiSystemCall64", 1, 0x10),
+        public(b"KiUnknown", 2, 0x10),
+    ]);
+
+    let symbols = symbols(&pdb, &ProfileOptions::default()).unwrap();
+    assert_eq!(symbols, [(String::from("KiSystemCall64"), 0x1810)]);
+
+    // Unlike an eliminated symbol, it can't be converted at all.
+    let options = ProfileOptions::default().with_strictness(Strictness::Strict);
+    assert!(matches!(
+        self::symbols(&pdb, &options),
+        Err(Error::Strict(_))
+    ));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use isr_core::{Profile, ProfileOptions};
+use isr_pdb::{create_profile_with_options, Error};
+
+use self::common::pdb::{Pdb, LF_STRUCTURE, T_64PVOID, T_UQUAD, T_USHORT};
+
+/// Creates a PDB with two valid types around a truncated struct record,
+/// and returns it with the index of the truncated record.
+fn pdb() -> (Pdb, u32) {
+    let mut pdb = Pdb::default();
+    let types = &mut pdb.types;
+
+    let buffer = types.pointer(T_USHORT);
+    types.structure(
+        "_UNICODE_STRING",
+        16,
+        &[
+            ("Length", T_USHORT, 0),
+            ("MaximumLength", T_USHORT, 2),
+            ("Buffer", buffer, 8),
+        ],
+    );
+
+    // Only the member count of `_EPROCESS` is left.
+    let corrupted = types.record(LF_STRUCTURE, &3u16.to_le_bytes());
+
+    let ref_cnt = types.bitfield(T_UQUAD, 0, 4);
+    types.union(
+        "_EX_FAST_REF",
+        8,
+        &[
+            ("Object", T_64PVOID, 0),
+            ("RefCnt", ref_cnt, 0),
+            ("Value", T_UQUAD, 0),
+        ],
+    );
+
+    (pdb, corrupted)
+}
+
+/// Returns the names of the structs of the profile and their number of
+/// fields.
+fn structs(profile: &Profile) -> Vec<(&'static str, Option<usize>)> {
+    ["_UNICODE_STRING", "_EPROCESS", "_EX_FAST_REF"]
+        .into_iter()
+        .map(|name| {
+            let fields = profile.find_struct(name).map(|udt| udt.fields.len());
+            (name, fields)
+        })
+        .collect()
+}
+
+#[test]
+fn fail_fast_by_default() {
+    let (pdb, _) = pdb();
+
+    let result = create_profile_with_options(pdb.file(), &ProfileOptions::default(), |_| {
+        Ok::<_, std::io::Error>(())
+    });
+    assert!(matches!(result, Err(Error::Pdb(_))));
+}
+
+#[test]
+fn partial_on_error() {
+    let (pdb, _) = pdb();
+
+    let mut structs = Vec::new();
+    let options = ProfileOptions::default().with_partial_on_error(true);
+    create_profile_with_options(pdb.file(), &options, |profile| {
+        structs = self::structs(profile);
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    // The types before and after the corrupted record are kept.
+    assert_eq!(
+        structs,
+        [
+            ("_UNICODE_STRING", Some(3)),
+            ("_EPROCESS", None),
+            ("_EX_FAST_REF", Some(3)),
+        ]
+    );
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn skipped_type_reported() {
+    let (pdb, corrupted) = pdb();
+
+    let options = ProfileOptions::default().with_partial_on_error(true);
+    let report =
+        isr_pdb::create_profile_with_report(pdb.file(), &options, |_| Ok::<_, std::io::Error>(()))
+            .unwrap();
+
+    assert!(report.is_partial());
+    assert_eq!(report.skipped_types.len(), 1);
+    assert_eq!(report.skipped_types[0].index, Some(corrupted));
+    assert!(!report.skipped_types[0].reason.is_empty());
+}
@@ -0,0 +1,63 @@
+//! Types defined more than once are resolved according to the policy.
+
+mod common;
+
+use isr_core::{ProfileOptions, ResolutionPolicy};
+
+use self::common::pdb::{Pdb, T_UCHAR, T_ULONG};
+
+/// Returns the size and number of fields of the kept `_KTHREAD`, and the
+/// number of variants of the kept `_POOL_TYPE`.
+fn resolve(policy: Option<ResolutionPolicy>) -> (u64, usize, usize) {
+    let mut pdb = Pdb::default();
+    let types = &mut pdb.types;
+
+    let fields = [("A", T_UCHAR, 0), ("B", T_UCHAR, 1), ("C", T_UCHAR, 2)];
+    for (size, count) in [(16, 2), (32, 1), (8, 3), (12, 3), (24, 1)] {
+        types.structure("_KTHREAD", size, &fields[..count]);
+    }
+
+    types.enumeration("_POOL_TYPE", T_ULONG, &[("NonPagedPool", 0)]);
+    types.enumeration(
+        "_POOL_TYPE",
+        T_ULONG,
+        &[("NonPagedPool", 0), ("PagedPool", 1)],
+    );
+    types.enumeration("_POOL_TYPE", T_ULONG, &[("PagedPool", 1)]);
+
+    let options = ProfileOptions::default().with_resolution_policy(policy);
+
+    let mut result = None;
+    isr_pdb::create_profile_with_options(pdb.file(), &options, |profile| {
+        let udt = profile.find_struct("_KTHREAD").unwrap();
+        let enum_ = profile.find_enum("_POOL_TYPE").unwrap();
+        result = Some((udt.size, udt.fields.len(), enum_.fields.len()));
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    result.unwrap()
+}
+
+#[test]
+fn last_seen_by_default() {
+    assert_eq!(resolve(None), (24, 1, 1));
+    assert_eq!(resolve(Some(ResolutionPolicy::LastSeen)), (24, 1, 1));
+}
+
+#[test]
+fn first_seen() {
+    assert_eq!(resolve(Some(ResolutionPolicy::FirstSeen)), (16, 2, 1));
+}
+
+#[test]
+fn largest_size() {
+    // Enums are resolved by their number of variants.
+    assert_eq!(resolve(Some(ResolutionPolicy::LargestSize)), (32, 1, 2));
+}
+
+#[test]
+fn most_fields() {
+    // On ties, the earlier definition is kept.
+    assert_eq!(resolve(Some(ResolutionPolicy::MostFields)), (8, 3, 2));
+}
@@ -0,0 +1,75 @@
+//! Lossy conversions are handled according to the strictness.
+
+mod common;
+
+use isr_core::{
+    types::{BaseRef, Type},
+    ProfileOptions, Strictness,
+};
+use isr_pdb::{create_profile_with_options, Error};
+
+use self::common::pdb::{Pdb, T_UQUAD};
+
+/// `T_REAL80`, a primitive without a counterpart in the profile.
+const T_REAL80: u32 = 0x0042;
+
+/// Creates a PDB with a struct field of an unhandled primitive type.
+fn pdb() -> Pdb {
+    let mut pdb = Pdb::default();
+    pdb.types.structure(
+        "_FLOATING_SAVE_AREA",
+        24,
+        &[("ControlWord", T_UQUAD, 0), ("St0", T_REAL80, 8)],
+    );
+    pdb
+}
+
+/// Returns whether `_FLOATING_SAVE_AREA.St0` was treated as void.
+fn treated_as_void(strictness: Strictness) -> Result<bool, Error> {
+    let options = ProfileOptions::default().with_strictness(strictness);
+
+    let mut result = false;
+    create_profile_with_options(pdb().file(), &options, |profile| {
+        let udt = profile.find_struct("_FLOATING_SAVE_AREA").unwrap();
+        result = matches!(udt.fields["St0"].type_, Type::Base(BaseRef::Void));
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    Ok(result)
+}
+
+#[test]
+fn lenient() {
+    for strictness in [Strictness::Lenient, Strictness::Warn] {
+        assert!(treated_as_void(strictness).unwrap());
+    }
+}
+
+#[test]
+fn strict() {
+    match treated_as_void(Strictness::Strict) {
+        Err(Error::Strict(warning)) => {
+            assert_eq!(warning.message, "unhandled primitive, treated as void");
+        }
+        result => panic!("unexpected result: {result:?}"),
+    }
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn reported() {
+    let options = ProfileOptions::default().with_strictness(Strictness::Warn);
+    let report =
+        isr_pdb::create_profile_with_report(
+            pdb().file(),
+            &options,
+            |_| Ok::<_, std::io::Error>(()),
+        )
+        .unwrap();
+
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(
+        report.warnings[0].message,
+        "unhandled primitive, treated as void"
+    );
+}
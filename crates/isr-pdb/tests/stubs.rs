@@ -0,0 +1,54 @@
+mod common;
+
+use isr_core::ProfileOptions;
+
+use self::common::pdb::{Pdb, T_UQUAD};
+
+/// Returns the size, number of fields and completeness of `_EPROCESS`
+/// and `_KTHREAD`.
+fn structs(forward_declaration_stubs: bool) -> [Option<(u64, usize, bool)>; 2] {
+    let mut pdb = Pdb::default();
+    let types = &mut pdb.types;
+
+    // `_EPROCESS` is forward declared before its definition, `_KTHREAD`
+    // is only forward declared.
+    let eprocess = types.forward_structure("_EPROCESS");
+    let kthread = types.forward_structure("_KTHREAD");
+    let eprocess_ptr = types.pointer(eprocess);
+    let kthread_ptr = types.pointer(kthread);
+    types.structure(
+        "_EPROCESS",
+        24,
+        &[
+            ("UniqueProcessId", T_UQUAD, 0),
+            ("Parent", eprocess_ptr, 8),
+            ("Thread", kthread_ptr, 16),
+        ],
+    );
+
+    let options =
+        ProfileOptions::default().with_forward_declaration_stubs(forward_declaration_stubs);
+
+    let mut result = [None; 2];
+    isr_pdb::create_profile_with_options(pdb.file(), &options, |profile| {
+        result = ["_EPROCESS", "_KTHREAD"].map(|name| {
+            let udt = profile.find_struct(name)?;
+            Some((udt.size, udt.fields.len(), udt.incomplete))
+        });
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+
+    result
+}
+
+#[test]
+fn without_stubs() {
+    assert_eq!(structs(false), [Some((24, 3, false)), None]);
+}
+
+#[test]
+fn with_stubs() {
+    // The defined struct isn't replaced by a stub.
+    assert_eq!(structs(true), [Some((24, 3, false)), Some((0, 0, true))]);
+}
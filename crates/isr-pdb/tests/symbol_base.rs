@@ -0,0 +1,31 @@
+mod common;
+
+use isr_core::{ProfileSource, SymbolBase};
+
+use self::common::pdb::{Pdb, Public};
+
+#[test]
+fn rva() {
+    // A `_text` symbol would make a profile without a recorded convention
+    // look like a System.map profile.
+    let pdb = Pdb {
+        publics: vec![Public {
+            name: b"_text",
+            section: 1,
+            offset: 0x10,
+        }],
+        ..Default::default()
+    };
+
+    isr_pdb::create_profile(pdb.file(), |profile| {
+        assert_eq!(profile.source(), Some(ProfileSource::Pdb));
+        assert_eq!(profile.symbol_base(), SymbolBase::Rva);
+        assert_eq!(profile.symbol_rva("_text"), Some(0x1010));
+        assert_eq!(
+            profile.symbol_va("_text", 0xfffff800_1b000000),
+            Some(0xfffff800_1b001010)
+        );
+        Ok::<_, std::io::Error>(())
+    })
+    .unwrap();
+}
@@ -0,0 +1,63 @@
+//! Types nested deeper than the limit are replaced with void.
+
+mod common;
+
+use isr_core::{
+    types::{BaseRef, Type},
+    ProfileOptions, Strictness,
+};
+use isr_pdb::{create_profile_with_options, Error};
+
+use self::common::pdb::{Pdb, T_ULONG};
+
+/// Creates a PDB with a chain of 5 pointers to an `unsigned long`.
+fn pdb() -> Pdb {
+    let mut pdb = Pdb::default();
+
+    let mut type_ = T_ULONG;
+    for _ in 0..5 {
+        type_ = pdb.types.pointer(type_);
+    }
+
+    pdb.types.structure("_CHAIN", 8, &[("Next", type_, 0)]);
+    pdb
+}
+
+/// Returns the number of pointers in the chain and whether it ends with
+/// `unsigned long`.
+fn chain(options: &ProfileOptions) -> Result<(usize, bool), Error> {
+    let mut result = (0, false);
+    create_profile_with_options(pdb().file(), options, |profile| {
+        let udt = profile.find_struct("_CHAIN").unwrap();
+
+        let mut type_ = &udt.fields["Next"].type_;
+        let mut pointers = 0;
+        while let Type::Pointer(pointer) = type_ {
+            type_ = &pointer.subtype;
+            pointers += 1;
+        }
+
+        result = (pointers, matches!(type_, Type::Base(BaseRef::U32)));
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    Ok(result)
+}
+
+#[test]
+fn within_limit() {
+    assert_eq!(chain(&ProfileOptions::default()).unwrap(), (5, true));
+
+    let options = ProfileOptions::default().with_max_type_depth(6);
+    assert_eq!(chain(&options).unwrap(), (5, true));
+}
+
+#[test]
+fn cut() {
+    let options = ProfileOptions::default().with_max_type_depth(3);
+    assert_eq!(chain(&options).unwrap(), (3, false));
+
+    // The cut is a lossy conversion.
+    let options = options.with_strictness(Strictness::Strict);
+    assert!(matches!(chain(&options), Err(Error::Strict(_))));
+}
@@ -0,0 +1,43 @@
+use std::sync::OnceLock;
+
+use crate::cache::{DefaultCodec, Error, IsrCache};
+
+/// The process-wide default cache.
+static DEFAULT_CACHE: OnceLock<IsrCache<DefaultCodec>> = OnceLock::new();
+
+/// Returns the process-wide default cache.
+///
+/// The cache is lazily created on first access in the default directory
+/// (see [`IsrCache::default_directory`]), unless it was configured earlier
+/// by [`set_default_cache`]. Profiles are stored using the
+/// [`DefaultCodec`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let cache = isr::default_cache()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn default_cache() -> Result<&'static IsrCache<DefaultCodec>, Error> {
+    if let Some(cache) = DEFAULT_CACHE.get() {
+        return Ok(cache);
+    }
+
+    // Concurrent first accesses might all create a cache, but only the
+    // first one is kept.
+    let cache = IsrCache::from_default_directory()?;
+    Ok(DEFAULT_CACHE.get_or_init(|| cache))
+}
+
+/// Sets the process-wide default cache.
+///
+/// Must be called before the first call to [`default_cache`]. If the
+/// default cache is already initialized, the given cache is returned back
+/// as an error.
+// Mirrors `OnceLock::set`, the rejected cache is handed back as is.
+#[allow(clippy::result_large_err)]
+pub fn set_default_cache(cache: IsrCache<DefaultCodec>) -> Result<(), IsrCache<DefaultCodec>> {
+    DEFAULT_CACHE.set(cache)
+}
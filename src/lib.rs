@@ -34,12 +34,7 @@
 //! ## Usage
 //!
 //! ```rust
-//! use isr::{
-//!     cache::JsonCodec,
-//!     download::pdb::CodeView,
-//!     macros::{symbols, offsets, Field},
-//!     IsrCache, Profile,
-//! };
+//! use isr::prelude::*;
 //!
 //! symbols! {
 //!     struct Symbols {
@@ -61,10 +56,8 @@
 //! let cache = IsrCache::<JsonCodec>::new("cache")?;
 //!
 //! // Use the CodeView information of the Windows 10.0.18362.356 kernel.
-//! let entry = cache.entry_from_codeview(CodeView {
-//!     path: String::from("ntkrnlmp.pdb"),
-//!     guid: String::from("ce7ffb00c20b87500211456b3e905c471"),
-//! })?;
+//! let codeview = CodeView::new("ntkrnlmp.pdb", "ce7ffb00c20b87500211456b3e905c471")?;
+//! let entry = cache.entry_from_codeview(codeview)?;
 //!
 //! // You can also use `entry_from_pe` method:
 //! // let entry = cache.entry_from_pe("path/to/ntoskrnl.exe")?;
@@ -102,6 +95,9 @@
 //! [`offsets!`]: crate::macros::offsets
 //! [`vmi`]: ../vmi/index.html
 
+#[cfg(feature = "cache")]
+mod default_cache;
+
 pub use isr_core::*;
 
 pub mod macros {
@@ -110,6 +106,7 @@ pub mod macros {
     pub use isr_macros::*;
 }
 
+#[cfg(feature = "cache")]
 pub mod cache {
     #![doc = include_str!("../docs/isr-cache.md")]
 
@@ -117,30 +114,86 @@ pub mod cache {
 }
 
 // Re-export the `IsrCache` to the root of the crate.
+#[cfg(feature = "cache")]
 #[doc(inline)]
 pub use self::cache::IsrCache;
+#[cfg(feature = "cache")]
+pub use self::default_cache::{default_cache, set_default_cache};
+
+pub mod prelude {
+    //! Commonly used items.
+    //!
+    //! ```rust
+    //! use isr::prelude::*;
+    //! ```
+    //!
+    //! Every crate has its own `Error` type, so they're renamed to avoid
+    //! conflicts.
 
+    #[cfg(feature = "cache")]
+    pub use isr_cache::{
+        BincodeCodec, Codec, DefaultCodec, Entry, Error as CacheError, IsrCache, JsonCodec,
+        MsgpackCodec,
+    };
+    pub use isr_core::{Profile, ProfileOptions};
+    #[cfg(feature = "dl-linux")]
+    pub use isr_dl_linux::{Error as LinuxDownloadError, LinuxBanner};
+    #[cfg(feature = "dl-pdb")]
+    pub use isr_dl_pdb::{CodeView, DebugId, Error as PdbDownloadError};
+    pub use isr_macros::{
+        enums, offsets, symbols, Bitfield, Error as MacrosError, Field, FromProfile, IsrLazy,
+    };
+}
+
+#[cfg(feature = "pdb")]
 pub mod pdb {
     #![doc = include_str!("../docs/isr-pdb.md")]
 
     pub use isr_pdb::*;
 }
 
+#[cfg(feature = "dwarf")]
 pub mod dwarf {
     #![doc = include_str!("../docs/isr-dwarf.md")]
 
     pub use isr_dwarf::*;
 }
 
+#[cfg(feature = "isf")]
+pub mod isf {
+    #![doc = include_str!("../docs/isr-isf.md")]
+
+    pub use isr_isf::*;
+}
+
+#[cfg(feature = "unstable")]
+pub mod unstable {
+    //! Experimental APIs.
+    //!
+    //! <div class="warning">
+    //!
+    //! Items in this module are only available with the `unstable` feature
+    //! and are not covered by semver guarantees. They may change or be
+    //! removed in any release.
+    //!
+    //! </div>
+
+    #[cfg(feature = "cache")]
+    pub use isr_cache::unstable::*;
+    pub use isr_core::unstable::*;
+}
+
 pub mod download {
     //! Downloaders for various symbol formats.
 
+    #[cfg(feature = "dl-pdb")]
     pub mod pdb {
         #![doc = include_str!("../docs/isr-dl-pdb.md")]
 
         pub use isr_dl_pdb::*;
     }
 
+    #[cfg(feature = "dl-linux")]
     pub mod linux {
         #![doc = include_str!("../docs/isr-dl-linux.md")]
 
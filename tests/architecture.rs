@@ -0,0 +1,91 @@
+mod common;
+
+use std::borrow::Cow;
+
+use isr::{
+    macros::{offsets, symbols, Error, Field},
+    types::Types,
+    Profile, Symbols,
+};
+
+use self::common::windows;
+
+offsets! {
+    #[isr(arch = "Amd64")]
+    struct Offsets {
+        struct _EPROCESS {
+            ActiveProcessLinks: Field,
+        }
+    }
+}
+
+mod other {
+    use isr::macros::{offsets, Field};
+
+    offsets! {
+        #[isr(arch = ["X86", "Arm64"])]
+        pub struct Offsets {
+            struct _EPROCESS {
+                ActiveProcessLinks: Field,
+            }
+        }
+    }
+}
+
+symbols! {
+    #[isr(arch = ["Amd64", "Arm64"])]
+    struct Symbols64 {
+        PsActiveProcessHead: u64,
+    }
+}
+
+/// Creates an empty x86 profile.
+fn x86_profile() -> Profile<'static> {
+    Profile::new(Cow::Borrowed("X86"), Symbols::default(), Types::default())
+}
+
+#[test]
+fn expect_architecture() {
+    let profile = windows::profile();
+    assert_eq!(profile.architecture(), "Amd64");
+    assert!(profile.expect_architecture(&["X86", "Amd64"]).is_ok());
+
+    let mismatch = profile.expect_architecture(&["X86", "Arm64"]).unwrap_err();
+    assert_eq!(mismatch.expected, ["X86", "Arm64"]);
+    assert_eq!(mismatch.found, "Amd64");
+    assert_eq!(
+        mismatch.to_string(),
+        "Expected architecture X86 or Arm64, found Amd64"
+    );
+}
+
+#[test]
+fn matching() {
+    let profile = windows::profile();
+
+    let offsets = Offsets::new(&profile).unwrap();
+    assert_eq!(offsets._EPROCESS.ActiveProcessLinks.offset, 752);
+
+    assert!(Symbols64::new(&profile).is_ok());
+}
+
+#[test]
+fn mismatch() {
+    let profile = windows::profile();
+    let result = other::Offsets::new(&profile);
+    assert!(matches!(
+        result,
+        Err(Error::ArchitectureMismatch { found, .. }) if found == "Amd64"
+    ));
+
+    // Checked before resolving anything, the x86 profile is empty.
+    let profile = x86_profile();
+    assert!(matches!(
+        Offsets::new(&profile),
+        Err(Error::ArchitectureMismatch { .. })
+    ));
+    assert!(matches!(
+        Symbols64::new(&profile),
+        Err(Error::ArchitectureMismatch { .. })
+    ));
+}
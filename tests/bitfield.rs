@@ -0,0 +1,89 @@
+use isr::macros::Bitfield;
+
+/// Returns the offset, size, bit position and bit length of the bitfield
+/// created by [`Bitfield::from_absolute`].
+fn from_absolute(
+    offset_bits: u64,
+    bit_length: u64,
+    storage_size: u64,
+) -> Option<(u64, u64, u64, u64)> {
+    Bitfield::from_absolute(offset_bits, bit_length, storage_size).map(|bitfield| {
+        (
+            bitfield.offset,
+            bitfield.size,
+            bitfield.bit_position,
+            bitfield.bit_length,
+        )
+    })
+}
+
+fn bitfield(offset: u64, size: u64, bit_position: u64, bit_length: u64) -> Bitfield {
+    Bitfield {
+        offset,
+        size,
+        bit_position,
+        bit_length,
+    }
+}
+
+#[test]
+fn decompose() {
+    // Bits 35..38 are bits 3..6 of the second u32.
+    assert_eq!(from_absolute(35, 3, 4), Some((4, 4, 3, 3)));
+
+    // Bits 35..38 are bits 35..38 of the first u64.
+    assert_eq!(from_absolute(35, 3, 8), Some((0, 8, 35, 3)));
+
+    // The whole storage unit.
+    assert_eq!(from_absolute(64, 32, 4), Some((8, 4, 0, 32)));
+}
+
+#[test]
+fn storage_unit_boundaries() {
+    // The last bit of a u32.
+    assert_eq!(from_absolute(31, 1, 4), Some((0, 4, 31, 1)));
+
+    // Position 31 with length 2 crosses into the next u32.
+    assert_eq!(from_absolute(31, 2, 4), None);
+    assert_eq!(from_absolute(63, 2, 4), None);
+
+    // The same bits fit into a u64.
+    assert_eq!(from_absolute(31, 2, 8), Some((0, 8, 31, 2)));
+
+    // Longer than the storage unit.
+    assert_eq!(from_absolute(0, 33, 4), None);
+
+    // The first bit of the next u32.
+    assert_eq!(from_absolute(32, 2, 4), Some((4, 4, 0, 2)));
+}
+
+#[test]
+fn from_absolute_invalid() {
+    assert_eq!(from_absolute(0, 0, 4), None);
+    assert_eq!(from_absolute(0, 1, 0), None);
+    assert_eq!(from_absolute(0, 1, u64::MAX), None);
+}
+
+#[test]
+fn absolute_bit_offset() {
+    assert_eq!(bitfield(0, 4, 31, 1).absolute_bit_offset(), 31);
+    assert_eq!(bitfield(4, 4, 3, 3).absolute_bit_offset(), 35);
+
+    // Round trip.
+    for (offset_bits, bit_length, storage_size) in [(35, 3, 4), (31, 1, 4), (31, 2, 8), (64, 32, 4)]
+    {
+        let bitfield = Bitfield::from_absolute(offset_bits, bit_length, storage_size).unwrap();
+        assert_eq!(bitfield.absolute_bit_offset(), offset_bits);
+        assert_eq!(bitfield.bit_length, bit_length);
+    }
+}
+
+#[test]
+fn contains_bit() {
+    let bitfield = bitfield(4, 4, 30, 2);
+    assert!(!bitfield.contains_bit(61));
+    assert!(bitfield.contains_bit(62));
+    assert!(bitfield.contains_bit(63));
+    assert!(!bitfield.contains_bit(64));
+    assert!(!bitfield.contains_bit(0));
+}
@@ -0,0 +1,100 @@
+//! Linux-like kernel profile, laid out after the x86_64 6.1 kernel.
+
+use std::borrow::Cow;
+
+use isr::{
+    types::{BaseRef, StructKind, Type, Types},
+    Profile, ProfileSource, Symbols,
+};
+
+use super::{add_struct, array, pointer, struct_};
+
+/// Creates the profile.
+pub fn profile() -> Profile<'static> {
+    let mut types = Types::default();
+
+    add_struct(
+        &mut types,
+        "list_head",
+        StructKind::Struct,
+        16,
+        vec![
+            ("next", 0, pointer(struct_("list_head"))),
+            ("prev", 8, pointer(struct_("list_head"))),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "rb_node",
+        StructKind::Struct,
+        24,
+        vec![
+            ("__rb_parent_color", 0, Type::Base(BaseRef::U64)),
+            ("rb_right", 8, pointer(struct_("rb_node"))),
+            ("rb_left", 16, pointer(struct_("rb_node"))),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "rb_root",
+        StructKind::Struct,
+        8,
+        vec![("rb_node", 0, pointer(struct_("rb_node")))],
+    );
+
+    add_struct(
+        &mut types,
+        "vmap_area",
+        StructKind::Struct,
+        64,
+        vec![
+            ("va_start", 0, Type::Base(BaseRef::U64)),
+            ("va_end", 8, Type::Base(BaseRef::U64)),
+            ("rb_node", 16, struct_("rb_node")),
+            ("list", 40, struct_("list_head")),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "qstr",
+        StructKind::Struct,
+        16,
+        vec![
+            ("hash", 0, Type::Base(BaseRef::U32)),
+            ("len", 4, Type::Base(BaseRef::U32)),
+            ("name", 8, pointer(Type::Base(BaseRef::U8))),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "dentry",
+        StructKind::Struct,
+        192,
+        vec![
+            ("d_flags", 0, Type::Base(BaseRef::U32)),
+            ("d_parent", 24, pointer(struct_("dentry"))),
+            ("d_name", 32, struct_("qstr")),
+            ("d_inode", 48, pointer(Type::Base(BaseRef::Void))),
+            ("d_iname", 56, array(Type::Base(BaseRef::U8), 32)),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "task_struct",
+        StructKind::Struct,
+        9856,
+        vec![
+            ("tasks", 2192, struct_("list_head")),
+            ("pid", 2464, Type::Base(BaseRef::I32)),
+            ("comm", 3008, array(Type::Base(BaseRef::U8), 16)),
+        ],
+    );
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+        .with_source(ProfileSource::Dwarf)
+}
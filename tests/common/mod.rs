@@ -0,0 +1,101 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+#[path = "../../crates/isr-dwarf/tests/common/kernel.rs"]
+pub mod kernel;
+pub mod linux;
+pub mod windows;
+
+use std::{borrow::Cow, path::Path};
+
+use isr::{
+    cache::{Codec, IsrCache, JsonCodec},
+    types::{
+        ArrayRef, BaseRef, BitfieldRef, Field, PointerRef, Struct, StructKind, StructRef, Type,
+        Types,
+    },
+    Profile, ProfileOptions, Symbols,
+};
+
+use self::kernel::Kernel;
+
+/// Creates the profile of the miniature Linux kernel.
+pub fn kernel_profile(kernel: &Kernel, options: &ProfileOptions) -> Profile<'static> {
+    let (image, systemmap) = kernel.files();
+
+    let mut encoded = Vec::new();
+    isr::dwarf::create_profile_with_options(image, systemmap, options, |profile| {
+        JsonCodec::encode(&mut encoded, profile)
+    })
+    .unwrap();
+
+    JsonCodec::decode(&encoded).unwrap().into_owned()
+}
+
+/// Checks whether the cache stores its entries in the directory, by
+/// creating an exports profile.
+pub fn stores_in<C: Codec>(cache: &IsrCache<C>, directory: &Path) -> bool {
+    let symbols = Symbols(
+        [(Cow::Borrowed("KeBugCheckEx"), 0x1c_14e0)]
+            .into_iter()
+            .collect(),
+    );
+    let entry = cache
+        .entry_from_exports("ntoskrnl.exe", "5F4E3A2B3000", "Amd64", symbols)
+        .unwrap();
+
+    entry.profile_path().starts_with(directory)
+}
+
+/// Adds a structure with the given fields to the types.
+pub fn add_struct(
+    types: &mut Types<'static>,
+    name: &'static str,
+    kind: StructKind,
+    size: u64,
+    fields: Vec<(&'static str, u64, Type<'static>)>,
+) {
+    let fields = fields
+        .into_iter()
+        .map(|(name, offset, type_)| (Cow::Borrowed(name), Field { offset, type_ }))
+        .collect();
+
+    types.structs.insert(
+        Cow::Borrowed(name),
+        Struct {
+            kind,
+            size,
+            fields,
+            incomplete: false,
+        },
+    );
+}
+
+pub fn struct_(name: &'static str) -> Type<'static> {
+    Type::Struct(StructRef {
+        name: Cow::Borrowed(name),
+    })
+}
+
+pub fn pointer(subtype: Type<'static>) -> Type<'static> {
+    Type::Pointer(PointerRef {
+        subtype: Box::new(subtype),
+    })
+}
+
+pub fn array(subtype: Type<'static>, count: u64) -> Type<'static> {
+    Type::Array(ArrayRef {
+        subtype: Box::new(subtype),
+        dims: [count].into_iter().collect(),
+        size: count,
+    })
+}
+
+pub fn bitfield(subtype: BaseRef, bit_position: u64, bit_length: u64) -> Type<'static> {
+    Type::Bitfield(BitfieldRef {
+        subtype: Box::new(Type::Base(subtype)),
+        bit_length,
+        bit_position,
+    })
+}
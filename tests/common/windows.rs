@@ -0,0 +1,142 @@
+//! Windows-like kernel profile, laid out after the 18362 `ntoskrnl`.
+
+use std::borrow::Cow;
+
+use isr::{
+    types::{BaseRef, Enum, StructKind, Type, Types, Variant},
+    Profile, ProfileSource, SymbolBase, Symbols,
+};
+
+use super::{add_struct, array, bitfield, pointer, struct_};
+
+/// Preferred base address of the kernel image.
+pub const IMAGE_BASE: u64 = 0x1_4000_0000;
+
+/// Symbols of the profile, as RVAs.
+pub const SYMBOLS: &[(&str, u64)] = &[
+    ("KeBugCheckEx", 0x1c_14e0),
+    ("KiSystemCall64Shadow", 0xa1_2180),
+    ("NtOpenFile", 0x6c_2f40),
+    ("PsActiveProcessHead", 0x43_7bc0),
+    ("PsInitialSystemProcess", 0x57_33a0),
+];
+
+/// Creates the profile.
+pub fn profile() -> Profile<'static> {
+    let symbols = SYMBOLS
+        .iter()
+        .map(|&(name, rva)| (Cow::Borrowed(name), rva))
+        .collect();
+
+    let mut types = Types::default();
+
+    types.enums.insert(
+        "_POOL_TYPE".into(),
+        Enum::new(
+            Type::Base(BaseRef::I32),
+            [
+                ("NonPagedPool", 0),
+                ("NonPagedPoolExecute", 0),
+                ("PagedPool", 1),
+                ("NonPagedPoolMustSucceed", 2),
+            ]
+            .into_iter()
+            .map(|(name, value)| (Cow::Borrowed(name), Variant::I64(value)))
+            .collect(),
+        ),
+    );
+
+    add_struct(
+        &mut types,
+        "_LIST_ENTRY",
+        StructKind::Struct,
+        16,
+        vec![
+            ("Flink", 0, pointer(struct_("_LIST_ENTRY"))),
+            ("Blink", 8, pointer(struct_("_LIST_ENTRY"))),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "_UNICODE_STRING",
+        StructKind::Struct,
+        16,
+        vec![
+            ("Length", 0, Type::Base(BaseRef::U16)),
+            ("MaximumLength", 2, Type::Base(BaseRef::U16)),
+            ("Buffer", 8, pointer(Type::Base(BaseRef::U16))),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "_EX_FAST_REF",
+        StructKind::Union,
+        8,
+        vec![
+            ("Object", 0, pointer(Type::Base(BaseRef::Void))),
+            ("RefCnt", 0, bitfield(BaseRef::U64, 0, 4)),
+            ("Value", 0, Type::Base(BaseRef::U64)),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "_KAFFINITY_EX",
+        StructKind::Struct,
+        168,
+        vec![
+            ("Count", 0, Type::Base(BaseRef::U16)),
+            ("Size", 2, Type::Base(BaseRef::U16)),
+            ("Reserved", 4, Type::Base(BaseRef::U32)),
+            ("Bitmap", 8, array(Type::Base(BaseRef::U64), 20)),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "_KPROCESS",
+        StructKind::Struct,
+        736,
+        vec![
+            ("ProfileListHead", 24, struct_("_LIST_ENTRY")),
+            ("DirectoryTableBase", 40, Type::Base(BaseRef::U64)),
+            ("Affinity", 80, struct_("_KAFFINITY_EX")),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "_EPROCESS",
+        StructKind::Struct,
+        2176,
+        vec![
+            ("Pcb", 0, struct_("_KPROCESS")),
+            ("UniqueProcessId", 744, pointer(Type::Base(BaseRef::Void))),
+            ("ActiveProcessLinks", 752, struct_("_LIST_ENTRY")),
+            ("Flags2", 768, Type::Base(BaseRef::U32)),
+            ("BreakOnTermination", 780, bitfield(BaseRef::U32, 13, 1)),
+            ("Token", 864, struct_("_EX_FAST_REF")),
+            ("Wow64Process", 1064, pointer(Type::Base(BaseRef::Void))),
+            ("ImageFileName", 1104, array(Type::Base(BaseRef::U8), 15)),
+        ],
+    );
+
+    add_struct(
+        &mut types,
+        "_KLDR_DATA_TABLE_ENTRY",
+        StructKind::Struct,
+        160,
+        vec![
+            ("InLoadOrderLinks", 0, struct_("_LIST_ENTRY")),
+            ("DllBase", 48, pointer(Type::Base(BaseRef::Void))),
+            ("FullDllName", 72, struct_("_UNICODE_STRING")),
+        ],
+    );
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), types)
+        .with_source(ProfileSource::Pdb)
+        .with_symbol_base(SymbolBase::Rva)
+        .with_preferred_base(IMAGE_BASE)
+}
@@ -0,0 +1,82 @@
+//! Profiles are created concurrently on worker threads (each call keeps
+//! its own state), shared between them, and the errors sent back.
+
+mod common;
+
+use std::sync::Barrier;
+
+use isr::{
+    cache::{Codec as _, JsonCodec},
+    Profile, ProfileOptions,
+};
+
+use self::common::kernel::Kernel;
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Profile<'static>>();
+    assert_send_sync::<ProfileOptions>();
+    #[cfg(feature = "unstable")]
+    assert_send_sync::<isr::unstable::ProfileReport>();
+    assert_send_sync::<isr::pdb::Error>();
+    assert_send_sync::<isr::dwarf::Error>();
+    #[cfg(feature = "isf")]
+    assert_send_sync::<isr::isf::Error>();
+};
+
+/// Number of profiles created at the same time.
+const THREADS: usize = 4;
+
+/// Creates the profile of the kernel and returns it encoded.
+fn encoded_profile(kernel: &Kernel, options: &ProfileOptions) -> Vec<u8> {
+    let (image, systemmap) = kernel.files();
+
+    let mut encoded = Vec::new();
+    isr::dwarf::create_profile_with_options(image, systemmap, options, |profile| {
+        JsonCodec::encode(&mut encoded, profile)
+    })
+    .unwrap();
+
+    encoded
+}
+
+#[test]
+fn concurrent_profiles_match_serial() {
+    // Two different kernels, so that the runs don't produce the same
+    // profile by chance.
+    let kernels = [Kernel::new(4), Kernel::new(12)];
+    let options = ProfileOptions::default();
+
+    let serial = kernels
+        .iter()
+        .map(|kernel| encoded_profile(kernel, &options))
+        .collect::<Vec<_>>();
+    assert_ne!(serial[0], serial[1]);
+
+    let barrier = Barrier::new(THREADS);
+    let concurrent = std::thread::scope(|scope| {
+        let handles = (0..THREADS)
+            .map(|index| {
+                let (barrier, kernel) = (&barrier, &kernels[index % kernels.len()]);
+                let options = options.clone().with_run_id(format!("run-{index}"));
+
+                scope.spawn(move || {
+                    barrier.wait();
+                    encoded_profile(kernel, &options)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    for (index, encoded) in concurrent.iter().enumerate() {
+        assert!(
+            *encoded == serial[index % kernels.len()],
+            "profile of run {index} differs from the serial one"
+        );
+    }
+}
@@ -0,0 +1,508 @@
+//! Conformance suite.
+//!
+//! Each `tests/conformance/*.expected` file lists the layout of a profile
+//! fixture. The expectations are checked through every codec, both with
+//! the `ProfileExt` APIs and with the `offsets!`, `symbols!` and `enums!`
+//! macros. A parser change that shifts any of these values fails the
+//! suite, intended changes must update the expectations.
+//!
+//! Lines are whitespace separated, `#` starts a comment:
+//!
+//! ```text
+//! architecture <name>
+//! size <struct> <bytes>
+//! field <struct> <field> <offset> <size>
+//! bitfield <struct> <field> <offset> <size> <bit position> <bit length>
+//! variant <enum> <variant> <value>
+//! symbol <name> <value> <rva>
+//! missing <symbol>
+//! ```
+//!
+//! Numbers are decimal, or hexadecimal with a `0x` prefix. Fields of
+//! nested structs are found as well (e.g. `_EPROCESS Affinity`, in
+//! `_EPROCESS.Pcb`).
+
+mod common;
+
+use isr::{
+    cache::{BincodeCodec, Codec, CompactJsonCodec, JsonCodec, MsgpackCodec},
+    macros::{
+        __private::ProfileExt as _, enums, offsets, symbols, Bitfield, Field, FieldDescriptor,
+    },
+    Profile, ProfileOptions,
+};
+
+use self::common::{kernel::Kernel, kernel_profile, windows};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Expectation {
+    Architecture(String),
+    Size(String, u64),
+    Field(String, String, u64, u64),
+    Bitfield(String, String, u64, u64, u64, u64),
+    Variant(String, String, u64),
+    Symbol(String, u64, u64),
+    Missing(String),
+}
+
+fn parse_number(value: &str) -> u64 {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).unwrap(),
+        None => value.parse().unwrap(),
+    }
+}
+
+fn parse(expected: &str) -> Vec<Expectation> {
+    let mut result = Vec::new();
+
+    for line in expected.lines() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let s = |index: usize| String::from(words[index]);
+        let n = |index: usize| parse_number(words[index]);
+
+        result.push(match (words[0], words.len()) {
+            ("architecture", 2) => Expectation::Architecture(s(1)),
+            ("size", 3) => Expectation::Size(s(1), n(2)),
+            ("field", 5) => Expectation::Field(s(1), s(2), n(3), n(4)),
+            ("bitfield", 7) => Expectation::Bitfield(s(1), s(2), n(3), n(4), n(5), n(6)),
+            ("variant", 4) => Expectation::Variant(s(1), s(2), n(3)),
+            ("symbol", 4) => Expectation::Symbol(s(1), n(2), n(3)),
+            ("missing", 2) => Expectation::Missing(s(1)),
+            _ => panic!("invalid expectation: {line}"),
+        });
+    }
+
+    result
+}
+
+/// Checks the expectations through the `ProfileExt` APIs.
+fn check_profile(profile: &Profile, expectations: &[Expectation]) {
+    for expectation in expectations {
+        let actual = match expectation {
+            Expectation::Architecture(_) => {
+                Expectation::Architecture(profile.architecture().into())
+            }
+            Expectation::Size(name, _) => {
+                Expectation::Size(name.clone(), profile.struct_size(name).unwrap())
+            }
+            Expectation::Field(type_name, field_name, ..) => {
+                match profile
+                    .find_field_descriptor(type_name, field_name)
+                    .unwrap()
+                {
+                    FieldDescriptor::Field(field) => Expectation::Field(
+                        type_name.clone(),
+                        field_name.clone(),
+                        field.offset,
+                        field.size,
+                    ),
+                    FieldDescriptor::Bitfield(_) => panic!("{type_name}.{field_name}: bitfield"),
+                }
+            }
+            Expectation::Bitfield(type_name, field_name, ..) => {
+                let bitfield = profile.find_bitfield(type_name, field_name).unwrap();
+                Expectation::Bitfield(
+                    type_name.clone(),
+                    field_name.clone(),
+                    bitfield.offset,
+                    bitfield.size,
+                    bitfield.bit_position,
+                    bitfield.bit_length,
+                )
+            }
+            Expectation::Variant(type_name, variant_name, _) => Expectation::Variant(
+                type_name.clone(),
+                variant_name.clone(),
+                profile.find_variant_value(type_name, variant_name).unwrap(),
+            ),
+            Expectation::Symbol(name, ..) => Expectation::Symbol(
+                name.clone(),
+                profile.find_symbol_descriptor(name).unwrap().offset,
+                profile.symbol_rva(name).unwrap(),
+            ),
+            Expectation::Missing(name) => {
+                assert!(profile.find_symbol_descriptor(name).is_err(), "{name}");
+                continue;
+            }
+        };
+
+        assert_eq!(&actual, expectation);
+    }
+}
+
+/// Checks that the expectations resolved by the macros are exactly the
+/// expected ones (besides the architecture).
+fn check_macros(mut actual: Vec<Expectation>, expectations: &[Expectation]) {
+    let mut expected = expectations
+        .iter()
+        .filter(|expectation| !matches!(expectation, Expectation::Architecture(_)))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    actual.sort();
+    expected.sort();
+    assert_eq!(actual, expected);
+}
+
+/// Encodes and decodes the profile with the codec, and checks the
+/// expectations on the decoded profile.
+fn check_codec<C: Codec>(
+    profile: &Profile,
+    expectations: &[Expectation],
+    macros: fn(&Profile) -> Vec<Expectation>,
+) {
+    let mut encoded = Vec::new();
+    C::encode(&mut encoded, profile).unwrap();
+    let decoded = C::decode(&encoded).unwrap();
+
+    check_profile(&decoded, expectations);
+    check_macros(macros(&decoded), expectations);
+
+    // Symbols found without decoding the whole profile.
+    let names = expectations
+        .iter()
+        .filter_map(|expectation| match expectation {
+            Expectation::Symbol(name, ..) | Expectation::Missing(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let found = C::find_symbols(&encoded, &names).unwrap();
+    for expectation in expectations {
+        match expectation {
+            Expectation::Symbol(name, value, _) => assert_eq!(found.get(name), Some(value)),
+            Expectation::Missing(name) => assert_eq!(found.get(name), None),
+            _ => (),
+        }
+    }
+}
+
+fn check_all_codecs(profile: &Profile, expected: &str, macros: fn(&Profile) -> Vec<Expectation>) {
+    let expectations = parse(expected);
+
+    check_profile(profile, &expectations);
+    check_codec::<BincodeCodec>(profile, &expectations, macros);
+    check_codec::<JsonCodec>(profile, &expectations, macros);
+    check_codec::<CompactJsonCodec>(profile, &expectations, macros);
+    check_codec::<MsgpackCodec>(profile, &expectations, macros);
+}
+
+fn field(type_name: &str, field_name: &str, field: Field) -> Expectation {
+    Expectation::Field(
+        type_name.into(),
+        field_name.into(),
+        field.offset,
+        field.size,
+    )
+}
+
+fn bitfield(type_name: &str, field_name: &str, bitfield: Bitfield) -> Expectation {
+    Expectation::Bitfield(
+        type_name.into(),
+        field_name.into(),
+        bitfield.offset,
+        bitfield.size,
+        bitfield.bit_position,
+        bitfield.bit_length,
+    )
+}
+
+fn size(type_name: &str, len: usize) -> Expectation {
+    Expectation::Size(type_name.into(), len as u64)
+}
+
+fn variant(type_name: &str, variant_name: &str, value: u64) -> Expectation {
+    Expectation::Variant(type_name.into(), variant_name.into(), value)
+}
+
+fn symbol(profile: &Profile, name: &str, value: u64) -> Expectation {
+    Expectation::Symbol(name.into(), value, profile.symbol_rva(name).unwrap())
+}
+
+fn optional_symbol(profile: &Profile, name: &str, value: Option<u64>) -> Expectation {
+    match value {
+        Some(value) => symbol(profile, name, value),
+        None => Expectation::Missing(name.into()),
+    }
+}
+
+//
+// Windows
+//
+
+offsets! {
+    struct WindowsOffsets {
+        struct _LIST_ENTRY {
+            Flink: Field,
+            Blink: Field,
+        }
+
+        struct _EX_FAST_REF {
+            Object: Field,
+            Value: Field,
+            RefCnt: Bitfield,
+        }
+
+        struct _KPROCESS {
+            DirectoryTableBase: Field,
+            Affinity: Field,
+        }
+
+        struct _EPROCESS {
+            UniqueProcessId: Field,
+            ActiveProcessLinks: Field,
+            Token: Field,
+            #[isr(alias = "Wow64Process")]
+            WoW64Process: Field,
+            BreakOnTermination: Bitfield,
+            Affinity: Field,
+            DirectoryTableBase: Field,
+            ImageFileName: Field,
+        }
+
+        #[isr(alias = "_KLDR_DATA_TABLE_ENTRY")]
+        struct _LDR_DATA_TABLE_ENTRY {
+            InLoadOrderLinks: Field,
+            DllBase: Field,
+            FullDllName: Field,
+        }
+    }
+}
+
+symbols! {
+    struct WindowsSymbols {
+        PsActiveProcessHead: u64,
+        PsInitialSystemProcess: u64,
+        #[isr(alias = "KiSystemCall64Shadow")]
+        KiSystemCall64: u64,
+        #[isr(alias = ["_NtOpenFile@24", "NtOpenFile"])]
+        NtOpenFile: u64,
+        NonExistentSymbol: Option<u64>,
+    }
+}
+
+enums! {
+    struct WindowsEnums {
+        enum _POOL_TYPE {
+            NonPagedPool,
+            PagedPool,
+            NonPagedPoolMustSucceed,
+        }
+    }
+}
+
+fn windows_macros(profile: &Profile) -> Vec<Expectation> {
+    let offsets = WindowsOffsets::new(profile).unwrap();
+    let symbols = WindowsSymbols::new(profile).unwrap();
+    let enums = WindowsEnums::new(profile).unwrap();
+
+    let list_entry = &offsets._LIST_ENTRY;
+    let fast_ref = &offsets._EX_FAST_REF;
+    let kprocess = &offsets._KPROCESS;
+    let eprocess = &offsets._EPROCESS;
+    let ldr = &offsets._LDR_DATA_TABLE_ENTRY;
+    let pool_type = &enums._POOL_TYPE;
+
+    vec![
+        size("_LIST_ENTRY", list_entry.len()),
+        size("_EX_FAST_REF", fast_ref.len()),
+        size("_KPROCESS", kprocess.len()),
+        size("_EPROCESS", eprocess.len()),
+        size("_KLDR_DATA_TABLE_ENTRY", ldr.len()),
+        field("_LIST_ENTRY", "Flink", list_entry.Flink),
+        field("_LIST_ENTRY", "Blink", list_entry.Blink),
+        field("_EX_FAST_REF", "Object", fast_ref.Object),
+        field("_EX_FAST_REF", "Value", fast_ref.Value),
+        bitfield("_EX_FAST_REF", "RefCnt", fast_ref.RefCnt),
+        field(
+            "_KPROCESS",
+            "DirectoryTableBase",
+            kprocess.DirectoryTableBase,
+        ),
+        field("_KPROCESS", "Affinity", kprocess.Affinity),
+        field("_EPROCESS", "UniqueProcessId", eprocess.UniqueProcessId),
+        field(
+            "_EPROCESS",
+            "ActiveProcessLinks",
+            eprocess.ActiveProcessLinks,
+        ),
+        field("_EPROCESS", "Token", eprocess.Token),
+        field("_EPROCESS", "Wow64Process", eprocess.WoW64Process),
+        bitfield(
+            "_EPROCESS",
+            "BreakOnTermination",
+            eprocess.BreakOnTermination,
+        ),
+        field("_EPROCESS", "Affinity", eprocess.Affinity),
+        field(
+            "_EPROCESS",
+            "DirectoryTableBase",
+            eprocess.DirectoryTableBase,
+        ),
+        field("_EPROCESS", "ImageFileName", eprocess.ImageFileName),
+        field(
+            "_KLDR_DATA_TABLE_ENTRY",
+            "InLoadOrderLinks",
+            ldr.InLoadOrderLinks,
+        ),
+        field("_KLDR_DATA_TABLE_ENTRY", "DllBase", ldr.DllBase),
+        field("_KLDR_DATA_TABLE_ENTRY", "FullDllName", ldr.FullDllName),
+        variant("_POOL_TYPE", "NonPagedPool", pool_type.NonPagedPool),
+        variant("_POOL_TYPE", "PagedPool", pool_type.PagedPool),
+        variant(
+            "_POOL_TYPE",
+            "NonPagedPoolMustSucceed",
+            pool_type.NonPagedPoolMustSucceed,
+        ),
+        symbol(profile, "PsActiveProcessHead", symbols.PsActiveProcessHead),
+        symbol(
+            profile,
+            "PsInitialSystemProcess",
+            symbols.PsInitialSystemProcess,
+        ),
+        symbol(profile, "KiSystemCall64Shadow", symbols.KiSystemCall64),
+        symbol(profile, "NtOpenFile", symbols.NtOpenFile),
+        optional_symbol(profile, "NonExistentSymbol", symbols.NonExistentSymbol),
+    ]
+}
+
+#[test]
+fn windows() {
+    check_all_codecs(
+        &windows::profile(),
+        include_str!("conformance/windows.expected"),
+        windows_macros,
+    );
+}
+
+//
+// Linux
+//
+
+offsets! {
+    struct LinuxOffsets {
+        struct list_head {
+            next: Field,
+            prev: Field,
+        }
+
+        struct task_struct {
+            __state: Field,
+            flags: Field,
+            sched_reset_on_fork: Bitfield,
+            sched_contributes_to_load: Bitfield,
+            in_execve: Bitfield,
+            on_cpu: Field,
+            tasks: Field,
+            pid: Field,
+            tgid: Field,
+            comm: Field,
+            mm: Field,
+            stack: Field,
+            stack_vm_area: Field,
+            thread_fn: Field,
+            nvcsw: Field,
+            pid_type: Field,
+        }
+
+        struct unit0 {
+            id: Field,
+        }
+
+        struct unit1 {
+            id: Field,
+        }
+
+        struct unit2 {
+            id: Field,
+        }
+    }
+}
+
+symbols! {
+    struct LinuxSymbols {
+        _text: u64,
+        schedule: u64,
+        init_task: u64,
+        jiffies: u64,
+        linux_banner: Option<u64>,
+    }
+}
+
+enums! {
+    struct LinuxEnums {
+        enum pid_type {
+            PIDTYPE_PID,
+            PIDTYPE_TGID,
+            PIDTYPE_MAX,
+        }
+    }
+}
+
+fn linux_macros(profile: &Profile) -> Vec<Expectation> {
+    let offsets = LinuxOffsets::new(profile).unwrap();
+    let symbols = LinuxSymbols::new(profile).unwrap();
+    let enums = LinuxEnums::new(profile).unwrap();
+
+    let list_head = &offsets.list_head;
+    let task = &offsets.task_struct;
+    let pid_type = &enums.pid_type;
+
+    vec![
+        size("list_head", list_head.len()),
+        size("task_struct", task.len()),
+        size("unit0", offsets.unit0.len()),
+        size("unit2", offsets.unit2.len()),
+        field("list_head", "next", list_head.next),
+        field("list_head", "prev", list_head.prev),
+        field("task_struct", "__state", task.__state),
+        field("task_struct", "flags", task.flags),
+        bitfield(
+            "task_struct",
+            "sched_reset_on_fork",
+            task.sched_reset_on_fork,
+        ),
+        bitfield(
+            "task_struct",
+            "sched_contributes_to_load",
+            task.sched_contributes_to_load,
+        ),
+        bitfield("task_struct", "in_execve", task.in_execve),
+        field("task_struct", "on_cpu", task.on_cpu),
+        field("task_struct", "tasks", task.tasks),
+        field("task_struct", "pid", task.pid),
+        field("task_struct", "tgid", task.tgid),
+        field("task_struct", "comm", task.comm),
+        field("task_struct", "mm", task.mm),
+        field("task_struct", "stack", task.stack),
+        field("task_struct", "stack_vm_area", task.stack_vm_area),
+        field("task_struct", "thread_fn", task.thread_fn),
+        field("task_struct", "nvcsw", task.nvcsw),
+        field("task_struct", "pid_type", task.pid_type),
+        field("unit1", "id", offsets.unit1.id),
+        variant("pid_type", "PIDTYPE_PID", pid_type.PIDTYPE_PID),
+        variant("pid_type", "PIDTYPE_TGID", pid_type.PIDTYPE_TGID),
+        variant("pid_type", "PIDTYPE_MAX", pid_type.PIDTYPE_MAX),
+        symbol(profile, "_text", symbols._text),
+        symbol(profile, "schedule", symbols.schedule),
+        symbol(profile, "init_task", symbols.init_task),
+        symbol(profile, "jiffies", symbols.jiffies),
+        optional_symbol(profile, "linux_banner", symbols.linux_banner),
+    ]
+}
+
+#[test]
+fn linux() {
+    let kernel = Kernel::new(3);
+
+    check_all_codecs(
+        &kernel_profile(&kernel, &ProfileOptions::default()),
+        include_str!("conformance/linux.expected"),
+        linux_macros,
+    );
+}
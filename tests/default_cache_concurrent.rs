@@ -0,0 +1,40 @@
+//! The default cache is process-wide, each scenario runs in its own test
+//! binary.
+
+mod common;
+
+use std::sync::Barrier;
+
+use isr::default_cache;
+
+use self::common::stores_in;
+
+#[test]
+fn concurrent_first_use() {
+    const THREADS: usize = 8;
+
+    let default_directory = tempfile::tempdir().unwrap();
+    std::env::set_var("ISR_CACHE_DIR", default_directory.path());
+
+    let barrier = Barrier::new(THREADS);
+    let caches = std::thread::scope(|scope| {
+        let threads = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|| {
+                    barrier.wait();
+                    default_cache().unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // Every thread gets the same cache.
+    let cache = default_cache().unwrap();
+    assert!(caches.iter().all(|other| std::ptr::eq(cache, *other)));
+    assert!(stores_in(cache, default_directory.path()));
+}
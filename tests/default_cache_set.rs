@@ -0,0 +1,29 @@
+//! The default cache is process-wide, each scenario runs in its own test
+//! binary.
+
+mod common;
+
+use isr::{default_cache, set_default_cache, IsrCache};
+
+use self::common::stores_in;
+
+#[test]
+fn set_before_first_use() {
+    let default_directory = tempfile::tempdir().unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    std::env::set_var("ISR_CACHE_DIR", default_directory.path());
+
+    assert!(set_default_cache(IsrCache::new(directory.path()).unwrap()).is_ok());
+
+    // The configured cache is used instead of the default directory.
+    let cache = default_cache().unwrap();
+    assert!(stores_in(cache, directory.path()));
+    assert!(std::ptr::eq(cache, default_cache().unwrap()));
+    assert_eq!(default_directory.path().read_dir().unwrap().count(), 0);
+
+    // The default cache can only be set once.
+    let other = tempfile::tempdir().unwrap();
+    let rejected = set_default_cache(IsrCache::new(other.path()).unwrap()).unwrap_err();
+    assert!(stores_in(&rejected, other.path()));
+    assert!(std::ptr::eq(cache, default_cache().unwrap()));
+}
@@ -0,0 +1,24 @@
+//! The default cache is process-wide, each scenario runs in its own test
+//! binary.
+
+mod common;
+
+use isr::{default_cache, set_default_cache, IsrCache};
+
+use self::common::stores_in;
+
+#[test]
+fn set_after_first_use() {
+    let default_directory = tempfile::tempdir().unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    std::env::set_var("ISR_CACHE_DIR", default_directory.path());
+
+    // The first use creates the cache in the default directory.
+    let cache = default_cache().unwrap();
+    assert!(stores_in(cache, default_directory.path()));
+
+    // The cache is handed back, and the default cache is unchanged.
+    let rejected = set_default_cache(IsrCache::new(directory.path()).unwrap()).unwrap_err();
+    assert!(stores_in(&rejected, directory.path()));
+    assert!(std::ptr::eq(cache, default_cache().unwrap()));
+}
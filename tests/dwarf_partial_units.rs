@@ -0,0 +1,35 @@
+//! Types defined in partial units are collected where the units are
+//! imported.
+
+mod common;
+
+use isr::ProfileOptions;
+
+use self::common::{kernel::Kernel, kernel_profile};
+
+#[test]
+fn imported_partial_unit() {
+    let kernel = Kernel::with_partial_unit(3);
+
+    for threads in [1, 2] {
+        let options = ProfileOptions::default()
+            .with_threads(threads)
+            .with_record_provenance(true);
+        let profile = kernel_profile(&kernel, &options);
+
+        let cred = profile.find_struct("cred").unwrap();
+        assert_eq!(cred.size, 8);
+        assert_eq!(cred.fields["usage"].offset, 0);
+        assert_eq!(cred.fields["uid"].offset, 4);
+
+        // The declaration is resolved in the line program of the partial
+        // unit.
+        let provenance = profile.type_provenance("cred").unwrap();
+        assert!(provenance.file.as_ref().unwrap().ends_with("linux/cred.h"));
+        assert_eq!(provenance.line, Some(111));
+
+        // The types of the other units are still collected.
+        assert!(profile.find_struct("task_struct").is_some());
+        assert!(profile.find_struct("unit2").is_some());
+    }
+}
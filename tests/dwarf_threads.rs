@@ -0,0 +1,70 @@
+//! Types collected on several threads are the same as those collected on
+//! a single one.
+
+mod common;
+
+use isr::{
+    cache::{Codec as _, JsonCodec},
+    ProfileOptions,
+};
+
+use self::common::kernel::Kernel;
+
+/// Creates the profile of the kernel and returns it encoded.
+fn encoded_profile(kernel: &Kernel, options: &ProfileOptions) -> Vec<u8> {
+    let (image, systemmap) = kernel.files();
+
+    let mut encoded = Vec::new();
+    isr::dwarf::create_profile_with_options(image, systemmap, options, |profile| {
+        JsonCodec::encode(&mut encoded, profile)
+    })
+    .unwrap();
+
+    encoded
+}
+
+fn assert_same_profiles(kernel: &Kernel, threads: &[usize]) {
+    let serial = encoded_profile(kernel, &ProfileOptions::default().with_threads(1));
+
+    for &threads in threads {
+        let parallel = encoded_profile(kernel, &ProfileOptions::default().with_threads(threads));
+        assert!(
+            parallel == serial,
+            "profile with {threads} threads differs from the serial one"
+        );
+    }
+}
+
+#[test]
+fn parallel_matches_serial() {
+    assert_same_profiles(&Kernel::new(16), &[2, 3, 4, 7, 16]);
+}
+
+#[test]
+fn more_threads_than_units() {
+    assert_same_profiles(&Kernel::new(2), &[3, 8, 64]);
+}
+
+#[test]
+fn zero_threads() {
+    // `0` collects the types sequentially, like `1`.
+    assert_same_profiles(&Kernel::new(9), &[0]);
+}
+
+#[test]
+fn options_are_kept() {
+    // Options affecting the types apply to every thread.
+    let kernel = Kernel::new(10);
+    let options = ProfileOptions::default()
+        .with_forward_declaration_stubs(true)
+        .with_max_name_length(24);
+
+    let serial = encoded_profile(&kernel, &options.clone().with_threads(1));
+    for threads in [2, 5] {
+        let parallel = encoded_profile(&kernel, &options.clone().with_threads(threads));
+        assert!(
+            parallel == serial,
+            "profile with {threads} threads differs from the serial one"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use isr::macros::{enums, Error};
+
+use self::common::windows;
+
+enums! {
+    struct Enums {
+        #[isr(derive_tryfrom = PoolType)]
+        enum _POOL_TYPE {
+            NonPagedPool,
+            NonPagedPoolExecute,
+            PagedPool,
+            NonPagedPoolMustSucceed,
+        }
+    }
+}
+
+#[test]
+fn conversion_table() {
+    let profile = windows::profile();
+    let enums = Enums::new(&profile).unwrap();
+    let pool_type = &enums._POOL_TYPE;
+
+    assert_eq!(pool_type.NonPagedPool, 0);
+    assert_eq!(pool_type.NonPagedPoolExecute, 0);
+    assert_eq!(pool_type.PagedPool, 1);
+    assert_eq!(pool_type.NonPagedPoolMustSucceed, 2);
+
+    assert_eq!(pool_type.name_of(1), Some("PagedPool"));
+    assert_eq!(pool_type.value_of("NonPagedPoolMustSucceed"), Some(2));
+}
+
+#[test]
+fn derive_tryfrom() {
+    let profile = windows::profile();
+    let enums = Enums::new(&profile).unwrap();
+    let pool_type = &enums._POOL_TYPE;
+
+    assert_eq!(PoolType::VARIANTS.len(), 4);
+    for &variant in PoolType::VARIANTS {
+        let value = pool_type.to_value(variant);
+        assert_eq!(pool_type.value_of(variant.name()), Some(value));
+    }
+
+    // The first declared variant wins for shared values.
+    assert_eq!(pool_type.try_from_value(0).unwrap(), PoolType::NonPagedPool);
+    assert_eq!(pool_type.try_from_value(1).unwrap(), PoolType::PagedPool);
+    assert_eq!(
+        pool_type.try_from_value(2).unwrap(),
+        PoolType::NonPagedPoolMustSucceed
+    );
+    assert!(matches!(
+        pool_type.try_from_value(3),
+        Err(Error::Conversion(_))
+    ));
+}
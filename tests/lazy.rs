@@ -0,0 +1,148 @@
+mod common;
+
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Barrier,
+    },
+};
+
+use isr::{
+    macros::{symbols, Error, FromProfile, IsrLazy},
+    types::Types,
+    Profile, Symbols,
+};
+
+use self::common::windows;
+
+symbols! {
+    struct Kernel {
+        PsActiveProcessHead: u64,
+        PsInitialSystemProcess: u64,
+    }
+}
+
+/// Counts the resolutions that succeeded.
+struct Counted {
+    head: u64,
+}
+
+static RESOLVED: AtomicUsize = AtomicUsize::new(0);
+
+impl FromProfile for Counted {
+    fn from_profile(profile: &Profile) -> Result<Self, Error> {
+        let head = Kernel::from_profile(profile)?.PsActiveProcessHead;
+        RESOLVED.fetch_add(1, Ordering::Relaxed);
+        Ok(Self { head })
+    }
+}
+
+/// Creates a profile without any symbols.
+fn empty_profile() -> Profile<'static> {
+    Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), Types::default())
+}
+
+#[test]
+fn racing_initializers() {
+    static KERNEL: IsrLazy<Kernel> = IsrLazy::new();
+    static COUNTED: IsrLazy<Counted> = IsrLazy::new();
+
+    const THREADS: usize = 16;
+
+    let profile = windows::profile();
+    let barrier = Barrier::new(THREADS);
+
+    let results = std::thread::scope(|scope| {
+        let handles = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|| {
+                    barrier.wait();
+                    let kernel = KERNEL.get_or_init(&profile).unwrap();
+                    let counted = COUNTED.get_or_init(&profile).unwrap();
+                    (kernel as *const Kernel as usize, counted.head)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // Every thread sees the same value.
+    let kernel = KERNEL.get().unwrap();
+    assert_eq!(kernel.PsActiveProcessHead, 0x43_7bc0);
+    for (address, head) in results {
+        assert_eq!(address, kernel as *const Kernel as usize);
+        assert_eq!(head, 0x43_7bc0);
+    }
+
+    // Racing threads may resolve the value more than once, but only one
+    // resolution is kept.
+    let resolved = RESOLVED.load(Ordering::Relaxed);
+    assert!((1..=THREADS).contains(&resolved));
+    assert_eq!(COUNTED.get().unwrap().head, 0x43_7bc0);
+}
+
+#[test]
+fn failing_then_succeeding() {
+    static KERNEL: IsrLazy<Kernel> = IsrLazy::new();
+
+    let empty = empty_profile();
+    let profile = windows::profile();
+
+    // The failed resolution doesn't poison the value.
+    let result = KERNEL.get_or_init(&empty);
+    assert!(matches!(result, Err(Error::SymbolsNotFound(_))));
+    assert!(!KERNEL.is_initialized());
+    assert!(KERNEL.get().is_none());
+
+    let result = KERNEL.get_or_init(&empty);
+    assert!(result.is_err());
+    assert!(!KERNEL.is_initialized());
+
+    let kernel = KERNEL.get_or_init(&profile).unwrap();
+    assert_eq!(kernel.PsInitialSystemProcess, 0x57_33a0);
+    assert!(KERNEL.is_initialized());
+
+    // Once initialized, the value is kept, whatever the profile.
+    let kernel = KERNEL.get_or_init(&empty).unwrap();
+    assert_eq!(kernel.PsActiveProcessHead, 0x43_7bc0);
+    assert_eq!(
+        unsafe { KERNEL.get_unchecked() }.PsActiveProcessHead,
+        0x43_7bc0
+    );
+}
+
+#[test]
+fn failing_racing_initializers() {
+    static KERNEL: IsrLazy<Kernel> = IsrLazy::new();
+
+    const THREADS: usize = 8;
+
+    let empty = empty_profile();
+    let profile = windows::profile();
+    let barrier = Barrier::new(THREADS);
+
+    // Half of the threads resolve the value from a profile without the
+    // symbols.
+    std::thread::scope(|scope| {
+        for index in 0..THREADS {
+            let (barrier, empty, profile) = (&barrier, &empty, &profile);
+            scope.spawn(move || {
+                barrier.wait();
+
+                if index % 2 == 0 {
+                    let _ = KERNEL.get_or_init(empty);
+                }
+                else {
+                    KERNEL.get_or_init(profile).unwrap();
+                }
+            });
+        }
+    });
+
+    assert_eq!(KERNEL.get().unwrap().PsActiveProcessHead, 0x43_7bc0);
+}
@@ -0,0 +1,504 @@
+mod common;
+
+use isr::macros::{
+    linux::{DentryPathReader, Error, ListHeadIter, Memory, RbTreeIter},
+    offsets, Field,
+};
+
+use self::common::linux;
+
+offsets! {
+    struct Offsets {
+        struct list_head {
+            next: Field,
+        }
+
+        struct rb_node {
+            rb_right: Field,
+            rb_left: Field,
+        }
+
+        struct vmap_area {
+            rb_node: Field,
+        }
+
+        struct qstr {
+            len: Field,
+            name: Field,
+        }
+
+        struct dentry {
+            d_parent: Field,
+            d_name: Field,
+        }
+
+        struct task_struct {
+            tasks: Field,
+        }
+    }
+}
+
+/// Base address of the synthetic memory.
+const BASE: u64 = 0xffff_8880_0000_0000;
+
+/// Size of the synthetic memory.
+const SIZE: u64 = 0x1_0000;
+
+/// Address of the list head.
+const HEAD: u64 = BASE + 0x100;
+
+/// Addresses of the `task_struct` structures.
+const TASKS: [u64; 3] = [BASE + 0x1000, BASE + 0x4000, BASE + 0x7000];
+
+/// Addresses of the `vmap_area` structures, in tree order.
+const AREAS: [u64; 5] = [
+    BASE + 0xa000,
+    BASE + 0xa100,
+    BASE + 0xa200,
+    BASE + 0xa300,
+    BASE + 0xa400,
+];
+
+/// Read outside of the synthetic memory.
+#[derive(Debug)]
+struct Unmapped(u64);
+
+impl std::fmt::Display for Unmapped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unmapped address {:#x}", self.0)
+    }
+}
+
+impl std::error::Error for Unmapped {}
+
+/// Synthetic, zero-initialized memory of `SIZE` bytes at `BASE`.
+struct SyntheticMemory {
+    data: Vec<u8>,
+}
+
+impl SyntheticMemory {
+    fn new() -> Self {
+        Self {
+            data: vec![0; SIZE as usize],
+        }
+    }
+
+    fn write(&mut self, address: u64, bytes: &[u8]) {
+        let start = (address - BASE) as usize;
+        self.data[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn write_u32(&mut self, address: u64, value: u32) {
+        self.write(address, &value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, address: u64, value: u64) {
+        self.write(address, &value.to_le_bytes());
+    }
+}
+
+impl Memory for SyntheticMemory {
+    type Error = Unmapped;
+
+    fn read(&self, address: u64, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let start = address
+            .checked_sub(BASE)
+            .filter(|start| start + buffer.len() as u64 <= SIZE)
+            .ok_or(Unmapped(address))?;
+
+        let start = start as usize;
+        buffer.copy_from_slice(&self.data[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+fn offsets() -> Offsets {
+    Offsets::new(&linux::profile()).unwrap()
+}
+
+//
+// list_head
+//
+
+/// Returns the address of the `tasks` of a task.
+fn tasks(offsets: &Offsets, task: u64) -> u64 {
+    task + offsets.task_struct.tasks.offset
+}
+
+/// Creates a memory with the list head linked to the given tasks, in
+/// order.
+fn task_list(offsets: &Offsets, tasks: &[u64]) -> SyntheticMemory {
+    let mut memory = SyntheticMemory::new();
+
+    let mut entry = HEAD;
+    for &task in tasks {
+        memory.write_u64(entry, self::tasks(offsets, task));
+        entry = self::tasks(offsets, task);
+    }
+    memory.write_u64(entry, HEAD);
+
+    memory
+}
+
+fn list<'m>(offsets: &Offsets, memory: &'m SyntheticMemory) -> ListHeadIter<'m, SyntheticMemory> {
+    ListHeadIter::new(
+        memory,
+        HEAD,
+        offsets.list_head.next,
+        offsets.task_struct.tasks,
+    )
+}
+
+#[test]
+fn list_head() {
+    let offsets = offsets();
+    let memory = task_list(&offsets, &TASKS);
+
+    let tasks = list(&offsets, &memory)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(tasks, TASKS);
+}
+
+#[test]
+fn list_head_empty() {
+    let offsets = offsets();
+    let memory = task_list(&offsets, &[]);
+
+    assert_eq!(list(&offsets, &memory).count(), 0);
+}
+
+#[test]
+fn list_head_max_entries() {
+    let offsets = offsets();
+    let memory = task_list(&offsets, &TASKS);
+
+    let mut iter = list(&offsets, &memory).with_max_entries(2);
+    assert_eq!(iter.next().unwrap().unwrap(), TASKS[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), TASKS[1]);
+    assert!(matches!(iter.next(), Some(Err(Error::TooManyEntries(2)))));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn list_head_truncated() {
+    let offsets = offsets();
+    let mut memory = task_list(&offsets, &TASKS);
+
+    // The `next` of the second task straddles the end of the memory.
+    let outside = BASE + SIZE - 4 - offsets.task_struct.tasks.offset;
+    memory.write_u64(tasks(&offsets, TASKS[0]), tasks(&offsets, outside));
+
+    let mut iter = list(&offsets, &memory);
+    assert_eq!(iter.next().unwrap().unwrap(), TASKS[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), outside);
+
+    match iter.next() {
+        Some(Err(Error::Read { address, source })) => {
+            assert_eq!(address, tasks(&offsets, outside));
+            assert_eq!(source.0, address);
+        }
+        other => panic!("expected a read error, got {other:?}"),
+    }
+    assert!(iter.next().is_none());
+}
+
+//
+// rb_root
+//
+
+/// Returns the address of the `rb_node` of a `vmap_area`.
+fn node(offsets: &Offsets, area: u64) -> u64 {
+    area + offsets.vmap_area.rb_node.offset
+}
+
+/// Links the children of the `rb_node` of a `vmap_area`.
+fn link(
+    offsets: &Offsets,
+    memory: &mut SyntheticMemory,
+    area: u64,
+    left: Option<u64>,
+    right: Option<u64>,
+) {
+    let node = self::node(offsets, area);
+    let left = left.map_or(0, |left| self::node(offsets, left));
+    let right = right.map_or(0, |right| self::node(offsets, right));
+
+    memory.write_u64(node + offsets.rb_node.rb_left.offset, left);
+    memory.write_u64(node + offsets.rb_node.rb_right.offset, right);
+}
+
+/// Creates a memory with the tree:
+///
+/// ```text
+///       1
+///     /   \
+///    0     3
+///         / \
+///        2   4
+/// ```
+///
+/// Returns the memory and the root node.
+fn tree(offsets: &Offsets) -> (SyntheticMemory, u64) {
+    let mut memory = SyntheticMemory::new();
+
+    link(
+        offsets,
+        &mut memory,
+        AREAS[1],
+        Some(AREAS[0]),
+        Some(AREAS[3]),
+    );
+    link(offsets, &mut memory, AREAS[0], None, None);
+    link(
+        offsets,
+        &mut memory,
+        AREAS[3],
+        Some(AREAS[2]),
+        Some(AREAS[4]),
+    );
+    link(offsets, &mut memory, AREAS[2], None, None);
+    link(offsets, &mut memory, AREAS[4], None, None);
+
+    (memory, node(offsets, AREAS[1]))
+}
+
+fn rb_tree<'m>(
+    offsets: &Offsets,
+    memory: &'m SyntheticMemory,
+    root: u64,
+) -> RbTreeIter<'m, SyntheticMemory> {
+    RbTreeIter::new(
+        memory,
+        root,
+        offsets.rb_node.rb_left,
+        offsets.rb_node.rb_right,
+        offsets.vmap_area.rb_node,
+    )
+}
+
+#[test]
+fn rb_tree_in_order() {
+    let offsets = offsets();
+    let (memory, root) = tree(&offsets);
+
+    let areas = rb_tree(&offsets, &memory, root)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(areas, AREAS);
+}
+
+#[test]
+fn rb_tree_empty_root() {
+    let offsets = offsets();
+    let memory = SyntheticMemory::new();
+
+    // The root isn't read.
+    assert_eq!(rb_tree(&offsets, &memory, 0).count(), 0);
+}
+
+#[test]
+fn rb_tree_max_entries() {
+    let offsets = offsets();
+    let (memory, root) = tree(&offsets);
+
+    // The nodes are counted as they're visited, not as they're returned.
+    let mut iter = rb_tree(&offsets, &memory, root).with_max_entries(3);
+    assert_eq!(iter.next().unwrap().unwrap(), AREAS[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), AREAS[1]);
+    assert!(matches!(iter.next(), Some(Err(Error::TooManyEntries(3)))));
+    assert!(iter.next().is_none());
+
+    // Exactly the maximum number of nodes.
+    let iter = rb_tree(&offsets, &memory, root).with_max_entries(5);
+    assert_eq!(iter.filter(Result::is_ok).count(), 5);
+}
+
+#[test]
+fn rb_tree_loop() {
+    let offsets = offsets();
+    let (mut memory, root) = tree(&offsets);
+
+    // The rightmost node links back to the root.
+    link(&offsets, &mut memory, AREAS[4], Some(AREAS[1]), None);
+
+    let results = rb_tree(&offsets, &memory, root).collect::<Vec<_>>();
+    assert_eq!(results.len(), 5);
+    assert!(results[..4].iter().all(Result::is_ok));
+    assert!(matches!(results[4], Err(Error::Loop(node)) if node == root));
+}
+
+#[test]
+fn rb_tree_truncated() {
+    let offsets = offsets();
+    let (mut memory, root) = tree(&offsets);
+
+    // The right child of the root straddles the end of the memory.
+    let outside = BASE + SIZE - 4;
+    memory.write_u64(root + offsets.rb_node.rb_right.offset, outside);
+
+    let mut iter = rb_tree(&offsets, &memory, root);
+    assert_eq!(iter.next().unwrap().unwrap(), AREAS[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), AREAS[1]);
+
+    match iter.next() {
+        Some(Err(Error::Read { address, .. })) => {
+            assert_eq!(address, outside + offsets.rb_node.rb_left.offset);
+        }
+        other => panic!("expected a read error, got {other:?}"),
+    }
+    assert!(iter.next().is_none());
+}
+
+//
+// dentry
+//
+
+/// Address of the names of the dentries.
+const NAMES: u64 = BASE + 0xc000;
+
+/// Creates a dentry with the given parent and name, and returns its
+/// address. A dentry without a parent is a root.
+fn dentry(
+    offsets: &Offsets,
+    memory: &mut SyntheticMemory,
+    dentry: u64,
+    parent: Option<u64>,
+    name: &[u8],
+) -> u64 {
+    let name_address = NAMES + (dentry - BASE) / 0x10;
+    memory.write(name_address, name);
+
+    let qstr = dentry + offsets.dentry.d_name.offset;
+    memory.write_u64(
+        dentry + offsets.dentry.d_parent.offset,
+        parent.unwrap_or(dentry),
+    );
+    memory.write_u32(qstr + offsets.qstr.len.offset, name.len() as u32);
+    memory.write_u64(qstr + offsets.qstr.name.offset, name_address);
+
+    dentry
+}
+
+/// Creates the dentries of `/usr/lib/modules`, and returns them from the
+/// root to the leaf.
+fn path(offsets: &Offsets, memory: &mut SyntheticMemory) -> [u64; 4] {
+    let root = dentry(offsets, memory, BASE + 0x2000, None, b"/");
+    let usr = dentry(offsets, memory, BASE + 0x2100, Some(root), b"usr");
+    let lib = dentry(offsets, memory, BASE + 0x2200, Some(usr), b"lib");
+    let modules = dentry(offsets, memory, BASE + 0x2300, Some(lib), b"modules");
+
+    [root, usr, lib, modules]
+}
+
+fn reader(offsets: &Offsets) -> DentryPathReader {
+    DentryPathReader::new(
+        offsets.dentry.d_parent,
+        offsets.dentry.d_name,
+        offsets.qstr.len,
+        offsets.qstr.name,
+    )
+}
+
+#[test]
+fn dentry_path() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [root, usr, _, modules] = path(&offsets, &mut memory);
+
+    let reader = reader(&offsets);
+    assert_eq!(reader.read(&memory, modules).unwrap(), "/usr/lib/modules");
+    assert_eq!(reader.read(&memory, usr).unwrap(), "/usr");
+    assert_eq!(reader.read(&memory, root).unwrap(), "/");
+}
+
+#[test]
+fn dentry_path_invalid_utf8() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [_, _, lib, _] = path(&offsets, &mut memory);
+    let file = dentry(&offsets, &mut memory, BASE + 0x2400, Some(lib), b"a\xffb");
+
+    assert_eq!(
+        reader(&offsets).read(&memory, file).unwrap(),
+        "/usr/lib/a\u{fffd}b"
+    );
+}
+
+#[test]
+fn dentry_path_max_depth() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [_, _, lib, modules] = path(&offsets, &mut memory);
+
+    let reader = reader(&offsets).with_max_depth(2);
+    assert_eq!(reader.read(&memory, lib).unwrap(), "/usr/lib");
+    assert!(matches!(
+        reader.read(&memory, modules),
+        Err(Error::TooManyEntries(2))
+    ));
+}
+
+#[test]
+fn dentry_path_loop() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [_, usr, lib, modules] = path(&offsets, &mut memory);
+
+    // `usr` is its own grandchild.
+    memory.write_u64(usr + offsets.dentry.d_parent.offset, lib);
+
+    let reader = reader(&offsets).with_max_depth(8);
+    assert!(matches!(
+        reader.read(&memory, modules),
+        Err(Error::TooManyEntries(8))
+    ));
+}
+
+#[test]
+fn dentry_path_null_parent() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [_, usr, _, modules] = path(&offsets, &mut memory);
+    memory.write_u64(usr + offsets.dentry.d_parent.offset, 0);
+
+    assert!(matches!(
+        reader(&offsets).read(&memory, modules),
+        Err(Error::NullLink(address)) if address == usr
+    ));
+}
+
+#[test]
+fn dentry_path_invalid_length() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [_, _, _, modules] = path(&offsets, &mut memory);
+
+    let qstr = modules + offsets.dentry.d_name.offset;
+    memory.write_u32(qstr + offsets.qstr.len.offset, 256);
+
+    assert!(matches!(
+        reader(&offsets).read(&memory, modules),
+        Err(Error::InvalidStringLength {
+            length: 256,
+            maximum_length: 255
+        })
+    ));
+}
+
+#[test]
+fn dentry_path_truncated_name() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let [_, _, _, modules] = path(&offsets, &mut memory);
+
+    // The name straddles the end of the memory.
+    let name = BASE + SIZE - 4;
+    let qstr = modules + offsets.dentry.d_name.offset;
+    memory.write_u64(qstr + offsets.qstr.name.offset, name);
+
+    assert!(matches!(
+        reader(&offsets).read(&memory, modules),
+        Err(Error::Read { address, .. }) if address == name
+    ));
+}
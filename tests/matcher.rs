@@ -0,0 +1,90 @@
+mod common;
+
+use std::borrow::Cow;
+
+use isr::{
+    macros::{__private::ProfileExt, offsets, CaseInsensitive, Error, Field, StripPrefix},
+    types::{BaseRef, StructKind, Type, Types},
+    Profile, Symbols,
+};
+
+use self::common::{add_struct, struct_};
+
+offsets! {
+    struct Offsets {
+        struct _DEVICE {
+            #[isr(matcher = StripPrefix("m_"))]
+            Flags: Field,
+
+            #[isr(matcher = CaseInsensitive)]
+            ReferenceCount: Field,
+
+            #[isr(matcher = CaseInsensitive)]
+            Type: Field,
+
+            #[isr(matcher = StripPrefix("m_"))]
+            Name: Field,
+        }
+    }
+}
+
+/// Creates a profile with the fields of `_DEVICE` named inconsistently.
+fn profile() -> Profile<'static> {
+    let mut types = Types::default();
+
+    add_struct(
+        &mut types,
+        "_DEVICE_HEADER",
+        StructKind::Struct,
+        8,
+        vec![("m_Name", 0, Type::Base(BaseRef::U64))],
+    );
+
+    add_struct(
+        &mut types,
+        "_DEVICE",
+        StructKind::Struct,
+        32,
+        vec![
+            ("m_Flags", 0, Type::Base(BaseRef::U32)),
+            ("referencecount", 4, Type::Base(BaseRef::U32)),
+            ("TYPE", 8, Type::Base(BaseRef::U16)),
+            ("Type", 10, Type::Base(BaseRef::U16)),
+            ("Header", 16, struct_("_DEVICE_HEADER")),
+        ],
+    );
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+}
+
+#[test]
+fn matchers() {
+    let offsets = Offsets::new(&profile()).unwrap();
+
+    assert_eq!(offsets._DEVICE.Flags.offset, 0);
+    assert_eq!(offsets._DEVICE.ReferenceCount.offset, 4);
+
+    // The exact match is preferred.
+    assert_eq!(offsets._DEVICE.Type.offset, 10);
+
+    // Fields of nested structs are matched too.
+    assert_eq!(offsets._DEVICE.Name.offset, 16);
+    assert_eq!(offsets._DEVICE.Name.size, 8);
+}
+
+#[test]
+fn closure() {
+    let profile = profile();
+
+    let matcher = |field_name: &str, requested: &str| field_name.ends_with(requested);
+    let descriptor = profile
+        .find_field_descriptor_with("_DEVICE", "count", &matcher)
+        .unwrap();
+    assert_eq!(descriptor.offset(), 4);
+
+    let result = profile.find_field_descriptor_with("_DEVICE", "Missing", &matcher);
+    assert!(matches!(result, Err(Error::FieldNotFound { .. })));
+
+    let result = profile.find_field_descriptor_with("_DEVICE", "flags", &CaseInsensitive);
+    assert!(matches!(result, Err(Error::FieldNotFound { .. })));
+}
@@ -0,0 +1,266 @@
+mod common;
+
+use std::borrow::Cow;
+
+use isr::{
+    macros::{__private::ProfileExt, offsets, Bitfield, Error, Field, FieldDescriptor},
+    types::{ArrayRef, BaseRef, Field as CoreField, Struct, StructKind, StructRef, Type, Types},
+    Profile, Symbols,
+};
+
+use self::common::windows;
+
+offsets! {
+    struct Offsets {
+        struct _EX_FAST_REF {
+            RefCnt: Bitfield,
+        }
+
+        struct _EPROCESS {
+            Wow64Process: Field,
+        }
+    }
+}
+
+offsets! {
+    struct PackedOffsets {
+        struct _MESSAGE {
+            Type: Field,
+            Length: Field,
+            Data: Field,
+        }
+    }
+}
+
+offsets! {
+    struct ForwardOffsets {
+        struct _OBJECT {
+            Header: Field,
+        }
+    }
+}
+
+offsets! {
+    struct OptionalForwardOffsets {
+        struct _OUTER {
+            Object: Field,
+            Header: Option<Field>,
+        }
+
+        struct _FORWARD {}
+    }
+}
+
+/// Creates a profile with a field of a forward-declared (zero-sized)
+/// struct, nested in another struct.
+fn forward_profile() -> Profile<'static> {
+    let mut types = Types::default();
+    for (name, size, fields) in [
+        ("_FORWARD", 0, vec![]),
+        ("_OBJECT", 8, vec![("Header", 0, "_FORWARD")]),
+        ("_OUTER", 16, vec![("Object", 8, "_OBJECT")]),
+    ] {
+        let fields = fields
+            .into_iter()
+            .map(|(name, offset, type_name)| {
+                let type_ = Type::Struct(StructRef {
+                    name: Cow::Borrowed(type_name),
+                });
+                (Cow::Borrowed(name), CoreField { offset, type_ })
+            })
+            .collect();
+
+        types.structs.insert(
+            Cow::Borrowed(name),
+            Struct {
+                kind: StructKind::Struct,
+                size,
+                fields,
+                incomplete: false,
+            },
+        );
+    }
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+}
+
+/// Creates an i686 profile with a packed structure ending with a
+/// flexible array member.
+fn packed_profile() -> Profile<'static> {
+    let fields = [
+        ("Type", 0, Type::Base(BaseRef::U8)),
+        ("Length", 1, Type::Base(BaseRef::U32)),
+        // The flexible array member is recorded with one element.
+        (
+            "Data",
+            5,
+            Type::Array(ArrayRef {
+                subtype: Box::new(Type::Base(BaseRef::U8)),
+                dims: [1].into_iter().collect(),
+                size: 1,
+            }),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, offset, type_)| (Cow::Borrowed(name), CoreField { offset, type_ }))
+    .collect();
+
+    let mut types = Types::default();
+    types.structs.insert(
+        Cow::Borrowed("_MESSAGE"),
+        Struct {
+            kind: StructKind::Struct,
+            size: 5,
+            fields,
+            incomplete: false,
+        },
+    );
+
+    Profile::new(Cow::Borrowed("X86"), Symbols::default(), types)
+}
+
+#[test]
+fn end() {
+    let offsets = Offsets::new(&windows::profile()).unwrap();
+
+    assert_eq!(offsets._EPROCESS.Wow64Process.offset, 1064);
+    assert_eq!(offsets._EPROCESS.Wow64Process.end(), 1072);
+
+    // The end of a bitfield is the end of its underlying field.
+    assert_eq!(offsets._EX_FAST_REF.RefCnt.bit_length, 4);
+    assert_eq!(offsets._EX_FAST_REF.RefCnt.end(), 8);
+}
+
+#[test]
+fn descriptor_end() {
+    let profile = windows::profile();
+
+    let field = profile.core_field("_EPROCESS", "Wow64Process").unwrap();
+    let descriptor = FieldDescriptor::from_core(&profile, field).unwrap();
+    assert!(matches!(descriptor, FieldDescriptor::Field(_)));
+    assert_eq!(descriptor.end(), 1072);
+
+    let field = profile.core_field("_EX_FAST_REF", "RefCnt").unwrap();
+    let descriptor = FieldDescriptor::from_core(&profile, field).unwrap();
+    assert!(matches!(descriptor, FieldDescriptor::Bitfield(_)));
+    assert_eq!(descriptor.end(), 8);
+}
+
+#[test]
+fn end_saturates() {
+    let field = Field {
+        offset: u64::MAX - 4,
+        size: 8,
+    };
+    assert_eq!(field.end(), u64::MAX);
+
+    let bitfield = Bitfield {
+        offset: u64::MAX,
+        size: 4,
+        bit_position: 0,
+        bit_length: 1,
+    };
+    assert_eq!(bitfield.end(), u64::MAX);
+    assert_eq!(FieldDescriptor::Bitfield(bitfield).end(), u64::MAX);
+}
+
+#[test]
+fn packed_flexible_array() {
+    let profile = packed_profile();
+
+    // The unaligned field still lies within the structure.
+    assert_eq!(
+        profile.field_extends_struct("_MESSAGE", "Type"),
+        Some(false)
+    );
+    assert_eq!(
+        profile.field_extends_struct("_MESSAGE", "Length"),
+        Some(false)
+    );
+    assert_eq!(profile.field_extends_struct("_MESSAGE", "Data"), Some(true));
+    assert_eq!(profile.field_extends_struct("_MESSAGE", "Missing"), None);
+    assert_eq!(profile.field_extends_struct("_MISSING", "Data"), None);
+
+    let offsets = PackedOffsets::new(&profile).unwrap();
+    assert_eq!(offsets._MESSAGE.Length.offset, 1);
+    assert_eq!(offsets._MESSAGE.Length.end(), 5);
+    assert_eq!(offsets._MESSAGE.len(), 5);
+    assert_eq!(offsets._MESSAGE.effective_len(), 6);
+    assert!(offsets._MESSAGE.exceeds_len());
+}
+
+#[test]
+fn field_type() {
+    let profile = windows::profile();
+
+    let type_ = profile.find_field_type("_EPROCESS", "ImageFileName");
+    assert!(matches!(type_, Some(Type::Array(array)) if array.size == 15));
+
+    // Found in the nested `_KPROCESS`.
+    let type_ = profile.find_field_type("_EPROCESS", "DirectoryTableBase");
+    assert!(matches!(type_, Some(Type::Base(BaseRef::U64))));
+
+    assert!(profile.find_field_type("_EPROCESS", "Missing").is_none());
+    assert!(profile.find_field_type("_MISSING", "Pcb").is_none());
+}
+
+#[test]
+fn zero_sized_field() {
+    let profile = forward_profile();
+
+    let err = ForwardOffsets::new(&profile).err().unwrap();
+    assert!(
+        matches!(&err, Error::ZeroSizedField { type_name, field_name }
+            if type_name == "_OBJECT" && field_name == "Header"),
+        "{err}"
+    );
+
+    // Also when found in a nested struct.
+    let err = profile
+        .find_field_descriptor("_OUTER", "Header")
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::ZeroSizedField { type_name, .. } if type_name == "_OUTER"),
+        "{err}"
+    );
+
+    // Optional fields resolve to `None`, and the empty struct can be
+    // declared without fields.
+    let offsets = OptionalForwardOffsets::new(&profile).unwrap();
+    assert_eq!(offsets._OUTER.Object.offset, 8);
+    assert!(offsets._OUTER.Header.is_none());
+    assert!(offsets._FORWARD.is_empty());
+    assert_eq!(profile.struct_size("_FORWARD"), Some(0));
+}
+
+#[test]
+fn from_core() {
+    let profile = windows::profile();
+
+    let field = profile.core_field("_EPROCESS", "UniqueProcessId").unwrap();
+    let converted = Field::from_core(&profile, field).unwrap();
+    assert_eq!((converted.offset, converted.size), (744, 8));
+    assert!(Bitfield::from_core(&profile, field).is_none());
+
+    let field = profile
+        .core_field("_EPROCESS", "BreakOnTermination")
+        .unwrap();
+    let converted = Bitfield::from_core(&profile, field).unwrap();
+    assert_eq!(
+        (
+            converted.offset,
+            converted.size,
+            converted.bit_position,
+            converted.bit_length
+        ),
+        (780, 4, 13, 1)
+    );
+
+    // Only the fields declared in the struct itself.
+    assert!(profile
+        .core_field("_EPROCESS", "DirectoryTableBase")
+        .is_none());
+    assert!(profile
+        .core_field("_KPROCESS", "DirectoryTableBase")
+        .is_some());
+}
@@ -0,0 +1,99 @@
+//! Measures the heap retained by the parts of a profile, with a counting
+//! global allocator. This test binary has a single test, so that no other
+//! test allocates concurrently.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use isr::{
+    cache::{Codec, JsonCodec},
+    types::{BaseRef, Field, Struct, StructKind, Type, Types},
+    Profile, Symbols,
+};
+
+/// Counts the bytes currently allocated on the heap.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the value built by `f` and the heap bytes it retains.
+fn retained<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = f();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+    (value, after.saturating_sub(before))
+}
+
+/// Creates a profile whose types, like in a real kernel profile, are much
+/// larger than its symbols.
+fn profile() -> Profile<'static> {
+    let symbols = (0..256)
+        .map(|index| (Cow::Owned(format!("Symbol{index}")), 0x1000 + index * 0x10))
+        .collect();
+
+    let mut types = Types::default();
+    for index in 0..256 {
+        let fields = (0..32)
+            .map(|field| {
+                let name = Cow::Owned(format!("Field{field}"));
+                let offset = field * 8;
+                let type_ = Type::Base(BaseRef::U64);
+                (name, Field { offset, type_ })
+            })
+            .collect();
+
+        types.structs.insert(
+            Cow::Owned(format!("_STRUCT{index}")),
+            Struct {
+                kind: StructKind::Struct,
+                size: 32 * 8,
+                fields,
+                incomplete: false,
+            },
+        );
+    }
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols(symbols), types)
+}
+
+#[test]
+fn symbols_only() {
+    let profile = profile();
+    let mut encoded = Vec::new();
+    JsonCodec::encode(&mut encoded, &profile).unwrap();
+    drop(profile);
+
+    let (full, full_size) = retained(|| JsonCodec::decode(&encoded).unwrap().into_owned());
+    let (symbols, symbols_size) = retained(|| {
+        let mut profile = JsonCodec::decode(&encoded).unwrap();
+        profile.take_symbols().into_owned()
+    });
+
+    // The symbols outlive the buffer they were decoded from.
+    drop(encoded);
+    assert_eq!(symbols.0.len(), full.symbols().count());
+
+    // The types, dropped with the rest of the profile, dominate.
+    assert!(
+        symbols_size * 10 < full_size,
+        "symbols retain {symbols_size} of {full_size} bytes"
+    );
+}
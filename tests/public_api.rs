@@ -0,0 +1,141 @@
+//! Names every public item of the sub-crates through the facade, so that an
+//! item dropped from a re-export fails to compile. New public items belong
+//! here, and in the prelude if they're part of the typical working set.
+
+#![allow(unused_imports)]
+
+#[cfg(all(feature = "cache", unix))]
+use isr::cache::CachePermissions;
+#[cfg(feature = "cache")]
+use isr::cache::{
+    detect_codec, AuditRecord, BincodeCodec, Codec, CompactJsonCodec, DefaultCodec, Entry,
+    Error as CacheError, IsrCache, JsonCodec, KnownCodec, MsgpackCodec, RepairSummary,
+};
+#[cfg(all(feature = "cache", any(feature = "pdb", feature = "dl-pdb")))]
+use isr::cache::{
+    BatchSummary, CodeView, CodeViewPlan, DebugId, PdbDownloader, PeKey, WindowsLayout,
+};
+#[cfg(all(feature = "cache", any(feature = "dwarf", feature = "dl-linux")))]
+use isr::cache::{
+    CompilerKind, LinuxBanner, LinuxBannerPlan, LinuxVersionSignature, ResolvedPackage,
+    ResolvedPlan, SkipPolicy, UbuntuDownloader, UbuntuVersionSignature, Url,
+};
+#[cfg(all(
+    feature = "cache",
+    any(
+        feature = "pdb",
+        feature = "dl-pdb",
+        feature = "dwarf",
+        feature = "dl-linux"
+    )
+))]
+use isr::cache::{DownloadEstimate, Manifest, ManifestEntry, PrefetchSummary};
+#[cfg(feature = "dl-linux")]
+use isr::download::linux::{
+    ubuntu::{
+        repository::{
+            fetch, fetch_with_client, parse, Error as RepositoryError,
+            UbuntuRepositoryEntry as RepositoryEntry,
+        },
+        DebianVersion, Error as UbuntuError, ResolvedPackage as UbuntuResolvedPackage,
+        ResolvedPlan as UbuntuResolvedPlan, SkipPolicy as UbuntuSkipPolicy,
+        UbuntuDownloader as DlUbuntuDownloader, UbuntuPackageCache,
+        UbuntuPaths as UbuntuDownloadPaths, UbuntuRepositoryEntry, VersionMatch, DEFAULT_ARCH,
+        DEFAULT_ARCHIVE_URL, DEFAULT_DDEBS_URL, DEFAULT_DISTS,
+        DEFAULT_MAX_REDIRECTS as UBUNTU_MAX_REDIRECTS,
+    },
+    CompilerKind as DlCompilerKind, Error as LinuxDownloadError, KernelVersion as DlKernelVersion,
+    LinuxBanner as DlLinuxBanner, LinuxVersionSignature as DlLinuxVersionSignature,
+    ResolvedPackage as DlResolvedPackage, ResolvedPlan as DlResolvedPlan,
+    SkipPolicy as DlSkipPolicy, UbuntuDownloader as DlLinuxUbuntuDownloader, UbuntuPaths,
+    UbuntuVersionSignature as DlUbuntuVersionSignature, Url as LinuxUrl,
+};
+#[cfg(feature = "dl-pdb")]
+use isr::download::pdb::{
+    architecture_from_image_bytes, exports_from_file_bytes, exports_from_image_bytes,
+    file_version_from_image_bytes, image_base_from_image_bytes,
+    pdb_identifier as dl_pdb_identifier, CodeView as DlCodeView, DebugId as DlDebugId,
+    Error as PdbDownloadError, PdbDownloader as DlPdbDownloader, PeKey as DlPeKey, Url as PdbUrl,
+    DEFAULT_MAX_REDIRECTS as PDB_MAX_REDIRECTS, DEFAULT_SERVER_URL,
+};
+#[cfg(all(feature = "dwarf", feature = "unstable"))]
+use isr::dwarf::create_profile_with_report as create_dwarf_profile_with_report;
+#[cfg(feature = "dwarf")]
+use isr::dwarf::{
+    create_profile as create_dwarf_profile, create_profile_from_object,
+    create_profile_symbols_only, create_profile_symbols_only_with_options,
+    create_profile_with_options as create_dwarf_profile_with_options, has_debug_info,
+    symbols::SystemMapSymbols,
+    types::{DwarfCache, DwarfTypes, ParseContext},
+    Error as DwarfError,
+};
+#[cfg(feature = "isf")]
+use isr::isf::{
+    create_profile as create_isf_profile,
+    create_profile_with_options as create_isf_profile_with_options, Error as IsfError, Isf,
+    IsfIdentity, PdbIdentity,
+};
+#[cfg(feature = "linux-helpers")]
+use isr::macros::linux::{
+    DentryPathReader, Error as LinuxWalkError, ListHeadIter, ListIter as LinuxListIter,
+    Memory as LinuxMemory, RbTreeIter, DEFAULT_MAX_ENTRIES as LINUX_MAX_ENTRIES,
+};
+#[cfg(feature = "windows-helpers")]
+use isr::macros::windows::{
+    Error as WindowsWalkError, ExFastRef, ListEntryIter, ListIter as WindowsListIter,
+    Memory as WindowsMemory, UnicodeStringReader, DEFAULT_MAX_ENTRIES as WINDOWS_MAX_ENTRIES,
+};
+#[cfg(all(feature = "pdb", feature = "unstable"))]
+use isr::pdb::create_profile_with_report as create_pdb_profile_with_report;
+#[cfg(feature = "pdb")]
+use isr::pdb::{
+    create_profile as create_pdb_profile,
+    create_profile_with_options as create_pdb_profile_with_options, pdb_guid, pdb_info,
+    Error as PdbError, PdbInfo,
+};
+#[cfg(all(
+    feature = "cache",
+    feature = "unstable",
+    any(feature = "pdb", feature = "dl-pdb")
+))]
+use isr::unstable::WindowsMetadata;
+#[cfg(all(feature = "cache", feature = "unstable"))]
+use isr::unstable::{CacheReport, EntryMetadata};
+#[cfg(all(feature = "cache", feature = "unstable", feature = "isf"))]
+use isr::unstable::{ImportReport, OnConflict};
+#[cfg(feature = "unstable")]
+use isr::unstable::{ProfileReport, SkippedType, Warning};
+#[cfg(feature = "cache")]
+use isr::{default_cache, set_default_cache};
+use isr::{
+    macros::{
+        enums, offsets, symbols, Bitfield, CaseInsensitive, Error as MacrosError,
+        Field as MacrosField, FieldDescriptor, FieldNameMatcher, FromProfile, IsrLazy, StripPrefix,
+    },
+    parse_pdb_guid, pdb_identifier, truncate_name,
+    types::{
+        ArrayRef, BaseRef, BitfieldRef, Enum, EnumRef, Field, PointerRef, Provenance, Struct,
+        StructKind, StructRef, Type, TypeProvenance, Types, Variant,
+    },
+    unique_name, AmbiguousName, ArchitectureMismatch, FieldPathError, KernelVersion, Lookup,
+    MissingNames, NestedField, Profile, ProfileOptions, ProfileSource, ReachableSet, Relaxation,
+    RelaxedMatch, RenderOptions, ResolutionPolicy, Strictness, StructLookup, StructReadCheck,
+    StructValue, SymbolBase, SymbolLookup, SymbolScope, Symbols, Value, WindowsBuild,
+    DEFAULT_MAX_TYPE_DEPTH,
+};
+
+/// The prelude exports the typical working set.
+mod prelude {
+    use isr::prelude::{
+        enums, offsets, symbols, Bitfield, Field, FromProfile, IsrLazy, MacrosError, Profile,
+        ProfileOptions,
+    };
+    #[cfg(feature = "cache")]
+    use isr::prelude::{
+        BincodeCodec, CacheError, Codec, DefaultCodec, Entry, IsrCache, JsonCodec, MsgpackCodec,
+    };
+    #[cfg(feature = "dl-pdb")]
+    use isr::prelude::{CodeView, DebugId, PdbDownloadError};
+    #[cfg(feature = "dl-linux")]
+    use isr::prelude::{LinuxBanner, LinuxDownloadError};
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::borrow::Cow;
+
+use isr::{
+    macros::{offsets, Error, Field},
+    types::{BaseRef, StructKind, Type, Types},
+    Profile, Relaxation, Symbols,
+};
+
+use self::common::add_struct;
+
+offsets! {
+    #[isr(relaxed)]
+    struct Offsets {
+        struct EPROCESS {
+            UniqueProcessId: Field,
+        }
+
+        struct _kthread {
+            InitialStack: Field,
+        }
+    }
+}
+
+mod ambiguous {
+    use isr::macros::{offsets, Field};
+
+    offsets! {
+        #[isr(relaxed)]
+        pub struct Offsets {
+            struct OBJECT_HEADER {
+                PointerCount: Field,
+            }
+        }
+    }
+}
+
+/// Creates a profile with inconsistently named structs.
+fn profile() -> Profile<'static> {
+    let mut types = Types::default();
+
+    let field = |name| vec![(name, 0, Type::Base(BaseRef::U64))];
+    add_struct(
+        &mut types,
+        "_EPROCESS",
+        StructKind::Struct,
+        8,
+        field("UniqueProcessId"),
+    );
+    add_struct(
+        &mut types,
+        "_KTHREAD",
+        StructKind::Struct,
+        8,
+        field("InitialStack"),
+    );
+    add_struct(
+        &mut types,
+        "_OBJECT_HEADER",
+        StructKind::Struct,
+        8,
+        field("PointerCount"),
+    );
+    add_struct(
+        &mut types,
+        "__OBJECT_HEADER",
+        StructKind::Struct,
+        8,
+        field("PointerCount"),
+    );
+
+    Profile::new(Cow::Borrowed("Amd64"), Symbols::default(), types)
+}
+
+#[test]
+fn find_struct_relaxed() {
+    let profile = profile();
+
+    // An exact match.
+    let found = profile
+        .find_struct_relaxed("_EPROCESS", Relaxation::ALL)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.name, "_EPROCESS");
+    assert!(found.matched.is_exact());
+
+    let found = profile
+        .find_struct_relaxed("EPROCESS", Relaxation::ALL)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.name, "_EPROCESS");
+    assert_eq!(
+        found.matched,
+        Relaxation::default().with_leading_underscores(true)
+    );
+
+    let found = profile
+        .find_struct_relaxed("_kthread", Relaxation::ALL)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.name, "_KTHREAD");
+    assert_eq!(found.matched, Relaxation::default().with_ascii_case(true));
+
+    // Only the selected differences are ignored.
+    let relaxation = Relaxation::default().with_ascii_case(true);
+    assert!(profile
+        .find_struct_relaxed("EPROCESS", relaxation)
+        .unwrap()
+        .is_none());
+    assert!(profile
+        .find_struct_relaxed("EPROCESS", Relaxation::default())
+        .unwrap()
+        .is_none());
+
+    let error = profile
+        .find_struct_relaxed("OBJECT_HEADER", Relaxation::ALL)
+        .unwrap_err();
+    assert_eq!(error.candidates, ["_OBJECT_HEADER", "__OBJECT_HEADER"]);
+}
+
+#[test]
+fn relaxed_offsets() {
+    let profile = profile();
+
+    let offsets = Offsets::new(&profile).unwrap();
+    assert_eq!(offsets.EPROCESS.UniqueProcessId.size, 8);
+    assert_eq!(offsets._kthread.InitialStack.size, 8);
+
+    let result = ambiguous::Offsets::new(&profile);
+    assert!(matches!(
+        result,
+        Err(Error::AmbiguousType { type_name, .. }) if type_name == "OBJECT_HEADER"
+    ));
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use isr::{
+    cache::{Codec as _, JsonCodec},
+    ProfileOptions, SymbolBase,
+};
+
+use self::common::kernel::{Kernel, TEXT};
+
+#[test]
+fn virtual_address() {
+    let (image, systemmap) = Kernel::new(1).files();
+
+    let mut encoded = Vec::new();
+    isr::dwarf::create_profile_with_options(
+        image,
+        systemmap,
+        &ProfileOptions::default(),
+        |profile| JsonCodec::encode(&mut encoded, profile),
+    )
+    .unwrap();
+
+    // The convention is recorded, not inferred from `_text`.
+    assert!(String::from_utf8_lossy(&encoded).contains("\"symbol_base\""));
+
+    let profile = JsonCodec::decode(&encoded).unwrap();
+    assert_eq!(
+        profile.symbol_base(),
+        SymbolBase::VirtualAddress { default_base: TEXT }
+    );
+    assert_eq!(
+        profile.find_symbol("init_task"),
+        Some(0xffff_ffff_82a0_c940)
+    );
+    assert_eq!(profile.symbol_rva("init_task"), Some(0x01a0_c940));
+
+    // KASLR slide of 0x2e000000.
+    assert_eq!(
+        profile.symbol_va("init_task", 0xffff_ffff_af00_0000),
+        Some(0xffff_ffff_b0a0_c940)
+    );
+}
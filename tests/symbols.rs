@@ -0,0 +1,70 @@
+mod common;
+
+use isr::macros::{symbols, Error};
+
+use self::common::windows;
+
+symbols! {
+    struct Present {
+        PsActiveProcessHead: u64,
+        PsInitialSystemProcess: Option<u64>,
+
+        #[isr(alias = "KiSystemCall64Shadow")]
+        KiSystemCall64: u64,
+    }
+}
+
+symbols! {
+    struct OneMissing {
+        PsActiveProcessHead: u64,
+        MmPfnDatabase: u64,
+    }
+}
+
+symbols! {
+    struct SeveralMissing {
+        MmPfnDatabase: u64,
+        PsActiveProcessHead: u64,
+        KdDebuggerDataBlock: u64,
+        NonExistentSymbol: Option<u64>,
+
+        #[isr(override = "PspCidTable")]
+        CidTable: u64,
+    }
+}
+
+#[test]
+fn all_present() {
+    let profile = windows::profile();
+    let symbols = Present::new(&profile).unwrap();
+
+    assert_eq!(symbols.PsActiveProcessHead, 0x43_7bc0);
+    assert_eq!(symbols.PsInitialSystemProcess, Some(0x57_33a0));
+    assert_eq!(symbols.KiSystemCall64, 0xa1_2180);
+}
+
+#[test]
+fn one_missing() {
+    let profile = windows::profile();
+
+    match OneMissing::new(&profile) {
+        Err(Error::SymbolNotFound(name)) => assert_eq!(name, "MmPfnDatabase"),
+        result => panic!("unexpected result {:?}", result.err()),
+    }
+}
+
+#[test]
+fn several_missing() {
+    let profile = windows::profile();
+
+    // Missing optional symbols aren't reported.
+    match SeveralMissing::new(&profile) {
+        Err(Error::SymbolsNotFound(names)) => {
+            assert_eq!(
+                names,
+                ["MmPfnDatabase", "KdDebuggerDataBlock", "PspCidTable"]
+            )
+        }
+        result => panic!("unexpected result {:?}", result.err()),
+    }
+}
@@ -0,0 +1,423 @@
+mod common;
+
+use isr::macros::{
+    offsets,
+    windows::{Error, ExFastRef, ListEntryIter, Memory, UnicodeStringReader},
+    Bitfield, Field,
+};
+
+use self::common::windows;
+
+offsets! {
+    struct Offsets {
+        struct _LIST_ENTRY {
+            Flink: Field,
+        }
+
+        struct _UNICODE_STRING {
+            Length: Field,
+            MaximumLength: Field,
+            Buffer: Field,
+        }
+
+        struct _EX_FAST_REF {
+            RefCnt: Bitfield,
+        }
+
+        struct _EPROCESS {
+            ActiveProcessLinks: Field,
+        }
+
+        struct _KLDR_DATA_TABLE_ENTRY {
+            InLoadOrderLinks: Field,
+            FullDllName: Field,
+        }
+    }
+}
+
+/// Base address of the synthetic memory.
+const BASE: u64 = 0xffff_8000_0000_0000;
+
+/// Size of the synthetic memory.
+const SIZE: u64 = 0x1_0000;
+
+/// Address of the list head.
+const HEAD: u64 = BASE + 0x100;
+
+/// Addresses of the `_EPROCESS` structures.
+const PROCESSES: [u64; 3] = [BASE + 0x1000, BASE + 0x2000, BASE + 0x3000];
+
+/// Read outside of the synthetic memory.
+#[derive(Debug)]
+struct Unmapped(u64);
+
+impl std::fmt::Display for Unmapped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unmapped address {:#x}", self.0)
+    }
+}
+
+impl std::error::Error for Unmapped {}
+
+/// Synthetic, zero-initialized memory of `SIZE` bytes at `BASE`.
+struct SyntheticMemory {
+    data: Vec<u8>,
+}
+
+impl SyntheticMemory {
+    fn new() -> Self {
+        Self {
+            data: vec![0; SIZE as usize],
+        }
+    }
+
+    fn write(&mut self, address: u64, bytes: &[u8]) {
+        let start = (address - BASE) as usize;
+        self.data[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn write_u16(&mut self, address: u64, value: u16) {
+        self.write(address, &value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, address: u64, value: u64) {
+        self.write(address, &value.to_le_bytes());
+    }
+
+    fn write_utf16(&mut self, address: u64, units: &[u16]) {
+        for (index, unit) in units.iter().enumerate() {
+            self.write_u16(address + 2 * index as u64, *unit);
+        }
+    }
+}
+
+impl Memory for SyntheticMemory {
+    type Error = Unmapped;
+
+    fn read(&self, address: u64, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let start = address
+            .checked_sub(BASE)
+            .filter(|start| start + buffer.len() as u64 <= SIZE)
+            .ok_or(Unmapped(address))?;
+
+        let start = start as usize;
+        buffer.copy_from_slice(&self.data[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+fn offsets() -> Offsets {
+    Offsets::new(&windows::profile()).unwrap()
+}
+
+/// Returns the address of the `ActiveProcessLinks` of a process.
+fn links(offsets: &Offsets, process: u64) -> u64 {
+    process + offsets._EPROCESS.ActiveProcessLinks.offset
+}
+
+/// Creates a memory with the list head linked to the given processes, in
+/// order.
+fn process_list(offsets: &Offsets, processes: &[u64]) -> SyntheticMemory {
+    let mut memory = SyntheticMemory::new();
+
+    let mut entry = HEAD;
+    for &process in processes {
+        memory.write_u64(entry, links(offsets, process));
+        entry = links(offsets, process);
+    }
+    memory.write_u64(entry, HEAD);
+
+    memory
+}
+
+fn iter<'m>(offsets: &Offsets, memory: &'m SyntheticMemory) -> ListEntryIter<'m, SyntheticMemory> {
+    ListEntryIter::new(
+        memory,
+        HEAD,
+        offsets._LIST_ENTRY.Flink,
+        offsets._EPROCESS.ActiveProcessLinks,
+    )
+}
+
+#[test]
+fn list() {
+    let offsets = offsets();
+    let memory = process_list(&offsets, &PROCESSES);
+
+    let processes = iter(&offsets, &memory)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(processes, PROCESSES);
+}
+
+#[test]
+fn empty_list() {
+    let offsets = offsets();
+    let memory = process_list(&offsets, &[]);
+
+    assert_eq!(iter(&offsets, &memory).count(), 0);
+}
+
+#[test]
+fn corrupted_circular_list() {
+    let offsets = offsets();
+    let mut memory = process_list(&offsets, &PROCESSES);
+
+    // The last process links back to the second one instead of the head.
+    memory.write_u64(links(&offsets, PROCESSES[2]), links(&offsets, PROCESSES[1]));
+
+    let mut iter = iter(&offsets, &memory);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[1]);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[2]);
+
+    match iter.next() {
+        Some(Err(Error::Loop(address))) => assert_eq!(address, links(&offsets, PROCESSES[1])),
+        other => panic!("expected a loop, got {other:?}"),
+    }
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn self_loop() {
+    let offsets = offsets();
+    let mut memory = process_list(&offsets, &PROCESSES[..1]);
+    memory.write_u64(links(&offsets, PROCESSES[0]), links(&offsets, PROCESSES[0]));
+
+    let results = iter(&offsets, &memory).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert_eq!(*results[0].as_ref().unwrap(), PROCESSES[0]);
+    assert!(matches!(results[1], Err(Error::Loop(_))));
+}
+
+#[test]
+fn max_entries() {
+    let offsets = offsets();
+    let memory = process_list(&offsets, &PROCESSES);
+
+    let mut iter = iter(&offsets, &memory).with_max_entries(2);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[1]);
+    assert!(matches!(iter.next(), Some(Err(Error::TooManyEntries(2)))));
+    assert!(iter.next().is_none());
+
+    // Exactly the maximum number of entries.
+    let iter = self::iter(&offsets, &memory).with_max_entries(3);
+    assert_eq!(iter.filter(Result::is_ok).count(), 3);
+}
+
+#[test]
+fn null_link() {
+    let offsets = offsets();
+    let mut memory = process_list(&offsets, &PROCESSES);
+    memory.write_u64(links(&offsets, PROCESSES[1]), 0);
+
+    let mut iter = iter(&offsets, &memory);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[1]);
+
+    match iter.next() {
+        Some(Err(Error::NullLink(address))) => assert_eq!(address, links(&offsets, PROCESSES[1])),
+        other => panic!("expected a null link, got {other:?}"),
+    }
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn unmapped_link() {
+    let offsets = offsets();
+    let mut memory = process_list(&offsets, &PROCESSES);
+
+    // The second process is outside of the memory.
+    let outside = BASE + SIZE + 0x1000;
+    memory.write_u64(links(&offsets, PROCESSES[0]), links(&offsets, outside));
+
+    let mut iter = iter(&offsets, &memory);
+    assert_eq!(iter.next().unwrap().unwrap(), PROCESSES[0]);
+    assert_eq!(iter.next().unwrap().unwrap(), outside);
+
+    match iter.next() {
+        Some(Err(Error::Read { address, source })) => {
+            assert_eq!(address, links(&offsets, outside));
+            assert_eq!(source.0, address);
+        }
+        other => panic!("expected a read error, got {other:?}"),
+    }
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn container_underflow() {
+    let offsets = offsets();
+    let mut memory = process_list(&offsets, &PROCESSES);
+
+    // The link is lower than the offset of `ActiveProcessLinks`.
+    memory.write_u64(HEAD, 0x10);
+
+    let mut iter = iter(&offsets, &memory);
+    assert!(matches!(
+        iter.next(),
+        Some(Err(Error::Field(isr::macros::Error::ContainerUnderflow {
+            member_address: 0x10,
+            ..
+        })))
+    ));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn unsupported_pointer_size() {
+    let offsets = offsets();
+    let memory = process_list(&offsets, &PROCESSES);
+
+    let flink = Field { offset: 0, size: 2 };
+    let mut iter = ListEntryIter::new(&memory, HEAD, flink, offsets._EPROCESS.ActiveProcessLinks);
+    assert!(matches!(iter.next(), Some(Err(Error::UnsupportedSize(2)))));
+    assert!(iter.next().is_none());
+}
+
+/// Creates a `_KLDR_DATA_TABLE_ENTRY` whose `FullDllName` has the given
+/// lengths and buffer, and returns the address of the `FullDllName`.
+fn unicode_string(
+    offsets: &Offsets,
+    memory: &mut SyntheticMemory,
+    length: u16,
+    maximum_length: u16,
+    buffer: u64,
+) -> u64 {
+    let entry = BASE + 0x4000;
+    let string = entry + offsets._KLDR_DATA_TABLE_ENTRY.FullDllName.offset;
+
+    memory.write_u16(string + offsets._UNICODE_STRING.Length.offset, length);
+    memory.write_u16(
+        string + offsets._UNICODE_STRING.MaximumLength.offset,
+        maximum_length,
+    );
+    memory.write_u64(string + offsets._UNICODE_STRING.Buffer.offset, buffer);
+
+    string
+}
+
+fn reader(offsets: &Offsets) -> UnicodeStringReader {
+    UnicodeStringReader::new(
+        offsets._UNICODE_STRING.Length,
+        offsets._UNICODE_STRING.MaximumLength,
+        offsets._UNICODE_STRING.Buffer,
+    )
+}
+
+#[test]
+fn unicode_string_read() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+
+    let name = "\\SystemRoot\\system32\\ntoskrnl.exe";
+    let units = name.encode_utf16().collect::<Vec<_>>();
+    let buffer = BASE + 0x5000;
+    memory.write_utf16(buffer, &units);
+
+    // The buffer is longer than the string, and not null-terminated.
+    let length = 2 * units.len() as u16;
+    memory.write_utf16(buffer + u64::from(length), &[u16::from(b'X'); 4]);
+
+    let string = unicode_string(&offsets, &mut memory, length, length + 8, buffer);
+    assert_eq!(reader(&offsets).read(&memory, string).unwrap(), name);
+
+    // Only `Length` bytes are read.
+    let string = unicode_string(&offsets, &mut memory, 22, length, buffer);
+    assert_eq!(
+        reader(&offsets).read(&memory, string).unwrap(),
+        "\\SystemRoot"
+    );
+}
+
+#[test]
+fn unicode_string_empty() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+
+    // The buffer of an empty string isn't read.
+    let string = unicode_string(&offsets, &mut memory, 0, 0, 0);
+    assert_eq!(reader(&offsets).read(&memory, string).unwrap(), "");
+}
+
+#[test]
+fn unicode_string_invalid_length() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let buffer = BASE + 0x5000;
+
+    // Odd length.
+    let string = unicode_string(&offsets, &mut memory, 7, 16, buffer);
+    assert!(matches!(
+        reader(&offsets).read(&memory, string),
+        Err(Error::InvalidStringLength {
+            length: 7,
+            maximum_length: 16
+        })
+    ));
+
+    // Longer than the maximum length.
+    let string = unicode_string(&offsets, &mut memory, 18, 16, buffer);
+    assert!(matches!(
+        reader(&offsets).read(&memory, string),
+        Err(Error::InvalidStringLength {
+            length: 18,
+            maximum_length: 16
+        })
+    ));
+}
+
+#[test]
+fn unicode_string_invalid_utf16() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+    let buffer = BASE + 0x5000;
+
+    // Unpaired surrogate.
+    memory.write_utf16(buffer, &[u16::from(b'a'), 0xd800, u16::from(b'b')]);
+
+    let string = unicode_string(&offsets, &mut memory, 6, 6, buffer);
+    assert_eq!(
+        reader(&offsets).read(&memory, string).unwrap(),
+        "a\u{fffd}b"
+    );
+}
+
+#[test]
+fn unicode_string_unmapped_buffer() {
+    let offsets = offsets();
+    let mut memory = SyntheticMemory::new();
+
+    let buffer = BASE + SIZE - 4;
+    let string = unicode_string(&offsets, &mut memory, 8, 8, buffer);
+    assert!(matches!(
+        reader(&offsets).read(&memory, string),
+        Err(Error::Read { address, .. }) if address == buffer
+    ));
+}
+
+#[test]
+fn ex_fast_ref() {
+    let offsets = offsets();
+    let fast_ref = ExFastRef::new(offsets._EX_FAST_REF.RefCnt);
+
+    let value = 0xffff_a08f_1234_5677;
+    assert_eq!(fast_ref.object_pointer(value), 0xffff_a08f_1234_5670);
+    assert_eq!(fast_ref.ref_count(value), 7);
+
+    assert_eq!(fast_ref.object_pointer(0xf), 0);
+    assert_eq!(fast_ref.ref_count(0xf), 0xf);
+}
+
+#[test]
+fn ex_fast_ref_whole_value() {
+    let fast_ref = ExFastRef::new(Bitfield {
+        offset: 0,
+        size: 8,
+        bit_position: 0,
+        bit_length: 64,
+    });
+
+    assert_eq!(fast_ref.object_pointer(u64::MAX), 0);
+}